@@ -0,0 +1,72 @@
+use crate::{AlphaPixel, PixelChannel};
+
+/// A running sum of pixels with their colour premultiplied by alpha, for averaging pixels so that transparent pixels
+/// don't darken the average.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct PremultipliedSum {
+    /// The sums of the premultiplied red, green and blue channels, and the alpha channel, as floats between 0 and 1
+    pub channels: [f64; 4]
+}
+
+impl PremultipliedSum {
+    /// Add a pixel, multiplied by `weight`.
+    pub fn add<T: PixelChannel>(&mut self, pixel: AlphaPixel<T>, weight: f64) {
+        let pixel = pixel.as_float_pixel();
+        let alpha = pixel.a as f64 * weight;
+        self.channels[0] += pixel.r as f64 * alpha;
+        self.channels[1] += pixel.g as f64 * alpha;
+        self.channels[2] += pixel.b as f64 * alpha;
+        self.channels[3] += alpha;
+    }
+
+    /// Get the average pixel, where `total_weight` is the weight of all of the pixels averaged, including any which
+    /// weren't added as they are missing, so they are treated as transparent.
+    /// 
+    /// The colour is weighted by alpha, and the alpha is divided by `total_weight`. Both are clamped between 0 and
+    /// the maximum value. If the sum is transparent, the average is transparent black. Channels which are NaN, such as
+    /// from pixels sampled at NaN coordinates, count as 0, so a NaN alpha is transparent.
+    pub fn average<T: PixelChannel>(&self, total_weight: f64) -> AlphaPixel<T> {
+        let alpha = self.channels[3];
+        if !(alpha > 0.0 && total_weight > 0.0) {
+            return AlphaPixel::default()
+        }
+
+        let max = T::MAX_PIXEL_VALUE.into();
+        let channel = |value: f64| match value.is_nan() {
+            true => T::zero(),
+            false => T::from_f32(value.clamp(0.0, 1.0) as f32 * max).unwrap()
+        };
+        AlphaPixel {
+            r: channel(self.channels[0] / alpha),
+            g: channel(self.channels[1] / alpha),
+            b: channel(self.channels[2] / alpha),
+            a: channel(alpha / total_weight)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn weighted_by_alpha() {
+        let mut sum = PremultipliedSum::default();
+        sum.add(AlphaPixel::<u8>::red(), 1.0);
+        sum.add(rgba!(0u8, 0, 255, 0), 1.0);
+
+        // The transparent pixel only halves the alpha
+        assert_eq!(sum.average::<u8>(2.0), rgba!(255, 0, 0, 127));
+        assert_eq!(PremultipliedSum::default().average::<u8>(1.0), AlphaPixel::default());
+    }
+
+    #[test]
+    fn not_a_number() {
+        let mut sum = PremultipliedSum::default();
+        sum.add(AlphaPixel::<u8>::red(), f64::NAN);
+        assert_eq!(sum.average::<u8>(1.0), AlphaPixel::default());
+        assert_eq!(PremultipliedSum { channels: [f64::NAN, 0.5, 0.5, 0.5] }.average::<u8>(f64::NAN), AlphaPixel::default());
+        assert_eq!(PremultipliedSum { channels: [f64::NAN, 0.5, 0.5, 0.5] }.average::<u8>(1.0), rgba!(0, 255, 255, 127));
+    }
+}
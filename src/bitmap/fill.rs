@@ -0,0 +1,125 @@
+use crate::{Image, AlphaPixel, PixelChannel, Rect, BlendingMethod, bitmap::mask::Mask};
+
+/// The part of an image filled by [`Image::fill_region`].
+#[derive(Clone, Copy)]
+pub enum Region<'a> {
+    Rect(Rect),
+    /// A mask, where the fill colour's alpha is multiplied by the mask's value. The top left of the mask
+    /// is the top left of the image.
+    Mask(&'a Mask)
+}
+
+impl<T: PixelChannel> Image<T> {
+    /// Select the area of similar colour around a seed pixel, which is connected to it horizontally or vertically.
+    /// 
+    /// `tolerance` is the largest difference of any channel from the seed pixel for a pixel to be included,
+    /// as a fraction of the channel range. A tolerance of 0.0 only selects pixels exactly the same as the seed.
+    /// The returned mask is 1.0 for selected pixels, and 0.0 elsewhere. Returns `None` if the seed isn't in the image.
+    pub fn flood_fill_mask(&self, x: usize, y: usize, tolerance: f32) -> Option<Mask> {
+        let seed = self.pixel_at(x, y)?.as_float_pixel();
+        let matches = |pixel: AlphaPixel<T>| {
+            let pixel = pixel.as_float_pixel();
+            (pixel.r - seed.r).abs() <= tolerance && (pixel.g - seed.g).abs() <= tolerance &&
+                (pixel.b - seed.b).abs() <= tolerance && (pixel.a - seed.a).abs() <= tolerance
+        };
+
+        let mut mask = Mask::new(self.get_width(), self.get_height(), 0.0);
+        let mut stack = vec![(x, y)];
+        mask.set_value(x, y, 1.0).ok()?;
+        while let Some((x, y)) = stack.pop() {
+            let neighbours = [
+                (x.checked_sub(1), Some(y)),
+                (Some(x + 1), Some(y)),
+                (Some(x), y.checked_sub(1)),
+                (Some(x), Some(y + 1))
+            ];
+            for (neighbour_x, neighbour_y) in neighbours {
+                let (Some(neighbour_x), Some(neighbour_y)) = (neighbour_x, neighbour_y) else {
+                    continue
+                };
+                let Some(pixel) = self.pixel_at(neighbour_x, neighbour_y) else {
+                    continue
+                };
+                if mask.value_at(neighbour_x, neighbour_y) == Some(0.0) && matches(pixel) {
+                    mask.set_value(neighbour_x, neighbour_y, 1.0).ok()?;
+                    stack.push((neighbour_x, neighbour_y));
+                }
+            }
+        }
+
+        Some(mask)
+    }
+
+    /// Replace the area of similar colour around a seed pixel with `color`, like a paint bucket tool.
+    /// 
+    /// See [`Image::flood_fill_mask`] for how pixels are selected. Returns the number of pixels filled,
+    /// or `None` if the seed isn't in the image.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, Rect, BlendingMethod, bitmap::fill::Region};
+    /// 
+    /// // A black square outline on white
+    /// let mut image: Image<u8> = Image::new_with_fill(AlphaPixel::white(), 10, 10);
+    /// image.fill_region(Region::Rect(Rect { x: 2, y: 2, width: 6, height: 6 }), AlphaPixel::black(), BlendingMethod::Replace);
+    /// image.fill_region(Region::Rect(Rect { x: 3, y: 3, width: 4, height: 4 }), AlphaPixel::white(), BlendingMethod::Replace);
+    /// 
+    /// // Only the inside of the outline is filled
+    /// assert_eq!(image.flood_fill(5, 5, AlphaPixel::red(), 0.1), Some(16));
+    /// assert_eq!(image.pixel_at(0, 0).unwrap(), AlphaPixel::white());
+    /// ```
+    pub fn flood_fill(&mut self, x: usize, y: usize, color: AlphaPixel<T>, tolerance: f32) -> Option<usize> {
+        let mask = self.flood_fill_mask(x, y, tolerance)?;
+        let count = mask.get_values().iter().filter(|value| **value > 0.0).count();
+        self.fill_region(Region::Mask(&mask), color, BlendingMethod::Replace);
+        Some(count)
+    }
+
+    /// Blend `color` over a region of the image, which is cut off at the edges of the image.
+    /// 
+    /// With a mask, pixels where the mask is 0.0 are unchanged. With `BlendingMethod::Replace`, pixels where the mask is
+    /// partially covered are replaced with `color` at reduced alpha.
+    pub fn fill_region(&mut self, region: Region, color: AlphaPixel<T>, blend: BlendingMethod<T>) {
+        let width = self.get_width();
+        for (index, pixel) in self.get_pixels_mut().iter_mut().enumerate() {
+            let (x, y) = (index % width, index / width);
+            let coverage = match region {
+                Region::Rect(rect) => if rect.contains(x, y) { 1.0 } else { 0.0 },
+                Region::Mask(mask) => mask.value_at(x, y).unwrap_or(0.0)
+            };
+            if coverage > 0.0 {
+                *pixel = blend.blend(*pixel, color.scale_alpha(coverage));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn flood_fill_tolerance() {
+        let mut image: Image<u8> = Image::from_pixels(vec![
+            rgba!(100, 100, 100, 255), rgba!(110, 100, 100, 255), rgba!(200, 100, 100, 255), rgba!(100, 100, 100, 255)
+        ], 4).unwrap();
+
+        assert_eq!(image.clone().flood_fill(0, 0, AlphaPixel::red(), 0.0), Some(1));
+        // The last pixel matches, but isn't connected to the seed
+        assert_eq!(image.flood_fill(0, 0, AlphaPixel::red(), 0.05), Some(2));
+        assert_eq!(image.pixel_at(1, 0).unwrap(), AlphaPixel::red());
+        assert_eq!(image.pixel_at(3, 0).unwrap(), rgba!(100, 100, 100, 255));
+        assert!(image.flood_fill(4, 0, AlphaPixel::red(), 0.0).is_none());
+    }
+
+    #[test]
+    fn fill_mask() {
+        let mut image: Image<u8> = Image::new_with_fill(AlphaPixel::black(), 2, 1);
+        let mask = Mask::from_function(2, 1, |x, _| if x == 0 { 0.5 } else { 0.0 });
+        image.fill_region(Region::Mask(&mask), AlphaPixel::white(), BlendingMethod::Over);
+
+        assert_eq!(image.pixel_at(0, 0).unwrap(), rgba!(127, 127, 127, 255));
+        assert_eq!(image.pixel_at(1, 0).unwrap(), AlphaPixel::black());
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{Image, AlphaPixel, PixelChannel, bitmap::ramp::ColorRamp};
+
+/// Get the colour from `ramp` at the luma of `pixel`, keeping the pixel's alpha multiplied by the ramp colour's alpha.
+pub(crate) fn gradient_map_pixel<T: PixelChannel>(pixel: AlphaPixel<T>, ramp: &ColorRamp<T>) -> AlphaPixel<T> {
+    let max = T::MAX_PIXEL_VALUE.into();
+    let mapped = ramp.color_at(pixel.luma().into() / max);
+    mapped.scale_alpha(pixel.a.into() / max)
+}
+
+impl<T: PixelChannel> Image<T> {
+    /// Recolour the image by mapping the luma of each pixel to a colour of `ramp`, where black is 0.0 and white is 1.0.
+    /// 
+    /// This gives duotone and heat map effects, and can be used to prepare assets without adding them to a canvas.
+    /// The alpha of each pixel is multiplied by the alpha of its ramp colour.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, rgba, bitmap::ramp::ColorRamp};
+    /// 
+    /// let photo: Image<u8> = Image::from_function(256, 1, |x, _| rgba!(x as u8, x as u8, x as u8, 255));
+    /// let duotone = photo.gradient_map(&ColorRamp::two_color(rgba!(20, 0, 80, 255), rgba!(255, 200, 0, 255)));
+    /// 
+    /// assert_eq!(duotone.pixel_at(0, 0).unwrap(), rgba!(20, 0, 80, 255));
+    /// assert_eq!(duotone.pixel_at(255, 0).unwrap(), rgba!(255, 200, 0, 255));
+    /// ```
+    pub fn gradient_map(&self, ramp: &ColorRamp<T>) -> Image<T> {
+        let mut mapped = self.clone();
+        for pixel in mapped.get_pixels_mut() {
+            *pixel = gradient_map_pixel(*pixel, ramp);
+        }
+        mapped
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{Image, AlphaPixel, PixelChannel};
+
+/// The number of bins in each channel's histogram, which is one per value of an 8 bit channel.
+const BINS: usize = 256;
+
+/// The cumulative histogram of one channel, weighted by alpha and normalised to end at 1.0.
+fn cumulative_histogram<T: PixelChannel>(image: &Image<T>, channel: fn(&AlphaPixel<f32>) -> f32) -> Option<[f32; BINS]> {
+    let mut histogram = [0.0; BINS];
+    for pixel in image.get_pixels().iter().map(|pixel| pixel.as_float_pixel()) {
+        let bin = (channel(&pixel).clamp(0.0, 1.0) * (BINS - 1) as f32).round() as usize;
+        histogram[bin] += pixel.a;
+    }
+
+    let total: f32 = histogram.iter().sum();
+    if total <= 0.0 {
+        return None
+    }
+    let mut running = 0.0;
+    for count in &mut histogram {
+        running += *count / total;
+        *count = running;
+    }
+    Some(histogram)
+}
+
+/// The cumulative histogram at the middle of each bin, so that each value counts half of the pixels which have it.
+fn midpoints(cumulative: &[f32; BINS]) -> [f32; BINS] {
+    std::array::from_fn(|bin| if bin == 0 { cumulative[0] / 2.0 } else { (cumulative[bin - 1] + cumulative[bin]) / 2.0 })
+}
+
+/// Map each bin of `source` to the value, from 0.0 to 1.0, which is at the same point of `reference`.
+fn tone_curve(source: &[f32; BINS], reference: &[f32; BINS]) -> [f32; BINS] {
+    let (source, reference) = (midpoints(source), midpoints(reference));
+    source.map(|quantile| {
+        match reference.iter().position(|&point| point >= quantile) {
+            Some(0) => 0.0,
+            Some(bin) => {
+                // Interpolate between the bins, so that smooth gradients aren't posterised
+                let (below, above) = (reference[bin - 1], reference[bin]);
+                let t = if above > below { (quantile - below) / (above - below) } else { 1.0 };
+                (bin as f32 - 1.0 + t) / (BINS - 1) as f32
+            },
+            None => 1.0
+        }
+    })
+}
+
+/// Look up a value from 0.0 to 1.0 in a tone curve, interpolating between its bins.
+fn apply_curve(curve: &[f32; BINS], value: f32) -> f32 {
+    let position = value.clamp(0.0, 1.0) * (BINS - 1) as f32;
+    let below = position.floor() as usize;
+    let above = (below + 1).min(BINS - 1);
+    let t = position - below as f32;
+    curve[below] + (curve[above] - curve[below]) * t
+}
+
+impl<T: PixelChannel> Image<T> {
+    /// Change the tones of the image so that the histogram of each colour channel matches that of `reference`.
+    /// 
+    /// This makes photos from different cameras and lighting look consistent when they are placed side by side,
+    /// such as in a collage. The reference can be one of the other photos, or the flattened canvas, so that a photo
+    /// matches the template that it is placed on. `strength` is from 0.0, which leaves the image unchanged, to 1.0,
+    /// which matches the histograms as closely as possible.
+    /// 
+    /// Pixels are weighted by their alpha, so transparent pixels of either image are ignored. The image is returned
+    /// unchanged if either image has no opaque pixels. Alpha is unchanged.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, rgba};
+    /// 
+    /// // A dark, low contrast photo, and a bright reference
+    /// let photo: Image<u8> = Image::from_function(10, 1, |x, _| rgba!(20 + x as u8, 20 + x as u8, 20 + x as u8, 255));
+    /// let reference: Image<u8> = Image::from_function(10, 1, |x, _| rgba!(100 + 10 * x as u8, 100 + 10 * x as u8, 100 + 10 * x as u8, 255));
+    /// 
+    /// let matched = photo.match_histogram(&reference, 1.0);
+    /// assert_eq!(matched, reference);
+    /// ```
+    pub fn match_histogram(&self, reference: &Image<T>, strength: f32) -> Image<T> {
+        let strength = strength.clamp(0.0, 1.0);
+        let channels: [fn(&AlphaPixel<f32>) -> f32; 3] = [|pixel| pixel.r, |pixel| pixel.g, |pixel| pixel.b];
+        let mut curves = [[0.0; BINS]; 3];
+        for (curve, channel) in curves.iter_mut().zip(channels) {
+            match (cumulative_histogram(self, channel), cumulative_histogram(reference, channel)) {
+                (Some(source), Some(reference)) => *curve = tone_curve(&source, &reference),
+                _ => return self.clone()
+            }
+        }
+
+        let max: f32 = T::MAX_PIXEL_VALUE.into();
+        let to_channel = |value: f32| T::from_f32(if max > 1.0 { (value * max).round() } else { value * max }).unwrap();
+        let map = |curve: &[f32; BINS], value: f32| {
+            let matched = apply_curve(curve, value);
+            to_channel(value + (matched - value) * strength)
+        };
+
+        let mut matched = self.clone();
+        for pixel in matched.get_pixels_mut() {
+            let float = pixel.as_float_pixel();
+            *pixel = AlphaPixel { r: map(&curves[0], float.r), g: map(&curves[1], float.g), b: map(&curves[2], float.b), a: pixel.a };
+        }
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn identical_images_are_unchanged() {
+        let image: Image<u8> = Image::from_function(16, 16, |x, y| rgba!((x * 16) as u8, (y * 16) as u8, ((x + y) * 8) as u8, 255));
+        assert_eq!(image.match_histogram(&image, 1.0), image);
+    }
+
+    #[test]
+    fn channels_and_strength() {
+        // A blue cast is removed by matching a neutral reference
+        let photo: Image<u8> = Image::from_function(8, 1, |x, _| rgba!((x * 20) as u8, (x * 20) as u8, (x * 20 + 100) as u8, 255));
+        let reference: Image<u8> = Image::from_function(8, 1, |x, _| rgba!((x * 20) as u8, (x * 20) as u8, (x * 20) as u8, 255));
+        let matched = photo.match_histogram(&reference, 1.0);
+        for x in 0..8 {
+            let pixel = matched.pixel_at(x, 0).unwrap();
+            assert_eq!((pixel.r, pixel.g, pixel.b), ((x * 20) as u8, (x * 20) as u8, (x * 20) as u8));
+        }
+
+        let half = photo.match_histogram(&reference, 0.5).pixel_at(2, 0).unwrap();
+        assert_eq!(half.b, 90);
+        assert_eq!(photo.match_histogram(&reference, 0.0), photo);
+    }
+
+    #[test]
+    fn transparent_pixels_are_ignored() {
+        let photo: Image<u8> = Image::from_pixels(vec![rgba!(50, 50, 50, 255), rgba!(255, 255, 255, 0)], 2).unwrap();
+        let reference: Image<u8> = Image::from_pixels(vec![rgba!(200, 200, 200, 255), rgba!(0, 0, 0, 0)], 2).unwrap();
+        let matched = photo.match_histogram(&reference, 1.0);
+        assert_eq!(matched.pixel_at(0, 0).unwrap(), rgba!(200, 200, 200, 255));
+        assert_eq!(matched.pixel_at(1, 0).unwrap().a, 0);
+
+        let empty: Image<u8> = Image::new_with_fill(rgba!(0, 0, 0, 0), 2, 2);
+        assert_eq!(photo.match_histogram(&empty, 1.0), photo);
+    }
+}
@@ -1,25 +1,52 @@
-use std::iter::repeat;
+use std::{fmt::{self, Debug, Display, Formatter}, iter::repeat_n, sync::Arc};
 
 use bytemuck::must_cast_slice;
+use xxhash_rust::xxh3::Xxh3;
 use thiserror::Error;
-use crate::{BlendingMethod, AlphaPixel, PixelChannel};
+use crate::{BlendingMethod, AlphaPixel, PixelChannel, Error};
+use super::sampler::{Sampler, BilinearSampler, sample};
 
-#[derive(Debug, Error, PartialEq)]
+/// The number of pixels normalized at a time by [`Image::content_hash`]
+const HASH_CHUNK_PIXELS: usize = 4096;
+
+#[derive(Debug, Error, Clone, PartialEq)]
 pub enum NewImageError {
     #[error("Width is incorrect")]
     IncorrectWidth,
     #[error("Width is 0, but buffer isn't zero-length")]
-    ZeroWidth
+    ZeroWidth,
+    #[error("An image of {width}x{height} pixels is too large to allocate")]
+    TooLarge { width: usize, height: usize },
+    #[error("Can't remove {removed} rows from an image with {height} rows")]
+    TooFewRows { removed: usize, height: usize },
+    #[error("The pixel at index {index} has a channel out of range")]
+    InvalidPixel { index: usize }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 /// A collection of `AlphaPixel`s that represent an image. This is stored in a `Vec`.
+/// 
+/// Cloning an image is cheap, as clones share their pixels until one of them is modified, when its pixels are copied.
+/// This allows the same image to be used by several layers without duplicating it.
 pub struct Image<T: PixelChannel> {
-    pixels: Vec<AlphaPixel<T>>,
+    pixels: Arc<Vec<AlphaPixel<T>>>,
     width: usize,
     height: usize,
 }
 
+/// Only the dimensions of the image are shown, as its pixels are too long to be useful.
+impl<T: PixelChannel> Debug for Image<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Image").field("width", &self.width).field("height", &self.height).finish_non_exhaustive()
+    }
+}
+
+impl<T: PixelChannel> Display for Image<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{} image", self.width, self.height)
+    }
+}
+
 impl<T: PixelChannel> Default for Image<T> {
     fn default() -> Self {
         Self::new()
@@ -31,6 +58,61 @@ impl<T: PixelChannel> Image<T> {
         &self.pixels
     }
 
+    /// Get the pixels mutably. If the pixels are shared with a clone of this image, they are copied first.
+    pub fn get_pixels_mut(&mut self) -> &mut [AlphaPixel<T>] {
+        Arc::make_mut(&mut self.pixels).as_mut_slice()
+    }
+
+    /// Check whether this image shares its pixels with another image, because one is an unmodified clone of the other.
+    /// 
+    /// ```
+    /// use image_template::{Image, AlphaPixel};
+    /// 
+    /// let cover_art: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 1000, 1000);
+    /// let mut background = cover_art.clone();
+    /// assert!(background.shares_pixels_with(&cover_art));
+    /// 
+    /// *background.pixel_at_mut(0, 0).unwrap() = AlphaPixel::blue();
+    /// assert!(!background.shares_pixels_with(&cover_art));
+    /// assert_eq!(cover_art.pixel_at(0, 0).unwrap(), AlphaPixel::red());
+    /// ```
+    pub fn shares_pixels_with(&self, other: &Image<T>) -> bool {
+        Arc::ptr_eq(&self.pixels, &other.pixels)
+    }
+
+    /// Get a 64 bit hash of the dimensions and pixels of the image, using XXH3.
+    /// 
+    /// Equal images have the same hash, so this can be stored instead of an image to check whether a render has changed,
+    /// such as in a cache or a golden test. The hash is of the bytes of each channel, so it is the same on every run
+    /// and platform with the same endianness. Negative zero is hashed as zero, as they are equal. Images with
+    /// NaN channels aren't equal to any image, but are hashed by their bytes.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel};
+    /// 
+    /// let poster: Image<u8> = Image::new_with_fill(AlphaPixel::white(), 100, 100);
+    /// let mut edited = poster.clone();
+    /// assert_eq!(poster.content_hash(), edited.content_hash());
+    /// 
+    /// *edited.pixel_at_mut(50, 50).unwrap() = AlphaPixel::black();
+    /// assert_ne!(poster.content_hash(), edited.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Xxh3::new();
+        hasher.update(&(self.width as u64).to_le_bytes());
+        hasher.update(&(self.height as u64).to_le_bytes());
+        // Floats have a negative zero, which is equal to zero but has different bytes
+        let normalize = |channel: T| if channel.is_zero() { T::zero() } else { channel };
+        let mut normalized = Vec::with_capacity(HASH_CHUNK_PIXELS);
+        for chunk in self.pixels.chunks(HASH_CHUNK_PIXELS) {
+            normalized.clear();
+            normalized.extend(chunk.iter().map(|pixel| AlphaPixel { r: normalize(pixel.r), g: normalize(pixel.g), b: normalize(pixel.b), a: normalize(pixel.a) }));
+            hasher.update(must_cast_slice(&normalized));
+        }
+        hasher.digest()
+    }
+
     pub fn get_width(&self) -> usize {
         self.width
     }
@@ -46,7 +128,7 @@ impl<T: PixelChannel> Image<T> {
     /// let image: Image<u8> = Image::new();
     /// ```
     pub fn new() -> Self {
-        Self { pixels: vec![], width: 0, height: 0 }
+        Self { pixels: Arc::new(vec![]), width: 0, height: 0 }
     }
 
     /// Create a new image, filled with `fill`.
@@ -56,12 +138,48 @@ impl<T: PixelChannel> Image<T> {
     /// let image: Image<u8> = Image::new_with_fill(rgba!(255, 0, 0, 255), 10, 10);
     /// ```
     pub fn new_with_fill(fill: AlphaPixel<T>, width: usize, height: usize) -> Self {
+        strict_assert!(fill.is_valid(), "fill has a channel out of range");
         let pixels = vec![fill; width*height];
-        Self { pixels, width, height }
+        Self { pixels: Arc::new(pixels), width, height }
+    }
+
+    /// Create a new image, filled with `fill`, or return an error if the dimensions are too large for the image
+    /// to be allocated, instead of panicking or aborting as [`Image::new_with_fill`] does.
+    /// 
+    /// This should be used when the dimensions come from untrusted input, such as a template. Returns
+    /// [`NewImageError::InvalidPixel`] if `fill` has a channel out of range.
+    /// 
+    /// ```
+    /// use image_template::{Image, AlphaPixel, bitmap::image::NewImageError};
+    /// 
+    /// let image: Result<Image<u8>, _> = Image::try_new_with_fill(AlphaPixel::red(), 10, 10);
+    /// assert!(image.is_ok());
+    /// 
+    /// let huge: Result<Image<u8>, _> = Image::try_new_with_fill(AlphaPixel::red(), usize::MAX, 2);
+    /// assert_eq!(huge.unwrap_err(), NewImageError::TooLarge { width: usize::MAX, height: 2 });
+    /// ```
+    pub fn try_new_with_fill(fill: AlphaPixel<T>, width: usize, height: usize) -> Result<Self, NewImageError> {
+        if !fill.is_valid() {
+            return Err(NewImageError::InvalidPixel { index: 0 })
+        }
+        let mut pixels = Self::try_allocate(width, height)?;
+        pixels.resize(width*height, fill);
+        Ok(Self { pixels: Arc::new(pixels), width, height })
+    }
+
+    /// Allocate space for the pixels of an image, or return an error if the allocation would overflow or fail.
+    fn try_allocate(width: usize, height: usize) -> Result<Vec<AlphaPixel<T>>, NewImageError> {
+        let too_large = NewImageError::TooLarge { width, height };
+        let length = width.checked_mul(height).ok_or(too_large.clone())?;
+        let mut pixels = vec![];
+        pixels.try_reserve_exact(length).map_err(|_| too_large)?;
+        Ok(pixels)
     }
 
     /// Create a new image, from a [`Vec`] of `AlphaPixel<T>`.
     /// 
+    /// Returns [`NewImageError::InvalidPixel`] if a pixel has a channel out of range, such as a float channel above 1.0.
+    /// 
     /// ```
     /// use image_template::{AlphaPixel, Image};
     /// 
@@ -79,11 +197,14 @@ impl<T: PixelChannel> Image<T> {
             }
         }
 
+        if let Some(index) = pixels.iter().position(|pixel| !pixel.is_valid()) {
+            return Err(NewImageError::InvalidPixel { index })
+        }
         let (height, rem) = (pixels.len() / width, pixels.len() % width);
         if rem != 0 {
             Err(NewImageError::IncorrectWidth)
         } else {
-            Ok(Self { pixels, width, height })
+            Ok(Self { pixels: Arc::new(pixels), width, height })
         }
     }
 
@@ -104,7 +225,31 @@ impl<T: PixelChannel> Image<T> {
                 pixels.push(function(col, row))
             }
         }
-        Self { pixels, width, height }
+        Self { pixels: Arc::new(pixels), width, height }
+    }
+
+    /// Create an image from a function that maps coordinates to pixels, or return an error if the dimensions are too
+    /// large for the image to be allocated, as with [`Image::try_new_with_fill`], or if `function` returns a pixel with a
+    /// channel out of range.
+    /// 
+    /// ```
+    /// use image_template::{Image, AlphaPixel};
+    /// 
+    /// let image: Image<u8> = Image::try_from_function(10, 10, |_x, _y| AlphaPixel::red()).unwrap();
+    /// assert!(Image::<u8>::try_from_function(usize::MAX, usize::MAX, |_x, _y| AlphaPixel::red()).is_err());
+    /// ```
+    pub fn try_from_function<F: FnMut(usize, usize) -> AlphaPixel<T>>(width: usize, height: usize, mut function: F) -> Result<Self, NewImageError> {
+        let mut pixels = Self::try_allocate(width, height)?;
+        for row in 0..height {
+            for col in 0..width {
+                let pixel = function(col, row);
+                if !pixel.is_valid() {
+                    return Err(NewImageError::InvalidPixel { index: pixels.len() })
+                }
+                pixels.push(pixel)
+            }
+        }
+        Ok(Self { pixels: Arc::new(pixels), width, height })
     }
 
     /// Get the index into the collection of pixels for a given coordinate.
@@ -174,7 +319,7 @@ impl<T: PixelChannel> Image<T> {
     /// assert_eq!(second_row, &[AlphaPixel::red(); 5])
     pub fn row_mut(&mut self, y: usize) -> Option<&mut [AlphaPixel<T>]> {
         let range = self.index_of(0, y)?..self.index_of(0, y+1)?;
-        self.pixels.get_mut(range)
+        Arc::make_mut(&mut self.pixels).get_mut(range)
     }
 
     /// Get the pixel at a given coordinate.
@@ -202,7 +347,47 @@ impl<T: PixelChannel> Image<T> {
     /// ```
     pub fn pixel_at_mut(&mut self, x: usize, y: usize) -> Option<&mut AlphaPixel<T>> {
         let idx = self.index_of(x, y)?;
-        self.pixels.get_mut(idx)
+        Arc::make_mut(&mut self.pixels).get_mut(idx)
+    }
+
+    /// Get the pixel at a non-integer coordinate, by bilinear interpolation of the 4 nearest pixels.
+    /// 
+    /// Pixel centers are at half-integer coordinates, so `(0.5, 0.5)` is exactly the top left pixel.
+    /// Colours are weighted by alpha, so transparent pixels don't darken the edges of the image.
+    /// Returns `None` if the coordinate isn't within the image.
+    /// 
+    /// ```
+    /// use image_template::{Image, AlphaPixel, rgba};
+    /// 
+    /// let image: Image<u8> = Image::from_pixels(vec![AlphaPixel::black(), AlphaPixel::white()], 2).unwrap();
+    /// assert_eq!(image.sample_bilinear(1.0, 0.5).unwrap(), rgba!(127, 127, 127, 255));
+    /// ```
+    pub fn sample_bilinear(&self, x: f32, y: f32) -> Option<AlphaPixel<T>> {
+        self.sample_with(&BilinearSampler, x, y)
+    }
+
+    /// Get the pixel at a non-integer coordinate with a [`Sampler`]. Pixels beyond the edge of the image are
+    /// clamped to the edge, so the image doesn't fade out at its border.
+    /// 
+    /// Returns `None` if the coordinate isn't within the image.
+    /// 
+    /// ```
+    /// use image_template::{Image, AlphaPixel};
+    /// use image_template::bitmap::sampler::NearestSampler;
+    /// 
+    /// let image: Image<u8> = Image::from_pixels(vec![AlphaPixel::black(), AlphaPixel::white()], 2).unwrap();
+    /// assert_eq!(image.sample_with(&NearestSampler, 1.2, 0.5).unwrap(), AlphaPixel::white());
+    /// ```
+    pub fn sample_with<S: Sampler + ?Sized>(&self, sampler: &S, x: f32, y: f32) -> Option<AlphaPixel<T>> {
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None
+        }
+
+        sample(sampler, x, y, |sample_x, sample_y| {
+            let sample_x = sample_x.clamp(0, self.width as isize - 1) as usize;
+            let sample_y = sample_y.clamp(0, self.height as isize - 1) as usize;
+            Some(self.pixels[self.index_of_unchecked(sample_x, sample_y)])
+        })
     }
 
     /// Check whether a coordinate is within the bounds of an image.
@@ -224,21 +409,28 @@ impl<T: PixelChannel> Image<T> {
     /// 
     /// `blend` is the method to combine the foreground and background. For most cases use [`BlendingMethod::Over`].
     /// 
-    /// If `None` is returned, then the coordinate is not in the image bounds.
-    pub fn draw_subimage(&mut self, image: &Image<T>, x: usize, y: usize, blend: BlendingMethod<T>) -> Option<()> {
+    /// Returns [`Error::OutOfBounds`] if the coordinate is not in the image bounds, unless the subimage is empty.
+    pub fn draw_subimage(&mut self, image: &Image<T>, x: usize, y: usize, blend: BlendingMethod<T>) -> Result<(), Error> {
+        if image.width == 0 || image.height == 0 {
+            return Ok(())
+        }
+        if !self.contains(x, y) {
+            return Err(Error::OutOfBounds { x, y })
+        }
+
         let subim_width = (x+image.width).min(self.width) - x;
         let subim_height = (y+image.height).min(self.height) - y;
 
         for row in 0..subim_height {
-            let slice = self.index_of(x, y+row)?..self.index_of_unchecked(x+subim_width, y+row);
+            let slice = self.index_of_unchecked(x, y+row)..self.index_of_unchecked(x+subim_width, y+row);
             let src_row = &image.row(row).unwrap()[0..subim_width];
             
-            self.pixels[slice].iter_mut()
+            Arc::make_mut(&mut self.pixels)[slice].iter_mut()
                 .zip(src_row.iter())
                 .for_each(|(dest, src)| *dest = blend.blend(*dest, *src));
         }
 
-        Some(())
+        Ok(())
     }
 
     /// Extend the height by `height` number of rows, filling with `fill`.
@@ -255,7 +447,7 @@ impl<T: PixelChannel> Image<T> {
     /// assert_eq!(image.pixel_at(3, 6).unwrap(), AlphaPixel::red());
     /// ```
     pub fn extend_height(&mut self, height: usize, fill: AlphaPixel<T>) {
-        self.pixels.extend(repeat(fill).take(height*self.width));
+        Arc::make_mut(&mut self.pixels).extend(repeat_n(fill, height*self.width));
         self.height += height;
     }
 
@@ -273,14 +465,42 @@ impl<T: PixelChannel> Image<T> {
     /// ```
     pub fn shrink_height(&mut self, height: usize) {
         let remaining_height = self.height - height;
-        self.pixels.truncate(remaining_height*self.width);
+        Arc::make_mut(&mut self.pixels).truncate(remaining_height*self.width);
         self.height = remaining_height;
     }
+
+    /// Shrink the height by `height` number of rows, or return an error without changing the image if it has fewer rows
+    /// than that, instead of panicking as [`Image::shrink_height`] does.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, bitmap::image::NewImageError};
+    /// 
+    /// let mut image: Image<u8> = Image::new_with_fill(AlphaPixel::black(), 5, 5);
+    /// assert_eq!(image.try_shrink_height(6), Err(NewImageError::TooFewRows { removed: 6, height: 5 }));
+    /// assert_eq!(image.try_shrink_height(5), Ok(()));
+    /// assert_eq!(image.get_height(), 0);
+    /// ```
+    pub fn try_shrink_height(&mut self, height: usize) -> Result<(), NewImageError> {
+        if height > self.height {
+            return Err(NewImageError::TooFewRows { removed: height, height: self.height })
+        }
+        self.shrink_height(height);
+        Ok(())
+    }
+}
+
+/// Images are equal if they have the same dimensions and pixels. Each pixel is compared, even if the images
+/// [share pixels](Image::shares_pixels_with), so an image with NaN channels isn't equal to itself, as with floats.
+impl<T: PixelChannel> PartialEq for Image<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.pixels == other.pixels
+    }
 }
 
 impl<T: PixelChannel> AsRef<[u8]> for Image<T> {
     fn as_ref(&self) -> &[u8] {
-        must_cast_slice(&self.pixels)
+        must_cast_slice(self.pixels.as_slice())
     }
 }
 
@@ -288,8 +508,10 @@ impl<T: PixelChannel> AsRef<[u8]> for Image<T> {
 use {
     std::path::Path,
     std::fs::File,
-    std::io::BufReader,
+    std::io::{BufReader, Cursor},
+    crate::limits::Limits,
     image::{
+        ImageReader,
         error::{ParameterError, ParameterErrorKind},
         save_buffer_with_format,
         GenericImageView,
@@ -322,6 +544,38 @@ impl<T: PixelChannel> Image<T> {
         image::load_from_memory_with_format(buffer.as_ref(), format).map(|im| im.into())
     }
 
+    /// Decode an image, or return an error without decoding it if it is larger than the
+    /// [`max_decode_dimensions`](Limits::max_decode_dimensions) of `limits`.
+    /// 
+    /// The dimensions are read from the image's header, and the decoder is also limited to them, so that an image can't
+    /// use more memory than allowed by claiming to be smaller than it is.
+    /// 
+    /// ```
+    /// use image_template::{Image, ImageFormat, Error, limits::{Limits, LimitError}};
+    /// 
+    /// let mut png = vec![];
+    /// image::RgbaImage::new(32, 32).write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png).unwrap();
+    /// 
+    /// let limits = Limits { max_decode_dimensions: (16, 16), ..Default::default() };
+    /// let image = Image::<u8>::load_from_memory_with_limits(&png, ImageFormat::Png, &limits);
+    /// assert!(matches!(image, Err(Error::Limit(LimitError::ImageTooLarge { width: 32, height: 32, .. }))));
+    /// 
+    /// let image = Image::<u8>::load_from_memory_with_limits(&png, ImageFormat::Png, &Limits::default()).unwrap();
+    /// assert_eq!(image.get_width(), 32);
+    /// ```
+    pub fn load_from_memory_with_limits<B: AsRef<[u8]>>(buffer: B, format: ImageFormat, limits: &Limits) -> Result<Image<T>, Error> {
+        let reader = || ImageReader::with_format(Cursor::new(buffer.as_ref()), format);
+        let (width, height) = reader().into_dimensions()?;
+        limits.check_decode(width as usize, height as usize)?;
+
+        let mut decoder_limits = image::Limits::default();
+        decoder_limits.max_image_width = Some(limits.max_decode_dimensions.0.try_into().unwrap_or(u32::MAX));
+        decoder_limits.max_image_height = Some(limits.max_decode_dimensions.1.try_into().unwrap_or(u32::MAX));
+        let mut reader = reader();
+        reader.limits(decoder_limits);
+        Ok(reader.decode()?.into())
+    }
+
     pub fn load_from_file<P: AsRef<Path>>(path: P, format: ImageFormat) -> ImageResult<Image<T>> {
         let file = File::open(path)
             .map_err(ImageError::IoError)?;
@@ -357,7 +611,7 @@ impl<T: PixelChannel> From<DynamicImage> for Image<T> {
             _ => unimplemented!()
         };
 
-        Self { pixels: AlphaPixel::try_pixel_vec_from_channels(channel_buf).unwrap(), width, height }
+        Self { pixels: Arc::new(AlphaPixel::try_pixel_vec_from_channels(channel_buf).unwrap()), width, height }
     }
 }
 
@@ -388,7 +642,7 @@ impl<T: PixelChannel, P: Pixel<Subpixel = T>> TryFrom<ImageBuffer<P, Vec<T>>> fo
         let (width, height) = (value.width() as usize, value.height() as usize);
         let buf = value.into_raw();
         let pixel_buf = AlphaPixel::try_pixel_vec_from_channels(buf)?;
-        Ok(Self { pixels: pixel_buf, width, height })
+        Ok(Self { pixels: Arc::new(pixel_buf), width, height })
     }
 }
 
@@ -421,6 +675,72 @@ impl<T: PixelChannel + Primitive> GenericImage for Image<T> {
     }
 }
 
+#[cfg(feature = "skia")]
+use tiny_skia::{Pixmap, IntSize, ColorU8, PremultipliedColorU8};
+
+#[cfg(feature = "skia")]
+#[derive(Debug, Error, Clone, Copy, PartialEq)]
+#[error("A {width}x{height} image can't be converted to a pixmap, which can't be empty or over i32::MAX pixels wide or tall")]
+pub struct PixmapSizeError {
+    pub width: usize,
+    pub height: usize
+}
+
+#[cfg(feature = "skia")]
+impl TryFrom<Image<u8>> for Pixmap {
+    type Error = PixmapSizeError;
+
+    /// Convert an `Image<u8>` to a [`Pixmap`], which stores premultiplied colours.
+    /// 
+    /// The pixels are premultiplied in place, so they are only copied if they are shared with another image.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, rgba};
+    /// 
+    /// let image: Image<u8> = Image::new_with_fill(rgba!(255, 0, 0, 128), 4, 4);
+    /// let pixmap = tiny_skia::Pixmap::try_from(image).unwrap();
+    /// assert_eq!(pixmap.pixel(0, 0).unwrap().red(), 128);
+    /// ```
+    fn try_from(image: Image<u8>) -> Result<Self, Self::Error> {
+        let size_error = PixmapSizeError { width: image.width, height: image.height };
+        let size = u32::try_from(image.width).ok()
+            .zip(u32::try_from(image.height).ok())
+            .and_then(|(width, height)| IntSize::from_wh(width, height))
+            .ok_or(size_error)?;
+
+        let pixels = Arc::try_unwrap(image.pixels).unwrap_or_else(|shared| (*shared).clone());
+        let mut data: Vec<u8> = bytemuck::allocation::try_cast_vec(pixels)
+            .unwrap_or_else(|(_, pixels)| must_cast_slice(&pixels).to_vec());
+        for pixel in data.chunks_exact_mut(4) {
+            let premultiplied = ColorU8::from_rgba(pixel[0], pixel[1], pixel[2], pixel[3]).premultiply();
+            pixel.copy_from_slice(&[premultiplied.red(), premultiplied.green(), premultiplied.blue(), premultiplied.alpha()]);
+        }
+
+        Pixmap::from_vec(data, size).ok_or(size_error)
+    }
+}
+
+#[cfg(feature = "skia")]
+impl From<Pixmap> for Image<u8> {
+    /// Convert a [`Pixmap`] to an `Image<u8>`. The colours are unpremultiplied in place, without copying the pixmap's pixels.
+    fn from(pixmap: Pixmap) -> Self {
+        let (width, height) = (pixmap.width() as usize, pixmap.height() as usize);
+        let mut data = pixmap.take();
+        for pixel in data.chunks_exact_mut(4) {
+            // The pixmap only contains valid premultiplied colours, where no channel is greater than the alpha
+            if let Some(premultiplied) = PremultipliedColorU8::from_rgba(pixel[0], pixel[1], pixel[2], pixel[3]) {
+                let color = premultiplied.demultiply();
+                pixel.copy_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+            }
+        }
+
+        let pixels = AlphaPixel::try_pixel_vec_from_channels(data)
+            .unwrap_or_else(|error| error.original_vec.chunks_exact(4).map(|c| AlphaPixel { r: c[0], g: c[1], b: c[2], a: c[3] }).collect());
+        Self { pixels: Arc::new(pixels), width, height }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -461,6 +781,17 @@ mod tests {
         assert_eq!(image_error_zero_width.unwrap_err(), NewImageError::ZeroWidth);
     }
 
+    #[test]
+    fn invalid_pixels_fail() {
+        let over = AlphaPixel { r: 1.5, g: 0.0, b: 0.0, a: 1.0 };
+        let pixels = vec![AlphaPixel::<f32>::black(), over];
+        assert_eq!(Image::from_pixels(pixels, 2).unwrap_err(), NewImageError::InvalidPixel { index: 1 });
+
+        assert_eq!(Image::try_new_with_fill(AlphaPixel { a: f32::NAN, ..over }, 2, 2).unwrap_err(), NewImageError::InvalidPixel { index: 0 });
+        let image = Image::try_from_function(2, 2, |x, y| if (x, y) == (1, 1) { over } else { AlphaPixel::white() });
+        assert_eq!(image.unwrap_err(), NewImageError::InvalidPixel { index: 3 });
+    }
+
     #[test]
     fn from_pixels_valid() {
         let valid_image = Image::<u8>::from_pixels(vec![], 0);
@@ -497,7 +828,8 @@ mod tests {
     fn draw_subimage() {
         let mut background_image = Image::<u8>::new_with_fill(AlphaPixel::red(), 100, 100);
         let subimage = Image::new_with_fill(AlphaPixel::blue(), 30, 20);
-        background_image.draw_subimage(&subimage, 50, 20, BlendingMethod::Replace);
+        background_image.draw_subimage(&subimage, 50, 20, BlendingMethod::Replace).unwrap();
+        assert!(matches!(background_image.draw_subimage(&subimage, 100, 0, BlendingMethod::Over), Err(Error::OutOfBounds { x: 100, y: 0 })));
 
         assert_eq!(background_image.pixel_at(0, 0).unwrap(), AlphaPixel::red());
 
@@ -513,4 +845,51 @@ mod tests {
 
         assert_eq!(background_image.pixel_at(99, 99).unwrap(), AlphaPixel::red());
     }
+
+    #[test]
+    fn equality_and_hash() {
+        let image = create_test_image();
+        let mut copy = Image::from_pixels(image.get_pixels().to_vec(), image.get_width()).unwrap();
+        assert_eq!(image, copy);
+        assert_eq!(image.content_hash(), copy.content_hash());
+
+        copy.pixel_at_mut(3, 3).unwrap().a = 0;
+        assert_ne!(image, copy);
+        assert_ne!(image.content_hash(), copy.content_hash());
+
+        // The same pixels with different dimensions aren't equal
+        let wide = Image::new_with_fill(AlphaPixel::<u8>::red(), 4, 1);
+        let tall = Image::new_with_fill(AlphaPixel::<u8>::red(), 1, 4);
+        assert_ne!(wide, tall);
+        assert_ne!(wide.content_hash(), tall.content_hash());
+    }
+
+    #[test]
+    fn float_equality_and_hash() {
+        let zero = Image::new_with_fill(AlphaPixel { r: 0.0f32, g: 0.5, b: 0.0, a: 1.0 }, 3, 2);
+        let negative_zero = Image::new_with_fill(AlphaPixel { r: -0.0f32, g: 0.5, b: -0.0, a: 1.0 }, 3, 2);
+        assert_eq!(zero, negative_zero);
+        assert_eq!(zero.content_hash(), negative_zero.content_hash());
+
+        let nan = Image::new_with_fill(AlphaPixel { r: f32::NAN, g: 0.5, b: 0.0, a: 1.0 }, 3, 2);
+        assert_ne!(nan, nan.clone());
+    }
+
+    #[test]
+    #[cfg(feature = "skia")]
+    fn pixmap_round_trip() {
+        let image = Image::from_function(3, 2, |x, y| rgba!(200, 100, 50, (x * 100 + y * 20) as u8));
+        let pixmap = tiny_skia::Pixmap::try_from(image.clone()).unwrap();
+        assert_eq!(pixmap.pixel(2, 1).unwrap().alpha(), 220);
+        assert_eq!(pixmap.pixel(2, 0).unwrap().red(), 157);
+
+        // Colours are rounded when premultiplied, and lost when fully transparent
+        let round_trip = Image::from(pixmap);
+        assert_eq!(round_trip.pixel_at(0, 0).unwrap(), rgba!(0, 0, 0, 0));
+        assert_eq!(round_trip.pixel_at(2, 1).unwrap(), rgba!(201, 100, 50, 220));
+
+        let empty: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 0, 3);
+        assert_eq!(tiny_skia::Pixmap::try_from(empty), Err(PixmapSizeError { width: 0, height: 3 }));
+    }
+
 }
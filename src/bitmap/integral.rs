@@ -0,0 +1,134 @@
+use crate::{Image, AlphaPixel, PixelChannel, Rect};
+use super::average::PremultipliedSum;
+
+/// A summed-area table of an image, for finding the average of any rectangle of pixels in constant time.
+/// 
+/// Colours are premultiplied by alpha, so averages are weighted by alpha, and transparent pixels don't darken them.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, Rect, bitmap::integral::IntegralImage};
+/// 
+/// let image: Image<u8> = Image::from_function(10, 10, |x, _| if x < 5 { AlphaPixel::black() } else { AlphaPixel::white() });
+/// let integral = IntegralImage::new(&image);
+/// 
+/// let average: AlphaPixel<u8> = integral.average(Rect { x: 0, y: 0, width: 10, height: 10 }).unwrap();
+/// assert_eq!(average.r, 127);
+/// ```
+#[derive(Clone)]
+pub struct IntegralImage {
+    width: usize,
+    height: usize,
+    /// Sums of the premultiplied channels of every pixel above and to the left, with an extra row and column of zeros
+    sums: Vec<[f64; 4]>
+}
+
+impl IntegralImage {
+    pub fn new<T: PixelChannel>(image: &Image<T>) -> Self {
+        let (width, height) = (image.get_width(), image.get_height());
+        let stride = width + 1;
+        let mut sums = vec![[0.0; 4]; stride * (height + 1)];
+
+        for y in 0..height {
+            let mut row_sum = PremultipliedSum::default();
+            for x in 0..width {
+                row_sum.add(image.pixel_at(x, y).unwrap(), 1.0);
+
+                let above = sums[y*stride + x + 1];
+                let row = row_sum.channels;
+                sums[(y + 1)*stride + x + 1] = [above[0] + row[0], above[1] + row[1], above[2] + row[2], above[3] + row[3]];
+            }
+        }
+
+        Self { width, height, sums }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// Clip `rect` to the bounds of the image, returning `None` if it is empty.
+    fn clip(&self, rect: Rect) -> Option<Rect> {
+        rect.intersection(&Rect { x: 0, y: 0, width: self.width, height: self.height })
+    }
+
+    /// Get the sums of the premultiplied red, green and blue channels, and the alpha channel, of the pixels in `rect`,
+    /// as floats between 0 and 1. `rect` is cut off at the edges of the image.
+    pub fn sum(&self, rect: Rect) -> [f64; 4] {
+        let Some(rect) = self.clip(rect) else {
+            return [0.0; 4]
+        };
+
+        let stride = self.width + 1;
+        let top_left = self.sums[rect.y*stride + rect.x];
+        let top_right = self.sums[rect.y*stride + rect.right_x()];
+        let bottom_left = self.sums[rect.bottom_y()*stride + rect.x];
+        let bottom_right = self.sums[rect.bottom_y()*stride + rect.right_x()];
+
+        let mut sum = [0.0; 4];
+        for (channel, total) in sum.iter_mut().enumerate() {
+            *total = bottom_right[channel] - top_right[channel] - bottom_left[channel] + top_left[channel];
+        }
+        sum
+    }
+
+    /// Get the alpha weighted average of the pixels in `rect`, which is cut off at the edges of the image.
+    /// Returns `None` if no pixels of `rect` are in the image.
+    pub fn average<T: PixelChannel>(&self, rect: Rect) -> Option<AlphaPixel<T>> {
+        let clipped = self.clip(rect)?;
+        let sum = PremultipliedSum { channels: self.sum(clipped) };
+        Some(sum.average((clipped.width * clipped.height) as f64))
+    }
+
+    /// Get the average of the square of pixels within `radius` of (`x`, `y`), cut off at the edges of the image.
+    pub fn box_average<T: PixelChannel>(&self, x: usize, y: usize, radius: usize) -> Option<AlphaPixel<T>> {
+        let left = x.saturating_sub(radius);
+        let top = y.saturating_sub(radius);
+        self.average(Rect { x: left, y: top, width: x + radius + 1 - left, height: y + radius + 1 - top })
+    }
+}
+
+impl<T: PixelChannel> Image<T> {
+    /// Blur the image, by replacing each pixel with the alpha weighted average of the square of pixels within `radius` of it.
+    /// 
+    /// This takes the same time for any radius, as averages are found with an [`IntegralImage`].
+    /// Near the edges, only pixels within the image are averaged.
+    pub fn box_blur(&self, radius: usize) -> Image<T> {
+        let integral = IntegralImage::new(self);
+        Image::from_function(self.get_width(), self.get_height(), |x, y| integral.box_average(x, y, radius).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn sum() {
+        let image: Image<u8> = Image::from_function(4, 3, |x, y| if x == y { AlphaPixel::white() } else { rgba!(0, 0, 0, 0) });
+        let integral = IntegralImage::new(&image);
+
+        assert_eq!(integral.sum(Rect { x: 0, y: 0, width: 4, height: 3 })[3], 3.0);
+        assert_eq!(integral.sum(Rect { x: 1, y: 0, width: 2, height: 2 })[0], 1.0);
+        // Cut off at the edges
+        assert_eq!(integral.sum(Rect { x: 2, y: 2, width: 10, height: 10 })[3], 1.0);
+        assert_eq!(integral.sum(Rect { x: 10, y: 10, width: 10, height: 10 })[3], 0.0);
+    }
+
+    #[test]
+    fn box_blur() {
+        let image: Image<u8> = Image::from_function(9, 1, |x, _| if x == 4 { AlphaPixel::red() } else { rgba!(0, 0, 255, 0) });
+        let blurred = image.box_blur(1);
+
+        // Transparent pixels don't change the colour, only the alpha
+        assert_eq!(blurred.pixel_at(3, 0).unwrap(), rgba!(255, 0, 0, 85));
+        assert_eq!(blurred.pixel_at(2, 0).unwrap().a, 0);
+        // At the edge, only 2 pixels are averaged
+        assert_eq!(Image::<u8>::from_pixels(vec![AlphaPixel::white(), AlphaPixel::black(), AlphaPixel::black()], 3).unwrap().box_blur(1).pixel_at(0, 0).unwrap().r, 127);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{Image, AlphaPixel, PixelChannel, Rect, Error};
+
+/// A single channel image of coverage values from 0.0 to 1.0, for selecting parts of an image.
+/// 
+/// Masks can be made from the alpha channel of an image, or from operations such as
+/// [`Image::flood_fill_mask`], and used to fill or cut out parts of an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mask {
+    values: Vec<f32>,
+    width: usize,
+    height: usize
+}
+
+impl Mask {
+    /// Create a mask where every value is `value`.
+    pub fn new(width: usize, height: usize, value: f32) -> Self {
+        Self { values: vec![value.clamp(0.0, 1.0); width*height], width, height }
+    }
+
+    /// Create a mask from a function that maps coordinates to values. Values are clamped between 0.0 and 1.0.
+    pub fn from_function<F: FnMut(usize, usize) -> f32>(width: usize, height: usize, mut function: F) -> Self {
+        let mut values = Vec::with_capacity(width*height);
+        for y in 0..height {
+            for x in 0..width {
+                values.push(function(x, y).clamp(0.0, 1.0));
+            }
+        }
+        Self { values, width, height }
+    }
+
+    /// Create a mask from the alpha channel of an image.
+    pub fn from_alpha<T: PixelChannel>(image: &Image<T>) -> Self {
+        let max = T::MAX_PIXEL_VALUE.into();
+        Self::from_function(image.get_width(), image.get_height(), |x, y| image.pixel_at(x, y).unwrap().a.into() / max)
+    }
+
+    /// Create a mask that is 1.0 inside `rect`, and 0.0 outside of it.
+    pub fn from_rect(width: usize, height: usize, rect: Rect) -> Self {
+        Self::from_function(width, height, |x, y| if rect.contains(x, y) { 1.0 } else { 0.0 })
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Get the value at a coordinate, or `None` if it isn't in the mask.
+    pub fn value_at(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None
+        }
+        Some(self.values[y*self.width + x])
+    }
+
+    /// Set the value at a coordinate, clamped between 0.0 and 1.0. Returns [`Error::OutOfBounds`] if the coordinate isn't in the mask.
+    pub fn set_value(&mut self, x: usize, y: usize, value: f32) -> Result<(), Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y })
+        }
+        self.values[y*self.width + x] = value.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    /// Swap covered and uncovered areas.
+    pub fn invert(&mut self) {
+        for value in &mut self.values {
+            *value = 1.0 - *value;
+        }
+    }
+
+    /// Create an image of `color`, with its alpha multiplied by this mask.
+    pub fn to_image<T: PixelChannel>(&self, color: AlphaPixel<T>) -> Image<T> {
+        Image::from_function(self.width, self.height, |x, y| color.scale_alpha(self.values[y*self.width + x]))
+    }
+
+    /// Multiply the alpha of each pixel of `image` by this mask, cutting out the uncovered areas.
+    /// Pixels of `image` outside of the mask are made transparent.
+    pub fn apply_to_alpha<T: PixelChannel>(&self, image: &mut Image<T>) {
+        let width = image.get_width();
+        for (index, pixel) in image.get_pixels_mut().iter_mut().enumerate() {
+            let value = self.value_at(index % width, index / width).unwrap_or(0.0);
+            *pixel = pixel.scale_alpha(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn alpha_round_trip() {
+        let image: Image<u8> = Image::from_pixels(vec![rgba!(10, 20, 30, 255), rgba!(10, 20, 30, 0)], 2).unwrap();
+        let mask = Mask::from_alpha(&image);
+        assert_eq!(mask.get_values(), &[1.0, 0.0]);
+        assert_eq!(mask.to_image(AlphaPixel::<u8>::red()).get_pixels(), &[AlphaPixel::red(), rgba!(255, 0, 0, 0)]);
+    }
+
+    #[test]
+    fn apply_to_alpha() {
+        let mut mask = Mask::from_rect(3, 1, Rect { x: 0, y: 0, width: 1, height: 1 });
+        mask.invert();
+        let mut image: Image<u8> = Image::new_with_fill(AlphaPixel::white(), 4, 1);
+        mask.apply_to_alpha(&mut image);
+
+        let alphas: Vec<u8> = image.get_pixels().iter().map(|pixel| pixel.a).collect();
+        assert_eq!(alphas, vec![0, 255, 255, 0]);
+    }
+}
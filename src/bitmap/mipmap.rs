@@ -0,0 +1,155 @@
+use crate::{Image, AlphaPixel, PixelChannel};
+use super::{sampler::{Sampler, BilinearSampler}, average::PremultipliedSum};
+
+/// A chain of progressively halved copies of an image, for sampling it when it is drawn much smaller than its size.
+/// 
+/// Sampling a large image at a small size skips most of its pixels, which causes aliasing such as jagged edges and moiré.
+/// Sampling from a smaller level instead averages the skipped pixels.
+/// 
+/// Level 0 is the original image, and each following level is half the size, rounded up, until a 1 by 1 image.
+/// [`ScaledImageLayer`](crate::layers::image::ScaledImageLayer) samples from mipmaps when it shrinks its image, and
+/// [`MatrixTransform::with_mipmaps`](crate::filters::transform::MatrixTransform::with_mipmaps) when a transform shrinks it.
+#[derive(Clone)]
+pub struct Mipmaps<T: PixelChannel> {
+    levels: Vec<Image<T>>
+}
+
+impl<T: PixelChannel> Mipmaps<T> {
+    pub fn levels(&self) -> &[Image<T>] {
+        &self.levels
+    }
+
+    /// Get the original image.
+    pub fn base(&self) -> &Image<T> {
+        &self.levels[0]
+    }
+
+    /// Sample the image at a coordinate of the original image, where `scale` is the number of original pixels that
+    /// each sampled pixel covers. A `scale` of 4.0 is drawing the image at a quarter of its size.
+    /// 
+    /// Levels are chosen by `scale`, and blended between with trilinear filtering. Returns `None` if the coordinate
+    /// isn't within the original image. As with [`Image::sample_bilinear`], pixel centers are at half-integer coordinates.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel};
+    /// 
+    /// // A checkerboard of single pixels averages to grey when shrunk
+    /// let checkerboard: Image<u8> = Image::from_function(64, 64, |x, y| if (x + y) % 2 == 0 { AlphaPixel::black() } else { AlphaPixel::white() });
+    /// let mipmaps = checkerboard.build_mipmaps();
+    /// 
+    /// let pixel = mipmaps.sample(32.0, 32.0, 8.0).unwrap();
+    /// assert!((120..=135).contains(&pixel.r));
+    /// ```
+    pub fn sample(&self, x: f32, y: f32, scale: f32) -> Option<AlphaPixel<T>> {
+        self.sample_with(&BilinearSampler, x, y, scale)
+    }
+
+    /// The same as [`Mipmaps::sample`], but each level is sampled with `sampler` rather than bilinearly.
+    pub fn sample_with<S: Sampler + ?Sized>(&self, sampler: &S, x: f32, y: f32, scale: f32) -> Option<AlphaPixel<T>> {
+        let base = self.base();
+        if x < 0.0 || y < 0.0 || x >= base.get_width() as f32 || y >= base.get_height() as f32 {
+            return None
+        }
+
+        let level = scale.max(1.0).log2().min((self.levels.len() - 1) as f32);
+        let lower = level.floor() as usize;
+        let fraction = level - lower as f32;
+
+        let lower_pixel = self.sample_level(sampler, lower, x, y);
+        if fraction == 0.0 || lower + 1 >= self.levels.len() {
+            return Some(lower_pixel)
+        }
+        Some(lower_pixel.lerp(self.sample_level(sampler, lower + 1, x, y), fraction))
+    }
+
+    /// Sample a level at a coordinate of the original image.
+    fn sample_level<S: Sampler + ?Sized>(&self, sampler: &S, level: usize, x: f32, y: f32) -> AlphaPixel<T> {
+        let image = &self.levels[level];
+        // Each level is rounded up, so scale by the actual ratio of sizes rather than a power of 2
+        let scale_x = image.get_width() as f32 / self.base().get_width() as f32;
+        let scale_y = image.get_height() as f32 / self.base().get_height() as f32;
+        let level_x = (x * scale_x).min(image.get_width() as f32 - f32::EPSILON);
+        let level_y = (y * scale_y).min(image.get_height() as f32 - f32::EPSILON);
+        image.sample_with(sampler, level_x, level_y).unwrap_or_default()
+    }
+}
+
+impl<T: PixelChannel> Image<T> {
+    /// Build a chain of halved copies of this image, for sampling it at a smaller size without aliasing.
+    /// 
+    /// Each level is a 2 by 2 box average of the level before it, weighted by alpha. An empty image has a single level.
+    pub fn build_mipmaps(&self) -> Mipmaps<T> {
+        let mut levels = vec![self.clone()];
+        loop {
+            let previous = levels.last().unwrap();
+            if previous.get_width() <= 1 && previous.get_height() <= 1 {
+                break
+            }
+            let next = half_size(previous);
+            levels.push(next);
+        }
+        Mipmaps { levels }
+    }
+}
+
+/// Halve an image, rounding up, by averaging each 2 by 2 block. Blocks on an odd edge average fewer pixels.
+fn half_size<T: PixelChannel>(image: &Image<T>) -> Image<T> {
+    let width = image.get_width().div_ceil(2);
+    let height = image.get_height().div_ceil(2);
+    Image::from_function(width, height, |x, y| {
+        let mut sum = PremultipliedSum::default();
+        let mut count = 0.0;
+        for source_y in 2*y..(2*y + 2).min(image.get_height()) {
+            for source_x in 2*x..(2*x + 2).min(image.get_width()) {
+                sum.add(image.pixel_at(source_x, source_y).unwrap(), 1.0);
+                count += 1.0;
+            }
+        }
+        sum.average(count)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rgba, bitmap::sampler::NearestSampler};
+
+    #[test]
+    fn level_sizes() {
+        let image: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 10, 3);
+        let sizes: Vec<(usize, usize)> = image.build_mipmaps().levels().iter().map(|level| (level.get_width(), level.get_height())).collect();
+        assert_eq!(sizes, vec![(10, 3), (5, 2), (3, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn transparent_pixels_are_weighted() {
+        let image: Image<u8> = Image::from_pixels(vec![AlphaPixel::red(), rgba!(0, 0, 0, 0)], 2).unwrap();
+        let mipmaps = image.build_mipmaps();
+        assert_eq!(mipmaps.levels()[1].pixel_at(0, 0).unwrap(), rgba!(255, 0, 0, 127));
+    }
+
+    #[test]
+    fn sample_levels() {
+        let image: Image<u8> = Image::from_function(4, 4, |x, _| if x < 2 { AlphaPixel::black() } else { AlphaPixel::white() });
+        let mipmaps = image.build_mipmaps();
+
+        // At full size, the original pixel is sampled
+        assert_eq!(mipmaps.sample(0.5, 0.5, 1.0).unwrap(), AlphaPixel::black());
+        // Shrunk to 1 pixel, everything is averaged
+        let shrunk = mipmaps.sample(0.5, 0.5, 4.0).unwrap();
+        assert!((126..=128).contains(&shrunk.r));
+        assert!(mipmaps.sample(4.0, 0.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn sample_with_nearest() {
+        let image: Image<u8> = Image::from_pixels(vec![AlphaPixel::black(), AlphaPixel::white()], 2).unwrap();
+        let mipmaps = image.build_mipmaps();
+
+        // Enlarging keeps the hard edge between the pixels, rather than blending them
+        assert_eq!(mipmaps.sample_with(&NearestSampler, 0.9, 0.5, 0.25).unwrap(), AlphaPixel::black());
+        assert_eq!(mipmaps.sample_with(&NearestSampler, 1.1, 0.5, 0.25).unwrap(), AlphaPixel::white());
+        assert_ne!(mipmaps.sample(1.1, 0.5, 0.25).unwrap(), AlphaPixel::white());
+    }
+}
@@ -1,3 +1,20 @@
 pub mod image;
 pub mod pixel;
-pub mod blending;
\ No newline at end of file
+pub mod blending;
+pub mod palette;
+pub mod mipmap;
+pub mod integral;
+pub mod tiled;
+pub mod mask;
+pub mod fill;
+pub mod morphology;
+pub mod trim;
+pub mod resize;
+pub mod rotate;
+pub mod ramp;
+pub mod gradient_map;
+pub mod white_balance;
+pub mod histogram;
+pub mod sampler;
+pub mod test_pattern;
+pub(crate) mod average;
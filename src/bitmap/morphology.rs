@@ -0,0 +1,160 @@
+//! Dilation and erosion of masks and alpha channels, for building outlines, glows and sticker borders.
+//! 
+//! Every operation uses a disc of `radius` pixels, so corners are rounded rather than square.
+
+use crate::{Image, AlphaPixel, PixelChannel, bitmap::mask::Mask};
+
+/// Get the offsets of every pixel within `radius` of the center.
+fn disc_offsets(radius: usize) -> Vec<(isize, isize)> {
+    let radius = radius as isize;
+    (-radius..=radius)
+        .flat_map(|offset_y| (-radius..=radius).map(move |offset_x| (offset_x, offset_y)))
+        .filter(|(offset_x, offset_y)| offset_x*offset_x + offset_y*offset_y <= radius*radius)
+        .collect()
+}
+
+/// Get the coordinates within the disc around (`x`, `y`) that are inside a `width` by `height` area.
+fn disc_around(offsets: &[(isize, isize)], x: usize, y: usize, width: usize, height: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+    offsets.iter().filter_map(move |(offset_x, offset_y)| {
+        let sample_x = x.checked_add_signed(*offset_x).filter(|sample_x| *sample_x < width)?;
+        let sample_y = y.checked_add_signed(*offset_y).filter(|sample_y| *sample_y < height)?;
+        Some((sample_x, sample_y))
+    })
+}
+
+impl Mask {
+    /// Grow the covered area by `radius` pixels, by taking the largest value within `radius` of each pixel.
+    pub fn dilate(&self, radius: usize) -> Mask {
+        let offsets = disc_offsets(radius);
+        let (width, height) = (self.get_width(), self.get_height());
+        Mask::from_function(width, height, |x, y| {
+            disc_around(&offsets, x, y, width, height)
+                .map(|(sample_x, sample_y)| self.value_at(sample_x, sample_y).unwrap())
+                .fold(0.0, f32::max)
+        })
+    }
+
+    /// Shrink the covered area by `radius` pixels, by taking the smallest value within `radius` of each pixel.
+    /// The area outside of the mask counts as uncovered, so the mask shrinks away from its edges.
+    pub fn erode(&self, radius: usize) -> Mask {
+        let offsets = disc_offsets(radius);
+        let (width, height) = (self.get_width(), self.get_height());
+        let offsets_len = offsets.len();
+        Mask::from_function(width, height, |x, y| {
+            let mut count = 0;
+            let minimum = disc_around(&offsets, x, y, width, height)
+                .inspect(|_| count += 1)
+                .map(|(sample_x, sample_y)| self.value_at(sample_x, sample_y).unwrap())
+                .fold(1.0, f32::min);
+            if count < offsets_len { 0.0 } else { minimum }
+        })
+    }
+
+    /// Erode and then dilate, removing covered details smaller than `radius`.
+    pub fn open(&self, radius: usize) -> Mask {
+        self.erode(radius).dilate(radius)
+    }
+
+    /// Dilate and then erode, filling uncovered gaps smaller than `radius`.
+    pub fn close(&self, radius: usize) -> Mask {
+        self.dilate(radius).erode(radius)
+    }
+
+    /// Get the ring of pixels within `radius` outside of the covered area, for outlines.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Rect, bitmap::mask::Mask};
+    /// 
+    /// let square = Mask::from_rect(10, 10, Rect { x: 4, y: 4, width: 2, height: 2 });
+    /// let outline = square.outline(1);
+    /// assert_eq!(outline.value_at(3, 4), Some(1.0));
+    /// assert_eq!(outline.value_at(4, 4), Some(0.0));
+    /// ```
+    pub fn outline(&self, radius: usize) -> Mask {
+        let dilated = self.dilate(radius);
+        Mask::from_function(self.get_width(), self.get_height(), |x, y| {
+            dilated.value_at(x, y).unwrap() - self.value_at(x, y).unwrap()
+        })
+    }
+}
+
+impl<T: PixelChannel> Image<T> {
+    /// Grow the opaque area of the image by `radius` pixels. Each pixel is replaced by the most opaque pixel within `radius`,
+    /// so the colours at the edge are extended outwards.
+    pub fn dilate(&self, radius: usize) -> Image<T> {
+        let offsets = disc_offsets(radius);
+        let (width, height) = (self.get_width(), self.get_height());
+        Image::from_function(width, height, |x, y| {
+            disc_around(&offsets, x, y, width, height)
+                .map(|(sample_x, sample_y)| self.pixel_at(sample_x, sample_y).unwrap())
+                .reduce(|most_opaque, pixel| if pixel.a > most_opaque.a { pixel } else { most_opaque })
+                .unwrap()
+        })
+    }
+
+    /// Shrink the opaque area of the image by `radius` pixels. Each pixel keeps its colour, with the smallest alpha
+    /// within `radius`. The area outside of the image counts as transparent.
+    pub fn erode(&self, radius: usize) -> Image<T> {
+        let mask = Mask::from_alpha(self).erode(radius);
+        let max = T::MAX_PIXEL_VALUE.into();
+        Image::from_function(self.get_width(), self.get_height(), |x, y| {
+            let pixel = self.pixel_at(x, y).unwrap();
+            let alpha = mask.value_at(x, y).unwrap() * max;
+            AlphaPixel { a: T::from_f32(alpha).unwrap(), ..pixel }
+        })
+    }
+
+    /// Erode and then dilate, removing opaque details smaller than `radius`.
+    pub fn open(&self, radius: usize) -> Image<T> {
+        self.erode(radius).dilate(radius)
+    }
+
+    /// Dilate and then erode, filling transparent gaps smaller than `radius`.
+    pub fn close(&self, radius: usize) -> Image<T> {
+        self.dilate(radius).erode(radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rect, rgba};
+
+    fn dot() -> Mask {
+        Mask::from_rect(9, 9, Rect { x: 4, y: 4, width: 1, height: 1 })
+    }
+
+    #[test]
+    fn dilate_is_round() {
+        let dilated = dot().dilate(2);
+        assert_eq!(dilated.value_at(4, 2), Some(1.0));
+        assert_eq!(dilated.value_at(3, 3), Some(1.0));
+        // The corner of the square around the disc isn't covered
+        assert_eq!(dilated.value_at(2, 2), Some(0.0));
+    }
+
+    #[test]
+    fn open_and_close() {
+        assert!(dot().open(1).get_values().iter().all(|value| *value == 0.0));
+
+        let mut gap = Mask::new(9, 9, 1.0);
+        gap.set_value(4, 4, 0.0).unwrap();
+        assert_eq!(gap.close(1).value_at(4, 4), Some(1.0));
+        // Eroding treats outside of the mask as uncovered
+        assert_eq!(gap.close(1).value_at(0, 4), Some(0.0));
+    }
+
+    #[test]
+    fn image_dilate_extends_colour() {
+        let square = Rect { x: 2, y: 2, width: 3, height: 3 };
+        let image: Image<u8> = Image::from_function(7, 7, |x, y| if square.contains(x, y) { AlphaPixel::red() } else { rgba!(0, 0, 0, 0) });
+        let dilated = image.dilate(1);
+        assert_eq!(dilated.pixel_at(1, 3).unwrap(), AlphaPixel::red());
+        assert_eq!(dilated.pixel_at(0, 3).unwrap().a, 0);
+
+        let eroded = dilated.erode(1);
+        assert_eq!(eroded.pixel_at(3, 3).unwrap(), AlphaPixel::red());
+        assert_eq!(eroded.pixel_at(1, 3).unwrap(), rgba!(255, 0, 0, 0));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{AlphaPixel, PixelChannel, Image};
+
+/// The 4x4 Bayer threshold matrix used for ordered dithering
+const BAYER_MATRIX: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0]
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// How colours that aren't in a [`Palette`] are approximated.
+pub enum Dithering {
+    /// Each pixel is replaced by the nearest palette colour.
+    None,
+    /// The error of each pixel is diffused to its neighbours,
+    /// by [Floyd-Steinberg dithering](https://en.wikipedia.org/wiki/Floyd%E2%80%93Steinberg_dithering).
+    FloydSteinberg,
+    /// Each channel is offset by a 4x4 Bayer matrix before finding the nearest palette colour.
+    /// 
+    /// The value is the size of the offset, as a fraction of the channel range. Around `1.0 / palette_size` works well.
+    Ordered(f32)
+}
+
+#[derive(Debug, Clone)]
+/// A fixed set of colours that an image can be constrained to.
+pub struct Palette<T> {
+    pub colors: Vec<AlphaPixel<T>>,
+    pub dithering: Dithering
+}
+
+impl<T: PixelChannel> Palette<T> {
+    pub fn new(colors: Vec<AlphaPixel<T>>, dithering: Dithering) -> Self {
+        Self { colors, dithering }
+    }
+
+    /// Find the palette colour closest to a float pixel, by euclidean distance of the channels.
+    /// 
+    /// Returns `None` if the palette is empty.
+    fn nearest_float(&self, pixel: [f32; 4]) -> Option<AlphaPixel<T>> {
+        self.colors.iter()
+            .map(|color| {
+                let float_color = color.as_float_pixel();
+                let distance = (float_color.r - pixel[0]).powi(2)
+                    + (float_color.g - pixel[1]).powi(2)
+                    + (float_color.b - pixel[2]).powi(2)
+                    + (float_color.a - pixel[3]).powi(2);
+                (color, distance)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(color, _)| *color)
+    }
+
+    /// Find the palette colour closest to `pixel`.
+    /// 
+    /// Returns `None` if the palette is empty.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{AlphaPixel, rgba};
+    /// use image_template::bitmap::palette::{Palette, Dithering};
+    /// 
+    /// let palette: Palette<u8> = Palette::new(vec![AlphaPixel::black(), AlphaPixel::white()], Dithering::None);
+    /// assert_eq!(palette.nearest(rgba!(200, 180, 220, 255)).unwrap(), AlphaPixel::white());
+    /// ```
+    pub fn nearest(&self, pixel: AlphaPixel<T>) -> Option<AlphaPixel<T>> {
+        let float_pixel = pixel.as_float_pixel();
+        self.nearest_float([float_pixel.r, float_pixel.g, float_pixel.b, float_pixel.a])
+    }
+
+    /// Replace every pixel of `image` with a colour from this palette, using the palette's dithering.
+    /// 
+    /// If the palette is empty, the image is unchanged.
+    pub fn apply(&self, image: &mut Image<T>) {
+        if self.colors.is_empty() {
+            return
+        }
+
+        match self.dithering {
+            Dithering::None => {
+                for pixel in image.get_pixels_mut() {
+                    *pixel = self.nearest(*pixel).unwrap();
+                }
+            },
+            Dithering::Ordered(spread) => {
+                let width = image.get_width();
+                for (i, pixel) in image.get_pixels_mut().iter_mut().enumerate() {
+                    let threshold = BAYER_MATRIX[(i / width) % 4][(i % width) % 4] / 16.0 - 0.5;
+                    let offset = threshold * spread;
+                    let float_pixel = pixel.as_float_pixel();
+                    *pixel = self.nearest_float([
+                        float_pixel.r + offset,
+                        float_pixel.g + offset,
+                        float_pixel.b + offset,
+                        float_pixel.a
+                    ]).unwrap();
+                }
+            },
+            Dithering::FloydSteinberg => self.apply_floyd_steinberg(image)
+        }
+    }
+
+    fn apply_floyd_steinberg(&self, image: &mut Image<T>) {
+        let (width, height) = (image.get_width(), image.get_height());
+        let mut float_pixels: Vec<[f32; 4]> = image.get_pixels().iter()
+            .map(|p| {
+                let float_pixel = p.as_float_pixel();
+                [float_pixel.r, float_pixel.g, float_pixel.b, float_pixel.a]
+            })
+            .collect();
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = y*width + x;
+                let old_pixel = float_pixels[index];
+                let new_pixel = self.nearest_float(old_pixel).unwrap();
+                image.get_pixels_mut()[index] = new_pixel;
+
+                let float_new_pixel = new_pixel.as_float_pixel();
+                let new_channels = [float_new_pixel.r, float_new_pixel.g, float_new_pixel.b, float_new_pixel.a];
+                let error: [f32; 4] = std::array::from_fn(|channel| old_pixel[channel] - new_channels[channel]);
+
+                let mut diffuse = |dx: isize, dy: usize, weight: f32| {
+                    let (Some(nx), ny) = (x.checked_add_signed(dx), y + dy) else { return };
+                    if nx < width && ny < height {
+                        let neighbour = &mut float_pixels[ny*width + nx];
+                        for channel in 0..4 {
+                            neighbour[channel] += error[channel] * weight;
+                        }
+                    }
+                };
+
+                diffuse(1, 0, 7.0 / 16.0);
+                diffuse(-1, 1, 3.0 / 16.0);
+                diffuse(0, 1, 5.0 / 16.0);
+                diffuse(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    fn black_and_white(dithering: Dithering) -> Palette<u8> {
+        Palette::new(vec![AlphaPixel::black(), AlphaPixel::white()], dithering)
+    }
+
+    #[test]
+    fn nearest() {
+        let palette = black_and_white(Dithering::None);
+        assert_eq!(palette.nearest(rgba!(10, 50, 20, 255)).unwrap(), AlphaPixel::black());
+        assert_eq!(palette.nearest(rgba!(200, 150, 220, 255)).unwrap(), AlphaPixel::white());
+        assert!(Palette::<u8>::new(vec![], Dithering::None).nearest(AlphaPixel::red()).is_none());
+    }
+
+    #[test]
+    fn only_palette_colors() {
+        let image = Image::from_function(16, 16, |x, y| rgba!((x*16) as u8, (y*16) as u8, 128, 255));
+
+        for dithering in [Dithering::None, Dithering::FloydSteinberg, Dithering::Ordered(0.5)] {
+            let palette = black_and_white(dithering);
+            let mut quantized = image.clone();
+            palette.apply(&mut quantized);
+            assert!(quantized.get_pixels().iter().all(|p| palette.colors.contains(p)));
+        }
+    }
+
+    #[test]
+    fn dithering_preserves_average() {
+        let mut grey = Image::new_with_fill(rgba!(128u8, 128, 128, 255), 16, 16);
+        black_and_white(Dithering::FloydSteinberg).apply(&mut grey);
+
+        let white_count = grey.get_pixels().iter().filter(|p| **p == AlphaPixel::white()).count();
+        assert!((120..=136).contains(&white_count));
+    }
+}
@@ -75,6 +75,25 @@ macro_rules! rgba {
     };
 }
 
+/// Create an [`AlphaPixel`], or `None` if any channel is out of range, such as a float channel above 1.0.
+/// 
+/// Unlike [`rgba!`], this can be used with channel values from untrusted input.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, try_rgba, rgba};
+/// 
+/// assert_eq!(try_rgba!(1.0, 0.5, 0.0, 1.0), Some(rgba!(1.0, 0.5, 0.0, 1.0)));
+/// assert_eq!(try_rgba!(1.5, 0.5, 0.0, 1.0), None);
+/// assert_eq!(try_rgba!(f32::NAN, 0.5, 0.0, 1.0), None);
+/// ```
+#[macro_export]
+macro_rules! try_rgba {
+    ($r: expr, $g: expr, $b: expr, $a: expr) => {
+        $crate::AlphaPixel::try_new($r, $g, $b, $a)
+    };
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, PartialEq)]
 /// A RGBA pixel, generic over the channel type `T`.
@@ -88,6 +107,19 @@ pub struct AlphaPixel<T> {
 }
 
 impl<T: PixelChannel> AlphaPixel<T> {
+    /// Create a pixel, or `None` if any channel is out of range. This is used by [`try_rgba!`].
+    pub fn try_new(r: T, g: T, b: T, a: T) -> Option<Self> {
+        let pixel = Self { r, g, b, a };
+        pixel.is_valid().then_some(pixel)
+    }
+
+    /// Whether every channel is within `T::MIN_PIXEL_VALUE..=T::MAX_PIXEL_VALUE`.
+    /// 
+    /// This is always true for integer channels. Float channels are invalid if they are outside of 0.0 to 1.0, or NaN.
+    pub fn is_valid(&self) -> bool {
+        [self.r, self.g, self.b, self.a].into_iter().all(T::is_valid_channel_value)
+    }
+
     /// `T: u8` rgba(255, 255, 255, 255)
     pub fn white() -> Self {
         Self { r: T::MAX_PIXEL_VALUE, g: T::MAX_PIXEL_VALUE, b: T::MAX_PIXEL_VALUE, a: T::MAX_PIXEL_VALUE  }
@@ -120,6 +152,39 @@ impl<T: PixelChannel> AlphaPixel<T> {
         T::from_f32(luma*(T::MAX_PIXEL_VALUE.into())).unwrap()
     }
 
+    /// Multiply the alpha channel by `factor`, clamping the result to the valid channel range.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{AlphaPixel, rgba};
+    /// 
+    /// let pixel: AlphaPixel<u8> = rgba!(255, 0, 0, 200);
+    /// assert_eq!(pixel.scale_alpha(0.5), rgba!(255, 0, 0, 100));
+    /// ```
+    pub fn scale_alpha(self, factor: f32) -> Self {
+        let alpha = (self.a.into() * factor)
+            .min(T::MAX_PIXEL_VALUE.into())
+            .max(T::MIN_PIXEL_VALUE.into());
+        Self { a: T::from_f32(alpha).unwrap(), ..self }
+    }
+
+    /// Linearly interpolate each channel between this pixel and `other`, where a `t` of 0.0 is this pixel and 1.0 is `other`.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{AlphaPixel, rgba};
+    /// 
+    /// let pixel: AlphaPixel<u8> = AlphaPixel::black();
+    /// assert_eq!(pixel.lerp(AlphaPixel::white(), 0.5), rgba!(127, 127, 127, 255));
+    /// ```
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let start = self.as_float_pixel();
+        let end = other.as_float_pixel();
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: f32, b: f32| T::from_f32((a + (b - a)*t) * T::MAX_PIXEL_VALUE.into()).unwrap();
+        Self { r: mix(start.r, end.r), g: mix(start.g, end.g), b: mix(start.b, end.b), a: mix(start.a, end.a) }
+    }
+
     fn invert(&mut self) {
         self.r = T::MAX_PIXEL_VALUE - self.r;
         self.g = T::MAX_PIXEL_VALUE - self.g;
@@ -339,8 +404,8 @@ impl<T: PixelChannel> AlphaPixel<T> {
         }
 
         // Inspired by https://docs.rs/bytemuck/1.16.1/bytemuck/allocation/fn.try_cast_vec.html
-        if channel_vec.len() % 4 == 0 {
-            if channel_vec.capacity() % 4 == 0 {
+        if channel_vec.len().is_multiple_of(4) {
+            if channel_vec.capacity().is_multiple_of(4) {
                 let new_length = channel_vec.len() / 4;
                 let new_cap = channel_vec.capacity() / 4;
 
@@ -359,6 +424,24 @@ impl<T: PixelChannel> AlphaPixel<T> {
     }
 }
 
+/// Convert an sRGB encoded value from 0.0 to 1.0 to linear light.
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear light value from 0.0 to 1.0 to sRGB encoding.
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
 /// Safety: `AlphaPixel` has no padding and all T: PixelChannel are NoUninit
 unsafe impl<T: PixelChannel + 'static> NoUninit for AlphaPixel<T> {}
 
@@ -551,6 +634,15 @@ mod tests {
         assert_eq!(rgba!(1000u16, 2000, 0, 100), AlphaPixel { r: 1000, g: 2000, b: 0, a: 100 });
     }
 
+    #[test]
+    fn validation() {
+        assert_eq!(try_rgba!(255u8, 0, 0, 255), Some(AlphaPixel::red()));
+        assert_eq!(try_rgba!(0.0, 1.0, 0.0, 1.0), Some(AlphaPixel::<f32>::green()));
+        assert_eq!(try_rgba!(0.0, 0.0, -0.1, 1.0), None);
+        assert_eq!(try_rgba!(0.0, 0.0, 0.0, f32::INFINITY), None);
+        assert!(!rgba!(0.5, f32::NAN, 0.5, 0.5).is_valid());
+    }
+
     
     #[test]
     #[cfg(feature = "image-crate")]
@@ -0,0 +1,155 @@
+use crate::{AlphaPixel, PixelChannel, BlendSpace, bitmap::pixel::{srgb_to_linear, linear_to_srgb}};
+
+/// How the colours of a [`ColorRamp`] change between two stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RampInterpolation {
+    /// Change at a constant rate
+    #[default]
+    Linear,
+    /// Ease in and out of each stop with a smoothstep curve, so there are no visible bands at the stops
+    Smooth,
+    /// Keep the colour of each stop until the next stop, for posterized gradients and discrete scales
+    Step
+}
+
+/// A gradient of colours between stops, from a position of 0.0 to 1.0.
+/// 
+/// Colours are interpolated between the two nearest stops, by its [`RampInterpolation`] and in its [`BlendSpace`].
+/// Positions before the first stop take the first stop's colour, and positions after the last stop take the last
+/// stop's colour.
+/// 
+/// A ramp is defined once and used by every kind of gradient, such as gradient layers, gradient maps, the gradient
+/// overlay of [`LayerEffects`](crate::layers::effects::LayerEffects) and gradient text strokes.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, BlendSpace, rgba, bitmap::ramp::{ColorRamp, RampInterpolation}};
+/// 
+/// let heat: ColorRamp<u8> = ColorRamp::new(vec![
+///     (0.0, AlphaPixel::black()),
+///     (0.5, AlphaPixel::red()),
+///     (1.0, rgba!(255, 255, 0, 255))
+/// ]);
+/// assert_eq!(heat.color_at(0.5), AlphaPixel::red());
+/// assert_eq!(heat.color_at(0.75), rgba!(255, 127, 0, 255));
+/// 
+/// // The same stops, held rather than blended
+/// let bands = heat.clone().with_interpolation(RampInterpolation::Step);
+/// assert_eq!(bands.color_at(0.75), AlphaPixel::red());
+/// 
+/// // Mixed in linear light, the middle of the ramp is brighter
+/// let linear = heat.with_space(BlendSpace::Linear);
+/// assert!(linear.color_at(0.25).r > 127);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp<T> {
+    /// Positions and colours, sorted by position
+    stops: Vec<(f32, AlphaPixel<T>)>,
+    pub interpolation: RampInterpolation,
+    /// The colour space that stops are mixed in
+    pub space: BlendSpace
+}
+
+impl<T: PixelChannel> ColorRamp<T> {
+    /// Create a ramp from stops, which are sorted by position.
+    pub fn new(mut stops: Vec<(f32, AlphaPixel<T>)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops, interpolation: RampInterpolation::default(), space: BlendSpace::default() }
+    }
+
+    /// Create a ramp from `start` at 0.0 to `end` at 1.0.
+    pub fn two_color(start: AlphaPixel<T>, end: AlphaPixel<T>) -> Self {
+        Self::new(vec![(0.0, start), (1.0, end)])
+    }
+
+    pub fn with_interpolation(mut self, interpolation: RampInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn with_space(mut self, space: BlendSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    pub fn get_stops(&self) -> &[(f32, AlphaPixel<T>)] {
+        &self.stops
+    }
+
+    /// Add a stop, keeping the stops sorted. A stop at the same position as an existing stop is placed after it,
+    /// for a hard edge between the two colours.
+    pub fn add_stop(&mut self, position: f32, color: AlphaPixel<T>) {
+        let index = self.stops.partition_point(|(stop, _)| *stop <= position);
+        self.stops.insert(index, (position, color));
+    }
+
+    /// Get the colour at a position. If the ramp has no stops, this is transparent.
+    pub fn color_at(&self, position: f32) -> AlphaPixel<T> {
+        let after = self.stops.iter().position(|(stop, _)| *stop > position);
+        let index = match after {
+            None => return self.stops.last().map_or(AlphaPixel::default(), |stop| stop.1),
+            Some(0) => return self.stops[0].1,
+            Some(index) => index
+        };
+
+        let (start_position, start) = self.stops[index - 1];
+        let (end_position, end) = self.stops[index];
+        let t = (position - start_position) / (end_position - start_position);
+        let t = match self.interpolation {
+            RampInterpolation::Linear => t,
+            RampInterpolation::Smooth => t*t*(3.0 - 2.0*t),
+            RampInterpolation::Step => return start
+        };
+
+        match self.space {
+            BlendSpace::Srgb => start.lerp(end, t),
+            BlendSpace::Linear => {
+                let (start, end) = (start.as_float_pixel(), end.as_float_pixel());
+                let mix = |a: f32, b: f32| linear_to_srgb(srgb_to_linear(a) + (srgb_to_linear(b) - srgb_to_linear(a))*t);
+                let alpha = start.a + (end.a - start.a)*t;
+                AlphaPixel { r: mix(start.r, end.r), g: mix(start.g, end.g), b: mix(start.b, end.b), a: alpha }.as_different_channel()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn interpolation() {
+        let ramp: ColorRamp<u8> = ColorRamp::two_color(AlphaPixel::black(), AlphaPixel::white());
+        assert_eq!(ramp.color_at(0.5), rgba!(127, 127, 127, 255));
+
+        // Smooth ramps are slower near the stops, and meet linear ramps halfway
+        let smooth = ramp.clone().with_interpolation(RampInterpolation::Smooth);
+        assert!(smooth.color_at(0.1).r < ramp.color_at(0.1).r);
+        assert!(smooth.color_at(0.9).r > ramp.color_at(0.9).r);
+        assert_eq!(smooth.color_at(0.5), ramp.color_at(0.5));
+
+        let step = ramp.clone().with_interpolation(RampInterpolation::Step);
+        assert_eq!(step.color_at(0.99), AlphaPixel::black());
+        assert_eq!(step.color_at(1.0), AlphaPixel::white());
+
+        // Linear light is brighter halfway, and the stops are exact
+        let linear = ramp.with_space(BlendSpace::Linear);
+        assert_eq!(linear.color_at(0.5).r, 187);
+        assert_eq!(linear.color_at(0.0), AlphaPixel::black());
+        assert_eq!(linear.color_at(1.0), AlphaPixel::white());
+    }
+
+    #[test]
+    fn add_stop() {
+        let mut ramp: ColorRamp<u8> = ColorRamp::two_color(AlphaPixel::red(), AlphaPixel::blue());
+        ramp.add_stop(0.5, AlphaPixel::red());
+        ramp.add_stop(0.5, AlphaPixel::green());
+        let positions: Vec<f32> = ramp.get_stops().iter().map(|stop| stop.0).collect();
+        assert_eq!(positions, [0.0, 0.5, 0.5, 1.0]);
+
+        // The stops at the same position make a hard edge
+        assert_eq!(ramp.color_at(0.49), AlphaPixel::red());
+        assert_eq!(ramp.color_at(0.5), AlphaPixel::green());
+    }
+}
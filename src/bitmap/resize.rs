@@ -0,0 +1,216 @@
+use std::f32::consts::PI;
+use crate::{Image, AlphaPixel, PixelChannel};
+
+/// The filter used to resample an image by [`Image::resize`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ResizeFilter {
+    /// The nearest pixel, for pixel art
+    Nearest,
+    /// The average of the pixels covered, which is fast and smooth when shrinking
+    Box,
+    /// Linear interpolation
+    Triangle,
+    /// A sharper cubic filter
+    CatmullRom,
+    /// A windowed sinc filter, which is the sharpest, but can ring around hard edges
+    #[default]
+    Lanczos3
+}
+
+impl ResizeFilter {
+    /// The distance from the center, in source pixels at a scale of 1, beyond which the filter is 0.
+    fn support(&self) -> f32 {
+        match self {
+            ResizeFilter::Nearest | ResizeFilter::Box => 0.5,
+            ResizeFilter::Triangle => 1.0,
+            ResizeFilter::CatmullRom => 2.0,
+            ResizeFilter::Lanczos3 => 3.0
+        }
+    }
+
+    fn weight(&self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Nearest | ResizeFilter::Box => if x <= 0.5 { 1.0 } else { 0.0 },
+            ResizeFilter::Triangle => (1.0 - x).max(0.0),
+            ResizeFilter::CatmullRom => {
+                if x < 1.0 {
+                    1.5*x*x*x - 2.5*x*x + 1.0
+                } else if x < 2.0 {
+                    -0.5*x*x*x + 2.5*x*x - 4.0*x + 2.0
+                } else {
+                    0.0
+                }
+            },
+            ResizeFilter::Lanczos3 => {
+                if x == 0.0 {
+                    1.0
+                } else if x < 3.0 {
+                    let pi_x = PI * x;
+                    3.0 * pi_x.sin() * (pi_x / 3.0).sin() / (pi_x * pi_x)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// How [`Image::resize`] resamples an image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResizeOptions {
+    pub filter: ResizeFilter,
+    /// Multiply colours by alpha before filtering, and divide after.
+    /// 
+    /// Without this, the colour of transparent pixels (usually black) bleeds into the edges of semi-transparent
+    /// images, such as logos, giving them dark halos. This should only be disabled if the colour of transparent
+    /// pixels is meaningful.
+    pub premultiply_alpha: bool
+}
+
+impl Default for ResizeOptions {
+    fn default() -> Self {
+        Self { filter: ResizeFilter::default(), premultiply_alpha: true }
+    }
+}
+
+/// The source pixels and their weights that make up one destination pixel, on one axis.
+struct Contributions {
+    start: usize,
+    weights: Vec<f32>
+}
+
+/// Calculate the contributions to each destination pixel when resampling `source_size` pixels to `destination_size`.
+fn contributions(filter: ResizeFilter, source_size: usize, destination_size: usize) -> Vec<Contributions> {
+    let scale = source_size as f32 / destination_size as f32;
+    // When shrinking, the filter is stretched to cover every source pixel
+    let filter_scale = scale.max(1.0);
+    let support = filter.support() * filter_scale;
+
+    (0..destination_size)
+        .map(|destination| {
+            let center = (destination as f32 + 0.5) * scale;
+            if filter == ResizeFilter::Nearest {
+                let nearest = (center as usize).min(source_size - 1);
+                return Contributions { start: nearest, weights: vec![1.0] }
+            }
+
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(source_size);
+            let mut weights: Vec<f32> = (start..end)
+                .map(|source| filter.weight((source as f32 + 0.5 - center) / filter_scale))
+                .collect();
+
+            let total: f32 = weights.iter().sum();
+            if total != 0.0 {
+                weights.iter_mut().for_each(|weight| *weight /= total);
+            }
+            Contributions { start, weights }
+        })
+        .collect()
+}
+
+impl<T: PixelChannel> Image<T> {
+    /// Resample the image to a new size.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, rgba, bitmap::resize::ResizeOptions};
+    /// 
+    /// // A red logo with a transparent black border
+    /// let logo: Image<u8> = Image::from_function(8, 8, |x, y| if (2..6).contains(&x) && (2..6).contains(&y) { AlphaPixel::red() } else { rgba!(0, 0, 0, 0) });
+    /// let small = logo.resize(4, 4, ResizeOptions::default());
+    /// 
+    /// // The edge is semi-transparent, but still red rather than darkened
+    /// let edge = small.pixel_at(0, 1).unwrap();
+    /// assert!(edge.a > 0 && edge.a < 255);
+    /// assert_eq!((edge.r, edge.g, edge.b), (255, 0, 0));
+    /// ```
+    pub fn resize(&self, width: usize, height: usize, options: ResizeOptions) -> Image<T> {
+        if width == 0 || height == 0 || self.get_width() == 0 || self.get_height() == 0 {
+            return Image::new_with_fill(AlphaPixel::default(), width, height)
+        }
+
+        let premultiply = options.premultiply_alpha;
+        let float_pixels: Vec<[f32; 4]> = self.get_pixels().iter()
+            .map(|pixel| {
+                let pixel = pixel.as_float_pixel();
+                let factor = if premultiply { pixel.a } else { 1.0 };
+                [pixel.r * factor, pixel.g * factor, pixel.b * factor, pixel.a]
+            })
+            .collect();
+
+        // Resample horizontally, then vertically
+        let horizontal = contributions(options.filter, self.get_width(), width);
+        let mut intermediate = Vec::with_capacity(width * self.get_height());
+        for y in 0..self.get_height() {
+            let row = &float_pixels[y*self.get_width()..(y + 1)*self.get_width()];
+            for contribution in &horizontal {
+                intermediate.push(weighted_sum(contribution, |source| row[source]));
+            }
+        }
+
+        let vertical = contributions(options.filter, self.get_height(), height);
+        let max = T::MAX_PIXEL_VALUE.into();
+        // Integer channels are rounded, so that filter weights which sum to just under 1 don't darken the image
+        let to_channel = |value: f32| T::from_f32(if max > 1.0 { (value * max).round() } else { value * max }).unwrap();
+        Image::from_function(width, height, |x, y| {
+            let [r, g, b, a] = weighted_sum(&vertical[y], |source| intermediate[source*width + x]);
+            // Sharp filters can overshoot, so clamp before converting
+            let a = a.clamp(0.0, 1.0);
+            let unpremultiply = |channel: f32| {
+                let channel = if !premultiply { channel } else if a == 0.0 { 0.0 } else { channel / a };
+                to_channel(channel.clamp(0.0, 1.0))
+            };
+            AlphaPixel { r: unpremultiply(r), g: unpremultiply(g), b: unpremultiply(b), a: to_channel(a) }
+        })
+    }
+}
+
+fn weighted_sum<F: Fn(usize) -> [f32; 4]>(contribution: &Contributions, source: F) -> [f32; 4] {
+    let mut sum = [0.0; 4];
+    for (offset, weight) in contribution.weights.iter().enumerate() {
+        let pixel = source(contribution.start + offset);
+        for channel in 0..4 {
+            sum[channel] += pixel[channel] * weight;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    fn half_transparent() -> Image<u8> {
+        Image::from_function(4, 1, |x, _| if x < 2 { AlphaPixel::white() } else { rgba!(0, 0, 0, 0) })
+    }
+
+    #[test]
+    fn premultiplied_has_no_halo() {
+        let options = ResizeOptions { filter: ResizeFilter::Box, premultiply_alpha: true };
+        let resized = half_transparent().resize(1, 1, options);
+        assert_eq!(resized.pixel_at(0, 0).unwrap(), rgba!(255, 255, 255, 128));
+    }
+
+    #[test]
+    fn straight_alpha_has_halo() {
+        let options = ResizeOptions { filter: ResizeFilter::Box, premultiply_alpha: false };
+        let resized = half_transparent().resize(1, 1, options);
+        assert_eq!(resized.pixel_at(0, 0).unwrap(), rgba!(128, 128, 128, 128));
+    }
+
+    #[test]
+    fn sizes_and_filters() {
+        let image: Image<u8> = Image::from_function(5, 3, |x, y| rgba!((x*50) as u8, (y*100) as u8, 0, 255));
+        for filter in [ResizeFilter::Nearest, ResizeFilter::Box, ResizeFilter::Triangle, ResizeFilter::CatmullRom, ResizeFilter::Lanczos3] {
+            let resized = image.resize(12, 2, ResizeOptions { filter, ..Default::default() });
+            assert_eq!((resized.get_width(), resized.get_height()), (12, 2));
+            assert!(resized.get_pixels().iter().all(|pixel| pixel.a == 255));
+        }
+
+        let nearest = image.resize(10, 6, ResizeOptions { filter: ResizeFilter::Nearest, ..Default::default() });
+        assert_eq!(nearest.pixel_at(3, 5).unwrap(), image.pixel_at(1, 2).unwrap());
+    }
+}
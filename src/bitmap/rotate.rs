@@ -0,0 +1,108 @@
+use crate::{Image, PixelChannel, bitmap::sampler::{Sampler, sample}};
+
+impl<T: PixelChannel> Image<T> {
+    /// Rotate the image clockwise by `angle` degrees around its center, resampling it with `sampler`.
+    /// 
+    /// If `expand` is true, the new image is large enough to hold the whole rotated image. Otherwise it is the
+    /// same size as this image, and the corners which are rotated out of it are cut off. Pixels which the rotated
+    /// image doesn't cover are transparent, and its edges fade out with smooth samplers. If `angle` is infinite or
+    /// NaN, the image is returned unchanged.
+    /// 
+    /// This is for preparing images, such as assets, before they are used. To rotate a layer on a canvas, use
+    /// a [`MatrixTransform`](crate::filters::transform::MatrixTransform).
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, bitmap::sampler::{NearestSampler, BilinearSampler}};
+    /// 
+    /// let image: Image<u8> = Image::from_function(30, 10, |x, _y| if x < 15 { AlphaPixel::red() } else { AlphaPixel::blue() });
+    /// 
+    /// // A quarter turn swaps the width and height, and moves the left of the image to the top
+    /// let turned = image.rotate(90.0, &NearestSampler, true);
+    /// assert_eq!((turned.get_width(), turned.get_height()), (10, 30));
+    /// assert_eq!(turned.pixel_at(5, 0).unwrap(), AlphaPixel::red());
+    /// 
+    /// let tilted = image.rotate(30.0, &BilinearSampler, false);
+    /// assert_eq!((tilted.get_width(), tilted.get_height()), (30, 10));
+    /// assert_eq!(tilted.pixel_at(0, 9).unwrap().a, 0);
+    /// ```
+    pub fn rotate<S: Sampler + ?Sized>(&self, angle: f32, sampler: &S, expand: bool) -> Image<T> {
+        if !angle.is_finite() {
+            return self.clone()
+        }
+
+        let (width, height) = (self.get_width() as f32, self.get_height() as f32);
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let (new_width, new_height) = match expand {
+            // Allow for rounding errors, so that quarter turns don't gain a row or column
+            true => (
+                ((width*cos.abs() + height*sin.abs()) - 1e-3).ceil().max(0.0) as usize,
+                ((width*sin.abs() + height*cos.abs()) - 1e-3).ceil().max(0.0) as usize
+            ),
+            false => (self.get_width(), self.get_height())
+        };
+
+        let center = (width / 2.0, height / 2.0);
+        let new_center = (new_width as f32 / 2.0, new_height as f32 / 2.0);
+        Image::from_function(new_width, new_height, |x, y| {
+            // Rotate the center of the new pixel back onto this image
+            let (offset_x, offset_y) = (x as f32 + 0.5 - new_center.0, y as f32 + 0.5 - new_center.1);
+            let source_x = offset_x*cos + offset_y*sin + center.0;
+            let source_y = -offset_x*sin + offset_y*cos + center.1;
+            sample(sampler, source_x, source_y, |sample_x, sample_y| {
+                if sample_x < 0 || sample_y < 0 {
+                    return None
+                }
+                self.pixel_at(sample_x as usize, sample_y as usize)
+            }).unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AlphaPixel, rgba, bitmap::sampler::{NearestSampler, BilinearSampler}};
+
+    #[test]
+    fn quarter_turns() {
+        let image: Image<u8> = Image::from_function(3, 2, |x, y| rgba!((x * 50) as u8, (y * 100) as u8, 0, 255));
+
+        let clockwise = image.rotate(90.0, &NearestSampler, true);
+        assert_eq!((clockwise.get_width(), clockwise.get_height()), (2, 3));
+        // The bottom left corner moves to the top left
+        assert_eq!(clockwise.pixel_at(0, 0).unwrap(), image.pixel_at(0, 1).unwrap());
+        assert_eq!(clockwise.pixel_at(1, 2).unwrap(), image.pixel_at(2, 0).unwrap());
+
+        let half = image.rotate(180.0, &NearestSampler, true);
+        assert_eq!((half.get_width(), half.get_height()), (3, 2));
+        assert_eq!(half.pixel_at(0, 0).unwrap(), image.pixel_at(2, 1).unwrap());
+
+        assert_eq!(image.rotate(360.0, &NearestSampler, false), image);
+    }
+
+    #[test]
+    fn non_finite_angles() {
+        let image: Image<u8> = Image::from_function(3, 2, |x, y| rgba!((x * 50) as u8, (y * 100) as u8, 0, 255));
+        for angle in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN] {
+            assert_eq!(image.rotate(angle, &BilinearSampler, true), image);
+        }
+    }
+
+    #[test]
+    fn expand() {
+        let image: Image<u8> = Image::new_with_fill(AlphaPixel::white(), 10, 10);
+
+        let expanded = image.rotate(45.0, &BilinearSampler, true);
+        assert_eq!((expanded.get_width(), expanded.get_height()), (15, 15));
+        assert_eq!(expanded.pixel_at(7, 7).unwrap(), AlphaPixel::white());
+        assert_eq!(expanded.pixel_at(0, 0).unwrap().a, 0);
+        // The corners of the square touch the edges of the expanded image
+        assert!(expanded.pixel_at(7, 0).unwrap().a > 0);
+
+        let cut = image.rotate(45.0, &BilinearSampler, false);
+        assert_eq!((cut.get_width(), cut.get_height()), (10, 10));
+        assert_eq!(cut.pixel_at(0, 0).unwrap().a, 0);
+        assert_eq!(cut.pixel_at(5, 0).unwrap(), AlphaPixel::white());
+    }
+}
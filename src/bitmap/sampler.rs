@@ -0,0 +1,183 @@
+use crate::{AlphaPixel, PixelChannel};
+use super::average::PremultipliedSum;
+
+/// A way of getting a pixel at a non-integer coordinate, from the pixels around it.
+/// 
+/// A sampler only chooses which pixels contribute to a coordinate and by how much. The pixels are combined by
+/// [`sample`], so a sampler can be used with any source of pixels, such as an [`Image`](crate::Image) or a layer.
+/// 
+/// Pixel centers are at half-integer coordinates, so `(0.5, 0.5)` is exactly the top left pixel.
+/// 
+/// # Example
+/// A custom sampler which averages a 2x2 block of pixels, whatever the coordinate is within it.
+/// ```
+/// use image_template::{AlphaPixel, rgba};
+/// use image_template::bitmap::sampler::{Sampler, sample};
+/// 
+/// struct BlockSampler;
+/// 
+/// impl Sampler for BlockSampler {
+///     fn weights(&self, x: f32, y: f32, weight: &mut dyn FnMut(isize, isize, f32)) {
+///         let (block_x, block_y) = ((x / 2.0).floor() as isize * 2, (y / 2.0).floor() as isize * 2);
+///         for (offset_x, offset_y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+///             weight(block_x + offset_x, block_y + offset_y, 0.25);
+///         }
+///     }
+/// }
+/// 
+/// let stripes = |x: isize, _y: isize| Some(if x % 2 == 0 { AlphaPixel::<u8>::black() } else { AlphaPixel::white() });
+/// assert_eq!(sample(&BlockSampler, 0.5, 0.5, stripes).unwrap(), rgba!(127, 127, 127, 255));
+/// ```
+pub trait Sampler {
+    /// Call `weight` with each pixel coordinate that contributes to the sample at (`x`, `y`), and how much it contributes.
+    /// 
+    /// The weights should add up to 1.0.
+    fn weights(&self, x: f32, y: f32, weight: &mut dyn FnMut(isize, isize, f32));
+}
+
+/// Use the pixel that contains the coordinate. This is the fastest sampler, and keeps pixel art sharp.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NearestSampler;
+
+impl Sampler for NearestSampler {
+    fn weights(&self, x: f32, y: f32, weight: &mut dyn FnMut(isize, isize, f32)) {
+        weight(x.floor() as isize, y.floor() as isize, 1.0);
+    }
+}
+
+/// Linearly interpolate between the 4 nearest pixels.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BilinearSampler;
+
+impl Sampler for BilinearSampler {
+    fn weights(&self, x: f32, y: f32, weight: &mut dyn FnMut(isize, isize, f32)) {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fraction_x, fraction_y) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        weight(x0, y0, (1.0 - fraction_x)*(1.0 - fraction_y));
+        weight(x0 + 1, y0, fraction_x*(1.0 - fraction_y));
+        weight(x0, y0 + 1, (1.0 - fraction_x)*fraction_y);
+        weight(x0 + 1, y0 + 1, fraction_x*fraction_y);
+    }
+}
+
+/// Interpolate between the 16 nearest pixels with a [Catmull-Rom](https://en.wikipedia.org/wiki/Cubic_Hermite_spline#Catmull%E2%80%93Rom_spline)
+/// cubic, which is sharper than bilinear when enlarging.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BicubicSampler;
+
+impl BicubicSampler {
+    /// The Catmull-Rom kernel at a distance from the sample point
+    fn kernel(distance: f32) -> f32 {
+        let distance = distance.abs();
+        if distance < 1.0 {
+            1.5*distance.powi(3) - 2.5*distance.powi(2) + 1.0
+        } else if distance < 2.0 {
+            -0.5*distance.powi(3) + 2.5*distance.powi(2) - 4.0*distance + 2.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Sampler for BicubicSampler {
+    fn weights(&self, x: f32, y: f32, weight: &mut dyn FnMut(isize, isize, f32)) {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fraction_x, fraction_y) = (x - x0, y - y0);
+
+        for offset_y in -1..=2 {
+            let weight_y = Self::kernel(offset_y as f32 - fraction_y);
+            for offset_x in -1..=2 {
+                let weight_x = Self::kernel(offset_x as f32 - fraction_x);
+                weight(x0 as isize + offset_x, y0 as isize + offset_y, weight_x*weight_y);
+            }
+        }
+    }
+}
+
+/// Sample `source` at (`x`, `y`) with `sampler`.
+/// 
+/// `source` gets the pixel at an integer coordinate, or `None` if there isn't a pixel there. Missing pixels are
+/// treated as transparent, so the edges of a source fade out. Returns `None` if none of the contributing pixels exist.
+/// 
+/// Colours are weighted by alpha, so transparent pixels don't darken the result.
+pub fn sample<T: PixelChannel, S: Sampler + ?Sized>(sampler: &S, x: f32, y: f32, source: impl Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+    weighted_average(|weight| sampler.weights(x, y, weight), source)
+}
+
+/// Average the pixels of `source` at the coordinates and weights that `weights` calls its argument with.
+/// 
+/// This is the same as [`sample`], for weights which don't come from a [`Sampler`]. If there is a single
+/// coordinate with a weight of 1.0, its pixel is returned exactly.
+pub(crate) fn weighted_average<T: PixelChannel>(weights: impl FnOnce(&mut dyn FnMut(isize, isize, f32)), source: impl Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+    let mut sum = PremultipliedSum::default();
+    let mut found = false;
+    let mut only_pixel = None;
+    let mut count = 0;
+    weights(&mut |sample_x, sample_y, weight| {
+        if weight == 0.0 {
+            return
+        }
+        count += 1;
+        if let Some(pixel) = source(sample_x, sample_y) {
+            found = true;
+            only_pixel = (weight == 1.0).then_some(pixel);
+            sum.add(pixel, weight as f64);
+        }
+    });
+
+    if !found {
+        return None
+    }
+    if count == 1 && only_pixel.is_some() {
+        // Avoid rounding the pixel by converting it to float and back
+        return only_pixel
+    }
+
+    // The weights add up to 1, so the alpha isn't divided
+    Some(sum.average(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Image, rgba};
+
+    fn total_weight(sampler: &dyn Sampler, x: f32, y: f32) -> f32 {
+        let mut total = 0.0;
+        sampler.weights(x, y, &mut |_, _, weight| total += weight);
+        total
+    }
+
+    #[test]
+    fn weights_sum_to_one() {
+        for sampler in [&NearestSampler as &dyn Sampler, &BilinearSampler, &BicubicSampler] {
+            for (x, y) in [(0.5, 0.5), (3.2, 7.9), (10.0, 0.1)] {
+                assert!((total_weight(sampler, x, y) - 1.0).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_centers_are_exact() {
+        let image: Image<u8> = Image::from_function(5, 5, |x, y| rgba!((x*50) as u8, (y*50) as u8, 0, 255));
+        let source = |x: isize, y: isize| image.pixel_at(usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+
+        for sampler in [&NearestSampler as &dyn Sampler, &BilinearSampler, &BicubicSampler] {
+            assert_eq!(sample(sampler, 2.5, 3.5, source).unwrap(), rgba!(100, 150, 0, 255));
+        }
+    }
+
+    #[test]
+    fn outside_source() {
+        let source = |x: isize, y: isize| if x == 0 && y == 0 { Some(AlphaPixel::<u8>::red()) } else { None };
+        assert!(sample(&NearestSampler, 5.5, 5.5, source).is_none());
+
+        // Half way between the pixel and nothing is half transparent
+        let edge = sample(&BilinearSampler, 1.0, 0.5, source).unwrap();
+        assert_eq!((edge.r, edge.a), (255, 127));
+    }
+}
@@ -0,0 +1,173 @@
+//! Generated images for checking that a pipeline reproduces colours, gradients and detail correctly,
+//! such as after resizing, saving in a different format, or being shown on another display.
+
+use crate::{Image, AlphaPixel, PixelChannel, bitmap::pixel::srgb_to_linear};
+
+/// The 4x4 ordered dithering thresholds used by [`Image::gamma_ramp`], from 0 to 15.
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5]
+];
+
+impl<T: PixelChannel> Image<T> {
+    /// Create SMPTE colour bars, the standard test pattern for video.
+    /// 
+    /// The top two thirds are 7 bars at 75% intensity: grey, yellow, cyan, green, magenta, red and blue.
+    /// Below them is a thin strip of the same bars in reverse order alternating with black, and the bottom quarter
+    /// has the -I, white and +Q bars, and PLUGE bars just below, at and just above black for setting brightness.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, rgba};
+    /// 
+    /// let bars: Image<u8> = Image::smpte_bars(700, 480);
+    /// assert_eq!(bars.pixel_at(50, 0).unwrap(), rgba!(191, 191, 191, 255));
+    /// assert_eq!(bars.pixel_at(650, 0).unwrap(), rgba!(0, 0, 191, 255));
+    /// assert_eq!(bars.pixel_at(200, 470).unwrap(), AlphaPixel::white());
+    /// ```
+    pub fn smpte_bars(width: usize, height: usize) -> Self {
+        const TOP: [(u8, u8, u8); 7] = [(191, 191, 191), (191, 191, 0), (0, 191, 191), (0, 191, 0), (191, 0, 191), (191, 0, 0), (0, 0, 191)];
+        const MIDDLE: [(u8, u8, u8); 7] = [(0, 0, 191), (19, 19, 19), (191, 0, 191), (19, 19, 19), (0, 191, 191), (19, 19, 19), (191, 191, 191)];
+        // The bottom row, with the right edge of each bar as a fraction of the width
+        const BOTTOM: [(f32, (u8, u8, u8)); 7] = [
+            (5.0 / 28.0, (0, 33, 76)),
+            (10.0 / 28.0, (255, 255, 255)),
+            (15.0 / 28.0, (50, 0, 106)),
+            (20.0 / 28.0, (19, 19, 19)),
+            (20.0 / 28.0 + 1.0 / 21.0, (9, 9, 9)),
+            (20.0 / 28.0 + 2.0 / 21.0, (19, 19, 19)),
+            (20.0 / 28.0 + 3.0 / 21.0, (29, 29, 29))
+        ];
+
+        Self::from_function(width, height, |x, y| {
+            let (x, y) = ((x as f32 + 0.5) / width as f32, (y as f32 + 0.5) / height as f32);
+            let bar = ((x * 7.0) as usize).min(6);
+            let (r, g, b) = if y < 2.0 / 3.0 {
+                TOP[bar]
+            } else if y < 0.75 {
+                MIDDLE[bar]
+            } else {
+                BOTTOM.iter().find(|(right, _)| x < *right).map_or((19, 19, 19), |(_, color)| *color)
+            };
+            AlphaPixel { r, g, b, a: 255 }.as_different_channel()
+        })
+    }
+
+    /// Create a horizontal gradient from `start` on the left to `end` on the right, such as to check for
+    /// banding after an image is saved with fewer bits per channel.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel};
+    /// 
+    /// let ramp: Image<u8> = Image::gradient_ramp(256, 16, AlphaPixel::black(), AlphaPixel::white());
+    /// assert_eq!(ramp.pixel_at(0, 0).unwrap(), AlphaPixel::black());
+    /// assert_eq!(ramp.pixel_at(100, 0).unwrap().r, 100);
+    /// assert_eq!(ramp.pixel_at(255, 15).unwrap(), AlphaPixel::white());
+    /// ```
+    pub fn gradient_ramp(width: usize, height: usize, start: AlphaPixel<T>, end: AlphaPixel<T>) -> Self {
+        // The first and last columns are exactly `start` and `end`
+        let steps = width.saturating_sub(1).max(1) as f32;
+        Self::from_function(width, height, |x, _y| start.lerp(end, x as f32 / steps))
+    }
+
+    /// Create a checkerboard of `first` and `second`, with `cell_size` pixel squares and `first` in the top left.
+    /// 
+    /// Single pixel cells are the finest possible detail, which shows blurring or aliasing when an image is resized.
+    /// A cell size of 0 is treated as 1.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel};
+    /// 
+    /// let checkers: Image<u8> = Image::checkers(8, 8, 1, AlphaPixel::black(), AlphaPixel::white());
+    /// assert_eq!(checkers.pixel_at(0, 0).unwrap(), AlphaPixel::black());
+    /// assert_eq!(checkers.pixel_at(1, 0).unwrap(), AlphaPixel::white());
+    /// ```
+    pub fn checkers(width: usize, height: usize, cell_size: usize, first: AlphaPixel<T>, second: AlphaPixel<T>) -> Self {
+        let cell_size = cell_size.max(1);
+        Self::from_function(width, height, |x, y| {
+            if (x / cell_size + y / cell_size).is_multiple_of(2) { first } else { second }
+        })
+    }
+
+    /// Create `steps` grey levels from black to white, left to right, for checking the gamma of a display or
+    /// of a pipeline which converts between colour spaces.
+    /// 
+    /// The top half of each step is a solid sRGB grey, and the bottom half is a fine dither of black and white
+    /// pixels which emits the same amount of light. When viewed from a distance on a correctly calibrated display,
+    /// or after a resize which is done in linear light, the two halves match.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel};
+    /// 
+    /// let ramp: Image<u8> = Image::gamma_ramp(160, 40, 5);
+    /// // The middle step is 50% sRGB grey, which is about 21% of the light of white,
+    /// // so 3 in every 16 pixels of the dither are white
+    /// assert_eq!(ramp.pixel_at(80, 0).unwrap().r, 127);
+    /// let dithered = (64..96).flat_map(|x| (20..40).map(move |y| (x, y)));
+    /// let white = dithered.filter(|&(x, y)| ramp.pixel_at(x, y).unwrap() == AlphaPixel::white()).count();
+    /// assert_eq!(white, 32 * 20 * 3 / 16);
+    /// ```
+    pub fn gamma_ramp(width: usize, height: usize, steps: usize) -> Self {
+        let steps = steps.max(1);
+        let half = height / 2;
+        Self::from_function(width, height, |x, y| {
+            let step = (x * steps / width.max(1)).min(steps - 1);
+            let level = if steps > 1 { step as f32 / (steps - 1) as f32 } else { 1.0 };
+            if y < half {
+                AlphaPixel::black().lerp(AlphaPixel::white(), level)
+            } else {
+                let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0;
+                if srgb_to_linear(level) > threshold { AlphaPixel::white() } else { AlphaPixel::black() }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn smpte_layout() {
+        let bars: Image<u8> = Image::smpte_bars(280, 120);
+        // Each of the top bars is a seventh of the width
+        assert_eq!(bars.pixel_at(39, 10).unwrap(), rgba!(191, 191, 191, 255));
+        assert_eq!(bars.pixel_at(40, 10).unwrap(), rgba!(191, 191, 0, 255));
+        // The middle strip is reversed
+        assert_eq!(bars.pixel_at(10, 85).unwrap(), rgba!(0, 0, 191, 255));
+        assert_eq!(bars.pixel_at(279, 85).unwrap(), rgba!(191, 191, 191, 255));
+        // -I, then the PLUGE bars below and above black
+        assert_eq!(bars.pixel_at(10, 110).unwrap(), rgba!(0, 33, 76, 255));
+        assert_eq!(bars.pixel_at(205, 110).unwrap(), rgba!(9, 9, 9, 255));
+        assert_eq!(bars.pixel_at(235, 110).unwrap(), rgba!(29, 29, 29, 255));
+        assert_eq!(bars.pixel_at(279, 110).unwrap(), rgba!(19, 19, 19, 255));
+    }
+
+    #[test]
+    fn other_channel_types() {
+        let bars: Image<u16> = Image::smpte_bars(7, 12);
+        assert_eq!(bars.pixel_at(0, 0).unwrap().r, 191 * 257);
+
+        let ramp: Image<f32> = Image::gradient_ramp(3, 1, AlphaPixel::black(), AlphaPixel::white());
+        assert_eq!(ramp.pixel_at(1, 0).unwrap().r, 0.5);
+    }
+
+    #[test]
+    fn gamma_ramp_ends() {
+        let ramp: Image<u8> = Image::gamma_ramp(40, 8, 4);
+        for y in 0..8 {
+            assert_eq!(ramp.pixel_at(0, y).unwrap(), AlphaPixel::black());
+            assert_eq!(ramp.pixel_at(39, y).unwrap(), AlphaPixel::white());
+        }
+
+        // Degenerate sizes don't panic
+        assert_eq!(Image::<u8>::gamma_ramp(0, 0, 0).get_width(), 0);
+        assert_eq!(Image::<u8>::gradient_ramp(1, 1, AlphaPixel::black(), AlphaPixel::white()).pixel_at(0, 0).unwrap(), AlphaPixel::black());
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{Image, AlphaPixel, PixelChannel, Rect, BlendingMethod, Error};
+
+/// The default width and height of the tiles of a [`TiledImage`]
+pub const DEFAULT_TILE_SIZE: usize = 256;
+
+/// An image stored as square tiles, which are only allocated once a pixel in them is changed.
+/// 
+/// Unallocated tiles are filled with a single colour. This allows very large or mostly empty images, such as
+/// print sized posters, to be created without allocating one contiguous buffer for every pixel.
+/// 
+/// Tiles on the right and bottom edges are cut off at the edges of the image.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, bitmap::tiled::TiledImage};
+/// 
+/// // A 100,000 by 100,000 pixel image, which would be 40 GB if it was contiguous
+/// let mut poster: TiledImage<u8> = TiledImage::new(100_000, 100_000, AlphaPixel::white());
+/// poster.set_pixel(50_000, 50_000, AlphaPixel::red()).unwrap();
+/// 
+/// assert_eq!(poster.pixel_at(50_000, 50_000).unwrap(), AlphaPixel::red());
+/// assert_eq!(poster.pixel_at(10, 10).unwrap(), AlphaPixel::white());
+/// assert_eq!(poster.allocated_tile_count(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TiledImage<T: PixelChannel> {
+    width: usize,
+    height: usize,
+    tile_size: usize,
+    /// The colour of unallocated tiles
+    fill: AlphaPixel<T>,
+    /// Tiles in rows, where each tile is `tile_size` by `tile_size` pixels
+    tiles: Vec<Option<Box<[AlphaPixel<T>]>>>
+}
+
+impl<T: PixelChannel> TiledImage<T> {
+    /// Create an image filled with `fill`, with no tiles allocated.
+    pub fn new(width: usize, height: usize, fill: AlphaPixel<T>) -> Self {
+        Self::with_tile_size(width, height, fill, DEFAULT_TILE_SIZE)
+    }
+
+    /// Create an image with a custom tile size. A `tile_size` of 0 is replaced with 1.
+    pub fn with_tile_size(width: usize, height: usize, fill: AlphaPixel<T>, tile_size: usize) -> Self {
+        let tile_size = tile_size.max(1);
+        let tile_count = width.div_ceil(tile_size) * height.div_ceil(tile_size);
+        Self { width, height, tile_size, fill, tiles: vec![None; tile_count] }
+    }
+
+    /// Create a tiled copy of an image. Tiles that are entirely `fill` aren't allocated.
+    pub fn from_image(image: &Image<T>, fill: AlphaPixel<T>, tile_size: usize) -> Self {
+        let mut tiled = Self::with_tile_size(image.get_width(), image.get_height(), fill, tile_size);
+        for tile_rect in tiled.tile_rects().collect::<Vec<_>>() {
+            tiled.set_tile_from(tile_rect, |x, y| image.pixel_at(x, y).unwrap());
+        }
+        tiled
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// Get the colour of unallocated tiles.
+    pub fn get_fill(&self) -> AlphaPixel<T> {
+        self.fill
+    }
+
+    /// Get the number of tiles that have been allocated.
+    pub fn allocated_tile_count(&self) -> usize {
+        self.tiles.iter().filter(|tile| tile.is_some()).count()
+    }
+
+    fn tiles_per_row(&self) -> usize {
+        self.width.div_ceil(self.tile_size)
+    }
+
+    /// Get the pixels covered by each tile, in rows from the top left.
+    pub fn tile_rects(&self) -> impl Iterator<Item = Rect> + '_ {
+        let tiles_per_row = self.tiles_per_row();
+        (0..self.tiles.len()).map(move |index| {
+            let x = (index % tiles_per_row) * self.tile_size;
+            let y = (index / tiles_per_row) * self.tile_size;
+            Rect { x, y, width: self.tile_size.min(self.width - x), height: self.tile_size.min(self.height - y) }
+        })
+    }
+
+    /// Get the index of the tile containing a pixel, and the index of the pixel within the tile.
+    fn locate(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x >= self.width || y >= self.height {
+            return None
+        }
+        let tile = (y / self.tile_size) * self.tiles_per_row() + x / self.tile_size;
+        let within = (y % self.tile_size) * self.tile_size + x % self.tile_size;
+        Some((tile, within))
+    }
+
+    pub fn pixel_at(&self, x: usize, y: usize) -> Option<AlphaPixel<T>> {
+        let (tile, within) = self.locate(x, y)?;
+        Some(match &self.tiles[tile] {
+            Some(pixels) => pixels[within],
+            None => self.fill
+        })
+    }
+
+    /// Get a mutable reference to a pixel, allocating its tile if it isn't already.
+    pub fn pixel_at_mut(&mut self, x: usize, y: usize) -> Option<&mut AlphaPixel<T>> {
+        let (tile, within) = self.locate(x, y)?;
+        let fill = self.fill;
+        let tile_length = self.tile_size * self.tile_size;
+        let pixels = self.tiles[tile].get_or_insert_with(|| vec![fill; tile_length].into_boxed_slice());
+        Some(&mut pixels[within])
+    }
+
+    /// Set a pixel, allocating its tile if it isn't already. Returns [`Error::OutOfBounds`] if the coordinate isn't in the image.
+    pub fn set_pixel(&mut self, x: usize, y: usize, pixel: AlphaPixel<T>) -> Result<(), Error> {
+        *self.pixel_at_mut(x, y).ok_or(Error::OutOfBounds { x, y })? = pixel;
+        Ok(())
+    }
+
+    /// Set every pixel of a tile from a function of the pixel's coordinate in the image.
+    /// If every pixel is the fill colour, the tile is left unallocated.
+    pub(crate) fn set_tile_from<F: FnMut(usize, usize) -> AlphaPixel<T>>(&mut self, tile_rect: Rect, mut function: F) {
+        let Some((tile, _)) = self.locate(tile_rect.x, tile_rect.y) else {
+            return
+        };
+
+        let mut pixels = vec![self.fill; self.tile_size * self.tile_size];
+        let mut all_fill = true;
+        for y in 0..tile_rect.height {
+            for x in 0..tile_rect.width {
+                let pixel = function(tile_rect.x + x, tile_rect.y + y);
+                all_fill &= pixel == self.fill;
+                pixels[y*self.tile_size + x] = pixel;
+            }
+        }
+        self.tiles[tile] = if all_fill { None } else { Some(pixels.into_boxed_slice()) };
+    }
+
+    /// Draw an image on top of this image at a coordinate, cut off at the edges of this image.
+    /// Only the tiles that the image covers are allocated.
+    pub fn draw_subimage(&mut self, image: &Image<T>, x: usize, y: usize, blend: BlendingMethod<T>) {
+        for source_y in 0..image.get_height() {
+            for source_x in 0..image.get_width() {
+                if let Some(pixel) = self.pixel_at_mut(x + source_x, y + source_y) {
+                    *pixel = blend.blend(*pixel, image.pixel_at(source_x, source_y).unwrap());
+                }
+            }
+        }
+    }
+
+    /// Copy a region of this image into a contiguous [`Image`]. The region is cut off at the edges of this image.
+    pub fn region(&self, rect: Rect) -> Image<T> {
+        let rect = rect.intersection(&Rect { x: 0, y: 0, width: self.width, height: self.height }).unwrap_or_default();
+        Image::from_function(rect.width, rect.height, |x, y| self.pixel_at(rect.x + x, rect.y + y).unwrap())
+    }
+
+    /// Copy the whole image into a contiguous [`Image`].
+    pub fn to_image(&self) -> Image<T> {
+        self.region(Rect { x: 0, y: 0, width: self.width, height: self.height })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_tiles() {
+        let mut tiled: TiledImage<u8> = TiledImage::with_tile_size(10, 5, AlphaPixel::black(), 4);
+        let rects: Vec<(usize, usize, usize, usize)> = tiled.tile_rects().map(|rect| (rect.x, rect.y, rect.width, rect.height)).collect();
+        assert_eq!(rects, vec![(0, 0, 4, 4), (4, 0, 4, 4), (8, 0, 2, 4), (0, 4, 4, 1), (4, 4, 4, 1), (8, 4, 2, 1)]);
+
+        tiled.set_pixel(9, 4, AlphaPixel::red()).unwrap();
+        assert_eq!(tiled.pixel_at(9, 4).unwrap(), AlphaPixel::red());
+        assert_eq!(tiled.allocated_tile_count(), 1);
+        assert!(tiled.set_pixel(10, 0, AlphaPixel::red()).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let image: Image<u8> = Image::from_function(9, 9, |x, y| if x < 3 && y < 3 { AlphaPixel::red() } else { AlphaPixel::black() });
+        let tiled = TiledImage::from_image(&image, AlphaPixel::black(), 3);
+
+        // Only the top left tile isn't the fill colour
+        assert_eq!(tiled.allocated_tile_count(), 1);
+        assert_eq!(tiled.to_image().get_pixels(), image.get_pixels());
+    }
+
+    #[test]
+    fn draw_subimage() {
+        let mut tiled: TiledImage<u8> = TiledImage::with_tile_size(100, 100, AlphaPixel::black(), 10);
+        tiled.draw_subimage(&Image::new_with_fill(AlphaPixel::white(), 5, 5), 8, 8, BlendingMethod::Over);
+
+        assert_eq!(tiled.allocated_tile_count(), 4);
+        let region = tiled.region(Rect { x: 7, y: 7, width: 2, height: 2 });
+        assert_eq!(region.get_pixels(), &[AlphaPixel::black(), AlphaPixel::black(), AlphaPixel::black(), AlphaPixel::white()]);
+    }
+}
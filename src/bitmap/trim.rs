@@ -0,0 +1,144 @@
+use crate::{Image, PixelChannel, Rect, bitmap::mask::Mask};
+
+impl<T: PixelChannel> Image<T> {
+    /// Copy a region of the image into a new image. The region is cut off at the edges of the image.
+    /// 
+    /// Returns `None` if the region doesn't overlap the image.
+    pub fn crop(&self, rect: Rect) -> Option<Image<T>> {
+        let rect = rect.intersection(&Rect { x: 0, y: 0, width: self.get_width(), height: self.get_height() })?;
+        Some(Image::from_function(rect.width, rect.height, |x, y| self.pixel_at(rect.x + x, rect.y + y).unwrap()))
+    }
+
+    /// Get the smallest `Rect` containing every pixel with an alpha greater than `alpha_threshold`,
+    /// or `None` if there are no such pixels.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, rgba};
+    /// 
+    /// let image: Image<u8> = Image::from_function(10, 10, |x, y| if (3..5).contains(&x) && y == 6 { AlphaPixel::red() } else { rgba!(0, 0, 0, 0) });
+    /// let content = image.bounding_box_of_content(0).unwrap();
+    /// assert_eq!((content.x, content.y, content.width, content.height), (3, 6, 2, 1));
+    /// ```
+    pub fn bounding_box_of_content(&self, alpha_threshold: T) -> Option<Rect> {
+        // Images with no columns have no rows to iterate
+        if self.get_width() == 0 {
+            return None
+        }
+
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for y in 0..self.get_height() {
+            for (x, pixel) in self.row(y).unwrap().iter().enumerate() {
+                if pixel.a > alpha_threshold {
+                    bounds = Some(match bounds {
+                        Some((left, top, right, _)) => (left.min(x), top, right.max(x), y),
+                        None => (x, y, x, y)
+                    });
+                }
+            }
+        }
+
+        bounds.map(|(left, top, right, bottom)| Rect { x: left, y: top, width: right - left + 1, height: bottom - top + 1 })
+    }
+
+    /// Crop away fully transparent borders. If the whole image is transparent, an empty image is returned.
+    /// 
+    /// The offset of the trimmed image within this image is the position of [`Image::bounding_box_of_content`].
+    pub fn trim(&self) -> Image<T> {
+        self.bounding_box_of_content(T::zero())
+            .and_then(|content| self.crop(content))
+            .unwrap_or_default()
+    }
+
+    /// Find the separate pieces of content in the image, such as the letters of rasterized text.
+    /// 
+    /// Pixels with an alpha greater than `alpha_threshold` which touch, including diagonally, are in the same piece.
+    /// Returns the bounding box and mask of each piece, ordered by their first pixel from the top left, in rows.
+    /// Each mask is the size of its bounding box.
+    pub fn connected_components(&self, alpha_threshold: T) -> Vec<(Rect, Mask)> {
+        let (width, height) = (self.get_width(), self.get_height());
+        let is_content = |x: usize, y: usize| self.pixel_at(x, y).is_some_and(|pixel| pixel.a > alpha_threshold);
+
+        // The index of the component that each pixel belongs to
+        let mut labels: Vec<Option<usize>> = vec![None; width*height];
+        let mut components = vec![];
+        for start_y in 0..height {
+            for start_x in 0..width {
+                if labels[start_y*width + start_x].is_some() || !is_content(start_x, start_y) {
+                    continue
+                }
+
+                let label = components.len();
+                let mut pixels = vec![];
+                let mut stack = vec![(start_x, start_y)];
+                labels[start_y*width + start_x] = Some(label);
+                while let Some((x, y)) = stack.pop() {
+                    pixels.push((x, y));
+                    for neighbour_y in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                        for neighbour_x in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                            let index = neighbour_y*width + neighbour_x;
+                            if labels[index].is_none() && is_content(neighbour_x, neighbour_y) {
+                                labels[index] = Some(label);
+                                stack.push((neighbour_x, neighbour_y));
+                            }
+                        }
+                    }
+                }
+
+                let left = pixels.iter().map(|pixel| pixel.0).min().unwrap();
+                let right = pixels.iter().map(|pixel| pixel.0).max().unwrap();
+                let top = pixels.iter().map(|pixel| pixel.1).min().unwrap();
+                let bottom = pixels.iter().map(|pixel| pixel.1).max().unwrap();
+                let rect = Rect { x: left, y: top, width: right - left + 1, height: bottom - top + 1 };
+                let mask = Mask::from_function(rect.width, rect.height, |x, y| {
+                    if labels[(top + y)*width + left + x] == Some(label) { 1.0 } else { 0.0 }
+                });
+                components.push((rect, mask));
+            }
+        }
+
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AlphaPixel, rgba};
+
+    fn two_pieces() -> Image<u8> {
+        // An L shape at the top left, and a diagonal line at the bottom right
+        let content = [(1, 1), (1, 2), (2, 2), (4, 3), (5, 4)];
+        Image::from_function(7, 6, |x, y| if content.contains(&(x, y)) { AlphaPixel::red() } else { rgba!(0, 0, 0, 50) })
+    }
+
+    #[test]
+    fn trim() {
+        let image = two_pieces();
+        assert!(image.bounding_box_of_content(50).is_some());
+        // Every pixel has some alpha, so nothing is trimmed
+        assert_eq!(image.trim().get_width(), 7);
+
+        let content = image.bounding_box_of_content(100).unwrap();
+        assert_eq!((content.x, content.y, content.width, content.height), (1, 1, 5, 4));
+        assert!(Image::<u8>::new_with_fill(rgba!(0, 0, 0, 0), 5, 5).trim().get_pixels().is_empty());
+    }
+
+    #[test]
+    fn empty_images() {
+        for (width, height) in [(0, 3), (3, 0)] {
+            let image: Image<u8> = Image::new_with_fill(AlphaPixel::red(), width, height);
+            assert!(image.bounding_box_of_content(0).is_none());
+            assert!(image.trim().get_pixels().is_empty());
+            assert!(image.connected_components(0).is_empty());
+        }
+    }
+
+    #[test]
+    fn components() {
+        let components = two_pieces().connected_components(100);
+        let rects: Vec<(usize, usize, usize, usize)> = components.iter().map(|(rect, _)| (rect.x, rect.y, rect.width, rect.height)).collect();
+        assert_eq!(rects, vec![(1, 1, 2, 2), (4, 3, 2, 2)]);
+        assert_eq!(components[0].1.get_values(), &[1.0, 0.0, 1.0, 1.0]);
+    }
+}
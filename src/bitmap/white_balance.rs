@@ -0,0 +1,115 @@
+use crate::{Image, AlphaPixel, PixelChannel};
+
+/// The largest factor that [`Image::auto_white_balance`] multiplies a channel by, so that images which are almost
+/// entirely one colour aren't blown out.
+const MAX_GAIN: f32 = 4.0;
+
+/// How [`Image::auto_white_balance`] estimates the colour cast of an image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WhiteBalance {
+    /// Assume the average colour of the image is grey, and scale each channel so that it is.
+    /// This works well for photos with a variety of colours.
+    GrayWorld,
+    /// Assume the brightest pixels are white, and scale each channel so that the value at `percentile`
+    /// (from 0.0 to 1.0) of that channel becomes the maximum.
+    /// A percentile just below 1.0, such as 0.99, ignores a few specular highlights.
+    WhitePatch { percentile: f32 }
+}
+
+impl<T: PixelChannel> Image<T> {
+    /// Get the factors that [`Image::auto_white_balance`] multiplies the red, green and blue channels by.
+    /// 
+    /// Transparent pixels are ignored. If there are no opaque pixels, or a channel is 0, its factor is 1.0.
+    pub fn white_balance_gains(&self, method: WhiteBalance) -> [f32; 3] {
+        let opaque: Vec<AlphaPixel<f32>> = self.get_pixels().iter()
+            .filter(|pixel| pixel.a > T::zero())
+            .map(|pixel| pixel.as_float_pixel())
+            .collect();
+        if opaque.is_empty() {
+            return [1.0; 3]
+        }
+
+        let gain = |target: f32, value: f32| if value > 0.0 { (target / value).clamp(1.0 / MAX_GAIN, MAX_GAIN) } else { 1.0 };
+        match method {
+            WhiteBalance::GrayWorld => {
+                // Weighted by alpha, so that mostly transparent edges count less
+                let total_alpha: f32 = opaque.iter().map(|pixel| pixel.a).sum();
+                let mean = |channel: fn(&AlphaPixel<f32>) -> f32| opaque.iter().map(|pixel| channel(pixel) * pixel.a).sum::<f32>() / total_alpha;
+                let means = [mean(|pixel| pixel.r), mean(|pixel| pixel.g), mean(|pixel| pixel.b)];
+                let gray = means.iter().sum::<f32>() / 3.0;
+                means.map(|channel_mean| gain(gray, channel_mean))
+            },
+            WhiteBalance::WhitePatch { percentile } => {
+                let index = ((opaque.len() - 1) as f32 * percentile.clamp(0.0, 1.0)).round() as usize;
+                let value_at_percentile = |channel: fn(&AlphaPixel<f32>) -> f32| {
+                    let mut values: Vec<f32> = opaque.iter().map(channel).collect();
+                    values.sort_by(f32::total_cmp);
+                    values[index]
+                };
+                [
+                    gain(1.0, value_at_percentile(|pixel| pixel.r)),
+                    gain(1.0, value_at_percentile(|pixel| pixel.g)),
+                    gain(1.0, value_at_percentile(|pixel| pixel.b))
+                ]
+            }
+        }
+    }
+
+    /// Remove a colour cast from the image, such as the orange of indoor lighting, by scaling each colour channel.
+    /// 
+    /// This is intended for preparing user photos before they are added to a template.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, rgba, bitmap::white_balance::WhiteBalance};
+    /// 
+    /// // A photo of grey and white with a warm cast
+    /// let photo: Image<u8> = Image::from_function(2, 1, |x, _| if x == 0 { rgba!(150, 120, 90, 255) } else { rgba!(250, 200, 150, 255) });
+    /// let balanced = photo.auto_white_balance(WhiteBalance::GrayWorld);
+    /// 
+    /// let grey = balanced.pixel_at(0, 0).unwrap();
+    /// assert!(grey.r.abs_diff(grey.b) <= 1);
+    /// ```
+    pub fn auto_white_balance(&self, method: WhiteBalance) -> Image<T> {
+        let gains = self.white_balance_gains(method);
+        let max = T::MAX_PIXEL_VALUE.into();
+        let scale = |channel: T, gain: f32| T::from_f32((channel.into() * gain).min(max)).unwrap();
+
+        let mut balanced = self.clone();
+        for pixel in balanced.get_pixels_mut() {
+            *pixel = AlphaPixel { r: scale(pixel.r, gains[0]), g: scale(pixel.g, gains[1]), b: scale(pixel.b, gains[2]), a: pixel.a };
+        }
+        balanced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn white_patch() {
+        let image: Image<u8> = Image::from_function(10, 1, |x, _| {
+            let value = (x * 20) as u8;
+            rgba!(value, value, value / 2, 255)
+        });
+        let balanced = image.auto_white_balance(WhiteBalance::WhitePatch { percentile: 1.0 });
+        assert_eq!(balanced.pixel_at(9, 0).unwrap(), rgba!(255, 255, 255, 255));
+    }
+
+    #[test]
+    fn transparent_pixels_are_ignored() {
+        let image: Image<u8> = Image::from_pixels(vec![rgba!(100, 100, 100, 255), rgba!(255, 0, 0, 0)], 2).unwrap();
+        assert_eq!(image.white_balance_gains(WhiteBalance::GrayWorld), [1.0; 3]);
+        assert_eq!(Image::<u8>::new().white_balance_gains(WhiteBalance::GrayWorld), [1.0; 3]);
+    }
+
+    #[test]
+    fn gains_are_limited() {
+        let image: Image<u8> = Image::new_with_fill(rgba!(255, 1, 0, 255), 2, 2);
+        let gains = image.white_balance_gains(WhiteBalance::GrayWorld);
+        assert_eq!(gains[1], MAX_GAIN);
+        assert_eq!(gains[2], 1.0);
+    }
+}
@@ -0,0 +1,289 @@
+//! A cache of flattened canvases, so that identical templates aren't rendered twice.
+
+use std::{collections::{HashMap, VecDeque}, hash::{Hash, Hasher}};
+#[cfg(feature = "image-crate")]
+use std::path::PathBuf;
+use xxhash_rust::xxh3::Xxh3;
+use crate::{Canvas, Image, PixelChannel, Error};
+#[cfg(feature = "image-crate")]
+use crate::ImageFormat;
+
+/// Get the hash that a key is stored under.
+/// 
+/// XXH3 is used instead of the standard library's hasher, which is randomly seeded, so that a key has the same hash
+/// each time the program runs and images saved to disk can be found again. The bytes that are hashed come from the
+/// key's [`Hash`] implementation, which the standard library doesn't guarantee to be stable between Rust versions,
+/// and integers are hashed in the byte order of the platform, with `usize` and `isize` also in its width. Files saved by one build may
+/// not be found by a build for another platform, or with another version of Rust, and are rendered again.
+fn key_hash<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = Xxh3::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The number of lookups which were and weren't found in a [`RenderCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize
+}
+
+/// A cache of rendered images, keyed by anything that can be hashed, such as a template name and the inputs
+/// substituted into it.
+/// 
+/// Up to `capacity` images are kept in memory, and the least recently used image is removed when another is added.
+/// With the `image-crate` feature, images can also be saved to a directory with [`RenderCache::with_directory`],
+/// so that they are kept after the memory cache is full or the program restarts.
+/// 
+/// Keys are compared by a 64 bit hash, so the key should include everything that changes the render.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, AlphaPixel, Rect, Error, cache::RenderCache, layers::shapes::RectangleLayer};
+/// 
+/// fn card(width: usize) -> Result<Canvas<u8>, Error> {
+///     let mut canvas = Canvas::from_dimensions(200, 100);
+///     canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width, height: 100 }));
+///     Ok(canvas)
+/// }
+/// 
+/// let mut cache: RenderCache<u8> = RenderCache::new(100);
+/// let first = cache.get_or_render(&("card", 50), || card(50)).unwrap();
+/// let second = cache.get_or_render(&("card", 50), || card(50)).unwrap();
+/// assert!(first.shares_pixels_with(&second));
+/// assert_eq!(cache.stats().hits, 1);
+/// ```
+pub struct RenderCache<T: PixelChannel> {
+    capacity: usize,
+    images: HashMap<u64, Image<T>>,
+    /// The hashes of the images in memory, from least to most recently used
+    order: VecDeque<u64>,
+    #[cfg(feature = "image-crate")]
+    directory: Option<(PathBuf, ImageFormat)>,
+    stats: CacheStats
+}
+
+impl<T: PixelChannel> RenderCache<T> {
+    /// Create a cache which keeps up to `capacity` images in memory.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            images: HashMap::new(),
+            order: VecDeque::new(),
+            #[cfg(feature = "image-crate")]
+            directory: None,
+            stats: CacheStats::default()
+        }
+    }
+
+    /// Also save images as files in `directory`, in `format`, which are loaded when an image isn't in memory.
+    /// 
+    /// The directory is created when the first image is saved. The format must support the channel type of the image,
+    /// such as [`ImageFormat::Png`] for `u8` and `u16`.
+    /// 
+    /// Files are named by the hash of their key, which is only stable for the same platform and version of Rust.
+    /// To share a directory between other builds, use keys whose hash doesn't depend on them, such as strings, rather
+    /// than integers, or slices and collections, whose lengths are hashed as `usize`.
+    #[cfg(feature = "image-crate")]
+    pub fn with_directory<P: Into<PathBuf>>(mut self, directory: P, format: ImageFormat) -> Self {
+        self.directory = Some((directory.into(), format));
+        self
+    }
+
+    /// Get the cached image for `key`, or flatten the canvas returned by `render` and cache it.
+    /// 
+    /// `render` is only called if the image isn't cached. Errors from `render`, and from saving the image
+    /// to the cache directory, are returned.
+    pub fn get_or_render<K: Hash + ?Sized, F: FnOnce() -> Result<Canvas<T>, Error>>(&mut self, key: &K, render: F) -> Result<Image<T>, Error> {
+        if let Some(image) = self.get(key) {
+            return Ok(image)
+        }
+
+        let image = render()?.try_flatten()?;
+        self.insert(key, image.clone())?;
+        Ok(image)
+    }
+
+    /// Get the cached image for `key`, from memory or the cache directory, or `None` if it isn't cached.
+    /// 
+    /// Files in the cache directory which can't be read are treated as not cached.
+    pub fn get<K: Hash + ?Sized>(&mut self, key: &K) -> Option<Image<T>> {
+        let hash = key_hash(key);
+        let image = match self.images.get(&hash) {
+            Some(image) => {
+                let image = image.clone();
+                self.touch(hash);
+                Some(image)
+            },
+            None => self.load(hash).inspect(|image| self.insert_in_memory(hash, image.clone()))
+        };
+
+        match image {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1
+        }
+        image
+    }
+
+    /// Cache `image` for `key`, replacing any image that is already cached for it.
+    pub fn insert<K: Hash + ?Sized>(&mut self, key: &K, image: Image<T>) -> Result<(), Error> {
+        let hash = key_hash(key);
+        self.save(hash, &image)?;
+        self.insert_in_memory(hash, image);
+        Ok(())
+    }
+
+    /// Remove every image from memory. Files in the cache directory are kept.
+    pub fn clear(&mut self) {
+        self.images.clear();
+        self.order.clear();
+    }
+
+    /// Get the number of images in memory.
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn insert_in_memory(&mut self, hash: u64, image: Image<T>) {
+        if self.capacity == 0 {
+            return
+        }
+
+        if self.images.insert(hash, image).is_some() {
+            self.touch(hash);
+            return
+        }
+        self.order.push_back(hash);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.images.remove(&oldest);
+            }
+        }
+    }
+
+    /// Mark an image in memory as the most recently used.
+    fn touch(&mut self, hash: u64) {
+        if let Some(index) = self.order.iter().position(|&other| other == hash) {
+            self.order.remove(index);
+        }
+        self.order.push_back(hash);
+    }
+
+    #[cfg(feature = "image-crate")]
+    fn path(&self, hash: u64) -> Option<(PathBuf, ImageFormat)> {
+        let (directory, format) = self.directory.as_ref()?;
+        let extension = format.extensions_str().first().copied().unwrap_or("img");
+        Some((directory.join(format!("{hash:016x}.{extension}")), *format))
+    }
+
+    #[cfg(feature = "image-crate")]
+    fn load(&self, hash: u64) -> Option<Image<T>> {
+        let (path, format) = self.path(hash)?;
+        Image::load_from_file(path, format).ok()
+    }
+
+    #[cfg(not(feature = "image-crate"))]
+    fn load(&self, _hash: u64) -> Option<Image<T>> {
+        None
+    }
+
+    #[cfg(feature = "image-crate")]
+    fn save(&self, hash: u64, rendered: &Image<T>) -> Result<(), Error> {
+        if let Some((path, format)) = self.path(hash) {
+            if let Some(directory) = path.parent() {
+                std::fs::create_dir_all(directory).map_err(image::ImageError::IoError)?;
+            }
+            rendered.save(path, format)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "image-crate"))]
+    fn save(&self, _hash: u64, _rendered: &Image<T>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AlphaPixel, Rect, layers::shapes::RectangleLayer, limits::{Limits, LimitError}};
+
+    fn square(fill: AlphaPixel<u8>) -> Result<Canvas<u8>, Error> {
+        let mut canvas = Canvas::from_dimensions(4, 4);
+        canvas.add_layer(RectangleLayer::new(fill, Rect { x: 0, y: 0, width: 4, height: 4 }));
+        Ok(canvas)
+    }
+
+    #[test]
+    fn renders_once() {
+        let mut cache = RenderCache::new(10);
+        let mut renders = 0;
+        for _ in 0..3 {
+            let image = cache.get_or_render("red", || { renders += 1; square(AlphaPixel::red()) }).unwrap();
+            assert_eq!(image.pixel_at(0, 0).unwrap(), AlphaPixel::red());
+        }
+        assert_eq!(renders, 1);
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 1 });
+    }
+
+    #[test]
+    fn limits_are_errors() {
+        let mut cache = RenderCache::new(10);
+        let result = cache.get_or_render("large", || {
+            let mut canvas = square(AlphaPixel::red())?;
+            canvas.limits = Some(Limits { max_canvas_pixels: 4, ..Default::default() });
+            Ok(canvas)
+        });
+        assert!(matches!(result, Err(Error::Limit(LimitError::CanvasTooLarge { .. }))));
+        assert!(cache.get("large").is_none());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = RenderCache::new(2);
+        cache.get_or_render(&1, || square(AlphaPixel::red())).unwrap();
+        cache.get_or_render(&2, || square(AlphaPixel::green())).unwrap();
+        // Using 1 makes 2 the least recently used
+        assert!(cache.get(&1).is_some());
+        cache.get_or_render(&3, || square(AlphaPixel::blue())).unwrap();
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&1).is_some());
+        assert!(cache.get(&2).is_none());
+        assert!(cache.get(&3).is_some());
+    }
+
+    #[test]
+    fn render_errors() {
+        let mut cache: RenderCache<u8> = RenderCache::new(2);
+        let result = cache.get_or_render("broken", || Err(Error::OutOfBounds { x: 0, y: 0 }));
+        assert!(result.is_err());
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(feature = "image-crate")]
+    #[test]
+    fn directory() {
+        let directory = std::env::temp_dir().join(format!("render-cache-test-{}", std::process::id()));
+        let mut cache = RenderCache::new(1).with_directory(&directory, ImageFormat::Png);
+        cache.get_or_render("red", || square(AlphaPixel::red())).unwrap();
+        cache.get_or_render("green", || square(AlphaPixel::green())).unwrap();
+
+        // Red isn't in memory, but is loaded from the directory
+        let mut renders = 0;
+        let red = cache.get_or_render("red", || { renders += 1; square(AlphaPixel::blue()) }).unwrap();
+        assert_eq!(renders, 0);
+        assert_eq!(red.pixel_at(0, 0).unwrap(), AlphaPixel::red());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,56 +1,661 @@
+use std::cell::OnceCell;
 use crate::{
     Layer,
+    Filter,
+    FilterContext,
     Image,
     AlphaPixel,
     PixelChannel,
-    BlendingMethod
+    BlendingMethod,
+    Rect,
+    Rng,
+    Error,
+    limits::Limits,
+    layers::image::ImageLayer,
+    bitmap::{palette::Palette, integral::IntegralImage, average::PremultipliedSum, tiled::TiledImage, resize::{ResizeOptions, ResizeFilter}}
 };
 
+/// The maximum number of samples taken along each axis by [`Backdrop::average`], when the backdrop isn't flattened.
+/// Larger areas are sampled sparsely, to keep the cost per pixel bounded.
+const MAX_SAMPLES_PER_AXIS: usize = 9;
+
+/// The pixels that have already been composited beneath a layer, which layers that
+/// [use the backdrop](Layer::uses_backdrop) can sample from.
+pub struct Backdrop<'a, T: PixelChannel> {
+    source: BackdropSource<'a, T>,
+    /// Built the first time that an image backdrop is averaged
+    integral: OnceCell<IntegralImage>
+}
+
+enum BackdropSource<'a, T: PixelChannel> {
+    /// A partially flattened canvas
+    Image(&'a Image<T>),
+    /// A partially flattened canvas, in tiles
+    Tiled(&'a TiledImage<T>),
+    /// The first `layer_count` layers of a canvas, which are composited when a pixel is sampled
+    Canvas { canvas: &'a Canvas<T>, layer_count: usize }
+}
+
+impl<'a, T: PixelChannel> Backdrop<'a, T> {
+    /// Use an image as the backdrop, with the top left of the image at the top left of the canvas.
+    pub fn from_image(image: &'a Image<T>) -> Self {
+        Self { source: BackdropSource::Image(image), integral: OnceCell::new() }
+    }
+
+    /// Use a tiled image as the backdrop, with the top left of the image at the top left of the canvas.
+    pub fn from_tiled(image: &'a TiledImage<T>) -> Self {
+        Self { source: BackdropSource::Tiled(image), integral: OnceCell::new() }
+    }
+
+    /// Get the composited pixel at a canvas location, or `None` if it is outside of the canvas.
+    pub fn pixel_at(&self, x: usize, y: usize) -> Option<AlphaPixel<T>> {
+        match &self.source {
+            BackdropSource::Image(image) => image.pixel_at(x, y),
+            BackdropSource::Tiled(image) => image.pixel_at(x, y),
+            BackdropSource::Canvas { canvas, layer_count } => {
+                if x >= canvas.width || y >= canvas.height {
+                    return None
+                }
+                Some(canvas.combined_pixel_below(*layer_count, x, y))
+            }
+        }
+    }
+
+    /// Get the alpha weighted average of the backdrop in `rect`, which is cut off at the edges of the canvas.
+    /// Returns `None` if no pixels of `rect` are in the canvas.
+    /// 
+    /// When the canvas is flattened, this is exact and takes constant time, using an [`IntegralImage`] of the backdrop.
+    /// When the canvas is flattened into tiles, or single pixels of it are composited, large areas are sampled sparsely.
+    pub fn average(&self, rect: Rect) -> Option<AlphaPixel<T>> {
+        match &self.source {
+            BackdropSource::Image(image) => self.integral.get_or_init(|| IntegralImage::new(*image)).average(rect),
+            BackdropSource::Tiled(_) | BackdropSource::Canvas { .. } => self.sampled_average(rect)
+        }
+    }
+
+    fn sampled_average(&self, rect: Rect) -> Option<AlphaPixel<T>> {
+        let rect = rect.intersection(&Rect { x: 0, y: 0, width: self.get_width(), height: self.get_height() })?;
+        let step_x = (rect.width / MAX_SAMPLES_PER_AXIS).max(1);
+        let step_y = (rect.height / MAX_SAMPLES_PER_AXIS).max(1);
+
+        let mut sum = PremultipliedSum::default();
+        let mut count = 0.0;
+        for y in (rect.y..rect.bottom_y()).step_by(step_y) {
+            for x in (rect.x..rect.right_x()).step_by(step_x) {
+                sum.add(self.pixel_at(x, y).unwrap(), 1.0);
+                count += 1.0;
+            }
+        }
+        Some(sum.average(count))
+    }
+
+    pub fn get_width(&self) -> usize {
+        match &self.source {
+            BackdropSource::Image(image) => image.get_width(),
+            BackdropSource::Tiled(image) => image.get_width(),
+            BackdropSource::Canvas { canvas, .. } => canvas.width
+        }
+    }
+
+    pub fn get_height(&self) -> usize {
+        match &self.source {
+            BackdropSource::Image(image) => image.get_height(),
+            BackdropSource::Tiled(image) => image.get_height(),
+            BackdropSource::Canvas { canvas, .. } => canvas.height
+        }
+    }
+}
+
+/// A way to choose one layer of a [`Canvas`], such as to [render it alone](Canvas::render_layer).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerSelector<'a> {
+    /// The layer at this index in `layers`
+    Index(usize),
+    /// The lowest layer with this [name](Layer::name)
+    Name(&'a str)
+}
+
+impl From<usize> for LayerSelector<'_> {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl<'a> From<&'a str> for LayerSelector<'a> {
+    fn from(name: &'a str) -> Self {
+        Self::Name(name)
+    }
+}
+
+/// How many samples a [`Canvas`] takes of each pixel when it is flattened, to antialias the edges of layers which
+/// aren't antialiased themselves, such as rotated shapes and thin lines.
+/// 
+/// The canvas is drawn larger, as with [`Canvas::flatten_scaled`], and then each block of samples is averaged into one pixel.
+/// This takes 4 or 16 times as long, and as much memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Supersampling {
+    #[default]
+    Off,
+    /// 2x2 samples per pixel
+    X2,
+    /// 4x4 samples per pixel
+    X4
+}
+
+impl Supersampling {
+    /// The number of samples along each axis of a pixel.
+    pub fn factor(&self) -> usize {
+        match self {
+            Supersampling::Off => 1,
+            Supersampling::X2 => 2,
+            Supersampling::X4 => 4
+        }
+    }
+}
+
+/// The colour space that a [`Canvas`] blends its layers in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendSpace {
+    /// Mix the sRGB encoded values, as most image editors and browsers do by default
+    #[default]
+    Srgb,
+    /// Mix in linear light, which is physically correct, and matches tools set to blend in linear light, such as
+    /// renderers and compositors. See [`BlendingMethod::OverLinear`].
+    Linear
+}
+
+/// A layer from [`Layer::scaled`], with the scaled filters of the original layer.
+struct FilteredLayer<T> {
+    layer: Box<dyn Layer<T>>,
+    filters: Vec<Box<dyn Filter<T>>>
+}
+
+impl<T: PixelChannel> Layer<T> for FilteredLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.layer.get_rect()
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.layer.unfiltered_pixel_at_unchecked(x, y)
+    }
+
+    fn name(&self) -> String {
+        self.layer.name()
+    }
+}
+
 pub struct Canvas<T> {
     pub layers: Vec<Box<dyn Layer<T>>>,
     pub background: AlphaPixel<T>,
     pub width: usize,
-    pub height: usize
+    pub height: usize,
+    /// If set, the flattened image is constrained to the colours of this palette.
+    pub palette: Option<Palette<T>>,
+    /// The seed used by [`Canvas::rng`], to seed procedural layers and filters.
+    pub seed: u64,
+    /// If true, layers are composited with [`BlendingMethod::OverFixedPoint`] instead of floating point,
+    /// so that the same canvas flattens to a bit-identical image on every platform. This is useful for golden image tests.
+    /// 
+    /// This only affects compositing layers onto the canvas. Blending within layers, such as text glyphs or effects,
+    /// is unchanged.
+    pub deterministic: bool,
+    /// If set, [`Canvas::try_add_layer`] and [`Canvas::try_flatten`] return an error instead of exceeding these limits.
+    /// 
+    /// Layers can still be added to [`layers`](Canvas::layers) directly, or with [`Canvas::add_layer`], which doesn't check the limits.
+    pub limits: Option<Limits>,
+    /// Antialias the whole canvas by drawing it larger and averaging the samples, when it is flattened with
+    /// [`Canvas::flatten`] or [`Canvas::try_flatten`]. The limits apply to the larger canvas.
+    pub supersampling: Supersampling,
+    /// The colour space that layers are blended over the layers beneath them in. Layers which
+    /// [use the backdrop](Layer::uses_backdrop) blend themselves with it, so aren't affected.
+    /// 
+    /// Linear blending is calculated in floating point, so it isn't bit-identical on every platform, even if
+    /// [`deterministic`](Canvas::deterministic) is set.
+    pub blend_space: BlendSpace
 }
 
 impl<T: PixelChannel> Canvas<T> {
     pub fn from_dimensions(width: usize, height: usize) -> Self {
-        Self { layers: vec![], background: AlphaPixel::default(), width, height }
+        Self { layers: vec![], background: AlphaPixel::default(), width, height, palette: None, seed: 0, deterministic: false, limits: None, supersampling: Supersampling::Off, blend_space: BlendSpace::Srgb }
+    }
+
+    /// Create a random number generator from this canvas' seed.
+    /// 
+    /// Seeds for procedural layers and filters can be taken from this, so that the whole canvas
+    /// is reproducible from a single seed.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, Rect, AlphaPixel, layers::shapes::RectangleLayer, filters::retro::GlitchFilter};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+    /// canvas.seed = 1234;
+    /// let mut rng = canvas.rng();
+    /// 
+    /// let mut rectangle = RectangleLayer::new(AlphaPixel::red(), Rect { x: 10, y: 10, width: 50, height: 50 });
+    /// rectangle.filters.push(Box::new(GlitchFilter::new(rng.next_u64())));
+    /// canvas.add_layer(rectangle);
+    /// ```
+    pub fn rng(&self) -> Rng {
+        Rng::new(self.seed)
+    }
+
+    /// The method used to blend each layer over the layers beneath it.
+    fn blending_method(&self) -> BlendingMethod<'static, T> {
+        match (self.blend_space, self.deterministic) {
+            (BlendSpace::Linear, _) => BlendingMethod::OverLinear,
+            (BlendSpace::Srgb, true) => BlendingMethod::OverFixedPoint,
+            (BlendSpace::Srgb, false) => BlendingMethod::Over
+        }
     }
 
     pub fn add_layer<L: Layer<T> + 'static>(&mut self, layer: L) {
         self.layers.push(Box::new(layer));
     }
 
+    /// Add a layer, or return an error if the canvas already has as many layers as its [`limits`](Canvas::limits) allow.
+    pub fn try_add_layer<L: Layer<T> + 'static>(&mut self, layer: L) -> Result<(), Error> {
+        if let Some(limits) = &self.limits {
+            limits.check_layer_count(self.layers.len() + 1)?;
+        }
+        self.add_layer(layer);
+        Ok(())
+    }
+
+    /// Get the context passed to the filters of a layer drawn on this canvas.
+    fn filter_context(&self, layer: &dyn Layer<T>) -> FilterContext {
+        FilterContext::new(layer.get_rect(), Some((self.width, self.height)))
+    }
+
+    /// Get the composited pixel at a location.
+    /// 
+    /// Layers which [use the backdrop](Layer::uses_backdrop) composite the layers beneath them again for each pixel that
+    /// they sample, so flatten the canvas instead to get many pixels.
     pub fn combined_pixel_at(&self, x: usize, y: usize) -> AlphaPixel<T> {
-        let mut running_pixel = self.background;
-        for layer in &self.layers {
-            let layer_pixel = layer.filtered_pixel_at(x, y);
+        self.combined_pixel_below(self.layers.len(), x, y)
+    }
 
-            if let Some(p) = layer_pixel {
-                running_pixel = BlendingMethod::Over.blend(running_pixel, p);
+    /// Get the layers which draw a visible pixel at a location, from the top down, with their index in `layers`.
+    /// 
+    /// Layers are hit-tested by their filtered pixel, so a layer can be hit outside of its `Rect` if a filter
+    /// moves it, and isn't hit where it is fully transparent. Locations outside of the canvas hit no layers.
+    /// This can be used to select a layer by clicking on it, in an editor.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::{RectangleLayer, CircleLayer}};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 100, height: 100 }));
+    /// canvas.add_layer(CircleLayer::new(AlphaPixel::red(), 50.0, 50.0, 20.0));
+    /// 
+    /// let hits: Vec<usize> = canvas.layers_at(50, 50).into_iter().map(|(index, _layer)| index).collect();
+    /// assert_eq!(hits, vec![1, 0]);
+    /// // The corner of the circle's `Rect` is transparent
+    /// assert_eq!(canvas.layers_at(31, 31).len(), 1);
+    /// ```
+    pub fn layers_at(&self, x: usize, y: usize) -> Vec<(usize, &dyn Layer<T>)> {
+        if x >= self.width || y >= self.height {
+            return vec![]
+        }
+
+        self.layers.iter()
+            .enumerate()
+            .rev()
+            .filter(|(_index, layer)| {
+                layer.filtered_pixel_in_context(x, y, &self.filter_context(layer.as_ref()))
+                    .is_some_and(|pixel| pixel.a > T::zero())
+            })
+            .map(|(index, layer)| (index, layer.as_ref()))
+            .collect()
+    }
+
+    /// Find a layer by its index or name. Returns `None` if there is no such layer.
+    pub fn find_layer<'a>(&self, selector: impl Into<LayerSelector<'a>>) -> Option<&dyn Layer<T>> {
+        match selector.into() {
+            LayerSelector::Index(index) => self.layers.get(index),
+            LayerSelector::Name(name) => self.layers.iter().find(|layer| layer.name() == name)
+        }.map(|layer| layer.as_ref())
+    }
+
+    /// Render one layer alone, on a transparent image the size of the canvas, such as to export it as a separate asset.
+    /// A container layer is rendered with all of its children, so a group of layers can be exported together.
+    /// 
+    /// The layer is drawn at its position on the canvas, with its filters. The canvas' background and palette aren't
+    /// used, and layers which [use the backdrop](Layer::uses_backdrop) are drawn over a transparent backdrop.
+    /// Returns `None` if there is no such layer.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::{RectangleLayer, CircleLayer}};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+    /// canvas.background = AlphaPixel::white();
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 0, y: 0, width: 100, height: 20 }));
+    /// canvas.add_layer(CircleLayer::new(AlphaPixel::red(), 50.0, 50.0, 10.0));
+    /// 
+    /// let circle = canvas.render_layer("CircleLayer").unwrap();
+    /// assert_eq!((circle.get_width(), circle.get_height()), (100, 100));
+    /// assert_eq!(circle.pixel_at(50, 50).unwrap(), AlphaPixel::red());
+    /// assert_eq!(circle.pixel_at(50, 10).unwrap().a, 0);
+    /// 
+    /// let (bar, position) = canvas.render_layer_trimmed(0).unwrap();
+    /// assert_eq!((bar.get_width(), bar.get_height(), position.y), (100, 20, 0));
+    /// ```
+    pub fn render_layer<'a>(&self, selector: impl Into<LayerSelector<'a>>) -> Option<Image<T>> {
+        let layer = self.find_layer(selector)?;
+        if layer.uses_backdrop() {
+            let transparent = Image::new_with_fill(AlphaPixel::default(), self.width, self.height);
+            let backdrop = Backdrop::from_image(&transparent);
+            return Some(Image::from_function(self.width, self.height, |x, y| layer.composite_over_backdrop(x, y, &backdrop)))
+        }
+
+        let context = self.filter_context(layer);
+        Some(Image::from_function(self.width, self.height, |x, y| {
+            layer.filtered_pixel_in_context(x, y, &context).unwrap_or_default()
+        }))
+    }
+
+    /// Render one layer alone, like [`Canvas::render_layer`], cropped to the pixels that it draws.
+    /// 
+    /// Also returns where the cropped image is on the canvas, so that it can be positioned again later, such as in an animation.
+    /// If the layer draws nothing, the image is empty. Returns `None` if there is no such layer.
+    pub fn render_layer_trimmed<'a>(&self, selector: impl Into<LayerSelector<'a>>) -> Option<(Image<T>, Rect)> {
+        let image = self.render_layer(selector)?;
+        let content = image.bounding_box_of_content(T::zero()).unwrap_or_default();
+        Some((image.crop(content).unwrap_or_default(), content))
+    }
+
+    /// Get the pixel at a location, composited from the background and the first `layer_count` layers.
+    fn combined_pixel_below(&self, layer_count: usize, x: usize, y: usize) -> AlphaPixel<T> {
+        self.composite_layers_over(self.background, 0, layer_count, x, y)
+    }
+
+    /// Composite the layers from `first_layer` up to `layer_count` over `running_pixel`, which is the pixel beneath them.
+    /// 
+    /// Layers which use the backdrop sample the canvas beneath them by compositing it again for each pixel,
+    /// so they must only be above `first_layer` if it is 0.
+    fn composite_layers_over(&self, mut running_pixel: AlphaPixel<T>, first_layer: usize, layer_count: usize, x: usize, y: usize) -> AlphaPixel<T> {
+        for (index, layer) in self.layers.iter().enumerate().take(layer_count).skip(first_layer) {
+            if layer.uses_backdrop() {
+                let backdrop = Backdrop { source: BackdropSource::Canvas { canvas: self, layer_count: index }, integral: OnceCell::new() };
+                running_pixel = layer.composite_over_backdrop(x, y, &backdrop);
+            } else if let Some(p) = layer.filtered_pixel_in_context(x, y, &self.filter_context(layer.as_ref())) {
+                strict_assert!(p.is_valid(), "layer {index} drew a pixel at ({x}, {y}) with a channel out of range");
+                running_pixel = self.blending_method().blend(running_pixel, p);
             }
         }
 
         running_pixel
     }
 
+    /// Flatten the canvas into an [`Image`].
+    /// 
+    /// This panics if the canvas is too large to allocate. Use [`Canvas::try_flatten`] if its dimensions come from
+    /// untrusted input.
     pub fn flatten(&self) -> Image<T> {
-        let mut pixels = Vec::with_capacity(self.width*self.height);
-        for row in 0..self.height {
-            for col in 0..self.width {
-                pixels.push(self.combined_pixel_at(col, row));
+        self.try_flatten().unwrap()
+    }
+
+    /// Flatten the canvas into an [`Image`], or return an error if the canvas is too large to allocate, or has more
+    /// pixels or layers than its [`limits`](Canvas::limits) allow.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, Error};
+    /// 
+    /// let canvas: Canvas<u8> = Canvas::from_dimensions(usize::MAX, 2);
+    /// assert!(matches!(canvas.try_flatten(), Err(Error::NewImage(_))));
+    /// ```
+    pub fn try_flatten(&self) -> Result<Image<T>, Error> {
+        if let Some(limits) = &self.limits {
+            limits.check_canvas(self.width, self.height)?;
+            limits.check_layer_count(self.layers.len())?;
+        }
+
+        let factor = self.supersampling.factor();
+        if factor > 1 {
+            let mut supersampled = self.scaled_canvas(factor as f32);
+            supersampled.supersampling = Supersampling::Off;
+            supersampled.palette = None;
+            let options = ResizeOptions { filter: ResizeFilter::Box, premultiply_alpha: true };
+            let mut image = supersampled.try_flatten()?.resize(self.width, self.height, options);
+            if let Some(palette) = &self.palette {
+                palette.apply(&mut image);
+            }
+            return Ok(image)
+        }
+
+        let mut image = if self.layers.iter().any(|layer| layer.uses_backdrop()) {
+            self.flatten_layer_by_layer(self.layers.len())?
+        } else {
+            Image::try_from_function(self.width, self.height, |col, row| self.combined_pixel_at(col, row))?
+        };
+
+        if let Some(palette) = &self.palette {
+            palette.apply(&mut image);
+        }
+        Ok(image)
+    }
+
+    /// Flatten the canvas `factor` times larger, such as at 2x or 3x for high-DPI screens, with the same layout.
+    /// 
+    /// Layers which can be [redrawn larger](Layer::scaled), such as shapes, paths and text, have their positions, sizes
+    /// and font sizes scaled, so they stay sharp. Other layers, and layers with filters which can't be
+    /// [scaled](Filter::scaled), are rendered at the canvas' size and upscaled. Layers which use the backdrop and can't be redrawn are rendered over a transparent backdrop,
+    /// as in [`Canvas::render_layer`].
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::RectangleLayer};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 50);
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 10, y: 10, width: 20, height: 20 }));
+    /// 
+    /// let retina = canvas.flatten_scaled(2.0);
+    /// assert_eq!((retina.get_width(), retina.get_height()), (200, 100));
+    /// assert_eq!(retina.pixel_at(20, 20).unwrap(), AlphaPixel::red());
+    /// assert_eq!(retina.pixel_at(19, 19).unwrap().a, 0);
+    /// ```
+    pub fn flatten_scaled(&self, factor: f32) -> Image<T> {
+        self.scaled_canvas(factor).flatten()
+    }
+
+    /// Copy the canvas `factor` times larger, for [`Canvas::flatten_scaled`].
+    fn scaled_canvas(&self, factor: f32) -> Canvas<T> {
+        let width = (self.width as f32 * factor).round() as usize;
+        let height = (self.height as f32 * factor).round() as usize;
+        let mut canvas = Canvas {
+            layers: Vec::with_capacity(self.layers.len()),
+            background: self.background,
+            width,
+            height,
+            palette: self.palette.clone(),
+            seed: self.seed,
+            deterministic: self.deterministic,
+            limits: self.limits,
+            supersampling: self.supersampling,
+            blend_space: self.blend_space
+        };
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let scaled = layer.scaled(factor).and_then(|scaled| {
+                let filters: Vec<Box<dyn Filter<T>>> = layer.get_filters().iter().map(|filter| filter.scaled(factor)).collect::<Option<_>>()?;
+                Some(if filters.is_empty() { scaled } else { Box::new(FilteredLayer { layer: scaled, filters }) as Box<dyn Layer<T>> })
+            });
+            if let Some(scaled) = scaled {
+                canvas.layers.push(scaled);
+                continue
+            }
+
+            let Some((image, rect)) = self.render_layer_trimmed(index) else { continue };
+            if rect.width == 0 || rect.height == 0 {
+                continue
+            }
+            let scaled_rect = rect.scale(factor);
+            let image = image.resize(scaled_rect.width, scaled_rect.height, ResizeOptions::default());
+            canvas.layers.push(Box::new(ImageLayer::new(image, scaled_rect.x, scaled_rect.y)));
+        }
+
+        canvas
+    }
+
+    /// Flatten the canvas into a [`TiledImage`], so that very large canvases don't need one contiguous buffer.
+    /// 
+    /// Tiles which no layer's `Rect` overlaps are left unallocated, and filled with the background. Layers with filters
+    /// can be moved outside of their `Rect`, so they are composited onto every tile.
+    /// If the canvas has a palette, it is applied to each tile separately, so error diffusion dithering restarts at
+    /// the edge of each tile, and unallocated tiles are filled with the palette colour nearest the background.
+    /// 
+    /// If any layers [use the backdrop](Layer::uses_backdrop), the layers up to the last of them are flattened into
+    /// tiles first, so that the backdrop isn't composited again for every pixel sampled from it.
+    /// 
+    /// Returns an error if a tile can't be allocated.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::RectangleLayer};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(20_000, 20_000);
+    /// canvas.background = AlphaPixel::white();
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 100, y: 100, width: 50, height: 50 }));
+    /// 
+    /// let image = canvas.flatten_tiled().unwrap();
+    /// assert_eq!(image.pixel_at(120, 120).unwrap(), AlphaPixel::red());
+    /// assert_eq!(image.allocated_tile_count(), 1);
+    /// ```
+    pub fn flatten_tiled(&self) -> Result<TiledImage<T>, Error> {
+        let fill = match &self.palette {
+            Some(palette) => palette.nearest(self.background).unwrap_or(self.background),
+            None => self.background
+        };
+        let mut image = TiledImage::new(self.width, self.height, fill);
+        // `None` for layers that could draw anywhere
+        let layer_rects: Vec<Option<Rect>> = self.layers.iter()
+            .map(|layer| if layer.get_filters().is_empty() { Some(layer.get_rect()) } else { None })
+            .collect();
+
+        // Layers up to the last one which uses the backdrop are flattened once, rather than composited again for
+        // every pixel that the backdrop is sampled at, and the layers above them are composited over each tile
+        let base_layers = self.layers.iter().rposition(|layer| layer.uses_backdrop()).map_or(0, |index| index + 1);
+        let base = if base_layers > 0 { Some(self.flatten_tiles_layer_by_layer(base_layers)?) } else { None };
+
+        for tile_rect in image.tile_rects().collect::<Vec<_>>() {
+            let covered = layer_rects.iter().any(|rect| rect.is_none_or(|rect| rect.intersection(&tile_rect).is_some()));
+            if !covered {
+                continue
+            }
+
+            let mut tile = Image::try_from_function(tile_rect.width, tile_rect.height, |x, y| {
+                let (x, y) = (tile_rect.x + x, tile_rect.y + y);
+                match &base {
+                    Some(base) => self.composite_layers_over(base.pixel_at(x, y).unwrap(), base_layers, self.layers.len(), x, y),
+                    None => self.combined_pixel_at(x, y)
+                }
+            })?;
+            if let Some(palette) = &self.palette {
+                palette.apply(&mut tile);
+            }
+            image.set_tile_from(tile_rect, |x, y| tile.pixel_at(x - tile_rect.x, y - tile_rect.y).unwrap());
+        }
+
+        Ok(image)
+    }
+
+    /// Composite the first `layer_count` layers onto a tiled image of the canvas in turn, as with
+    /// [`Canvas::flatten_layer_by_layer`], allocating only the tiles which the layers draw on.
+    fn flatten_tiles_layer_by_layer(&self, layer_count: usize) -> Result<TiledImage<T>, Error> {
+        let mut image = TiledImage::new(self.width, self.height, self.background);
+        let blending_method = self.blending_method();
+        let canvas_rect = Rect { x: 0, y: 0, width: self.width, height: self.height };
+
+        for (layer_index, layer) in self.layers[..layer_count].iter().enumerate() {
+            // Only the layer's `Rect` changes, unless it has filters which could move it
+            let rect = if layer.get_filters().is_empty() { layer.get_rect().intersection(&canvas_rect) } else { Some(canvas_rect) };
+            let Some(rect) = rect else {
+                continue
+            };
+            let parts: Vec<Rect> = image.tile_rects().filter_map(|tile_rect| tile_rect.intersection(&rect)).collect();
+
+            let composited = if layer.uses_backdrop() {
+                // The whole rect is composited before any of it is replaced, as the layer can sample the backdrop around each pixel
+                let backdrop = Backdrop::from_tiled(&image);
+                parts.iter()
+                    .map(|part| Image::try_from_function(part.width, part.height, |x, y| layer.composite_over_backdrop(part.x + x, part.y + y, &backdrop)))
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                let context = self.filter_context(layer.as_ref());
+                parts.iter()
+                    .map(|part| Image::try_from_function(part.width, part.height, |x, y| {
+                        let (x, y) = (part.x + x, part.y + y);
+                        let below = image.pixel_at(x, y).unwrap();
+                        match layer.filtered_pixel_in_context(x, y, &context) {
+                            Some(layer_pixel) => {
+                                strict_assert!(layer_pixel.is_valid(), "layer {layer_index} drew a pixel at ({x}, {y}) with a channel out of range");
+                                blending_method.blend(below, layer_pixel)
+                            },
+                            None => below
+                        }
+                    }))
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+
+            for (part, pixels) in parts.iter().zip(&composited) {
+                image.draw_subimage(pixels, part.x, part.y, BlendingMethod::Replace);
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Composite the first `layer_count` layers onto the whole canvas in turn, so that layers which use the backdrop
+    /// can sample from the partially flattened image beneath them.
+    fn flatten_layer_by_layer(&self, layer_count: usize) -> Result<Image<T>, Error> {
+        let mut image = Image::try_new_with_fill(self.background, self.width, self.height)?;
+        let blending_method = self.blending_method();
+        let canvas_rect = Rect { x: 0, y: 0, width: self.width, height: self.height };
+
+        for (layer_index, layer) in self.layers[..layer_count].iter().enumerate() {
+            if layer.uses_backdrop() {
+                // Only the layer's `Rect` changes, unless it has filters which could move it
+                let rect = if layer.get_filters().is_empty() { layer.get_rect().intersection(&canvas_rect) } else { Some(canvas_rect) };
+                let Some(rect) = rect else {
+                    continue
+                };
+
+                // The whole rect is composited before any of it is replaced, as the layer can sample the backdrop around each pixel
+                let backdrop = Backdrop::from_image(&image);
+                let composited = Image::try_from_function(rect.width, rect.height, |x, y| {
+                    layer.composite_over_backdrop(rect.x + x, rect.y + y, &backdrop)
+                })?;
+                image.draw_subimage(&composited, rect.x, rect.y, BlendingMethod::Replace)?;
+            } else {
+                let width = self.width;
+                let context = self.filter_context(layer.as_ref());
+                for (index, pixel) in image.get_pixels_mut().iter_mut().enumerate() {
+                    if let Some(layer_pixel) = layer.filtered_pixel_in_context(index % width, index / width, &context) {
+                        strict_assert!(
+                            layer_pixel.is_valid(),
+                            "layer {layer_index} drew a pixel at ({}, {}) with a channel out of range", index % width, index / width
+                        );
+                        *pixel = blending_method.blend(*pixel, layer_pixel);
+                    }
+                }
             }
         }
-        // `pixels.len() = self.width*self.height`
-        Image::from_pixels(pixels, self.width).unwrap()
+
+        Ok(image)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{layers::shapes::RectangleLayer, rect::Rect, rgba};
+    use crate::{layers::shapes::RectangleLayer, rect::Rect, rgba, bitmap::palette::Dithering, limits::LimitError};
 
     #[test]
     fn background() {
@@ -96,4 +701,166 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn flatten_deterministic() {
+        let mut canvas = half_colored_canvas();
+        canvas.add_layer(RectangleLayer::new(rgba!(0, 255, 0, 100), Rect { x: 0, y: 0, width: 10, height: 10 }));
+        let float_image = canvas.flatten();
+        canvas.deterministic = true;
+        let fixed_image = canvas.flatten();
+
+        // Fixed point compositing rounds to the nearest value, so it differs from floating point by at most 1
+        for (float_pixel, fixed_pixel) in float_image.get_pixels().iter().zip(fixed_image.get_pixels()) {
+            for (float_channel, fixed_channel) in float_pixel.channels().iter().zip(fixed_pixel.channels()) {
+                assert!(float_channel.abs_diff(*fixed_channel) <= 1);
+            }
+        }
+        assert_eq!(fixed_image.pixel_at(0, 0).unwrap(), rgba!(155, 100, 0, 255));
+    }
+
+    #[test]
+    fn flatten_palette() {
+        let mut canvas = half_colored_canvas();
+        let orange = rgba!(255, 128, 0, 255);
+        canvas.palette = Some(Palette::new(vec![orange, AlphaPixel::black()], Dithering::None));
+        let image = canvas.flatten();
+
+        for row in 0..10 {
+            for col in 0..10 {
+                assert_eq!(image.pixel_at(col, row).unwrap(), if row < 5 { orange } else { AlphaPixel::black() })
+            }
+        }
+    }
+
+    #[test]
+    fn layers_at() {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(20, 20);
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 10, height: 10 }));
+        let mut moved = RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 5, height: 5 });
+        moved.filters.push(Box::new(crate::filters::transform::TranslateFilter { x: 10, y: 10 }));
+        canvas.add_layer(moved);
+        canvas.add_layer(RectangleLayer::new(rgba!(0, 0, 255, 0), Rect { x: 0, y: 0, width: 20, height: 20 }));
+
+        let indices = |x, y| canvas.layers_at(x, y).into_iter().map(|(index, _layer)| index).collect::<Vec<_>>();
+        // Transparent layers aren't hit, and filtered layers are hit where they are drawn
+        assert_eq!(indices(2, 2), vec![0]);
+        assert_eq!(indices(12, 12), vec![1]);
+        assert!(indices(15, 15).is_empty());
+        assert!(indices(25, 5).is_empty());
+    }
+
+    #[test]
+    fn render_layer() {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(20, 20);
+        canvas.background = AlphaPixel::white();
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 5, y: 6, width: 4, height: 3 }));
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 0, y: 0, width: 10, height: 10 }));
+
+        // The layer isn't covered by the layers above it, and the background isn't drawn
+        let red = canvas.render_layer(0).unwrap();
+        assert_eq!(red.pixel_at(6, 7).unwrap(), AlphaPixel::red());
+        assert_eq!(red.pixel_at(0, 0).unwrap().a, 0);
+
+        let (trimmed, position) = canvas.render_layer_trimmed("RectangleLayer").unwrap();
+        assert_eq!((trimmed.get_width(), trimmed.get_height()), (4, 3));
+        assert_eq!((position.x, position.y), (5, 6));
+
+        assert!(canvas.render_layer(2).is_none());
+        assert!(canvas.render_layer("TextLayer").is_none());
+    }
+
+    #[test]
+    fn limits() {
+        let mut canvas = half_colored_canvas();
+        assert!(canvas.try_flatten().is_ok());
+
+        canvas.limits = Some(Limits { max_canvas_pixels: 99, ..Default::default() });
+        assert!(matches!(canvas.try_flatten(), Err(Error::Limit(LimitError::CanvasTooLarge { width: 10, height: 10, max_pixels: 99 }))));
+
+        // Layers added directly aren't checked until the canvas is flattened
+        canvas.limits = Some(Limits { max_layers: 1, ..Default::default() });
+        assert!(matches!(canvas.try_flatten(), Err(Error::Limit(LimitError::TooManyLayers(1)))));
+        assert!(canvas.try_add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 1, height: 1 })).is_err());
+        assert_eq!(canvas.layers.len(), 2);
+    }
+
+    #[test]
+    fn flatten_scaled() {
+        use crate::{Filter, layers::shapes::CircleLayer};
+
+        /// A filter which changes nothing, but can't be scaled
+        struct Unscalable;
+        impl<T> Filter<T> for Unscalable {}
+
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(20, 10);
+        canvas.background = AlphaPixel::white();
+        canvas.add_layer(CircleLayer::new(AlphaPixel::blue(), 5.0, 5.0, 3.0));
+        let mut filtered = RectangleLayer::new(AlphaPixel::red(), Rect { x: 12, y: 2, width: 4, height: 4 });
+        filtered.filters.push(Box::new(Unscalable));
+        canvas.add_layer(filtered);
+
+        let image = canvas.flatten_scaled(3.0);
+        assert_eq!((image.get_width(), image.get_height()), (60, 30));
+
+        // The circle is redrawn with a radius of 9, rather than upscaled
+        assert_eq!(image.pixel_at(15, 7).unwrap(), AlphaPixel::blue());
+        assert_eq!(image.pixel_at(15, 4).unwrap(), AlphaPixel::white());
+
+        // The filtered layer is upscaled into place
+        assert_eq!(image.pixel_at(37, 7).unwrap(), AlphaPixel::red());
+        assert_eq!(image.pixel_at(40, 12).unwrap(), AlphaPixel::red());
+        assert_eq!(image.pixel_at(35, 12).unwrap(), AlphaPixel::white());
+
+        assert_eq!(canvas.flatten_scaled(1.0), canvas.flatten());
+    }
+
+    #[test]
+    fn supersampling() {
+        use crate::filters::transform::MatrixTransform;
+
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(40, 40);
+        canvas.background = AlphaPixel::white();
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 2, y: 2, width: 6, height: 6 }));
+        let mut rotated = RectangleLayer::new(AlphaPixel::black(), Rect { x: 10, y: 10, width: 20, height: 20 });
+        rotated.filters.push(Box::new(MatrixTransform::around_layer_center().rotate(30.0)));
+        canvas.add_layer(rotated);
+
+        let greys = |image: &Image<u8>| image.get_pixels().iter().filter(|pixel| pixel.r > 10 && pixel.r < 245).count();
+        let aliased = canvas.flatten();
+        assert_eq!(greys(&aliased), 0);
+
+        for supersampling in [Supersampling::X2, Supersampling::X4] {
+            canvas.supersampling = supersampling;
+            let image = canvas.flatten();
+            assert_eq!((image.get_width(), image.get_height()), (40, 40));
+            // The edges of the rotated square are antialiased, and the edges of the other square are still sharp
+            assert!(greys(&image) > 40);
+            assert_eq!(image.pixel_at(2, 2).unwrap(), AlphaPixel::blue());
+            assert_eq!(image.pixel_at(1, 2).unwrap(), AlphaPixel::white());
+            assert_eq!(image.pixel_at(20, 20).unwrap(), AlphaPixel::black());
+        }
+
+        // The limits apply to the supersampled canvas
+        canvas.limits = Some(Limits { max_canvas_pixels: 40 * 40 * 4, ..Default::default() });
+        assert!(canvas.try_flatten().is_err());
+    }
+
+    #[test]
+    fn blend_space() {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(4, 4);
+        canvas.background = AlphaPixel::black();
+        canvas.add_layer(RectangleLayer::new(rgba!(255, 255, 255, 128), Rect { x: 0, y: 0, width: 2, height: 4 }));
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 2, y: 0, width: 2, height: 4 }));
+
+        assert_eq!(canvas.flatten().pixel_at(0, 0).unwrap(), rgba!(128, 128, 128, 255));
+        canvas.blend_space = BlendSpace::Linear;
+        let linear = canvas.flatten();
+        assert_eq!(linear.pixel_at(0, 0).unwrap(), rgba!(187, 187, 187, 255));
+        assert_eq!(linear.pixel_at(3, 3).unwrap(), AlphaPixel::red());
+
+        // Linear blending takes priority over fixed point blending
+        canvas.deterministic = true;
+        assert_eq!(canvas.flatten(), linear);
+    }
 }
@@ -0,0 +1,313 @@
+//! Tools for debugging the layout of a [`Canvas`].
+
+use std::fmt::{self, Debug, Display, Formatter, Write};
+use crate::{Canvas, Image, AlphaPixel, PixelChannel, Rect, BlendingMethod, Layer};
+
+/// Colours of the outlines drawn by [`Canvas::debug_render`], chosen by the index of the layer
+const DEBUG_COLORS: [(u8, u8, u8); 6] = [
+    (255, 0, 0),
+    (0, 200, 0),
+    (0, 100, 255),
+    (255, 160, 0),
+    (200, 0, 200),
+    (0, 200, 200)
+];
+
+/// The scale of the built in font used for labels, which is 3 by 5 pixels before scaling
+const LABEL_SCALE: usize = 2;
+
+/// Two layers whose `Rect`s overlap.
+#[derive(Debug, Clone, Copy)]
+pub struct LayerOverlap {
+    /// The index of the lower layer
+    pub below: usize,
+    /// The index of the higher layer
+    pub above: usize,
+    /// The area covered by both layers
+    pub rect: Rect
+}
+
+impl<T: PixelChannel> Canvas<T> {
+    /// Flatten the canvas, then draw the outline of each layer's `Rect` over it, labelled with its index and
+    /// [name](crate::Layer::name).
+    /// 
+    /// Labels are drawn inside the top left corner of each `Rect`. This is useful for finding which layer
+    /// is out of place when a template breaks, such as with longer text than expected.
+    pub fn debug_render(&self) -> Image<T> {
+        let mut image = self.flatten();
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let (r, g, b) = DEBUG_COLORS[index % DEBUG_COLORS.len()];
+            let color: AlphaPixel<T> = AlphaPixel { r, g, b, a: 255u8 }.as_different_channel();
+            let rect = layer.get_rect();
+
+            draw_outline(&mut image, rect, color);
+            let label = format!("#{index} {}", layer.name());
+            draw_label(&mut image, &label, rect.x, rect.y, color);
+        }
+
+        image
+    }
+
+    /// Get every pair of layers whose `Rect`s overlap, ordered by the lower layer and then the higher layer.
+    /// 
+    /// Layers are often expected not to overlap, such as text beside an image, so new overlaps show where
+    /// a layout has broken.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::RectangleLayer};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 50, height: 50 }));
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 60, y: 0, width: 40, height: 40 }));
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::green(), Rect { x: 40, y: 30, width: 30, height: 10 }));
+    /// 
+    /// let overlaps = canvas.overlapping_layers();
+    /// assert_eq!(overlaps.iter().map(|overlap| (overlap.below, overlap.above)).collect::<Vec<_>>(), vec![(0, 2), (1, 2)]);
+    /// ```
+    pub fn overlapping_layers(&self) -> Vec<LayerOverlap> {
+        let rects: Vec<Rect> = self.layers.iter().map(|layer| layer.get_rect()).collect();
+
+        let mut overlaps = vec![];
+        for (below, below_rect) in rects.iter().enumerate() {
+            for (above, above_rect) in rects.iter().enumerate().skip(below + 1) {
+                if let Some(rect) = below_rect.intersection(above_rect) {
+                    overlaps.push(LayerOverlap { below, above, rect });
+                }
+            }
+        }
+        overlaps
+    }
+
+    /// Describe the layers of the canvas as a tree, with one line for each layer giving its index,
+    /// [name](Layer::name), `Rect` and number of filters.
+    /// 
+    /// The [children](Layer::children) of container layers are indented beneath them, and their index is
+    /// appended to the index of their parent. The `Rect` of a child is its own, before the container moves it.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::RectangleLayer};
+    /// use image_template::layers::container::{CrossAlign, flow::{FlowLayer, FlowDirection}};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 100, height: 100 }));
+    /// 
+    /// let mut row = FlowLayer::new(10, 10, FlowDirection::Horizontal, 5, CrossAlign::Start);
+    /// row.push(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 20, height: 20 }));
+    /// canvas.add_layer(row);
+    /// 
+    /// assert_eq!(canvas.describe(), "\
+    /// 100x100 canvas with 2 layers
+    /// #0 RectangleLayer at (0, 0), 100x100
+    /// #1 FlowLayer at (10, 10), 20x20
+    ///   #1.0 RectangleLayer at (0, 0), 20x20
+    /// ");
+    /// ```
+    pub fn describe(&self) -> String {
+        let mut description = format!("{self}\n");
+        for (index, layer) in self.layers.iter().enumerate() {
+            describe_layer(&mut description, layer.as_ref(), &index.to_string(), 0);
+        }
+        description
+    }
+}
+
+/// Write a line describing `layer` to `description`, followed by lines for each of its children.
+fn describe_layer<T: PixelChannel>(description: &mut String, layer: &dyn Layer<T>, index: &str, depth: usize) {
+    // Writing to a `String` can't fail
+    let _ = writeln!(description, "{}#{index} {}", "  ".repeat(depth), LayerSummary(layer));
+    for (child_index, child) in layer.children().into_iter().enumerate() {
+        describe_layer(description, child, &format!("{index}.{child_index}"), depth + 1);
+    }
+}
+
+/// The name, `Rect` and number of filters of a layer, used when formatting a canvas.
+struct LayerSummary<'a, T>(&'a dyn Layer<T>);
+
+impl<T: PixelChannel> Display for LayerSummary<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rect = self.0.get_rect();
+        write!(f, "{} at ({}, {}), {}x{}", self.0.name(), rect.x, rect.y, rect.width, rect.height)?;
+        match self.0.get_filters().len() {
+            0 => Ok(()),
+            1 => write!(f, ", 1 filter"),
+            count => write!(f, ", {count} filters")
+        }
+    }
+}
+
+impl<T: PixelChannel> Debug for LayerSummary<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// Shows the dimensions of the canvas, its background in 8 bit channels, and a summary of each layer.
+impl<T: PixelChannel> Debug for Canvas<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Canvas")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("background", &self.background.as_different_channel::<u8>())
+            .field("layers", &self.layers.iter().map(|layer| LayerSummary(layer.as_ref())).collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: PixelChannel> Display for Canvas<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let plural = if self.layers.len() == 1 { "" } else { "s" };
+        write!(f, "{}x{} canvas with {} layer{plural}", self.width, self.height, self.layers.len())
+    }
+}
+
+/// Draw a 1 pixel outline on the inside edge of `rect`, cut off at the edges of the image.
+fn draw_outline<T: PixelChannel>(image: &mut Image<T>, rect: Rect, color: AlphaPixel<T>) {
+    if rect.width == 0 || rect.height == 0 {
+        return
+    }
+
+    for x in rect.x..rect.right_x() {
+        for y in [rect.y, rect.bottom_y() - 1] {
+            if let Some(pixel) = image.pixel_at_mut(x, y) {
+                *pixel = color;
+            }
+        }
+    }
+    for y in rect.y..rect.bottom_y() {
+        for x in [rect.x, rect.right_x() - 1] {
+            if let Some(pixel) = image.pixel_at_mut(x, y) {
+                *pixel = color;
+            }
+        }
+    }
+}
+
+/// Draw `text` in the built in font, on a translucent black background, with its top left at (`x`, `y`).
+fn draw_label<T: PixelChannel>(image: &mut Image<T>, text: &str, x: usize, y: usize, color: AlphaPixel<T>) {
+    let advance = 4 * LABEL_SCALE;
+    let width = text.chars().count() * advance + LABEL_SCALE;
+    let height = 7 * LABEL_SCALE;
+    let background: AlphaPixel<T> = AlphaPixel { r: 0u8, g: 0, b: 0, a: 160 }.as_different_channel();
+
+    let mut label = Image::new_with_fill(background, width, height);
+    for (index, character) in text.chars().enumerate() {
+        let rows = glyph(character);
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue
+                }
+                let left = LABEL_SCALE + index * advance + column * LABEL_SCALE;
+                let top = LABEL_SCALE + row * LABEL_SCALE;
+                for pixel_y in top..top + LABEL_SCALE {
+                    for pixel_x in left..left + LABEL_SCALE {
+                        *label.pixel_at_mut(pixel_x, pixel_y).unwrap() = color;
+                    }
+                }
+            }
+        }
+    }
+
+    // Labels outside of the image aren't drawn
+    let _ = image.draw_subimage(&label, x, y, BlendingMethod::Over);
+}
+
+/// Get the rows of a character in the built in 3 by 5 font, where the highest of the 3 bits is the left column.
+/// Lowercase letters are drawn as uppercase, and unknown characters as `?`.
+fn glyph(character: char) -> [u8; 5] {
+    match character.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        ' ' => [0b000; 5],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Layer, layers::shapes::RectangleLayer};
+
+    #[test]
+    fn layer_name() {
+        let layer: RectangleLayer<u8> = RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 5, height: 5 });
+        assert_eq!(layer.name(), "RectangleLayer");
+        let boxed: Box<dyn Layer<u8>> = Box::new(layer);
+        assert_eq!(boxed.name(), "RectangleLayer");
+    }
+
+    #[test]
+    fn debug_render() {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+        canvas.background = AlphaPixel::white();
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 10, y: 10, width: 80, height: 80 }));
+        let image = canvas.debug_render();
+
+        // The outline is drawn on the edge of the rect, and the rest is unchanged
+        let (r, g, b) = DEBUG_COLORS[0];
+        assert_eq!(image.pixel_at(89, 50).unwrap(), AlphaPixel { r, g, b, a: 255 });
+        assert_eq!(image.pixel_at(50, 50).unwrap(), AlphaPixel::white());
+        // The label is in the top left corner
+        assert_ne!(image.pixel_at(11, 11).unwrap(), AlphaPixel::white());
+    }
+
+    #[test]
+    fn formatting() {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(40, 30);
+        assert_eq!(canvas.to_string(), "40x30 canvas with 0 layers");
+        let mut layer = RectangleLayer::new(AlphaPixel::red(), Rect { x: 5, y: 5, width: 10, height: 10 });
+        layer.filters.push(Box::new(crate::filters::brightness::BrightnessFilter { multiplier: 0.5 }));
+        canvas.add_layer(layer);
+        assert_eq!(canvas.to_string(), "40x30 canvas with 1 layer");
+        assert_eq!(
+            format!("{canvas:?}"),
+            "Canvas { width: 40, height: 30, background: rgba(0, 0, 0, 0), layers: [RectangleLayer at (5, 5), 10x10, 1 filter], .. }"
+        );
+
+        let image = canvas.flatten();
+        assert_eq!(image.to_string(), "40x30 image");
+        assert_eq!(format!("{image:?}"), "Image { width: 40, height: 30, .. }");
+    }
+}
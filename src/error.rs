@@ -0,0 +1,67 @@
+use thiserror::Error;
+use crate::{
+    bitmap::image::NewImageError,
+    layers::{
+        text::layout::LayoutError,
+        path::SvgPathError,
+        container::{constraints::ConstraintError, grid::GridError}
+    },
+    registry::RegistryError,
+    figma::FigmaError,
+    lottie::LottieError,
+    limits::LimitError
+};
+
+/// Any error returned by this crate.
+/// 
+/// Each module's own error type converts into this with `?`, so functions which use several parts of the crate
+/// can return a single error type.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, Error};
+/// 
+/// fn stamp(background: &mut Image<u8>, x: usize, y: usize) -> Result<(), Error> {
+///     let stamp = Image::from_pixels(vec![AlphaPixel::red(); 4], 2)?;
+///     background.draw_subimage(&stamp, x, y, image_template::BlendingMethod::Over)
+/// }
+/// 
+/// let mut background = Image::new_with_fill(AlphaPixel::white(), 10, 10);
+/// assert!(stamp(&mut background, 5, 5).is_ok());
+/// assert!(matches!(stamp(&mut background, 20, 5), Err(Error::OutOfBounds { x: 20, y: 5 })));
+/// ```
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Coordinate ({x}, {y}) is outside of the bounds")]
+    OutOfBounds { x: usize, y: usize },
+    #[error(transparent)]
+    NewImage(#[from] NewImageError),
+    #[error(transparent)]
+    Layout(#[from] LayoutError),
+    #[error(transparent)]
+    Constraint(#[from] ConstraintError),
+    #[error(transparent)]
+    Grid(#[from] GridError),
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+    #[error(transparent)]
+    SvgPath(#[from] SvgPathError),
+    #[error(transparent)]
+    Figma(#[from] FigmaError),
+    #[error(transparent)]
+    Lottie(#[from] LottieError),
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    #[cfg(feature = "image-crate")]
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
+    #[cfg(feature = "qr")]
+    #[error(transparent)]
+    QrCode(#[from] qrcodegen::DataTooLong),
+    #[cfg(feature = "html")]
+    #[error(transparent)]
+    Html(#[from] crate::layers::html::HtmlError),
+    #[cfg(feature = "system-fonts")]
+    #[error(transparent)]
+    SystemFont(#[from] crate::layers::text::system_font::SystemFontError)
+}
@@ -0,0 +1,414 @@
+//! Import a subset of the JSON that Figma exports for a design, so that designs can be turned into templates.
+//! 
+//! The JSON of a whole file, from Figma's REST API, or of a single frame can be imported. These nodes are supported:
+//! - Frames, groups, components and instances, with their fills and children. Frames don't clip their children.
+//! - Rectangles, with their corner radii, and ellipses
+//! - Text, with its font family, size, colour, horizontal and vertical alignment, and case. Text in fixed width boxes is wrapped.
+//! 
+//! Only solid fills are drawn, and opacity is applied to each fill. Hidden nodes and fills are skipped, as are other
+//! types of node, such as vectors and lines, and strokes and effects.
+
+use std::collections::HashMap;
+use fontdue::Font;
+use thiserror::Error;
+use crate::{
+    Canvas,
+    Layer,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    Error,
+    limits::Limits,
+    json::{JsonValue, JsonError},
+    layers::{
+        shapes::{RectangleLayer, RoundedRectangleLayer, EllipseLayer, CornerRadii},
+        text::{TextLayer, TextSettings, layout::{TextLayout, TextOverflow, LayoutAlign, VerticalAlign, TextTransform}},
+        metadata::AnnotatedLayer
+    }
+};
+
+/// The metadata key of the ID of the Figma node that a layer was imported from.
+pub const FIGMA_ID_KEY: &str = "figma_id";
+/// The metadata key of the name of the Figma node that a layer was imported from.
+pub const FIGMA_NAME_KEY: &str = "figma_name";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FigmaError {
+    #[error(transparent)]
+    Json(#[from] JsonError),
+    #[error("The document has no frames")]
+    NoFrame,
+    #[error("No frame is named \"{0}\"")]
+    FrameNotFound(String),
+    #[error("Node \"{node}\" is missing \"{field}\"")]
+    MissingField { node: String, field: &'static str },
+    #[error("No font was given for the font family \"{0}\"")]
+    MissingFont(String)
+}
+
+/// Imports frames from Figma's JSON, with the fonts to draw their text with.
+/// 
+/// Each imported layer has the ID and name of the Figma node it was made from in its [metadata](crate::Layer::metadata),
+/// under [`FIGMA_ID_KEY`] and [`FIGMA_NAME_KEY`].
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, figma::FigmaImporter};
+/// 
+/// let json = r#"{
+///     "type": "FRAME", "name": "Banner", "id": "1:2",
+///     "absoluteBoundingBox": { "x": 100, "y": 100, "width": 300, "height": 100 },
+///     "fills": [{ "type": "SOLID", "color": { "r": 1, "g": 1, "b": 1, "a": 1 } }],
+///     "children": [{
+///         "type": "RECTANGLE", "name": "Badge", "id": "1:3", "cornerRadius": 8,
+///         "absoluteBoundingBox": { "x": 120, "y": 120, "width": 60, "height": 60 },
+///         "fills": [{ "type": "SOLID", "color": { "r": 1, "g": 0, "b": 0, "a": 1 } }]
+///     }]
+/// }"#;
+/// 
+/// let canvas = FigmaImporter::new().import::<u8>(json, None).unwrap();
+/// assert_eq!((canvas.width, canvas.height), (300, 100));
+/// let image = canvas.flatten();
+/// assert_eq!(image.pixel_at(50, 50).unwrap(), AlphaPixel::red());
+/// assert_eq!(image.pixel_at(250, 50).unwrap(), AlphaPixel::white());
+/// ```
+#[derive(Default)]
+pub struct FigmaImporter {
+    fonts: HashMap<String, Font>,
+    limits: Option<Limits>
+}
+
+impl FigmaImporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `font` for text in the font family `family`, such as `"Inter"`.
+    pub fn with_font(mut self, family: &str, font: Font) -> Self {
+        self.fonts.insert(family.to_string(), font);
+        self
+    }
+
+    /// Return an error instead of importing a frame which is larger, or has more layers or longer text, than `limits` allow.
+    /// The imported canvas also has these limits, so they are checked when it is flattened.
+    /// 
+    /// This should be used when importing documents from untrusted users.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Error, figma::FigmaImporter, limits::{Limits, LimitError}};
+    /// 
+    /// let json = r#"{ "type": "FRAME", "name": "Huge", "id": "1:2", "absoluteBoundingBox": { "x": 0, "y": 0, "width": 100000, "height": 100000 } }"#;
+    /// let importer = FigmaImporter::new().with_limits(Limits::default());
+    /// assert!(matches!(importer.import::<u8>(json, None), Err(Error::Limit(LimitError::CanvasTooLarge { .. }))));
+    /// ```
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Import a frame as a canvas the size of the frame.
+    /// 
+    /// If `frame_name` is `None`, the first frame in the document is imported. Otherwise, the first frame with that name is.
+    /// The layers are drawn in the same order as in Figma, and positioned relative to the top left of the frame.
+    pub fn import<T: PixelChannel>(&self, json: &str, frame_name: Option<&str>) -> Result<Canvas<T>, Error> {
+        let document = JsonValue::parse(json).map_err(FigmaError::from)?;
+        let frame = find_frame(&document, frame_name).ok_or_else(|| match frame_name {
+            Some(name) => FigmaError::FrameNotFound(name.to_string()),
+            None => FigmaError::NoFrame
+        })?;
+
+        let (x, y, width, height) = bounding_box(frame)?;
+        let mut canvas = Canvas::from_dimensions(width.round().max(0.0) as usize, height.round().max(0.0) as usize);
+        if let Some(limits) = &self.limits {
+            limits.check_canvas(canvas.width, canvas.height)?;
+        }
+        canvas.limits = self.limits;
+        self.add_node(&mut canvas, frame, (x, y), 1.0)?;
+        Ok(canvas)
+    }
+
+    /// Add the layers of a node and its children, which are positioned relative to `origin`, to the canvas.
+    fn add_node<T: PixelChannel>(&self, canvas: &mut Canvas<T>, node: &JsonValue, origin: (f64, f64), parent_opacity: f64) -> Result<(), Error> {
+        if node.get("visible").and_then(JsonValue::as_bool) == Some(false) {
+            return Ok(())
+        }
+        let opacity = parent_opacity * node.get("opacity").and_then(JsonValue::as_f64).unwrap_or(1.0);
+
+        match node.get("type").and_then(JsonValue::as_str).unwrap_or_default() {
+            "FRAME" | "GROUP" | "COMPONENT" | "COMPONENT_SET" | "INSTANCE" | "SECTION" => {
+                self.add_fills(canvas, node, origin, opacity, false)?;
+                for child in node.get("children").and_then(JsonValue::as_array).unwrap_or_default() {
+                    self.add_node(canvas, child, origin, opacity)?;
+                }
+            },
+            "RECTANGLE" => self.add_fills(canvas, node, origin, opacity, false)?,
+            "ELLIPSE" => self.add_fills(canvas, node, origin, opacity, true)?,
+            "TEXT" => self.add_text(canvas, node, origin, opacity)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Add a layer for each solid fill of a rectangular or elliptical node.
+    fn add_fills<T: PixelChannel>(&self, canvas: &mut Canvas<T>, node: &JsonValue, origin: (f64, f64), opacity: f64, ellipse: bool) -> Result<(), Error> {
+        let fills = solid_fills(node, opacity);
+        if fills.is_empty() {
+            return Ok(())
+        }
+
+        let (x, y, width, height) = bounding_box(node)?;
+        let (x, y) = (x - origin.0, y - origin.1);
+        let radii = corner_radii(node);
+        for fill in fills {
+            if ellipse {
+                let (radius_x, radius_y) = (width / 2.0, height / 2.0);
+                canvas.try_add_layer(annotate(node, EllipseLayer::new(fill, (x + radius_x) as f32, (y + radius_y) as f32, radius_x as f32, radius_y as f32)))?;
+            } else if [radii.top_left, radii.top_right, radii.bottom_right, radii.bottom_left].iter().any(|radius| *radius > 0.0) {
+                canvas.try_add_layer(annotate(node, RoundedRectangleLayer::new(fill, canvas_rect(x, y, width, height), radii)))?;
+            } else {
+                canvas.try_add_layer(annotate(node, RectangleLayer::new(fill, canvas_rect(x, y, width, height))))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_text<T: PixelChannel>(&self, canvas: &mut Canvas<T>, node: &JsonValue, origin: (f64, f64), opacity: f64) -> Result<(), Error> {
+        let text = node.get("characters").and_then(JsonValue::as_str).unwrap_or_default();
+        if let Some(limits) = &self.limits {
+            limits.check_text(text)?;
+        }
+
+        let (x, y, width, height) = bounding_box(node)?;
+        let style = node.get("style");
+        let style_text = |key: &str| style.and_then(|style| style.get(key)).and_then(JsonValue::as_str).unwrap_or_default();
+
+        let family = style.and_then(|style| style.get("fontFamily")).and_then(JsonValue::as_str)
+            .ok_or_else(|| missing_field(node, "style.fontFamily"))?;
+        let font = self.fonts.get(family).ok_or_else(|| FigmaError::MissingFont(family.to_string()))?;
+        let size = style.and_then(|style| style.get("fontSize")).and_then(JsonValue::as_f64).unwrap_or(12.0);
+
+        let align = match style_text("textAlignHorizontal") {
+            "CENTER" => LayoutAlign::Center,
+            "RIGHT" => LayoutAlign::End,
+            "JUSTIFIED" => LayoutAlign::Justify,
+            _ => LayoutAlign::Start
+        };
+        let vertical_align = match style_text("textAlignVertical") {
+            "CENTER" => VerticalAlign::Middle,
+            "BOTTOM" => VerticalAlign::Bottom,
+            _ => VerticalAlign::Top
+        };
+        let transform = match style_text("textCase") {
+            "UPPER" => TextTransform::Uppercase,
+            "LOWER" => TextTransform::Lowercase,
+            "TITLE" => TextTransform::TitleCase,
+            "SMALL_CAPS" | "SMALL_CAPS_FORCED" => TextTransform::SmallCaps,
+            _ => TextTransform::None
+        };
+
+        // The REST API gives letter spacing in pixels
+        let letter_spacing = style.and_then(|style| style.get("letterSpacing")).and_then(JsonValue::as_f64).unwrap_or(0.0) as f32;
+
+        let mut settings = TextSettings {
+            size: size as f32,
+            fill: solid_fills(node, opacity).last().copied().unwrap_or(AlphaPixel::black()),
+            layout: TextLayout { align, transform, letter_spacing, ..Default::default() },
+            text: text.to_string(),
+            font: font.clone()
+        };
+        // Text boxes which grow to fit their text are never wrapped
+        if node.get("textAutoResize").and_then(JsonValue::as_str) != Some("WIDTH_AND_HEIGHT") {
+            settings.wrap_text(width as f32);
+        }
+        // Truncated text ends with an ellipsis, after its last line or where it overflows the box
+        if node.get("textTruncation").and_then(JsonValue::as_str) == Some("ENDING") {
+            settings.layout.overflow = TextOverflow::Ellipsis;
+            settings.layout.max_width = Some(width as f32);
+            settings.layout.max_lines = node.get("maxLines").and_then(JsonValue::as_f64).map(|max_lines| max_lines as usize);
+        }
+
+        let mut text = TextLayer::try_new(settings, 0, 0)?;
+        text.set_target(Some((canvas_rect(x - origin.0, y - origin.1, width, height), vertical_align)))?;
+        canvas.try_add_layer(annotate(node, text))?;
+        Ok(())
+    }
+}
+
+/// Find the first frame, or the first frame named `name`, searching the whole document in order.
+fn find_frame<'a>(value: &'a JsonValue, name: Option<&str>) -> Option<&'a JsonValue> {
+    let is_frame = matches!(value.get("type").and_then(JsonValue::as_str), Some("FRAME" | "COMPONENT" | "INSTANCE"));
+    if is_frame && name.is_none_or(|name| value.get("name").and_then(JsonValue::as_str) == Some(name)) {
+        return Some(value)
+    }
+
+    match value {
+        JsonValue::Object(members) => members.iter().find_map(|(_key, member)| find_frame(member, name)),
+        JsonValue::Array(values) => values.iter().find_map(|value| find_frame(value, name)),
+        _ => None
+    }
+}
+
+/// Get the position and size of a node on its page.
+fn bounding_box(node: &JsonValue) -> Result<(f64, f64, f64, f64), FigmaError> {
+    let bounds = node.get("absoluteBoundingBox").ok_or_else(|| missing_field(node, "absoluteBoundingBox"))?;
+    let number = |key: &'static str| bounds.get(key).and_then(JsonValue::as_f64).ok_or_else(|| missing_field(node, key));
+    Ok((number("x")?, number("y")?, number("width")?, number("height")?))
+}
+
+fn missing_field(node: &JsonValue, field: &'static str) -> FigmaError {
+    let name = node.get("name").and_then(JsonValue::as_str).unwrap_or_default();
+    FigmaError::MissingField { node: name.to_string(), field }
+}
+
+/// Convert a position and size relative to the frame to a `Rect`, which is cut off at the top and left of the canvas.
+fn canvas_rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+    let (left, top) = (x.round().max(0.0), y.round().max(0.0));
+    let right = (x + width).round().max(left);
+    let bottom = (y + height).round().max(top);
+    Rect { x: left as usize, y: top as usize, width: (right - left) as usize, height: (bottom - top) as usize }
+}
+
+fn corner_radii(node: &JsonValue) -> CornerRadii {
+    let radii: Vec<f32> = node.get("rectangleCornerRadii").and_then(JsonValue::as_array).unwrap_or_default()
+        .iter()
+        .filter_map(|radius| radius.as_f64().map(|radius| radius as f32))
+        .collect();
+    match radii[..] {
+        [top_left, top_right, bottom_right, bottom_left] => CornerRadii { top_left, top_right, bottom_right, bottom_left },
+        _ => CornerRadii::uniform(node.get("cornerRadius").and_then(JsonValue::as_f64).unwrap_or(0.0) as f32)
+    }
+}
+
+/// Get the colours of the visible solid fills of a node, from the bottom up, with their opacity multiplied by `opacity`.
+fn solid_fills<T: PixelChannel>(node: &JsonValue, opacity: f64) -> Vec<AlphaPixel<T>> {
+    node.get("fills").and_then(JsonValue::as_array).unwrap_or_default()
+        .iter()
+        .filter(|paint| paint.get("visible").and_then(JsonValue::as_bool) != Some(false))
+        .filter(|paint| paint.get("type").and_then(JsonValue::as_str) == Some("SOLID"))
+        .filter_map(|paint| {
+            let color = paint.get("color")?;
+            let channel = |key: &str, default: f64| color.get(key).and_then(JsonValue::as_f64).unwrap_or(default).clamp(0.0, 1.0) as f32;
+            let paint_opacity = paint.get("opacity").and_then(JsonValue::as_f64).unwrap_or(1.0);
+            let alpha = channel("a", 1.0) * (paint_opacity * opacity).clamp(0.0, 1.0) as f32;
+            Some(AlphaPixel { r: channel("r", 0.0), g: channel("g", 0.0), b: channel("b", 0.0), a: alpha }.as_different_channel())
+        })
+        .collect()
+}
+
+fn annotate<T: PixelChannel, L: Layer<T>>(node: &JsonValue, layer: L) -> AnnotatedLayer<T, L> {
+    let text = |key: &str| node.get(key).and_then(JsonValue::as_str).unwrap_or_default();
+    AnnotatedLayer::new(layer)
+        .with(FIGMA_ID_KEY, text("id"))
+        .with(FIGMA_NAME_KEY, text("name"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rgba, limits::LimitError};
+
+    const FILE: &str = r#"{
+        "name": "Templates",
+        "document": { "type": "DOCUMENT", "children": [{ "type": "CANVAS", "name": "Page 1", "children": [
+            {
+                "type": "FRAME", "name": "Square", "id": "1:1",
+                "absoluteBoundingBox": { "x": -500, "y": 0, "width": 20, "height": 20 },
+                "fills": []
+            },
+            {
+                "type": "FRAME", "name": "Card", "id": "2:1",
+                "absoluteBoundingBox": { "x": 0, "y": 0, "width": 100, "height": 50 },
+                "fills": [
+                    { "type": "SOLID", "color": { "r": 0, "g": 0, "b": 1, "a": 1 } },
+                    { "type": "GRADIENT_LINEAR", "gradientStops": [] }
+                ],
+                "children": [
+                    {
+                        "type": "GROUP", "name": "Faded", "id": "2:2", "opacity": 0.5,
+                        "absoluteBoundingBox": { "x": 0, "y": 0, "width": 10, "height": 10 },
+                        "children": [{
+                            "type": "RECTANGLE", "name": "Half", "id": "2:3",
+                            "absoluteBoundingBox": { "x": -5, "y": 0, "width": 15, "height": 10 },
+                            "fills": [{ "type": "SOLID", "color": { "r": 1, "g": 1, "b": 1, "a": 1 } }]
+                        }]
+                    },
+                    {
+                        "type": "ELLIPSE", "name": "Dot", "id": "2:4",
+                        "absoluteBoundingBox": { "x": 40, "y": 10, "width": 30, "height": 30 },
+                        "fills": [{ "type": "SOLID", "color": { "r": 1, "g": 0, "b": 0 }, "opacity": 1 }]
+                    },
+                    {
+                        "type": "RECTANGLE", "name": "Hidden", "id": "2:5", "visible": false,
+                        "absoluteBoundingBox": { "x": 0, "y": 0, "width": 100, "height": 50 },
+                        "fills": [{ "type": "SOLID", "color": { "r": 0, "g": 1, "b": 0, "a": 1 } }]
+                    },
+                    {
+                        "type": "VECTOR", "name": "Unsupported", "id": "2:6",
+                        "absoluteBoundingBox": { "x": 0, "y": 0, "width": 100, "height": 50 }
+                    }
+                ]
+            }
+        ]}]}
+    }"#;
+
+    #[test]
+    fn named_frame() {
+        let canvas: Canvas<u8> = FigmaImporter::new().import(FILE, Some("Card")).unwrap();
+        assert_eq!((canvas.width, canvas.height), (100, 50));
+        // The frame's fill, the rectangle and the ellipse
+        assert_eq!(canvas.layers.len(), 3);
+        assert_eq!(canvas.layers[1].metadata().unwrap()[FIGMA_NAME_KEY], "Half");
+        assert_eq!(canvas.layers[2].metadata().unwrap()[FIGMA_ID_KEY], "2:4");
+
+        let image = canvas.flatten();
+        assert_eq!(image.pixel_at(5, 5).unwrap(), rgba!(127, 127, 255, 255));
+        assert_eq!(image.pixel_at(12, 5).unwrap(), AlphaPixel::blue());
+        assert_eq!(image.pixel_at(55, 25).unwrap(), AlphaPixel::red());
+    }
+
+    #[test]
+    fn first_frame() {
+        let canvas: Canvas<u8> = FigmaImporter::new().import(FILE, None).unwrap();
+        assert_eq!((canvas.width, canvas.height, canvas.layers.len()), (20, 20, 0));
+    }
+
+    #[test]
+    fn errors() {
+        let importer = FigmaImporter::new();
+        let error = |json: &str, name: Option<&str>| match importer.import::<u8>(json, name) {
+            Err(Error::Figma(error)) => error,
+            _ => panic!("importing should fail")
+        };
+
+        assert_eq!(error(FILE, Some("Poster")), FigmaError::FrameNotFound("Poster".to_string()));
+        assert_eq!(error(r#"{"type": "CANVAS"}"#, None), FigmaError::NoFrame);
+        assert!(matches!(error("{", None), FigmaError::Json(_)));
+        assert_eq!(
+            error(r#"{"type": "FRAME", "name": "Broken", "absoluteBoundingBox": {"x": 0, "y": 0}}"#, None),
+            FigmaError::MissingField { node: "Broken".to_string(), field: "width" }
+        );
+
+        let text = r#"{"type": "FRAME", "name": "F", "absoluteBoundingBox": {"x": 0, "y": 0, "width": 9, "height": 9}, "children": [
+            {"type": "TEXT", "name": "T", "characters": "Hi", "style": {"fontFamily": "Inter"}, "absoluteBoundingBox": {"x": 0, "y": 0, "width": 9, "height": 9}}
+        ]}"#;
+        assert_eq!(error(text, None), FigmaError::MissingFont("Inter".to_string()));
+    }
+
+    #[test]
+    fn limits() {
+        let import = |limits: Limits| FigmaImporter::new().with_limits(limits).import::<u8>(FILE, Some("Card"));
+        assert!(import(Limits::default()).unwrap().limits.is_some());
+        assert!(matches!(
+            import(Limits { max_canvas_pixels: 4999, ..Default::default() }),
+            Err(Error::Limit(LimitError::CanvasTooLarge { width: 100, height: 50, max_pixels: 4999 }))
+        ));
+        assert!(matches!(import(Limits { max_layers: 2, ..Default::default() }), Err(Error::Limit(LimitError::TooManyLayers(2)))));
+
+        let text = r#"{"type": "FRAME", "name": "F", "absoluteBoundingBox": {"x": 0, "y": 0, "width": 9, "height": 9}, "children": [
+            {"type": "TEXT", "name": "T", "characters": "Hello", "style": {"fontFamily": "Inter"}, "absoluteBoundingBox": {"x": 0, "y": 0, "width": 9, "height": 9}}
+        ]}"#;
+        let importer = FigmaImporter::new().with_limits(Limits { max_text_length: 4, ..Default::default() });
+        assert!(matches!(importer.import::<u8>(text, None), Err(Error::Limit(LimitError::TextTooLong { length: 5, max_length: 4 }))));
+    }
+}
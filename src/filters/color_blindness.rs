@@ -0,0 +1,132 @@
+use crate::{Filter, AlphaPixel, PixelChannel, bitmap::pixel::{srgb_to_linear, linear_to_srgb}};
+
+/// A kind of colour vision deficiency, which can be simulated with a [`ColorBlindnessFilter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorBlindness {
+    /// No red cones, so reds look darker and are confused with greens
+    Protanopia,
+    /// No green cones, the most common kind, so reds and greens are confused
+    Deuteranopia,
+    /// No blue cones, so blues are confused with greens, and yellows with pinks
+    Tritanopia,
+    /// No colour vision at all, so only brightness is seen
+    Achromatopsia
+}
+
+impl ColorBlindness {
+    /// The matrix which simulates this deficiency in linear RGB, from
+    /// [Machado, Oliveira and Fernandes (2009)](https://www.inf.ufrgs.br/~oliveira/pubs_files/CVD_Simulation/CVD_Simulation.html)
+    /// at full severity, or the luminance of each channel for achromatopsia.
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorBlindness::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998]
+            ],
+            ColorBlindness::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881]
+            ],
+            ColorBlindness::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900]
+            ],
+            ColorBlindness::Achromatopsia => [[0.2126, 0.7152, 0.0722]; 3]
+        }
+    }
+}
+
+/// A filter that simulates how a layer looks to someone with a colour vision deficiency, so that colours which
+/// are hard to tell apart can be found while designing a template.
+/// 
+/// `severity` is from 0.0, which leaves the colours unchanged, to 1.0, which simulates the complete deficiency.
+/// Values in between approximate the milder, anomalous forms, such as protanomaly.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::RectangleLayer, filters::color_blindness::{ColorBlindnessFilter, ColorBlindness}};
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+/// let mut red = RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 50, height: 100 });
+/// let mut green = RectangleLayer::new(AlphaPixel::green(), Rect { x: 50, y: 0, width: 50, height: 100 });
+/// red.filters.push(Box::new(ColorBlindnessFilter::new(ColorBlindness::Deuteranopia)));
+/// green.filters.push(Box::new(ColorBlindnessFilter::new(ColorBlindness::Deuteranopia)));
+/// canvas.add_layer(red);
+/// canvas.add_layer(green);
+/// 
+/// // Red and green both look yellowish
+/// let image = canvas.flatten();
+/// let (red, green) = (image.pixel_at(25, 50).unwrap(), image.pixel_at(75, 50).unwrap());
+/// assert!(red.r > red.b && green.r > green.b);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ColorBlindnessFilter {
+    pub kind: ColorBlindness,
+    pub severity: f32
+}
+
+impl ColorBlindnessFilter {
+    /// Simulate the complete deficiency.
+    pub fn new(kind: ColorBlindness) -> Self {
+        Self { kind, severity: 1.0 }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for ColorBlindnessFilter {
+    fn filter_pixel(&self, pixel: AlphaPixel<T>) -> AlphaPixel<T> {
+        let severity = self.severity.clamp(0.0, 1.0);
+        if severity == 0.0 {
+            return pixel
+        }
+
+        let float = pixel.as_float_pixel();
+        let linear = [float.r, float.g, float.b].map(srgb_to_linear);
+        let matrix = self.kind.matrix();
+
+        let channel = |row: usize| {
+            let simulated: f32 = (0..3).map(|column| matrix[row][column] * linear[column]).sum();
+            let mixed = linear[row] + (simulated - linear[row])*severity;
+            linear_to_srgb(mixed.clamp(0.0, 1.0))
+        };
+        AlphaPixel { r: channel(0), g: channel(1), b: channel(2), a: float.a }.as_different_channel()
+    }
+
+    fn scaled(&self, _factor: f32) -> Option<Box<dyn Filter<T>>> {
+        Some(Box::new(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn simulation() {
+        let filter = |kind: ColorBlindness, severity: f32, pixel: AlphaPixel<u8>| {
+            Filter::<u8>::filter_pixel(&ColorBlindnessFilter { kind, severity }, pixel)
+        };
+
+        // Greys are unchanged, and so is alpha
+        for kind in [ColorBlindness::Protanopia, ColorBlindness::Deuteranopia, ColorBlindness::Tritanopia, ColorBlindness::Achromatopsia] {
+            let grey = filter(kind, 1.0, rgba!(128, 128, 128, 100));
+            assert!(grey.r.abs_diff(128) <= 1 && grey.g.abs_diff(128) <= 1 && grey.b.abs_diff(128) <= 1);
+            assert_eq!(grey.a, 100);
+        }
+
+        // Red looks dark and yellowish without red cones
+        let red = filter(ColorBlindness::Protanopia, 1.0, AlphaPixel::red());
+        assert!(red.r < 150 && red.r > red.b && red.g > red.b);
+        assert_eq!(filter(ColorBlindness::Protanopia, 0.0, AlphaPixel::red()), AlphaPixel::red());
+
+        // Blue and green are hard to tell apart without blue cones
+        let blue = filter(ColorBlindness::Tritanopia, 1.0, rgba!(0, 0, 255, 255));
+        assert!(blue.g > blue.r);
+
+        let achromatic = filter(ColorBlindness::Achromatopsia, 1.0, rgba!(200, 50, 100, 255));
+        assert!(achromatic.r == achromatic.g && achromatic.g == achromatic.b);
+    }
+}
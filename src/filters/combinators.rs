@@ -0,0 +1,203 @@
+use crate::{Filter, FilterContext, AlphaPixel, PixelChannel, bitmap::mask::Mask, filters::{sample_filters, filter_colours, apply_filter}};
+
+/// Mix between an unfiltered and a filtered pixel, where a `t` of 0.0 is `original` and 1.0 is `filtered`.
+/// 
+/// If only one of the pixels exists, the other is treated as that pixel's colour, fully transparent.
+fn mix<T: PixelChannel>(original: Option<AlphaPixel<T>>, filtered: Option<AlphaPixel<T>>, t: f32) -> Option<AlphaPixel<T>> {
+    match (original, filtered) {
+        _ if t <= 0.0 => original,
+        _ if t >= 1.0 => filtered,
+        (Some(original), Some(filtered)) => Some(original.lerp(filtered, t)),
+        (Some(original), None) => Some(original.scale_alpha(1.0 - t)),
+        (None, Some(filtered)) => Some(filtered.scale_alpha(t)),
+        (None, None) => None
+    }
+}
+
+/// Apply a list of filters as if they were a single filter.
+/// 
+/// As with the filters of a layer, the first filter receives the coordinate passed to the chain, and the colour of
+/// the pixel is filtered by each filter in order.
+/// 
+/// # Example
+/// ```
+/// use image_template::filters::{combinators::FilterChain, brightness::BrightnessFilter, transform::TranslateFilter};
+/// 
+/// let shifted_and_darkened: FilterChain<u8> = FilterChain::new(vec![
+///     Box::new(TranslateFilter { x: 5, y: 5 }),
+///     Box::new(BrightnessFilter { multiplier: 0.5 })
+/// ]);
+/// ```
+pub struct FilterChain<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>
+}
+
+impl<T> FilterChain<T> {
+    pub fn new(filters: Vec<Box<dyn Filter<T>>>) -> Self {
+        Self { filters }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for FilterChain<T> {
+    fn filter_pixel_at(&self, pixel: AlphaPixel<T>, x: isize, y: isize, context: &FilterContext) -> AlphaPixel<T> {
+        filter_colours(&self.filters, pixel, x, y, context)
+    }
+
+    fn filter_transform(&self, x: isize, y: isize, context: &FilterContext) -> Option<(isize, isize)> {
+        self.filters.iter().try_fold((x, y), |(x, y), filter| filter.filter_transform(x, y, context))
+    }
+
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+        sample_filters(&self.filters, x, y, context, sample)
+    }
+}
+
+/// Mix the output of a filter with the unfiltered layer. An `amount` of 0.0 is the unfiltered layer,
+/// and 1.0 is the fully filtered layer.
+/// 
+/// # Example
+/// ```
+/// use image_template::filters::{combinators::BlendFilter, brightness::BrightnessFilter};
+/// 
+/// // Half of the brightening effect
+/// let subtle: BlendFilter<u8> = BlendFilter::new(BrightnessFilter { multiplier: 2.0 }, 0.5);
+/// ```
+pub struct BlendFilter<T> {
+    pub filter: Box<dyn Filter<T>>,
+    pub amount: f32
+}
+
+impl<T> BlendFilter<T> {
+    pub fn new<F: Filter<T> + 'static>(filter: F, amount: f32) -> Self {
+        Self { filter: Box::new(filter), amount }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for BlendFilter<T> {
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+        mix(sample(x, y), apply_filter(self.filter.as_ref(), x, y, context, sample), self.amount)
+    }
+}
+
+/// Apply a filter only where a [`Mask`] is covered, mixing it with the unfiltered layer where the mask is partially covered.
+/// 
+/// The top left of the mask is placed at (`mask_x`, `mask_y`) on the canvas. Outside of the mask, the layer is unfiltered.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Rect, bitmap::mask::Mask};
+/// use image_template::filters::{combinators::MaskedFilter, brightness::BrightnessFilter};
+/// 
+/// // Only darken the left half of a 100 by 100 area
+/// let mask = Mask::from_rect(100, 100, Rect { x: 0, y: 0, width: 50, height: 100 });
+/// let half_dark: MaskedFilter<u8> = MaskedFilter::new(BrightnessFilter { multiplier: 0.5 }, mask, 0, 0);
+/// ```
+pub struct MaskedFilter<T> {
+    pub filter: Box<dyn Filter<T>>,
+    pub mask: Mask,
+    pub mask_x: usize,
+    pub mask_y: usize
+}
+
+impl<T> MaskedFilter<T> {
+    pub fn new<F: Filter<T> + 'static>(filter: F, mask: Mask, mask_x: usize, mask_y: usize) -> Self {
+        Self { filter: Box::new(filter), mask, mask_x, mask_y }
+    }
+
+    fn coverage_at(&self, x: isize, y: isize) -> f32 {
+        match (usize::try_from(x).ok().and_then(|x| x.checked_sub(self.mask_x)), usize::try_from(y).ok().and_then(|y| y.checked_sub(self.mask_y))) {
+            (Some(mask_x), Some(mask_y)) => self.mask.value_at(mask_x, mask_y).unwrap_or(0.0),
+            _ => 0.0
+        }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for MaskedFilter<T> {
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+        let coverage = self.coverage_at(x, y);
+        if coverage <= 0.0 {
+            return sample(x, y)
+        }
+        mix(sample(x, y), apply_filter(self.filter.as_ref(), x, y, context, sample), coverage)
+    }
+}
+
+/// The condition of an [`IfFilter`], which takes the unfiltered pixel and its canvas coordinate.
+pub type FilterPredicate<T> = dyn Fn(AlphaPixel<T>, isize, isize) -> bool;
+
+/// Apply a filter only to pixels where a predicate is true. The predicate is given the unfiltered pixel and its coordinate.
+/// 
+/// Coordinates outside of the unfiltered layer are always filtered, so that transform filters can still move the layer there.
+/// 
+/// # Example
+/// ```
+/// use image_template::filters::{combinators::IfFilter, brightness::BrightnessFilter};
+/// 
+/// // Brighten the dark parts of the layer
+/// let lift_shadows: IfFilter<u8> = IfFilter::new(BrightnessFilter { multiplier: 1.5 }, |pixel, _, _| pixel.luma() < 64);
+/// ```
+pub struct IfFilter<T> {
+    pub filter: Box<dyn Filter<T>>,
+    predicate: Box<FilterPredicate<T>>
+}
+
+impl<T> IfFilter<T> {
+    pub fn new<F: Filter<T> + 'static, P: Fn(AlphaPixel<T>, isize, isize) -> bool + 'static>(filter: F, predicate: P) -> Self {
+        Self { filter: Box::new(filter), predicate: Box::new(predicate) }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for IfFilter<T> {
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+        match sample(x, y) {
+            Some(pixel) if !(self.predicate)(pixel, x, y) => Some(pixel),
+            _ => apply_filter(self.filter.as_ref(), x, y, context, sample)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layers::shapes::RectangleLayer, filters::{brightness::BrightnessFilter, transform::TranslateFilter}, Layer, Rect, rgba};
+
+    fn grey_square(filter: Box<dyn Filter<u8>>) -> RectangleLayer<u8> {
+        RectangleLayer { fill: rgba!(100, 100, 100, 255), rect: Rect { x: 0, y: 0, width: 10, height: 10 }, filters: vec![filter] }
+    }
+
+    #[test]
+    fn chain() {
+        let layer = grey_square(Box::new(FilterChain::new(vec![
+            Box::new(TranslateFilter { x: 5, y: 0 }),
+            Box::new(BrightnessFilter { multiplier: 2.0 })
+        ])));
+        assert!(layer.filtered_pixel_at(2, 2).is_none());
+        assert_eq!(layer.filtered_pixel_at(12, 2).unwrap(), rgba!(200, 200, 200, 255));
+    }
+
+    #[test]
+    fn blend() {
+        let layer = grey_square(Box::new(BlendFilter::new(BrightnessFilter { multiplier: 2.0 }, 0.5)));
+        let pixel = layer.filtered_pixel_at(2, 2).unwrap();
+        assert!((149..=150).contains(&pixel.r));
+
+        let unfiltered = grey_square(Box::new(BlendFilter::new(BrightnessFilter { multiplier: 2.0 }, 0.0)));
+        assert_eq!(unfiltered.filtered_pixel_at(2, 2).unwrap(), rgba!(100, 100, 100, 255));
+    }
+
+    #[test]
+    fn masked() {
+        let mask = Mask::from_rect(5, 5, Rect { x: 0, y: 0, width: 2, height: 5 });
+        let layer = grey_square(Box::new(MaskedFilter::new(BrightnessFilter { multiplier: 2.0 }, mask, 3, 3)));
+        assert_eq!(layer.filtered_pixel_at(4, 4).unwrap(), rgba!(200, 200, 200, 255));
+        assert_eq!(layer.filtered_pixel_at(5, 4).unwrap(), rgba!(100, 100, 100, 255));
+        assert_eq!(layer.filtered_pixel_at(0, 0).unwrap(), rgba!(100, 100, 100, 255));
+    }
+
+    #[test]
+    fn predicate() {
+        let layer = grey_square(Box::new(IfFilter::new(BrightnessFilter { multiplier: 2.0 }, |_, x, _| x < 5)));
+        assert_eq!(layer.filtered_pixel_at(4, 0).unwrap(), rgba!(200, 200, 200, 255));
+        assert_eq!(layer.filtered_pixel_at(5, 0).unwrap(), rgba!(100, 100, 100, 255));
+    }
+}
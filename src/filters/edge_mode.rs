@@ -0,0 +1,113 @@
+use crate::{Filter, FilterContext, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// What is sampled when a coordinate is outside of the source of a layer.
+pub enum EdgeMode {
+    /// Nothing is sampled, so the pixel is transparent.
+    #[default]
+    Transparent,
+    /// The nearest pixel on the edge of the source is sampled, stretching the edge outwards.
+    Clamp,
+    /// The source repeats, so the coordinate wraps around to the opposite edge.
+    Wrap,
+    /// The source repeats, flipped each time, so that the edges of neighbouring copies match.
+    Mirror
+}
+
+impl EdgeMode {
+    /// Map a coordinate on one axis into the range `start..start + length`.
+    fn map_axis(&self, coordinate: isize, start: usize, length: usize) -> isize {
+        if length == 0 {
+            return coordinate
+        }
+
+        let relative = coordinate.saturating_sub(start as isize);
+        let length = length as isize;
+        let mapped = match self {
+            EdgeMode::Transparent => return coordinate,
+            EdgeMode::Clamp => relative.clamp(0, length - 1),
+            EdgeMode::Wrap => relative.rem_euclid(length),
+            EdgeMode::Mirror => {
+                let position = relative.rem_euclid(2*length);
+                if position < length { position } else { 2*length - 1 - position }
+            }
+        };
+        start as isize + mapped
+    }
+}
+
+/// A filter that maps coordinates outside of the layer's `Rect` back into it, following an [`EdgeMode`].
+/// 
+/// This is placed after the transform filters of a layer, so that they can sample beyond the layer,
+/// for example to tile a rotated image.
+/// 
+/// # Example
+/// ```
+/// use image_template::layers::image::ImageLayer;
+/// use image_template::filters::{transform::TranslateFilter, edge_mode::{EdgeModeFilter, EdgeMode}};
+/// use image_template::{Image, AlphaPixel, Layer};
+/// 
+/// let tile: Image<u8> = Image::from_function(2, 2, |x, _| if x == 0 { AlphaPixel::red() } else { AlphaPixel::blue() });
+/// let mut layer = ImageLayer::new(tile, 10, 10);
+/// layer.filters.push(Box::new(TranslateFilter { x: 1, y: 0 }));
+/// layer.filters.push(Box::new(EdgeModeFilter::new(EdgeMode::Wrap)));
+/// 
+/// // The pixel shifted off the right of the image wraps around to the left
+/// assert_eq!(layer.filtered_pixel_at(10, 10).unwrap(), AlphaPixel::blue());
+/// assert_eq!(layer.filtered_pixel_at(11, 10).unwrap(), AlphaPixel::red());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeModeFilter {
+    pub mode: EdgeMode,
+    /// The area that coordinates are mapped into, or `None` for the layer's `Rect`
+    pub rect: Option<Rect>
+}
+
+impl EdgeModeFilter {
+    pub fn new(mode: EdgeMode) -> Self {
+        Self { mode, rect: None }
+    }
+}
+
+impl<T> Filter<T> for EdgeModeFilter {
+    fn filter_transform(&self, x: isize, y: isize, context: &FilterContext) -> Option<(isize, isize)> {
+        let rect = self.rect.unwrap_or(context.layer_rect);
+        Some((
+            self.mode.map_axis(x, rect.x, rect.width),
+            self.mode.map_axis(y, rect.y, rect.height)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{filters::transform::MatrixTransform, layers::image::ImageLayer, Image, AlphaPixel, Layer};
+
+    #[test]
+    fn map_axis() {
+        // A source from 10 to 13
+        let mapped = |mode: EdgeMode, coordinate: isize| mode.map_axis(coordinate, 10, 4);
+
+        assert_eq!(mapped(EdgeMode::Transparent, 20), 20);
+        assert_eq!(mapped(EdgeMode::Clamp, 20), 13);
+        assert_eq!(mapped(EdgeMode::Clamp, -3), 10);
+        assert_eq!(mapped(EdgeMode::Wrap, 14), 10);
+        assert_eq!(mapped(EdgeMode::Wrap, 9), 13);
+        assert_eq!(mapped(EdgeMode::Mirror, 14), 13);
+        assert_eq!(mapped(EdgeMode::Mirror, 9), 10);
+        assert_eq!(mapped(EdgeMode::Mirror, 18), 10);
+    }
+
+    #[test]
+    fn clamp_scaled_down_layer() {
+        let image: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 4, 4);
+        let mut layer = ImageLayer::new(image, 0, 0);
+        // Shrinking the layer samples beyond its edge
+        layer.filters.push(Box::new(MatrixTransform::new(0.0, 0.0).scale(0.5)));
+        assert!(layer.filtered_pixel_at(3, 3).is_none());
+
+        layer.filters.push(Box::new(EdgeModeFilter::new(EdgeMode::Clamp)));
+        assert_eq!(layer.filtered_pixel_at(3, 3).unwrap(), AlphaPixel::red());
+    }
+}
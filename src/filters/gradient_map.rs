@@ -0,0 +1,37 @@
+use crate::{Filter, AlphaPixel, PixelChannel, bitmap::{ramp::ColorRamp, gradient_map::gradient_map_pixel}};
+
+/// A filter that recolours a layer by mapping the luma of each pixel to a colour of a [`ColorRamp`].
+/// 
+/// This is the filter version of [`Image::gradient_map`](crate::Image::gradient_map).
+pub struct GradientMapFilter<T> {
+    pub ramp: ColorRamp<T>
+}
+
+impl<T: PixelChannel> GradientMapFilter<T> {
+    pub fn new(ramp: ColorRamp<T>) -> Self {
+        Self { ramp }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for GradientMapFilter<T> {
+    fn filter_pixel(&self, pixel: AlphaPixel<T>) -> AlphaPixel<T> {
+        gradient_map_pixel(pixel, &self.ramp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layers::shapes::RectangleLayer, rgba, Layer, Rect};
+
+    #[test]
+    fn gradient_map() {
+        let ramp = ColorRamp::new(vec![(1.0, AlphaPixel::red()), (0.0, AlphaPixel::blue())]);
+        let rectangle: RectangleLayer<u8> = RectangleLayer {
+            fill: rgba!(255, 255, 255, 100),
+            rect: Rect { x: 0, y: 0, width: 10, height: 10 },
+            filters: vec![Box::new(GradientMapFilter::new(ramp))]
+        };
+        assert_eq!(rectangle.filtered_pixel_at(5, 5).unwrap(), rgba!(255, 0, 0, 100));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{Filter, FilterContext, AlphaPixel, PixelChannel};
+
+/// Rotate a coordinate by `angle` degrees, so that patterns can be drawn at an angle.
+fn rotated_coordinate(x: isize, y: isize, angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (x, y) = (x as f32, y as f32);
+    (x*cos + y*sin, -x*sin + y*cos)
+}
+
+/// Get how dark a pixel is, from 0.0 (white) to 1.0 (black).
+fn darkness<T: PixelChannel>(pixel: AlphaPixel<T>) -> f32 {
+    1.0 - pixel.luma().into() / T::MAX_PIXEL_VALUE.into()
+}
+
+/// Draw `ink` if `inked`, or `paper` otherwise, keeping the transparency of the original pixel.
+fn ink_or_paper<T: PixelChannel>(pixel: AlphaPixel<T>, inked: bool, ink: AlphaPixel<T>, paper: AlphaPixel<T>) -> AlphaPixel<T> {
+    let alpha = pixel.a.into() / T::MAX_PIXEL_VALUE.into();
+    if inked {
+        ink.scale_alpha(alpha)
+    } else {
+        paper.scale_alpha(alpha)
+    }
+}
+
+/// A filter that converts the luminance of a layer into cross-hatched lines, for an engraved look.
+/// 
+/// Lines are drawn every `spacing` pixels at `angle` degrees. Darker areas have extra sets of lines drawn over them,
+/// rotated by 90 degrees and then 45 degrees from the first set.
+pub struct CrossHatchFilter<T> {
+    pub spacing: f32,
+    pub angle: f32,
+    pub line_width: f32,
+    pub ink: AlphaPixel<T>,
+    pub paper: AlphaPixel<T>
+}
+
+impl<T: PixelChannel> CrossHatchFilter<T> {
+    /// Darkness thresholds and angle offsets for each set of hatching lines
+    const HATCH_SETS: [(f32, f32); 3] = [(0.2, 0.0), (0.45, 90.0), (0.7, 45.0)];
+
+    /// Create a cross hatch filter with black lines on white paper.
+    pub fn new(spacing: f32, angle: f32) -> Self {
+        Self { spacing, angle, line_width: 1.0, ink: AlphaPixel::black(), paper: AlphaPixel::white() }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for CrossHatchFilter<T> {
+    fn filter_pixel_at(&self, pixel: AlphaPixel<T>, x: isize, y: isize, _context: &FilterContext) -> AlphaPixel<T> {
+        let darkness = darkness(pixel);
+
+        let inked = Self::HATCH_SETS.iter()
+            .filter(|(threshold, _)| darkness > *threshold)
+            .any(|(_, angle_offset)| {
+                let (distance, _) = rotated_coordinate(x, y, self.angle + angle_offset);
+                distance.rem_euclid(self.spacing) < self.line_width
+            });
+
+        ink_or_paper(pixel, inked, self.ink, self.paper)
+    }
+}
+
+/// A filter that converts the luminance of a layer into stippled dots, for a newspaper print look.
+/// 
+/// Dots are placed on a grid with cells of `spacing` pixels, rotated by `angle` degrees.
+/// The area of each dot is proportional to the darkness of the layer.
+pub struct StippleFilter<T> {
+    pub spacing: f32,
+    pub angle: f32,
+    pub ink: AlphaPixel<T>,
+    pub paper: AlphaPixel<T>
+}
+
+impl<T: PixelChannel> StippleFilter<T> {
+    /// Create a stipple filter with black dots on white paper.
+    pub fn new(spacing: f32, angle: f32) -> Self {
+        Self { spacing, angle, ink: AlphaPixel::black(), paper: AlphaPixel::white() }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for StippleFilter<T> {
+    fn filter_pixel_at(&self, pixel: AlphaPixel<T>, x: isize, y: isize, _context: &FilterContext) -> AlphaPixel<T> {
+        let (grid_x, grid_y) = rotated_coordinate(x, y, self.angle);
+
+        // Distance from the center of the grid cell
+        let cell_x = grid_x.rem_euclid(self.spacing) - self.spacing / 2.0;
+        let cell_y = grid_y.rem_euclid(self.spacing) - self.spacing / 2.0;
+        let distance = (cell_x*cell_x + cell_y*cell_y).sqrt();
+
+        // A dot with this radius covers the whole cell when fully dark
+        let max_radius = self.spacing * std::f32::consts::FRAC_1_SQRT_2;
+        let radius = max_radius * darkness(pixel).sqrt();
+
+        ink_or_paper(pixel, distance < radius, self.ink, self.paper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layers::shapes::RectangleLayer, Layer, Rect, rgba};
+
+    fn filtered_rectangle(fill: AlphaPixel<u8>, filter: Box<dyn Filter<u8>>) -> RectangleLayer<u8> {
+        RectangleLayer { rect: Rect { x: 0, y: 0, width: 20, height: 20 }, fill, filters: vec![filter] }
+    }
+
+    fn ink_count(layer: &RectangleLayer<u8>) -> usize {
+        (0..20).flat_map(|y| (0..20).map(move |x| (x, y)))
+            .filter(|(x, y)| layer.filtered_pixel_at(*x, *y).unwrap() == AlphaPixel::black())
+            .count()
+    }
+
+    #[test]
+    fn cross_hatch_darkness() {
+        let white = filtered_rectangle(AlphaPixel::white(), Box::new(CrossHatchFilter::new(4.0, 0.0)));
+        let grey = filtered_rectangle(rgba!(128, 128, 128, 255), Box::new(CrossHatchFilter::new(4.0, 0.0)));
+        let black = filtered_rectangle(AlphaPixel::black(), Box::new(CrossHatchFilter::new(4.0, 0.0)));
+
+        assert_eq!(ink_count(&white), 0);
+        assert!(ink_count(&grey) > 0);
+        assert!(ink_count(&black) > ink_count(&grey));
+    }
+
+    #[test]
+    fn cross_hatch_lines() {
+        let light_grey = filtered_rectangle(rgba!(180, 180, 180, 255), Box::new(CrossHatchFilter::new(4.0, 0.0)));
+
+        // Only vertical lines every 4 pixels
+        assert_eq!(light_grey.filtered_pixel_at(0, 7).unwrap(), AlphaPixel::black());
+        assert_eq!(light_grey.filtered_pixel_at(4, 3).unwrap(), AlphaPixel::black());
+        assert_eq!(light_grey.filtered_pixel_at(1, 7).unwrap(), AlphaPixel::white());
+    }
+
+    #[test]
+    fn stipple_darkness() {
+        let white = filtered_rectangle(AlphaPixel::white(), Box::new(StippleFilter::new(5.0, 30.0)));
+        let grey = filtered_rectangle(rgba!(128, 128, 128, 255), Box::new(StippleFilter::new(5.0, 30.0)));
+        let black = filtered_rectangle(AlphaPixel::black(), Box::new(StippleFilter::new(5.0, 30.0)));
+
+        assert_eq!(ink_count(&white), 0);
+        assert!(ink_count(&grey) > 0);
+        assert!(ink_count(&black) > ink_count(&grey));
+    }
+}
@@ -1,7 +1,34 @@
-use crate::AlphaPixel;
+use crate::{AlphaPixel, PixelChannel, Rect, bitmap::sampler::weighted_average};
 
 pub mod transform;
 pub mod brightness;
+pub mod reflection;
+pub mod retro;
+pub mod hatching;
+pub mod gradient_map;
+pub mod edge_mode;
+pub mod combinators;
+pub mod color_blindness;
+
+#[derive(Debug, Clone, Copy)]
+/// The geometry of the layer that is being filtered, passed to each filter method that takes a coordinate.
+/// 
+/// This lets filters work relative to the layer, such as flipping it around its own center,
+/// without having the layer's position hard-coded into them.
+pub struct FilterContext {
+    /// The bounding `Rect` of the layer, before it is filtered
+    pub layer_rect: Rect,
+    /// The width and height of the canvas that the layer is drawn on.
+    /// 
+    /// This is `None` if the layer is sampled outside of a canvas, such as with [`Layer::filtered_pixel_at`](crate::Layer::filtered_pixel_at).
+    pub canvas_size: Option<(usize, usize)>
+}
+
+impl FilterContext {
+    pub fn new(layer_rect: Rect, canvas_size: Option<(usize, usize)>) -> Self {
+        Self { layer_rect, canvas_size }
+    }
+}
 
 /// This trait is used for types that can be added to layers to filter them.
 pub trait Filter<T> {
@@ -12,6 +39,19 @@ pub trait Filter<T> {
         pixel
     }
 
+    /// This method is used to filter the colour of an image, for filters that depend on
+    /// where the pixel is drawn.
+    /// 
+    /// `x` and `y` are the coordinate that was passed to this filter's `filter_transform`.
+    /// By default, this calls `filter_pixel`.
+    /// 
+    /// The colour of a pixel is filtered after it has been sampled through all of the filters of a layer,
+    /// by each filter in order, so the first filter's colour is applied first. Filters wrapped by a combinator,
+    /// such as [`BlendFilter`](combinators::BlendFilter), are filtered when the combinator samples the layer instead.
+    fn filter_pixel_at(&self, pixel: AlphaPixel<T>, _x: isize, _y: isize, _context: &FilterContext) -> AlphaPixel<T> {
+        self.filter_pixel(pixel)
+    }
+
     /// This method is used to filter the location that the pixel is sampled from.
     /// 
     /// It takes the coordinate of the pixel that is being sampled, and returns
@@ -19,7 +59,78 @@ pub trait Filter<T> {
     /// 
     /// This means that the actual transformations that are applied to the layer
     /// are inverted.
-    fn filter_transform(&self, x: usize, y: usize) -> (usize, usize) {
-        (x, y)
+    /// 
+    /// Coordinates are signed, so a layer can be moved to and from the left of or above the canvas.
+    /// Returns `None` if nothing of the layer is drawn at the coordinate.
+    fn filter_transform(&self, x: isize, y: isize, _context: &FilterContext) -> Option<(isize, isize)> {
+        Some((x, y))
+    }
+
+    /// This method is used to filter the location that the pixel is sampled from, for filters that
+    /// sample several locations and average them, such as to antialias the edges of a transformed layer.
+    /// 
+    /// It calls `sample` with each transformed coordinate, and its weight. The weights should add up to 1.0.
+    /// Coordinates outside of the layer count as transparent, so a pixel on the edge of the layer is
+    /// partially transparent by how much of it is covered.
+    /// 
+    /// By default, this calls `sample` once, with the coordinate from `filter_transform` and a weight of 1.0,
+    /// or doesn't call it if `filter_transform` returns `None`.
+    fn filter_transform_weighted(&self, x: isize, y: isize, context: &FilterContext, sample: &mut dyn FnMut(isize, isize, f32)) {
+        if let Some((transformed_x, transformed_y)) = self.filter_transform(x, y, context) {
+            sample(transformed_x, transformed_y, 1.0);
+        }
+    }
+
+    /// This method is used by filters that need to sample the layer at more than one location.
+    /// 
+    /// `sample` gets the pixel of the layer at a coordinate, after it has been sampled through the
+    /// filters following this one, but before any colour is filtered. It returns `None` if the coordinate
+    /// is outside of the layer.
+    /// 
+    /// By default, this averages the coordinates from `filter_transform_weighted`. The colour is filtered
+    /// with `filter_pixel_at` afterwards, so this shouldn't call it.
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>>
+    where T: PixelChannel {
+        sample_weighted(self, x, y, context, sample)
+    }
+
+    /// Create a copy of this filter for a layer drawn `factor` times larger, such as by [`Layer::scaled`](crate::Layer::scaled),
+    /// so that the filter looks the same on a larger canvas.
+    /// 
+    /// This returns `None` by default, and the canvas upscales the layer's pixels instead.
+    fn scaled(&self, _factor: f32) -> Option<Box<dyn Filter<T>>> {
+        None
+    }
+}
+
+/// Average the layer at the coordinates from [`Filter::filter_transform_weighted`]. This is the default [`Filter::filter_sample`].
+pub(crate) fn sample_weighted<T: PixelChannel, F: Filter<T> + ?Sized>(filter: &F, x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+    weighted_average(|weight| filter.filter_transform_weighted(x, y, context, weight), sample)
+}
+
+/// Sample `sample` through each of `filters`, where the first filter receives (`x`, `y`) and each following filter
+/// receives the coordinate sampled by the filter before it. The colour of the pixel isn't filtered.
+pub(crate) fn sample_filters<T: PixelChannel>(filters: &[Box<dyn Filter<T>>], x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+    match filters.split_first() {
+        Some((filter, remaining)) => filter.filter_sample(x, y, context, &|x, y| sample_filters(remaining, x, y, context, sample)),
+        None => sample(x, y)
+    }
+}
+
+/// Filter the colour of `pixel` with each of `filters` in order, where the first filter receives (`x`, `y`) and each
+/// following filter receives the coordinate transformed by the filters before it.
+pub(crate) fn filter_colours<T: PixelChannel>(filters: &[Box<dyn Filter<T>>], mut pixel: AlphaPixel<T>, x: isize, y: isize, context: &FilterContext) -> AlphaPixel<T> {
+    let mut coord = (x, y);
+    for filter in filters {
+        pixel = filter.filter_pixel_at(pixel, coord.0, coord.1, context);
+        // Filters which don't draw the layer at a coordinate still filter the pixel sampled around it
+        coord = filter.filter_transform(coord.0, coord.1, context).unwrap_or(coord);
     }
+    pixel
+}
+
+/// Sample a single filter and filter the colour of the pixel, as a layer with only `filter` would.
+pub(crate) fn apply_filter<T: PixelChannel>(filter: &dyn Filter<T>, x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+    let pixel = filter.filter_sample(x, y, context, sample)?;
+    Some(filter.filter_pixel_at(pixel, x, y, context))
 }
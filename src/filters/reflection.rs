@@ -0,0 +1,100 @@
+use crate::{Filter, FilterContext, AlphaPixel, PixelChannel};
+
+/// A filter that draws a vertically flipped, faded copy of the layer beneath it.
+/// 
+/// The layer is reflected across the horizontal line at `axis_y`, which is usually the bottom edge
+/// of the layer. The reflection starts `gap` pixels below the axis, and its alpha falls off linearly
+/// from `opacity` to zero over `falloff` pixels.
+pub struct ReflectionFilter {
+    pub axis_y: usize,
+    pub gap: usize,
+    pub falloff: usize,
+    pub opacity: f32
+}
+
+impl ReflectionFilter {
+    /// Create a reflection across `axis_y`, with a starting opacity of 0.5.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::layers::shapes::RectangleLayer;
+    /// use image_template::filters::reflection::ReflectionFilter;
+    /// use image_template::{Rect, AlphaPixel};
+    /// 
+    /// let rect = Rect { x: 10, y: 10, width: 50, height: 50 };
+    /// let reflection_filter = Box::new(ReflectionFilter::new(rect.bottom_y(), 5, 30));
+    /// let reflected_rectangle: RectangleLayer<u8> = RectangleLayer {
+    ///     rect,
+    ///     fill: AlphaPixel::red(),
+    ///     filters: vec![reflection_filter]
+    /// };
+    /// ```
+    pub fn new(axis_y: usize, gap: usize, falloff: usize) -> Self {
+        Self { axis_y, gap, falloff, opacity: 0.5 }
+    }
+
+    /// Get the distance of `y` below the top of the reflection, if it is within the reflection.
+    fn reflection_distance(&self, y: isize) -> Option<usize> {
+        let reflection_start = (self.axis_y + self.gap) as isize;
+        if y >= reflection_start && ((y - reflection_start) as usize) < self.falloff {
+            Some((y - reflection_start) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for ReflectionFilter {
+    fn filter_pixel_at(&self, pixel: AlphaPixel<T>, _x: isize, y: isize, _context: &FilterContext) -> AlphaPixel<T> {
+        match self.reflection_distance(y) {
+            Some(distance) => {
+                let fade = 1.0 - distance as f32 / self.falloff as f32;
+                pixel.scale_alpha(self.opacity * fade)
+            },
+            None => pixel
+        }
+    }
+
+    fn filter_transform(&self, x: isize, y: isize, _context: &FilterContext) -> Option<(isize, isize)> {
+        match self.reflection_distance(y) {
+            Some(distance) => Some((x, self.axis_y as isize - distance as isize - 1)),
+            None => Some((x, y))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layers::shapes::RectangleLayer, Layer, Rect, rgba};
+
+    fn reflected_rectangle() -> RectangleLayer<u8> {
+        RectangleLayer {
+            rect: Rect { x: 0, y: 0, width: 4, height: 4 },
+            fill: rgba!(255, 0, 0, 200),
+            filters: vec![Box::new(ReflectionFilter::new(4, 2, 4))]
+        }
+    }
+
+    #[test]
+    fn reflection_bounds() {
+        let rectangle = reflected_rectangle();
+
+        assert_eq!(rectangle.filtered_pixel_at(1, 1).unwrap(), rgba!(255, 0, 0, 200));
+        assert!(rectangle.filtered_pixel_at(1, 4).is_none());
+        assert!(rectangle.filtered_pixel_at(1, 5).is_none());
+        assert!(rectangle.filtered_pixel_at(1, 6).is_some());
+        assert!(rectangle.filtered_pixel_at(1, 9).is_some());
+        assert!(rectangle.filtered_pixel_at(1, 10).is_none());
+        assert!(rectangle.filtered_pixel_at(4, 6).is_none());
+    }
+
+    #[test]
+    fn reflection_falloff() {
+        let rectangle = reflected_rectangle();
+
+        assert_eq!(rectangle.filtered_pixel_at(0, 6).unwrap(), rgba!(255, 0, 0, 100));
+        assert_eq!(rectangle.filtered_pixel_at(0, 7).unwrap(), rgba!(255, 0, 0, 75));
+        assert_eq!(rectangle.filtered_pixel_at(0, 9).unwrap(), rgba!(255, 0, 0, 25));
+    }
+}
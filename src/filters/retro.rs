@@ -0,0 +1,195 @@
+use crate::{Filter, FilterContext, AlphaPixel, PixelChannel, rng::hash_unit};
+
+fn multiply_color<T: PixelChannel>(pixel: AlphaPixel<T>, multiplier: f32) -> AlphaPixel<T> {
+    let maximum = T::MAX_PIXEL_VALUE.into();
+    let minimum = T::MIN_PIXEL_VALUE.into();
+
+    AlphaPixel {
+        r: T::from_f32((pixel.r.into() * multiplier).min(maximum).max(minimum)).unwrap(),
+        g: T::from_f32((pixel.g.into() * multiplier).min(maximum).max(minimum)).unwrap(),
+        b: T::from_f32((pixel.b.into() * multiplier).min(maximum).max(minimum)).unwrap(),
+        a: pixel.a
+    }
+}
+
+/// A filter that darkens horizontal lines across the layer, like the scanlines of a CRT display.
+/// 
+/// Every `spacing` rows, `thickness` rows are darkened by `intensity` (0.0 leaves them unchanged, 1.0 makes them black).
+pub struct ScanlineFilter {
+    pub spacing: usize,
+    pub thickness: usize,
+    pub intensity: f32
+}
+
+impl<T: PixelChannel> Filter<T> for ScanlineFilter {
+    fn filter_pixel_at(&self, pixel: AlphaPixel<T>, _x: isize, y: isize, _context: &FilterContext) -> AlphaPixel<T> {
+        if self.spacing != 0 && (y.rem_euclid(self.spacing as isize) as usize) < self.thickness {
+            multiply_color(pixel, 1.0 - self.intensity)
+        } else {
+            pixel
+        }
+    }
+}
+
+/// A filter that splits the RGB channels of a layer horizontally, and shifts bands of rows by random offsets.
+/// 
+/// The red channel is sampled `channel_offset` pixels to the left, and the blue channel `channel_offset` pixels
+/// to the right. Each band of `band_height` rows is shifted with a probability of `band_probability`, by up to
+/// `max_band_offset` pixels in either direction.
+/// 
+/// The offsets are generated from `seed`, so the same seed will always produce the same glitch.
+pub struct GlitchFilter {
+    pub seed: u64,
+    pub channel_offset: isize,
+    pub band_height: usize,
+    pub band_probability: f32,
+    pub max_band_offset: isize
+}
+
+impl GlitchFilter {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, channel_offset: 3, band_height: 8, band_probability: 0.2, max_band_offset: 20 }
+    }
+
+    /// Get the horizontal offset of the band containing row `y`
+    fn band_offset(&self, y: isize) -> isize {
+        if self.band_height == 0 {
+            return 0
+        }
+
+        let band = y.div_euclid(self.band_height as isize) as u64;
+        if hash_unit(self.seed, band*2) < self.band_probability {
+            let offset = (hash_unit(self.seed, band*2 + 1) * 2.0 - 1.0) * self.max_band_offset as f32;
+            offset.round() as isize
+        } else {
+            0
+        }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for GlitchFilter {
+    fn filter_transform(&self, x: isize, y: isize, _context: &FilterContext) -> Option<(isize, isize)> {
+        Some((x.checked_sub(self.band_offset(y))?, y))
+    }
+
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, sample: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+        let (shifted_x, y) = Filter::<T>::filter_transform(self, x, y, context)?;
+
+        let red = sample(shifted_x.saturating_sub(self.channel_offset), y);
+        let green = sample(shifted_x, y);
+        let blue = sample(shifted_x.saturating_add(self.channel_offset), y);
+
+        if red.is_none() && green.is_none() && blue.is_none() {
+            return None
+        }
+
+        let alpha = [red, green, blue].iter()
+            .flatten()
+            .map(|p| p.a)
+            .fold(T::MIN_PIXEL_VALUE, |a, b| if b > a { b } else { a });
+
+        Some(AlphaPixel {
+            r: red.map(|p| p.r).unwrap_or(T::zero()),
+            g: green.map(|p| p.g).unwrap_or(T::zero()),
+            b: blue.map(|p| p.b).unwrap_or(T::zero()),
+            a: alpha
+        })
+    }
+}
+
+/// A filter that adds horizontal bands of static noise to a layer, like a worn VHS tape.
+/// 
+/// Each band of `band_height` rows contains noise with a probability of `band_probability`. In a noisy band,
+/// each pixel's colour is mixed with random grey by up to `noise_strength`.
+/// 
+/// The noise is generated from `seed`, so the same seed will always produce the same noise.
+pub struct VhsNoiseFilter {
+    pub seed: u64,
+    pub band_height: usize,
+    pub band_probability: f32,
+    pub noise_strength: f32
+}
+
+impl VhsNoiseFilter {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, band_height: 4, band_probability: 0.1, noise_strength: 0.5 }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for VhsNoiseFilter {
+    fn filter_pixel_at(&self, pixel: AlphaPixel<T>, x: isize, y: isize, _context: &FilterContext) -> AlphaPixel<T> {
+        if self.band_height == 0 {
+            return pixel
+        }
+
+        let band = y.div_euclid(self.band_height as isize) as u64;
+        if hash_unit(self.seed, band) >= self.band_probability {
+            return pixel
+        }
+
+        let pixel_hash = ((y as u64) << 32) ^ x as u64;
+        let noise = hash_unit(self.seed.wrapping_add(band), pixel_hash);
+        let amount = noise * self.noise_strength;
+        let grey = noise * T::MAX_PIXEL_VALUE.into();
+        let mix = |channel: T| T::from_f32(channel.into() * (1.0 - amount) + grey * amount).unwrap();
+
+        AlphaPixel { r: mix(pixel.r), g: mix(pixel.g), b: mix(pixel.b), a: pixel.a }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layers::image::ImageLayer, layers::shapes::RectangleLayer, Image, Layer, Rect, rgba};
+
+    #[test]
+    fn scanlines() {
+        let rectangle: RectangleLayer<u8> = RectangleLayer {
+            rect: Rect { x: 0, y: 0, width: 10, height: 10 },
+            fill: rgba!(200, 100, 50, 255),
+            filters: vec![Box::new(ScanlineFilter { spacing: 4, thickness: 1, intensity: 0.5 })]
+        };
+
+        assert_eq!(rectangle.filtered_pixel_at(3, 0).unwrap(), rgba!(100, 50, 25, 255));
+        assert_eq!(rectangle.filtered_pixel_at(3, 1).unwrap(), rgba!(200, 100, 50, 255));
+        assert_eq!(rectangle.filtered_pixel_at(3, 4).unwrap(), rgba!(100, 50, 25, 255));
+    }
+
+    #[test]
+    fn glitch_channel_split() {
+        let image = Image::from_function(3, 1, |x, _| [AlphaPixel::red(), AlphaPixel::green(), AlphaPixel::blue()][x]);
+        let mut layer: ImageLayer<u8> = ImageLayer::new(image, 1, 0);
+        layer.filters.push(Box::new(GlitchFilter { channel_offset: 1, band_probability: 0.0, ..GlitchFilter::new(0) }));
+
+        assert_eq!(layer.filtered_pixel_at(0, 0).unwrap(), rgba!(0, 0, 0, 255));
+        assert_eq!(layer.filtered_pixel_at(2, 0).unwrap(), rgba!(255, 255, 255, 255));
+        assert_eq!(layer.filtered_pixel_at(4, 0).unwrap(), rgba!(0, 0, 0, 255));
+        assert!(layer.filtered_pixel_at(5, 0).is_none());
+    }
+
+    #[test]
+    fn glitch_seeded() {
+        let image = Image::from_function(50, 50, |x, y| rgba!(x as u8, y as u8, 0, 255));
+        let mut first: ImageLayer<u8> = ImageLayer::new(image.clone(), 0, 0);
+        first.filters.push(Box::new(GlitchFilter { band_probability: 1.0, ..GlitchFilter::new(42) }));
+        let mut second: ImageLayer<u8> = ImageLayer::new(image, 0, 0);
+        second.filters.push(Box::new(GlitchFilter { band_probability: 1.0, ..GlitchFilter::new(42) }));
+
+        for y in 0..50 {
+            for x in 0..50 {
+                assert_eq!(first.filtered_pixel_at(x, y), second.filtered_pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn vhs_noise_probability() {
+        let fill = rgba!(200u8, 100, 50, 255);
+        let mut rectangle = RectangleLayer::new(fill, Rect { x: 0, y: 0, width: 20, height: 20 });
+        rectangle.filters.push(Box::new(VhsNoiseFilter { band_probability: 0.0, ..VhsNoiseFilter::new(1) }));
+        assert!((0..20).all(|y| rectangle.filtered_pixel_at(5, y).unwrap() == fill));
+
+        rectangle.filters = vec![Box::new(VhsNoiseFilter { band_probability: 1.0, noise_strength: 1.0, ..VhsNoiseFilter::new(1) })];
+        assert!((0..20).any(|y| rectangle.filtered_pixel_at(5, y).unwrap() != fill));
+    }
+}
@@ -1,5 +1,6 @@
 use num_traits::Inv;
-use crate::Filter;
+use crate::{Filter, FilterContext, AlphaPixel, PixelChannel, Rect, filters::sample_weighted};
+use crate::bitmap::{sampler::{Sampler, BilinearSampler, weighted_average}, mipmap::Mipmaps, average::PremultipliedSum};
 
 /// A filter to translate (move) the layer in 2D space.
 #[derive(Default)]
@@ -9,43 +10,288 @@ pub struct TranslateFilter {
 }
 
 impl<T> Filter<T> for TranslateFilter {
-    fn filter_transform(&self, x: usize, y: usize) -> (usize, usize) {
-        (x.wrapping_add_signed(-self.x), y.wrapping_add_signed(-self.y))
+    fn filter_transform(&self, x: isize, y: isize, _context: &FilterContext) -> Option<(isize, isize)> {
+        Some((x.checked_sub(self.x)?, y.checked_sub(self.y)?))
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Filter<T>>> {
+        let scale = |distance: isize| (distance as f32 * factor).round() as isize;
+        Some(Box::new(TranslateFilter { x: scale(self.x), y: scale(self.y) }))
+    }
+}
+
+/// A filter to flip (mirror) the layer horizontally and/or vertically, around the center of its own `Rect`.
+/// 
+/// # Example
+/// ```
+/// use image_template::layers::image::ImageLayer;
+/// use image_template::filters::transform::FlipFilter;
+/// use image_template::{Image, AlphaPixel, Layer};
+/// 
+/// let image: Image<u8> = Image::from_pixels(vec![AlphaPixel::red(), AlphaPixel::blue()], 2).unwrap();
+/// let mut layer = ImageLayer::new(image, 10, 10);
+/// layer.filters.push(Box::new(FlipFilter { horizontal: true, vertical: false }));
+/// assert_eq!(layer.filtered_pixel_at(10, 10).unwrap(), AlphaPixel::blue());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlipFilter {
+    pub horizontal: bool,
+    pub vertical: bool
+}
+
+impl<T> Filter<T> for FlipFilter {
+    fn filter_transform(&self, x: isize, y: isize, context: &FilterContext) -> Option<(isize, isize)> {
+        let rect = context.layer_rect;
+        // The first and last pixels of the rect swap places
+        let x = if self.horizontal { ((rect.x + rect.right_x()) as isize - 1).checked_sub(x)? } else { x };
+        let y = if self.vertical { ((rect.y + rect.bottom_y()) as isize - 1).checked_sub(y)? } else { y };
+        Some((x, y))
+    }
+
+    fn scaled(&self, _factor: f32) -> Option<Box<dyn Filter<T>>> {
+        Some(Box::new(*self))
+    }
+}
+
+/// The point that a transformation is around.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransformOrigin {
+    /// A point on the canvas
+    Absolute(f32, f32),
+    /// A point in the layer's `Rect`, as a fraction of its width and height, so (0.5, 0.5) is its center.
+    /// 
+    /// This is resolved when the layer is drawn, so the origin moves with the layer.
+    Relative(f32, f32)
+}
+
+impl TransformOrigin {
+    /// Get the point on the canvas, for a layer with `rect`.
+    pub fn resolve(&self, rect: Rect) -> (f32, f32) {
+        match *self {
+            TransformOrigin::Absolute(x, y) => (x, y),
+            TransformOrigin::Relative(x, y) => (rect.x as f32 + rect.width as f32 * x, rect.y as f32 + rect.height as f32 * y)
+        }
     }
 }
 
 /// A filter to transform a layer by a matrix linear transformation.
+/// 
+/// The transformation is around its [`TransformOrigin`], which is either a point on the canvas, or a point relative
+/// to the layer's `Rect`, such as its center.
+/// 
+/// By default, each pixel is sampled from the single pixel of the layer that it is transformed onto.
+/// A smoother [`Sampler`] can be set with [`MatrixTransform::with_sampler`], and the edges of the layer
+/// can be antialiased with [`MatrixTransform::with_supersampling`].
+/// 
+/// A transform which shrinks a detailed layer a lot skips most of its pixels, which aliases. An image can be sampled
+/// from its [`Mipmaps`] instead with [`MatrixTransform::with_mipmaps`].
 pub struct MatrixTransform {
     pub matrix: [f32; 4],
-    pub center_x: f32,
-    pub center_y: f32
+    pub origin: TransformOrigin,
+    pub sampler: Option<Box<dyn Sampler>>,
+    /// The number of samples taken along each axis of a pixel, which are averaged. 1 takes a single sample.
+    pub supersampling: usize
 }
 
-impl<T> Filter<T> for MatrixTransform {
-    fn filter_transform(&self, x: usize, y: usize) -> (usize, usize) {
-        let relative_x = x as f32 - self.center_x;
-        let relative_y = y as f32 - self.center_y;
+impl MatrixTransform {
+    /// Get the non-integer coordinate of the layer that is drawn at (`x`, `y`)
+    fn transform_point(&self, x: f32, y: f32, context: &FilterContext) -> (f32, f32) {
+        let (center_x, center_y) = self.origin.resolve(context.layer_rect);
+
+        let relative_x = x - center_x;
+        let relative_y = y - center_y;
 
         let new_x = relative_x * self.matrix[0] + relative_y * self.matrix[1];
         let new_y = relative_x * self.matrix[2] + relative_y * self.matrix[3];
 
-        let uncentered_new_x = new_x + self.center_x;
-        let uncentered_new_y = new_y + self.center_y;
+        (new_x + center_x, new_y + center_y)
+    }
+
+    /// The number of pixels of the layer that each pixel of the transformed layer covers, along its more shrunk axis.
+    /// This is more than 1 when the layer is shrunk.
+    fn minification(&self) -> f32 {
+        // The matrix maps canvas coordinates onto the layer, so its columns are how far a step along each axis moves on the layer
+        let step_x = self.matrix[0].hypot(self.matrix[2]);
+        let step_y = self.matrix[1].hypot(self.matrix[3]);
+        step_x.max(step_y)
+    }
+
+    /// Call `point` with the transformed coordinate of each supersample of the pixel at (`x`, `y`), and its weight.
+    fn supersample_points(&self, x: isize, y: isize, context: &FilterContext, point: &mut dyn FnMut(f32, f32, f32)) {
+        supersample_offsets(self.supersampling, &mut |offset_x, offset_y, weight| {
+            let (new_x, new_y) = self.transform_point(x as f32 + offset_x, y as f32 + offset_y, context);
+            // A matrix which isn't finite has nothing to sample
+            if new_x.is_finite() && new_y.is_finite() {
+                point(new_x, new_y, weight);
+            }
+        });
+    }
+}
+
+/// Call `offset` with the offset of each of `samples` by `samples` points spread evenly around a pixel's coordinate,
+/// and its weight.
+fn supersample_offsets(samples: usize, offset: &mut dyn FnMut(f32, f32, f32)) {
+    let samples = samples.max(1);
+    let weight = 1.0 / (samples*samples) as f32;
+    for sample_y in 0..samples {
+        for sample_x in 0..samples {
+            offset((sample_x as f32 + 0.5) / samples as f32 - 0.5, (sample_y as f32 + 0.5) / samples as f32 - 0.5, weight);
+        }
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for MatrixTransform {
+    fn filter_transform(&self, x: isize, y: isize, context: &FilterContext) -> Option<(isize, isize)> {
+        let (new_x, new_y) = self.transform_point(x as f32, y as f32, context);
+        if !new_x.is_finite() || !new_y.is_finite() {
+            return None
+        }
+        Some((new_x as isize, new_y as isize))
+    }
+
+    fn filter_transform_weighted(&self, x: isize, y: isize, context: &FilterContext, sample: &mut dyn FnMut(isize, isize, f32)) {
+        if self.supersampling <= 1 {
+            if let Some((transformed_x, transformed_y)) = Filter::<T>::filter_transform(self, x, y, context) {
+                sample(transformed_x, transformed_y, 1.0);
+            }
+            return
+        }
 
-        // If coordinates are negative, then return usize::MAX (this can't be a valid coordinate)
-        (
-            (uncentered_new_x as i32).try_into().unwrap_or(usize::MAX),
-            (uncentered_new_y as i32).try_into().unwrap_or(usize::MAX)
+        self.supersample_points(x, y, context, &mut |new_x, new_y, weight| {
+            sample(new_x.floor() as isize, new_y.floor() as isize, weight);
+        });
+    }
+
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, sample_layer: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+        let Some(sampler) = &self.sampler else {
+            return sample_weighted(self, x, y, context, sample_layer)
+        };
+
+        // The transform maps pixel coordinates, so each supersample is taken at the center of the transformed pixel
+        weighted_average(
+            |weight| self.supersample_points(x, y, context, &mut |new_x, new_y, point_weight| {
+                sampler.weights(new_x + 0.5, new_y + 0.5, &mut |sample_x, sample_y, sampler_weight| weight(sample_x, sample_y, point_weight*sampler_weight));
+            }),
+            sample_layer
         )
     }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Filter<T>>> {
+        // Samplers can't be copied
+        if self.sampler.is_some() {
+            return None
+        }
+        Some(Box::new(MatrixTransform {
+            matrix: self.matrix,
+            origin: match self.origin {
+                TransformOrigin::Absolute(x, y) => TransformOrigin::Absolute(x*factor, y*factor),
+                relative => relative
+            },
+            sampler: None,
+            supersampling: self.supersampling
+        }))
+    }
 }
 
 impl MatrixTransform {
+    /// Create a transformation around (`center_x`, `center_y`) on the canvas.
     pub fn new(center_x: f32, center_y: f32) -> Self {
+        Self::with_origin(TransformOrigin::Absolute(center_x, center_y))
+    }
+
+    pub fn with_origin(origin: TransformOrigin) -> Self {
         // Identity matrix
-        Self { matrix: [1.0, 0.0, 0.0, 1.0], center_x, center_y }
+        Self { matrix: [1.0, 0.0, 0.0, 1.0], origin, sampler: None, supersampling: 1 }
+    }
+
+    /// Create a transformation around the center of the layer's `Rect`, wherever the layer is.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::layers::shapes::RectangleLayer;
+    /// use image_template::filters::transform::MatrixTransform;
+    /// use image_template::{Rect, AlphaPixel};
+    /// 
+    /// // Spin the rectangle in place
+    /// let mut rectangle: RectangleLayer<u8> = RectangleLayer::new(AlphaPixel::black(), Rect { x: 100, y: 50, width: 40, height: 10 });
+    /// rectangle.filters.push(Box::new(MatrixTransform::around_layer_center().rotate(45.0)));
+    /// ```
+    pub fn around_layer_center() -> Self {
+        Self::with_origin(TransformOrigin::Relative(0.5, 0.5))
+    }
+
+    /// Create a transformation around a point of the layer's `Rect`, as a fraction of its width and height,
+    /// wherever the layer is.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::layers::shapes::RectangleLayer;
+    /// use image_template::filters::transform::MatrixTransform;
+    /// use image_template::{Rect, AlphaPixel, Layer};
+    /// 
+    /// // Swing the rectangle down around its top left corner, like a hinge
+    /// let mut rectangle: RectangleLayer<u8> = RectangleLayer::new(AlphaPixel::black(), Rect { x: 100, y: 50, width: 40, height: 10 });
+    /// rectangle.filters.push(Box::new(MatrixTransform::around_layer_point(0.0, 0.0).rotate(90.0)));
+    /// assert!(rectangle.filtered_pixel_at(95, 80).is_some());
+    /// 
+    /// // The hinge moves with the rectangle
+    /// rectangle.rect.x = 200;
+    /// assert!(rectangle.filtered_pixel_at(195, 80).is_some());
+    /// ```
+    pub fn around_layer_point(x: f32, y: f32) -> Self {
+        Self::with_origin(TransformOrigin::Relative(x, y))
+    }
+
+    /// Set the sampler used to get the layer's pixels at transformed coordinates.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::filters::transform::MatrixTransform;
+    /// use image_template::bitmap::sampler::BilinearSampler;
+    /// 
+    /// let smooth_rotation = MatrixTransform::new(50.0, 50.0).rotate(30.0).with_sampler(BilinearSampler);
+    /// ```
+    pub fn with_sampler<S: Sampler + 'static>(mut self, sampler: S) -> Self {
+        self.sampler = Some(Box::new(sampler));
+        self
+    }
+
+    /// Average `samples` by `samples` points within each pixel, so that the edges of the transformed layer are antialiased.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::filters::transform::MatrixTransform;
+    /// 
+    /// let smooth_edges = MatrixTransform::new(50.0, 50.0).rotate(30.0).with_supersampling(4);
+    /// ```
+    pub fn with_supersampling(mut self, samples: usize) -> Self {
+        self.supersampling = samples;
+        self
     }
     
+    /// Sample an image from `mipmaps` at the level of detail that the transform shrinks it to, instead of sampling the layer,
+    /// so that a large image can be shrunk without aliasing.
+    /// 
+    /// The top left of the image is at (`x`, `y`) on the canvas. This should be the image drawn by the layer, such as the
+    /// image of an [`ImageLayer`](crate::layers::image::ImageLayer) at the same position.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Image, AlphaPixel, Layer, layers::image::ImageLayer, filters::transform::MatrixTransform};
+    /// 
+    /// // A checkerboard of single pixels averages to grey when it is shrunk, rather than aliasing
+    /// let checkerboard: Image<u8> = Image::from_function(64, 64, |x, y| if (x + y) % 2 == 0 { AlphaPixel::black() } else { AlphaPixel::white() });
+    /// let transform = MatrixTransform::new(0.0, 0.0).scale(0.125).with_mipmaps(checkerboard.build_mipmaps(), 0, 0);
+    /// 
+    /// let mut layer = ImageLayer::new(checkerboard, 0, 0);
+    /// layer.filters.push(Box::new(transform));
+    /// let pixel = layer.filtered_pixel_at(4, 4).unwrap();
+    /// assert!((120..=135).contains(&pixel.r));
+    /// ```
+    pub fn with_mipmaps<T: PixelChannel>(self, mipmaps: Mipmaps<T>, x: usize, y: usize) -> MipmappedTransform<T> {
+        MipmappedTransform { transform: self, mipmaps, x, y }
+    }
+
     /// Apply the **INVERSE** matrix of the transformation to be applied to the layer.
     /// 
     /// This is because transform filters map the transformed location on to the original location.
@@ -159,10 +405,222 @@ impl MatrixTransform {
     }
 }
 
+/// A [`MatrixTransform`] of an image, which samples the image from its [`Mipmaps`] at the level of detail that the
+/// transform shrinks it to, rather than sampling the layer. Created with [`MatrixTransform::with_mipmaps`].
+/// 
+/// Each pixel is sampled with the transform's sampler, or bilinearly if it doesn't have one, and its supersampling.
+/// The layer's pixels aren't used, so transform filters after this one don't move the image, but colour filters still apply.
+pub struct MipmappedTransform<T: PixelChannel> {
+    pub transform: MatrixTransform,
+    pub mipmaps: Mipmaps<T>,
+    /// The position of the top left of the image on the canvas
+    pub x: usize,
+    pub y: usize
+}
+
+impl<T: PixelChannel> Filter<T> for MipmappedTransform<T> {
+    fn filter_transform(&self, x: isize, y: isize, context: &FilterContext) -> Option<(isize, isize)> {
+        Filter::<T>::filter_transform(&self.transform, x, y, context)
+    }
+
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, _sample_layer: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+        let sampler: &dyn Sampler = self.transform.sampler.as_deref().unwrap_or(&BilinearSampler);
+        // Each supersample covers a fraction of the pixel
+        let scale = self.transform.minification() / self.transform.supersampling.max(1) as f32;
+
+        let mut sum = PremultipliedSum::default();
+        let mut total_weight = 0.0;
+        let mut found = false;
+        self.transform.supersample_points(x, y, context, &mut |new_x, new_y, weight| {
+            total_weight += weight as f64;
+            // The transform maps pixel coordinates, so each supersample is taken at the center of the transformed pixel
+            let image_x = new_x + 0.5 - self.x as f32;
+            let image_y = new_y + 0.5 - self.y as f32;
+            if let Some(pixel) = self.mipmaps.sample_with(sampler, image_x, image_y, scale) {
+                sum.add(pixel, weight as f64);
+                found = true;
+            }
+        });
+
+        // Supersamples outside of the image count as transparent, so the edges of the image are antialiased
+        found.then(|| sum.average(total_weight))
+    }
+}
+
+/// A filter to transform a layer by a [perspective transformation](https://en.wikipedia.org/wiki/Homography),
+/// which can move the 4 corners of a quadrilateral anywhere, such as to draw a flat image onto a tilted screen.
+/// 
+/// Straight lines stay straight, but unlike a [`MatrixTransform`], parallel lines can converge.
+/// 
+/// As with [`MatrixTransform`], the layer can be sampled smoothly with [`PerspectiveTransform::with_sampler`],
+/// and its edges antialiased with [`PerspectiveTransform::with_supersampling`].
+/// 
+/// # Example
+/// ```
+/// use image_template::layers::shapes::RectangleLayer;
+/// use image_template::filters::transform::PerspectiveTransform;
+/// use image_template::{Rect, AlphaPixel, Layer};
+/// 
+/// // Lean the rectangle back, so that its top edge is narrower
+/// let rect = Rect { x: 0, y: 0, width: 100, height: 100 };
+/// let mut rectangle: RectangleLayer<u8> = RectangleLayer::new(AlphaPixel::red(), rect);
+/// let corners = [(25.0, 0.0), (75.0, 0.0), (100.0, 100.0), (0.0, 100.0)];
+/// rectangle.filters.push(Box::new(PerspectiveTransform::from_rect(rect, corners).unwrap()));
+/// 
+/// assert_eq!(rectangle.filtered_pixel_at(50, 2).unwrap(), AlphaPixel::red());
+/// assert!(rectangle.filtered_pixel_at(5, 2).is_none());
+/// assert_eq!(rectangle.filtered_pixel_at(5, 98).unwrap(), AlphaPixel::red());
+/// ```
+pub struct PerspectiveTransform {
+    /// The row major 3x3 matrix which maps the homogeneous coordinate of a point where the layer is drawn,
+    /// onto the point of the layer that is drawn there
+    pub matrix: [f32; 9],
+    pub sampler: Option<Box<dyn Sampler>>,
+    /// The number of samples taken along each axis of a pixel, which are averaged. 1 takes a single sample.
+    pub supersampling: usize
+}
+
+impl PerspectiveTransform {
+    /// Create a transformation which moves each of the `from` points of the layer to the `to` point at the same index.
+    /// 
+    /// The points are the corners of a quadrilateral in order, such as top left, top right, bottom right, bottom left.
+    /// Returns `None` if either quadrilateral has 3 points in a line, so that there isn't a single transformation.
+    pub fn from_quads(from: [(f32, f32); 4], to: [(f32, f32); 4]) -> Option<Self> {
+        let matrix = solve_homography(to, from)?;
+        Some(Self { matrix, sampler: None, supersampling: 1 })
+    }
+
+    /// Create a transformation which moves the corners of `rect` to `corners`, in the order top left, top right,
+    /// bottom right, bottom left.
+    /// 
+    /// Returns `None` if 3 of the corners are in a line.
+    pub fn from_rect(rect: Rect, corners: [(f32, f32); 4]) -> Option<Self> {
+        let (left, top) = (rect.x as f32, rect.y as f32);
+        let (right, bottom) = (left + rect.width as f32, top + rect.height as f32);
+        Self::from_quads([(left, top), (right, top), (right, bottom), (left, bottom)], corners)
+    }
+
+    /// Set the sampler used to get the layer's pixels at transformed coordinates.
+    pub fn with_sampler<S: Sampler + 'static>(mut self, sampler: S) -> Self {
+        self.sampler = Some(Box::new(sampler));
+        self
+    }
+
+    /// Average `samples` by `samples` points within each pixel, so that the edges of the transformed layer are antialiased.
+    pub fn with_supersampling(mut self, samples: usize) -> Self {
+        self.supersampling = samples;
+        self
+    }
+
+    /// Get the non-integer coordinate of the layer that is drawn at (`x`, `y`), or `None` if nothing is drawn there,
+    /// such as behind the horizon of the transformation.
+    fn transform_point(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let m = &self.matrix;
+        let w = m[6]*x + m[7]*y + m[8];
+        if w <= 0.0 {
+            return None
+        }
+        let new_x = (m[0]*x + m[1]*y + m[2]) / w;
+        let new_y = (m[3]*x + m[4]*y + m[5]) / w;
+        (new_x.is_finite() && new_y.is_finite()).then_some((new_x, new_y))
+    }
+
+    /// Call `point` with the transformed coordinate of each supersample of the pixel at (`x`, `y`), and its weight.
+    /// 
+    /// Unlike a [`MatrixTransform`], the corners of the quadrilateral are at the edges of pixels, so each pixel is
+    /// transformed from its center.
+    fn supersample_points(&self, x: isize, y: isize, point: &mut dyn FnMut(f32, f32, f32)) {
+        supersample_offsets(self.supersampling, &mut |offset_x, offset_y, weight| {
+            if let Some((new_x, new_y)) = self.transform_point(x as f32 + 0.5 + offset_x, y as f32 + 0.5 + offset_y) {
+                point(new_x, new_y, weight);
+            }
+        });
+    }
+}
+
+impl<T: PixelChannel> Filter<T> for PerspectiveTransform {
+    fn filter_transform(&self, x: isize, y: isize, _context: &FilterContext) -> Option<(isize, isize)> {
+        let (new_x, new_y) = self.transform_point(x as f32 + 0.5, y as f32 + 0.5)?;
+        Some((new_x.floor() as isize, new_y.floor() as isize))
+    }
+
+    fn filter_transform_weighted(&self, x: isize, y: isize, context: &FilterContext, sample: &mut dyn FnMut(isize, isize, f32)) {
+        if self.supersampling <= 1 {
+            if let Some((transformed_x, transformed_y)) = Filter::<T>::filter_transform(self, x, y, context) {
+                sample(transformed_x, transformed_y, 1.0);
+            }
+            return
+        }
+
+        self.supersample_points(x, y, &mut |new_x, new_y, weight| {
+            sample(new_x.floor() as isize, new_y.floor() as isize, weight);
+        });
+    }
+
+    fn filter_sample(&self, x: isize, y: isize, context: &FilterContext, sample_layer: &dyn Fn(isize, isize) -> Option<AlphaPixel<T>>) -> Option<AlphaPixel<T>> {
+        let Some(sampler) = &self.sampler else {
+            return sample_weighted(self, x, y, context, sample_layer)
+        };
+
+        weighted_average(
+            |weight| self.supersample_points(x, y, &mut |new_x, new_y, point_weight| {
+                sampler.weights(new_x, new_y, &mut |sample_x, sample_y, sampler_weight| weight(sample_x, sample_y, point_weight*sampler_weight));
+            }),
+            sample_layer
+        )
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Filter<T>>> {
+        if self.sampler.is_some() {
+            return None
+        }
+        // Scale points down onto the layer, transform them, and scale them back up
+        let m = &self.matrix;
+        let matrix = [m[0], m[1], m[2]*factor, m[3], m[4], m[5]*factor, m[6]/factor, m[7]/factor, m[8]];
+        Some(Box::new(PerspectiveTransform { matrix, sampler: None, supersampling: self.supersampling }))
+    }
+}
+
+/// Find the homography which maps each of the `from` points onto the `to` point at the same index, with the last
+/// element of the matrix fixed at 1.0, or `None` if the points don't define one.
+fn solve_homography(from: [(f32, f32); 4], to: [(f32, f32); 4]) -> Option<[f32; 9]> {
+    // Each pair of points gives 2 linear equations in the 8 unknown elements, solved with Gaussian elimination
+    let mut rows = [[0.0f64; 9]; 8];
+    for (index, (&(x, y), &(u, v))) in from.iter().zip(to.iter()).enumerate() {
+        let (x, y, u, v) = (x as f64, y as f64, u as f64, v as f64);
+        rows[index*2] = [x, y, 1.0, 0.0, 0.0, 0.0, -x*u, -y*u, u];
+        rows[index*2 + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -x*v, -y*v, v];
+    }
+
+    for column in 0..8 {
+        let pivot = (column..8).max_by(|&a, &b| rows[a][column].abs().total_cmp(&rows[b][column].abs()))?;
+        if rows[pivot][column].abs() < 1e-9 {
+            return None
+        }
+        rows.swap(column, pivot);
+        let pivot_row = rows[column];
+        for (index, row) in rows.iter_mut().enumerate() {
+            if index != column {
+                let factor = row[column] / pivot_row[column];
+                for (element, pivot_element) in row.iter_mut().zip(pivot_row.iter()).skip(column) {
+                    *element -= factor * pivot_element;
+                }
+            }
+        }
+    }
+
+    let mut matrix = [1.0; 9];
+    for (index, row) in rows.iter().enumerate() {
+        matrix[index] = (row[8] / row[index]) as f32;
+    }
+    Some(matrix)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{layers::shapes::RectangleLayer, AlphaPixel, Canvas, Layer, Rect, rgba};
+    use crate::{layers::{shapes::RectangleLayer, image::ImageLayer}, Canvas, Image, Layer, Rect, rgba};
+    use crate::bitmap::sampler::BilinearSampler;
 
     #[test]
     fn translate_test() {
@@ -182,6 +640,24 @@ mod tests {
         assert!(rectangle.filtered_pixel_at(13, 4).is_some());
     }
 
+    #[test]
+    fn signed_coordinates() {
+        // Moved off the top left of the canvas and back, without losing the pixels in between
+        let mut layer = RectangleLayer::new(AlphaPixel::<u8>::red(), Rect { x: 0, y: 0, width: 4, height: 4 });
+        layer.filters.push(Box::new(TranslateFilter { x: 10, y: 10 }));
+        layer.filters.push(Box::new(TranslateFilter { x: -10, y: -10 }));
+        assert_eq!(layer.filtered_pixel_at(3, 3).unwrap(), AlphaPixel::red());
+        assert!(layer.filtered_pixel_at(4, 4).is_none());
+
+        // Translating by an extreme amount doesn't overflow
+        layer.filters = vec![Box::new(TranslateFilter { x: isize::MIN, y: 0 })];
+        assert!(layer.filtered_pixel_at(0, 0).is_none());
+
+        // A matrix which isn't finite draws nothing, rather than sampling the origin
+        layer.filters = vec![Box::new(MatrixTransform::new(0.0, 0.0).scale(0.0).rotate(f32::NAN))];
+        assert!(layer.filtered_pixel_at(0, 0).is_none());
+    }
+
     #[test]
     fn rotate_test() {
         let rotated_image = [
@@ -201,4 +677,144 @@ mod tests {
         let image = canvas.flatten();
         assert_eq!(image.get_pixels(), rotated_image);
     }
+
+    #[test]
+    fn flip_test() {
+        let image = Image::from_function(3, 2, |x, y| rgba!(x as u8, y as u8, 0, 255));
+        let mut layer = ImageLayer::new(image, 5, 7);
+        layer.filters.push(Box::new(FlipFilter { horizontal: true, vertical: true }));
+
+        assert_eq!(layer.filtered_pixel_at(5, 7).unwrap(), rgba!(2, 1, 0, 255));
+        assert_eq!(layer.filtered_pixel_at(6, 8).unwrap(), rgba!(1, 0, 0, 255));
+        assert!(layer.filtered_pixel_at(4, 7).is_none());
+    }
+
+    #[test]
+    fn around_layer_center() {
+        let rectangle = |x: usize| RectangleLayer {
+            rect: Rect { x, y: 0, width: 10, height: 2 },
+            fill: AlphaPixel::<u8>::red(),
+            filters: vec![Box::new(MatrixTransform::around_layer_center().rotate(90.0))]
+        };
+
+        // Rotating in place makes the rectangle tall, wherever it is
+        for x in [4, 40] {
+            let rotated = rectangle(x);
+            assert!(rotated.filtered_pixel_at(x + 5, 4).is_some());
+            assert!(rotated.filtered_pixel_at(x + 1, 1).is_none());
+        }
+    }
+
+    #[test]
+    fn relative_origin() {
+        let rect = Rect { x: 10, y: 20, width: 40, height: 10 };
+        assert_eq!(TransformOrigin::Relative(0.5, 0.5).resolve(rect), (30.0, 25.0));
+        assert_eq!(TransformOrigin::Relative(1.0, 0.0).resolve(rect), (50.0, 20.0));
+        assert_eq!(TransformOrigin::Absolute(3.0, 4.0).resolve(rect), (3.0, 4.0));
+
+        // Relative origins are kept when the canvas is scaled, as they scale with the layer
+        let relative = Filter::<u8>::scaled(&MatrixTransform::around_layer_point(0.0, 1.0).rotate(180.0), 2.0).unwrap();
+        let layer = RectangleLayer { rect, fill: AlphaPixel::<u8>::red(), filters: vec![relative] };
+        // Flipped around the bottom left corner, onto the left of and below the rect
+        assert!(layer.filtered_pixel_at(5, 35).is_some());
+        assert!(layer.filtered_pixel_at(15, 25).is_none());
+    }
+
+    #[test]
+    fn supersampled_rotation() {
+        let mut canvas = Canvas::<u8>::from_dimensions(5, 6);
+        let rotate_filter = Box::new(MatrixTransform::new(5.0, 2.0).rotate(45.0).with_supersampling(4));
+        canvas.add_layer(RectangleLayer { rect: Rect { x: 2, y: 2, width: 3, height: 6 }, fill: AlphaPixel::red(), filters: vec![rotate_filter] });
+        let image = canvas.flatten();
+
+        // The inside of the rotated rectangle is solid, and the diagonal edges are partially covered
+        assert_eq!(image.pixel_at(2, 2).unwrap(), AlphaPixel::red());
+        let partial = image.get_pixels().iter().filter(|p| p.a > 0 && p.a < 255).count();
+        assert!(partial >= 5);
+        assert!(image.get_pixels().iter().all(|p| p.a == 0 || (p.r, p.g, p.b) == (255, 0, 0)));
+    }
+
+    #[test]
+    fn bilinear_sampler() {
+        let rectangle = |filter: MatrixTransform| RectangleLayer {
+            rect: Rect { x: 0, y: 0, width: 10, height: 10 },
+            fill: AlphaPixel::<u8>::red(),
+            filters: vec![Box::new(filter)]
+        };
+
+        // Scaling up by 2 makes the edge of the rectangle fall between two pixels
+        let nearest = rectangle(MatrixTransform::new(0.0, 0.0).scale(2.0));
+        let bilinear = rectangle(MatrixTransform::new(0.0, 0.0).scale(2.0).with_sampler(BilinearSampler));
+
+        assert_eq!(nearest.filtered_pixel_at(19, 5).unwrap().a, 255);
+        assert_eq!(bilinear.filtered_pixel_at(10, 5).unwrap(), AlphaPixel::red());
+        let edge = bilinear.filtered_pixel_at(19, 5).unwrap().a;
+        assert!(edge > 0 && edge < 255);
+    }
+
+    #[test]
+    fn perspective_corners() {
+        let image = Image::from_function(4, 4, |x, y| rgba!(x as u8, y as u8, 0, 255));
+        let rect = Rect { x: 0, y: 0, width: 4, height: 4 };
+
+        // Moving the corners to the same place changes nothing
+        let mut layer = ImageLayer::new(image.clone(), 0, 0);
+        layer.filters.push(Box::new(PerspectiveTransform::from_rect(rect, [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]).unwrap()));
+        for (x, y) in [(0, 0), (3, 1), (2, 3)] {
+            assert_eq!(layer.filtered_pixel_at(x, y), image.pixel_at(x, y));
+        }
+
+        // Each corner of the image is drawn at its new corner
+        let corners = [(10.0, 20.0), (50.0, 10.0), (60.0, 60.0), (5.0, 40.0)];
+        layer.filters = vec![Box::new(PerspectiveTransform::from_rect(rect, corners).unwrap())];
+        assert_eq!(layer.filtered_pixel_at(11, 20).unwrap(), rgba!(0, 0, 0, 255));
+        assert_eq!(layer.filtered_pixel_at(48, 11).unwrap(), rgba!(3, 0, 0, 255));
+        assert_eq!(layer.filtered_pixel_at(58, 58).unwrap(), rgba!(3, 3, 0, 255));
+        assert_eq!(layer.filtered_pixel_at(6, 39).unwrap(), rgba!(0, 3, 0, 255));
+        assert!(layer.filtered_pixel_at(5, 5).is_none());
+    }
+
+    #[test]
+    fn degenerate_perspective() {
+        let rect = Rect { x: 0, y: 0, width: 10, height: 10 };
+        assert!(PerspectiveTransform::from_rect(rect, [(0.0, 0.0), (5.0, 5.0), (10.0, 10.0), (0.0, 10.0)]).is_none());
+        assert!(PerspectiveTransform::from_rect(Rect { width: 0, ..rect }, [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]).is_none());
+    }
+
+    #[test]
+    fn supersampled_perspective() {
+        let rect = Rect { x: 0, y: 0, width: 20, height: 20 };
+        let corners = [(5.0, 0.0), (15.0, 0.0), (20.0, 20.0), (0.0, 20.0)];
+        let layer = RectangleLayer {
+            rect,
+            fill: AlphaPixel::<u8>::red(),
+            filters: vec![Box::new(PerspectiveTransform::from_rect(rect, corners).unwrap().with_supersampling(4).with_sampler(BilinearSampler))]
+        };
+
+        // The slanted sides are partially covered
+        assert_eq!(layer.filtered_pixel_at(10, 10).unwrap(), AlphaPixel::red());
+        let edge = layer.filtered_pixel_at(2, 10).unwrap().a;
+        assert!(edge > 0 && edge < 255);
+    }
+
+    #[test]
+    fn mipmapped_transform() {
+        let checkerboard: Image<u8> = Image::from_function(64, 64, |x, y| if (x + y) % 2 == 0 { AlphaPixel::black() } else { AlphaPixel::white() });
+        let shrink = || MatrixTransform::new(10.0, 10.0).scale(0.25).rotate(30.0);
+        let layer = |filter: Box<dyn Filter<u8>>| ImageLayer { im: checkerboard.clone(), filters: vec![filter], x: 10, y: 10 };
+        // Bilinear weights can add up to slightly less than 1, so the alpha can be rounded down
+        let is_grey = |pixel: AlphaPixel<u8>| (110..=145).contains(&pixel.r) && pixel.a >= 254;
+
+        // Without mipmaps, each pixel is a single black or white pixel of the checkerboard
+        let aliased = layer(Box::new(shrink()));
+        assert!(!is_grey(aliased.filtered_pixel_at(18, 18).unwrap()));
+
+        for transform in [shrink(), shrink().with_supersampling(3)] {
+            let mipmapped = layer(Box::new(transform.with_mipmaps(checkerboard.build_mipmaps(), 10, 10)));
+            for (x, y) in [(14, 14), (18, 18), (14, 22)] {
+                assert!(is_grey(mipmapped.filtered_pixel_at(x, y).unwrap()));
+            }
+            assert!(mipmapped.filtered_pixel_at(5, 5).is_none());
+        }
+    }
 }
@@ -0,0 +1,351 @@
+//! A minimal JSON parser, used to import designs exported by other tools.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+#[error("Invalid JSON at byte {position}: {reason}")]
+pub struct JsonError {
+    /// The byte offset in the text where the error was found
+    pub position: usize,
+    pub reason: &'static str
+}
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// The members of an object, in the order they were written
+    Object(Vec<(String, JsonValue)>)
+}
+
+impl JsonValue {
+    /// Parse a JSON document. Whitespace is allowed around the value, but nothing else.
+    pub fn parse(text: &str) -> Result<Self, JsonError> {
+        let mut parser = Parser { text: text.as_bytes(), position: 0 };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.position < text.len() {
+            return Err(parser.error("unexpected text after the value"))
+        }
+        Ok(value)
+    }
+
+    /// Get a member of an object, or `None` if this isn't an object or has no such member.
+    /// If the member is repeated, the last value is used.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members.iter().rev().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None
+        }
+    }
+
+    /// Get an element of an array, or `None` if this isn't an array or is too short.
+    pub fn index(&self, index: usize) -> Option<&JsonValue> {
+        self.as_array()?.get(index)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(number) => Some(*number),
+            _ => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(text) => Some(text),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None
+        }
+    }
+}
+
+/// The deepest that arrays and objects can be nested, so that malicious input can't overflow the stack.
+const MAX_DEPTH: usize = 256;
+
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &'static str) -> JsonError {
+        JsonError { position: self.position, reason }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.text.get(self.position).is_some_and(|byte| matches!(byte, b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    /// Consume `byte`, after any whitespace, or return an error.
+    fn expect(&mut self, byte: u8, reason: &'static str) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.error(reason))
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<JsonValue, JsonError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("too deeply nested"))
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b't') => self.literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.literal("false", JsonValue::Bool(false)),
+            Some(b'n') => self.literal("null", JsonValue::Null),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of text"))
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, JsonError> {
+        if !self.text[self.position..].starts_with(word.as_bytes()) {
+            return Err(self.error("expected a value"))
+        }
+        self.position += word.len();
+        Ok(value)
+    }
+
+    fn object(&mut self, depth: usize) -> Result<JsonValue, JsonError> {
+        self.position += 1;
+        let mut members = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(JsonValue::Object(members))
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a string key"))
+            }
+            let key = self.string()?;
+            self.expect(b':', "expected ':' after a key")?;
+            members.push((key, self.value(depth + 1)?));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Object(members))
+                },
+                _ => return Err(self.error("expected ',' or '}'"))
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<JsonValue, JsonError> {
+        self.position += 1;
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(JsonValue::Array(values))
+        }
+
+        loop {
+            values.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Array(values))
+                },
+                _ => return Err(self.error("expected ',' or ']'"))
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<JsonValue, JsonError> {
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            let digits_start = parser.position;
+            while parser.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+                parser.position += 1;
+            }
+            parser.position > digits_start
+        };
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        if self.peek() == Some(b'0') {
+            self.position += 1;
+        } else if !digits(self) {
+            return Err(self.error("expected a digit"))
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            if !digits(self) {
+                return Err(self.error("expected a digit after '.'"))
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            if !digits(self) {
+                return Err(self.error("expected a digit in the exponent"))
+            }
+        }
+
+        // The number is only made of ASCII characters, which were checked above
+        let number = std::str::from_utf8(&self.text[start..self.position]).unwrap();
+        number.parse().map(JsonValue::Number).map_err(|_| JsonError { position: start, reason: "invalid number" })
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.position += 1;
+        let mut bytes = vec![];
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.position += 1;
+                    // The text was a `str`, and escapes are written as UTF-8, so this is valid
+                    return Ok(String::from_utf8(bytes).unwrap())
+                },
+                Some(b'\\') => {
+                    self.position += 1;
+                    let escaped = match self.peek() {
+                        Some(b'u') => {
+                            self.position += 1;
+                            self.unicode_escape()?
+                        },
+                        Some(byte) => {
+                            let escaped = match byte {
+                                b'"' => '"',
+                                b'\\' => '\\',
+                                b'/' => '/',
+                                b'b' => '\u{8}',
+                                b'f' => '\u{c}',
+                                b'n' => '\n',
+                                b'r' => '\r',
+                                b't' => '\t',
+                                _ => return Err(self.error("invalid escape"))
+                            };
+                            self.position += 1;
+                            escaped
+                        },
+                        None => return Err(self.error("unterminated string"))
+                    };
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut [0; 4]).as_bytes());
+                },
+                Some(0..=0x1f) => return Err(self.error("control character in a string")),
+                Some(byte) => {
+                    bytes.push(byte);
+                    self.position += 1;
+                },
+                None => return Err(self.error("unterminated string"))
+            }
+        }
+    }
+
+    /// Parse the hex digits of a `\u` escape, and the low surrogate that follows a high surrogate.
+    /// The position is left after the last hex digit.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex_code_unit()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("unpaired surrogate"))
+        }
+
+        if !self.text[self.position..].starts_with(b"\\u") {
+            return Err(self.error("unpaired surrogate"))
+        }
+        self.position += 2;
+        let low = self.hex_code_unit()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return Err(self.error("unpaired surrogate"))
+        }
+        char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    fn hex_code_unit(&mut self) -> Result<u32, JsonError> {
+        let digits = self.text.get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("expected 4 hex digits"))?;
+        let unit = u32::from_str_radix(digits, 16).unwrap();
+        self.position += 4;
+        Ok(unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        let value = JsonValue::parse(" {\"a\": [1, -2.5e2, 0.25, true, null], \"b\": {}, \"c\": [], \"a\": \"last\"} ").unwrap();
+        assert_eq!(value.get("a"), Some(&JsonValue::String("last".to_string())));
+        let JsonValue::Object(members) = &value else { panic!() };
+        assert_eq!(members[0].1, JsonValue::Array(vec![
+            JsonValue::Number(1.0), JsonValue::Number(-250.0), JsonValue::Number(0.25), JsonValue::Bool(true), JsonValue::Null
+        ]));
+        assert_eq!(value.get("b"), Some(&JsonValue::Object(vec![])));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn accessors() {
+        let value = JsonValue::parse(r#"{"name": "Cover", "size": [1080, 1920], "draft": false}"#).unwrap();
+        assert_eq!(value.get("name").and_then(JsonValue::as_str), Some("Cover"));
+        assert_eq!(value.get("size").and_then(|size| size.index(1)).and_then(JsonValue::as_f64), Some(1920.0));
+        assert_eq!(value.get("draft").and_then(JsonValue::as_bool), Some(false));
+        assert!(JsonValue::parse("[1, 2,]").is_err());
+    }
+
+    #[test]
+    fn strings() {
+        let value = JsonValue::parse(r#""café \"quoted\" \\ \n 🎵 ☕""#).unwrap();
+        assert_eq!(value.as_str(), Some("café \"quoted\" \\ \n 🎵 ☕"));
+        assert!(JsonValue::parse(r#""\ud83c""#).is_err());
+        assert!(JsonValue::parse("\"a\nb\"").is_err());
+    }
+
+    #[test]
+    fn errors() {
+        for invalid in ["", "{", "[1 2]", "{\"a\" 1}", "{1: 2}", "01", "1.", "-", "tru", "[1] x", "\"unterminated"] {
+            assert!(JsonValue::parse(invalid).is_err(), "{invalid} should be invalid");
+        }
+        assert_eq!(JsonValue::parse("[1, x]").unwrap_err().position, 4);
+
+        let nested = "[".repeat(1000) + &"]".repeat(1000);
+        assert!(JsonValue::parse(&nested).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{
+    Filter,
+    Layer,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    layers::shapes::{line_coverage, polygon_coverage, polygon_bounds}
+};
+
+/// The shape of the head at the end of an [`ArrowLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrowHead {
+    /// A filled triangle
+    #[default]
+    Triangle,
+    /// Two lines meeting at the point, as wide as the shaft
+    Open,
+    /// A plain line, with no head
+    None
+}
+
+/// A straight line from `start` to `end` with an arrowhead at `end`, such as to point at part of an image in an annotation.
+/// 
+/// The head is `head_length` pixels long from its point back to its base, and `head_width` pixels wide at its base.
+/// A head can also be drawn at `start`, for a double headed arrow. Points are in canvas coordinates, where (0.0, 0.0)
+/// is the top left corner of the top left pixel.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, layers::arrow::{ArrowLayer, ArrowHead}};
+/// 
+/// let arrow: ArrowLayer<u8> = ArrowLayer::new(AlphaPixel::red(), (10.0, 50.0), (90.0, 50.0), 4.0)
+///     .with_head(ArrowHead::Triangle, 20.0, 16.0);
+/// // The shaft, and the head, which is wider than it
+/// assert_eq!(arrow.filtered_pixel_at(30, 50).unwrap(), AlphaPixel::red());
+/// assert_eq!(arrow.filtered_pixel_at(30, 55).unwrap().a, 0);
+/// assert_eq!(arrow.filtered_pixel_at(75, 53).unwrap(), AlphaPixel::red());
+/// ```
+pub struct ArrowLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub color: AlphaPixel<T>,
+    pub start: (f32, f32),
+    pub end: (f32, f32),
+    /// The width of the shaft
+    pub width: f32,
+    pub head: ArrowHead,
+    pub head_length: f32,
+    pub head_width: f32,
+    /// If true, there is also a head at `start`
+    pub double_headed: bool
+}
+
+impl<T: PixelChannel> ArrowLayer<T> {
+    /// Create an arrow with a triangular head, sized in proportion to the width of the shaft.
+    pub fn new(color: AlphaPixel<T>, start: (f32, f32), end: (f32, f32), width: f32) -> Self {
+        let head_length = (width * 4.0).max(8.0);
+        Self {
+            filters: vec![],
+            color,
+            start,
+            end,
+            width,
+            head: ArrowHead::Triangle,
+            head_length,
+            head_width: head_length * 0.8,
+            double_headed: false
+        }
+    }
+
+    pub fn with_head(mut self, head: ArrowHead, length: f32, width: f32) -> Self {
+        self.head = head;
+        self.head_length = length;
+        self.head_width = width;
+        self
+    }
+
+    /// Draw a head at both ends of the arrow.
+    pub fn with_double_head(mut self) -> Self {
+        self.double_headed = true;
+        self
+    }
+
+    /// Get the point of a head at `tip`, and the two corners of its base, for an arrow coming from `from`.
+    /// 
+    /// Returns `None` if there is no head, or the arrow has no length so the head has no direction.
+    fn head_points(&self, tip: (f32, f32), from: (f32, f32)) -> Option<[(f32, f32); 3]> {
+        let direction = (tip.0 - from.0, tip.1 - from.1);
+        let length = (direction.0*direction.0 + direction.1*direction.1).sqrt();
+        if self.head == ArrowHead::None || length == 0.0 {
+            return None
+        }
+
+        let unit = (direction.0 / length, direction.1 / length);
+        let base = (tip.0 - unit.0*self.head_length, tip.1 - unit.1*self.head_length);
+        let half_width = self.head_width / 2.0;
+        Some([
+            tip,
+            (base.0 - unit.1*half_width, base.1 + unit.0*half_width),
+            (base.0 + unit.1*half_width, base.1 - unit.0*half_width)
+        ])
+    }
+
+    /// Get the ends of the shaft, which stop short of triangular heads so that the round ends of the line
+    /// don't show past their points.
+    fn shaft(&self) -> ((f32, f32), (f32, f32)) {
+        let direction = (self.end.0 - self.start.0, self.end.1 - self.start.1);
+        let length = (direction.0*direction.0 + direction.1*direction.1).sqrt();
+        if length == 0.0 {
+            return (self.start, self.end)
+        }
+
+        let inset = match self.head {
+            ArrowHead::Triangle => self.head_length.min(length / 2.0),
+            ArrowHead::Open => (self.width / 2.0).min(length / 2.0),
+            ArrowHead::None => 0.0
+        };
+        let unit = (direction.0 / length, direction.1 / length);
+        let start_inset = if self.double_headed { inset } else { 0.0 };
+        (
+            (self.start.0 + unit.0*start_inset, self.start.1 + unit.1*start_inset),
+            (self.end.0 - unit.0*inset, self.end.1 - unit.1*inset)
+        )
+    }
+
+    /// Get the heads at each end which are drawn.
+    fn heads(&self) -> impl Iterator<Item = [(f32, f32); 3]> + '_ {
+        let start_head = self.double_headed.then(|| self.head_points(self.start, self.end)).flatten();
+        self.head_points(self.end, self.start).into_iter().chain(start_head)
+    }
+
+    /// Get the coverage of the pixel at (`x`, `y`) by a head.
+    fn head_coverage(&self, head: &[(f32, f32); 3], x: usize, y: usize) -> f32 {
+        match self.head {
+            ArrowHead::Triangle => polygon_coverage(head, x, y),
+            ArrowHead::Open => {
+                let [tip, left, right] = *head;
+                line_coverage(tip, left, self.width, x, y).max(line_coverage(tip, right, self.width, x, y))
+            },
+            ArrowHead::None => 0.0
+        }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for ArrowLayer<T> {
+    fn get_rect(&self) -> Rect {
+        let mut points = vec![self.start, self.end];
+        points.extend(self.heads().flatten());
+        // The line is drawn around the points, so the bounds are expanded by half of its width
+        let padding = (self.width / 2.0).max(0.0) + 1.0;
+        let mut expanded = Vec::with_capacity(points.len() * 2);
+        for (x, y) in points {
+            expanded.push((x - padding, y - padding));
+            expanded.push((x + padding, y + padding));
+        }
+        polygon_bounds(&expanded)
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let (shaft_start, shaft_end) = self.shaft();
+        let coverage = self.heads()
+            .map(|head| self.head_coverage(&head, x, y))
+            .fold(line_coverage(shaft_start, shaft_end, self.width, x, y), f32::max);
+        self.color.scale_alpha(coverage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shaft_stops_at_head() {
+        let arrow: ArrowLayer<u8> = ArrowLayer::new(AlphaPixel::black(), (0.0, 10.5), (40.0, 10.5), 6.0)
+            .with_head(ArrowHead::Triangle, 10.0, 4.0);
+        // The round end of the shaft would reach past the point of the narrow head
+        assert_eq!(arrow.filtered_pixel_at(39, 12).unwrap().a, 0);
+        assert_eq!(arrow.filtered_pixel_at(34, 10).unwrap(), AlphaPixel::black());
+        // No head at the start
+        assert_eq!(arrow.filtered_pixel_at(0, 12).unwrap(), AlphaPixel::black());
+    }
+
+    #[test]
+    fn double_open_head() {
+        let arrow: ArrowLayer<u8> = ArrowLayer::new(AlphaPixel::black(), (20.0, 20.0), (20.0, 80.0), 2.0)
+            .with_head(ArrowHead::Open, 10.0, 20.0)
+            .with_double_head();
+        let rect = arrow.get_rect();
+        assert!(rect.x <= 9 && rect.right_x() >= 31);
+
+        // The ends of the open heads are drawn at both ends, but not the space between them and the shaft
+        assert!(arrow.filtered_pixel_at(11, 71).unwrap().a > 0);
+        assert!(arrow.filtered_pixel_at(11, 29).unwrap().a > 0);
+        assert_eq!(arrow.filtered_pixel_at(13, 76).unwrap().a, 0);
+    }
+
+    #[test]
+    fn zero_length() {
+        let arrow: ArrowLayer<u8> = ArrowLayer::new(AlphaPixel::black(), (5.0, 5.0), (5.0, 5.0), 4.0);
+        // A dot, with no head
+        assert_eq!(arrow.filtered_pixel_at(5, 5).unwrap(), AlphaPixel::black());
+        assert_eq!(arrow.get_rect().width, 6);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod, Backdrop};
+
+/// A function which composites a layer's pixel with the backdrop at a canvas location.
+/// 
+/// Takes the layer's filtered pixel, the backdrop, and the canvas coordinate, and returns the pixel which replaces the backdrop.
+pub type BackdropBlendFunction<T> = dyn Fn(AlphaPixel<T>, &Backdrop<T>, usize, usize) -> AlphaPixel<T>;
+
+/// A wrapper around a layer which composites it using a custom function that can sample any part of the backdrop,
+/// not just the pixel directly beneath it.
+/// 
+/// This allows effects such as displacing the layer by the background, or colouring it to contrast with the background.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, AlphaPixel, Rect, BlendingMethod, layers::{shapes::RectangleLayer, backdrop_blend::BackdropBlendLayer}};
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+/// canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 50, height: 100 }));
+/// 
+/// // Draw the rectangle at half opacity over the backdrop from 10 pixels to the left
+/// let rectangle = RectangleLayer::new(AlphaPixel::blue(), Rect { x: 40, y: 40, width: 20, height: 20 });
+/// canvas.add_layer(BackdropBlendLayer::new(rectangle, |pixel, backdrop, x, y| {
+///     let shifted = backdrop.pixel_at(x.saturating_sub(10), y).unwrap_or_default();
+///     BlendingMethod::Over.blend(shifted, pixel.scale_alpha(0.5))
+/// }));
+/// ```
+pub struct BackdropBlendLayer<T: PixelChannel, L: Layer<T>> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub layer: L,
+    blend: Box<BackdropBlendFunction<T>>
+}
+
+impl<T: PixelChannel, L: Layer<T>> BackdropBlendLayer<T, L> {
+    pub fn new<F: Fn(AlphaPixel<T>, &Backdrop<T>, usize, usize) -> AlphaPixel<T> + 'static>(layer: L, blend: F) -> Self {
+        Self { filters: vec![], layer, blend: Box::new(blend) }
+    }
+
+    /// Recolour the layer to `light` or `dark`, whichever contrasts more with the average brightness
+    /// of the visible backdrop within `radius` pixels. The layer's alpha is kept.
+    /// 
+    /// This is useful for text which is placed over a photo or other unknown background.
+    pub fn contrasting(layer: L, light: AlphaPixel<T>, dark: AlphaPixel<T>, radius: usize) -> Self {
+        Self::new(layer, move |pixel, backdrop, x, y| {
+            let below = backdrop.pixel_at(x, y).unwrap_or_default();
+            let fill = if average_luma(backdrop, x, y, radius) > 0.5 { dark } else { light };
+            let alpha = pixel.a.into() / T::MAX_PIXEL_VALUE.into();
+            BlendingMethod::Over.blend(below, fill.scale_alpha(alpha))
+        })
+    }
+}
+
+/// Get the average luma of the backdrop in a square around a coordinate, from 0.0 to 1.0.
+/// 
+/// Pixels are weighted by their alpha, so transparent parts of the backdrop don't darken the average. This uses
+/// [`Backdrop::average`], which takes constant time for a flattened backdrop, whatever the radius.
+fn average_luma<T: PixelChannel>(backdrop: &Backdrop<T>, x: usize, y: usize, radius: usize) -> f32 {
+    let (left, top) = (x.saturating_sub(radius), y.saturating_sub(radius));
+    let rect = Rect {
+        x: left,
+        y: top,
+        width: x.saturating_add(radius).saturating_add(1) - left,
+        height: y.saturating_add(radius).saturating_add(1) - top
+    };
+    backdrop.average(rect).map_or(0.0, |average| average.luma().into() / T::MAX_PIXEL_VALUE.into())
+}
+
+impl<T: PixelChannel, L: Layer<T>> Layer<T> for BackdropBlendLayer<T, L> {
+    fn get_rect(&self) -> Rect {
+        self.layer.get_rect()
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.layer.filtered_pixel_at(x, y).unwrap_or_default()
+    }
+
+    fn uses_backdrop(&self) -> bool {
+        true
+    }
+
+    fn composite_over_backdrop(&self, x: usize, y: usize, backdrop: &Backdrop<T>) -> AlphaPixel<T> {
+        match self.filtered_pixel_at(x, y) {
+            Some(pixel) => (self.blend)(pixel, backdrop, x, y),
+            None => backdrop.pixel_at(x, y).unwrap_or_default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, layers::shapes::RectangleLayer};
+
+    fn half_white_canvas() -> Canvas<u8> {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(20, 10);
+        canvas.background = AlphaPixel::black();
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 10, y: 0, width: 10, height: 10 }));
+        canvas
+    }
+
+    #[test]
+    fn contrasting() {
+        let mut canvas = half_white_canvas();
+        let label = RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 4, width: 20, height: 2 });
+        canvas.add_layer(BackdropBlendLayer::contrasting(label, AlphaPixel::white(), AlphaPixel::black(), 1));
+        let image = canvas.flatten();
+
+        assert_eq!(image.pixel_at(2, 4).unwrap(), AlphaPixel::white());
+        assert_eq!(image.pixel_at(17, 4).unwrap(), AlphaPixel::black());
+        // Outside of the layer, the backdrop is unchanged
+        assert_eq!(image.pixel_at(17, 0).unwrap(), AlphaPixel::white());
+    }
+
+    #[test]
+    fn contrasting_ignores_transparent_backdrop() {
+        // Half of the canvas is transparent, rather than black
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(20, 10);
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 10, y: 0, width: 10, height: 10 }));
+        let label = RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 4, width: 20, height: 2 });
+        canvas.add_layer(BackdropBlendLayer::contrasting(label, AlphaPixel::white(), AlphaPixel::black(), 3));
+        let image = canvas.flatten();
+
+        // Only white is visible within 3 pixels, so the label is dark
+        assert_eq!(image.pixel_at(9, 4).unwrap(), AlphaPixel::black());
+        // With nothing visible, the label is light
+        assert_eq!(image.pixel_at(2, 4).unwrap(), AlphaPixel::white());
+    }
+
+    #[test]
+    fn samples_backdrop_region() {
+        let mut canvas = half_white_canvas();
+        let layer = RectangleLayer::new(AlphaPixel::red(), Rect { x: 10, y: 0, width: 5, height: 10 });
+        // Replace the backdrop with the pixel 10 to the left
+        canvas.add_layer(BackdropBlendLayer::new(layer, |_, backdrop, x, y| backdrop.pixel_at(x - 10, y).unwrap()));
+        let image = canvas.flatten();
+
+        assert_eq!(image.pixel_at(12, 3).unwrap(), AlphaPixel::black());
+        assert_eq!(image.pixel_at(15, 3).unwrap(), AlphaPixel::white());
+
+        for y in 0..10 {
+            for x in 0..20 {
+                assert_eq!(image.pixel_at(x, y).unwrap(), canvas.combined_pixel_at(x, y));
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod, Backdrop};
+
+/// A wrapper around a layer which blurs everything beneath it before drawing it, for frosted glass effects.
+/// 
+/// The backdrop is blurred wherever the inner layer isn't fully transparent, so a partially transparent
+/// inner layer (such as a translucent white card) shows a blurred version of the layers beneath it.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, AlphaPixel, Rect, rgba, layers::{shapes::RectangleLayer, backdrop_blur::BackdropBlurLayer}};
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+/// canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 50, height: 100 }));
+/// 
+/// let card = RectangleLayer::new(rgba!(255, 255, 255, 80), Rect { x: 20, y: 20, width: 60, height: 60 });
+/// canvas.add_layer(BackdropBlurLayer::new(card, 8));
+/// let image = canvas.flatten();
+/// ```
+pub struct BackdropBlurLayer<T: PixelChannel, L: Layer<T>> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub layer: L,
+    /// The radius of the box blur applied to the backdrop, in pixels
+    pub radius: usize
+}
+
+impl<T: PixelChannel, L: Layer<T>> BackdropBlurLayer<T, L> {
+    pub fn new(layer: L, radius: usize) -> Self {
+        Self { filters: vec![], layer, radius }
+    }
+
+    /// Average the backdrop in a square around a pixel, weighting colours by their alpha.
+    fn blurred_backdrop(&self, x: usize, y: usize, backdrop: &Backdrop<T>) -> AlphaPixel<T> {
+        let left = x.saturating_sub(self.radius);
+        let top = y.saturating_sub(self.radius);
+        let square = Rect { x: left, y: top, width: x + self.radius + 1 - left, height: y + self.radius + 1 - top };
+        backdrop.average(square).unwrap_or_default()
+    }
+}
+
+impl<T: PixelChannel, L: Layer<T>> Layer<T> for BackdropBlurLayer<T, L> {
+    fn get_rect(&self) -> Rect {
+        self.layer.get_rect()
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.layer.filtered_pixel_at(x, y).unwrap_or_default()
+    }
+
+    fn uses_backdrop(&self) -> bool {
+        true
+    }
+
+    fn pixel_over_backdrop(&self, x: usize, y: usize, backdrop: &Backdrop<T>) -> Option<AlphaPixel<T>> {
+        let pixel = self.filtered_pixel_at(x, y)?;
+        if pixel.a == T::zero() {
+            return Some(pixel)
+        }
+
+        // The blurred backdrop replaces the sharp backdrop, as it is opaque wherever the backdrop is
+        Some(BlendingMethod::Over.blend(self.blurred_backdrop(x, y, backdrop), pixel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, layers::shapes::RectangleLayer, rgba};
+
+    fn striped_canvas() -> Canvas<u8> {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(20, 20);
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 10, height: 20 }));
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 10, y: 0, width: 10, height: 20 }));
+        canvas
+    }
+
+    #[test]
+    fn blur_behind() {
+        let mut canvas = striped_canvas();
+        // A nearly transparent card, so that the colour is almost entirely the blurred backdrop
+        let card = RectangleLayer::new(rgba!(0, 0, 0, 1), Rect { x: 5, y: 5, width: 10, height: 10 });
+        canvas.add_layer(BackdropBlurLayer::new(card, 3));
+        let image = canvas.flatten();
+
+        // Outside of the card, the backdrop is sharp
+        assert_eq!(image.pixel_at(9, 0).unwrap(), AlphaPixel::red());
+        assert_eq!(image.pixel_at(10, 0).unwrap(), AlphaPixel::blue());
+
+        // Inside of the card, the edge between the colours is blurred
+        let left = image.pixel_at(9, 10).unwrap();
+        let right = image.pixel_at(10, 10).unwrap();
+        assert!(left.r > left.b && left.b > 0);
+        assert!(right.b > right.r && right.r > 0);
+        assert_eq!(left.a, 255);
+
+        // Far from the edge, the blur doesn't change the colour
+        let far = image.pixel_at(5, 10).unwrap();
+        assert!(far.r >= 254 && far.b == 0);
+    }
+
+    #[test]
+    fn blur_behind_matches_combined_pixel() {
+        let mut canvas = striped_canvas();
+        // A small radius, so that single pixels are composited without sparse sampling
+        let card = RectangleLayer::new(rgba!(255, 255, 255, 100), Rect { x: 2, y: 2, width: 16, height: 8 });
+        canvas.add_layer(BackdropBlurLayer::new(card, 3));
+        let image = canvas.flatten();
+
+        // Flattening averages with an integral image, which can round differently
+        for y in 0..20 {
+            for x in 0..20 {
+                let flattened = image.pixel_at(x, y).unwrap();
+                let combined = canvas.combined_pixel_at(x, y);
+                for (flattened_channel, combined_channel) in flattened.channels().iter().zip(combined.channels()) {
+                    assert!(flattened_channel.abs_diff(*combined_channel) <= 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blur_behind_tiled() {
+        let mut canvas = striped_canvas();
+        let card = RectangleLayer::new(rgba!(255, 255, 255, 100), Rect { x: 2, y: 2, width: 16, height: 8 });
+        canvas.add_layer(BackdropBlurLayer::new(card, 3));
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::black(), Rect { x: 0, y: 15, width: 20, height: 2 }));
+        let sharp = striped_canvas().flatten();
+        let image = canvas.flatten();
+        let tiled = canvas.flatten_tiled().unwrap();
+
+        for y in 0..20 {
+            for x in 0..20 {
+                assert_eq!(tiled.pixel_at(x, y).unwrap(), image.pixel_at(x, y).unwrap());
+                // Only the card's `Rect` is blurred
+                if !(2..18).contains(&x) || !(2..10).contains(&y) {
+                    let expected = if (15..17).contains(&y) { AlphaPixel::black() } else { sharp.pixel_at(x, y).unwrap() };
+                    assert_eq!(image.pixel_at(x, y).unwrap(), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blur_behind_tiled_allocates_only_covered_tiles() {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(4096, 4096);
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 10, height: 20 }));
+        let card = RectangleLayer::new(rgba!(255, 255, 255, 100), Rect { x: 5, y: 5, width: 10, height: 10 });
+        canvas.add_layer(BackdropBlurLayer::new(card, 3));
+
+        let tiled = canvas.flatten_tiled().unwrap();
+        assert_eq!(tiled.allocated_tile_count(), 1);
+        assert_eq!(tiled.pixel_at(2, 2).unwrap(), AlphaPixel::red());
+    }
+}
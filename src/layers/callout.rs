@@ -0,0 +1,156 @@
+use crate::{
+    Filter,
+    Layer,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    BlendingMethod,
+    Error,
+    layers::{
+        shapes::{rounded_rect_coverage, polygon_coverage, line_coverage},
+        text::{TextLayer, TextSettings}
+    }
+};
+
+/// The appearance of a [`CalloutLayer`].
+#[derive(Clone, Copy)]
+pub struct CalloutStyle<T> {
+    pub box_fill: AlphaPixel<T>,
+    pub line_color: AlphaPixel<T>,
+    pub line_width: f32,
+    pub corner_radius: f32,
+    /// Space between the label text and the edge of its box, in pixels
+    pub padding: usize,
+    /// The preferred offset from the target point to the center of the label box, in pixels.
+    /// 
+    /// If the label box would go past the edge of the canvas, the offset is flipped on that axis.
+    pub offset: (isize, isize),
+    /// The length of the arrow head drawn at the target point. If `None`, a plain leader line is drawn.
+    pub arrow_head: Option<f32>
+}
+
+impl<T: PixelChannel> Default for CalloutStyle<T> {
+    fn default() -> Self {
+        Self {
+            box_fill: AlphaPixel::white(),
+            line_color: AlphaPixel::black(),
+            line_width: 2.0,
+            corner_radius: 5.0,
+            padding: 8,
+            offset: (80, -60),
+            arrow_head: Some(12.0)
+        }
+    }
+}
+
+/// An annotation that points at a location with an arrow or leader line, from a label box.
+/// 
+/// The label box is positioned relative to the target point so that it stays within the canvas.
+pub struct CalloutLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    text: TextLayer<T>,
+    style: CalloutStyle<T>,
+    target: (f32, f32),
+    label_box: Rect,
+    arrow_head: Option<[(f32, f32); 3]>,
+    rect: Rect
+}
+
+impl<T: PixelChannel> CalloutLayer<T> {
+    /// Create a callout pointing at `target`, on a canvas of size `canvas_size` (width, height).
+    pub fn try_new(settings: TextSettings<T>, target: (usize, usize), canvas_size: (usize, usize), style: CalloutStyle<T>) -> Result<Self, Error> {
+        // Create the text at the origin first, to measure it
+        let mut text = TextLayer::try_new(settings, 0, 0)?;
+        let text_rect = text.get_rect();
+        let box_width = text_rect.width + 2*style.padding;
+        let box_height = text_rect.height + 2*style.padding;
+
+        let box_x = Self::position_on_axis(target.0, style.offset.0, box_width, canvas_size.0);
+        let box_y = Self::position_on_axis(target.1, style.offset.1, box_height, canvas_size.1);
+        let label_box = Rect { x: box_x, y: box_y, width: box_width, height: box_height };
+        text.x = box_x + style.padding;
+        text.y = box_y + style.padding;
+
+        let target_center = (target.0 as f32 + 0.5, target.1 as f32 + 0.5);
+        let arrow_head = style.arrow_head.and_then(|length| {
+            let box_center = (box_x as f32 + box_width as f32 / 2.0, box_y as f32 + box_height as f32 / 2.0);
+            let direction = (box_center.0 - target_center.0, box_center.1 - target_center.1);
+            let distance = (direction.0*direction.0 + direction.1*direction.1).sqrt();
+            if distance == 0.0 {
+                return None
+            }
+
+            let unit = (direction.0 / distance, direction.1 / distance);
+            let base = (target_center.0 + unit.0*length, target_center.1 + unit.1*length);
+            let half_width = length / 2.0;
+            Some([
+                target_center,
+                (base.0 - unit.1*half_width, base.1 + unit.0*half_width),
+                (base.0 + unit.1*half_width, base.1 - unit.0*half_width)
+            ])
+        });
+
+        // The bounding rect contains the label box, the target, and the arrow head
+        let padding = style.line_width.ceil() as usize + style.arrow_head.unwrap_or(0.0).ceil() as usize;
+        let min_x = label_box.x.min(target.0.saturating_sub(padding));
+        let min_y = label_box.y.min(target.1.saturating_sub(padding));
+        let max_x = label_box.right_x().max(target.0 + padding + 1);
+        let max_y = label_box.bottom_y().max(target.1 + padding + 1);
+        let rect = Rect { x: min_x, y: min_y, width: max_x - min_x, height: max_y - min_y };
+
+        Ok(Self { filters: vec![], text, style, target: target_center, label_box, arrow_head, rect })
+    }
+
+    /// Get the start of the label box on one axis, flipping the offset if the box would go past the edge of the canvas.
+    fn position_on_axis(target: usize, offset: isize, box_size: usize, canvas_size: usize) -> usize {
+        let start_from_offset = |offset: isize| (target as isize + offset) - box_size as isize / 2;
+        let fits = |start: isize| start >= 0 && start + box_size as isize <= canvas_size as isize;
+
+        let mut start = start_from_offset(offset);
+        if !fits(start) && fits(start_from_offset(-offset)) {
+            start = start_from_offset(-offset);
+        }
+
+        // Clamp to the canvas if neither side fits
+        start.min(canvas_size as isize - box_size as isize).max(0) as usize
+    }
+
+    pub fn get_text_settings(&self) -> &TextSettings<T> {
+        self.text.get_settings()
+    }
+
+    /// Get the `Rect` of the label box, after it has been positioned.
+    pub fn get_label_rect(&self) -> Rect {
+        self.label_box
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for CalloutLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let label_box = (self.label_box.x as f32, self.label_box.y as f32, self.label_box.width as f32, self.label_box.height as f32);
+        let box_center = (label_box.0 + label_box.2 / 2.0, label_box.1 + label_box.3 / 2.0);
+
+        // The line goes to the center of the box, and is covered by it
+        let mut line = line_coverage(self.target, box_center, self.style.line_width, x, y);
+        if let Some(arrow_head) = &self.arrow_head {
+            line = line.max(polygon_coverage(arrow_head, x, y));
+        }
+        let mut pixel = self.style.line_color.scale_alpha(line);
+
+        let box_coverage = rounded_rect_coverage(label_box, self.style.corner_radius, x, y);
+        pixel = BlendingMethod::Over.blend(pixel, self.style.box_fill.scale_alpha(box_coverage));
+
+        match self.text.unfiltered_pixel_at(x, y) {
+            Some(text_pixel) => BlendingMethod::Over.blend(pixel, text_pixel),
+            None => pixel
+        }
+    }
+}
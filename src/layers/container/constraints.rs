@@ -0,0 +1,365 @@
+use std::cell::OnceCell;
+use thiserror::Error;
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod};
+use super::{bounding_rect, moved_rect, moved_pixel_at};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConstraintError {
+    #[error("Can't constrain a horizontal anchor to a vertical anchor.")]
+    MismatchedAxis,
+    #[error("Constraint would make a layer's position depend on itself.")]
+    Cycle,
+    #[error("No child with this id.")]
+    UnknownChild
+}
+
+/// An edge or center line of a layer's `Rect`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    Left,
+    CenterX,
+    Right,
+    Top,
+    CenterY,
+    Bottom
+}
+
+impl Anchor {
+    fn is_horizontal(&self) -> bool {
+        matches!(self, Anchor::Left | Anchor::CenterX | Anchor::Right)
+    }
+
+    /// Get the coordinate of this anchor in `rect`.
+    fn position(&self, rect: Rect) -> isize {
+        (match self {
+            Anchor::Left => rect.x,
+            Anchor::CenterX => rect.x + rect.width / 2,
+            Anchor::Right => rect.x + rect.width,
+            Anchor::Top => rect.y,
+            Anchor::CenterY => rect.y + rect.height / 2,
+            Anchor::Bottom => rect.y + rect.height
+        }) as isize
+    }
+
+    /// Get the distance from the left or top of `rect` to this anchor.
+    fn offset(&self, rect: Rect) -> isize {
+        let start = if self.is_horizontal() { rect.x } else { rect.y };
+        self.position(rect) - start as isize
+    }
+}
+
+/// Identifies a child of a [`ConstraintLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChildId(usize);
+
+/// What a constraint positions a child relative to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Child(ChildId),
+    /// The `Rect` of the [`ConstraintLayer`] itself, such as the whole canvas
+    Parent
+}
+
+/// `anchor` of a child = `target_anchor` of `target` + `offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Constraint {
+    anchor: Anchor,
+    target: Target,
+    target_anchor: Anchor,
+    offset: isize
+}
+
+struct ConstrainedChild<T: PixelChannel> {
+    layer: Box<dyn Layer<T>>,
+    horizontal: Option<Constraint>,
+    vertical: Option<Constraint>
+}
+
+/// Where every child is, after constraints are applied
+struct Resolved {
+    positions: Vec<(usize, usize)>,
+    /// The bounding `Rect` of the children
+    rect: Rect
+}
+
+/// A layer which positions its children with relational constraints, such as
+/// "the top of B is 20 pixels below the bottom of A" or "the center of B is the center of the canvas".
+/// 
+/// Each child can have one horizontal and one vertical constraint. Children without a constraint on an
+/// axis keep their own position on that axis. Constraints are resolved when the layer is drawn, from the `Rect`s
+/// that the children have then, so a child placed below some text moves down when the text is replaced by taller
+/// text with [`ConstraintLayer::get_child_mut`]. Positions above or left of 0 are clamped to 0.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Rect, layers::{shapes::RectangleLayer, container::constraints::{ConstraintLayer, Anchor, Target}}};
+/// 
+/// let mut layout: ConstraintLayer<u8> = ConstraintLayer::new(Rect { x: 0, y: 0, width: 800, height: 600 });
+/// let title = layout.add(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 40, width: 400, height: 80 }));
+/// let subtitle = layout.add(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 0, y: 0, width: 200, height: 30 }));
+/// 
+/// layout.constrain(title, Anchor::CenterX, Target::Parent, Anchor::CenterX, 0).unwrap();
+/// layout.constrain(subtitle, Anchor::Top, Target::Child(title), Anchor::Bottom, 20).unwrap();
+/// layout.constrain(subtitle, Anchor::Right, Target::Child(title), Anchor::Right, 0).unwrap();
+/// 
+/// let subtitle_rect = layout.get_child_rect(subtitle).unwrap();
+/// assert_eq!((subtitle_rect.x, subtitle_rect.y), (400, 140));
+/// ```
+pub struct ConstraintLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    children: Vec<ConstrainedChild<T>>,
+    /// The `Rect` that `Target::Parent` refers to
+    parent: Rect,
+    /// Resolved the first time that the layer is drawn or measured, and cleared when it is changed
+    resolved: OnceCell<Resolved>
+}
+
+impl<T: PixelChannel> ConstraintLayer<T> {
+    /// Create an empty layout. `parent` is the `Rect` that children can be constrained to with `Target::Parent`.
+    pub fn new(parent: Rect) -> Self {
+        Self { filters: vec![], children: vec![], parent, resolved: OnceCell::new() }
+    }
+
+    /// Add a child at its own position, and get its id to constrain it with.
+    pub fn add<L: Layer<T> + 'static>(&mut self, layer: L) -> ChildId {
+        self.add_boxed(Box::new(layer))
+    }
+
+    pub fn add_boxed(&mut self, layer: Box<dyn Layer<T>>) -> ChildId {
+        self.resolved.take();
+        self.children.push(ConstrainedChild { layer, horizontal: None, vertical: None });
+        ChildId(self.children.len() - 1)
+    }
+
+    /// Get a child to replace it, such as with a text layer with different text. It keeps its id and constraints,
+    /// and the constraints are resolved again the next time that the layer is drawn, so children constrained to it
+    /// move with it.
+    pub fn get_child_mut(&mut self, child: ChildId) -> Option<&mut Box<dyn Layer<T>>> {
+        self.resolved.take();
+        self.children.get_mut(child.0).map(|constrained| &mut constrained.layer)
+    }
+
+    /// Constrain `anchor` of `child` to be `offset` pixels right of, or below, `target_anchor` of `target`.
+    /// 
+    /// This replaces any previous constraint on the same axis of `child`. If the constraint would create a cycle,
+    /// it isn't added.
+    pub fn constrain(&mut self, child: ChildId, anchor: Anchor, target: Target, target_anchor: Anchor, offset: isize) -> Result<(), ConstraintError> {
+        if anchor.is_horizontal() != target_anchor.is_horizontal() {
+            return Err(ConstraintError::MismatchedAxis)
+        }
+        if let Target::Child(ChildId(target_index)) = target {
+            if target_index >= self.children.len() {
+                return Err(ConstraintError::UnknownChild)
+            }
+        }
+
+        let constrained = self.children.get_mut(child.0).ok_or(ConstraintError::UnknownChild)?;
+        let constraint = Some(Constraint { anchor, target, target_anchor, offset });
+        let previous = if anchor.is_horizontal() {
+            std::mem::replace(&mut constrained.horizontal, constraint)
+        } else {
+            std::mem::replace(&mut constrained.vertical, constraint)
+        };
+
+        if let Err(error) = self.resolution_order(anchor.is_horizontal()) {
+            let constrained = &mut self.children[child.0];
+            if anchor.is_horizontal() {
+                constrained.horizontal = previous;
+            } else {
+                constrained.vertical = previous;
+            }
+            return Err(error)
+        }
+        self.resolved.take();
+        Ok(())
+    }
+
+    /// Remove the constraints of a child, returning it to its own position.
+    pub fn unconstrain(&mut self, child: ChildId) -> Result<(), ConstraintError> {
+        let constrained = self.children.get_mut(child.0).ok_or(ConstraintError::UnknownChild)?;
+        constrained.horizontal = None;
+        constrained.vertical = None;
+        self.resolved.take();
+        Ok(())
+    }
+
+    /// Get the `Rect` of a child, after constraints are applied.
+    pub fn get_child_rect(&self, child: ChildId) -> Option<Rect> {
+        let constrained = self.children.get(child.0)?;
+        Some(moved_rect(constrained.layer.as_ref(), self.resolved().positions[child.0]))
+    }
+
+    fn constraint(child: &ConstrainedChild<T>, axis_is_horizontal: bool) -> Option<Constraint> {
+        if axis_is_horizontal { child.horizontal } else { child.vertical }
+    }
+
+    /// Get the order to position the children in on an axis, so that each child is positioned after the child it
+    /// targets, or an error if the constraints have a cycle.
+    fn resolution_order(&self, axis_is_horizontal: bool) -> Result<Vec<usize>, ConstraintError> {
+        // 0 is unvisited, 1 is being resolved, and 2 is resolved
+        let mut states = vec![0u8; self.children.len()];
+        let mut order = Vec::with_capacity(self.children.len());
+        for start in 0..self.children.len() {
+            let mut stack = vec![(start, false)];
+            while let Some((index, dependencies_done)) = stack.pop() {
+                if dependencies_done {
+                    states[index] = 2;
+                    order.push(index);
+                    continue
+                }
+                match states[index] {
+                    2 => continue,
+                    1 => return Err(ConstraintError::Cycle),
+                    _ => {}
+                }
+                states[index] = 1;
+                stack.push((index, true));
+                if let Some(Constraint { target: Target::Child(ChildId(target)), .. }) = Self::constraint(&self.children[index], axis_is_horizontal) {
+                    if states[target] == 1 {
+                        return Err(ConstraintError::Cycle)
+                    }
+                    stack.push((target, false));
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    fn resolved(&self) -> &Resolved {
+        self.resolved.get_or_init(|| self.resolve())
+    }
+
+    /// Position every child, resolving each constraint after the constraints of the child it targets.
+    fn resolve(&self) -> Resolved {
+        let mut positions: Vec<(usize, usize)> = self.children.iter()
+            .map(|constrained| {
+                let rect = constrained.layer.get_rect();
+                (rect.x, rect.y)
+            })
+            .collect();
+
+        for axis_is_horizontal in [true, false] {
+            let order = self.resolution_order(axis_is_horizontal).expect("Constraints which create a cycle aren't added");
+            for index in order {
+                let constrained = &self.children[index];
+                let Some(constraint) = Self::constraint(constrained, axis_is_horizontal) else {
+                    continue
+                };
+
+                let rect = moved_rect(constrained.layer.as_ref(), positions[index]);
+                let target_rect = match constraint.target {
+                    Target::Child(ChildId(target)) => moved_rect(self.children[target].layer.as_ref(), positions[target]),
+                    Target::Parent => self.parent
+                };
+                let target_position = constraint.target_anchor.position(target_rect) + constraint.offset;
+                let position = (target_position - constraint.anchor.offset(rect)).max(0) as usize;
+                if axis_is_horizontal {
+                    positions[index].0 = position;
+                } else {
+                    positions[index].1 = position;
+                }
+            }
+        }
+
+        let rects = self.children.iter().zip(&positions).map(|(constrained, position)| moved_rect(constrained.layer.as_ref(), *position));
+        let rect = bounding_rect(rects, self.parent.x, self.parent.y);
+        Resolved { positions, rect }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for ConstraintLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.resolved().rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn children(&self) -> Vec<&dyn Layer<T>> {
+        self.children.iter().map(|constrained| constrained.layer.as_ref()).collect()
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.children.iter()
+            .zip(&self.resolved().positions)
+            .filter_map(|(constrained, position)| moved_pixel_at(constrained.layer.as_ref(), *position, x, y))
+            .fold(AlphaPixel::default(), |background, pixel| BlendingMethod::Over.blend(background, pixel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::shapes::RectangleLayer;
+
+    fn rectangle(x: usize, y: usize, width: usize, height: usize) -> RectangleLayer<u8> {
+        RectangleLayer::new(AlphaPixel::red(), Rect { x, y, width, height })
+    }
+
+    fn position(layout: &ConstraintLayer<u8>, child: ChildId) -> (usize, usize) {
+        let rect = layout.get_child_rect(child).unwrap();
+        (rect.x, rect.y)
+    }
+
+    #[test]
+    fn chained_constraints() {
+        let mut layout = ConstraintLayer::new(Rect { x: 0, y: 0, width: 100, height: 100 });
+        let c = layout.add(rectangle(5, 0, 10, 10));
+        let b = layout.add(rectangle(0, 0, 20, 20));
+        let a = layout.add(rectangle(0, 30, 40, 10));
+
+        // Constraints are resolved in dependency order, not the order that children were added
+        layout.constrain(c, Anchor::Top, Target::Child(b), Anchor::Bottom, 5).unwrap();
+        layout.constrain(b, Anchor::Top, Target::Child(a), Anchor::Bottom, 0).unwrap();
+        layout.constrain(b, Anchor::CenterX, Target::Child(a), Anchor::CenterX, 0).unwrap();
+
+        assert_eq!(position(&layout, b), (10, 40));
+        assert_eq!(position(&layout, c), (5, 65));
+        let rect = layout.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (0, 30, 40, 45));
+    }
+
+    #[test]
+    fn parent_constraints() {
+        let mut layout = ConstraintLayer::new(Rect { x: 0, y: 0, width: 100, height: 100 });
+        let a = layout.add(rectangle(0, 0, 20, 20));
+        layout.constrain(a, Anchor::Right, Target::Parent, Anchor::Right, -10).unwrap();
+        layout.constrain(a, Anchor::Bottom, Target::Parent, Anchor::Top, 5).unwrap();
+
+        // Positions above the top are clamped
+        assert_eq!(position(&layout, a), (70, 0));
+        assert_eq!(layout.filtered_pixel_at(70, 0).unwrap(), AlphaPixel::red());
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        let mut layout = ConstraintLayer::new(Rect { x: 0, y: 0, width: 100, height: 100 });
+        let a = layout.add(rectangle(0, 0, 20, 20));
+        let b = layout.add(rectangle(0, 50, 20, 20));
+
+        assert_eq!(layout.constrain(a, Anchor::Top, Target::Parent, Anchor::Left, 0), Err(ConstraintError::MismatchedAxis));
+        layout.constrain(a, Anchor::Top, Target::Child(b), Anchor::Bottom, 0).unwrap();
+        assert_eq!(layout.constrain(b, Anchor::Top, Target::Child(a), Anchor::Bottom, 0), Err(ConstraintError::Cycle));
+
+        // The cycle isn't added
+        assert_eq!(position(&layout, b), (0, 50));
+        assert_eq!(position(&layout, a), (0, 70));
+    }
+
+    #[test]
+    fn replaced_child() {
+        let mut layout = ConstraintLayer::new(Rect { x: 0, y: 0, width: 100, height: 100 });
+        let title = layout.add(rectangle(0, 10, 40, 10));
+        let subtitle = layout.add(rectangle(0, 0, 20, 10));
+        layout.constrain(subtitle, Anchor::Top, Target::Child(title), Anchor::Bottom, 5).unwrap();
+        assert_eq!(position(&layout, subtitle), (0, 25));
+
+        // A taller title pushes the subtitle down, and keeps its own constraints
+        *layout.get_child_mut(title).unwrap() = Box::new(rectangle(0, 10, 40, 30));
+        assert_eq!(position(&layout, subtitle), (0, 45));
+        assert_eq!(layout.get_rect().height, 45);
+        assert_eq!(layout.filtered_pixel_at(0, 50).unwrap(), AlphaPixel::red());
+        assert!(layout.get_child_mut(ChildId(2)).is_none());
+    }
+}
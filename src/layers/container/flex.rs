@@ -0,0 +1,342 @@
+use std::cell::OnceCell;
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod};
+use super::{CrossAlign, moved_rect, moved_pixel_at};
+
+/// The axis that a [`FlexLayer`] places its children along.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FlexDirection {
+    /// Left to right
+    #[default]
+    Row,
+    /// Top to bottom
+    Column
+}
+
+/// How free space on the main axis is distributed, when the children don't grow to fill it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum JustifyContent {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// Free space is divided between the gaps, with no space before the first child or after the last
+    SpaceBetween,
+    /// Free space is divided equally around each child, so the space at the edges is half of the space between children
+    SpaceAround
+}
+
+/// The layout of a [`FlexLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FlexStyle {
+    pub direction: FlexDirection,
+    /// Space between each child on the main axis, in pixels
+    pub gap: usize,
+    /// Space inside every edge of the container, in pixels
+    pub padding: usize,
+    pub justify: JustifyContent,
+    /// The default alignment of children on the cross axis
+    pub align_items: CrossAlign
+}
+
+/// How a child of a [`FlexLayer`] is sized and aligned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlexItem {
+    /// The share of free space that this child's slot grows by, relative to the other children
+    pub grow: f32,
+    /// How much this child's slot shrinks when there isn't enough space, relative to the other children
+    /// and weighted by its basis
+    pub shrink: f32,
+    /// The size of this child's slot on the main axis before growing or shrinking.
+    /// If `None`, the size of the child's `Rect` is used.
+    pub basis: Option<usize>,
+    /// Overrides the container's `align_items` for this child
+    pub align_self: Option<CrossAlign>
+}
+
+impl Default for FlexItem {
+    fn default() -> Self {
+        Self { grow: 0.0, shrink: 1.0, basis: None, align_self: None }
+    }
+}
+
+struct FlexChild<T: PixelChannel> {
+    layer: Box<dyn Layer<T>>,
+    item: FlexItem
+}
+
+/// Where a child is placed by the layout.
+struct Placement {
+    /// The top left of the child's `Rect`
+    position: (usize, usize),
+    /// The space given to the child. The child is cut off at the edges of its slot.
+    slot: Rect
+}
+
+/// A layer which arranges its children in a row or column within a fixed `Rect`, similar to CSS flexbox.
+/// 
+/// Each child is given a slot on the main axis, which can grow to fill free space or shrink when there isn't enough.
+/// Children are placed at the start of their slot and cut off at its edges, as layers can't be resized.
+/// Children are positioned from the size of their `Rect` when the layer is drawn, so templates adapt when text
+/// length or image sizes change, such as when a child is replaced with [`FlexLayer::get_child_mut`].
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Rect, layers::{shapes::RectangleLayer, container::{CrossAlign, flex::{FlexLayer, FlexStyle, FlexItem, JustifyContent}}}};
+/// 
+/// let style = FlexStyle { gap: 10, padding: 20, justify: JustifyContent::SpaceBetween, align_items: CrossAlign::Center, ..Default::default() };
+/// let mut header: FlexLayer<u8> = FlexLayer::new(Rect { x: 0, y: 0, width: 800, height: 100 }, style);
+/// header.push(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 60, height: 60 }), FlexItem::default());
+/// header.push(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 0, y: 0, width: 200, height: 30 }), FlexItem::default());
+/// 
+/// // The second child is pushed to the right edge, inside the padding
+/// assert_eq!(header.get_child_rects()[1].x, 580);
+/// ```
+pub struct FlexLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    children: Vec<FlexChild<T>>,
+    rect: Rect,
+    style: FlexStyle,
+    /// Laid out the first time that the layer is drawn or measured, and cleared when it is changed
+    placements: OnceCell<Vec<Placement>>
+}
+
+impl<T: PixelChannel> FlexLayer<T> {
+    /// Create an empty container, which fills `rect`.
+    pub fn new(rect: Rect, style: FlexStyle) -> Self {
+        Self { filters: vec![], children: vec![], rect, style, placements: OnceCell::new() }
+    }
+
+    pub fn push<L: Layer<T> + 'static>(&mut self, layer: L, item: FlexItem) {
+        self.push_boxed(Box::new(layer), item);
+    }
+
+    pub fn push_boxed(&mut self, layer: Box<dyn Layer<T>>, item: FlexItem) {
+        self.placements.take();
+        self.children.push(FlexChild { layer, item });
+    }
+
+    /// Get a child to replace it, such as with a text layer with different text. The children are laid out again
+    /// the next time that the layer is drawn.
+    pub fn get_child_mut(&mut self, index: usize) -> Option<&mut Box<dyn Layer<T>>> {
+        self.placements.take();
+        self.children.get_mut(index).map(|child| &mut child.layer)
+    }
+
+    /// Change how a child is sized and aligned.
+    pub fn set_item(&mut self, index: usize, item: FlexItem) {
+        self.placements.take();
+        if let Some(child) = self.children.get_mut(index) {
+            child.item = item;
+        }
+    }
+
+    pub fn get_style(&self) -> FlexStyle {
+        self.style
+    }
+
+    pub fn set_style(&mut self, style: FlexStyle) {
+        self.placements.take();
+        self.style = style;
+    }
+
+    /// Move and resize the container.
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.placements.take();
+        self.rect = rect;
+    }
+
+    /// Get the `Rect` of each child, after it has been positioned. This isn't cut off by the child's slot.
+    pub fn get_child_rects(&self) -> Vec<Rect> {
+        self.children.iter()
+            .zip(self.placements())
+            .map(|(child, placement)| moved_rect(child.layer.as_ref(), placement.position))
+            .collect()
+    }
+
+    /// Get the slot of each child, which is the space that it was given on the main axis.
+    pub fn get_slot_rects(&self) -> Vec<Rect> {
+        self.placements().iter().map(|placement| placement.slot).collect()
+    }
+
+    /// Get the size of a `Rect` on the main and cross axes.
+    fn main_and_cross(&self, rect: Rect) -> (usize, usize) {
+        match self.style.direction {
+            FlexDirection::Row => (rect.width, rect.height),
+            FlexDirection::Column => (rect.height, rect.width)
+        }
+    }
+
+    fn placements(&self) -> &[Placement] {
+        self.placements.get_or_init(|| self.layout())
+    }
+
+    /// Place every child, from the sizes of their `Rect`s.
+    fn layout(&self) -> Vec<Placement> {
+        let padding = self.style.padding;
+        let (main_size, cross_size) = self.main_and_cross(self.rect);
+        let main_size = main_size.saturating_sub(2*padding) as f32;
+        let cross_size = cross_size.saturating_sub(2*padding);
+
+        let count = self.children.len();
+        let bases: Vec<f32> = self.children.iter()
+            .map(|child| child.item.basis.unwrap_or_else(|| self.main_and_cross(child.layer.get_rect()).0) as f32)
+            .collect();
+        let total_gaps = (self.style.gap * count.saturating_sub(1)) as f32;
+        let mut free = main_size - bases.iter().sum::<f32>() - total_gaps;
+
+        let total_grow: f32 = self.children.iter().map(|child| child.item.grow.max(0.0)).sum();
+        let total_shrink: f32 = self.children.iter().zip(&bases).map(|(child, basis)| child.item.shrink.max(0.0) * basis).sum();
+
+        let sizes: Vec<f32> = if free > 0.0 && total_grow > 0.0 {
+            let sizes = self.children.iter().zip(&bases)
+                .map(|(child, basis)| basis + free * child.item.grow.max(0.0) / total_grow)
+                .collect();
+            free = 0.0;
+            sizes
+        } else if free < 0.0 && total_shrink > 0.0 {
+            let sizes = self.children.iter().zip(&bases)
+                .map(|(child, basis)| (basis + free * child.item.shrink.max(0.0) * basis / total_shrink).max(0.0))
+                .collect();
+            free = 0.0;
+            sizes
+        } else {
+            bases
+        };
+
+        let free = free.max(0.0);
+        let (mut position, spacing) = match self.style.justify {
+            JustifyContent::Start => (0.0, 0.0),
+            JustifyContent::Center => (free / 2.0, 0.0),
+            JustifyContent::End => (free, 0.0),
+            JustifyContent::SpaceBetween if count > 1 => (0.0, free / (count - 1) as f32),
+            JustifyContent::SpaceBetween => (0.0, 0.0),
+            JustifyContent::SpaceAround => (free / count.max(1) as f32 / 2.0, free / count.max(1) as f32)
+        };
+
+        let (start_x, start_y) = (self.rect.x + padding, self.rect.y + padding);
+        let mut placements = Vec::with_capacity(count);
+        for (flex_child, size) in self.children.iter().zip(sizes) {
+            let child_rect = flex_child.layer.get_rect();
+            let align = flex_child.item.align_self.unwrap_or(self.style.align_items);
+            let main_start = position.round() as usize;
+            let main_end = (position + size).round() as usize;
+
+            placements.push(match self.style.direction {
+                FlexDirection::Row => Placement {
+                    position: (start_x + main_start, start_y + align.offset(child_rect.height, cross_size)),
+                    slot: Rect { x: start_x + main_start, y: start_y, width: main_end - main_start, height: cross_size }
+                },
+                FlexDirection::Column => Placement {
+                    position: (start_x + align.offset(child_rect.width, cross_size), start_y + main_start),
+                    slot: Rect { x: start_x, y: start_y + main_start, width: cross_size, height: main_end - main_start }
+                }
+            });
+
+            position += size + self.style.gap as f32 + spacing;
+        }
+        placements
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for FlexLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn children(&self) -> Vec<&dyn Layer<T>> {
+        self.children.iter().map(|child| child.layer.as_ref()).collect()
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.children.iter()
+            .zip(self.placements())
+            .filter(|(_child, placement)| placement.slot.contains(x, y))
+            .filter_map(|(child, placement)| moved_pixel_at(child.layer.as_ref(), placement.position, x, y))
+            .fold(AlphaPixel::default(), |background, pixel| BlendingMethod::Over.blend(background, pixel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::shapes::RectangleLayer;
+
+    const CONTAINER: Rect = Rect { x: 10, y: 10, width: 100, height: 50 };
+
+    fn rectangle(width: usize, height: usize) -> RectangleLayer<u8> {
+        RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width, height })
+    }
+
+    fn slot_sizes(flex: &FlexLayer<u8>) -> Vec<usize> {
+        flex.get_slot_rects().iter().map(|slot| slot.width).collect()
+    }
+
+    #[test]
+    fn grow() {
+        let mut flex = FlexLayer::new(CONTAINER, FlexStyle { gap: 10, ..Default::default() });
+        flex.push(rectangle(10, 10), FlexItem { grow: 1.0, ..Default::default() });
+        flex.push(rectangle(10, 10), FlexItem { grow: 3.0, ..Default::default() });
+
+        // 70 pixels of free space, divided 1:3
+        assert_eq!(slot_sizes(&flex), vec![28, 62]);
+        assert_eq!(flex.get_child_rects()[1].x, 48);
+    }
+
+    #[test]
+    fn shrink() {
+        let mut flex = FlexLayer::new(CONTAINER, FlexStyle::default());
+        flex.push(rectangle(100, 10), FlexItem::default());
+        flex.push(rectangle(50, 10), FlexItem { shrink: 0.0, ..Default::default() });
+
+        assert_eq!(slot_sizes(&flex), vec![50, 50]);
+        // The first child is cut off at the end of its slot
+        assert_eq!(flex.filtered_pixel_at(59, 15).unwrap(), AlphaPixel::red());
+        assert_eq!(flex.get_child_rects()[1].x, 60);
+    }
+
+    #[test]
+    fn justify_and_align() {
+        let style = FlexStyle { padding: 5, justify: JustifyContent::SpaceAround, align_items: CrossAlign::Center, ..Default::default() };
+        let mut flex = FlexLayer::new(CONTAINER, style);
+        flex.push(rectangle(20, 10), FlexItem::default());
+        flex.push(rectangle(20, 40), FlexItem { align_self: Some(CrossAlign::End), ..Default::default() });
+
+        let rects = flex.get_child_rects();
+        // 50 pixels of free space, 12.5 around each child
+        assert_eq!((rects[0].x, rects[0].y), (28, 30));
+        assert_eq!((rects[1].x, rects[1].y), (73, 15));
+    }
+
+    #[test]
+    fn column() {
+        let style = FlexStyle { direction: FlexDirection::Column, justify: JustifyContent::End, ..Default::default() };
+        let mut flex = FlexLayer::new(CONTAINER, style);
+        flex.push(rectangle(20, 10), FlexItem::default());
+        flex.push(rectangle(20, 15), FlexItem::default());
+
+        let rects = flex.get_child_rects();
+        assert_eq!((rects[0].x, rects[0].y), (10, 35));
+        assert_eq!((rects[1].x, rects[1].y), (10, 45));
+    }
+
+    #[test]
+    fn replaced_child() {
+        let mut flex = FlexLayer::new(CONTAINER, FlexStyle { gap: 10, ..Default::default() });
+        flex.push(rectangle(20, 10), FlexItem::default());
+        flex.push(rectangle(20, 10), FlexItem::default());
+        assert_eq!(flex.get_child_rects()[1].x, 40);
+
+        // The layout is cached until a child is replaced
+        *flex.get_child_mut(0).unwrap() = Box::new(rectangle(40, 10));
+        assert_eq!(flex.get_child_rects()[1].x, 60);
+        assert_eq!(flex.filtered_pixel_at(45, 15).unwrap(), AlphaPixel::red());
+
+        flex.set_item(0, FlexItem { basis: Some(10), ..Default::default() });
+        assert_eq!(slot_sizes(&flex), vec![10, 20]);
+        assert!(flex.get_child_mut(2).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod};
+use super::{CrossAlign, PlacedChild, bounding_rect};
+
+/// The direction that a [`FlowLayer`] stacks its children in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlowDirection {
+    /// Top to bottom
+    Vertical,
+    /// Left to right
+    Horizontal
+}
+
+/// A layer which stacks its children one after another, with a gap between each child.
+/// 
+/// Children are positioned by the size of their `Rect`, so the position that they were created with is ignored.
+/// Children later in the flow are drawn over earlier children.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, AlphaPixel, Rect, Layer, layers::{shapes::RectangleLayer, container::{CrossAlign, flow::{FlowLayer, FlowDirection}}}};
+/// 
+/// let mut flow: FlowLayer<u8> = FlowLayer::new(10, 10, FlowDirection::Vertical, 20, CrossAlign::Center);
+/// flow.push(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 100, height: 40 }));
+/// flow.push(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 0, y: 0, width: 50, height: 40 }));
+/// 
+/// assert_eq!(flow.get_child_rects()[1].y, 70);
+/// assert_eq!(flow.get_child_rects()[1].x, 35);
+/// 
+/// let mut canvas = Canvas::from_dimensions(200, 200);
+/// canvas.add_layer(flow);
+/// ```
+pub struct FlowLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    children: Vec<PlacedChild<T>>,
+    x: usize,
+    y: usize,
+    direction: FlowDirection,
+    gap: usize,
+    align: CrossAlign,
+    rect: Rect
+}
+
+impl<T: PixelChannel> FlowLayer<T> {
+    /// Create an empty flow, with its top left corner at (`x`, `y`).
+    pub fn new(x: usize, y: usize, direction: FlowDirection, gap: usize, align: CrossAlign) -> Self {
+        Self { filters: vec![], children: vec![], x, y, direction, gap, align, rect: Rect { x, y, width: 0, height: 0 } }
+    }
+
+    /// Add a layer to the end of the flow.
+    pub fn push<L: Layer<T> + 'static>(&mut self, layer: L) {
+        self.push_boxed(Box::new(layer));
+    }
+
+    pub fn push_boxed(&mut self, layer: Box<dyn Layer<T>>) {
+        self.children.push(PlacedChild::new(layer));
+        self.layout();
+    }
+
+    /// Get the `Rect` of each child, after it has been positioned.
+    pub fn get_child_rects(&self) -> Vec<Rect> {
+        self.children.iter().map(|child| child.rect()).collect()
+    }
+
+    /// Position the children, and update the bounding `Rect`.
+    fn layout(&mut self) {
+        let cross_size = self.children.iter()
+            .map(|child| {
+                let rect = child.layer.get_rect();
+                match self.direction {
+                    FlowDirection::Vertical => rect.width,
+                    FlowDirection::Horizontal => rect.height
+                }
+            })
+            .max()
+            .unwrap_or(0);
+
+        let mut position = 0;
+        for child in &mut self.children {
+            let rect = child.layer.get_rect();
+            match self.direction {
+                FlowDirection::Vertical => {
+                    child.x = self.x + self.align.offset(rect.width, cross_size);
+                    child.y = self.y + position;
+                    position += rect.height + self.gap;
+                },
+                FlowDirection::Horizontal => {
+                    child.x = self.x + position;
+                    child.y = self.y + self.align.offset(rect.height, cross_size);
+                    position += rect.width + self.gap;
+                }
+            }
+        }
+
+        self.rect = bounding_rect(self.children.iter().map(|child| child.rect()), self.x, self.y);
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for FlowLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn children(&self) -> Vec<&dyn Layer<T>> {
+        self.children.iter().map(|child| child.layer.as_ref()).collect()
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.children.iter()
+            .filter_map(|child| child.pixel_at(x, y))
+            .fold(AlphaPixel::default(), |background, pixel| BlendingMethod::Over.blend(background, pixel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::shapes::RectangleLayer;
+
+    fn rectangle(fill: AlphaPixel<u8>, width: usize, height: usize) -> RectangleLayer<u8> {
+        // The original position is ignored by the flow
+        RectangleLayer::new(fill, Rect { x: 500, y: 500, width, height })
+    }
+
+    #[test]
+    fn horizontal_flow() {
+        let mut flow = FlowLayer::new(5, 5, FlowDirection::Horizontal, 10, CrossAlign::End);
+        flow.push(rectangle(AlphaPixel::red(), 20, 30));
+        flow.push(rectangle(AlphaPixel::blue(), 10, 10));
+
+        let rects = flow.get_child_rects();
+        assert_eq!((rects[0].x, rects[0].y), (5, 5));
+        assert_eq!((rects[1].x, rects[1].y), (35, 25));
+
+        let rect = flow.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (5, 5, 40, 30));
+
+        assert_eq!(flow.filtered_pixel_at(5, 5).unwrap(), AlphaPixel::red());
+        assert_eq!(flow.filtered_pixel_at(40, 30).unwrap(), AlphaPixel::blue());
+        // The gap, and the space above the second child, are transparent
+        assert_eq!(flow.filtered_pixel_at(30, 30).unwrap().a, 0);
+        assert_eq!(flow.filtered_pixel_at(40, 10).unwrap().a, 0);
+    }
+
+    #[test]
+    fn empty_flow() {
+        let flow: FlowLayer<u8> = FlowLayer::new(5, 5, FlowDirection::Vertical, 10, CrossAlign::Start);
+        assert_eq!(flow.get_rect().width, 0);
+        assert!(flow.filtered_pixel_at(5, 5).is_none());
+    }
+}
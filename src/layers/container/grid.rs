@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use thiserror::Error;
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod};
+use super::{CrossAlign, PlacedChild};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum GridError {
+    #[error("Grid template has {found} rows, but the grid has {expected}.")]
+    WrongRowCount { expected: usize, found: usize },
+    #[error("Row {row} of the grid template has {found} columns, but the grid has {expected}.")]
+    WrongColumnCount { row: usize, expected: usize, found: usize },
+    #[error("Grid area \"{0}\" isn't a rectangle.")]
+    NonRectangularArea(String),
+    #[error("Grid has no area named \"{0}\".")]
+    UnknownArea(String),
+    #[error("Grid area is outside of the grid.")]
+    AreaOutOfBounds
+}
+
+/// The size of a row or column of a [`GridLayer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Track {
+    /// A fixed size, in pixels
+    Px(usize),
+    /// A share of the space left over after fixed tracks and gaps, relative to the other fractional tracks
+    Fr(f32)
+}
+
+/// A rectangle of cells in a [`GridLayer`], from the column and row of its top left cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridArea {
+    pub column: usize,
+    pub row: usize,
+    pub column_span: usize,
+    pub row_span: usize
+}
+
+impl GridArea {
+    /// A single cell.
+    pub fn cell(column: usize, row: usize) -> Self {
+        Self { column, row, column_span: 1, row_span: 1 }
+    }
+}
+
+/// How a child of a [`GridLayer`] is aligned within its area.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GridItem {
+    pub horizontal: CrossAlign,
+    pub vertical: CrossAlign
+}
+
+impl GridItem {
+    pub fn centered() -> Self {
+        Self { horizontal: CrossAlign::Center, vertical: CrossAlign::Center }
+    }
+}
+
+struct GridChild<T: PixelChannel> {
+    child: PlacedChild<T>,
+    /// The pixels covered by the child's area. The child is cut off at its edges.
+    area: Rect
+}
+
+/// A layer which divides a fixed `Rect` into rows and columns, similar to CSS grid, and places its children in areas of the grid.
+/// 
+/// Areas can be named with a template of strings, one per row, where each cell is a whitespace separated name.
+/// Cells with the same name form an area, which must be a rectangle. Cells named `.` are left unnamed.
+/// This allows poster layouts to be described as text, such as in a config file.
+/// 
+/// Children are aligned within their area, and cut off at its edges.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Rect, layers::{shapes::RectangleLayer, container::grid::{GridLayer, GridItem, Track}}};
+/// 
+/// let mut poster: GridLayer<u8> = GridLayer::new(
+///     Rect { x: 0, y: 0, width: 600, height: 800 },
+///     vec![Track::Px(200), Track::Fr(1.0)],
+///     vec![Track::Px(100), Track::Fr(1.0), Track::Px(50)],
+///     10
+/// ).with_areas(&[
+///     "header header",
+///     "sidebar main",
+///     "footer footer"
+/// ]).unwrap();
+/// 
+/// let title = RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 300, height: 60 });
+/// poster.place_in(title, "header", GridItem::centered()).unwrap();
+/// 
+/// let main = poster.get_area_rect("main").unwrap();
+/// assert_eq!((main.x, main.y, main.width, main.height), (210, 110, 390, 630));
+/// ```
+pub struct GridLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    children: Vec<GridChild<T>>,
+    rect: Rect,
+    /// The start and size of each column, in pixels
+    columns: Vec<(usize, usize)>,
+    /// The start and size of each row, in pixels
+    rows: Vec<(usize, usize)>,
+    areas: HashMap<String, GridArea>
+}
+
+impl<T: PixelChannel> GridLayer<T> {
+    /// Create an empty grid which fills `rect`, with `gap` pixels between each row and column.
+    pub fn new(rect: Rect, columns: Vec<Track>, rows: Vec<Track>, gap: usize) -> Self {
+        Self {
+            filters: vec![],
+            children: vec![],
+            columns: resolve_tracks(&columns, rect.x, rect.width, gap),
+            rows: resolve_tracks(&rows, rect.y, rect.height, gap),
+            rect,
+            areas: HashMap::new()
+        }
+    }
+
+    /// Name areas of the grid from a template, with one string for each row.
+    pub fn with_areas(mut self, template: &[&str]) -> Result<Self, GridError> {
+        let cells: Vec<Vec<&str>> = template.iter().map(|row| row.split_whitespace().collect()).collect();
+        if cells.len() != self.rows.len() {
+            return Err(GridError::WrongRowCount { expected: self.rows.len(), found: cells.len() })
+        }
+        if let Some((row, found)) = cells.iter().enumerate().find(|(_, row)| row.len() != self.columns.len()) {
+            return Err(GridError::WrongColumnCount { row, expected: self.columns.len(), found: found.len() })
+        }
+
+        // Expand the bounds of each name to every cell it is in, then check that it fills its bounds
+        let mut areas: HashMap<String, (usize, usize, usize, usize)> = HashMap::new();
+        for (row, names) in cells.iter().enumerate() {
+            for (column, name) in names.iter().enumerate().filter(|(_, name)| **name != ".") {
+                let bounds = areas.entry(name.to_string()).or_insert((column, row, column, row));
+                *bounds = (bounds.0.min(column), bounds.1.min(row), bounds.2.max(column), bounds.3.max(row));
+            }
+        }
+
+        for (name, (left, top, right, bottom)) in areas {
+            let filled = (top..=bottom).all(|row| (left..=right).all(|column| cells[row][column] == name));
+            if !filled {
+                return Err(GridError::NonRectangularArea(name))
+            }
+            self.areas.insert(name, GridArea { column: left, row: top, column_span: right - left + 1, row_span: bottom - top + 1 });
+        }
+
+        Ok(self)
+    }
+
+    /// Get a named area of the grid.
+    pub fn get_area(&self, name: &str) -> Option<GridArea> {
+        self.areas.get(name).copied()
+    }
+
+    /// Get the pixels covered by a named area of the grid.
+    pub fn get_area_rect(&self, name: &str) -> Option<Rect> {
+        self.area_rect(self.get_area(name)?).ok()
+    }
+
+    /// Get the pixels covered by an area of the grid.
+    pub fn area_rect(&self, area: GridArea) -> Result<Rect, GridError> {
+        if area.column_span == 0 || area.row_span == 0 {
+            return Err(GridError::AreaOutOfBounds)
+        }
+        let first_column = self.columns.get(area.column).ok_or(GridError::AreaOutOfBounds)?;
+        let last_column = self.columns.get(area.column + area.column_span - 1).ok_or(GridError::AreaOutOfBounds)?;
+        let first_row = self.rows.get(area.row).ok_or(GridError::AreaOutOfBounds)?;
+        let last_row = self.rows.get(area.row + area.row_span - 1).ok_or(GridError::AreaOutOfBounds)?;
+
+        Ok(Rect {
+            x: first_column.0,
+            y: first_row.0,
+            width: last_column.0 + last_column.1 - first_column.0,
+            height: last_row.0 + last_row.1 - first_row.0
+        })
+    }
+
+    /// Place a layer in an area of the grid.
+    pub fn place<L: Layer<T> + 'static>(&mut self, layer: L, area: GridArea, item: GridItem) -> Result<(), GridError> {
+        self.place_boxed(Box::new(layer), area, item)
+    }
+
+    pub fn place_boxed(&mut self, layer: Box<dyn Layer<T>>, area: GridArea, item: GridItem) -> Result<(), GridError> {
+        let area = self.area_rect(area)?;
+        let mut child = PlacedChild::new(layer);
+        let rect = child.layer.get_rect();
+        child.x = area.x + item.horizontal.offset(rect.width, area.width);
+        child.y = area.y + item.vertical.offset(rect.height, area.height);
+        self.children.push(GridChild { child, area });
+        Ok(())
+    }
+
+    /// Place a layer in a named area of the grid.
+    pub fn place_in<L: Layer<T> + 'static>(&mut self, layer: L, name: &str, item: GridItem) -> Result<(), GridError> {
+        let area = self.get_area(name).ok_or_else(|| GridError::UnknownArea(name.to_string()))?;
+        self.place(layer, area, item)
+    }
+
+    /// Get the `Rect` of each child, after it has been positioned. This isn't cut off by the child's area.
+    pub fn get_child_rects(&self) -> Vec<Rect> {
+        self.children.iter().map(|child| child.child.rect()).collect()
+    }
+}
+
+/// Get the start and size of each track, within a space starting at `start` of size `size`.
+fn resolve_tracks(tracks: &[Track], start: usize, size: usize, gap: usize) -> Vec<(usize, usize)> {
+    let fixed: usize = tracks.iter().map(|track| match track { Track::Px(pixels) => *pixels, Track::Fr(_) => 0 }).sum();
+    let gaps = gap * tracks.len().saturating_sub(1);
+    let free = size.saturating_sub(fixed + gaps) as f32;
+    let total_fractions: f32 = tracks.iter().map(|track| match track { Track::Fr(fraction) => fraction.max(0.0), Track::Px(_) => 0.0 }).sum();
+
+    // Positions are accumulated as floats and rounded, so that fractional tracks fill the space exactly
+    let mut position = start as f32;
+    tracks.iter()
+        .map(|track| {
+            let track_size = match track {
+                Track::Px(pixels) => *pixels as f32,
+                Track::Fr(fraction) if total_fractions > 0.0 => free * fraction.max(0.0) / total_fractions,
+                Track::Fr(_) => 0.0
+            };
+            let track_start = position.round() as usize;
+            let track_end = (position + track_size).round() as usize;
+            position += track_size + gap as f32;
+            (track_start, track_end - track_start)
+        })
+        .collect()
+}
+
+impl<T: PixelChannel> Layer<T> for GridLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn children(&self) -> Vec<&dyn Layer<T>> {
+        self.children.iter().map(|child| child.child.layer.as_ref()).collect()
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.children.iter()
+            .filter(|child| child.area.contains(x, y))
+            .filter_map(|child| child.child.pixel_at(x, y))
+            .fold(AlphaPixel::default(), |background, pixel| BlendingMethod::Over.blend(background, pixel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::shapes::RectangleLayer;
+
+    fn grid() -> GridLayer<u8> {
+        GridLayer::new(
+            Rect { x: 0, y: 0, width: 100, height: 100 },
+            vec![Track::Fr(1.0), Track::Px(20), Track::Fr(3.0)],
+            vec![Track::Fr(1.0), Track::Fr(1.0)],
+            0
+        )
+    }
+
+    #[test]
+    fn fractional_tracks() {
+        let grid = grid();
+        let rect = grid.area_rect(GridArea::cell(2, 1)).unwrap();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (40, 50, 60, 50));
+    }
+
+    #[test]
+    fn named_areas() {
+        let grid = grid().with_areas(&["a a b", ". . b"]).unwrap();
+        assert_eq!(grid.get_area("a").unwrap(), GridArea { column: 0, row: 0, column_span: 2, row_span: 1 });
+        assert_eq!(grid.get_area("b").unwrap(), GridArea { column: 2, row: 0, column_span: 1, row_span: 2 });
+        assert!(grid.get_area(".").is_none());
+    }
+
+    #[test]
+    fn invalid_areas() {
+        assert_eq!(grid().with_areas(&["a a b", "a . b"]).err().unwrap(), GridError::NonRectangularArea(String::from("a")));
+        assert!(matches!(grid().with_areas(&["a a b"]), Err(GridError::WrongRowCount { .. })));
+        assert!(matches!(grid().with_areas(&["a a", "b b b"]), Err(GridError::WrongColumnCount { row: 0, .. })));
+    }
+
+    #[test]
+    fn placed_child() {
+        let mut grid = grid().with_areas(&["a a b", ". . b"]).unwrap();
+        let square = RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 80, height: 10 });
+        grid.place_in(square, "b", GridItem::centered()).unwrap();
+
+        // The child is wider than its area, so it is cut off at the edges
+        assert_eq!(grid.get_child_rects()[0].y, 45);
+        assert_eq!(grid.filtered_pixel_at(40, 50).unwrap(), AlphaPixel::red());
+        assert_eq!(grid.filtered_pixel_at(39, 50).unwrap().a, 0);
+        assert!(grid.place_in(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 1, height: 1 }), "c", GridItem::default()).is_err());
+    }
+}
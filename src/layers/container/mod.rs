@@ -0,0 +1,88 @@
+//! Layers which contain other layers, and position them by their `Rect`s.
+//! 
+//! Any layer can be a child of a container. Children are moved by sampling them with an offset,
+//! so the child's own position is ignored, and only the size of its `Rect` is used.
+
+pub mod flow;
+pub mod flex;
+pub mod grid;
+pub mod constraints;
+
+use crate::{Layer, AlphaPixel, PixelChannel, Rect};
+
+/// How children are aligned on the axis that they aren't stacked along.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CrossAlign {
+    #[default]
+    Start,
+    Center,
+    End
+}
+
+impl CrossAlign {
+    /// Get the offset of a child of size `child_size` within a space of size `available`.
+    pub(crate) fn offset(&self, child_size: usize, available: usize) -> usize {
+        let free = available.saturating_sub(child_size);
+        match self {
+            CrossAlign::Start => 0,
+            CrossAlign::Center => free / 2,
+            CrossAlign::End => free
+        }
+    }
+}
+
+/// A child layer, and the position that the top left of its `Rect` is moved to.
+pub(crate) struct PlacedChild<T: PixelChannel> {
+    pub layer: Box<dyn Layer<T>>,
+    pub x: usize,
+    pub y: usize
+}
+
+impl<T: PixelChannel> PlacedChild<T> {
+    pub fn new(layer: Box<dyn Layer<T>>) -> Self {
+        let rect = layer.get_rect();
+        Self { layer, x: rect.x, y: rect.y }
+    }
+
+    /// The `Rect` of the child, after it has been moved.
+    pub fn rect(&self) -> Rect {
+        moved_rect(self.layer.as_ref(), (self.x, self.y))
+    }
+
+    /// Get the filtered pixel of the child at a canvas location, after it has been moved.
+    pub fn pixel_at(&self, x: usize, y: usize) -> Option<AlphaPixel<T>> {
+        moved_pixel_at(self.layer.as_ref(), (self.x, self.y), x, y)
+    }
+}
+
+/// Get the `Rect` of a layer, after the top left of its `Rect` has been moved to `position`.
+pub(crate) fn moved_rect<T: PixelChannel>(layer: &dyn Layer<T>, position: (usize, usize)) -> Rect {
+    let rect = layer.get_rect();
+    Rect { x: position.0, y: position.1, width: rect.width, height: rect.height }
+}
+
+/// Get the filtered pixel of a layer at a canvas location, after the top left of its `Rect` has been moved to `position`.
+pub(crate) fn moved_pixel_at<T: PixelChannel>(layer: &dyn Layer<T>, position: (usize, usize), x: usize, y: usize) -> Option<AlphaPixel<T>> {
+    if !moved_rect(layer, position).contains(x, y) {
+        return None
+    }
+
+    let rect = layer.get_rect();
+    layer.filtered_pixel_at(x - position.0 + rect.x, y - position.1 + rect.y)
+}
+
+/// Get the smallest `Rect` containing all of `rects`, or an empty `Rect` at (`x`, `y`) if there are none.
+pub(crate) fn bounding_rect(rects: impl Iterator<Item = Rect>, x: usize, y: usize) -> Rect {
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for rect in rects {
+        bounds = Some(match bounds {
+            Some((left, top, right, bottom)) => (left.min(rect.x), top.min(rect.y), right.max(rect.right_x()), bottom.max(rect.bottom_y())),
+            None => (rect.x, rect.y, rect.right_x(), rect.bottom_y())
+        });
+    }
+
+    match bounds {
+        Some((left, top, right, bottom)) => Rect { x: left, y: top, width: right - left, height: bottom - top },
+        None => Rect { x, y, width: 0, height: 0 }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod, bitmap::ramp::ColorRamp, layers::shapes::linear_gradient_position};
+
+/// The maximum number of samples taken along each axis when blurring the layer's alpha.
+/// Larger radii are sampled sparsely, to keep the cost per pixel bounded.
+const MAX_SAMPLES_PER_AXIS: usize = 9;
+
+/// Replace the colour of the layer with a solid colour.
+#[derive(Clone, Copy)]
+pub struct ColorOverlay<T> {
+    pub color: AlphaPixel<T>,
+    pub opacity: f32
+}
+
+impl<T> ColorOverlay<T> {
+    pub fn new(color: AlphaPixel<T>) -> Self {
+        Self { color, opacity: 1.0 }
+    }
+}
+
+/// Replace the colour of the layer with a linear gradient across its `Rect`.
+#[derive(Clone)]
+pub struct GradientOverlay<T> {
+    pub ramp: ColorRamp<T>,
+    /// The direction of the gradient in degrees, clockwise from left to right
+    pub angle: f32,
+    pub opacity: f32
+}
+
+impl<T: PixelChannel> GradientOverlay<T> {
+    /// Create a gradient from `start` to `end`.
+    pub fn new(start: AlphaPixel<T>, end: AlphaPixel<T>, angle: f32) -> Self {
+        Self::from_ramp(ColorRamp::two_color(start, end), angle)
+    }
+
+    pub fn from_ramp(ramp: ColorRamp<T>, angle: f32) -> Self {
+        Self { ramp, angle, opacity: 1.0 }
+    }
+}
+
+/// A shadow inside the edges of the layer, as if the layer was cut out of the surface beneath it.
+#[derive(Clone, Copy)]
+pub struct InnerShadow<T> {
+    pub color: AlphaPixel<T>,
+    /// The offset of the shadow, in pixels. A positive offset casts the shadow from the top left edges.
+    pub offset: (isize, isize),
+    /// The blur radius of the shadow, in pixels
+    pub radius: usize,
+    pub opacity: f32
+}
+
+impl<T: PixelChannel> InnerShadow<T> {
+    pub fn new(offset: (isize, isize), radius: usize) -> Self {
+        Self { color: AlphaPixel::black(), offset, radius, opacity: 0.75 }
+    }
+}
+
+/// A soft glow around the outside of the layer.
+#[derive(Clone, Copy)]
+pub struct OuterGlow<T> {
+    pub color: AlphaPixel<T>,
+    /// How far the glow extends from the edges of the layer, in pixels
+    pub radius: usize,
+    pub opacity: f32
+}
+
+impl<T> OuterGlow<T> {
+    pub fn new(color: AlphaPixel<T>, radius: usize) -> Self {
+        Self { color, radius, opacity: 0.75 }
+    }
+}
+
+/// Highlights and shadows along the edges of the layer, so that it appears raised.
+#[derive(Clone, Copy)]
+pub struct Bevel<T> {
+    /// The width of the bevelled edge, in pixels
+    pub size: usize,
+    /// The direction that the light comes from in degrees, clockwise from the right. The default of 225 is the top left.
+    pub light_angle: f32,
+    pub highlight: AlphaPixel<T>,
+    pub shadow: AlphaPixel<T>,
+    pub opacity: f32
+}
+
+impl<T: PixelChannel> Bevel<T> {
+    pub fn new(size: usize) -> Self {
+        Self { size, light_angle: 225.0, highlight: AlphaPixel::white(), shadow: AlphaPixel::black(), opacity: 0.75 }
+    }
+}
+
+/// A wrapper around a layer which adds effects like those in image editors: colour and gradient overlays,
+/// an inner shadow, an outer glow and a bevel.
+/// 
+/// Each effect is enabled by setting it to `Some`. The effects are always rendered in the same order, from bottom to top:
+/// outer glow, the layer, gradient overlay, colour overlay, inner shadow, then bevel.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, AlphaPixel, Rect, layers::{shapes::RectangleLayer, effects::{LayerEffects, OuterGlow, Bevel}}};
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+/// let button = RectangleLayer::new(AlphaPixel::blue(), Rect { x: 20, y: 30, width: 60, height: 40 });
+/// 
+/// let mut effects = LayerEffects::new(button);
+/// effects.outer_glow = Some(OuterGlow::new(AlphaPixel::white(), 8));
+/// effects.bevel = Some(Bevel::new(4));
+/// canvas.add_layer(effects);
+/// ```
+pub struct LayerEffects<T: PixelChannel, L: Layer<T>> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub layer: L,
+    pub color_overlay: Option<ColorOverlay<T>>,
+    pub gradient_overlay: Option<GradientOverlay<T>>,
+    pub inner_shadow: Option<InnerShadow<T>>,
+    pub outer_glow: Option<OuterGlow<T>>,
+    pub bevel: Option<Bevel<T>>
+}
+
+impl<T: PixelChannel, L: Layer<T>> LayerEffects<T, L> {
+    /// Wrap a layer, with no effects enabled.
+    pub fn new(layer: L) -> Self {
+        Self {
+            filters: vec![],
+            layer,
+            color_overlay: None,
+            gradient_overlay: None,
+            inner_shadow: None,
+            outer_glow: None,
+            bevel: None
+        }
+    }
+
+    /// Get the alpha of the wrapped layer from 0.0 to 1.0, or 0.0 outside of the canvas.
+    fn alpha_at(&self, x: isize, y: isize) -> f32 {
+        if x < 0 || y < 0 {
+            return 0.0
+        }
+
+        match self.layer.filtered_pixel_at(x as usize, y as usize) {
+            Some(pixel) => pixel.a.into() / T::MAX_PIXEL_VALUE.into(),
+            None => 0.0
+        }
+    }
+
+    /// Average the alpha of the wrapped layer in a square around a coordinate.
+    fn blurred_alpha_at(&self, x: isize, y: isize, radius: usize) -> f32 {
+        let step = (2*radius / (MAX_SAMPLES_PER_AXIS - 1)).max(1);
+        let radius = radius as isize;
+
+        let mut total = 0.0;
+        let mut count = 0.0;
+        for offset_y in (-radius..=radius).step_by(step) {
+            for offset_x in (-radius..=radius).step_by(step) {
+                total += self.alpha_at(x + offset_x, y + offset_y);
+                count += 1.0;
+            }
+        }
+
+        total / count
+    }
+
+    /// Get the strength of the bevel's lighting at a pixel, from -1.0 (fully in shadow) to 1.0 (fully highlighted).
+    fn bevel_lighting(&self, bevel: &Bevel<T>, x: isize, y: isize) -> f32 {
+        let gradient_x = self.blurred_alpha_at(x + 1, y, bevel.size) - self.blurred_alpha_at(x - 1, y, bevel.size);
+        let gradient_y = self.blurred_alpha_at(x, y + 1, bevel.size) - self.blurred_alpha_at(x, y - 1, bevel.size);
+
+        // The gradient across an edge blurred by `size` is about 2 / (2*size + 1), so normalise it to 1.0
+        let scale = (2*bevel.size + 1) as f32 / 2.0;
+        let (light_y, light_x) = bevel.light_angle.to_radians().sin_cos();
+        (-(gradient_x*light_x + gradient_y*light_y) * scale).clamp(-1.0, 1.0)
+    }
+}
+
+/// Blend `color` over the colour of `pixel` with the given opacity, keeping the alpha of `pixel`.
+fn overlay<T: PixelChannel>(pixel: AlphaPixel<T>, color: AlphaPixel<T>, opacity: f32) -> AlphaPixel<T> {
+    let opaque = AlphaPixel { a: T::MAX_PIXEL_VALUE, ..pixel };
+    let blended = BlendingMethod::Over.blend(opaque, color.scale_alpha(opacity));
+    AlphaPixel { a: pixel.a, ..blended }
+}
+
+impl<T: PixelChannel, L: Layer<T>> Layer<T> for LayerEffects<T, L> {
+    fn get_rect(&self) -> Rect {
+        let rect = self.layer.get_rect();
+        match &self.outer_glow {
+            Some(glow) => {
+                let x = rect.x.saturating_sub(glow.radius);
+                let y = rect.y.saturating_sub(glow.radius);
+                Rect { x, y, width: rect.right_x() + glow.radius - x, height: rect.bottom_y() + glow.radius - y }
+            },
+            None => rect
+        }
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let mut pixel = self.layer.filtered_pixel_at(x, y).unwrap_or_default();
+        let (signed_x, signed_y) = (x as isize, y as isize);
+
+        if pixel.a != T::zero() {
+            if let Some(gradient) = &self.gradient_overlay {
+                let position = linear_gradient_position(self.layer.get_rect(), gradient.angle, x, y);
+                pixel = overlay(pixel, gradient.ramp.color_at(position), gradient.opacity);
+            }
+
+            if let Some(color) = &self.color_overlay {
+                pixel = overlay(pixel, color.color, color.opacity);
+            }
+
+            if let Some(shadow) = &self.inner_shadow {
+                let shifted = self.blurred_alpha_at(signed_x - shadow.offset.0, signed_y - shadow.offset.1, shadow.radius);
+                pixel = overlay(pixel, shadow.color, (1.0 - shifted) * shadow.opacity);
+            }
+
+            if let Some(bevel) = &self.bevel {
+                let lighting = self.bevel_lighting(bevel, signed_x, signed_y);
+                pixel = if lighting > 0.0 {
+                    overlay(pixel, bevel.highlight, lighting * bevel.opacity)
+                } else {
+                    overlay(pixel, bevel.shadow, -lighting * bevel.opacity)
+                };
+            }
+        }
+
+        match &self.outer_glow {
+            Some(glow) => {
+                let glow_pixel = glow.color.scale_alpha(self.blurred_alpha_at(signed_x, signed_y, glow.radius) * glow.opacity);
+                BlendingMethod::Over.blend(glow_pixel, pixel)
+            },
+            None => pixel
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layers::shapes::RectangleLayer, bitmap::ramp::RampInterpolation, rgba};
+
+    const RECT: Rect = Rect { x: 10, y: 10, width: 20, height: 20 };
+
+    fn square() -> LayerEffects<u8, RectangleLayer<u8>> {
+        LayerEffects::new(RectangleLayer::new(AlphaPixel::red(), RECT))
+    }
+
+    #[test]
+    fn no_effects() {
+        let effects = square();
+        for y in 0..40 {
+            for x in 0..40 {
+                assert_eq!(effects.filtered_pixel_at(x, y), effects.layer.filtered_pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn overlays() {
+        let mut effects = square();
+        effects.gradient_overlay = Some(GradientOverlay::new(AlphaPixel::black(), AlphaPixel::white(), 0.0));
+        assert!(effects.filtered_pixel_at(10, 20).unwrap().g < 10);
+        assert!(effects.filtered_pixel_at(29, 20).unwrap().g > 245);
+
+        // Any ramp can be used, such as one with hard bands
+        let bands = ColorRamp::new(vec![(0.0, AlphaPixel::green()), (0.5, AlphaPixel::blue())]).with_interpolation(RampInterpolation::Step);
+        effects.gradient_overlay = Some(GradientOverlay::from_ramp(bands, 0.0));
+        assert_eq!(effects.filtered_pixel_at(18, 20).unwrap(), AlphaPixel::green());
+        assert_eq!(effects.filtered_pixel_at(25, 20).unwrap(), AlphaPixel::blue());
+
+        // The colour overlay is drawn over the gradient overlay
+        effects.color_overlay = Some(ColorOverlay::new(AlphaPixel::blue()));
+        assert_eq!(effects.filtered_pixel_at(20, 20).unwrap(), AlphaPixel::blue());
+        assert!(effects.filtered_pixel_at(5, 5).is_none());
+    }
+
+    #[test]
+    fn inner_shadow() {
+        let mut effects = square();
+        effects.inner_shadow = Some(InnerShadow { opacity: 1.0, ..InnerShadow::new((3, 3), 0) });
+
+        assert_eq!(effects.filtered_pixel_at(11, 11).unwrap(), AlphaPixel::black());
+        assert_eq!(effects.filtered_pixel_at(20, 20).unwrap(), AlphaPixel::red());
+        // The shadow is only cast from the top left edges
+        assert_eq!(effects.filtered_pixel_at(28, 28).unwrap(), AlphaPixel::red());
+    }
+
+    #[test]
+    fn outer_glow() {
+        let mut effects = square();
+        effects.outer_glow = Some(OuterGlow { opacity: 1.0, ..OuterGlow::new(AlphaPixel::white(), 4) });
+
+        assert_eq!(effects.get_rect().x, 6);
+        assert_eq!(effects.get_rect().width, 28);
+
+        let near = effects.filtered_pixel_at(9, 20).unwrap();
+        let far = effects.filtered_pixel_at(6, 20).unwrap();
+        assert_eq!(rgba!(near.r, near.g, near.b, 255), AlphaPixel::white());
+        assert!(near.a > far.a && far.a > 0);
+        // The layer is drawn over its glow
+        assert_eq!(effects.filtered_pixel_at(20, 20).unwrap(), AlphaPixel::red());
+    }
+
+    #[test]
+    fn bevel() {
+        let mut effects = square();
+        effects.bevel = Some(Bevel::new(2));
+
+        // With the light from the top left, the top edge is highlighted and the bottom edge is shaded
+        let top = effects.filtered_pixel_at(20, 10).unwrap();
+        let bottom = effects.filtered_pixel_at(20, 29).unwrap();
+        assert!(top.g > 100);
+        assert!(bottom.r < 150 && bottom.g == 0);
+        assert_eq!(effects.filtered_pixel_at(20, 20).unwrap(), AlphaPixel::red());
+    }
+}
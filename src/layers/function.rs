@@ -0,0 +1,80 @@
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect};
+
+/// The function of a [`FunctionLayer`], which takes a coordinate relative to the top left of the layer.
+pub type PixelFunction<T> = dyn Fn(usize, usize) -> AlphaPixel<T>;
+
+/// A layer whose pixels are computed by a function when they are drawn, such as a procedural pattern.
+/// 
+/// Unlike [`Image::from_function`](crate::Image::from_function), no pixels are stored, so this can cover a huge `Rect`
+/// and only the pixels that are drawn are computed. The function is given coordinates relative to the top left of `rect`,
+/// so the same function can be used for both.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, layers::function::FunctionLayer};
+/// 
+/// // Diagonal stripes, 10 pixels wide
+/// let stripes: FunctionLayer<u8> = FunctionLayer::new(Rect { x: 50, y: 50, width: 10000, height: 10000 }, |x, y| {
+///     if (x + y) / 10 % 2 == 0 { AlphaPixel::black() } else { AlphaPixel::white() }
+/// });
+/// assert_eq!(stripes.filtered_pixel_at(50, 50).unwrap(), AlphaPixel::black());
+/// assert_eq!(stripes.filtered_pixel_at(55, 55).unwrap(), AlphaPixel::white());
+/// assert!(stripes.filtered_pixel_at(0, 0).is_none());
+/// ```
+pub struct FunctionLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub rect: Rect,
+    function: Box<PixelFunction<T>>
+}
+
+impl<T> FunctionLayer<T> {
+    pub fn new<F: Fn(usize, usize) -> AlphaPixel<T> + 'static>(rect: Rect, function: F) -> Self {
+        Self { filters: vec![], rect, function: Box::new(function) }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for FunctionLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        (self.function)(x - self.rect.x, y - self.rect.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, Image, filters::transform::TranslateFilter};
+
+    fn gradient(x: usize, y: usize) -> AlphaPixel<u8> {
+        AlphaPixel { r: x as u8, g: y as u8, b: 0, a: 255 }
+    }
+
+    #[test]
+    fn matches_image() {
+        let rect = Rect { x: 3, y: 4, width: 20, height: 10 };
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(30, 20);
+        canvas.add_layer(FunctionLayer::new(rect, gradient));
+        let flattened = canvas.flatten();
+
+        let image = Image::from_function(rect.width, rect.height, gradient);
+        for y in 0..rect.height {
+            for x in 0..rect.width {
+                assert_eq!(flattened.pixel_at(x + rect.x, y + rect.y), image.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn filtered() {
+        let mut layer = FunctionLayer::new(Rect { x: 0, y: 0, width: 10, height: 10 }, gradient);
+        layer.filters.push(Box::new(TranslateFilter { x: 5, y: 0 }));
+        assert_eq!(layer.filtered_pixel_at(7, 2).unwrap(), gradient(2, 2));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, bitmap::ramp::ColorRamp, layers::shapes::linear_gradient_position};
+
+/// A rectangle filled with a linear gradient, from the first stop of `ramp` to the last.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, bitmap::ramp::ColorRamp, layers::gradient::LinearGradientLayer};
+/// 
+/// let sky: LinearGradientLayer<u8> = LinearGradientLayer::new(
+///     Rect { x: 0, y: 0, width: 100, height: 100 },
+///     ColorRamp::two_color(AlphaPixel::blue(), AlphaPixel::white()),
+///     90.0
+/// );
+/// assert_eq!(sky.filtered_pixel_at(50, 0).unwrap().b, 255);
+/// assert!(sky.filtered_pixel_at(50, 0).unwrap().r < 10);
+/// assert!(sky.filtered_pixel_at(50, 99).unwrap().r > 245);
+/// ```
+pub struct LinearGradientLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub rect: Rect,
+    pub ramp: ColorRamp<T>,
+    /// The direction of the gradient in degrees, clockwise from left to right
+    pub angle: f32
+}
+
+impl<T> LinearGradientLayer<T> {
+    pub fn new(rect: Rect, ramp: ColorRamp<T>, angle: f32) -> Self {
+        Self { filters: vec![], rect, ramp, angle }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for LinearGradientLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.ramp.color_at(linear_gradient_position(self.rect, self.angle, x, y))
+    }
+}
+
+/// A rectangle filled with a radial gradient, from the first stop of `ramp` at the center to the last stop at `radius`.
+/// Outside of `radius`, the rectangle is the colour of the last stop.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, bitmap::ramp::ColorRamp, layers::gradient::RadialGradientLayer};
+/// 
+/// // A vignette, transparent in the middle and dark at the corners
+/// let ramp = ColorRamp::new(vec![(0.5, AlphaPixel { a: 0, ..AlphaPixel::black() }), (1.0, AlphaPixel::black())]);
+/// let vignette: RadialGradientLayer<u8> = RadialGradientLayer::new(Rect { x: 0, y: 0, width: 100, height: 100 }, ramp, 50.0, 50.0, 70.0);
+/// assert_eq!(vignette.filtered_pixel_at(50, 50).unwrap().a, 0);
+/// assert_eq!(vignette.filtered_pixel_at(0, 0).unwrap().a, 255);
+/// ```
+pub struct RadialGradientLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub rect: Rect,
+    pub ramp: ColorRamp<T>,
+    /// The center of the gradient in canvas coordinates, where (0.0, 0.0) is the top left corner of the top left pixel
+    pub center_x: f32,
+    pub center_y: f32,
+    pub radius: f32
+}
+
+impl<T> RadialGradientLayer<T> {
+    pub fn new(rect: Rect, ramp: ColorRamp<T>, center_x: f32, center_y: f32, radius: f32) -> Self {
+        Self { filters: vec![], rect, ramp, center_x, center_y, radius }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for RadialGradientLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let distance = ((x as f32 + 0.5 - self.center_x).powi(2) + (y as f32 + 0.5 - self.center_y).powi(2)).sqrt();
+        let position = if self.radius <= 0.0 { 1.0 } else { distance / self.radius };
+        self.ramp.color_at(position.min(1.0))
+    }
+}
+
+/// A rectangle filled with a conic gradient, which sweeps clockwise around the center through the stops of `ramp`.
+/// 
+/// The first stop is at `start_angle`, in degrees clockwise where 0.0 points right, and the last stop is a full turn later.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, bitmap::ramp::ColorRamp, layers::gradient::ConicGradientLayer};
+/// 
+/// // A colour wheel, with red at the top
+/// let ramp = ColorRamp::new(vec![(0.0, AlphaPixel::red()), (1.0 / 3.0, AlphaPixel::green()), (2.0 / 3.0, AlphaPixel::blue()), (1.0, AlphaPixel::red())]);
+/// let wheel: ConicGradientLayer<u8> = ConicGradientLayer::new(Rect { x: 0, y: 0, width: 100, height: 100 }, ramp, 50.0, 50.0, -90.0);
+/// assert!(wheel.filtered_pixel_at(50, 0).unwrap().r > 250);
+/// ```
+pub struct ConicGradientLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub rect: Rect,
+    pub ramp: ColorRamp<T>,
+    /// The center of the gradient in canvas coordinates, where (0.0, 0.0) is the top left corner of the top left pixel
+    pub center_x: f32,
+    pub center_y: f32,
+    pub start_angle: f32
+}
+
+impl<T> ConicGradientLayer<T> {
+    pub fn new(rect: Rect, ramp: ColorRamp<T>, center_x: f32, center_y: f32, start_angle: f32) -> Self {
+        Self { filters: vec![], rect, ramp, center_x, center_y, start_angle }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for ConicGradientLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let angle = (y as f32 + 0.5 - self.center_y).atan2(x as f32 + 0.5 - self.center_x).to_degrees();
+        self.ramp.color_at((angle - self.start_angle).rem_euclid(360.0) / 360.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp() -> ColorRamp<u8> {
+        ColorRamp::two_color(AlphaPixel::black(), AlphaPixel::white())
+    }
+
+    #[test]
+    fn radial() {
+        let rect = Rect { x: 0, y: 0, width: 20, height: 20 };
+        let gradient: RadialGradientLayer<u8> = RadialGradientLayer::new(rect, ramp(), 10.0, 10.0, 10.0);
+        assert!(gradient.filtered_pixel_at(10, 10).unwrap().r < 20);
+        let halfway = gradient.filtered_pixel_at(15, 10).unwrap().r;
+        assert!((120..=140).contains(&halfway));
+        assert_eq!(gradient.filtered_pixel_at(0, 0).unwrap(), AlphaPixel::white());
+    }
+
+    #[test]
+    fn conic() {
+        let rect = Rect { x: 0, y: 0, width: 20, height: 20 };
+        let gradient: ConicGradientLayer<u8> = ConicGradientLayer::new(rect, ramp(), 10.0, 10.0, 0.0);
+        // A quarter turn clockwise from the right is the bottom
+        let bottom = gradient.filtered_pixel_at(9, 19).unwrap().r;
+        assert!((55..=72).contains(&bottom));
+        // Just before a full turn
+        assert!(gradient.filtered_pixel_at(19, 9).unwrap().r > 240);
+    }
+
+    #[test]
+    fn shared_stops() {
+        let rect = Rect { x: 0, y: 0, width: 10, height: 10 };
+        let stops = ColorRamp::new(vec![(0.0, AlphaPixel::red()), (1.0, AlphaPixel::blue())]);
+        let linear: LinearGradientLayer<u8> = LinearGradientLayer::new(rect, stops.clone(), 0.0);
+        let radial: RadialGradientLayer<u8> = RadialGradientLayer::new(rect, stops, 5.0, 5.0, 0.0);
+        assert!(linear.filtered_pixel_at(9, 5).unwrap().b > 230);
+        // With no radius, every pixel is past the last stop
+        assert_eq!(radial.filtered_pixel_at(5, 5).unwrap(), AlphaPixel::blue());
+    }
+}
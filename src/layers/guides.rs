@@ -0,0 +1,181 @@
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect};
+
+/// Evenly spaced horizontal and vertical lines across a `Rect`, for aligning layers while designing a template,
+/// or as a graph paper background. Between the lines, the layer is transparent.
+/// 
+/// Lines start at the top left of the `Rect`, and are `spacing_x` and `spacing_y` pixels apart. A spacing of 0
+/// draws no lines in that direction. Every `major_every` lines, a line is drawn in `major_color` instead.
+/// 
+/// This isn't the same as the [`GridLayer`](crate::layers::container::grid::GridLayer) container, which places
+/// its children in a grid.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, layers::guides::GridLinesLayer};
+/// 
+/// // Graph paper, with a darker line every 50 pixels
+/// let paper: GridLinesLayer<u8> = GridLinesLayer::new(Rect { x: 0, y: 0, width: 200, height: 200 }, AlphaPixel { r: 180, g: 200, b: 255, a: 255 }, 10, 1)
+///     .with_major(5, AlphaPixel::blue());
+/// 
+/// assert_eq!(paper.filtered_pixel_at(10, 3).unwrap().r, 180);
+/// assert_eq!(paper.filtered_pixel_at(50, 3).unwrap(), AlphaPixel::blue());
+/// assert_eq!(paper.filtered_pixel_at(15, 15).unwrap().a, 0);
+/// ```
+pub struct GridLinesLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub rect: Rect,
+    pub color: AlphaPixel<T>,
+    pub spacing_x: usize,
+    pub spacing_y: usize,
+    pub thickness: usize,
+    /// Every this many lines is a major line, or there are no major lines if this is 0
+    pub major_every: usize,
+    pub major_color: AlphaPixel<T>
+}
+
+impl<T: PixelChannel> GridLinesLayer<T> {
+    /// Create square grid lines `spacing` pixels apart, each `thickness` pixels thick.
+    pub fn new(rect: Rect, color: AlphaPixel<T>, spacing: usize, thickness: usize) -> Self {
+        Self { filters: vec![], rect, color, spacing_x: spacing, spacing_y: spacing, thickness, major_every: 0, major_color: color }
+    }
+
+    /// Draw every `every` lines in `color`, starting with the first line.
+    pub fn with_major(mut self, every: usize, color: AlphaPixel<T>) -> Self {
+        self.major_every = every;
+        self.major_color = color;
+        self
+    }
+
+    /// Get the index of the line covering `position` pixels from the start of the `Rect`, if there is one.
+    fn line_at(&self, position: usize, spacing: usize) -> Option<usize> {
+        (spacing > 0 && position % spacing < self.thickness).then(|| position / spacing)
+    }
+
+    fn is_major(&self, line: usize) -> bool {
+        self.major_every > 0 && line.is_multiple_of(self.major_every)
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for GridLinesLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let column = self.line_at(x - self.rect.x, self.spacing_x);
+        let row = self.line_at(y - self.rect.y, self.spacing_y);
+        // Major lines are drawn over minor lines where they cross
+        let lines = [column, row];
+        if lines.iter().flatten().any(|&line| self.is_major(line)) {
+            self.major_color
+        } else if lines.iter().any(Option::is_some) {
+            self.color
+        } else {
+            AlphaPixel::default()
+        }
+    }
+}
+
+/// Squares of two alternating colours across a `Rect`, which is the usual background to show transparency behind
+/// a preview of a canvas.
+/// 
+/// The top left cell of the `Rect` is `first`, and each cell is `cell_size` pixels square.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, AlphaPixel, Rect, layers::{guides::CheckerboardLayer, image::ImageLayer}};
+/// 
+/// let mut design: Canvas<u8> = Canvas::from_dimensions(64, 64);
+/// // ...add the layers of the design, which may be partially transparent
+/// 
+/// let mut preview: Canvas<u8> = Canvas::from_dimensions(64, 64);
+/// preview.add_layer(CheckerboardLayer::transparency(Rect { x: 0, y: 0, width: 64, height: 64 }));
+/// preview.add_layer(ImageLayer::new(design.flatten(), 0, 0));
+/// 
+/// let preview = preview.flatten();
+/// assert_eq!(preview.pixel_at(0, 0).unwrap(), AlphaPixel::white());
+/// assert_ne!(preview.pixel_at(8, 0).unwrap(), AlphaPixel::white());
+/// ```
+pub struct CheckerboardLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub rect: Rect,
+    pub cell_size: usize,
+    pub first: AlphaPixel<T>,
+    pub second: AlphaPixel<T>
+}
+
+impl<T: PixelChannel> CheckerboardLayer<T> {
+    pub fn new(rect: Rect, cell_size: usize, first: AlphaPixel<T>, second: AlphaPixel<T>) -> Self {
+        Self { filters: vec![], rect, cell_size, first, second }
+    }
+
+    /// The familiar white and light grey checkerboard with 8 pixel cells, used by image editors behind transparency.
+    pub fn transparency(rect: Rect) -> Self {
+        let grey = AlphaPixel { r: 204u8, g: 204, b: 204, a: 255 }.as_different_channel();
+        Self::new(rect, 8, AlphaPixel::white(), grey)
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for CheckerboardLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        // A cell size of 0 is treated as 1, rather than dividing by zero
+        let cell_size = self.cell_size.max(1);
+        let column = (x - self.rect.x) / cell_size;
+        let row = (y - self.rect.y) / cell_size;
+        if (column + row).is_multiple_of(2) { self.first } else { self.second }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let rect = Rect { x: 5, y: 5, width: 30, height: 30 };
+        let mut grid: GridLinesLayer<u8> = GridLinesLayer::new(rect, AlphaPixel::black(), 10, 2);
+        grid.spacing_y = 0;
+        assert_eq!(grid.filtered_pixel_at(5, 20).unwrap(), AlphaPixel::black());
+        assert_eq!(grid.filtered_pixel_at(16, 20).unwrap(), AlphaPixel::black());
+        assert_eq!(grid.filtered_pixel_at(17, 20).unwrap().a, 0);
+        // No horizontal lines are drawn
+        assert_eq!(grid.filtered_pixel_at(8, 5).unwrap().a, 0);
+    }
+
+    #[test]
+    fn major_crossings() {
+        let rect = Rect { x: 0, y: 0, width: 30, height: 30 };
+        let grid: GridLinesLayer<u8> = GridLinesLayer::new(rect, AlphaPixel::black(), 5, 1).with_major(2, AlphaPixel::red());
+        // A major row crossing a minor column
+        assert_eq!(grid.filtered_pixel_at(5, 10).unwrap(), AlphaPixel::red());
+        assert_eq!(grid.filtered_pixel_at(5, 15).unwrap(), AlphaPixel::black());
+        assert_eq!(grid.filtered_pixel_at(0, 7).unwrap(), AlphaPixel::red());
+    }
+
+    #[test]
+    fn checkerboard() {
+        let rect = Rect { x: 3, y: 3, width: 20, height: 20 };
+        let board: CheckerboardLayer<u8> = CheckerboardLayer::new(rect, 4, AlphaPixel::black(), AlphaPixel::red());
+        assert_eq!(board.filtered_pixel_at(3, 3).unwrap(), AlphaPixel::black());
+        assert_eq!(board.filtered_pixel_at(6, 6).unwrap(), AlphaPixel::black());
+        assert_eq!(board.filtered_pixel_at(7, 3).unwrap(), AlphaPixel::red());
+        assert_eq!(board.filtered_pixel_at(3, 7).unwrap(), AlphaPixel::red());
+        assert_eq!(board.filtered_pixel_at(7, 7).unwrap(), AlphaPixel::black());
+
+        // Every pixel alternates with a cell size of 0
+        let tiny = CheckerboardLayer::new(rect, 0, AlphaPixel::<u8>::black(), AlphaPixel::red());
+        assert_eq!(tiny.filtered_pixel_at(4, 3).unwrap(), AlphaPixel::red());
+    }
+}
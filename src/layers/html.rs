@@ -0,0 +1,1267 @@
+//! A layer which renders a small subset of HTML and CSS, for templates that are written as HTML snippets.
+//! 
+//! # Elements
+//! - `<div>` and `<p>` are blocks, which are stacked vertically
+//! - `<span>` is inline, and sets the colour and size of the text in it
+//! - `<img>` draws an image given to [`HtmlResources`], named by its `src`. It is sized by its `width` and `height`
+//!   attributes or properties, keeping its aspect ratio if only one is set.
+//! - `<br>` starts a new line
+//! 
+//! Text is wrapped at spaces to fit its block. Comments are skipped, and the character references `&amp;`, `&lt;`, `&gt;`,
+//! `&quot;`, `&apos;`, `&nbsp;` and `&#...;` are decoded. Any other element is an error.
+//! 
+//! # CSS
+//! Styles are only read from `style` attributes. Lengths must be in pixels, no longer than 16,384 pixels, and all boxes
+//! are rectangles. Only margins can be negative.
+//! - `display`: `block`, `inline`, `flex` or `none`
+//! - `width`, `height`, `box-sizing`, `padding` and `margin`, including the properties of each side. Margins don't collapse.
+//! - `color`, `background` and `background-color`, as `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`,
+//!   `transparent` or a basic colour name
+//! - `border`, `border-width`, `border-color`, `border-style` (`solid` or `none`) and `border-radius`
+//! - `font-size`, `text-align` and `opacity`
+//! - `flex-direction` (`row` or `column`), `justify-content`, `align-items`, `gap`, `flex`, `flex-grow` and `flex-shrink`.
+//!   Flex containers don't wrap.
+//! 
+//! Backgrounds, borders, padding and margins are only drawn on blocks, flex containers and their items, not on inline spans.
+//! Unknown properties are ignored, as they are by browsers, but invalid values of the properties above are errors.
+
+use std::collections::HashMap;
+use fontdue::Font;
+use thiserror::Error;
+use crate::{
+    Filter,
+    Layer,
+    Image,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    BlendingMethod,
+    Error,
+    bitmap::resize::ResizeOptions,
+    layers::{
+        shapes::rounded_rect_coverage,
+        text::layout::LayoutError,
+        container::flex::{FlexDirection, JustifyContent}
+    }
+};
+
+/// The deepest that elements can be nested, so that malicious input can't overflow the stack.
+const MAX_DEPTH: usize = 256;
+
+/// The font size of text that isn't in an element with a `font-size`
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+/// The largest length, in pixels, of any CSS length or `<img>` size, so that malicious input can't make an
+/// image too large to allocate.
+const MAX_LENGTH: f32 = 16_384.0;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum HtmlError {
+    #[error("Invalid HTML at byte {position}: {reason}")]
+    Syntax { position: usize, reason: &'static str },
+    #[error("The <{0}> element isn't supported")]
+    UnsupportedElement(String),
+    #[error("Invalid value \"{value}\" for the CSS property \"{property}\"")]
+    InvalidStyle { property: String, value: String },
+    #[error("No image was given for \"{0}\"")]
+    MissingImage(String)
+}
+
+/// The font, and the images that `<img>` elements refer to by their `src`, used to render HTML.
+pub struct HtmlResources<T: PixelChannel> {
+    pub font: Font,
+    pub images: HashMap<String, Image<T>>
+}
+
+impl<T: PixelChannel> HtmlResources<T> {
+    pub fn new(font: Font) -> Self {
+        Self { font, images: HashMap::new() }
+    }
+
+    /// Add an image, which is drawn by `<img src="{name}">`.
+    pub fn with_image(mut self, name: &str, image: Image<T>) -> Self {
+        self.images.insert(name.to_string(), image);
+        self
+    }
+}
+
+/// A layer of HTML, rendered with a subset of HTML and CSS described in the [module documentation](self).
+/// 
+/// The HTML is laid out in a fixed width, and the layer is as tall as its content.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use image_template::{Image, AlphaPixel, layers::html::{HtmlLayer, HtmlResources}};
+/// 
+/// let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+/// let resources = HtmlResources::new(font).with_image("avatar", Image::new_with_fill(AlphaPixel::<u8>::blue(), 64, 64));
+/// let html = r#"
+///     <div style="display: flex; align-items: center; gap: 16px; padding: 16px; background: #fff; border: 2px solid #ddd; border-radius: 12px">
+///         <img src="avatar" width="48">
+///         <div style="flex: 1">
+///             <div style="font-size: 24px">Ada Lovelace</div>
+///             <span style="color: gray">Posted <span style="color: #0a7">2 hours ago</span></span>
+///         </div>
+///     </div>
+/// "#;
+/// let card = HtmlLayer::try_new(html, &resources, 20, 20, 400).unwrap();
+/// ```
+pub struct HtmlLayer<T: PixelChannel> {
+    rasterized: Image<T>,
+    pub x: usize,
+    pub y: usize,
+    pub filters: Vec<Box<dyn Filter<T>>>
+}
+
+impl<T: PixelChannel> HtmlLayer<T> {
+    /// Render `html` with a width of `width` pixels, at (`x`, `y`).
+    /// 
+    /// Returns [`Error::Html`] if the HTML or CSS is invalid or unsupported, or refers to a missing image, and
+    /// [`Error::NewImage`] if the content is too large to allocate.
+    pub fn try_new(html: &str, resources: &HtmlResources<T>, x: usize, y: usize, width: usize) -> Result<Self, Error> {
+        let nodes = HtmlParser { text: html, position: 0 }.nodes(None, 0)?;
+        let style = Style::root();
+        let children = Builder { resources }.children(&nodes, &style)?;
+        let mut root = LayoutBox { style, content: Content::Children(children), rect: Frame::default() };
+        root.layout(width as f32, None);
+
+        let mut rasterized = Image::try_new_with_fill(AlphaPixel::default(), width, root.rect.height.ceil().max(0.0) as usize)?;
+        paint(&mut rasterized, &root, &resources.font, (0.0, 0.0));
+        Ok(Self { rasterized, x, y, filters: vec![] })
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for HtmlLayer<T> {
+    fn get_rect(&self) -> Rect {
+        Rect { x: self.x, y: self.y, width: self.rasterized.get_width(), height: self.rasterized.get_height() }
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.rasterized.pixel_at(x - self.x, y - self.y).unwrap()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Element { tag: String, attributes: Vec<(String, String)>, children: Vec<Node> },
+    Text(String)
+}
+
+impl Node {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        match self {
+            Node::Element { attributes, .. } => attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str()),
+            Node::Text(_) => None
+        }
+    }
+}
+
+struct HtmlParser<'a> {
+    text: &'a str,
+    position: usize
+}
+
+impl<'a> HtmlParser<'a> {
+    fn error(&self, reason: &'static str) -> HtmlError {
+        HtmlError::Syntax { position: self.position, reason }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Parse nodes until the closing tag of `parent`, or the end of the text if `parent` is `None`.
+    fn nodes(&mut self, parent: Option<&str>, depth: usize) -> Result<Vec<Node>, HtmlError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("elements are too deeply nested"))
+        }
+
+        let mut nodes = vec![];
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return match parent {
+                    Some(_) => Err(self.error("unclosed element")),
+                    None => Ok(nodes)
+                }
+            } else if rest.starts_with("<!--") {
+                let end = rest.find("-->").ok_or_else(|| self.error("unclosed comment"))?;
+                self.position += end + 3;
+            } else if let Some(closing) = rest.strip_prefix("</") {
+                let name_length = closing.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(closing.len());
+                if parent.is_none_or(|parent| !closing[..name_length].eq_ignore_ascii_case(parent)) {
+                    return Err(self.error("unexpected closing tag"))
+                }
+                self.position += 2 + name_length;
+                self.skip_whitespace();
+                if !self.rest().starts_with('>') {
+                    return Err(self.error("expected '>'"))
+                }
+                self.position += 1;
+                return Ok(nodes)
+            } else if rest.starts_with('<') {
+                nodes.push(self.element(depth)?);
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                nodes.push(Node::Text(decode_entities(&rest[..end])));
+                self.position += end;
+            }
+        }
+    }
+
+    fn element(&mut self, depth: usize) -> Result<Node, HtmlError> {
+        self.position += 1;
+        let rest = self.rest();
+        let name_length = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+        if name_length == 0 {
+            return Err(self.error("expected a tag name"))
+        }
+        let tag = rest[..name_length].to_ascii_lowercase();
+        self.position += name_length;
+
+        let mut attributes = vec![];
+        let self_closing = loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.position += 2;
+                break true
+            } else if rest.starts_with('>') {
+                self.position += 1;
+                break false
+            }
+
+            let name_length = rest.find(|c: char| c.is_whitespace() || "=>/\"'".contains(c)).unwrap_or(rest.len());
+            if name_length == 0 {
+                return Err(self.error("expected an attribute name or '>'"))
+            }
+            let name = rest[..name_length].to_ascii_lowercase();
+            self.position += name_length;
+
+            self.skip_whitespace();
+            let value = if self.rest().starts_with('=') {
+                self.position += 1;
+                self.skip_whitespace();
+                self.attribute_value()?
+            } else {
+                String::new()
+            };
+            attributes.push((name, value));
+        };
+
+        let children = match self_closing || matches!(tag.as_str(), "img" | "br") {
+            true => vec![],
+            false => self.nodes(Some(&tag), depth + 1)?
+        };
+        Ok(Node::Element { tag, attributes, children })
+    }
+
+    fn attribute_value(&mut self) -> Result<String, HtmlError> {
+        let rest = self.rest();
+        let (value, length) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = rest[1..].find(quote).ok_or_else(|| self.error("unclosed attribute value"))?;
+                (&rest[1..end + 1], end + 2)
+            },
+            _ => {
+                let end = rest.find(|c: char| c.is_whitespace() || c == '>').unwrap_or(rest.len());
+                (&rest[..end], end)
+            }
+        };
+        self.position += length;
+        Ok(decode_entities(value))
+    }
+}
+
+/// Replace character references with the characters they refer to. Unknown references are kept.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let character = reference.and_then(|reference| match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let code = reference.strip_prefix("#x").or_else(|| reference.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| reference.strip_prefix('#').map(|decimal| decimal.parse()))?;
+                code.ok().and_then(char::from_u32)
+            }
+        });
+
+        match (reference, character) {
+            (Some(reference), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[reference.len() + 2..];
+            },
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Display {
+    Block,
+    Inline,
+    Flex,
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextAlign {
+    Left,
+    Center,
+    Right
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AlignItems {
+    Stretch,
+    Start,
+    Center,
+    End
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Edges {
+    top: f32,
+    right: f32,
+    bottom: f32,
+    left: f32
+}
+
+impl Edges {
+    fn horizontal(&self) -> f32 {
+        self.left + self.right
+    }
+
+    fn vertical(&self) -> f32 {
+        self.top + self.bottom
+    }
+
+    fn is_non_negative(&self) -> bool {
+        [self.top, self.right, self.bottom, self.left].iter().all(|edge| *edge >= 0.0)
+    }
+}
+
+/// The computed style of an element
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Style {
+    display: Display,
+    // Inherited by children
+    color: AlphaPixel<f32>,
+    font_size: f32,
+    text_align: TextAlign,
+    /// The product of the opacity of this element and its ancestors
+    opacity: f32,
+    // Not inherited
+    background: AlphaPixel<f32>,
+    border_width: f32,
+    /// If `None`, the border is the text colour
+    border_color: Option<AlphaPixel<f32>>,
+    border_radius: f32,
+    width: Option<f32>,
+    height: Option<f32>,
+    /// If the width and height include the padding and border
+    border_box: bool,
+    padding: Edges,
+    margin: Edges,
+    flex_direction: FlexDirection,
+    justify: JustifyContent,
+    align_items: AlignItems,
+    gap: f32,
+    grow: f32,
+    shrink: f32
+}
+
+impl Style {
+    fn root() -> Self {
+        Self {
+            display: Display::Block,
+            color: AlphaPixel::black(),
+            font_size: DEFAULT_FONT_SIZE,
+            text_align: TextAlign::Left,
+            opacity: 1.0,
+            background: AlphaPixel::default(),
+            border_width: 0.0,
+            border_color: None,
+            border_radius: 0.0,
+            width: None,
+            height: None,
+            border_box: false,
+            padding: Edges::default(),
+            margin: Edges::default(),
+            flex_direction: FlexDirection::Row,
+            justify: JustifyContent::Start,
+            align_items: AlignItems::Stretch,
+            gap: 0.0,
+            grow: 0.0,
+            shrink: 1.0
+        }
+    }
+
+    /// Get the style of a child, with only the inherited properties of this style.
+    fn child(&self, display: Display) -> Self {
+        Self {
+            display,
+            color: self.color,
+            font_size: self.font_size,
+            text_align: self.text_align,
+            opacity: self.opacity,
+            ..Self::root()
+        }
+    }
+
+    /// The width of the padding and border on the left and right
+    fn frame_horizontal(&self) -> f32 {
+        self.padding.horizontal() + 2.0 * self.border_width
+    }
+
+    fn frame_vertical(&self) -> f32 {
+        self.padding.vertical() + 2.0 * self.border_width
+    }
+
+    /// Set a property from a CSS declaration.
+    fn apply(&mut self, property: &str, value: &str) -> Result<(), HtmlError> {
+        let invalid = || HtmlError::InvalidStyle { property: property.to_string(), value: value.to_string() };
+        let length = || parse_length(value).ok_or_else(invalid);
+        // Sizes, unlike margins, can't be negative
+        let size = || parse_length(value).filter(|length| *length >= 0.0).ok_or_else(invalid);
+        let number = || value.parse::<f32>().ok().filter(|number| number.is_finite()).ok_or_else(invalid);
+        let color = || parse_color(value).ok_or_else(invalid);
+
+        match property {
+            "display" => self.display = match value {
+                "block" => Display::Block,
+                "inline" => Display::Inline,
+                "flex" => Display::Flex,
+                "none" => Display::None,
+                _ => return Err(invalid())
+            },
+            "color" => self.color = color()?,
+            "background" | "background-color" => self.background = color()?,
+            "font-size" => self.font_size = size()?,
+            "text-align" => self.text_align = match value {
+                "left" | "start" => TextAlign::Left,
+                "center" => TextAlign::Center,
+                "right" | "end" => TextAlign::Right,
+                _ => return Err(invalid())
+            },
+            "opacity" => self.opacity *= number()?.clamp(0.0, 1.0),
+            "width" => self.width = Some(size()?),
+            "height" => self.height = Some(size()?),
+            "box-sizing" => self.border_box = match value {
+                "content-box" => false,
+                "border-box" => true,
+                _ => return Err(invalid())
+            },
+            "padding" => self.padding = parse_edges(value).filter(Edges::is_non_negative).ok_or_else(invalid)?,
+            "padding-top" => self.padding.top = size()?,
+            "padding-right" => self.padding.right = size()?,
+            "padding-bottom" => self.padding.bottom = size()?,
+            "padding-left" => self.padding.left = size()?,
+            "margin" => self.margin = parse_edges(value).ok_or_else(invalid)?,
+            "margin-top" => self.margin.top = length()?,
+            "margin-right" => self.margin.right = length()?,
+            "margin-bottom" => self.margin.bottom = length()?,
+            "margin-left" => self.margin.left = length()?,
+            "border" => {
+                // Any of a width, a style and a colour, in any order
+                for token in split_tokens(value) {
+                    if let Some(width) = parse_length(token) {
+                        if width < 0.0 {
+                            return Err(invalid())
+                        }
+                        self.border_width = width;
+                    } else if token == "solid" {
+                        self.border_width = if self.border_width == 0.0 { 1.0 } else { self.border_width };
+                    } else if token == "none" {
+                        self.border_width = 0.0;
+                    } else {
+                        self.border_color = Some(parse_color(token).ok_or_else(invalid)?);
+                    }
+                }
+            },
+            "border-width" => self.border_width = size()?,
+            "border-color" => self.border_color = Some(color()?),
+            "border-style" => match value {
+                "solid" => {},
+                "none" => self.border_width = 0.0,
+                _ => return Err(invalid())
+            },
+            "border-radius" => self.border_radius = size()?,
+            "flex-direction" => self.flex_direction = match value {
+                "row" => FlexDirection::Row,
+                "column" => FlexDirection::Column,
+                _ => return Err(invalid())
+            },
+            "justify-content" => self.justify = match value {
+                "flex-start" | "start" => JustifyContent::Start,
+                "center" => JustifyContent::Center,
+                "flex-end" | "end" => JustifyContent::End,
+                "space-between" => JustifyContent::SpaceBetween,
+                "space-around" => JustifyContent::SpaceAround,
+                _ => return Err(invalid())
+            },
+            "align-items" => self.align_items = match value {
+                "stretch" => AlignItems::Stretch,
+                "flex-start" | "start" => AlignItems::Start,
+                "center" => AlignItems::Center,
+                "flex-end" | "end" => AlignItems::End,
+                _ => return Err(invalid())
+            },
+            "gap" => self.gap = size()?,
+            "flex" | "flex-grow" => self.grow = number()?.max(0.0),
+            "flex-shrink" => self.shrink = number()?.max(0.0),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Parse a length in pixels, such as `12px`, or `0`. Lengths longer than [`MAX_LENGTH`] aren't allowed.
+fn parse_length(value: &str) -> Option<f32> {
+    let number = match value.strip_suffix("px") {
+        Some(number) => number,
+        None if value == "0" => value,
+        None => return None
+    };
+    number.trim().parse().ok().filter(|length: &f32| length.abs() <= MAX_LENGTH)
+}
+
+/// Parse 1 to 4 lengths, for the top, right, bottom and left, in the same way as CSS.
+fn parse_edges(value: &str) -> Option<Edges> {
+    let lengths = value.split_whitespace().map(parse_length).collect::<Option<Vec<f32>>>()?;
+    let (top, right, bottom, left) = match lengths[..] {
+        [all] => (all, all, all, all),
+        [vertical, horizontal] => (vertical, horizontal, vertical, horizontal),
+        [top, horizontal, bottom] => (top, horizontal, bottom, horizontal),
+        [top, right, bottom, left] => (top, right, bottom, left),
+        _ => return None
+    };
+    Some(Edges { top, right, bottom, left })
+}
+
+/// Split a value at whitespace that isn't inside parentheses, so that `1px solid rgb(0, 0, 0)` has three tokens.
+fn split_tokens(value: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut depth = 0;
+    let mut start = None;
+    for (index, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if c.is_whitespace() && depth == 0 {
+            if let Some(token_start) = start.take() {
+                tokens.push(&value[token_start..index]);
+            }
+        } else if start.is_none() {
+            start = Some(index);
+        }
+    }
+    if let Some(token_start) = start {
+        tokens.push(&value[token_start..]);
+    }
+    tokens
+}
+
+/// Parse a CSS colour, with channels from 0 to 1.
+fn parse_color(value: &str) -> Option<AlphaPixel<f32>> {
+    let value = value.trim().to_ascii_lowercase();
+    let rgb = |r: u8, g: u8, b: u8| AlphaPixel { r: r as f32 / 255.0, g: g as f32 / 255.0, b: b as f32 / 255.0, a: 1.0 };
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if !hex.is_ascii() {
+            return None
+        }
+        let digits = hex.chars().map(|c| c.to_digit(16).map(|digit| digit as f32)).collect::<Option<Vec<f32>>>()?;
+        let channels: Vec<f32> = match digits.len() {
+            3 | 4 => digits.iter().map(|digit| digit * 17.0 / 255.0).collect(),
+            6 | 8 => digits.chunks(2).map(|pair| (pair[0] * 16.0 + pair[1]) / 255.0).collect(),
+            _ => return None
+        };
+        return Some(AlphaPixel { r: channels[0], g: channels[1], b: channels[2], a: channels.get(3).copied().unwrap_or(1.0) })
+    }
+
+    if let Some(arguments) = value.strip_prefix("rgba(").or_else(|| value.strip_prefix("rgb(")) {
+        let arguments = arguments.strip_suffix(')')?;
+        let numbers = arguments.split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|argument| !argument.is_empty())
+            .collect::<Vec<&str>>();
+        let channel = |argument: &str| match argument.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok().map(|percent| percent / 100.0),
+            None => argument.parse::<f32>().ok().map(|number| number / 255.0)
+        }.map(|channel| channel.clamp(0.0, 1.0));
+        let alpha = match numbers.get(3) {
+            Some(alpha) => match alpha.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+                None => alpha.parse().ok()?
+            },
+            None => 1.0
+        };
+        if !(3..=4).contains(&numbers.len()) {
+            return None
+        }
+        return Some(AlphaPixel { r: channel(numbers[0])?, g: channel(numbers[1])?, b: channel(numbers[2])?, a: f32::clamp(alpha, 0.0, 1.0) })
+    }
+
+    Some(match value.as_str() {
+        "transparent" => AlphaPixel::default(),
+        "black" => rgb(0, 0, 0),
+        "white" => rgb(255, 255, 255),
+        "red" => rgb(255, 0, 0),
+        "green" => rgb(0, 128, 0),
+        "lime" => rgb(0, 255, 0),
+        "blue" => rgb(0, 0, 255),
+        "yellow" => rgb(255, 255, 0),
+        "cyan" | "aqua" => rgb(0, 255, 255),
+        "magenta" | "fuchsia" => rgb(255, 0, 255),
+        "gray" | "grey" => rgb(128, 128, 128),
+        "silver" => rgb(192, 192, 192),
+        "orange" => rgb(255, 165, 0),
+        "purple" => rgb(128, 0, 128),
+        "navy" => rgb(0, 0, 128),
+        "maroon" => rgb(128, 0, 0),
+        "teal" => rgb(0, 128, 128),
+        "olive" => rgb(128, 128, 0),
+        _ => return None
+    })
+}
+
+/// A rectangle with floating point coordinates, relative to the top left of the parent box.
+#[derive(Debug, Clone, Copy, Default)]
+struct Frame {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32
+}
+
+/// A word, space, line break or image in a line of text.
+/// `above` and `below` are how far the item reaches above and below the baseline, including its share of the line gap.
+enum InlineItem<T: PixelChannel> {
+    Word { text: String, size: f32, color: AlphaPixel<f32>, width: f32, above: f32, below: f32 },
+    Space { width: f32 },
+    Break { above: f32, below: f32 },
+    Image { image: Image<T>, opacity: f32 }
+}
+
+/// An inline item which has been laid out, with the position of its left edge and baseline relative to its box.
+struct PlacedItem {
+    item: usize,
+    x: f32,
+    baseline: f32
+}
+
+enum Content<T: PixelChannel> {
+    /// Blocks, or the items of a flex container
+    Children(Vec<LayoutBox<T>>),
+    /// Lines of text and images
+    Inline { items: Vec<InlineItem<T>>, placed: Vec<PlacedItem> },
+    Image(Image<T>)
+}
+
+struct LayoutBox<T: PixelChannel> {
+    style: Style,
+    content: Content<T>,
+    /// The border box, relative to the border box of the parent
+    rect: Frame
+}
+
+/// Converts parsed HTML into boxes.
+struct Builder<'a, T: PixelChannel> {
+    resources: &'a HtmlResources<T>
+}
+
+impl<T: PixelChannel> Builder<'_, T> {
+    fn style(&self, node: &Node, parent: &Style) -> Result<Style, HtmlError> {
+        let Node::Element { tag, .. } = node else {
+            return Ok(parent.child(Display::Inline))
+        };
+        let display = match tag.as_str() {
+            "div" | "p" => Display::Block,
+            "span" | "img" | "br" => Display::Inline,
+            _ => return Err(HtmlError::UnsupportedElement(tag.clone()))
+        };
+
+        let mut style = parent.child(display);
+        for declaration in node.attribute("style").unwrap_or_default().split(';') {
+            if let Some((property, value)) = declaration.split_once(':') {
+                style.apply(&property.trim().to_ascii_lowercase(), value.trim())?;
+            }
+        }
+        Ok(style)
+    }
+
+    /// Create the boxes of the children of an element with the style `parent`.
+    /// 
+    /// Runs of text and inline elements are put in anonymous boxes. The children of flex containers are all boxes.
+    fn children(&self, nodes: &[Node], parent: &Style) -> Result<Vec<LayoutBox<T>>, Error> {
+        let mut boxes = vec![];
+        let mut inline = vec![];
+        for node in nodes {
+            let style = self.style(node, parent)?;
+            if style.display == Display::None {
+                continue
+            }
+
+            match node {
+                Node::Text(text) => self.add_text(&mut inline, text, &style)?,
+                Node::Element { .. } if style.display == Display::Inline && parent.display != Display::Flex => {
+                    self.add_inline(&mut inline, node, &style)?;
+                },
+                Node::Element { tag, children, .. } => {
+                    self.flush_inline(&mut boxes, &mut inline, parent);
+                    let content = match tag.as_str() {
+                        "img" => Content::Image(self.image(node, &style)?),
+                        _ => Content::Children(self.children(children, &style)?)
+                    };
+                    boxes.push(LayoutBox { style, content, rect: Frame::default() });
+                }
+            }
+        }
+        self.flush_inline(&mut boxes, &mut inline, parent);
+        Ok(boxes)
+    }
+
+    /// Put the inline items collected so far into an anonymous box, unless they are only spaces.
+    fn flush_inline(&self, boxes: &mut Vec<LayoutBox<T>>, inline: &mut Vec<InlineItem<T>>, parent: &Style) {
+        if inline.iter().all(|item| matches!(item, InlineItem::Space { .. })) {
+            inline.clear();
+            return
+        }
+        let items = std::mem::take(inline);
+        boxes.push(LayoutBox { style: parent.child(Display::Block), content: Content::Inline { items, placed: vec![] }, rect: Frame::default() });
+    }
+
+    /// Add the items of an inline element. Elements inside it are treated as inline, whatever their display.
+    fn add_inline(&self, inline: &mut Vec<InlineItem<T>>, node: &Node, style: &Style) -> Result<(), Error> {
+        let Node::Element { tag, children, .. } = node else {
+            return Ok(())
+        };
+        match tag.as_str() {
+            "br" => {
+                let (above, below) = self.line_metrics(style.font_size)?;
+                inline.push(InlineItem::Break { above, below });
+            },
+            "img" => inline.push(InlineItem::Image { image: self.image(node, style)?, opacity: style.opacity }),
+            _ => for child in children {
+                let child_style = self.style(child, style)?;
+                match child {
+                    _ if child_style.display == Display::None => {},
+                    Node::Text(text) => self.add_text(inline, text, &child_style)?,
+                    Node::Element { .. } => self.add_inline(inline, child, &child_style)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get how far text reaches above and below its baseline, with the gap between lines shared between them.
+    fn line_metrics(&self, size: f32) -> Result<(f32, f32), LayoutError> {
+        let metrics = self.resources.font.horizontal_line_metrics(size).ok_or(LayoutError::MissingLineSpacing)?;
+        let gap = metrics.new_line_size - (metrics.ascent - metrics.descent);
+        Ok((metrics.ascent + gap / 2.0, -metrics.descent + gap / 2.0))
+    }
+
+    /// Split text into words and spaces, where any run of whitespace is a single space.
+    fn add_text(&self, inline: &mut Vec<InlineItem<T>>, text: &str, style: &Style) -> Result<(), Error> {
+        let (above, below) = self.line_metrics(style.font_size)?;
+        let color = AlphaPixel { a: style.color.a * style.opacity, ..style.color };
+
+        for (index, word) in text.split(|c: char| c.is_ascii_whitespace()).enumerate() {
+            if index > 0 && !matches!(inline.last(), Some(InlineItem::Space { .. })) {
+                let width = self.resources.font.metrics(' ', style.font_size).advance_width;
+                inline.push(InlineItem::Space { width });
+            }
+            if !word.is_empty() {
+                let width = word_width(&self.resources.font, word, style.font_size);
+                inline.push(InlineItem::Word { text: word.to_string(), size: style.font_size, color, width, above, below });
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the image of an `<img>`, resized to its width and height.
+    fn image(&self, node: &Node, style: &Style) -> Result<Image<T>, HtmlError> {
+        let source = node.attribute("src").unwrap_or_default();
+        let image = self.resources.images.get(source).ok_or_else(|| HtmlError::MissingImage(source.to_string()))?;
+        let (natural_width, natural_height) = (image.get_width() as f32, image.get_height() as f32);
+
+        let attribute = |name: &str| node.attribute(name).map(|value| {
+            value.trim_end_matches("px").parse::<f32>().ok().filter(|length| (0.0..=MAX_LENGTH).contains(length))
+                .ok_or_else(|| HtmlError::InvalidStyle { property: name.to_string(), value: value.to_string() })
+        }).transpose();
+        let width = style.width.or(attribute("width")?);
+        let height = style.height.or(attribute("height")?);
+
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (width, width * natural_height / natural_width.max(1.0)),
+            (None, Some(height)) => (height * natural_width / natural_height.max(1.0), height),
+            (None, None) => (natural_width, natural_height)
+        };
+        let (width, height) = (width.round().max(0.0) as usize, height.round().max(0.0) as usize);
+        if (width, height) == (image.get_width(), image.get_height()) {
+            Ok(image.clone())
+        } else {
+            Ok(image.resize(width, height, ResizeOptions::default()))
+        }
+    }
+}
+
+/// Get the width of a word, from the advance of each character and the kerning between them.
+fn word_width(font: &Font, word: &str, size: f32) -> f32 {
+    let mut width = 0.0;
+    let mut prev_char = None;
+    for c in word.chars() {
+        if let Some(prev_char) = prev_char {
+            width += font.horizontal_kern(prev_char, c, size).unwrap_or(0.0);
+        }
+        width += font.metrics(c, size).advance_width;
+        prev_char = Some(c);
+    }
+    width
+}
+
+#[derive(Default)]
+struct Line {
+    /// The index of each item, and the position of its left edge
+    items: Vec<(usize, f32)>,
+    width: f32,
+    above: f32,
+    below: f32
+}
+
+/// Break inline items into lines no wider than `max_width`. Lines are only broken at spaces.
+fn break_lines<T: PixelChannel>(items: &[InlineItem<T>], max_width: f32) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut line = Line::default();
+    let mut space = None;
+
+    for (index, item) in items.iter().enumerate() {
+        let (width, above, below) = match item {
+            InlineItem::Space { width } => {
+                // Spaces at the start of a line aren't drawn
+                if !line.items.is_empty() {
+                    space = space.or(Some(*width));
+                }
+                continue
+            },
+            InlineItem::Break { above, below } => {
+                line.above = line.above.max(*above);
+                line.below = line.below.max(*below);
+                lines.push(std::mem::take(&mut line));
+                space = None;
+                continue
+            },
+            InlineItem::Word { width, above, below, .. } => (*width, *above, *below),
+            InlineItem::Image { image, .. } => (image.get_width() as f32, image.get_height() as f32, 0.0)
+        };
+
+        let mut gap = space.take().unwrap_or(0.0);
+        if gap > 0.0 && line.width + gap + width > max_width {
+            lines.push(std::mem::take(&mut line));
+            gap = 0.0;
+        }
+        line.items.push((index, line.width + gap));
+        line.width += gap + width;
+        line.above = line.above.max(above);
+        line.below = line.below.max(below);
+    }
+
+    if !line.items.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Get the position of the first child, and the extra space between children, for distributing `free` space.
+fn justify_offsets(justify: JustifyContent, free: f32, count: usize) -> (f32, f32) {
+    match justify {
+        JustifyContent::Start => (0.0, 0.0),
+        JustifyContent::Center => (free / 2.0, 0.0),
+        JustifyContent::End => (free, 0.0),
+        JustifyContent::SpaceBetween if count > 1 => (0.0, free / (count - 1) as f32),
+        JustifyContent::SpaceBetween => (0.0, 0.0),
+        JustifyContent::SpaceAround => (free / count.max(1) as f32 / 2.0, free / count.max(1) as f32)
+    }
+}
+
+/// Grow or shrink the main size of each flex item, to share `free` space between them.
+fn flex_sizes<T: PixelChannel>(children: &[LayoutBox<T>], bases: &[f32], free: f32) -> Vec<f32> {
+    let total_grow: f32 = children.iter().map(|child| child.style.grow).sum();
+    let total_shrink: f32 = children.iter().zip(bases).map(|(child, basis)| child.style.shrink * basis).sum();
+
+    children.iter().zip(bases).map(|(child, basis)| {
+        if free > 0.0 && total_grow > 0.0 {
+            basis + free * child.style.grow / total_grow
+        } else if free < 0.0 && total_shrink > 0.0 {
+            (basis + free * child.style.shrink * basis / total_shrink).max(0.0)
+        } else {
+            *basis
+        }
+    }).collect()
+}
+
+/// Get the offset of an item on the cross axis of a flex container.
+fn cross_offset(align: AlignItems, size: f32, available: f32) -> f32 {
+    match align {
+        AlignItems::Stretch | AlignItems::Start => 0.0,
+        AlignItems::Center => ((available - size) / 2.0).max(0.0),
+        AlignItems::End => (available - size).max(0.0)
+    }
+}
+
+impl<T: PixelChannel> LayoutBox<T> {
+    fn specified_width(&self) -> Option<f32> {
+        let style = &self.style;
+        style.width.map(|width| if style.border_box { width } else { width + style.frame_horizontal() })
+    }
+
+    fn specified_height(&self) -> Option<f32> {
+        let style = &self.style;
+        style.height.map(|height| if style.border_box { height } else { height + style.frame_vertical() })
+    }
+
+    /// Get the width of the border box if nothing in it wraps.
+    fn max_content_width(&self) -> f32 {
+        if let Some(width) = self.specified_width() {
+            return width
+        }
+
+        let content = match &self.content {
+            Content::Image(image) => image.get_width() as f32,
+            Content::Inline { items, .. } => break_lines(items, f32::INFINITY).iter().map(|line| line.width).fold(0.0, f32::max),
+            Content::Children(children) => {
+                let widths = children.iter().map(|child| child.max_content_width() + child.style.margin.horizontal());
+                if self.style.display == Display::Flex && self.style.flex_direction == FlexDirection::Row {
+                    widths.sum::<f32>() + self.style.gap * children.len().saturating_sub(1) as f32
+                } else {
+                    widths.fold(0.0, f32::max)
+                }
+            }
+        };
+        content + self.style.frame_horizontal()
+    }
+
+    /// Lay out the box with a border box `width`, and a `height` if a flex container stretches it,
+    /// and position its contents relative to its top left.
+    fn layout(&mut self, width: f32, height: Option<f32>) {
+        let style = self.style;
+        let height = height.or(self.specified_height());
+        let inner_width = (width - style.frame_horizontal()).max(0.0);
+        let inner_height = height.map(|height| (height - style.frame_vertical()).max(0.0));
+        let origin = (style.border_width + style.padding.left, style.border_width + style.padding.top);
+
+        let content_height = match &mut self.content {
+            Content::Image(image) => image.get_height() as f32,
+            Content::Inline { items, placed } => layout_inline(items, placed, &style, inner_width, origin),
+            Content::Children(children) => match (style.display, style.flex_direction) {
+                (Display::Flex, FlexDirection::Row) => layout_flex_row(children, &style, inner_width, inner_height, origin),
+                (Display::Flex, FlexDirection::Column) => layout_flex_column(children, &style, inner_width, inner_height, origin),
+                _ => layout_blocks(children, inner_width, origin)
+            }
+        };
+
+        self.rect.width = width;
+        self.rect.height = height.unwrap_or(content_height + style.frame_vertical());
+    }
+}
+
+fn layout_inline<T: PixelChannel>(items: &[InlineItem<T>], placed: &mut Vec<PlacedItem>, style: &Style, width: f32, origin: (f32, f32)) -> f32 {
+    placed.clear();
+    let mut y = 0.0;
+    for line in break_lines(items, width) {
+        let offset = match style.text_align {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => (width - line.width) / 2.0,
+            TextAlign::Right => width - line.width
+        }.max(0.0);
+
+        let baseline = origin.1 + y + line.above;
+        placed.extend(line.items.iter().map(|(item, x)| PlacedItem { item: *item, x: origin.0 + offset + x, baseline }));
+        y += line.above + line.below;
+    }
+    y
+}
+
+fn layout_blocks<T: PixelChannel>(children: &mut [LayoutBox<T>], width: f32, origin: (f32, f32)) -> f32 {
+    let mut y = 0.0;
+    for child in children {
+        let margin = child.style.margin;
+        let child_width = child.specified_width().unwrap_or((width - margin.horizontal()).max(0.0));
+        child.layout(child_width, None);
+        child.rect.x = origin.0 + margin.left;
+        child.rect.y = origin.1 + y + margin.top;
+        y += margin.vertical() + child.rect.height;
+    }
+    y
+}
+
+fn layout_flex_row<T: PixelChannel>(children: &mut [LayoutBox<T>], style: &Style, width: f32, height: Option<f32>, origin: (f32, f32)) -> f32 {
+    let gaps = style.gap * children.len().saturating_sub(1) as f32;
+    let margins: f32 = children.iter().map(|child| child.style.margin.horizontal()).sum();
+    let bases: Vec<f32> = children.iter().map(|child| child.max_content_width()).collect();
+    let sizes = flex_sizes(children, &bases, width - bases.iter().sum::<f32>() - margins - gaps);
+
+    for (child, size) in children.iter_mut().zip(&sizes) {
+        child.layout(*size, None);
+    }
+    let line_height = height.unwrap_or_else(|| {
+        children.iter().map(|child| child.rect.height + child.style.margin.vertical()).fold(0.0, f32::max)
+    });
+    if style.align_items == AlignItems::Stretch {
+        for (child, size) in children.iter_mut().zip(&sizes) {
+            if child.specified_height().is_none() {
+                child.layout(*size, Some((line_height - child.style.margin.vertical()).max(0.0)));
+            }
+        }
+    }
+
+    let free = (width - sizes.iter().sum::<f32>() - margins - gaps).max(0.0);
+    let (mut x, spacing) = justify_offsets(style.justify, free, children.len());
+    for child in children {
+        let margin = child.style.margin;
+        child.rect.x = origin.0 + x + margin.left;
+        child.rect.y = origin.1 + margin.top + cross_offset(style.align_items, child.rect.height + margin.vertical(), line_height);
+        x += child.rect.width + margin.horizontal() + style.gap + spacing;
+    }
+    line_height
+}
+
+fn layout_flex_column<T: PixelChannel>(children: &mut [LayoutBox<T>], style: &Style, width: f32, height: Option<f32>, origin: (f32, f32)) -> f32 {
+    for child in children.iter_mut() {
+        let available = (width - child.style.margin.horizontal()).max(0.0);
+        let child_width = child.specified_width().unwrap_or_else(|| match style.align_items {
+            AlignItems::Stretch => available,
+            _ => child.max_content_width().min(available)
+        });
+        child.layout(child_width, None);
+    }
+
+    let gaps = style.gap * children.len().saturating_sub(1) as f32;
+    let margins: f32 = children.iter().map(|child| child.style.margin.vertical()).sum();
+    let bases: Vec<f32> = children.iter().map(|child| child.rect.height).collect();
+    let sizes = flex_sizes(children, &bases, height.map_or(0.0, |height| height - bases.iter().sum::<f32>() - margins - gaps));
+    for (child, size) in children.iter_mut().zip(&sizes) {
+        if *size != child.rect.height {
+            child.layout(child.rect.width, Some(*size));
+        }
+    }
+
+    let used = sizes.iter().sum::<f32>() + margins + gaps;
+    let free = height.map_or(0.0, |height| height - used).max(0.0);
+    let (mut y, spacing) = justify_offsets(style.justify, free, children.len());
+    for child in children {
+        let margin = child.style.margin;
+        child.rect.x = origin.0 + margin.left + cross_offset(style.align_items, child.rect.width + margin.horizontal(), width);
+        child.rect.y = origin.1 + y + margin.top;
+        y += child.rect.height + margin.vertical() + style.gap + spacing;
+    }
+    height.unwrap_or(used)
+}
+
+/// Blend `color`, with its alpha scaled by `coverage`, over a pixel of the image.
+fn blend_pixel<T: PixelChannel>(image: &mut Image<T>, x: usize, y: usize, color: AlphaPixel<f32>, coverage: f32) {
+    if coverage <= 0.0 || color.a <= 0.0 {
+        return
+    }
+    if let Some(pixel) = image.pixel_at_mut(x, y) {
+        let color = AlphaPixel { a: color.a * coverage.min(1.0), ..color }.as_different_channel();
+        *pixel = BlendingMethod::Over.blend(*pixel, color);
+    }
+}
+
+/// Draw a box and its contents, where `origin` is the top left of its parent's border box.
+fn paint<T: PixelChannel>(image: &mut Image<T>, layout_box: &LayoutBox<T>, font: &Font, origin: (f32, f32)) {
+    let style = &layout_box.style;
+    let (x, y) = (origin.0 + layout_box.rect.x, origin.1 + layout_box.rect.y);
+    paint_background(image, style, (x, y, layout_box.rect.width, layout_box.rect.height));
+
+    match &layout_box.content {
+        Content::Children(children) => for child in children {
+            paint(image, child, font, (x, y));
+        },
+        Content::Inline { items, placed } => for placed_item in placed {
+            match &items[placed_item.item] {
+                InlineItem::Word { text, size, color, .. } => {
+                    paint_word(image, font, text, *size, *color, (x + placed_item.x, y + placed_item.baseline));
+                },
+                InlineItem::Image { image: picture, opacity } => {
+                    let top = y + placed_item.baseline - picture.get_height() as f32;
+                    paint_image(image, picture, *opacity, (x + placed_item.x, top));
+                },
+                InlineItem::Space { .. } | InlineItem::Break { .. } => {}
+            }
+        },
+        Content::Image(picture) => {
+            let inset = style.border_width;
+            paint_image(image, picture, style.opacity, (x + inset + style.padding.left, y + inset + style.padding.top));
+        }
+    }
+}
+
+fn paint_background<T: PixelChannel>(image: &mut Image<T>, style: &Style, rect: (f32, f32, f32, f32)) {
+    let border_color = style.border_color.unwrap_or(style.color);
+    let has_border = style.border_width > 0.0 && border_color.a > 0.0;
+    if style.background.a <= 0.0 && !has_border {
+        return
+    }
+
+    let (left, top, width, height) = rect;
+    let border = style.border_width.min(width / 2.0).min(height / 2.0);
+    let inner_rect = (left + border, top + border, width - 2.0 * border, height - 2.0 * border);
+    let background = AlphaPixel { a: style.background.a * style.opacity, ..style.background };
+    let border_color = AlphaPixel { a: border_color.a * style.opacity, ..border_color };
+
+    let (start_x, start_y) = (left.floor().max(0.0) as usize, top.floor().max(0.0) as usize);
+    let end_x = ((left + width).ceil().max(0.0) as usize).min(image.get_width());
+    let end_y = ((top + height).ceil().max(0.0) as usize).min(image.get_height());
+    for pixel_y in start_y..end_y {
+        for pixel_x in start_x..end_x {
+            let outer = rounded_rect_coverage(rect, style.border_radius, pixel_x, pixel_y);
+            let inner = if border > 0.0 {
+                rounded_rect_coverage(inner_rect, (style.border_radius - border).max(0.0), pixel_x, pixel_y)
+            } else {
+                outer
+            };
+            blend_pixel(image, pixel_x, pixel_y, background, inner);
+            if has_border {
+                blend_pixel(image, pixel_x, pixel_y, border_color, outer - inner);
+            }
+        }
+    }
+}
+
+/// Draw a word, with the left of its first character and its baseline at `origin`.
+fn paint_word<T: PixelChannel>(image: &mut Image<T>, font: &Font, word: &str, size: f32, color: AlphaPixel<f32>, origin: (f32, f32)) {
+    let mut pen_x = origin.0;
+    let mut prev_char = None;
+    for c in word.chars() {
+        if let Some(prev_char) = prev_char {
+            pen_x += font.horizontal_kern(prev_char, c, size).unwrap_or(0.0);
+        }
+        let (metrics, coverage) = font.rasterize(c, size);
+        let glyph_x = (pen_x + metrics.xmin as f32).round() as isize;
+        let glyph_y = (origin.1 - metrics.ymin as f32 - metrics.height as f32).round() as isize;
+
+        for (index, alpha) in coverage.iter().enumerate() {
+            let (x, y) = (glyph_x + (index % metrics.width) as isize, glyph_y + (index / metrics.width) as isize);
+            if let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) {
+                blend_pixel(image, x, y, color, *alpha as f32 / 255.0);
+            }
+        }
+
+        pen_x += metrics.advance_width;
+        prev_char = Some(c);
+    }
+}
+
+fn paint_image<T: PixelChannel>(image: &mut Image<T>, picture: &Image<T>, opacity: f32, origin: (f32, f32)) {
+    let (left, top) = (origin.0.round() as isize, origin.1.round() as isize);
+    for picture_y in 0..picture.get_height() {
+        for picture_x in 0..picture.get_width() {
+            let (Ok(x), Ok(y)) = (usize::try_from(left + picture_x as isize), usize::try_from(top + picture_y as isize)) else {
+                continue
+            };
+            let source = picture.pixel_at(picture_x, picture_y).unwrap().scale_alpha(opacity);
+            if let Some(pixel) = image.pixel_at_mut(x, y) {
+                *pixel = BlendingMethod::Over.blend(*pixel, source);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(html: &str) -> Result<Vec<Node>, HtmlError> {
+        HtmlParser { text: html, position: 0 }.nodes(None, 0)
+    }
+
+    #[test]
+    fn parse_elements() {
+        let nodes = parse("<div class=card STYLE='color: red'>Fish &amp; chips<br><img src=\"logo\"/><!-- note --></DIV>").unwrap();
+        let Node::Element { tag, attributes, children } = &nodes[0] else { panic!() };
+        assert_eq!(tag, "div");
+        assert_eq!(attributes, &vec![("class".to_string(), "card".to_string()), ("style".to_string(), "color: red".to_string())]);
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[0], Node::Text("Fish & chips".to_string()));
+        assert_eq!(children[2].attribute("src"), Some("logo"));
+
+        assert!(matches!(parse("<div><span></div>"), Err(HtmlError::Syntax { .. })));
+        assert!(matches!(parse("<div>"), Err(HtmlError::Syntax { reason: "unclosed element", .. })));
+        assert!(matches!(parse("</div>"), Err(HtmlError::Syntax { .. })));
+        assert!(parse(&"<div>".repeat(1000)).is_err());
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(decode_entities("&lt;b&gt; &#65;&#x42; &nbsp;&unknown; & ;"), "<b> AB \u{a0}&unknown; & ;");
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(parse_color("#f00"), Some(AlphaPixel { r: 1.0, g: 0.0, b: 0.0, a: 1.0 }));
+        assert_eq!(parse_color("#0000FF80").map(|color| (color.b, (color.a * 255.0).round())), Some((1.0, 128.0)));
+        assert_eq!(parse_color("rgba(255, 0, 0, 0.5)"), Some(AlphaPixel { r: 1.0, g: 0.0, b: 0.0, a: 0.5 }));
+        assert_eq!(parse_color("rgb(0 100% 0)"), Some(AlphaPixel { r: 0.0, g: 1.0, b: 0.0, a: 1.0 }));
+        assert_eq!(parse_color("transparent"), Some(AlphaPixel::default()));
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("chartreuse"), None);
+    }
+
+    #[test]
+    fn styles() {
+        let mut style = Style::root();
+        style.apply("padding", "4px 8px").unwrap();
+        style.apply("border", "2px solid rgb(0, 0, 255)").unwrap();
+        style.apply("display", "flex").unwrap();
+        style.apply("flex", "2").unwrap();
+        style.apply("font-family", "serif").unwrap();
+
+        assert_eq!(style.padding, Edges { top: 4.0, right: 8.0, bottom: 4.0, left: 8.0 });
+        assert_eq!((style.border_width, style.border_color), (2.0, Some(AlphaPixel { r: 0.0, g: 0.0, b: 1.0, a: 1.0 })));
+        assert_eq!((style.display, style.grow), (Display::Flex, 2.0));
+        assert_eq!(style.frame_horizontal(), 20.0);
+
+        assert_eq!(
+            style.apply("width", "50%"),
+            Err(HtmlError::InvalidStyle { property: "width".to_string(), value: "50%".to_string() })
+        );
+        assert!(style.apply("display", "grid").is_err());
+
+        // Negative sizes, and lengths too large to draw, are invalid
+        assert!(style.apply("font-size", "-4px").is_err());
+        assert!(style.apply("padding", "4px -1px").is_err());
+        assert!(style.apply("border", "99999999999px solid red").is_err());
+        assert!(style.apply("height", "1e40px").is_err());
+        style.apply("margin-left", "-8px").unwrap();
+    }
+}
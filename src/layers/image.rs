@@ -1,4 +1,5 @@
-use crate::{Filter, Image, AlphaPixel, PixelChannel, Rect, Layer};
+use crate::{Filter, Image, AlphaPixel, PixelChannel, Rect, Layer, bitmap::mipmap::Mipmaps};
+use crate::bitmap::sampler::{Sampler, BilinearSampler};
 
 #[derive(Default)]
 pub struct ImageLayer<T: PixelChannel> {
@@ -27,3 +28,172 @@ impl<T: PixelChannel> Layer<T> for ImageLayer<T> {
         self.im.pixel_at(x-self.x, y-self.y).unwrap()
     }
 }
+
+/// A layer which stretches an image to fill a `Rect`.
+/// 
+/// When the image is shrunk, it is sampled from [`Mipmaps`] to avoid aliasing, so large photos can be drawn as
+/// thumbnails without jagged edges or moiré.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, Rect, Layer, layers::image::ScaledImageLayer};
+/// 
+/// let photo: Image<u8> = Image::new_with_fill(AlphaPixel::blue(), 4000, 3000);
+/// let thumbnail = ScaledImageLayer::new(&photo, Rect { x: 10, y: 10, width: 200, height: 150 });
+/// assert_eq!(thumbnail.filtered_pixel_at(100, 100).unwrap(), AlphaPixel::blue());
+/// ```
+pub struct ScaledImageLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    mipmaps: Mipmaps<T>,
+    rect: Rect,
+    sampler: Box<dyn Sampler>
+}
+
+impl<T: PixelChannel> ScaledImageLayer<T> {
+    /// Create a layer that draws `im` stretched to fill `rect`.
+    pub fn new(im: &Image<T>, rect: Rect) -> Self {
+        Self { filters: vec![], mipmaps: im.build_mipmaps(), rect, sampler: Box::new(BilinearSampler) }
+    }
+
+    /// Set the sampler used to get the image's pixels. By default, the image is sampled bilinearly.
+    pub fn with_sampler<S: Sampler + 'static>(mut self, sampler: S) -> Self {
+        self.sampler = Box::new(sampler);
+        self
+    }
+
+    pub fn get_image(&self) -> &Image<T> {
+        self.mipmaps.base()
+    }
+
+    /// Get the number of image pixels that each layer pixel covers, on the x and y axes.
+    fn scale(&self) -> (f32, f32) {
+        let image = self.mipmaps.base();
+        (image.get_width() as f32 / self.rect.width as f32, image.get_height() as f32 / self.rect.height as f32)
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for ScaledImageLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let (scale_x, scale_y) = self.scale();
+        let image_x = ((x - self.rect.x) as f32 + 0.5) * scale_x;
+        let image_y = ((y - self.rect.y) as f32 + 0.5) * scale_y;
+        // Use the larger scale, so that the more shrunk axis doesn't alias
+        self.mipmaps.sample_with(self.sampler.as_ref(), image_x, image_y, scale_x.max(scale_y)).unwrap_or_default()
+    }
+}
+
+/// A layer which repeats an image across a `Rect`, such as a texture for a background.
+/// 
+/// The tiles are `spacing_x` and `spacing_y` pixels apart, with transparent gaps between them, and the top left
+/// of one tile is `offset_x` and `offset_y` pixels from the top left of the `Rect`. Tiles are cut off at the
+/// edges of the `Rect`.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, Rect, Layer, layers::image::PatternLayer};
+/// 
+/// let dot: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 2, 2);
+/// let dots = PatternLayer::new(dot, Rect { x: 0, y: 0, width: 100, height: 100 }).with_spacing(8, 8).with_offset(1, 1);
+/// assert_eq!(dots.filtered_pixel_at(1, 1).unwrap(), AlphaPixel::red());
+/// assert_eq!(dots.filtered_pixel_at(11, 12).unwrap(), AlphaPixel::red());
+/// assert_eq!(dots.filtered_pixel_at(5, 5).unwrap().a, 0);
+/// ```
+pub struct PatternLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub im: Image<T>,
+    pub rect: Rect,
+    pub offset_x: usize,
+    pub offset_y: usize,
+    pub spacing_x: usize,
+    pub spacing_y: usize
+}
+
+impl<T: PixelChannel> PatternLayer<T> {
+    /// Create a layer that repeats `im` across `rect`, with no spacing, starting at the top left of `rect`.
+    pub fn new(im: Image<T>, rect: Rect) -> Self {
+        Self { filters: vec![], im, rect, offset_x: 0, offset_y: 0, spacing_x: 0, spacing_y: 0 }
+    }
+
+    /// Move the tiles right by `x` and down by `y` pixels.
+    pub fn with_offset(mut self, x: usize, y: usize) -> Self {
+        self.offset_x = x;
+        self.offset_y = y;
+        self
+    }
+
+    /// Leave a transparent gap of `x` pixels between columns of tiles, and `y` pixels between rows.
+    pub fn with_spacing(mut self, x: usize, y: usize) -> Self {
+        self.spacing_x = x;
+        self.spacing_y = y;
+        self
+    }
+}
+
+/// Get the position within a repeating cell of a tile and its spacing, of a pixel `distance` from the start of the pattern.
+fn position_in_cell(distance: usize, offset: usize, cell_size: usize) -> usize {
+    (distance + cell_size - offset % cell_size) % cell_size
+}
+
+impl<T: PixelChannel> Layer<T> for PatternLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let (width, height) = (self.im.get_width(), self.im.get_height());
+        if width == 0 || height == 0 {
+            return AlphaPixel::default()
+        }
+
+        let tile_x = position_in_cell(x - self.rect.x, self.offset_x, width + self.spacing_x);
+        let tile_y = position_in_cell(y - self.rect.y, self.offset_y, height + self.spacing_y);
+        // Pixels in the spacing are outside of the image
+        self.im.pixel_at(tile_x, tile_y).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker() -> Image<u8> {
+        Image::from_function(2, 1, |x, _| if x == 0 { AlphaPixel::red() } else { AlphaPixel::blue() })
+    }
+
+    #[test]
+    fn repeats() {
+        let pattern = PatternLayer::new(checker(), Rect { x: 10, y: 10, width: 9, height: 3 });
+        assert_eq!(pattern.filtered_pixel_at(10, 10).unwrap(), AlphaPixel::red());
+        assert_eq!(pattern.filtered_pixel_at(17, 12).unwrap(), AlphaPixel::blue());
+        assert_eq!(pattern.filtered_pixel_at(18, 11).unwrap(), AlphaPixel::red());
+        assert!(pattern.filtered_pixel_at(19, 10).is_none());
+    }
+
+    #[test]
+    fn offset_and_spacing() {
+        let pattern = PatternLayer::new(checker(), Rect { x: 0, y: 0, width: 20, height: 20 }).with_offset(7, 0).with_spacing(1, 0);
+        // Cells are 3 pixels wide, so an offset of 7 starts a tile at 1
+        assert_eq!(pattern.filtered_pixel_at(1, 0).unwrap(), AlphaPixel::red());
+        assert_eq!(pattern.filtered_pixel_at(2, 0).unwrap(), AlphaPixel::blue());
+        assert_eq!(pattern.filtered_pixel_at(3, 0).unwrap().a, 0);
+        assert_eq!(pattern.filtered_pixel_at(0, 0).unwrap().a, 0);
+    }
+
+    #[test]
+    fn empty_image() {
+        let pattern: PatternLayer<u8> = PatternLayer::new(Image::new(), Rect { x: 0, y: 0, width: 5, height: 5 });
+        assert_eq!(pattern.filtered_pixel_at(2, 2).unwrap().a, 0);
+    }
+}
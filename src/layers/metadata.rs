@@ -0,0 +1,112 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, Backdrop};
+
+/// String keys and values describing a layer, such as where its content came from.
+pub type Metadata = BTreeMap<String, String>;
+
+/// The metadata key of the asset that a layer was made from, set by [`AnnotatedLayer::with_source`].
+pub const SOURCE_KEY: &str = "source";
+
+/// A layer with [`Metadata`] attached, which is included in the manifest of
+/// [`Canvas::flatten_with_manifest`](crate::Canvas::flatten_with_manifest).
+/// 
+/// The metadata doesn't change how the layer is drawn. The wrapped layer's filters, name and children are used as they are.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, Layer, layers::{image::ImageLayer, metadata::AnnotatedLayer}};
+/// 
+/// let logo: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 32, 32);
+/// let layer = AnnotatedLayer::new(ImageLayer::new(logo, 10, 10))
+///     .with_source("assets/logo.png")
+///     .with("licence", "CC-BY-4.0");
+/// 
+/// assert_eq!(layer.name(), "ImageLayer");
+/// assert_eq!(layer.metadata().unwrap()["source"], "assets/logo.png");
+/// ```
+pub struct AnnotatedLayer<T: PixelChannel, L: Layer<T>> {
+    pub layer: L,
+    pub metadata: Metadata,
+    channel: PhantomData<T>
+}
+
+impl<T: PixelChannel, L: Layer<T>> AnnotatedLayer<T, L> {
+    /// Wrap a layer, with no metadata.
+    pub fn new(layer: L) -> Self {
+        Self { layer, metadata: Metadata::new(), channel: PhantomData }
+    }
+
+    /// Set the metadata `key` to `value`, replacing any value it already has.
+    pub fn with(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Record the asset that the layer was made from, such as the path or URL of an image, under [`SOURCE_KEY`].
+    pub fn with_source(self, source: &str) -> Self {
+        self.with(SOURCE_KEY, source)
+    }
+}
+
+impl<T: PixelChannel, L: Layer<T>> Layer<T> for AnnotatedLayer<T, L> {
+    fn get_rect(&self) -> Rect {
+        self.layer.get_rect()
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        self.layer.get_filters()
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.layer.unfiltered_pixel_at_unchecked(x, y)
+    }
+
+    fn name(&self) -> String {
+        self.layer.name()
+    }
+
+    fn children(&self) -> Vec<&dyn Layer<T>> {
+        self.layer.children()
+    }
+
+    fn metadata(&self) -> Option<&Metadata> {
+        Some(&self.metadata)
+    }
+
+    fn uses_backdrop(&self) -> bool {
+        self.layer.uses_backdrop()
+    }
+
+    fn composite_over_backdrop(&self, x: usize, y: usize, backdrop: &Backdrop<T>) -> AlphaPixel<T> {
+        self.layer.composite_over_backdrop(x, y, backdrop)
+    }
+
+    fn pixel_over_backdrop(&self, x: usize, y: usize, backdrop: &Backdrop<T>) -> Option<AlphaPixel<T>> {
+        self.layer.pixel_over_backdrop(x, y, backdrop)
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        self.layer.scaled(factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, rgba, layers::{shapes::RectangleLayer, backdrop_blur::BackdropBlurLayer}};
+
+    #[test]
+    fn draws_like_wrapped_layer() {
+        let rect = Rect { x: 2, y: 2, width: 4, height: 4 };
+        let mut plain: Canvas<u8> = Canvas::from_dimensions(10, 10);
+        plain.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 10, height: 5 }));
+        let mut annotated: Canvas<u8> = Canvas::from_dimensions(10, 10);
+        annotated.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 10, height: 5 }));
+
+        // Backdrop layers still read the backdrop through the wrapper
+        plain.add_layer(BackdropBlurLayer::new(RectangleLayer::new(rgba!(255, 255, 255, 80), rect), 2));
+        annotated.add_layer(AnnotatedLayer::new(BackdropBlurLayer::new(RectangleLayer::new(rgba!(255, 255, 255, 80), rect), 2)).with("id", "frosted"));
+        assert!(annotated.layers[1].uses_backdrop());
+        assert!(plain.flatten() == annotated.flatten());
+    }
+}
@@ -1,8 +1,32 @@
-use crate::{Filter, AlphaPixel, PixelChannel, Rect};
+use crate::{Filter, FilterContext, AlphaPixel, PixelChannel, Rect, Backdrop, BlendingMethod, filters::{sample_filters, filter_colours}};
+use metadata::Metadata;
+use rotated::RotatedLayer;
 
 pub mod image;
 pub mod shapes;
+pub mod path;
+pub mod gradient;
+pub mod function;
+pub mod guides;
+pub mod scatter;
+pub mod speech_bubble;
+pub mod callout;
+pub mod arrow;
+pub mod ribbon;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod backdrop_blur;
+pub mod backdrop_blend;
+pub mod effects;
+pub mod metadata;
+pub mod static_group;
+pub mod rotated;
+pub mod container;
 pub mod text;
+#[cfg(feature = "html")]
+pub mod html;
+#[cfg(feature = "skia")]
+pub mod skia;
 
 pub trait Layer<T: PixelChannel> {
     /// Get a bounding `Rect` relative to top left of the canvas.
@@ -14,18 +38,23 @@ pub trait Layer<T: PixelChannel> {
     fn get_filters(&self) -> &[Box<dyn Filter<T>>];
 
     /// Get the pixel at a canvas location, after it has been filtered
+    /// 
+    /// The first filter receives the canvas coordinate, and each following filter receives the
+    /// coordinate transformed by the filters before it. The colour of the sampled pixel is then
+    /// filtered by each filter in order.
     fn filtered_pixel_at(&self, x: usize, y: usize) -> Option<AlphaPixel<T>> {
-        let mut transformed_coord = (x, y);
-        let filters = self.get_filters();
-        for filter in filters {
-            transformed_coord = filter.filter_transform(transformed_coord.0, transformed_coord.1);
-        }
+        self.filtered_pixel_in_context(x, y, &FilterContext::new(self.get_rect(), None))
+    }
 
-        let mut pixel = self.unfiltered_pixel_at(transformed_coord.0, transformed_coord.1)?;
-        for filter in filters {
-            pixel = filter.filter_pixel(pixel)
-        }
-        Some(pixel)
+    /// Get the pixel at a canvas location, after it has been filtered with `context` passed to each filter.
+    /// 
+    /// This is used by a [`Canvas`](crate::Canvas), so that filters know the size of the canvas.
+    fn filtered_pixel_in_context(&self, x: usize, y: usize, context: &FilterContext) -> Option<AlphaPixel<T>> {
+        let (x, y) = (isize::try_from(x).ok()?, isize::try_from(y).ok()?);
+        let filters = self.get_filters();
+        // Coordinates to the left of or above the canvas aren't in any layer
+        let pixel = sample_filters(filters, x, y, context, &|x, y| self.unfiltered_pixel_at(usize::try_from(x).ok()?, usize::try_from(y).ok()?))?;
+        Some(filter_colours(filters, pixel, x, y, context))
     }
 
     /// Get the pixel at a canvas location, before it has been filtered.
@@ -45,4 +74,81 @@ pub trait Layer<T: PixelChannel> {
     /// 
     /// Use `unfiltered_pixel_at` if the coordinate may not be in bounds.
     fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T>;
+
+    /// A name for this layer, used when debugging a canvas.
+    /// 
+    /// By default this is the name of the layer's type, without its module path or generics.
+    fn name(&self) -> String {
+        let type_name = std::any::type_name::<Self>();
+        let without_generics = type_name.split('<').next().unwrap_or(type_name);
+        without_generics.rsplit("::").next().unwrap_or(without_generics).to_string()
+    }
+
+    /// The layers contained in this layer, in the order they are drawn, used when debugging a canvas.
+    /// 
+    /// This is empty by default, and is overridden by container layers.
+    fn children(&self) -> Vec<&dyn Layer<T>> {
+        vec![]
+    }
+
+    /// Metadata describing this layer, such as where its content came from, which is included in the manifest of
+    /// [`Canvas::flatten_with_manifest`](crate::Canvas::flatten_with_manifest).
+    /// 
+    /// This is `None` by default. Metadata can be attached to any layer with [`AnnotatedLayer`](metadata::AnnotatedLayer).
+    fn metadata(&self) -> Option<&Metadata> {
+        None
+    }
+
+    /// Whether this layer reads the pixels that have already been composited beneath it.
+    /// 
+    /// If this returns true, the canvas calls [`Layer::composite_over_backdrop`] instead of
+    /// blending [`Layer::filtered_pixel_at`] over the pixel beneath it.
+    fn uses_backdrop(&self) -> bool {
+        false
+    }
+
+    /// Composite this layer with the backdrop at a canvas location, returning the pixel which replaces it.
+    /// 
+    /// By default this blends [`Layer::pixel_over_backdrop`] over the backdrop pixel. Layers can override this
+    /// to blend with the backdrop in other ways, such as displacing it or choosing a colour that contrasts with it.
+    /// This is only called if [`Layer::uses_backdrop`] returns true.
+    fn composite_over_backdrop(&self, x: usize, y: usize, backdrop: &Backdrop<T>) -> AlphaPixel<T> {
+        let below = backdrop.pixel_at(x, y).unwrap_or_default();
+        match self.pixel_over_backdrop(x, y, backdrop) {
+            Some(pixel) => BlendingMethod::Over.blend(below, pixel),
+            None => below
+        }
+    }
+
+    /// Get the pixel at a canvas location, which will be composited over `backdrop`.
+    /// 
+    /// `backdrop` contains the background and all layers beneath this one, already composited.
+    /// This is only called by the default [`Layer::composite_over_backdrop`].
+    fn pixel_over_backdrop(&self, x: usize, y: usize, _backdrop: &Backdrop<T>) -> Option<AlphaPixel<T>> {
+        self.filtered_pixel_at(x, y)
+    }
+
+    /// Create a copy of this layer drawn `factor` times larger, with its positions, sizes and font sizes scaled,
+    /// which is used by [`Canvas::flatten_scaled`](crate::Canvas::flatten_scaled). The copy has no filters.
+    /// 
+    /// This returns `None` by default, and the canvas upscales the layer's pixels instead. Layers which can be
+    /// redrawn at a larger size, such as shapes and text, override this so that they stay sharp.
+    fn scaled(&self, _factor: f32) -> Option<Box<dyn Layer<T>>> {
+        None
+    }
+
+    /// Rotate this layer clockwise by `angle` degrees around the center of its `Rect`, which is expanded to fit it.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// # use image_template::{AlphaPixel, Canvas, Layer, layers::text::{TextLayer, TextSettings, layout::TextLayout}};
+    /// # let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// # let settings = TextSettings { size: 40.0, fill: AlphaPixel::<u8>::black(), layout: TextLayout::default(), text: String::from("New!"), font };
+    /// let mut canvas = Canvas::from_dimensions(400, 200);
+    /// // A caption tilted by 15 degrees
+    /// canvas.add_layer(TextLayer::try_new(settings, 50, 50).unwrap().rotated(15.0));
+    /// ```
+    fn rotated(self, angle: f32) -> RotatedLayer<T, Self> where Self: Sized {
+        RotatedLayer::new(self, angle)
+    }
 }
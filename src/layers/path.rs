@@ -0,0 +1,702 @@
+use std::{f32::consts::PI, str::FromStr};
+use thiserror::Error;
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod, layers::shapes::{FillRule, winding_number, line_coverage}};
+
+/// The maximum number of straight lines that each curve is split into.
+const MAX_CURVE_SUBDIVISIONS: usize = 64;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SvgPathError {
+    #[error("Path data must start with a move command.")]
+    MissingMoveTo,
+    #[error("Unknown command '{character}' at position {position} of path data.")]
+    UnknownCommand { character: char, position: usize },
+    #[error("Expected a number at position {0} of path data.")]
+    ExpectedNumber(usize),
+    #[error("Expected an arc flag of 0 or 1 at position {0} of path data.")]
+    ExpectedFlag(usize)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A part of a [`Path`]. Coordinates are on the canvas, where (0.0, 0.0) is the top left corner of the top left pixel.
+pub enum PathSegment {
+    /// Start a new subpath at a point, without drawing a line to it.
+    MoveTo(f32, f32),
+    /// A straight line to a point.
+    LineTo(f32, f32),
+    /// A quadratic bezier curve, with a control point and an end point.
+    QuadTo(f32, f32, f32, f32),
+    /// A cubic bezier curve, with two control points and an end point.
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    /// A straight line back to the start of the current subpath.
+    Close
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// A shape made of straight lines and bezier curves, which can be drawn with a [`PathLayer`].
+/// 
+/// # Example
+/// ```
+/// use image_template::layers::path::Path;
+/// 
+/// // A teardrop
+/// let path = Path::new()
+///     .move_to(50.0, 10.0)
+///     .cubic_to(90.0, 60.0, 70.0, 90.0, 50.0, 90.0)
+///     .cubic_to(30.0, 90.0, 10.0, 60.0, 50.0, 10.0)
+///     .close();
+/// ```
+pub struct Path {
+    pub segments: Vec<PathSegment>
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self { segments: vec![] }
+    }
+
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.segments.push(PathSegment::MoveTo(x, y));
+        self
+    }
+
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        self.segments.push(PathSegment::LineTo(x, y));
+        self
+    }
+
+    pub fn quad_to(mut self, control_x: f32, control_y: f32, x: f32, y: f32) -> Self {
+        self.segments.push(PathSegment::QuadTo(control_x, control_y, x, y));
+        self
+    }
+
+    pub fn cubic_to(mut self, control1_x: f32, control1_y: f32, control2_x: f32, control2_y: f32, x: f32, y: f32) -> Self {
+        self.segments.push(PathSegment::CubicTo(control1_x, control1_y, control2_x, control2_y, x, y));
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.segments.push(PathSegment::Close);
+        self
+    }
+
+    /// Multiply every coordinate of the path by `factor`, about (0.0, 0.0).
+    pub fn scale(&self, factor: f32) -> Self {
+        let segments = self.segments.iter().map(|segment| match *segment {
+            PathSegment::MoveTo(x, y) => PathSegment::MoveTo(x*factor, y*factor),
+            PathSegment::LineTo(x, y) => PathSegment::LineTo(x*factor, y*factor),
+            PathSegment::QuadTo(control_x, control_y, x, y) => PathSegment::QuadTo(control_x*factor, control_y*factor, x*factor, y*factor),
+            PathSegment::CubicTo(control1_x, control1_y, control2_x, control2_y, x, y) => PathSegment::CubicTo(
+                control1_x*factor, control1_y*factor, control2_x*factor, control2_y*factor, x*factor, y*factor
+            ),
+            PathSegment::Close => PathSegment::Close
+        }).collect();
+        Self { segments }
+    }
+
+    /// Parse SVG path data, as in the `d` attribute of a `<path>` element.
+    /// 
+    /// Every command is supported, in absolute and relative forms. Arcs are converted to cubic curves,
+    /// and horizontal and vertical lines to [`PathSegment::LineTo`].
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{AlphaPixel, Layer, layers::path::{Path, PathLayer}};
+    /// 
+    /// // A heart
+    /// let path = Path::from_svg("M 50,30 A 20,20 0 0 1 90,30 Q 90,60 50,90 Q 10,60 10,30 A 20,20 0 0 1 50,30 z").unwrap();
+    /// let heart: PathLayer<u8> = PathLayer::new(path).with_fill(AlphaPixel::red());
+    /// assert_eq!(heart.filtered_pixel_at(30, 40).unwrap(), AlphaPixel::red());
+    /// assert_eq!(heart.filtered_pixel_at(50, 15).unwrap().a, 0);
+    /// ```
+    pub fn from_svg(data: &str) -> Result<Self, SvgPathError> {
+        SvgPathParser { data: data.as_bytes(), position: 0 }.parse()
+    }
+
+    /// Convert the path into a list of subpaths, each of which is a list of points joined by straight lines.
+    /// 
+    /// Curves are split into enough lines that each is at most a couple of pixels long. The boolean is whether
+    /// the subpath was closed. A path which doesn't start with [`PathSegment::MoveTo`] starts at (0.0, 0.0).
+    pub fn flatten(&self) -> Vec<(Vec<(f32, f32)>, bool)> {
+        let mut subpaths = vec![];
+        let mut current: Vec<(f32, f32)> = vec![];
+        let mut position = (0.0, 0.0);
+
+        let mut finish = |current: &mut Vec<(f32, f32)>, closed: bool| {
+            if current.len() > 1 {
+                subpaths.push((std::mem::take(current), closed));
+            } else {
+                current.clear();
+            }
+        };
+
+        for segment in &self.segments {
+            if current.is_empty() && !matches!(segment, PathSegment::MoveTo(..)) {
+                current.push(position);
+            }
+
+            match *segment {
+                PathSegment::MoveTo(x, y) => {
+                    finish(&mut current, false);
+                    current.push((x, y));
+                    position = (x, y);
+                },
+                PathSegment::LineTo(x, y) => {
+                    current.push((x, y));
+                    position = (x, y);
+                },
+                PathSegment::QuadTo(control_x, control_y, x, y) => {
+                    let points = [position, (control_x, control_y), (x, y)];
+                    let subdivisions = curve_subdivisions(&points);
+                    for i in 1..=subdivisions {
+                        let t = i as f32 / subdivisions as f32;
+                        let u = 1.0 - t;
+                        current.push((
+                            u*u*points[0].0 + 2.0*u*t*points[1].0 + t*t*points[2].0,
+                            u*u*points[0].1 + 2.0*u*t*points[1].1 + t*t*points[2].1
+                        ));
+                    }
+                    position = (x, y);
+                },
+                PathSegment::CubicTo(control1_x, control1_y, control2_x, control2_y, x, y) => {
+                    let points = [position, (control1_x, control1_y), (control2_x, control2_y), (x, y)];
+                    let subdivisions = curve_subdivisions(&points);
+                    for i in 1..=subdivisions {
+                        let t = i as f32 / subdivisions as f32;
+                        let u = 1.0 - t;
+                        current.push((
+                            u*u*u*points[0].0 + 3.0*u*u*t*points[1].0 + 3.0*u*t*t*points[2].0 + t*t*t*points[3].0,
+                            u*u*u*points[0].1 + 3.0*u*u*t*points[1].1 + 3.0*u*t*t*points[2].1 + t*t*t*points[3].1
+                        ));
+                    }
+                    position = (x, y);
+                },
+                PathSegment::Close => {
+                    position = current[0];
+                    finish(&mut current, true);
+                }
+            }
+        }
+        finish(&mut current, false);
+
+        subpaths
+    }
+}
+
+impl FromStr for Path {
+    type Err = SvgPathError;
+
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        Path::from_svg(data)
+    }
+}
+
+/// Reads the commands of SVG path data one at a time, keeping track of the state needed by relative
+/// and smooth commands.
+struct SvgPathParser<'a> {
+    data: &'a [u8],
+    position: usize
+}
+
+impl SvgPathParser<'_> {
+    fn parse(mut self) -> Result<Path, SvgPathError> {
+        let mut path = Path::new();
+        let mut current = (0.0, 0.0);
+        let mut subpath_start = (0.0, 0.0);
+        // The command which is repeated when a command is followed by more numbers than it takes
+        let mut command: Option<u8> = None;
+        // The last control point of the previous segment, if it was a cubic or quadratic curve, which smooth curves reflect
+        let mut last_cubic_control: Option<(f32, f32)> = None;
+        let mut last_quad_control: Option<(f32, f32)> = None;
+
+        loop {
+            self.skip_separators();
+            let Some(&next) = self.data.get(self.position) else { break };
+            let letter = if next.is_ascii_alphabetic() {
+                self.position += 1;
+                next
+            } else {
+                // Numbers without a command repeat the previous command, except after a close
+                match command {
+                    Some(previous) if !previous.eq_ignore_ascii_case(&b'z') => previous,
+                    Some(_) => return Err(SvgPathError::UnknownCommand { character: next as char, position: self.position }),
+                    None => return Err(SvgPathError::MissingMoveTo)
+                }
+            };
+            if command.is_none() && !letter.eq_ignore_ascii_case(&b'm') {
+                return Err(SvgPathError::MissingMoveTo)
+            }
+
+            let relative = letter.is_ascii_lowercase();
+            let offset = if relative { current } else { (0.0, 0.0) };
+            let mut cubic_control = None;
+            let mut quad_control = None;
+
+            match letter.to_ascii_uppercase() {
+                b'M' => {
+                    current = self.point(offset)?;
+                    subpath_start = current;
+                    path.segments.push(PathSegment::MoveTo(current.0, current.1));
+                    // Further points are lines
+                    command = Some(if relative { b'l' } else { b'L' });
+                    last_cubic_control = None;
+                    last_quad_control = None;
+                    continue
+                },
+                b'L' => {
+                    current = self.point(offset)?;
+                    path.segments.push(PathSegment::LineTo(current.0, current.1));
+                },
+                b'H' => {
+                    current.0 = self.number()? + offset.0;
+                    path.segments.push(PathSegment::LineTo(current.0, current.1));
+                },
+                b'V' => {
+                    current.1 = self.number()? + offset.1;
+                    path.segments.push(PathSegment::LineTo(current.0, current.1));
+                },
+                b'C' | b'S' => {
+                    let control1 = match letter.to_ascii_uppercase() {
+                        b'C' => self.point(offset)?,
+                        _ => reflect(last_cubic_control, current)
+                    };
+                    let control2 = self.point(offset)?;
+                    current = self.point(offset)?;
+                    path.segments.push(PathSegment::CubicTo(control1.0, control1.1, control2.0, control2.1, current.0, current.1));
+                    cubic_control = Some(control2);
+                },
+                b'Q' | b'T' => {
+                    let control = match letter.to_ascii_uppercase() {
+                        b'Q' => self.point(offset)?,
+                        _ => reflect(last_quad_control, current)
+                    };
+                    current = self.point(offset)?;
+                    path.segments.push(PathSegment::QuadTo(control.0, control.1, current.0, current.1));
+                    quad_control = Some(control);
+                },
+                b'A' => {
+                    let (radius_x, radius_y) = (self.number()?, self.number()?);
+                    let rotation = self.number()?;
+                    let (large_arc, sweep) = (self.flag()?, self.flag()?);
+                    let end = self.point(offset)?;
+                    path.segments.extend(arc_segments(current, (radius_x, radius_y), rotation, large_arc, sweep, end));
+                    current = end;
+                },
+                b'Z' => {
+                    path.segments.push(PathSegment::Close);
+                    current = subpath_start;
+                },
+                _ => return Err(SvgPathError::UnknownCommand { character: letter as char, position: self.position - 1 })
+            }
+
+            command = Some(letter);
+            last_cubic_control = cubic_control;
+            last_quad_control = quad_control;
+        }
+
+        Ok(path)
+    }
+
+    /// Skip whitespace, and at most one comma.
+    fn skip_separators(&mut self) {
+        let mut comma = false;
+        while let Some(&next) = self.data.get(self.position) {
+            match next {
+                b',' if !comma => comma = true,
+                next if next.is_ascii_whitespace() => {},
+                _ => break
+            }
+            self.position += 1;
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, SvgPathError> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            let digits_start = parser.position;
+            while parser.data.get(parser.position).is_some_and(u8::is_ascii_digit) {
+                parser.position += 1;
+            }
+            parser.position > digits_start
+        };
+        let sign = |parser: &mut Self| {
+            if matches!(parser.data.get(parser.position), Some(b'+' | b'-')) {
+                parser.position += 1;
+            }
+        };
+
+        // A number ends where it can't continue, so "0.5.5" is two numbers and "1-2" is two numbers
+        sign(self);
+        let mut has_digits = digits(self);
+        if self.data.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            has_digits |= digits(self);
+        }
+        if !has_digits {
+            self.position = start;
+            return Err(SvgPathError::ExpectedNumber(start))
+        }
+        if matches!(self.data.get(self.position), Some(b'e' | b'E')) {
+            let exponent_start = self.position;
+            self.position += 1;
+            sign(self);
+            if !digits(self) {
+                // The "e" is the start of the next command
+                self.position = exponent_start;
+            }
+        }
+
+        // The bytes are ASCII digits, signs, dots and exponents, so they are valid UTF-8 and a valid float
+        std::str::from_utf8(&self.data[start..self.position]).ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or(SvgPathError::ExpectedNumber(start))
+    }
+
+    fn point(&mut self, offset: (f32, f32)) -> Result<(f32, f32), SvgPathError> {
+        Ok((self.number()? + offset.0, self.number()? + offset.1))
+    }
+
+    /// Read an arc flag, which may be written without a separator before the next number.
+    fn flag(&mut self) -> Result<bool, SvgPathError> {
+        self.skip_separators();
+        let flag = match self.data.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(SvgPathError::ExpectedFlag(self.position))
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+}
+
+/// Get the first control point of a smooth curve, which is the reflection of the previous curve's last control point
+/// about the current point, or the current point if the previous segment wasn't the same kind of curve.
+fn reflect(control: Option<(f32, f32)>, current: (f32, f32)) -> (f32, f32) {
+    match control {
+        Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        None => current
+    }
+}
+
+/// Convert an SVG elliptical arc from `start` to `end` into cubic curves, of at most a quarter turn each.
+/// 
+/// Follows the endpoint to center conversion in the SVG specification. Radii which are too small to reach `end`
+/// are scaled up, and an arc with a zero radius is a straight line.
+fn arc_segments(start: (f32, f32), radii: (f32, f32), rotation: f32, large_arc: bool, sweep: bool, end: (f32, f32)) -> Vec<PathSegment> {
+    if start == end {
+        return vec![]
+    }
+    let (mut radius_x, mut radius_y) = (radii.0.abs(), radii.1.abs());
+    if radius_x == 0.0 || radius_y == 0.0 {
+        return vec![PathSegment::LineTo(end.0, end.1)]
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    // The midpoint between the ends, in the rotated space of the ellipse
+    let (half_x, half_y) = ((start.0 - end.0) / 2.0, (start.1 - end.1) / 2.0);
+    let (mid_x, mid_y) = (cos * half_x + sin * half_y, -sin * half_x + cos * half_y);
+
+    let lambda = (mid_x / radius_x).powi(2) + (mid_y / radius_y).powi(2);
+    if lambda > 1.0 {
+        radius_x *= lambda.sqrt();
+        radius_y *= lambda.sqrt();
+    }
+
+    let numerator = (radius_x * radius_y).powi(2) - (radius_x * mid_y).powi(2) - (radius_y * mid_x).powi(2);
+    let denominator = (radius_x * mid_y).powi(2) + (radius_y * mid_x).powi(2);
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let (center_x, center_y) = (coefficient * radius_x * mid_y / radius_y, -coefficient * radius_y * mid_x / radius_x);
+
+    let angle = |(u_x, u_y): (f32, f32), (v_x, v_y): (f32, f32)| (u_x * v_y - u_y * v_x).atan2(u_x * v_x + u_y * v_y);
+    let start_vector = ((mid_x - center_x) / radius_x, (mid_y - center_y) / radius_y);
+    let end_vector = ((-mid_x - center_x) / radius_x, (-mid_y - center_y) / radius_y);
+    let start_angle = angle((1.0, 0.0), start_vector);
+    let mut sweep_angle = angle(start_vector, end_vector);
+    if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    } else if sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    }
+
+    // Map a point on the unit circle to the ellipse on the canvas
+    let (center_x, center_y) = (cos * center_x - sin * center_y + (start.0 + end.0) / 2.0, sin * center_x + cos * center_y + (start.1 + end.1) / 2.0);
+    let to_canvas = |(x, y): (f32, f32)| {
+        let (x, y) = (x * radius_x, y * radius_y);
+        (center_x + cos * x - sin * y, center_y + sin * x + cos * y)
+    };
+
+    let count = (sweep_angle.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+    let step = sweep_angle / count as f32;
+    // The distance of the control points along the tangents, for a cubic curve close to a circular arc of `step`
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+    (0..count)
+        .map(|i| {
+            let (from, to) = (start_angle + step * i as f32, start_angle + step * (i + 1) as f32);
+            let control1 = to_canvas((from.cos() - handle * from.sin(), from.sin() + handle * from.cos()));
+            let control2 = to_canvas((to.cos() + handle * to.sin(), to.sin() - handle * to.cos()));
+            // End exactly at `end`, without rounding errors
+            let point = if i == count - 1 { end } else { to_canvas((to.cos(), to.sin())) };
+            PathSegment::CubicTo(control1.0, control1.1, control2.0, control2.1, point.0, point.1)
+        })
+        .collect()
+}
+
+/// Get the number of lines to split a curve into, from the length of its control polygon.
+fn curve_subdivisions(points: &[(f32, f32)]) -> usize {
+    let length: f32 = points.windows(2)
+        .map(|pair| ((pair[1].0 - pair[0].0).powi(2) + (pair[1].1 - pair[0].1).powi(2)).sqrt())
+        .sum();
+    ((length / 2.0).ceil() as usize).clamp(1, MAX_CURVE_SUBDIVISIONS)
+}
+
+/// A layer which fills and/or strokes a [`Path`].
+/// 
+/// The fill is antialiased by sampling a 4x4 grid within each pixel, and the stroke is drawn with round ends and joins,
+/// centered on the path. The stroke is drawn over the fill.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, layers::path::{Path, PathLayer}};
+/// 
+/// let path = Path::new().move_to(10.0, 10.0).quad_to(50.0, 90.0, 90.0, 10.0).close();
+/// let layer: PathLayer<u8> = PathLayer::new(path)
+///     .with_fill(AlphaPixel::red())
+///     .with_stroke(AlphaPixel::black(), 4.0);
+/// 
+/// assert_eq!(layer.filtered_pixel_at(50, 30).unwrap(), AlphaPixel::red());
+/// assert_eq!(layer.filtered_pixel_at(50, 10).unwrap(), AlphaPixel::black());
+/// ```
+pub struct PathLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub fill: Option<AlphaPixel<T>>,
+    pub fill_rule: FillRule,
+    pub stroke: Option<AlphaPixel<T>>,
+    pub stroke_width: f32,
+    path: Path,
+    subpaths: Vec<(Vec<(f32, f32)>, bool)>
+}
+
+impl<T> PathLayer<T> {
+    /// Create a layer for `path`, which has no fill or stroke until they are set.
+    pub fn new(path: Path) -> Self {
+        let subpaths = path.flatten();
+        Self { filters: vec![], fill: None, fill_rule: FillRule::NonZero, stroke: None, stroke_width: 1.0, path, subpaths }
+    }
+
+    pub fn with_fill(mut self, fill: AlphaPixel<T>) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: AlphaPixel<T>, width: f32) -> Self {
+        self.stroke = Some(stroke);
+        self.stroke_width = width;
+        self
+    }
+
+    /// Create a layer from SVG path data, which has no fill or stroke until they are set. See [`Path::from_svg`].
+    pub fn from_svg(data: &str) -> Result<Self, SvgPathError> {
+        Ok(Self::new(Path::from_svg(data)?))
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn set_path(&mut self, path: Path) {
+        self.subpaths = path.flatten();
+        self.path = path;
+    }
+
+    fn fill_contains(&self, x: f32, y: f32) -> bool {
+        // Subpaths are filled as if they were closed
+        let winding: i32 = self.subpaths.iter().map(|(points, _)| winding_number(points, x, y)).sum();
+        match self.fill_rule {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0
+        }
+    }
+
+    fn fill_coverage(&self, x: usize, y: usize) -> f32 {
+        const SAMPLES: usize = 4;
+        let covered = (0..SAMPLES*SAMPLES)
+            .filter(|i| {
+                let sample_x = x as f32 + ((i % SAMPLES) as f32 + 0.5) / SAMPLES as f32;
+                let sample_y = y as f32 + ((i / SAMPLES) as f32 + 0.5) / SAMPLES as f32;
+                self.fill_contains(sample_x, sample_y)
+            })
+            .count();
+        covered as f32 / (SAMPLES*SAMPLES) as f32
+    }
+
+    fn stroke_coverage(&self, x: usize, y: usize) -> f32 {
+        let reach = self.stroke_width / 2.0 + 1.0;
+        let (point_x, point_y) = (x as f32 + 0.5, y as f32 + 0.5);
+        let mut coverage: f32 = 0.0;
+
+        for (points, closed) in &self.subpaths {
+            let closing_line = closed.then(|| [points[points.len() - 1], points[0]]);
+            let lines = points.windows(2).map(|pair| [pair[0], pair[1]]).chain(closing_line);
+            for [start, end] in lines {
+                // Skip lines which are too far away to cover the pixel
+                if point_x < start.0.min(end.0) - reach || point_x > start.0.max(end.0) + reach
+                    || point_y < start.1.min(end.1) - reach || point_y > start.1.max(end.1) + reach {
+                    continue
+                }
+                coverage = coverage.max(line_coverage(start, end, self.stroke_width, x, y));
+                if coverage >= 1.0 {
+                    return 1.0
+                }
+            }
+        }
+
+        coverage
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for PathLayer<T> {
+    fn get_rect(&self) -> Rect {
+        let points = self.subpaths.iter().flat_map(|(points, _)| points);
+        let (mut left, mut top) = (f32::INFINITY, f32::INFINITY);
+        let (mut right, mut bottom) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for (x, y) in points {
+            left = left.min(*x);
+            top = top.min(*y);
+            right = right.max(*x);
+            bottom = bottom.max(*y);
+        }
+        if left > right {
+            return Rect::default()
+        }
+
+        let padding = if self.stroke.is_some() { self.stroke_width / 2.0 + 1.0 } else { 0.0 };
+        let (left, top) = ((left - padding).floor().max(0.0), (top - padding).floor().max(0.0));
+        let (right, bottom) = ((right + padding).ceil().max(left), (bottom + padding).ceil().max(top));
+        Rect { x: left as usize, y: top as usize, width: (right - left) as usize, height: (bottom - top) as usize }
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let fill = match self.fill {
+            Some(fill) => fill.scale_alpha(self.fill_coverage(x, y)),
+            None => AlphaPixel::default()
+        };
+        match self.stroke {
+            Some(stroke) => BlendingMethod::Over.blend(fill, stroke.scale_alpha(self.stroke_coverage(x, y))),
+            None => fill
+        }
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        let mut layer = PathLayer::new(self.path.scale(factor));
+        layer.fill = self.fill;
+        layer.fill_rule = self.fill_rule;
+        layer.stroke = self.stroke;
+        layer.stroke_width = self.stroke_width*factor;
+        Some(Box::new(layer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flatten() {
+        let path = Path::new()
+            .move_to(0.0, 0.0).line_to(10.0, 0.0).quad_to(10.0, 10.0, 0.0, 10.0).close()
+            .move_to(20.0, 20.0).line_to(30.0, 30.0);
+        let subpaths = path.flatten();
+
+        assert_eq!(subpaths.len(), 2);
+        assert!(subpaths[0].1);
+        assert!(!subpaths[1].1);
+        assert_eq!(subpaths[0].0.last(), Some(&(0.0, 10.0)));
+        assert!(subpaths[0].0.len() > 4);
+        assert_eq!(subpaths[1].0, vec![(20.0, 20.0), (30.0, 30.0)]);
+    }
+
+    #[test]
+    fn stroke_only() {
+        let path = Path::new().move_to(0.0, 5.0).line_to(20.0, 5.0);
+        let layer: PathLayer<u8> = PathLayer::new(path).with_stroke(AlphaPixel::blue(), 2.0);
+        let rect = layer.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (0, 3, 22, 4));
+
+        assert_eq!(layer.filtered_pixel_at(10, 4).unwrap(), AlphaPixel::blue());
+        assert_eq!(layer.filtered_pixel_at(10, 6).unwrap().a, 0);
+    }
+
+    #[test]
+    fn cubic_fill() {
+        // A circle approximated by cubic curves
+        let k = 0.5523 * 10.0;
+        let path = Path::new()
+            .move_to(20.0, 10.0)
+            .cubic_to(20.0, 10.0 + k, 10.0 + k, 20.0, 10.0, 20.0)
+            .cubic_to(10.0 - k, 20.0, 0.0, 10.0 + k, 0.0, 10.0)
+            .cubic_to(0.0, 10.0 - k, 10.0 - k, 0.0, 10.0, 0.0)
+            .cubic_to(10.0 + k, 0.0, 20.0, 10.0 - k, 20.0, 10.0)
+            .close();
+        let layer: PathLayer<u8> = PathLayer::new(path).with_fill(AlphaPixel::red());
+
+        assert_eq!(layer.filtered_pixel_at(10, 10).unwrap(), AlphaPixel::red());
+        assert_eq!(layer.filtered_pixel_at(1, 1).unwrap().a, 0);
+        let edge = layer.filtered_pixel_at(17, 16).unwrap().a;
+        assert!(edge > 0 && edge < 255);
+    }
+
+    #[test]
+    fn svg_commands() {
+        let path = Path::from_svg("M10 10 h 10 v10 H10 z m 5-5 L 30,30 40,40").unwrap();
+        assert_eq!(path.segments, vec![
+            PathSegment::MoveTo(10.0, 10.0),
+            PathSegment::LineTo(20.0, 10.0),
+            PathSegment::LineTo(20.0, 20.0),
+            PathSegment::LineTo(10.0, 20.0),
+            PathSegment::Close,
+            PathSegment::MoveTo(15.0, 5.0),
+            PathSegment::LineTo(30.0, 30.0),
+            PathSegment::LineTo(40.0, 40.0)
+        ]);
+    }
+
+    #[test]
+    fn svg_numbers() {
+        // Numbers can be written without separators, where they can't be read as one number
+        let path = Path::from_svg("M-1.5.5l2-2e1,1E1.5").unwrap();
+        assert_eq!(path.segments, vec![PathSegment::MoveTo(-1.5, 0.5), PathSegment::LineTo(0.5, -19.5), PathSegment::LineTo(10.5, -19.0)]);
+    }
+
+    #[test]
+    fn svg_smooth_curves() {
+        let path = Path::from_svg("M0 0 C 0 10 10 10 10 0 S 20 -10 20 0 Q 25 5 30 0 T 40 0").unwrap();
+        assert_eq!(path.segments[2], PathSegment::CubicTo(10.0, -10.0, 20.0, -10.0, 20.0, 0.0));
+        assert_eq!(path.segments[4], PathSegment::QuadTo(35.0, -5.0, 40.0, 0.0));
+    }
+
+    #[test]
+    fn svg_arc() {
+        // A half circle of radius 10, with flags written without separators
+        let path = Path::from_svg("M 0 10 a10 10 0 01 20 0").unwrap();
+        assert_eq!(path.segments.len(), 3);
+        let Some(PathSegment::CubicTo(.., x, y)) = path.segments.get(1) else { panic!("Arc isn't a curve") };
+        assert!((x - 10.0).abs() < 0.01 && y.abs() < 0.01);
+        // The arc ends exactly at its end point
+        assert!(matches!(path.segments[2], PathSegment::CubicTo(.., 20.0, 10.0)));
+    }
+
+    #[test]
+    fn svg_errors() {
+        assert_eq!(Path::from_svg("L 10 10"), Err(SvgPathError::MissingMoveTo));
+        assert_eq!(Path::from_svg("M 10 10 X 5"), Err(SvgPathError::UnknownCommand { character: 'X', position: 8 }));
+        assert_eq!(Path::from_svg("M 10"), Err(SvgPathError::ExpectedNumber(4)));
+        assert_eq!(Path::from_svg("M 0 0 A 5 5 0 2 0 10 10"), Err(SvgPathError::ExpectedFlag(14)));
+        assert_eq!(Path::from_svg(""), Ok(Path::new()));
+    }
+}
@@ -0,0 +1,114 @@
+use qrcodegen::{QrCode, QrCodeEcc};
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, Error};
+
+/// The number of light modules around a QR code, which scanners need to find its edges.
+const QUIET_ZONE: usize = 4;
+
+/// A QR code, scaled to the largest whole number of pixels per module that fits in a `Rect`.
+/// 
+/// The code is centered in the `Rect`, with a quiet zone of light modules around it, and the rest of the `Rect`
+/// is also light. If the `Rect` is too small for one pixel per module, the code is cut off and won't scan.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, layers::qr::QrCodeLayer};
+/// 
+/// let code: QrCodeLayer<u8> = QrCodeLayer::try_new("https://example.com", Rect { x: 0, y: 0, width: 200, height: 200 }).unwrap();
+/// // The corner is in the quiet zone, and the finder pattern starts just inside it
+/// assert_eq!(code.filtered_pixel_at(0, 0).unwrap(), AlphaPixel::white());
+/// let start = code.code_rect().x;
+/// assert_eq!(code.filtered_pixel_at(start, start).unwrap(), AlphaPixel::black());
+/// ```
+pub struct QrCodeLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub dark: AlphaPixel<T>,
+    pub light: AlphaPixel<T>,
+    code: QrCode,
+    rect: Rect
+}
+
+impl<T: PixelChannel> QrCodeLayer<T> {
+    /// Encode `data` as a black and white QR code filling `rect`, with medium error correction.
+    /// 
+    /// Returns [`Error::QrCode`] if `data` is too long to fit in a QR code.
+    pub fn try_new(data: &str, rect: Rect) -> Result<Self, Error> {
+        let code = QrCode::encode_text(data, QrCodeEcc::Medium)?;
+        Ok(Self { filters: vec![], dark: AlphaPixel::black(), light: AlphaPixel::white(), code, rect })
+    }
+
+    pub fn with_colors(mut self, dark: AlphaPixel<T>, light: AlphaPixel<T>) -> Self {
+        self.dark = dark;
+        self.light = light;
+        self
+    }
+
+    /// Get the number of modules along each side of the code, without the quiet zone.
+    pub fn module_count(&self) -> usize {
+        self.code.size() as usize
+    }
+
+    /// Get the size of each module in pixels, which is at least 1.
+    pub fn module_size(&self) -> usize {
+        let total = self.module_count() + 2 * QUIET_ZONE;
+        (self.rect.width.min(self.rect.height) / total).max(1)
+    }
+
+    /// Get the `Rect` covered by the modules of the code, without the quiet zone.
+    pub fn code_rect(&self) -> Rect {
+        let size = self.module_count() * self.module_size();
+        Rect {
+            x: self.rect.x + self.rect.width.saturating_sub(size) / 2,
+            y: self.rect.y + self.rect.height.saturating_sub(size) / 2,
+            width: size,
+            height: size
+        }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for QrCodeLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let code_rect = self.code_rect();
+        if !code_rect.contains(x, y) {
+            return self.light
+        }
+
+        let module_size = self.module_size();
+        let module_x = (x - code_rect.x) / module_size;
+        let module_y = (y - code_rect.y) / module_size;
+        if self.code.get_module(module_x as i32, module_y as i32) { self.dark } else { self.light }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_modules() {
+        let code: QrCodeLayer<u8> = QrCodeLayer::try_new("SERIAL-0001", Rect { x: 10, y: 10, width: 100, height: 120 }).unwrap();
+        // Version 1 codes are 21 modules, so with the quiet zone 29 modules fit 3 pixels each
+        assert_eq!(code.module_count(), 21);
+        assert_eq!(code.module_size(), 3);
+        let rect = code.code_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (28, 38, 63, 63));
+
+        // The finder pattern in the top left is 7 dark modules wide, with a light ring inside
+        assert_eq!(code.filtered_pixel_at(28 + 20, 38).unwrap(), AlphaPixel::black());
+        assert_eq!(code.filtered_pixel_at(28 + 3, 38 + 3).unwrap(), AlphaPixel::white());
+        assert_eq!(code.filtered_pixel_at(27, 38).unwrap(), AlphaPixel::white());
+    }
+
+    #[test]
+    fn too_long() {
+        let data = "0".repeat(10000);
+        assert!(matches!(QrCodeLayer::<u8>::try_new(&data, Rect::default()), Err(Error::QrCode(_))));
+    }
+}
@@ -0,0 +1,155 @@
+use std::f32::consts::SQRT_2;
+
+use crate::{
+    Filter,
+    Layer,
+    Image,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    BlendingMethod,
+    Error,
+    layers::text::TextSettings
+};
+
+/// A corner of a `Rect`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight
+}
+
+/// The appearance of a [`RibbonLayer`].
+#[derive(Clone, Copy)]
+pub struct RibbonStyle<T> {
+    pub fill: AlphaPixel<T>,
+    /// The width and height of the square in the corner that the ribbon is drawn in
+    pub size: usize,
+    /// The width of the ribbon's band
+    pub band_width: f32,
+    /// The distance from the corner to the center of the band, measured along the diagonal
+    pub band_offset: f32
+}
+
+impl<T: PixelChannel> Default for RibbonStyle<T> {
+    fn default() -> Self {
+        Self { fill: AlphaPixel::red(), size: 120, band_width: 30.0, band_offset: 55.0 }
+    }
+}
+
+/// A diagonal ribbon across the corner of a `Rect`, with text along it, such as "NEW" or "SALE".
+/// 
+/// The ribbon and its text are cut off at the edges of the corner.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use image_template::{Canvas, AlphaPixel, Rect, layers::ribbon::{RibbonLayer, RibbonStyle, Corner}};
+/// use image_template::layers::text::{TextSettings, layout::TextLayout};
+/// 
+/// let font_bytes = std::fs::read("font.ttf").unwrap();
+/// let font = fontdue::Font::from_bytes(font_bytes, fontdue::FontSettings::default()).unwrap();
+/// 
+/// let settings = TextSettings {
+///     size: 20.0,
+///     fill: AlphaPixel::white(),
+///     layout: TextLayout::default(),
+///     text: String::from("SALE"),
+///     font
+/// };
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(400, 300);
+/// let card = Rect { x: 0, y: 0, width: 400, height: 300 };
+/// canvas.add_layer(RibbonLayer::try_new(settings, card, Corner::TopRight, RibbonStyle::default()).unwrap());
+/// ```
+pub struct RibbonLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    settings: TextSettings<T>,
+    text: Image<T>,
+    corner: Corner,
+    style: RibbonStyle<T>,
+    rect: Rect
+}
+
+impl<T: PixelChannel> RibbonLayer<T> {
+    /// Create a ribbon in a corner of `target`, which is usually the `Rect` of a card or the whole canvas.
+    pub fn try_new(settings: TextSettings<T>, target: Rect, corner: Corner, style: RibbonStyle<T>) -> Result<Self, Error> {
+        let text = settings.raster_from_settings()?;
+        let size = style.size.min(target.width).min(target.height);
+
+        let rect = match corner {
+            Corner::TopLeft => Rect { x: target.x, y: target.y, width: size, height: size },
+            Corner::TopRight => Rect { x: target.right_x() - size, y: target.y, width: size, height: size },
+            Corner::BottomLeft => Rect { x: target.x, y: target.bottom_y() - size, width: size, height: size },
+            Corner::BottomRight => Rect { x: target.right_x() - size, y: target.bottom_y() - size, width: size, height: size }
+        };
+
+        Ok(Self { filters: vec![], settings, text, corner, style, rect })
+    }
+
+    pub fn get_text_settings(&self) -> &TextSettings<T> {
+        &self.settings
+    }
+
+    /// Get a coordinate within the corner square, flipped so that the corner of the ribbon is at the top left.
+    fn coordinate_from_corner(&self, x: usize, y: usize) -> (f32, f32) {
+        let size = self.rect.width as f32;
+        let local_x = (x - self.rect.x) as f32 + 0.5;
+        let local_y = (y - self.rect.y) as f32 + 0.5;
+
+        match self.corner {
+            Corner::TopLeft => (local_x, local_y),
+            Corner::TopRight => (size - local_x, local_y),
+            Corner::BottomLeft => (local_x, size - local_y),
+            Corner::BottomRight => (size - local_x, size - local_y)
+        }
+    }
+
+    /// Sample the text, rotated to follow the band. The text is centered on the middle of the band.
+    fn text_pixel(&self, corner_x: f32, corner_y: f32) -> Option<AlphaPixel<T>> {
+        // Position along the band, and across the band
+        let along = (corner_x - corner_y) / SQRT_2;
+        let across = (corner_x + corner_y) / SQRT_2 - self.style.band_offset;
+
+        // Flipping the corner to the top left also mirrors the text, so it's flipped back for the right corners.
+        // The top of the text always faces away from the band's outer edge, towards the top of the canvas.
+        let along = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => along,
+            Corner::TopRight | Corner::BottomRight => -along
+        };
+        let across = match self.corner {
+            Corner::TopLeft | Corner::TopRight => across,
+            Corner::BottomLeft | Corner::BottomRight => -across
+        };
+
+        self.text.sample_bilinear(
+            along + self.text.get_width() as f32 / 2.0,
+            across + self.text.get_height() as f32 / 2.0
+        )
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for RibbonLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let (corner_x, corner_y) = self.coordinate_from_corner(x, y);
+
+        let distance_from_corner = (corner_x + corner_y) / SQRT_2;
+        let distance_from_band = (distance_from_corner - self.style.band_offset).abs();
+        let coverage = (self.style.band_width / 2.0 + 0.5 - distance_from_band).clamp(0.0, 1.0);
+        let band = self.style.fill.scale_alpha(coverage);
+
+        match self.text_pixel(corner_x, corner_y) {
+            Some(text_pixel) => BlendingMethod::Over.blend(band, text_pixel),
+            None => band
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, bitmap::sampler::{BilinearSampler, sample}};
+
+/// A wrapper around a layer which rotates it clockwise by `angle` degrees around the center of its `Rect`.
+/// 
+/// The `Rect` of the rotated layer is expanded to fit the whole rotated layer, so nothing is cut off, and the
+/// rotated pixels are sampled smoothly. This is simpler than rotating with a
+/// [`MatrixTransform`](crate::filters::transform::MatrixTransform), which maps coordinates the other way, and doesn't
+/// change the `Rect` of the layer. Any layer can be rotated with [`Layer::rotated`].
+/// 
+/// The wrapped layer's filters are applied before it is rotated, and the filters of the wrapper after.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, layers::shapes::RectangleLayer};
+/// 
+/// // A wide bar stood on its end
+/// let bar = RectangleLayer::<u8>::new(AlphaPixel::red(), Rect { x: 20, y: 45, width: 60, height: 10 }).rotated(90.0);
+/// let rect = bar.get_rect();
+/// assert!(rect.width < 15 && rect.height >= 60);
+/// assert_eq!(bar.filtered_pixel_at(50, 25).unwrap(), AlphaPixel::red());
+/// assert_eq!(bar.filtered_pixel_at(25, 50), None);
+/// ```
+pub struct RotatedLayer<T: PixelChannel, L: Layer<T>> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub layer: L,
+    /// The clockwise rotation, in degrees
+    pub angle: f32
+}
+
+impl<T: PixelChannel, L: Layer<T>> RotatedLayer<T, L> {
+    pub fn new(layer: L, angle: f32) -> Self {
+        Self { filters: vec![], layer, angle }
+    }
+
+    /// Get the center of the wrapped layer's `Rect`, and the sine and cosine of the angle.
+    fn rotation(&self) -> ((f32, f32), f32, f32) {
+        let rect = self.layer.get_rect();
+        let center = (rect.x as f32 + rect.width as f32 / 2.0, rect.y as f32 + rect.height as f32 / 2.0);
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        (center, sin, cos)
+    }
+}
+
+impl<T: PixelChannel, L: Layer<T>> Layer<T> for RotatedLayer<T, L> {
+    fn get_rect(&self) -> Rect {
+        let rect = self.layer.get_rect();
+        if rect.width == 0 || rect.height == 0 {
+            return rect
+        }
+
+        let (center, sin, cos) = self.rotation();
+        let corners = [(rect.x, rect.y), (rect.right_x(), rect.y), (rect.x, rect.bottom_y()), (rect.right_x(), rect.bottom_y())]
+            .map(|(x, y)| {
+                let (relative_x, relative_y) = (x as f32 - center.0, y as f32 - center.1);
+                (relative_x*cos - relative_y*sin + center.0, relative_x*sin + relative_y*cos + center.1)
+            });
+
+        // Allow for rounding errors, so that quarter turns don't gain a row or column
+        let left = corners.iter().map(|corner| corner.0).fold(f32::INFINITY, f32::min);
+        let top = corners.iter().map(|corner| corner.1).fold(f32::INFINITY, f32::min);
+        let right = corners.iter().map(|corner| corner.0).fold(f32::NEG_INFINITY, f32::max);
+        let bottom = corners.iter().map(|corner| corner.1).fold(f32::NEG_INFINITY, f32::max);
+        let (left, top) = ((left + 1e-3).floor().max(0.0), (top + 1e-3).floor().max(0.0));
+        let (right, bottom) = ((right - 1e-3).ceil().max(left), (bottom - 1e-3).ceil().max(top));
+        Rect { x: left as usize, y: top as usize, width: (right - left) as usize, height: (bottom - top) as usize }
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        // Rotate the center of the pixel back onto the wrapped layer
+        let (center, sin, cos) = self.rotation();
+        let (relative_x, relative_y) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+        let source_x = relative_x*cos + relative_y*sin + center.0;
+        let source_y = -relative_x*sin + relative_y*cos + center.1;
+        sample(&BilinearSampler, source_x, source_y, |sample_x, sample_y| {
+            self.layer.filtered_pixel_at(usize::try_from(sample_x).ok()?, usize::try_from(sample_y).ok()?)
+        }).unwrap_or_default()
+    }
+
+    fn name(&self) -> String {
+        self.layer.name()
+    }
+
+    fn children(&self) -> Vec<&dyn Layer<T>> {
+        self.layer.children()
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        Some(Box::new(RotatedLayer::new(ScaledLayer(self.layer.scaled(factor)?), self.angle)))
+    }
+}
+
+/// The wrapped layer of a scaled [`RotatedLayer`].
+struct ScaledLayer<T>(Box<dyn Layer<T>>);
+
+impl<T: PixelChannel> Layer<T> for ScaledLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.0.get_rect()
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        self.0.get_filters()
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.0.unfiltered_pixel_at_unchecked(x, y)
+    }
+
+    fn name(&self) -> String {
+        self.0.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Canvas, layers::shapes::RectangleLayer};
+
+    #[test]
+    fn rect_and_pixels() {
+        let square = || RectangleLayer::<u8>::new(AlphaPixel::red(), Rect { x: 10, y: 10, width: 20, height: 20 });
+
+        // No rotation draws the same pixels
+        let unrotated = square().rotated(0.0);
+        let rect = unrotated.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (10, 10, 20, 20));
+        assert_eq!(unrotated.filtered_pixel_at(10, 10).unwrap(), AlphaPixel::red());
+
+        // A diamond grows to fit its corners, around the same center
+        let diamond = square().rotated(45.0);
+        let rect = diamond.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (5, 5, 30, 30));
+        assert_eq!(diamond.filtered_pixel_at(20, 10).unwrap(), AlphaPixel::red());
+        assert_eq!(diamond.filtered_pixel_at(7, 7).unwrap().a, 0);
+        // The edges are antialiased
+        let edge = diamond.filtered_pixel_at(14, 14).unwrap();
+        assert!(edge.a > 0);
+
+        // Rotated layers are redrawn when the canvas is scaled
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(40, 40);
+        canvas.add_layer(square().rotated(45.0));
+        let scaled = canvas.flatten_scaled(2.0);
+        assert!(scaled.pixel_at(40, 20).unwrap().a >= 254);
+        assert_eq!(scaled.pixel_at(14, 14).unwrap().a, 0);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{Filter, Layer, Image, AlphaPixel, PixelChannel, Rect, BlendingMethod, Rng};
+
+/// The ranges that each copy of a sprite in a [`ScatterLayer`] is randomized between.
+#[derive(Clone)]
+pub struct ScatterSettings<T> {
+    /// The number of copies of the sprite
+    pub count: usize,
+    /// The minimum and maximum scale of each copy
+    pub scale: (f32, f32),
+    /// The minimum and maximum clockwise rotation of each copy, in degrees
+    pub rotation: (f32, f32),
+    /// Colours that the copies are randomly tinted with. The sprite's channels are multiplied by the tint.
+    /// 
+    /// If this is empty, the sprite isn't tinted.
+    pub tints: Vec<AlphaPixel<T>>
+}
+
+impl<T> Default for ScatterSettings<T> {
+    fn default() -> Self {
+        Self { count: 50, scale: (0.5, 1.5), rotation: (0.0, 360.0), tints: vec![] }
+    }
+}
+
+/// A single copy of the sprite, with its position relative to the top left of the region.
+struct ScatterInstance<T> {
+    center_x: f32,
+    center_y: f32,
+    /// Rotation in radians
+    rotation: f32,
+    scale: f32,
+    tint: Option<AlphaPixel<T>>
+}
+
+/// A layer that places copies of a sprite at random positions, rotations, scales and tints within a region.
+/// 
+/// This can be used for confetti, bokeh or sparkles. The copies are generated when the layer is created,
+/// from a [`Rng`], so the same seed will always produce the same layer.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, Image, AlphaPixel, Rect, layers::scatter::{ScatterLayer, ScatterSettings}};
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(200, 200);
+/// let confetti = Image::new_with_fill(AlphaPixel::white(), 4, 8);
+/// let settings = ScatterSettings { tints: vec![AlphaPixel::red(), AlphaPixel::blue()], ..Default::default() };
+/// let scatter = ScatterLayer::new(confetti, Rect { x: 0, y: 0, width: 200, height: 200 }, &settings, &mut canvas.rng());
+/// canvas.add_layer(scatter);
+/// ```
+pub struct ScatterLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    sprite: Image<T>,
+    region: Rect,
+    instances: Vec<ScatterInstance<T>>
+}
+
+impl<T: PixelChannel> ScatterLayer<T> {
+    pub fn new(sprite: Image<T>, region: Rect, settings: &ScatterSettings<T>, rng: &mut Rng) -> Self {
+        let instances = (0..settings.count)
+            .map(|_| ScatterInstance {
+                center_x: rng.range_f32(0.0, region.width as f32),
+                center_y: rng.range_f32(0.0, region.height as f32),
+                rotation: rng.range_f32(settings.rotation.0, settings.rotation.1).to_radians(),
+                scale: rng.range_f32(settings.scale.0, settings.scale.1),
+                tint: match settings.tints.len() {
+                    0 => None,
+                    len => Some(settings.tints[rng.range_usize(0, len)])
+                }
+            })
+            .collect();
+
+        Self { filters: vec![], sprite, region, instances }
+    }
+
+    /// Create a scatter layer where the sprite is another layer, drawn within its bounding `Rect`.
+    pub fn from_layer<L: Layer<T>>(sprite: &L, region: Rect, settings: &ScatterSettings<T>, rng: &mut Rng) -> Self {
+        let rect = sprite.get_rect();
+        let sprite_image = Image::from_function(rect.width, rect.height, |x, y| {
+            sprite.filtered_pixel_at(rect.x + x, rect.y + y).unwrap_or_default()
+        });
+        Self::new(sprite_image, region, settings, rng)
+    }
+
+    /// Sample a copy of the sprite at a coordinate relative to the top left of the region.
+    fn sample_instance(&self, instance: &ScatterInstance<T>, x: f32, y: f32) -> Option<AlphaPixel<T>> {
+        let half_width = self.sprite.get_width() as f32 / 2.0;
+        let half_height = self.sprite.get_height() as f32 / 2.0;
+
+        let relative_x = x - instance.center_x;
+        let relative_y = y - instance.center_y;
+
+        // Skip copies which can't contain this coordinate, before doing any trigonometry
+        let bounding_radius = (half_width*half_width + half_height*half_height).sqrt() * instance.scale;
+        if relative_x.abs() > bounding_radius || relative_y.abs() > bounding_radius || instance.scale <= 0.0 {
+            return None
+        }
+
+        // Inverse rotation and scale, to map onto the sprite
+        let (sin, cos) = instance.rotation.sin_cos();
+        let sprite_x = (relative_x*cos + relative_y*sin) / instance.scale + half_width;
+        let sprite_y = (-relative_x*sin + relative_y*cos) / instance.scale + half_height;
+
+        if sprite_x < 0.0 || sprite_y < 0.0 {
+            return None
+        }
+        let pixel = self.sprite.pixel_at(sprite_x as usize, sprite_y as usize)?;
+
+        Some(match instance.tint {
+            Some(tint) => tint_pixel(pixel, tint),
+            None => pixel
+        })
+    }
+}
+
+/// Multiply each channel of `pixel` by the corresponding channel of `tint`.
+fn tint_pixel<T: PixelChannel>(pixel: AlphaPixel<T>, tint: AlphaPixel<T>) -> AlphaPixel<T> {
+    let tint = tint.as_float_pixel();
+    AlphaPixel {
+        r: T::from_f32(pixel.r.into() * tint.r).unwrap(),
+        g: T::from_f32(pixel.g.into() * tint.g).unwrap(),
+        b: T::from_f32(pixel.b.into() * tint.b).unwrap(),
+        a: T::from_f32(pixel.a.into() * tint.a).unwrap()
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for ScatterLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.region
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        // Sample from the center of the pixel
+        let region_x = (x - self.region.x) as f32 + 0.5;
+        let region_y = (y - self.region.y) as f32 + 0.5;
+
+        self.instances.iter()
+            .filter_map(|instance| self.sample_instance(instance, region_x, region_y))
+            .fold(AlphaPixel::default(), |background, pixel| BlendingMethod::Over.blend(background, pixel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    const REGION: Rect = Rect { x: 5, y: 5, width: 40, height: 30 };
+
+    fn covered_pixels(layer: &ScatterLayer<u8>) -> Vec<(usize, usize)> {
+        (REGION.y..REGION.bottom_y())
+            .flat_map(|y| (REGION.x..REGION.right_x()).map(move |x| (x, y)))
+            .filter(|(x, y)| layer.filtered_pixel_at(*x, *y).unwrap().a != 0)
+            .collect()
+    }
+
+    #[test]
+    fn scatter_empty() {
+        let settings = ScatterSettings { count: 0, ..Default::default() };
+        let layer = ScatterLayer::new(Image::new_with_fill(AlphaPixel::<u8>::red(), 3, 3), REGION, &settings, &mut Rng::new(1));
+        assert!(covered_pixels(&layer).is_empty());
+        assert!(layer.filtered_pixel_at(0, 0).is_none());
+    }
+
+    #[test]
+    fn scatter_seeded() {
+        let sprite = Image::new_with_fill(AlphaPixel::<u8>::white(), 2, 4);
+        let settings = ScatterSettings { count: 10, ..Default::default() };
+
+        let first = ScatterLayer::new(sprite.clone(), REGION, &settings, &mut Rng::new(7));
+        let second = ScatterLayer::new(sprite.clone(), REGION, &settings, &mut Rng::new(7));
+        let different = ScatterLayer::new(sprite, REGION, &settings, &mut Rng::new(8));
+
+        assert!(!covered_pixels(&first).is_empty());
+        assert_eq!(covered_pixels(&first), covered_pixels(&second));
+        assert_ne!(covered_pixels(&first), covered_pixels(&different));
+    }
+
+    #[test]
+    fn scatter_tint() {
+        let sprite = Image::new_with_fill(AlphaPixel::<u8>::white(), 1, 1);
+        let tint = rgba!(255, 0, 255, 255);
+        let settings = ScatterSettings { count: 5, scale: (1.0, 1.0), rotation: (0.0, 0.0), tints: vec![tint] };
+        let layer = ScatterLayer::new(sprite, REGION, &settings, &mut Rng::new(3));
+
+        let covered = covered_pixels(&layer);
+        assert!(!covered.is_empty() && covered.len() <= 5);
+        for (x, y) in covered {
+            assert_eq!(layer.filtered_pixel_at(x, y).unwrap(), tint);
+        }
+    }
+}
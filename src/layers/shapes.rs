@@ -1,6 +1,107 @@
-use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect};
+use crate::{Filter, Layer, AlphaPixel, PixelChannel, Rect, BlendingMethod};
 
+/// The maximum number of samples taken along each axis when blurring a shadow.
+/// Larger blurs are sampled sparsely, to keep the cost per pixel bounded.
+const MAX_SHADOW_SAMPLES_PER_AXIS: usize = 9;
 
+/// A soft shadow drawn beneath a shape, such as to lift a card off the background.
+/// 
+/// The shadow is the shape's own outline, moved by `offset` and blurred by `blur` pixels. The `Rect` of a shape
+/// with a shadow is expanded to include it.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, rgba, layers::shapes::{RoundedRectangleLayer, CornerRadii, Shadow}};
+/// 
+/// let card: RoundedRectangleLayer<u8> = RoundedRectangleLayer::new(AlphaPixel::white(), Rect { x: 20, y: 20, width: 100, height: 60 }, CornerRadii::uniform(8.0))
+///     .with_shadow(Shadow::new((0, 4), 8, rgba!(0, 0, 0, 100)));
+/// let rect = card.get_rect();
+/// assert_eq!((rect.x, rect.y, rect.width, rect.height), (12, 16, 116, 76));
+/// 
+/// // The shadow is visible below the card, and fades out
+/// let near = card.filtered_pixel_at(70, 82).unwrap().a;
+/// let far = card.filtered_pixel_at(70, 88).unwrap().a;
+/// assert!(near > far && far > 0);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Shadow<T> {
+    /// How far the shadow is moved from the shape, in pixels
+    pub offset: (isize, isize),
+    /// The radius of the box blur applied to the shadow, in pixels
+    pub blur: usize,
+    pub color: AlphaPixel<T>
+}
+
+impl<T> Shadow<T> {
+    pub fn new(offset: (isize, isize), blur: usize, color: AlphaPixel<T>) -> Self {
+        Self { offset, blur, color }
+    }
+}
+
+impl<T: Copy> Shadow<T> {
+    /// Multiply the offset and blur of the shadow by `factor`, rounded to whole pixels.
+    pub fn scale(&self, factor: f32) -> Self {
+        let scale = |value: f32| (value*factor).round();
+        Self {
+            offset: (scale(self.offset.0 as f32) as isize, scale(self.offset.1 as f32) as isize),
+            blur: scale(self.blur as f32).max(0.0) as usize,
+            color: self.color
+        }
+    }
+}
+
+/// Multiply the coordinates of the vertices of a polygon by `factor`.
+fn scale_vertices(vertices: &[(f32, f32)], factor: f32) -> Vec<(f32, f32)> {
+    vertices.iter().map(|(x, y)| (x*factor, y*factor)).collect()
+}
+
+/// Get the `Rect` covering both a shape's `Rect` and its shadow, if it has one.
+fn shadow_bounds<T>(rect: Rect, shadow: Option<&Shadow<T>>) -> Rect {
+    let Some(shadow) = shadow else {
+        return rect
+    };
+
+    let blur = shadow.blur as isize;
+    let left = (rect.x as isize + shadow.offset.0 - blur).max(0) as usize;
+    let top = (rect.y as isize + shadow.offset.1 - blur).max(0) as usize;
+    let right = (rect.right_x() as isize + shadow.offset.0 + blur).max(0) as usize;
+    let bottom = (rect.bottom_y() as isize + shadow.offset.1 + blur).max(0) as usize;
+
+    let (left, top) = (left.min(rect.x), top.min(rect.y));
+    let (right, bottom) = (right.max(rect.right_x()), bottom.max(rect.bottom_y()));
+    Rect { x: left, y: top, width: right - left, height: bottom - top }
+}
+
+/// Get the pixel of a shape filled with `fill` at (`x`, `y`), over its shadow if it has one.
+/// 
+/// `coverage` gets the fraction of a pixel covered by the shape, and is called for pixels around the shape to blur its shadow.
+fn shadowed_pixel<T: PixelChannel>(fill: AlphaPixel<T>, shadow: Option<&Shadow<T>>, x: usize, y: usize, coverage: impl Fn(usize, usize) -> f32) -> AlphaPixel<T> {
+    let pixel = fill.scale_alpha(coverage(x, y));
+    let Some(shadow) = shadow else {
+        return pixel
+    };
+
+    let center_x = x as isize - shadow.offset.0;
+    let center_y = y as isize - shadow.offset.1;
+    let radius = shadow.blur as isize;
+    let step = (2*shadow.blur / (MAX_SHADOW_SAMPLES_PER_AXIS - 1)).max(1);
+
+    let mut total = 0.0;
+    let mut count = 0.0;
+    for sample_y in (center_y - radius..=center_y + radius).step_by(step) {
+        for sample_x in (center_x - radius..=center_x + radius).step_by(step) {
+            // Nothing is drawn to the left of or above the canvas
+            if let (Ok(sample_x), Ok(sample_y)) = (usize::try_from(sample_x), usize::try_from(sample_y)) {
+                total += coverage(sample_x, sample_y);
+            }
+            count += 1.0;
+        }
+    }
+
+    BlendingMethod::Over.blend(shadow.color.scale_alpha(total / count), pixel)
+}
+
+/// A filled rectangle.
 pub struct RectangleLayer<T> {
     pub filters: Vec<Box<dyn Filter<T>>>,
     pub fill: AlphaPixel<T>,
@@ -11,6 +112,11 @@ impl<T> RectangleLayer<T> {
     pub fn new(fill: AlphaPixel<T>, rect: Rect) -> Self {
         Self { filters: vec![], fill, rect }
     }
+
+    /// Draw the rectangle with a shadow, as a [`RoundedRectangleLayer`] with square corners.
+    pub fn with_shadow(self, shadow: Shadow<T>) -> RoundedRectangleLayer<T> {
+        RoundedRectangleLayer { filters: self.filters, fill: self.fill, rect: self.rect, radii: CornerRadii::default(), shadow: Some(shadow) }
+    }
 }
 
 impl<T: PixelChannel> Layer<T> for RectangleLayer<T> {
@@ -25,4 +131,964 @@ impl<T: PixelChannel> Layer<T> for RectangleLayer<T> {
     fn unfiltered_pixel_at_unchecked(&self, _x: usize, _y: usize) -> AlphaPixel<T> {
         self.fill
     }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        Some(Box::new(RectangleLayer {
+            filters: vec![],
+            fill: self.fill,
+            rect: self.rect.scale(factor)
+        }))
+    }
+}
+
+/// A filled circle.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, layers::shapes::CircleLayer};
+/// 
+/// let circle: CircleLayer<u8> = CircleLayer::new(AlphaPixel::red(), 50.0, 50.0, 20.0);
+/// assert_eq!(circle.filtered_pixel_at(50, 50).unwrap(), AlphaPixel::red());
+/// assert_eq!(circle.filtered_pixel_at(31, 31).unwrap().a, 0);
+/// ```
+pub struct CircleLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub fill: AlphaPixel<T>,
+    pub center_x: f32,
+    pub center_y: f32,
+    pub radius: f32,
+    /// If true, pixels on the edge are partially transparent by how much of them the circle covers.
+    /// Otherwise, pixels are either filled or transparent, which can look jagged.
+    pub antialias: bool,
+    pub shadow: Option<Shadow<T>>
+}
+
+impl<T> CircleLayer<T> {
+    /// Create an antialiased circle. The center is in canvas coordinates, where (0.0, 0.0) is the top left corner of the top left pixel.
+    pub fn new(fill: AlphaPixel<T>, center_x: f32, center_y: f32, radius: f32) -> Self {
+        Self { filters: vec![], fill, center_x, center_y, radius, antialias: true, shadow: None }
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow<T>) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Get the `Rect` of the circle, without its shadow.
+    fn shape_rect(&self) -> Rect {
+        let radius = self.radius.max(0.0);
+        let left = (self.center_x - radius).floor().max(0.0);
+        let top = (self.center_y - radius).floor().max(0.0);
+        let right = (self.center_x + radius).ceil().max(0.0);
+        let bottom = (self.center_y + radius).ceil().max(0.0);
+        Rect { x: left as usize, y: top as usize, width: (right - left) as usize, height: (bottom - top) as usize }
+    }
+
+    fn coverage(&self, x: usize, y: usize) -> f32 {
+        let distance = ((x as f32 + 0.5 - self.center_x).powi(2) + (y as f32 + 0.5 - self.center_y).powi(2)).sqrt();
+        if self.antialias {
+            (self.radius + 0.5 - distance).clamp(0.0, 1.0)
+        } else if distance <= self.radius {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for CircleLayer<T> {
+    fn get_rect(&self) -> Rect {
+        shadow_bounds(self.shape_rect(), self.shadow.as_ref())
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        shadowed_pixel(self.fill, self.shadow.as_ref(), x, y, |x, y| self.coverage(x, y))
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        Some(Box::new(CircleLayer {
+            filters: vec![],
+            fill: self.fill,
+            center_x: self.center_x*factor,
+            center_y: self.center_y*factor,
+            radius: self.radius*factor,
+            antialias: self.antialias,
+            shadow: self.shadow.map(|shadow| shadow.scale(factor))
+        }))
+    }
+}
+
+/// A filled ellipse, with separate horizontal and vertical radii.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, layers::shapes::EllipseLayer};
+/// 
+/// let badge: EllipseLayer<u8> = EllipseLayer::from_rect(AlphaPixel::red(), Rect { x: 10, y: 20, width: 100, height: 50 });
+/// let rect = badge.get_rect();
+/// assert_eq!((rect.x, rect.y, rect.width, rect.height), (10, 20, 100, 50));
+/// assert_eq!(badge.filtered_pixel_at(60, 45).unwrap(), AlphaPixel::red());
+/// assert_eq!(badge.filtered_pixel_at(12, 22).unwrap().a, 0);
+/// ```
+pub struct EllipseLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub fill: AlphaPixel<T>,
+    pub center_x: f32,
+    pub center_y: f32,
+    pub radius_x: f32,
+    pub radius_y: f32,
+    /// If true, pixels on the edge are partially transparent by how much of them the ellipse covers.
+    pub antialias: bool,
+    pub shadow: Option<Shadow<T>>
+}
+
+impl<T> EllipseLayer<T> {
+    /// Create an antialiased ellipse from its center and radii, in canvas coordinates.
+    pub fn new(fill: AlphaPixel<T>, center_x: f32, center_y: f32, radius_x: f32, radius_y: f32) -> Self {
+        Self { filters: vec![], fill, center_x, center_y, radius_x, radius_y, antialias: true, shadow: None }
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow<T>) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Create an antialiased ellipse which fills `rect`.
+    pub fn from_rect(fill: AlphaPixel<T>, rect: Rect) -> Self {
+        let radius_x = rect.width as f32 / 2.0;
+        let radius_y = rect.height as f32 / 2.0;
+        Self::new(fill, rect.x as f32 + radius_x, rect.y as f32 + radius_y, radius_x, radius_y)
+    }
+
+    /// Get the approximate signed distance from the center of a pixel to the edge of the ellipse,
+    /// which is negative inside the ellipse.
+    fn edge_distance(&self, x: usize, y: usize) -> f32 {
+        if self.radius_x <= 0.0 || self.radius_y <= 0.0 {
+            return f32::INFINITY
+        }
+
+        let relative_x = x as f32 + 0.5 - self.center_x;
+        let relative_y = y as f32 + 0.5 - self.center_y;
+        let implicit = (relative_x / self.radius_x).powi(2) + (relative_y / self.radius_y).powi(2) - 1.0;
+        // Divide by the gradient of the implicit function, to get a distance in pixels
+        let gradient = ((2.0*relative_x / self.radius_x.powi(2)).powi(2) + (2.0*relative_y / self.radius_y.powi(2)).powi(2)).sqrt();
+        if gradient == 0.0 {
+            return -self.radius_x.min(self.radius_y)
+        }
+        implicit / gradient
+    }
+
+    /// Get the `Rect` of the ellipse, without its shadow.
+    fn shape_rect(&self) -> Rect {
+        let (radius_x, radius_y) = (self.radius_x.max(0.0), self.radius_y.max(0.0));
+        let left = (self.center_x - radius_x).floor().max(0.0);
+        let top = (self.center_y - radius_y).floor().max(0.0);
+        let right = (self.center_x + radius_x).ceil().max(0.0);
+        let bottom = (self.center_y + radius_y).ceil().max(0.0);
+        Rect { x: left as usize, y: top as usize, width: (right - left) as usize, height: (bottom - top) as usize }
+    }
+
+    fn coverage(&self, x: usize, y: usize) -> f32 {
+        let distance = self.edge_distance(x, y);
+        if self.antialias {
+            (0.5 - distance).clamp(0.0, 1.0)
+        } else if distance <= 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for EllipseLayer<T> {
+    fn get_rect(&self) -> Rect {
+        shadow_bounds(self.shape_rect(), self.shadow.as_ref())
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        shadowed_pixel(self.fill, self.shadow.as_ref(), x, y, |x, y| self.coverage(x, y))
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        Some(Box::new(EllipseLayer {
+            filters: vec![],
+            fill: self.fill,
+            center_x: self.center_x*factor,
+            center_y: self.center_y*factor,
+            radius_x: self.radius_x*factor,
+            radius_y: self.radius_y*factor,
+            antialias: self.antialias,
+            shadow: self.shadow.map(|shadow| shadow.scale(factor))
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How the inside of a self-intersecting polygon is decided.
+pub enum FillRule {
+    /// A point is inside if a ray from it crosses the edges an odd number of times, so overlapping areas are holes.
+    #[default]
+    EvenOdd,
+    /// A point is inside if the edges wind around it at all, so overlapping areas are filled.
+    NonZero
+}
+
+/// A filled polygon, with any number of vertices.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, layers::shapes::PolygonLayer};
+/// 
+/// let triangle: PolygonLayer<u8> = PolygonLayer::new(AlphaPixel::red(), vec![(10.0, 0.0), (20.0, 20.0), (0.0, 20.0)]);
+/// let rect = triangle.get_rect();
+/// assert_eq!((rect.x, rect.y, rect.width, rect.height), (0, 0, 20, 20));
+/// assert_eq!(triangle.filtered_pixel_at(10, 15).unwrap(), AlphaPixel::red());
+/// assert_eq!(triangle.filtered_pixel_at(1, 1).unwrap().a, 0);
+/// ```
+pub struct PolygonLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub fill: AlphaPixel<T>,
+    /// The vertices in canvas coordinates, where (0.0, 0.0) is the top left corner of the top left pixel.
+    /// The last vertex is joined to the first.
+    pub vertices: Vec<(f32, f32)>,
+    pub fill_rule: FillRule,
+    /// If true, pixels on the edge are partially transparent by how much of them the polygon covers.
+    pub antialias: bool,
+    pub shadow: Option<Shadow<T>>
+}
+
+impl<T> PolygonLayer<T> {
+    /// Create an antialiased polygon, filled with the even-odd rule.
+    pub fn new(fill: AlphaPixel<T>, vertices: Vec<(f32, f32)>) -> Self {
+        Self { filters: vec![], fill, vertices, fill_rule: FillRule::EvenOdd, antialias: true, shadow: None }
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow<T>) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for PolygonLayer<T> {
+    fn get_rect(&self) -> Rect {
+        shadow_bounds(polygon_bounds(&self.vertices), self.shadow.as_ref())
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        shadowed_pixel(self.fill, self.shadow.as_ref(), x, y, |x, y| polygon_pixel_coverage(&self.vertices, self.fill_rule, self.antialias, x, y))
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        Some(Box::new(PolygonLayer {
+            filters: vec![],
+            fill: self.fill,
+            vertices: scale_vertices(&self.vertices, factor),
+            fill_rule: self.fill_rule,
+            antialias: self.antialias,
+            shadow: self.shadow.map(|shadow| shadow.scale(factor))
+        }))
+    }
+}
+
+/// A regular polygon with `sides` equal sides, such as a triangle, hexagon or octagon.
+/// 
+/// With a `rotation` of 0.0, the first vertex points straight up. `rotation` is in degrees clockwise.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, layers::shapes::RegularPolygonLayer};
+/// 
+/// let hexagon: RegularPolygonLayer<u8> = RegularPolygonLayer::new(AlphaPixel::blue(), 50.0, 50.0, 20.0, 6, 0.0);
+/// assert_eq!(hexagon.get_vertices().len(), 6);
+/// assert_eq!(hexagon.filtered_pixel_at(50, 50).unwrap(), AlphaPixel::blue());
+/// ```
+pub struct RegularPolygonLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub fill: AlphaPixel<T>,
+    /// If true, pixels on the edge are partially transparent by how much of them the polygon covers.
+    pub antialias: bool,
+    pub shadow: Option<Shadow<T>>,
+    vertices: Vec<(f32, f32)>
+}
+
+impl<T> RegularPolygonLayer<T> {
+    /// Create an antialiased regular polygon, with its vertices `radius` away from the center.
+    /// There are no vertices if `sides` is less than 3.
+    pub fn new(fill: AlphaPixel<T>, center_x: f32, center_y: f32, radius: f32, sides: usize, rotation: f32) -> Self {
+        Self { filters: vec![], fill, antialias: true, shadow: None, vertices: regular_polygon_vertices(center_x, center_y, radius, sides, rotation) }
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow<T>) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Get the vertices in canvas coordinates, clockwise from the first vertex.
+    pub fn get_vertices(&self) -> &[(f32, f32)] {
+        &self.vertices
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for RegularPolygonLayer<T> {
+    fn get_rect(&self) -> Rect {
+        shadow_bounds(polygon_bounds(&self.vertices), self.shadow.as_ref())
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        shadowed_pixel(self.fill, self.shadow.as_ref(), x, y, |x, y| polygon_pixel_coverage(&self.vertices, FillRule::EvenOdd, self.antialias, x, y))
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        Some(Box::new(RegularPolygonLayer {
+            filters: vec![],
+            fill: self.fill,
+            antialias: self.antialias,
+            shadow: self.shadow.map(|shadow| shadow.scale(factor)),
+            vertices: scale_vertices(&self.vertices, factor)
+        }))
+    }
+}
+
+/// A star with `points` points, which are `outer_radius` from the center, and the inner corners between them `inner_radius` from the center.
+/// 
+/// With a `rotation` of 0.0, the first point points straight up. `rotation` is in degrees clockwise.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Layer, layers::shapes::StarLayer, rgba};
+/// 
+/// // A rating star
+/// let star: StarLayer<u8> = StarLayer::new(rgba!(255, 200, 0, 255), 50.0, 50.0, 20.0, 8.0, 5, 0.0);
+/// assert_eq!(star.filtered_pixel_at(50, 35).unwrap(), rgba!(255, 200, 0, 255));
+/// // Between the top two points
+/// assert_eq!(star.filtered_pixel_at(60, 36).unwrap().a, 0);
+/// ```
+pub struct StarLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub fill: AlphaPixel<T>,
+    /// If true, pixels on the edge are partially transparent by how much of them the star covers.
+    pub antialias: bool,
+    pub shadow: Option<Shadow<T>>,
+    vertices: Vec<(f32, f32)>
+}
+
+impl<T> StarLayer<T> {
+    /// Create an antialiased star. There are no vertices if `points` is less than 2.
+    pub fn new(fill: AlphaPixel<T>, center_x: f32, center_y: f32, outer_radius: f32, inner_radius: f32, points: usize, rotation: f32) -> Self {
+        Self { filters: vec![], fill, antialias: true, shadow: None, vertices: star_vertices(center_x, center_y, outer_radius, inner_radius, points, rotation) }
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow<T>) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Get the vertices in canvas coordinates, clockwise from the first point, alternating between points and inner corners.
+    pub fn get_vertices(&self) -> &[(f32, f32)] {
+        &self.vertices
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for StarLayer<T> {
+    fn get_rect(&self) -> Rect {
+        shadow_bounds(polygon_bounds(&self.vertices), self.shadow.as_ref())
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        shadowed_pixel(self.fill, self.shadow.as_ref(), x, y, |x, y| polygon_pixel_coverage(&self.vertices, FillRule::NonZero, self.antialias, x, y))
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        Some(Box::new(StarLayer {
+            filters: vec![],
+            fill: self.fill,
+            antialias: self.antialias,
+            shadow: self.shadow.map(|shadow| shadow.scale(factor)),
+            vertices: scale_vertices(&self.vertices, factor)
+        }))
+    }
+}
+
+/// Get the vertices of a regular polygon, clockwise from the vertex above the center, rotated clockwise by `rotation` degrees.
+pub fn regular_polygon_vertices(center_x: f32, center_y: f32, radius: f32, sides: usize, rotation: f32) -> Vec<(f32, f32)> {
+    if sides < 3 {
+        return vec![]
+    }
+    (0..sides)
+        .map(|i| point_at_angle(center_x, center_y, radius, rotation + i as f32 * 360.0 / sides as f32))
+        .collect()
+}
+
+/// Get the vertices of a star, clockwise from the point above the center, rotated clockwise by `rotation` degrees.
+/// Points are `outer_radius` from the center, and alternate with inner corners `inner_radius` from the center.
+pub fn star_vertices(center_x: f32, center_y: f32, outer_radius: f32, inner_radius: f32, points: usize, rotation: f32) -> Vec<(f32, f32)> {
+    if points < 2 {
+        return vec![]
+    }
+    (0..points*2)
+        .map(|i| {
+            let radius = if i % 2 == 0 { outer_radius } else { inner_radius };
+            point_at_angle(center_x, center_y, radius, rotation + i as f32 * 180.0 / points as f32)
+        })
+        .collect()
+}
+
+/// The point `radius` from the center, at `angle` degrees clockwise from straight up.
+fn point_at_angle(center_x: f32, center_y: f32, radius: f32, angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    (center_x + radius*sin, center_y - radius*cos)
+}
+
+/// Get the smallest `Rect` of whole pixels containing every vertex, cut off at the top and left of the canvas.
+pub(crate) fn polygon_bounds(vertices: &[(f32, f32)]) -> Rect {
+    if vertices.is_empty() {
+        return Rect::default()
+    }
+
+    let (mut left, mut top) = (f32::INFINITY, f32::INFINITY);
+    let (mut right, mut bottom) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for (x, y) in vertices {
+        left = left.min(*x);
+        top = top.min(*y);
+        right = right.max(*x);
+        bottom = bottom.max(*y);
+    }
+
+    let (left, top) = (left.floor().max(0.0), top.floor().max(0.0));
+    let (right, bottom) = (right.ceil().max(left), bottom.ceil().max(top));
+    Rect { x: left as usize, y: top as usize, width: (right - left) as usize, height: (bottom - top) as usize }
+}
+
+/// Get the coverage of a pixel by a polygon, either antialiased or sampled at the pixel's center.
+fn polygon_pixel_coverage(vertices: &[(f32, f32)], fill_rule: FillRule, antialias: bool, x: usize, y: usize) -> f32 {
+    if antialias {
+        polygon_coverage_with_rule(vertices, fill_rule, x, y)
+    } else if polygon_contains_with_rule(vertices, fill_rule, x as f32 + 0.5, y as f32 + 0.5) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+/// The radius of each corner of a rounded rectangle, in pixels.
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32
+}
+
+impl CornerRadii {
+    /// The same radius for every corner.
+    pub fn uniform(radius: f32) -> Self {
+        Self { top_left: radius, top_right: radius, bottom_right: radius, bottom_left: radius }
+    }
+}
+
+/// A filled rectangle with rounded, antialiased corners. Each corner can have a different radius.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, Rect, layers::shapes::{RoundedRectangleLayer, CornerRadii}};
+/// 
+/// let card: RoundedRectangleLayer<u8> = RoundedRectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 200, height: 100 }, CornerRadii::uniform(16.0));
+/// assert_eq!(card.filtered_pixel_at(100, 50).unwrap(), AlphaPixel::white());
+/// assert_eq!(card.filtered_pixel_at(0, 0).unwrap().a, 0);
+/// ```
+pub struct RoundedRectangleLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub fill: AlphaPixel<T>,
+    pub rect: Rect,
+    /// Radii larger than half of the width or height are reduced to fit
+    pub radii: CornerRadii,
+    pub shadow: Option<Shadow<T>>
+}
+
+impl<T> RoundedRectangleLayer<T> {
+    pub fn new(fill: AlphaPixel<T>, rect: Rect, radii: CornerRadii) -> Self {
+        Self { filters: vec![], fill, rect, radii, shadow: None }
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow<T>) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for RoundedRectangleLayer<T> {
+    fn get_rect(&self) -> Rect {
+        shadow_bounds(self.rect, self.shadow.as_ref())
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let rect = (self.rect.x as f32, self.rect.y as f32, self.rect.width as f32, self.rect.height as f32);
+        shadowed_pixel(self.fill, self.shadow.as_ref(), x, y, |x, y| rounded_rect_coverage_per_corner(rect, self.radii, x, y))
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        let radii = CornerRadii {
+            top_left: self.radii.top_left*factor,
+            top_right: self.radii.top_right*factor,
+            bottom_right: self.radii.bottom_right*factor,
+            bottom_left: self.radii.bottom_left*factor
+        };
+        Some(Box::new(RoundedRectangleLayer {
+            filters: vec![],
+            fill: self.fill,
+            rect: self.rect.scale(factor),
+            radii,
+            shadow: self.shadow.map(|shadow| shadow.scale(factor))
+        }))
+    }
+}
+
+/// A segment of a ring, for pie charts, donut charts and progress rings.
+/// 
+/// Angles are in degrees clockwise, where 0.0 points right. The arc starts at `start_angle` and covers `sweep_angle`,
+/// which is anticlockwise if negative. A sweep of 360.0 or more is a full ring, and an `inner_radius` of 0.0 is a pie slice.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, Layer, layers::shapes::ArcLayer};
+/// 
+/// // The top right quarter of a donut
+/// let quarter: ArcLayer<u8> = ArcLayer::new(AlphaPixel::red(), 50.0, 50.0, 20.0, 40.0, -90.0, 90.0);
+/// assert_eq!(quarter.filtered_pixel_at(70, 30).unwrap(), AlphaPixel::red());
+/// // In the hole
+/// assert_eq!(quarter.filtered_pixel_at(55, 45).unwrap().a, 0);
+/// // In the top left quarter
+/// assert_eq!(quarter.filtered_pixel_at(30, 30).unwrap().a, 0);
+/// ```
+pub struct ArcLayer<T> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    pub fill: AlphaPixel<T>,
+    pub center_x: f32,
+    pub center_y: f32,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub start_angle: f32,
+    pub sweep_angle: f32,
+    /// If true, pixels on the edge are partially transparent by how much of them the arc covers.
+    pub antialias: bool,
+    pub shadow: Option<Shadow<T>>
+}
+
+impl<T> ArcLayer<T> {
+    /// Create an antialiased arc. The center is in canvas coordinates, where (0.0, 0.0) is the top left corner of the top left pixel.
+    pub fn new(fill: AlphaPixel<T>, center_x: f32, center_y: f32, inner_radius: f32, outer_radius: f32, start_angle: f32, sweep_angle: f32) -> Self {
+        Self { filters: vec![], fill, center_x, center_y, inner_radius, outer_radius, start_angle, sweep_angle, antialias: true, shadow: None }
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow<T>) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    fn contains(&self, x: f32, y: f32) -> bool {
+        let (relative_x, relative_y) = (x - self.center_x, y - self.center_y);
+        let distance = (relative_x*relative_x + relative_y*relative_y).sqrt();
+        if distance < self.inner_radius || distance > self.outer_radius {
+            return false
+        }
+        if self.sweep_angle.abs() >= 360.0 {
+            return true
+        }
+
+        let angle = relative_y.atan2(relative_x).to_degrees();
+        if self.sweep_angle >= 0.0 {
+            (angle - self.start_angle).rem_euclid(360.0) <= self.sweep_angle
+        } else {
+            (self.start_angle - angle).rem_euclid(360.0) <= -self.sweep_angle
+        }
+    }
+
+    /// Get the `Rect` of the arc, without its shadow.
+    fn shape_rect(&self) -> Rect {
+        let radius = self.outer_radius.max(0.0);
+        let left = (self.center_x - radius).floor().max(0.0);
+        let top = (self.center_y - radius).floor().max(0.0);
+        let right = (self.center_x + radius).ceil().max(left);
+        let bottom = (self.center_y + radius).ceil().max(top);
+        Rect { x: left as usize, y: top as usize, width: (right - left) as usize, height: (bottom - top) as usize }
+    }
+
+    fn coverage(&self, x: usize, y: usize) -> f32 {
+        if !self.antialias {
+            return if self.contains(x as f32 + 0.5, y as f32 + 0.5) { 1.0 } else { 0.0 }
+        }
+
+        const SAMPLES: usize = 4;
+        let covered = (0..SAMPLES*SAMPLES)
+            .filter(|i| {
+                let sample_x = x as f32 + ((i % SAMPLES) as f32 + 0.5) / SAMPLES as f32;
+                let sample_y = y as f32 + ((i / SAMPLES) as f32 + 0.5) / SAMPLES as f32;
+                self.contains(sample_x, sample_y)
+            })
+            .count();
+        covered as f32 / (SAMPLES*SAMPLES) as f32
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for ArcLayer<T> {
+    fn get_rect(&self) -> Rect {
+        shadow_bounds(self.shape_rect(), self.shadow.as_ref())
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        shadowed_pixel(self.fill, self.shadow.as_ref(), x, y, |x, y| self.coverage(x, y))
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        Some(Box::new(ArcLayer {
+            filters: vec![],
+            fill: self.fill,
+            center_x: self.center_x*factor,
+            center_y: self.center_y*factor,
+            inner_radius: self.inner_radius*factor,
+            outer_radius: self.outer_radius*factor,
+            antialias: self.antialias,
+            shadow: self.shadow.map(|shadow| shadow.scale(factor)),
+            ..*self
+        }))
+    }
+}
+
+/// Get the fraction of the pixel at (`x`, `y`) covered by a rectangle with rounded corners, for antialiasing.
+/// 
+/// The rectangle is defined by its top left corner, width and height as floats, and the radius of the corners.
+pub(crate) fn rounded_rect_coverage(rect: (f32, f32, f32, f32), radius: f32, x: usize, y: usize) -> f32 {
+    rounded_rect_coverage_per_corner(rect, CornerRadii::uniform(radius), x, y)
+}
+
+/// The same as [`rounded_rect_coverage`], with a different radius for each corner.
+pub(crate) fn rounded_rect_coverage_per_corner(rect: (f32, f32, f32, f32), radii: CornerRadii, x: usize, y: usize) -> f32 {
+    let (left, top, width, height) = rect;
+    let half_width = width / 2.0;
+    let half_height = height / 2.0;
+    let center_offset_x = x as f32 + 0.5 - (left + half_width);
+    let center_offset_y = y as f32 + 0.5 - (top + half_height);
+
+    // Only the corner in the same quadrant as the pixel affects it
+    let radius = match (center_offset_x < 0.0, center_offset_y < 0.0) {
+        (true, true) => radii.top_left,
+        (false, true) => radii.top_right,
+        (false, false) => radii.bottom_right,
+        (true, false) => radii.bottom_left
+    };
+    let radius = radius.min(half_width).min(half_height).max(0.0);
+
+    // Signed distance from the pixel center to the edge of the rounded rectangle
+    let relative_x = center_offset_x.abs() - (half_width - radius);
+    let relative_y = center_offset_y.abs() - (half_height - radius);
+    let outside = (relative_x.max(0.0).powi(2) + relative_y.max(0.0).powi(2)).sqrt();
+    let inside = relative_x.max(relative_y).min(0.0);
+    let distance = outside + inside - radius;
+
+    (0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// Get the fraction of the pixel at (`x`, `y`) covered by a line segment from `start` to `end` with a width of `width`.
+/// 
+/// The ends of the line are rounded.
+pub(crate) fn line_coverage(start: (f32, f32), end: (f32, f32), width: f32, x: usize, y: usize) -> f32 {
+    let (point_x, point_y) = (x as f32 + 0.5, y as f32 + 0.5);
+    let (line_x, line_y) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = line_x*line_x + line_y*line_y;
+
+    // Project the pixel onto the line, clamped to the ends of the line
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point_x - start.0)*line_x + (point_y - start.1)*line_y) / length_squared).clamp(0.0, 1.0)
+    };
+    let closest = (start.0 + t*line_x, start.1 + t*line_y);
+    let distance = ((point_x - closest.0).powi(2) + (point_y - closest.1).powi(2)).sqrt();
+
+    (width / 2.0 + 0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// Get the position of the pixel at (`x`, `y`) along a linear gradient across `rect`, from 0.0 to 1.0.
+/// 
+/// The gradient goes through the center of the rect in the direction of `angle`, in degrees clockwise from left to right,
+/// and is long enough to reach the corners of the rect.
+pub(crate) fn linear_gradient_position(rect: Rect, angle: f32, x: usize, y: usize) -> f32 {
+    let (sin, cos) = angle.to_radians().sin_cos();
+
+    let relative_x = x as f32 + 0.5 - (rect.x as f32 + rect.width as f32 / 2.0);
+    let relative_y = y as f32 + 0.5 - (rect.y as f32 + rect.height as f32 / 2.0);
+    let half_length = (rect.width as f32 * cos.abs() + rect.height as f32 * sin.abs()) / 2.0;
+    if half_length == 0.0 {
+        return 0.5
+    }
+
+    ((relative_x*cos + relative_y*sin) / half_length / 2.0 + 0.5).clamp(0.0, 1.0)
+}
+
+/// Check whether a point is inside a polygon, using the even-odd rule.
+pub(crate) fn polygon_contains(vertices: &[(f32, f32)], x: f32, y: f32) -> bool {
+    let mut inside = false;
+    let mut previous = match vertices.last() {
+        Some(vertex) => *vertex,
+        None => return false
+    };
+
+    for vertex in vertices {
+        if (vertex.1 > y) != (previous.1 > y) {
+            let intersection_x = vertex.0 + (y - vertex.1) / (previous.1 - vertex.1) * (previous.0 - vertex.0);
+            if x < intersection_x {
+                inside = !inside;
+            }
+        }
+        previous = *vertex;
+    }
+
+    inside
+}
+
+/// Get the winding number of a polygon around a point: the number of times that its edges go anticlockwise around
+/// the point, minus the number of times that they go clockwise.
+pub(crate) fn winding_number(vertices: &[(f32, f32)], x: f32, y: f32) -> i32 {
+    let mut winding = 0;
+    let mut previous = match vertices.last() {
+        Some(vertex) => *vertex,
+        None => return 0
+    };
+
+    for vertex in vertices {
+        if (vertex.1 > y) != (previous.1 > y) {
+            let intersection_x = vertex.0 + (y - vertex.1) / (previous.1 - vertex.1) * (previous.0 - vertex.0);
+            if x < intersection_x {
+                winding += if vertex.1 > previous.1 { 1 } else { -1 };
+            }
+        }
+        previous = *vertex;
+    }
+
+    winding
+}
+
+/// Check whether a point is inside a polygon, using `fill_rule`.
+pub(crate) fn polygon_contains_with_rule(vertices: &[(f32, f32)], fill_rule: FillRule, x: f32, y: f32) -> bool {
+    match fill_rule {
+        FillRule::EvenOdd => polygon_contains(vertices, x, y),
+        FillRule::NonZero => winding_number(vertices, x, y) != 0
+    }
+}
+
+/// Get the fraction of the pixel at (`x`, `y`) covered by a polygon, by sampling a 4x4 grid within the pixel.
+pub(crate) fn polygon_coverage(vertices: &[(f32, f32)], x: usize, y: usize) -> f32 {
+    polygon_coverage_with_rule(vertices, FillRule::EvenOdd, x, y)
+}
+
+/// The same as [`polygon_coverage`], using `fill_rule`.
+pub(crate) fn polygon_coverage_with_rule(vertices: &[(f32, f32)], fill_rule: FillRule, x: usize, y: usize) -> f32 {
+    const SAMPLES: usize = 4;
+    let covered = (0..SAMPLES*SAMPLES)
+        .filter(|i| {
+            let sample_x = x as f32 + ((i % SAMPLES) as f32 + 0.5) / SAMPLES as f32;
+            let sample_y = y as f32 + ((i / SAMPLES) as f32 + 0.5) / SAMPLES as f32;
+            polygon_contains_with_rule(vertices, fill_rule, sample_x, sample_y)
+        })
+        .count();
+
+    covered as f32 / (SAMPLES*SAMPLES) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle() {
+        let mut circle: CircleLayer<u8> = CircleLayer::new(AlphaPixel::red(), 10.0, 10.0, 4.5);
+        let rect = circle.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (5, 5, 10, 10));
+
+        let edge = circle.filtered_pixel_at(14, 10).unwrap().a;
+        assert!(edge > 0 && edge < 255);
+
+        circle.antialias = false;
+        assert_eq!(circle.filtered_pixel_at(13, 10).unwrap().a, 255);
+        assert_eq!(circle.filtered_pixel_at(14, 10).unwrap().a, 0);
+    }
+
+    #[test]
+    fn ellipse() {
+        let mut ellipse: EllipseLayer<u8> = EllipseLayer::new(AlphaPixel::red(), 20.0, 10.0, 10.0, 4.0);
+        let rect = ellipse.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (10, 6, 20, 8));
+
+        // Wider than it is tall
+        assert_eq!(ellipse.filtered_pixel_at(28, 9).unwrap().a, 255);
+        assert_eq!(ellipse.filtered_pixel_at(10, 6).unwrap().a, 0);
+        let edge = ellipse.filtered_pixel_at(29, 9).unwrap().a;
+        assert!(edge > 0 && edge < 255);
+
+        ellipse.antialias = false;
+        assert!([0, 255].contains(&ellipse.filtered_pixel_at(29, 9).unwrap().a));
+
+        let empty: EllipseLayer<u8> = EllipseLayer::new(AlphaPixel::red(), 5.0, 5.0, 0.0, 3.0);
+        assert_eq!(empty.get_rect().width, 0);
+    }
+
+    #[test]
+    fn rounded_rect_corners() {
+        let rect = (0.0, 0.0, 20.0, 10.0);
+        assert_eq!(rounded_rect_coverage(rect, 4.0, 10, 5), 1.0);
+        assert_eq!(rounded_rect_coverage(rect, 4.0, 0, 5), 1.0);
+        assert_eq!(rounded_rect_coverage(rect, 4.0, 0, 0), 0.0);
+        assert_eq!(rounded_rect_coverage(rect, 0.0, 0, 0), 1.0);
+        assert_eq!(rounded_rect_coverage(rect, 4.0, 20, 5), 0.0);
+
+        let corner_edge = rounded_rect_coverage(rect, 4.0, 1, 1);
+        assert!(corner_edge > 0.0 && corner_edge < 1.0);
+    }
+
+    #[test]
+    fn per_corner_radii() {
+        let radii = CornerRadii { top_left: 0.0, top_right: 5.0, bottom_right: 0.0, bottom_left: 5.0 };
+        let layer: RoundedRectangleLayer<u8> = RoundedRectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 20, height: 20 }, radii);
+
+        assert_eq!(layer.filtered_pixel_at(0, 0).unwrap().a, 255);
+        assert_eq!(layer.filtered_pixel_at(19, 19).unwrap().a, 255);
+        assert_eq!(layer.filtered_pixel_at(19, 0).unwrap().a, 0);
+        assert_eq!(layer.filtered_pixel_at(0, 19).unwrap().a, 0);
+
+        let edge = layer.filtered_pixel_at(18, 1).unwrap().a;
+        assert!(edge > 0 && edge < 255);
+    }
+
+    #[test]
+    fn line() {
+        assert_eq!(line_coverage((0.0, 5.5), (10.0, 5.5), 2.0, 5, 5), 1.0);
+        assert_eq!(line_coverage((0.0, 5.5), (10.0, 5.5), 2.0, 5, 8), 0.0);
+        assert_eq!(line_coverage((0.0, 5.5), (10.0, 5.5), 2.0, 14, 5), 0.0);
+        assert_eq!(line_coverage((0.0, 0.0), (10.0, 10.0), 1.0, 5, 5), 1.0);
+    }
+
+    #[test]
+    fn polygon_triangle() {
+        let triangle = [(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)];
+        assert!(polygon_contains(&triangle, 2.0, 2.0));
+        assert!(!polygon_contains(&triangle, 8.0, 8.0));
+        assert_eq!(polygon_coverage(&triangle, 1, 1), 1.0);
+        assert_eq!(polygon_coverage(&triangle, 8, 8), 0.0);
+
+        let diagonal = polygon_coverage(&triangle, 4, 5);
+        assert!(diagonal > 0.0 && diagonal < 1.0);
+        assert!(!polygon_contains(&[], 0.0, 0.0));
+    }
+
+    #[test]
+    fn polygon_fill_rules() {
+        // A pentagram, whose center is enclosed twice
+        let star: Vec<(f32, f32)> = (0..5)
+            .map(|i| {
+                let angle = (i as f32 * 144.0 - 90.0).to_radians();
+                (20.0 + 20.0*angle.cos(), 20.0 + 20.0*angle.sin())
+            })
+            .collect();
+        let mut layer: PolygonLayer<u8> = PolygonLayer::new(AlphaPixel::red(), star);
+        layer.antialias = false;
+
+        assert_eq!(layer.filtered_pixel_at(20, 20).unwrap().a, 0);
+        assert_eq!(layer.filtered_pixel_at(20, 3).unwrap().a, 255);
+
+        layer.fill_rule = FillRule::NonZero;
+        assert_eq!(layer.filtered_pixel_at(20, 20).unwrap().a, 255);
+        assert_eq!(layer.filtered_pixel_at(20, 3).unwrap().a, 255);
+        assert_eq!(layer.filtered_pixel_at(1, 1).unwrap().a, 0);
+    }
+
+    #[test]
+    fn arc() {
+        let mut ring: ArcLayer<u8> = ArcLayer::new(AlphaPixel::red(), 20.0, 20.0, 5.0, 10.0, 0.0, 360.0);
+        let rect = ring.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (10, 10, 20, 20));
+        assert_eq!(ring.filtered_pixel_at(20, 20).unwrap().a, 0);
+        assert_eq!(ring.filtered_pixel_at(27, 20).unwrap().a, 255);
+        assert_eq!(ring.filtered_pixel_at(12, 20).unwrap().a, 255);
+
+        // Clockwise from the right to the bottom
+        ring.sweep_angle = 90.0;
+        assert_eq!(ring.filtered_pixel_at(24, 26).unwrap().a, 255);
+        assert_eq!(ring.filtered_pixel_at(24, 13).unwrap().a, 0);
+
+        // Anticlockwise from the right to the top
+        ring.sweep_angle = -90.0;
+        ring.antialias = false;
+        assert_eq!(ring.filtered_pixel_at(24, 26).unwrap().a, 0);
+        assert_eq!(ring.filtered_pixel_at(24, 13).unwrap().a, 255);
+    }
+
+    #[test]
+    fn regular_polygon_and_star() {
+        let square: RegularPolygonLayer<u8> = RegularPolygonLayer::new(AlphaPixel::red(), 10.0, 10.0, 5.0, 4, 45.0);
+        let rect = square.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (6, 6, 8, 8));
+        assert_eq!(square.filtered_pixel_at(7, 7).unwrap().a, 255);
+
+        let triangle: RegularPolygonLayer<u8> = RegularPolygonLayer::new(AlphaPixel::red(), 10.0, 10.0, 5.0, 3, 0.0);
+        let (top_x, top_y) = triangle.get_vertices()[0];
+        assert!((top_x - 10.0).abs() < 1e-4 && (top_y - 5.0).abs() < 1e-4);
+
+        let star: StarLayer<u8> = StarLayer::new(AlphaPixel::red(), 10.0, 10.0, 8.0, 3.0, 5, 0.0);
+        assert_eq!(star.get_vertices().len(), 10);
+        assert_eq!(star.filtered_pixel_at(10, 10).unwrap().a, 255);
+
+        let line: RegularPolygonLayer<u8> = RegularPolygonLayer::new(AlphaPixel::red(), 10.0, 10.0, 5.0, 2, 0.0);
+        assert_eq!(line.get_rect().width, 0);
+    }
+
+    #[test]
+    fn shadow() {
+        let rect = Rect { x: 10, y: 10, width: 10, height: 10 };
+        let plain: RectangleLayer<u8> = RectangleLayer::new(AlphaPixel::white(), rect);
+        let hard = RectangleLayer::new(AlphaPixel::white(), rect).with_shadow(Shadow::new((5, 5), 0, AlphaPixel::black()));
+        let bounds = hard.get_rect();
+        assert_eq!((bounds.x, bounds.y, bounds.width, bounds.height), (10, 10, 15, 15));
+
+        // The shape is drawn over its shadow, which shows past it
+        assert_eq!(hard.filtered_pixel_at(12, 12).unwrap(), plain.filtered_pixel_at(12, 12).unwrap());
+        assert_eq!(hard.filtered_pixel_at(22, 22).unwrap(), AlphaPixel::black());
+        assert_eq!(hard.filtered_pixel_at(12, 22).unwrap().a, 0);
+
+        // A blurred shadow fades towards its edges, and can't extend past the top left of the canvas
+        let circle: CircleLayer<u8> = CircleLayer::new(AlphaPixel::red(), 5.0, 5.0, 4.0).with_shadow(Shadow::new((-2, 2), 4, AlphaPixel::black()));
+        let bounds = circle.get_rect();
+        assert_eq!((bounds.x, bounds.y, bounds.width, bounds.height), (0, 0, 11, 15));
+        let near = circle.filtered_pixel_at(4, 10).unwrap().a;
+        let far = circle.filtered_pixel_at(4, 13).unwrap().a;
+        assert!(near > far && far > 0);
+        assert_eq!(circle.filtered_pixel_at(4, 5).unwrap(), AlphaPixel::red());
+    }
 }
@@ -0,0 +1,145 @@
+use crate::{
+    Filter,
+    Layer,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    BlendingMethod,
+    Error,
+    layers::{
+        shapes::{rounded_rect_coverage, polygon_coverage},
+        text::{TextLayer, TextSettings}
+    }
+};
+
+/// The appearance of a [`SpeechBubbleLayer`].
+#[derive(Clone, Copy)]
+pub struct SpeechBubbleStyle<T> {
+    pub fill: AlphaPixel<T>,
+    pub corner_radius: f32,
+    /// Space between the text and the edge of the bubble, in pixels
+    pub padding: usize,
+    /// The maximum width of the text before it is wrapped onto a new line, in pixels
+    pub max_text_width: Option<f32>,
+    /// Horizontal position of the center of the tail's base, from 0.0 (left of the bubble) to 1.0 (right of the bubble)
+    pub tail_position: f32,
+    /// Width of the tail's base, in pixels
+    pub tail_width: f32,
+    /// Offset of the tip of the tail from the center of its base, in pixels. The base is on the bottom edge of the bubble.
+    pub tail_tip: (f32, f32)
+}
+
+impl<T: PixelChannel> Default for SpeechBubbleStyle<T> {
+    fn default() -> Self {
+        Self {
+            fill: AlphaPixel::white(),
+            corner_radius: 15.0,
+            padding: 15,
+            max_text_width: None,
+            tail_position: 0.25,
+            tail_width: 20.0,
+            tail_tip: (-10.0, 25.0)
+        }
+    }
+}
+
+/// A speech bubble for comic or meme style templates.
+/// 
+/// The bubble is a rounded rectangle with a triangular tail, which is sized to fit the wrapped text inside it.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use image_template::{Canvas, AlphaPixel, layers::speech_bubble::{SpeechBubbleLayer, SpeechBubbleStyle}};
+/// use image_template::layers::text::{TextSettings, layout::TextLayout};
+/// 
+/// let font_bytes = std::fs::read("font.ttf").unwrap();
+/// let font = fontdue::Font::from_bytes(font_bytes, fontdue::FontSettings::default()).unwrap();
+/// 
+/// let settings = TextSettings {
+///     size: 30.0,
+///     fill: AlphaPixel::black(),
+///     layout: TextLayout::default(),
+///     text: String::from("Is this a speech bubble?"),
+///     font
+/// };
+/// let style = SpeechBubbleStyle { max_text_width: Some(200.0), ..Default::default() };
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(400, 300);
+/// canvas.add_layer(SpeechBubbleLayer::try_new(settings, 50, 50, style).unwrap());
+/// ```
+pub struct SpeechBubbleLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    text: TextLayer<T>,
+    style: SpeechBubbleStyle<T>,
+    body: Rect,
+    tail: [(f32, f32); 3],
+    rect: Rect
+}
+
+impl<T: PixelChannel> SpeechBubbleLayer<T> {
+    /// Create a speech bubble with its top left corner at (`x`, `y`).
+    /// 
+    /// The tail may extend outside of the top left corner, but it is cut off at the edges of the canvas.
+    pub fn try_new(mut settings: TextSettings<T>, x: usize, y: usize, style: SpeechBubbleStyle<T>) -> Result<Self, Error> {
+        if let Some(max_width) = style.max_text_width {
+            settings.wrap_text(max_width);
+        }
+
+        let text = TextLayer::try_new(settings, x + style.padding, y + style.padding)?;
+        let text_rect = text.get_rect();
+        let body = Rect { x, y, width: text_rect.width + 2*style.padding, height: text_rect.height + 2*style.padding };
+
+        let base_center_x = body.x as f32 + body.width as f32 * style.tail_position;
+        // Overlap the body slightly, so there is no seam between the tail and the body
+        let base_y = body.bottom_y() as f32 - 1.0;
+        let tail = [
+            (base_center_x - style.tail_width / 2.0, base_y),
+            (base_center_x + style.tail_width / 2.0, base_y),
+            (base_center_x + style.tail_tip.0, base_y + style.tail_tip.1)
+        ];
+
+        let min_x = tail.iter().map(|p| p.0).fold(body.x as f32, f32::min).max(0.0);
+        let min_y = tail.iter().map(|p| p.1).fold(body.y as f32, f32::min).max(0.0);
+        let max_x = tail.iter().map(|p| p.0).fold(body.right_x() as f32, f32::max).ceil();
+        let max_y = tail.iter().map(|p| p.1).fold(body.bottom_y() as f32, f32::max).ceil();
+        let rect = Rect {
+            x: min_x as usize,
+            y: min_y as usize,
+            width: (max_x - min_x.floor()) as usize,
+            height: (max_y - min_y.floor()) as usize
+        };
+
+        Ok(Self { filters: vec![], text, style, body, tail, rect })
+    }
+
+    pub fn get_text_settings(&self) -> &TextSettings<T> {
+        self.text.get_settings()
+    }
+
+    /// Get the `Rect` of the bubble, without the tail.
+    pub fn get_body_rect(&self) -> Rect {
+        self.body
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for SpeechBubbleLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let body = (self.body.x as f32, self.body.y as f32, self.body.width as f32, self.body.height as f32);
+        let coverage = rounded_rect_coverage(body, self.style.corner_radius, x, y)
+            .max(polygon_coverage(&self.tail, x, y));
+        let bubble = self.style.fill.scale_alpha(coverage);
+
+        match self.text.unfiltered_pixel_at(x, y) {
+            Some(text_pixel) => BlendingMethod::Over.blend(bubble, text_pixel),
+            None => bubble
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::{cell::OnceCell, ops::{Bound, RangeBounds}};
+use crate::{Canvas, Filter, Layer, Image, AlphaPixel, PixelChannel, Rect};
+
+/// A group of layers which don't change, flattened once and drawn from the cached image after that.
+/// 
+/// This is useful when rendering many images from one template, such as personalised cards, where only a few layers
+/// change between renders. The layers are flattened the first time that the group is drawn, and the cache is kept
+/// until the layers are changed with [`StaticGroupLayer::layers_mut`] or [`StaticGroupLayer::add_layer`].
+/// 
+/// The layers are flattened onto a transparent canvas, so layers in the group which
+/// [use the backdrop](Layer::uses_backdrop) only see the layers in the group beneath them.
+/// Layers of a canvas can be moved into a group with [`Canvas::make_static`].
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, AlphaPixel, Rect, layers::{shapes::{RectangleLayer, CircleLayer}, static_group::StaticGroupLayer}};
+/// 
+/// let mut background = StaticGroupLayer::new(200, 100);
+/// background.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 200, height: 100 }));
+/// background.add_layer(CircleLayer::new(AlphaPixel::red(), 50.0, 50.0, 30.0));
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(200, 100);
+/// canvas.add_layer(background);
+/// for name in ["Ann", "Bob"] {
+///     // Only the layers after the group are composited again for each card
+///     canvas.layers.truncate(1);
+///     canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 120, y: 40, width: 20 * name.len(), height: 20 }));
+///     let card = canvas.flatten();
+///     assert_eq!(card.pixel_at(50, 50).unwrap(), AlphaPixel::red());
+/// }
+/// ```
+pub struct StaticGroupLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    canvas: Canvas<T>,
+    /// The flattened layers, cropped to the pixels they draw, and where they are on the canvas
+    cache: OnceCell<(Image<T>, Rect)>
+}
+
+impl<T: PixelChannel> StaticGroupLayer<T> {
+    /// Create an empty group, for a canvas of `width` by `height` pixels.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::from_layers(width, height, vec![])
+    }
+
+    pub fn from_layers(width: usize, height: usize, layers: Vec<Box<dyn Layer<T>>>) -> Self {
+        let mut canvas = Canvas::from_dimensions(width, height);
+        canvas.layers = layers;
+        Self { filters: vec![], canvas, cache: OnceCell::new() }
+    }
+
+    pub fn add_layer<L: Layer<T> + 'static>(&mut self, layer: L) {
+        self.invalidate();
+        self.canvas.add_layer(layer);
+    }
+
+    pub fn layers(&self) -> &[Box<dyn Layer<T>>] {
+        &self.canvas.layers
+    }
+
+    /// Get the layers to change them, which clears the cache.
+    pub fn layers_mut(&mut self) -> &mut Vec<Box<dyn Layer<T>>> {
+        self.invalidate();
+        &mut self.canvas.layers
+    }
+
+    /// Whether the layers have been flattened since they were last changed.
+    pub fn is_cached(&self) -> bool {
+        self.cache.get().is_some()
+    }
+
+    /// Clear the cache, so that the layers are flattened again the next time that the group is drawn.
+    pub fn invalidate(&mut self) {
+        self.cache.take();
+    }
+
+    fn cached(&self) -> &(Image<T>, Rect) {
+        self.cache.get_or_init(|| {
+            let image = self.canvas.flatten();
+            let content = image.bounding_box_of_content(T::zero()).unwrap_or_default();
+            (image.crop(content).unwrap_or_default(), content)
+        })
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for StaticGroupLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.cached().1
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let (image, rect) = self.cached();
+        image.pixel_at(x - rect.x, y - rect.y).unwrap()
+    }
+
+    fn children(&self) -> Vec<&dyn Layer<T>> {
+        self.canvas.layers.iter().map(|layer| layer.as_ref()).collect()
+    }
+}
+
+impl<T: PixelChannel> Canvas<T> {
+    /// Move a range of this canvas' layers into a [`StaticGroupLayer`], in their place, so that they are only
+    /// flattened once however many times the canvas is flattened.
+    /// 
+    /// This panics if the range is out of bounds.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::RectangleLayer};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 100, height: 100 }));
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 10, y: 10, width: 50, height: 50 }));
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 40, y: 40, width: 20, height: 20 }));
+    /// 
+    /// let before = canvas.flatten();
+    /// canvas.make_static(..2);
+    /// assert_eq!(canvas.layers.len(), 2);
+    /// assert_eq!(canvas.flatten(), before);
+    /// ```
+    pub fn make_static(&mut self, range: impl RangeBounds<usize>) {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0
+        };
+        let layers: Vec<Box<dyn Layer<T>>> = self.layers.drain(range).collect();
+        let mut group = StaticGroupLayer::from_layers(self.width, self.height, layers);
+        group.canvas.deterministic = self.deterministic;
+        group.canvas.seed = self.seed;
+        group.canvas.blend_space = self.blend_space;
+        self.layers.insert(start, Box::new(group));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rgba, layers::shapes::RectangleLayer};
+
+    #[test]
+    fn cache() {
+        let mut group: StaticGroupLayer<u8> = StaticGroupLayer::new(20, 20);
+        group.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 2, y: 3, width: 4, height: 5 }));
+        group.add_layer(RectangleLayer::new(rgba!(0, 0, 255, 128), Rect { x: 4, y: 4, width: 10, height: 2 }));
+        assert!(!group.is_cached());
+
+        let rect = group.get_rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (2, 3, 12, 5));
+        assert!(group.is_cached());
+        assert_eq!(group.filtered_pixel_at(2, 3).unwrap(), AlphaPixel::red());
+        assert_eq!(group.filtered_pixel_at(13, 3).unwrap().a, 0);
+        assert_eq!(group.filtered_pixel_at(13, 4).unwrap().a, 128);
+
+        group.layers_mut().pop();
+        assert!(!group.is_cached());
+        let rect = group.get_rect();
+        assert_eq!((rect.width, rect.height), (4, 5));
+        assert_eq!(group.children().len(), 1);
+    }
+}
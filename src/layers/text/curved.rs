@@ -0,0 +1,335 @@
+use crate::{
+    Filter,
+    Layer,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    Image,
+    BlendingMethod,
+    Error,
+    bitmap::sampler::{sample, BilinearSampler},
+    layers::{path::Path, text::{TextSettings, TextOptions, shadow::{shadow_margins, shadow_raster}, layout::{LayoutAlign, SpacingMode}}}
+};
+
+/// The most points that an arc is split into, so that huge radii from untrusted input can't use unbounded memory.
+const MAX_ARC_POINTS: usize = 4096;
+
+/// The curve that the baseline of a [`CurvedTextLayer`] follows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextCurve {
+    /// An arc of a circle from `start_angle` to `end_angle`, in degrees clockwise from the right of the center,
+    /// so -90.0 is the top of the circle.
+    /// 
+    /// If `end_angle` is greater than `start_angle`, the text runs clockwise with the tops of the glyphs facing out of
+    /// the circle, as on the top of a badge. Otherwise, it runs anticlockwise with the tops of the glyphs facing the
+    /// center, as on the bottom of a badge.
+    Arc { center: (f32, f32), radius: f32, start_angle: f32, end_angle: f32 },
+    /// The first subpath of a path, from its start to its end. Glyphs are upright where the path runs to the right.
+    Path(Path)
+}
+
+impl TextCurve {
+    /// Split the curve into points joined by straight lines.
+    fn points(&self) -> Vec<(f32, f32)> {
+        match self {
+            TextCurve::Arc { center, radius, start_angle, end_angle } => {
+                let (start, end) = (start_angle.to_radians(), end_angle.to_radians());
+                // Each line is at most a couple of pixels long
+                let count = ((end - start).abs() * radius.abs() / 2.0).ceil().clamp(1.0, MAX_ARC_POINTS as f32) as usize;
+                (0..=count).map(|i| {
+                    let angle = start + (end - start) * i as f32 / count as f32;
+                    (center.0 + radius*angle.cos(), center.1 + radius*angle.sin())
+                }).collect()
+            },
+            TextCurve::Path(path) => path.flatten().into_iter().next()
+                .map(|(mut points, closed)| {
+                    if closed {
+                        points.push(points[0]);
+                    }
+                    points
+                })
+                .unwrap_or_default()
+        }
+    }
+}
+
+/// Points joined by straight lines, which can be measured along.
+struct Polyline {
+    points: Vec<(f32, f32)>,
+    /// The distance along the polyline to each point
+    distances: Vec<f32>
+}
+
+impl Polyline {
+    fn new(points: Vec<(f32, f32)>) -> Self {
+        let mut distances = Vec::with_capacity(points.len());
+        let mut total = 0.0;
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                let previous = points[i - 1];
+                total += (point.0 - previous.0).hypot(point.1 - previous.1);
+            }
+            distances.push(total);
+        }
+        Self { points, distances }
+    }
+
+    fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// Get the point at a distance along the polyline. Distances before the start or after the end continue in a
+    /// straight line from the first or last line.
+    fn point_at(&self, distance: f32) -> (f32, f32) {
+        match self.points.len() {
+            0 => return (distance, 0.0),
+            1 => return (self.points[0].0 + distance, self.points[0].1),
+            _ => {}
+        }
+
+        // The index of the line that the distance is on, clamped to the first and last lines
+        let end = self.distances.partition_point(|point_distance| *point_distance < distance).clamp(1, self.points.len() - 1);
+        let (start_point, end_point) = (self.points[end - 1], self.points[end]);
+        let line_length = self.distances[end] - self.distances[end - 1];
+        let t = if line_length > 0.0 { (distance - self.distances[end - 1]) / line_length } else { 0.0 };
+        (start_point.0 + (end_point.0 - start_point.0)*t, start_point.1 + (end_point.1 - start_point.1)*t)
+    }
+}
+
+/// A single line of text laid out along a [`TextCurve`], with each glyph rotated to follow the curve.
+/// 
+/// The baseline of the text is on the curve. The text is aligned along the curve by the `align` of the layout,
+/// where [`LayoutAlign::Justify`] spreads the glyphs out to fill the whole curve. Text which doesn't fit on the curve
+/// continues in a straight line from its ends. Line breaks are replaced with spaces, and the direction, line spacing
+/// and baseline grid of the layout are ignored. The shadow of the options is drawn, but not the decoration.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use image_template::{Canvas, AlphaPixel, layers::text::{TextSettings, layout::{TextLayout, LayoutAlign}, curved::{CurvedTextLayer, TextCurve}}};
+/// 
+/// let font_bytes = std::fs::read("font.ttf").unwrap();
+/// let font = fontdue::Font::from_bytes(font_bytes, fontdue::FontSettings::default()).unwrap();
+/// 
+/// // Text around the top of a circular logo centered at (200, 200)
+/// let settings = TextSettings {
+///     size: 32.0,
+///     fill: AlphaPixel::white(),
+///     layout: TextLayout { align: LayoutAlign::Center, ..Default::default() },
+///     text: String::from("OFFICIAL SEAL"),
+///     font
+/// };
+/// let curve = TextCurve::Arc { center: (200.0, 200.0), radius: 120.0, start_angle: -180.0, end_angle: 0.0 };
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(400, 400);
+/// canvas.add_layer(CurvedTextLayer::try_new(settings, curve).unwrap());
+/// ```
+pub struct CurvedTextLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    settings: TextSettings<T>,
+    options: TextOptions<T>,
+    curve: TextCurve,
+    rasterized: Image<T>,
+    /// The position of the top left of the rasterized text on the canvas, which may be above or left of the canvas
+    x: isize,
+    y: isize
+}
+
+impl<T: PixelChannel> CurvedTextLayer<T> {
+    pub fn try_new(settings: TextSettings<T>, curve: TextCurve) -> Result<Self, Error> {
+        Self::try_with_options(settings, TextOptions::default(), curve)
+    }
+
+    /// Create a layer with the shadow of `options` drawn under the text.
+    pub fn try_with_options(settings: TextSettings<T>, options: TextOptions<T>, curve: TextCurve) -> Result<Self, Error> {
+        let mut layer = Self { filters: vec![], settings, options, curve, rasterized: Image::new(), x: 0, y: 0 };
+        layer.rasterize()?;
+        Ok(layer)
+    }
+
+    pub fn get_settings(&self) -> &TextSettings<T> {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: TextSettings<T>) -> Result<(), Error> {
+        self.settings = settings;
+        self.rasterize()
+    }
+
+    pub fn get_options(&self) -> &TextOptions<T> {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: TextOptions<T>) -> Result<(), Error> {
+        self.options = options;
+        self.rasterize()
+    }
+
+    pub fn get_curve(&self) -> &TextCurve {
+        &self.curve
+    }
+
+    pub fn set_curve(&mut self, curve: TextCurve) -> Result<(), Error> {
+        self.curve = curve;
+        self.rasterize()
+    }
+
+    /// Get each glyph with the distance along the curve to the start and end of its advance.
+    fn glyph_spans(&self, curve_length: f32) -> Vec<(char, f32, f32)> {
+        let settings = &self.settings;
+        let text: Vec<char> = settings.layout.transform.apply(&settings.text).chars()
+            .map(|c| if c == '\n' { ' ' } else { c })
+            .collect();
+
+        let mut spans = Vec::with_capacity(text.len());
+        let mut pen = 0.0;
+        for (i, &glyph) in text.iter().enumerate() {
+            if i > 0 && settings.layout.use_kern {
+                let kern = settings.font.horizontal_kern(text[i - 1], glyph, settings.size).unwrap_or(0.0);
+                pen += match settings.layout.glyph_spacing {
+                    SpacingMode::Scale(scale) => kern*scale,
+                    SpacingMode::Constant(_) => kern
+                };
+            }
+            let advance = match settings.layout.glyph_spacing {
+                SpacingMode::Scale(scale) => settings.font.metrics(glyph, settings.size).advance_width*scale,
+                SpacingMode::Constant(spacing) => spacing
+            };
+            spans.push((glyph, pen, pen + advance));
+            pen += advance + settings.layout.spacing_after(glyph);
+        }
+
+        let free = curve_length - pen;
+        let (start, gap) = match settings.layout.align {
+            LayoutAlign::Start => (0.0, 0.0),
+            LayoutAlign::End => (free, 0.0),
+            LayoutAlign::Center => (free / 2.0, 0.0),
+            LayoutAlign::Justify if text.len() > 1 => (0.0, free.max(0.0) / (text.len() - 1) as f32),
+            LayoutAlign::Justify => (0.0, 0.0)
+        };
+        for (i, (_glyph, span_start, span_end)) in spans.iter_mut().enumerate() {
+            *span_start += start + gap*i as f32;
+            *span_end += start + gap*i as f32;
+        }
+        spans
+    }
+
+    fn rasterize(&mut self) -> Result<(), Error> {
+        let polyline = Polyline::new(self.curve.points());
+
+        // Each glyph's coverage, with the canvas position of its origin and the direction of its baseline
+        let mut glyphs = vec![];
+        let (mut left, mut top, mut right, mut bottom) = (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for (glyph, start, end) in self.glyph_spans(polyline.length()) {
+            let (metrics, coverage) = self.settings.font.rasterize(glyph, self.settings.size);
+            if metrics.width == 0 || metrics.height == 0 {
+                continue
+            }
+
+            // The baseline of the glyph is the chord of the curve under its advance
+            let origin = polyline.point_at(start);
+            let baseline_end = polyline.point_at(end);
+            let angle = if end > start { (baseline_end.1 - origin.1).atan2(baseline_end.0 - origin.0) } else { 0.0 };
+            let (sin, cos) = angle.sin_cos();
+
+            // The top left of the glyph's coverage, relative to its origin, where y is down
+            let offset = (metrics.xmin as f32, -(metrics.ymin as f32 + metrics.height as f32));
+            let mut bounds = (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+            for corner in [(0.0, 0.0), (metrics.width as f32, 0.0), (0.0, metrics.height as f32), (metrics.width as f32, metrics.height as f32)] {
+                let (local_x, local_y) = (offset.0 + corner.0, offset.1 + corner.1);
+                let (x, y) = (origin.0 + local_x*cos - local_y*sin, origin.1 + local_x*sin + local_y*cos);
+                bounds = (bounds.0.min(x), bounds.1.min(y), bounds.2.max(x), bounds.3.max(y));
+            }
+            (left, top, right, bottom) = (left.min(bounds.0), top.min(bounds.1), right.max(bounds.2), bottom.max(bounds.3));
+            glyphs.push((metrics, coverage, origin, offset, (sin, cos), bounds));
+        }
+
+        if glyphs.is_empty() {
+            (self.rasterized, self.x, self.y) = (Image::new(), 0, 0);
+            return Ok(())
+        }
+
+        let (left, top) = (left.floor(), top.floor());
+        let (width, height) = ((right.ceil() - left) as usize, (bottom.ceil() - top) as usize);
+        let mut image = Image::try_new_with_fill(AlphaPixel::default(), width, height)?;
+        for (metrics, coverage, origin, offset, (sin, cos), bounds) in glyphs {
+            let coverage_at = |x: isize, y: isize| {
+                let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+                (x < metrics.width && y < metrics.height)
+                    .then(|| AlphaPixel { a: T::from_u8(coverage[y*metrics.width + x]).unwrap(), ..self.settings.fill })
+            };
+
+            // Only the pixels within the rotated glyph's bounds
+            let (glyph_left, glyph_top) = ((bounds.0 - left).floor() as usize, (bounds.1 - top).floor() as usize);
+            let (glyph_right, glyph_bottom) = (((bounds.2 - left).ceil() as usize).min(width), ((bounds.3 - top).ceil() as usize).min(height));
+            for image_y in glyph_top..glyph_bottom {
+                for image_x in glyph_left..glyph_right {
+                    // Rotate the center of the pixel back onto the unrotated glyph
+                    let x = left + image_x as f32 + 0.5 - origin.0;
+                    let y = top + image_y as f32 + 0.5 - origin.1;
+                    let (local_x, local_y) = (x*cos + y*sin, -x*sin + y*cos);
+                    if let Some(glyph_pixel) = sample(&BilinearSampler, local_x - offset.0, local_y - offset.1, coverage_at) {
+                        let pixel = image.pixel_at_mut(image_x, image_y).unwrap();
+                        *pixel = BlendingMethod::Over.blend(*pixel, glyph_pixel);
+                    }
+                }
+            }
+        }
+
+        (self.x, self.y) = (left as isize, top as isize);
+        if let Some(shadow) = &self.options.shadow {
+            let [shadow_left, shadow_top, ..] = shadow_margins(shadow);
+            image = shadow_raster(&image, shadow);
+            (self.x, self.y) = (self.x - shadow_left as isize, self.y - shadow_top as isize);
+        }
+        self.rasterized = image;
+        Ok(())
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for CurvedTextLayer<T> {
+    fn get_rect(&self) -> Rect {
+        // Text above or left of the canvas is cut off
+        let (cut_x, cut_y) = ((-self.x).max(0) as usize, (-self.y).max(0) as usize);
+        Rect {
+            x: self.x.max(0) as usize,
+            y: self.y.max(0) as usize,
+            width: self.rasterized.get_width().saturating_sub(cut_x),
+            height: self.rasterized.get_height().saturating_sub(cut_y)
+        }
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.rasterized.pixel_at((x as isize - self.x) as usize, (y as isize - self.y) as usize).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+    use super::*;
+
+    #[test]
+    fn arc_points() {
+        let arc = TextCurve::Arc { center: (50.0, 50.0), radius: 20.0, start_angle: -180.0, end_angle: 0.0 };
+        let polyline = Polyline::new(arc.points());
+        assert!((polyline.length() - 20.0*PI).abs() < 0.1);
+
+        let top = polyline.point_at(polyline.length() / 2.0);
+        assert!((top.0 - 50.0).abs() < 0.01 && (top.1 - 30.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn point_at_extends_ends() {
+        let path = Path::new().move_to(10.0, 10.0).line_to(20.0, 10.0).line_to(20.0, 30.0);
+        let polyline = Polyline::new(TextCurve::Path(path).points());
+        assert_eq!(polyline.length(), 30.0);
+        assert_eq!(polyline.point_at(5.0), (15.0, 10.0));
+        assert_eq!(polyline.point_at(20.0), (20.0, 20.0));
+        assert_eq!(polyline.point_at(-5.0), (5.0, 10.0));
+        assert_eq!(polyline.point_at(35.0), (20.0, 35.0));
+    }
+}
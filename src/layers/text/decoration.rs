@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use fontdue::{Font, Metrics};
+use crate::{Image, AlphaPixel, PixelChannel, BlendingMethod};
+
+/// Lines drawn under or through text.
+/// 
+/// The lines are drawn under each line of left to right text, from its first visible glyph to its last. Their
+/// positions and thickness are taken from the underscore and hyphen glyphs of the font, so they match the font's
+/// own underscores and hyphens. Top to bottom text isn't decorated.
+#[derive(Clone, Copy)]
+pub struct TextDecoration<T> {
+    pub underline: bool,
+    pub strikethrough: bool,
+    /// The colour of the lines, or the text's fill if this is `None`
+    pub color: Option<AlphaPixel<T>>,
+    /// The thickness of the lines in pixels, or the thickness from the font if this is `None`
+    pub thickness: Option<f32>
+}
+
+impl<T> TextDecoration<T> {
+    pub fn underline() -> Self {
+        Self { underline: true, strikethrough: false, color: None, thickness: None }
+    }
+
+    pub fn strikethrough() -> Self {
+        Self { underline: false, strikethrough: true, color: None, thickness: None }
+    }
+}
+
+/// A horizontal bar, with its left, top, right and bottom edges in the coordinates of the laid out glyphs.
+pub(crate) type Bar = (isize, isize, isize, isize);
+
+/// Get the top of a line drawn like `glyph`, relative to the baseline, and its thickness.
+/// 
+/// Returns `None` if the font doesn't have the glyph.
+fn line_from_glyph(font: &Font, glyph: char, size: f32) -> Option<(isize, isize)> {
+    if font.lookup_glyph_index(glyph) == 0 {
+        return None
+    }
+    let metrics = font.metrics(glyph, size);
+    (metrics.height > 0).then(|| (-(metrics.ymin as isize + metrics.height as isize), metrics.height as isize))
+}
+
+/// Get the bars which decorate text, from the position and metrics of each of its glyphs.
+pub(crate) fn decoration_bars<T>(
+    decoration: &TextDecoration<T>,
+    font: &Font,
+    size: f32,
+    glyphs: impl Iterator<Item = (char, isize, isize, Metrics)>
+) -> Vec<Bar> {
+    // The left and right edges of the visible glyphs on each baseline
+    let mut rows: BTreeMap<isize, (isize, isize)> = BTreeMap::new();
+    for (glyph, x, y, metrics) in glyphs {
+        if glyph.is_whitespace() {
+            continue
+        }
+        let baseline = y + metrics.height as isize + metrics.ymin as isize;
+        let right = x + metrics.width as isize;
+        rows.entry(baseline)
+            .and_modify(|(left_edge, right_edge)| {
+                *left_edge = (*left_edge).min(x);
+                *right_edge = (*right_edge).max(right);
+            })
+            .or_insert((x, right));
+    }
+
+    let fallback_thickness = (size / 16.0).round().max(1.0) as isize;
+    let mut lines = vec![];
+    if decoration.underline {
+        lines.push(line_from_glyph(font, '_', size).unwrap_or(((size * 0.1).round() as isize, fallback_thickness)));
+    }
+    if decoration.strikethrough {
+        lines.push(line_from_glyph(font, '-', size).unwrap_or((-(size * 0.3).round() as isize, fallback_thickness)));
+    }
+
+    let mut bars = vec![];
+    for (top, thickness) in lines {
+        // A set thickness is centered on the font's line
+        let (top, thickness) = match decoration.thickness {
+            Some(set) => {
+                let set = set.round().max(1.0) as isize;
+                (top + (thickness - set) / 2, set)
+            },
+            None => (top, thickness)
+        };
+        for (baseline, (left, right)) in &rows {
+            bars.push((*left, baseline + top, *right, baseline + top + thickness));
+        }
+    }
+    bars
+}
+
+/// Draw bars over an image, whose top left is at `origin` in the coordinates of the bars.
+pub(crate) fn draw_bars<T: PixelChannel>(image: &mut Image<T>, bars: &[Bar], origin: (isize, isize), color: AlphaPixel<T>) {
+    for (left, top, right, bottom) in bars {
+        for y in (top - origin.1).max(0)..(bottom - origin.1) {
+            for x in (left - origin.0).max(0)..(right - origin.0) {
+                if let Some(pixel) = image.pixel_at_mut(x as usize, y as usize) {
+                    *pixel = BlendingMethod::Over.blend(*pixel, color);
+                }
+            }
+        }
+    }
+}
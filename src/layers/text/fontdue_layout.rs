@@ -0,0 +1,134 @@
+use fontdue::{Font, layout::{Layout, GlyphRasterConfig}};
+use crate::{
+    Image,
+    PixelChannel,
+    Error,
+    layers::text::{
+        TextLayer,
+        TextSettings,
+        TextOptions,
+        layout::LayoutError,
+        decoration::TextDecoration,
+        glyphs::{PositionedGlyph, GlyphDecoration, raster_positioned_glyphs}
+    }
+};
+
+/// A glyph laid out by fontdue, with the index of its font and the top left of its bounding box.
+#[derive(Clone, Copy)]
+struct FontdueGlyph {
+    parent: char,
+    font_index: usize,
+    key: GlyphRasterConfig,
+    x: f32,
+    y: f32
+}
+
+/// The glyphs of a fontdue [`Layout`], which a [`TextLayer`] draws instead of laying out its text itself.
+#[derive(Clone)]
+pub(crate) struct FontdueGlyphs {
+    fonts: Vec<Font>,
+    glyphs: Vec<FontdueGlyph>
+}
+
+impl FontdueGlyphs {
+    fn new<U: Copy>(layout: &Layout<U>, fonts: &[Font]) -> Result<Self, LayoutError> {
+        let glyphs = layout.glyphs().iter()
+            .map(|glyph| if glyph.font_index < fonts.len() {
+                Ok(FontdueGlyph { parent: glyph.parent, font_index: glyph.font_index, key: glyph.key, x: glyph.x, y: glyph.y })
+            } else {
+                Err(LayoutError::MissingFont { index: glyph.font_index, count: fonts.len() })
+            })
+            .collect::<Result<Vec<FontdueGlyph>, LayoutError>>()?;
+        Ok(Self { fonts: fonts.to_vec(), glyphs })
+    }
+
+    /// The same glyphs at a different size, with their positions scaled around the origin of the layout.
+    pub(crate) fn scaled(&self, factor: f32) -> Self {
+        let glyphs = self.glyphs.iter()
+            .map(|glyph| FontdueGlyph {
+                key: GlyphRasterConfig { px: glyph.key.px * factor, ..glyph.key },
+                x: glyph.x * factor,
+                y: glyph.y * factor,
+                ..*glyph
+            })
+            .collect();
+        Self { fonts: self.fonts.clone(), glyphs }
+    }
+
+    /// Rasterize the glyphs with the fill of `settings` and `decoration`, with the origin of the layout at the top left.
+    pub(crate) fn raster<T: PixelChannel>(&self, settings: &TextSettings<T>, decoration: Option<&TextDecoration<T>>) -> Result<Image<T>, Error> {
+        let positioned: Vec<PositionedGlyph<T>> = self.glyphs.iter()
+            .map(|glyph| PositionedGlyph {
+                parent: glyph.parent,
+                font: &self.fonts[glyph.font_index],
+                index: glyph.key.glyph_index,
+                size: glyph.key.px,
+                fill: settings.fill,
+                x: glyph.x.round() as isize,
+                y: glyph.y.round() as isize
+            })
+            .collect();
+
+        // The lines are positioned from the first font, at the size of the first glyph
+        let decoration = decoration.zip(positioned.first())
+            .map(|(decoration, first)| GlyphDecoration { decoration, font: first.font, size: first.size });
+        raster_positioned_glyphs(&positioned, settings.fill, decoration)
+    }
+}
+
+impl<T: PixelChannel> TextLayer<T> {
+    /// Draw text laid out by fontdue's [`Layout`], with its origin at (`x`, `y`), for wrapping and alignment which
+    /// this crate's [`TextLayout`](super::layout::TextLayout) doesn't provide.
+    /// 
+    /// `fonts` are the fonts that the layout's text styles were appended with, in the same order. The layout must use
+    /// [`CoordinateSystem::PositiveYDown`](fontdue::layout::CoordinateSystem::PositiveYDown), like the rest of this crate.
+    /// 
+    /// The glyphs are drawn with the fill of `settings`, and the layer's options, strokes, target and filters work as
+    /// they do for any other text. The text, font, size and layout of `settings` aren't used to draw
+    /// the glyphs, and columns don't apply. [`TextLayer::set_text`] and [`TextLayer::set_size`] replace the fontdue
+    /// layout, and lay out the text of the settings with this crate's layout instead.
+    /// 
+    /// Returns [`LayoutError::MissingFont`] if a glyph uses a font which isn't in `fonts`.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// # use image_template::{AlphaPixel, layers::text::{TextLayer, TextSettings}};
+    /// use fontdue::layout::{Layout, LayoutSettings, CoordinateSystem, TextStyle, HorizontalAlign};
+    /// 
+    /// # let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+    /// layout.reset(&LayoutSettings { max_width: Some(300.0), horizontal_align: HorizontalAlign::Center, ..Default::default() });
+    /// layout.append(&[&font], &TextStyle::new("Text wrapped and centered by fontdue", 32.0, 0));
+    /// 
+    /// # let settings = TextSettings::new("", font.clone(), 32.0, AlphaPixel::<u8>::black());
+    /// let text = TextLayer::from_fontdue_layout(&layout, &[font], settings, 50, 50).unwrap();
+    /// ```
+    pub fn from_fontdue_layout<U: Copy>(layout: &Layout<U>, fonts: &[Font], settings: TextSettings<T>, x: usize, y: usize) -> Result<Self, Error> {
+        Self::from_fontdue_glyphs(FontdueGlyphs::new(layout, fonts)?, settings, TextOptions::default(), x, y)
+    }
+
+    pub(crate) fn from_fontdue_glyphs(glyphs: FontdueGlyphs, settings: TextSettings<T>, options: TextOptions<T>, x: usize, y: usize) -> Result<Self, Error> {
+        let mut layer = Self {
+            settings,
+            styled: None,
+            options,
+            strokes: vec![],
+            columns: None,
+            target: None,
+            text: Image::new(),
+            rasterized: Image::new(),
+            fontdue_glyphs: Some(glyphs),
+            x,
+            y,
+            filters: vec![]
+        };
+        layer.rasterize()?;
+        Ok(layer)
+    }
+
+    /// Whether the layer draws glyphs laid out by fontdue, from [`TextLayer::from_fontdue_layout`].
+    pub fn has_fontdue_layout(&self) -> bool {
+        self.fontdue_glyphs.is_some()
+    }
+}
+
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use fontdue::Font;
+use crate::{
+    Image,
+    AlphaPixel,
+    PixelChannel,
+    BlendingMethod,
+    Error,
+    layers::text::decoration::{TextDecoration, decoration_bars, draw_bars}
+};
+
+/// A glyph which has been laid out, with the coordinate of the top left of its bounding box.
+pub(crate) struct PositionedGlyph<'a, T> {
+    /// The character which the glyph was laid out from, so that whitespace isn't decorated
+    pub parent: char,
+    pub font: &'a Font,
+    pub index: u16,
+    pub size: f32,
+    pub fill: AlphaPixel<T>,
+    pub x: isize,
+    pub y: isize
+}
+
+impl<'a, T> PositionedGlyph<'a, T> {
+    pub fn new(parent: char, font: &'a Font, size: f32, fill: AlphaPixel<T>, x: isize, y: isize) -> Self {
+        Self { parent, font, index: font.lookup_glyph_index(parent), size, fill, x, y }
+    }
+}
+
+/// The decoration of some glyphs, with the font and size which the positions of its lines are taken from.
+pub(crate) struct GlyphDecoration<'a, T> {
+    pub decoration: &'a TextDecoration<T>,
+    pub font: &'a Font,
+    pub size: f32
+}
+
+/// Rasterize glyphs into an image, with their decoration drawn in its colour or `fill`.
+/// 
+/// The top left of the image is the origin of the layout, unless glyphs or the decoration go above or to the left of it.
+pub(crate) fn raster_positioned_glyphs<T: PixelChannel>(
+    glyphs: &[PositionedGlyph<T>],
+    fill: AlphaPixel<T>,
+    decoration: Option<GlyphDecoration<T>>
+) -> Result<Image<T>, Error> {
+    let mut minimum_coord = (0, 0);
+    let mut maximum_coord = (0, 0);
+    for positioned in glyphs {
+        let metrics = positioned.font.metrics_indexed(positioned.index, positioned.size);
+        minimum_coord = (minimum_coord.0.min(positioned.x), minimum_coord.1.min(positioned.y));
+        maximum_coord = (maximum_coord.0.max(positioned.x + metrics.width as isize), maximum_coord.1.max(positioned.y + metrics.height as isize));
+    }
+
+    let bars = match &decoration {
+        Some(GlyphDecoration { decoration, font, size }) => {
+            let glyphs = glyphs.iter().map(|positioned| {
+                (positioned.parent, positioned.x, positioned.y, positioned.font.metrics_indexed(positioned.index, positioned.size))
+            });
+            decoration_bars(decoration, font, *size, glyphs)
+        },
+        None => vec![]
+    };
+    for (left, top, right, bottom) in &bars {
+        minimum_coord = (minimum_coord.0.min(*left), minimum_coord.1.min(*top));
+        maximum_coord = (maximum_coord.0.max(*right), maximum_coord.1.max(*bottom));
+    }
+
+    let width = (maximum_coord.0 - minimum_coord.0) as usize;
+    let height = (maximum_coord.1 - minimum_coord.1) as usize;
+    let mut image = Image::new_with_fill(AlphaPixel::default(), width, height);
+
+    // Glyph coverage, keyed by the font, the glyph and the bits of its size
+    let mut coverage_cache: HashMap<(*const Font, u16, u32), (usize, Vec<u8>)> = HashMap::new();
+    for positioned in glyphs {
+        let (glyph_width, coverage) = coverage_cache.entry((positioned.font, positioned.index, positioned.size.to_bits()))
+            .or_insert_with(|| {
+                let (metrics, coverage) = positioned.font.rasterize_indexed(positioned.index, positioned.size);
+                (metrics.width, coverage)
+            });
+        if *glyph_width == 0 {
+            continue
+        }
+
+        let glyph_pixels = coverage.iter()
+            .map(|alpha| AlphaPixel { a: T::from_u8(*alpha).unwrap(), ..positioned.fill })
+            .collect();
+        let glyph_image = Image::from_pixels(glyph_pixels, *glyph_width)?;
+        image.draw_subimage(
+            &glyph_image,
+            (positioned.x - minimum_coord.0) as usize,
+            (positioned.y - minimum_coord.1) as usize,
+            BlendingMethod::Over
+        )?;
+    }
+
+    if let Some(GlyphDecoration { decoration, .. }) = decoration {
+        draw_bars(&mut image, &bars, minimum_coord, decoration.color.unwrap_or(fill));
+    }
+    Ok(image)
+}
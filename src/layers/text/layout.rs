@@ -1,22 +1,48 @@
-use std::{iter::Rev, str::{Chars, Split}};
-use fontdue::Metrics;
+use std::{iter::Rev, vec::IntoIter};
+use fontdue::{Font, Metrics};
 use thiserror::Error;
-use crate::PixelChannel;
-use super::TextSettings;
+use crate::{AlphaPixel, PixelChannel};
+use super::{TextSettings, rich::StyledText};
 
 pub const DEFAULT_VERTICAL_SPACING: f32 = 10.0;
 
 #[derive(Debug, Error)]
 pub enum LayoutError {
     #[error("Font doesn't have line spacing. Use constant line spacing, or another font.")]
-    MissingLineSpacing
+    MissingLineSpacing,
+    #[error("A glyph uses font {index}, but there are only {count} fonts")]
+    MissingFont { index: usize, count: usize }
 }
 
-// TODO: Implement alignment for `LayoutDirection::TopToBottom`
+/// How the lines of text are aligned with each other.
+/// 
+/// For [`LayoutDirection::TopToBottom`], each line is a column, which is aligned vertically:
+/// `Start` aligns the tops of the columns and `End` aligns their bottoms.
 #[derive(PartialEq, Clone, Copy)]
 pub enum LayoutAlign {
     Start,
-    End
+    End,
+    /// Each line is centered within the width of the widest line, or the height of the tallest column
+    Center,
+    /// Spaces are widened so that each line is as wide as the widest line.
+    /// 
+    /// The last line of each paragraph, before an empty line or the end of the text, is start aligned.
+    /// Top to bottom text is start aligned.
+    Justify
+}
+
+/// How text is positioned vertically within the target `Rect` of a [`TextLayer`](super::TextLayer).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+    /// The baseline of the last line is on the bottom edge of the `Rect`, and descenders hang below it,
+    /// so that text of different sizes sits on the same line.
+    /// 
+    /// Top to bottom text has no baseline, so it is aligned to the bottom instead.
+    Baseline
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -31,13 +57,95 @@ pub enum SpacingMode {
     Constant(f32)
 }
 
+/// A change to the case of text, applied when it is laid out so that templates have a consistent style
+/// regardless of the casing of their input.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextTransform {
+    #[default]
+    None,
+    Uppercase,
+    Lowercase,
+    /// The first letter of each word is uppercase, and the rest are lowercase
+    TitleCase,
+    /// Lowercase letters are drawn as smaller uppercase letters.
+    /// 
+    /// This is synthesized when rasterizing left to right text, with any alignment. Top to bottom text is
+    /// uppercased instead, as are the glyphs positioned by [`LayoutIter`], which doesn't yield the size of each glyph.
+    SmallCaps
+}
+
+/// How text which is longer than the `max_width` or `max_lines` of its [`TextLayout`] is cut short.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextOverflow {
+    /// The characters which don't fit are removed
+    #[default]
+    Clip,
+    /// The characters which don't fit are replaced with an ellipsis, "…", or "..." if the font doesn't have one
+    Ellipsis
+}
+
+/// The scale of the uppercase letters which replace lowercase letters in [`TextTransform::SmallCaps`]
+pub const SMALL_CAPS_SCALE: f32 = 0.75;
+
+impl TextTransform {
+    /// Apply the transform to some text. Small caps are uppercased.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::layers::text::layout::TextTransform;
+    /// 
+    /// assert_eq!(TextTransform::TitleCase.apply("the QUICK brown\nfox"), "The Quick Brown\nFox");
+    /// assert_eq!(TextTransform::Uppercase.apply("straße"), "STRASSE");
+    /// ```
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            TextTransform::None => text.to_string(),
+            TextTransform::Uppercase | TextTransform::SmallCaps => text.to_uppercase(),
+            TextTransform::Lowercase => text.to_lowercase(),
+            TextTransform::TitleCase => {
+                let mut title = String::with_capacity(text.len());
+                let mut start_of_word = true;
+                for c in text.chars() {
+                    if start_of_word {
+                        title.extend(c.to_uppercase());
+                    } else {
+                        title.extend(c.to_lowercase());
+                    }
+                    start_of_word = c.is_whitespace();
+                }
+                title
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub struct TextLayout {
     pub direction: LayoutDirection,
     pub align: LayoutAlign,
     pub line_spacing: SpacingMode,
     pub glyph_spacing: SpacingMode,
-    pub use_kern: bool
+    /// Extra space after every glyph in pixels, which is added after kerning, so it doesn't change the shape of
+    /// the kerning like scaling the `glyph_spacing` does. Negative values bring the glyphs closer together.
+    pub letter_spacing: f32,
+    /// Extra space after every space in pixels, as well as the `letter_spacing`
+    pub word_spacing: f32,
+    pub use_kern: bool,
+    /// If set, the baselines of left to right text are snapped down onto a grid with this spacing in pixels,
+    /// measured from the top of the layer.
+    /// 
+    /// The first baseline is placed on the first grid line below the font's ascent, and each line is
+    /// moved down by a whole number of grid lines. Text layers with the same `y` and grid spacing have aligned
+    /// baselines, even if they use different fonts or sizes.
+    pub baseline_grid: Option<f32>,
+    pub transform: TextTransform,
+    /// The widest that each line can be in pixels, before it is cut short by `overflow`.
+    /// 
+    /// Lines aren't wrapped. Use [`TextSettings::wrap_text`] first to wrap them. Top to bottom text isn't cut short by width.
+    pub max_width: Option<f32>,
+    /// The most lines that are drawn. If there are more, the rest are removed and the last line is ended by `overflow`.
+    pub max_lines: Option<usize>,
+    pub overflow: TextOverflow
 }
 
 impl Default for TextLayout {
@@ -47,45 +155,426 @@ impl Default for TextLayout {
             align: LayoutAlign::Start,
             line_spacing: SpacingMode::Scale(1.0),
             glyph_spacing: SpacingMode::Scale(1.0),
-            use_kern: true
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            use_kern: true,
+            baseline_grid: None,
+            transform: TextTransform::None,
+            max_width: None,
+            max_lines: None,
+            overflow: TextOverflow::Clip
         }
     }
 }
+impl TextLayout {
+    /// Get the extra space after `glyph` from the letter and word spacing, in pixels.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::layers::text::layout::TextLayout;
+    /// 
+    /// let layout = TextLayout { letter_spacing: 2.0, word_spacing: 5.0, ..Default::default() };
+    /// assert_eq!(layout.spacing_after('a'), 2.0);
+    /// assert_eq!(layout.spacing_after(' '), 7.0);
+    /// ```
+    pub fn spacing_after(&self, glyph: char) -> f32 {
+        self.letter_spacing + if glyph == ' ' { self.word_spacing } else { 0.0 }
+    }
+}
+
+/// The font, size and fill of a glyph, from the settings of the text or a span of styled text.
+#[derive(Clone, Copy)]
+pub(crate) struct GlyphStyle<'a, T> {
+    pub font: &'a Font,
+    pub size: f32,
+    pub fill: AlphaPixel<T>,
+    /// The size relative to the size of the settings, which scales constant glyph spacing
+    pub scale: f32,
+    /// How far the baseline of left to right text is raised, in pixels. Negative values lower it.
+    pub baseline_shift: f32
+}
+
+impl<T: Copy> GlyphStyle<'_, T> {
+    /// Whether glyphs in this style can be kerned with glyphs in `other`. Clones of a font are the same font.
+    fn kerns_with(&self, other: &Self) -> bool {
+        self.size == other.size && (std::ptr::eq(self.font, other.font) || self.font.file_hash() == other.font.file_hash())
+    }
+
+    fn small_caps(self) -> Self {
+        Self { size: self.size * SMALL_CAPS_SCALE, scale: self.scale * SMALL_CAPS_SCALE, ..self }
+    }
+}
+
+/// Some text in a single style
+pub(crate) type TextPiece<'a, T> = (&'a str, GlyphStyle<'a, T>);
+
+/// A character, and the index of its style
+type StyledChar = (char, usize);
+
+/// A glyph which has been laid out, with its style and the coordinate of the top left of its bounding box
+pub(crate) type StyledGlyph<'a, T> = (char, GlyphStyle<'a, T>, isize, isize);
+
+/// Cut lines of styled characters short by the `max_width`, `max_lines` and `overflow` of the layout.
+pub(crate) fn truncate_lines<T: PixelChannel>(settings: &TextSettings<T>, styles: &[GlyphStyle<T>], mut lines: Vec<Vec<StyledChar>>) -> Vec<Vec<StyledChar>> {
+    let layout = &settings.layout;
+    if layout.max_width.is_none() && layout.max_lines.is_none() {
+        return lines
+    }
+
+    let max_lines = layout.max_lines.unwrap_or(usize::MAX);
+    let removed_lines = lines.len() > max_lines;
+    lines.truncate(max_lines);
+
+    let max_width = layout.max_width.filter(|_| layout.direction == LayoutDirection::LeftToRight);
+    let last = lines.len().saturating_sub(1);
+    lines.into_iter().enumerate().map(|(index, line)| {
+        let too_wide = max_width.is_some_and(|max_width| drawn_width(settings, styles, &line) > max_width);
+        let ellipsis = layout.overflow == TextOverflow::Ellipsis && (too_wide || (removed_lines && index == last));
+        if !too_wide && !ellipsis {
+            return line
+        }
+        fit_line(settings, styles, &line, max_width.unwrap_or(f32::INFINITY), ellipsis)
+    }).collect()
+}
+
+/// Cut a line short so that it fits in `max_width`, ending it with an ellipsis in the style of the last character
+/// which is kept if `ellipsis` is true.
+fn fit_line<T: PixelChannel>(settings: &TextSettings<T>, styles: &[GlyphStyle<T>], line: &[StyledChar], max_width: f32, ellipsis: bool) -> Vec<StyledChar> {
+    let with_ending = |length: usize| {
+        let mut kept = line[..length].to_vec();
+        if ellipsis {
+            while kept.last().is_some_and(|(c, _style)| c.is_whitespace()) {
+                kept.pop();
+            }
+            let style = kept.last().or(line.first()).map_or(0, |(_c, style)| *style);
+            let ending = if styles[style].font.lookup_glyph_index('…') != 0 { "…" } else { "..." };
+            kept.extend(ending.chars().map(|c| (c, style)));
+        }
+        kept
+    };
+
+    // Find the most characters which fit, as lines only get wider as characters are added
+    let (mut fits, mut too_many) = (0, line.len() + 1);
+    while too_many - fits > 1 {
+        let middle = (fits + too_many) / 2;
+        if drawn_width(settings, styles, &with_ending(middle)) <= max_width {
+            fits = middle;
+        } else {
+            too_many = middle;
+        }
+    }
+
+    let fitted = with_ending(fits);
+    if drawn_width(settings, styles, &fitted) <= max_width { fitted } else { vec![] }
+}
+
+/// Get the width of the pixels drawn for a single line of styled characters, which can be wider than
+/// [`TextSettings::line_width`] as it includes the left side bearing of each glyph.
+fn drawn_width<T: PixelChannel>(settings: &TextSettings<T>, styles: &[GlyphStyle<T>], line: &[StyledChar]) -> f32 {
+    let transformed = transform_line(settings.layout.transform, line, None);
+    let (mut left, mut right) = (0, 0);
+    let mut glyphs = LayoutIter::from_lines(settings, LayoutAlign::Start, styles.to_vec(), vec![transformed]);
+    while let Some(Ok((glyph, style, glyph_x, _glyph_y))) = glyphs.next_glyph() {
+        left = left.min(glyph_x);
+        right = right.max(glyph_x + style.font.metrics(glyph, style.size).width as isize);
+    }
+    (right - left) as f32
+}
+
+/// Apply a text transform to a line of styled characters. Title case continues across styles, so a span which starts
+/// in the middle of a word isn't capitalized.
+/// 
+/// If `small_caps` is the index of the first small caps style, small caps are synthesized by moving lowercase letters
+/// into the small caps version of their style. Otherwise, they are uppercased.
+fn transform_line(transform: TextTransform, line: &[StyledChar], small_caps: Option<usize>) -> Vec<StyledChar> {
+    let mut transformed = Vec::with_capacity(line.len());
+    match (transform, small_caps) {
+        (TextTransform::SmallCaps, Some(first_small_caps)) => for &(c, style) in line {
+            let style = if c.is_lowercase() { style + first_small_caps } else { style };
+            transformed.extend(c.to_uppercase().map(|upper| (upper, style)));
+        },
+        (TextTransform::TitleCase, _) => {
+            let mut start_of_word = true;
+            for &(c, style) in line {
+                if start_of_word {
+                    transformed.extend(c.to_uppercase().map(|upper| (upper, style)));
+                } else {
+                    transformed.extend(c.to_lowercase().map(|lower| (lower, style)));
+                }
+                start_of_word = c.is_whitespace();
+            }
+        },
+        // The transform is applied to each piece, rather than each character, as it can depend on the characters around it
+        _ => for piece in line.chunk_by(|(_a, a_style), (_b, b_style)| a_style == b_style) {
+            let text: String = piece.iter().map(|(c, _style)| c).collect();
+            transformed.extend(transform.apply(&text).chars().map(|c| (c, piece[0].1)));
+        }
+    }
+    transformed
+}
+
+/// Lays out text, yielding each character with the coordinate of the top left of its glyph.
+/// 
+/// Each line of left to right text is moved down to fit the largest font on it, and each column of top to bottom
+/// text is moved right.
 pub struct LayoutIter<'a, T: PixelChannel> {
     settings: &'a TextSettings<T>,
-    lines: Split<'a, char>,
-    current_row_text: either::Either<Rev<Chars<'a>>, Chars<'a>>,
+    /// The styles of the characters, where the first is the style of the settings
+    styles: Vec<GlyphStyle<'a, T>>,
+    lines: IntoIter<Vec<StyledChar>>,
+    current_row_text: either::Either<Rev<IntoIter<StyledChar>>, IntoIter<StyledChar>>,
 
-    // Previous char, x/y (depending on direction) coordinate of the next origin position
-    prev_data: Option<(char, isize)>,
+    // Previous char and its style, x/y (depending on direction) coordinate of the next origin position
+    prev_data: Option<(char, usize, isize)>,
 
-    row: usize
+    row: usize,
+    align: LayoutAlign,
+    /// The baseline of each left to right row, or the x coordinate of each top to bottom column.
+    /// This is `None` if a font doesn't have line spacing.
+    row_positions: Option<Vec<f32>>,
+    /// For centered and justified left to right text, the offset of the start of each row, and the extra space after each space in it.
+    /// For centered and end aligned top to bottom text, the offset of the top of each column, with no extra space.
+    row_spacing: Vec<(f32, f32)>,
+    /// The number of spaces already laid out in the current row
+    row_spaces: usize,
+    /// The letter and word spacing already added to the current row
+    row_tracking: f32
 }
 
 impl<'a, T: PixelChannel> LayoutIter<'a, T> {
     pub fn new(settings: &'a TextSettings<T>) -> Self {
-        let mut lines = settings.text.split('\n');
-        // Will never panic as `Split` always emits at least one item.
-        let current_row_text = lines.next().unwrap().chars();
-        let either_iters = Self::either_iter_from_chars(settings.layout.align, current_row_text);
-        Self { lines, current_row_text: either_iters, prev_data: None, settings, row: 0 }
+        Self::from_pieces(settings, vec![(&settings.text, settings.style())], false)
     }
 
-    fn either_iter_from_chars(align: LayoutAlign, chars: Chars<'a>) -> either::Either<Rev<Chars<'a>>, Chars<'a>> {
-        match align {
-            LayoutAlign::Start => either::Either::Right(chars),
-            LayoutAlign::End => either::Either::Left(chars.rev())
+    /// Lay out [`StyledText`], where the glyphs of each span are in its font and size. The `text` of the settings isn't used.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use image_template::{AlphaPixel, layers::text::{TextSettings, layout::LayoutIter, rich::{StyledText, TextSpan}}};
+    /// 
+    /// let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// let settings = TextSettings::new("", font, 40.0, AlphaPixel::<u8>::black());
+    /// let text = StyledText::new().plain("Only ").span(TextSpan::new("£5").with_size(60.0));
+    /// for glyph in LayoutIter::styled(&settings, &text) {
+    ///     let (character, x, y) = glyph.unwrap();
+    ///     println!("{character} at ({x}, {y})");
+    /// }
+    /// ```
+    pub fn styled(settings: &'a TextSettings<T>, text: &'a StyledText<T>) -> Self {
+        Self::from_pieces(settings, text.pieces(settings), false)
+    }
+
+    /// Lay out pieces of text in their own styles, after truncating and transforming them. If `synthesize_small_caps` is
+    /// true, small caps are synthesized for left to right text instead of being uppercased.
+    pub(crate) fn from_pieces(settings: &'a TextSettings<T>, pieces: Vec<TextPiece<'a, T>>, synthesize_small_caps: bool) -> Self {
+        // The settings' style is used for an ellipsis when there is no text before it
+        let mut styles = vec![settings.style()];
+        let mut lines: Vec<Vec<StyledChar>> = vec![vec![]];
+        for (text, style) in pieces {
+            styles.push(style);
+            for c in text.chars() {
+                match c {
+                    '\n' => lines.push(vec![]),
+                    _ => lines.last_mut().unwrap().push((c, styles.len() - 1))
+                }
+            }
+        }
+
+        let lines = truncate_lines(settings, &styles, lines);
+        let small_caps = synthesize_small_caps
+            && settings.layout.transform == TextTransform::SmallCaps
+            && settings.layout.direction == LayoutDirection::LeftToRight;
+        let first_small_caps = small_caps.then_some(styles.len());
+        if small_caps {
+            styles.extend_from_within(..);
+            for style in &mut styles[first_small_caps.unwrap()..] {
+                *style = style.small_caps();
+            }
+        }
+
+        let lines = lines.iter().map(|line| transform_line(settings.layout.transform, line, first_small_caps)).collect();
+        Self::from_lines(settings, settings.layout.align, styles, lines)
+    }
+
+    fn from_lines(settings: &'a TextSettings<T>, align: LayoutAlign, styles: Vec<GlyphStyle<'a, T>>, mut lines: Vec<Vec<StyledChar>>) -> Self {
+        if lines.is_empty() {
+            lines.push(vec![]);
+        }
+
+        let row_positions = Self::row_positions(settings, &styles, &lines);
+        let row_spacing = match (align, settings.layout.direction) {
+            (LayoutAlign::Center | LayoutAlign::Justify, LayoutDirection::LeftToRight) => Self::row_spacing(settings, align, &styles, &lines),
+            (LayoutAlign::Center | LayoutAlign::End, LayoutDirection::TopToBottom) => Self::column_offsets(settings, align, &styles, &lines),
+            _ => vec![]
+        };
+
+        let mut lines = lines.into_iter();
+        let current_row_text = lines.next().unwrap().into_iter();
+        let either_iters = Self::either_iter_from_chars(Self::is_reversed(settings, align), current_row_text);
+        Self {
+            lines,
+            current_row_text: either_iters,
+            prev_data: None,
+            settings,
+            styles,
+            row: 0,
+            align,
+            row_positions,
+            row_spacing,
+            row_spaces: 0,
+            row_tracking: 0.0
+        }
+    }
+
+    /// Calculate the baseline of each left to right row, or the x coordinate of each top to bottom column, so that it
+    /// fits the largest font on it and the rows before it. Returns `None` if a font doesn't have line spacing.
+    fn row_positions(settings: &TextSettings<T>, styles: &[GlyphStyle<T>], lines: &[Vec<StyledChar>]) -> Option<Vec<f32>> {
+        let layout = &settings.layout;
+        // The distance from the top of the row to its baseline, and from the row before it
+        let row_metrics = |style: &GlyphStyle<T>| match (layout.direction, layout.line_spacing) {
+            (LayoutDirection::LeftToRight, SpacingMode::Constant(spacing)) => Some((spacing, spacing)),
+            (LayoutDirection::LeftToRight, SpacingMode::Scale(scale)) => style.font.horizontal_line_metrics(style.size)
+                .map(|line_metrics| (line_metrics.ascent - line_metrics.descent, line_metrics.new_line_size * scale)),
+            (LayoutDirection::TopToBottom, SpacingMode::Constant(spacing)) => Some((0.0, spacing)),
+            // The scale is applied to the position of each column, as it was before rows had their own metrics
+            (LayoutDirection::TopToBottom, SpacingMode::Scale(_scale)) => style.font.vertical_line_metrics(style.size)
+                .map(|line_metrics| (0.0, line_metrics.new_line_size))
+        };
+
+        let settings_metrics = row_metrics(&settings.style());
+        let mut metrics = Vec::with_capacity(lines.len());
+        for line in lines {
+            let mut line_metrics = settings_metrics?;
+            let mut previous_style = None;
+            for &(_c, style) in line {
+                if previous_style != Some(style) {
+                    let (to_baseline, line_height) = row_metrics(&styles[style])?;
+                    line_metrics = (line_metrics.0.max(to_baseline), line_metrics.1.max(line_height));
+                    previous_style = Some(style);
+                }
+            }
+            metrics.push(line_metrics);
+        }
+
+        // As the baseline grid is measured from the top of the layer, the first baseline is snapped down onto it,
+        // and each row moves down by whole grid lines
+        let grid = layout.baseline_grid.filter(|grid| *grid > 0.0 && layout.direction == LayoutDirection::LeftToRight);
+        let snap = |distance: f32, minimum_lines: f32| grid.map_or(distance, |grid| (distance / grid).ceil().max(minimum_lines) * grid);
+
+        // Rows are moved from the start of each run of rows with the same height, so that rounding errors don't add up
+        let mut positions = Vec::with_capacity(lines.len());
+        let (mut start, mut height, mut rows) = (snap(metrics[0].0, 0.0), 0.0, 0.0);
+        for (row, (_to_baseline, line_height)) in metrics.into_iter().enumerate() {
+            if row > 0 {
+                let line_height = snap(line_height, 1.0);
+                if line_height != height {
+                    start += height * rows;
+                    (height, rows) = (line_height, 0.0);
+                }
+                rows += 1.0;
+            }
+            positions.push(start + height * rows);
+        }
+
+        if let (LayoutDirection::TopToBottom, SpacingMode::Scale(scale)) = (layout.direction, layout.line_spacing) {
+            positions.iter_mut().for_each(|position| *position *= scale);
+        }
+        Some(positions)
+    }
+
+    /// Calculate the offset and extra space after each space of each row, for centered or justified text.
+    fn row_spacing(settings: &'a TextSettings<T>, align: LayoutAlign, styles: &[GlyphStyle<'a, T>], lines: &[Vec<StyledChar>]) -> Vec<(f32, f32)> {
+        // Lay the text out start aligned, to find the visible extent of each row. Errors are returned when the text is laid out again.
+        let mut row_extents: Vec<Option<(isize, isize)>> = vec![None; lines.len()];
+        let mut start_aligned = Self::from_lines(settings, LayoutAlign::Start, styles.to_vec(), lines.to_vec());
+        while let Some(Ok((glyph, style, glyph_x, _glyph_y))) = start_aligned.next_glyph() {
+            if !glyph.is_whitespace() {
+                let glyph_right = glyph_x + style.font.metrics(glyph, style.size).width as isize;
+                let extent = &mut row_extents[start_aligned.row];
+                *extent = Some(extent.map_or((glyph_x, glyph_right), |(left, right)| (left.min(glyph_x), right.max(glyph_right))));
+            }
+        }
+        let block_left = row_extents.iter().flatten().map(|(left, _right)| *left).min().unwrap_or(0);
+        let block_right = row_extents.iter().flatten().map(|(_left, right)| *right).max().unwrap_or(0);
+
+        lines.iter().zip(row_extents).enumerate().map(|(row, (line, extent))| {
+            let Some((row_left, row_right)) = extent else {
+                return (0.0, 0.0)
+            };
+            let spare_width = (block_right - row_right) as f32;
+            if align == LayoutAlign::Center {
+                return ((spare_width - (row_left - block_left) as f32) / 2.0, 0.0)
+            }
+
+            // Spaces after the last word don't move anything that is visible
+            let last_word_end = line.iter().rposition(|(c, _style)| *c != ' ').map_or(0, |index| index + 1);
+            let spaces = line[..last_word_end].iter().filter(|(c, _style)| *c == ' ').count();
+            let paragraph_end = lines.get(row + 1).is_none_or(|next_line| next_line.iter().all(|(c, _style)| c.is_whitespace()));
+            if paragraph_end || spaces == 0 {
+                (0.0, 0.0)
+            } else {
+                (0.0, spare_width / spaces as f32)
+            }
+        }).collect()
+    }
+
+    /// Calculate the offset of the top of each column, for centered or end aligned top to bottom text.
+    fn column_offsets(settings: &'a TextSettings<T>, align: LayoutAlign, styles: &[GlyphStyle<'a, T>], lines: &[Vec<StyledChar>]) -> Vec<(f32, f32)> {
+        // Lay the text out start aligned, to find the visible extent of each column. Errors are returned when the text is laid out again.
+        let mut column_extents: Vec<Option<(isize, isize)>> = vec![None; lines.len()];
+        let mut start_aligned = Self::from_lines(settings, LayoutAlign::Start, styles.to_vec(), lines.to_vec());
+        while let Some(Ok((glyph, style, _glyph_x, glyph_y))) = start_aligned.next_glyph() {
+            if !glyph.is_whitespace() {
+                let glyph_bottom = glyph_y + style.font.metrics(glyph, style.size).height as isize;
+                let extent = &mut column_extents[start_aligned.row];
+                *extent = Some(extent.map_or((glyph_y, glyph_bottom), |(top, bottom)| (top.min(glyph_y), bottom.max(glyph_bottom))));
+            }
+        }
+        let block_top = column_extents.iter().flatten().map(|(top, _bottom)| *top).min().unwrap_or(0);
+        let block_bottom = column_extents.iter().flatten().map(|(_top, bottom)| *bottom).max().unwrap_or(0);
+
+        column_extents.into_iter().map(|extent| {
+            let Some((column_top, column_bottom)) = extent else {
+                return (0.0, 0.0)
+            };
+            let spare_height = (block_bottom - column_bottom) as f32;
+            match align {
+                LayoutAlign::Center => ((spare_height - (column_top - block_top) as f32) / 2.0, 0.0),
+                _ => (spare_height, 0.0)
+            }
+        }).collect()
+    }
+
+    /// Whether each row is laid out from its end, which is only done for end aligned left to right text.
+    fn is_reversed(settings: &TextSettings<T>, align: LayoutAlign) -> bool {
+        align == LayoutAlign::End && settings.layout.direction == LayoutDirection::LeftToRight
+    }
+
+    fn either_iter_from_chars(reversed: bool, chars: IntoIter<StyledChar>) -> either::Either<Rev<IntoIter<StyledChar>>, IntoIter<StyledChar>> {
+        if reversed {
+            either::Either::Left(chars.rev())
+        } else {
+            either::Either::Right(chars)
         }
     }
 
     /// Only used for left to right layouts. Calculate the origin for `next_char` using scaled kerning values.
-    fn calculate_kerned_origin(&self, origin: isize, prev_char: char, next_char: char) -> isize {
+    /// 
+    /// Only glyphs of the same font and size are kerned.
+    fn calculate_kerned_origin(&self, origin: isize, prev_char: char, prev_style: usize, next_char: char, next_style: usize) -> isize {
+        let style = &self.styles[next_style];
+        if !style.kerns_with(&self.styles[prev_style]) {
+            return origin
+        }
+
         // If alignment is `LayoutAlign::End`, then `prev_char` is on the right, and `next_char` is on the left
         // The kern must be negated as it is moving the left character in the opposite direction,
         // instead of moving the right character
-        let kern = match self.settings.layout.align {
-            LayoutAlign::Start => self.settings.font.horizontal_kern(prev_char, next_char, self.settings.size).unwrap_or(0.0),
-            LayoutAlign::End => -self.settings.font.horizontal_kern(next_char, prev_char, self.settings.size).unwrap_or(0.0)
+        let kern = match self.align {
+            LayoutAlign::End => -style.font.horizontal_kern(next_char, prev_char, style.size).unwrap_or(0.0),
+            LayoutAlign::Start | LayoutAlign::Center | LayoutAlign::Justify => {
+                style.font.horizontal_kern(prev_char, next_char, style.size).unwrap_or(0.0)
+            }
         };
 
         if let SpacingMode::Scale(scale) = self.settings.layout.glyph_spacing {
@@ -96,95 +585,113 @@ impl<'a, T: PixelChannel> LayoutIter<'a, T> {
     }
 
     /// Calculate the baseline of the next character
-    fn calculate_baseline(&self, metrics: &Metrics) -> Result<isize, LayoutError> {
+    fn calculate_baseline(&self, metrics: &Metrics, style: &GlyphStyle<T>) -> Result<isize, LayoutError> {
         match self.settings.layout.direction {
-            LayoutDirection::LeftToRight => match self.settings.layout.line_spacing {
-                SpacingMode::Constant(spacing) => Ok((spacing * (self.row + 1) as f32) as isize),
-                SpacingMode::Scale(scale) => match self.settings.font.horizontal_line_metrics(self.settings.size) {
-                    Some(line_metrics) => {
-                        let first_line_height = line_metrics.ascent - line_metrics.descent;
-                        let other_line_height = line_metrics.new_line_size * self.row as f32 * scale;
-                        Ok((first_line_height + other_line_height) as isize)
-                    },
-                    None => Err(LayoutError::MissingLineSpacing)
-                }
+            LayoutDirection::LeftToRight => match &self.row_positions {
+                Some(baselines) => Ok((baselines[self.row] - style.baseline_shift) as isize),
+                None => Err(LayoutError::MissingLineSpacing)
             },
             LayoutDirection::TopToBottom => match self.prev_data {
-                Some((_prev_char, next_origin_y)) => Ok(next_origin_y),
+                Some((_prev_char, _prev_style, next_origin_y)) => Ok(next_origin_y),
                 // Baseline of first character in a column
-                None => Ok(metrics.height as isize + metrics.ymin as isize)
+                None => {
+                    let offset = self.row_spacing.get(self.row).map_or(0, |(offset, _extra_space)| offset.round() as isize);
+                    Ok(metrics.height as isize + metrics.ymin as isize + offset)
+                }
             },
         }
     }
 
-    fn calculate_origin_x(&self, next_char: char) -> Result<isize, LayoutError> {
+    fn calculate_origin_x(&self, next_char: char, next_style: usize) -> Result<isize, LayoutError> {
         match self.settings.layout.direction {
             LayoutDirection::LeftToRight => match self.prev_data {
-                Some((prev_char, next_origin_x)) => match self.settings.layout.use_kern {
-                    true => Ok(self.calculate_kerned_origin(next_origin_x, prev_char, next_char)),
+                Some((prev_char, prev_style, next_origin_x)) => match self.settings.layout.use_kern {
+                    true => Ok(self.calculate_kerned_origin(next_origin_x, prev_char, prev_style, next_char, next_style)),
                     false => Ok(next_origin_x)
                 },
-                None => Ok(0),
+                None => Ok(self.row_spacing.get(self.row).map_or(0, |(offset, _extra_space)| offset.round() as isize)),
             }
-            LayoutDirection::TopToBottom => match self.settings.layout.line_spacing {
-                SpacingMode::Scale(scale) => match self.settings.font.vertical_line_metrics(self.settings.size) {
-                    Some(line_metrics) => Ok((line_metrics.new_line_size * self.row as f32 * scale) as isize),
-                    None => Err(LayoutError::MissingLineSpacing)
-                },
-                SpacingMode::Constant(spacing) => Ok((self.row as f32 * spacing) as isize),
+            LayoutDirection::TopToBottom => match &self.row_positions {
+                Some(columns) => Ok(columns[self.row] as isize),
+                None => Err(LayoutError::MissingLineSpacing)
             },
         }
     }
-}
-
-impl<'a, T: PixelChannel> Iterator for LayoutIter<'a, T> {
-    type Item = Result<(char, isize, isize), LayoutError>;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let next_char = loop {
+    /// Lay out the next character, with its style.
+    pub(crate) fn next_glyph(&mut self) -> Option<Result<StyledGlyph<'a, T>, LayoutError>> {
+        let (next_char, style_index) = loop {
             match self.current_row_text.next() {
                 Some(next_char) => {
                     break next_char;
                 },
                 None => {
-                    self.current_row_text = Self::either_iter_from_chars(self.settings.layout.align, self.lines.next()?.chars());
+                    self.current_row_text = Self::either_iter_from_chars(Self::is_reversed(self.settings, self.align), self.lines.next()?.into_iter());
                     self.row += 1;
                     self.prev_data = None;
+                    self.row_spaces = 0;
+                    self.row_tracking = 0.0;
                 }
             }
         };
 
-        let metrics = self.settings.font.metrics(next_char, self.settings.size);
+        let style = self.styles[style_index];
+        let metrics = style.font.metrics(next_char, style.size);
 
         // Glyph x is the coordinate that the rasterized glyph should be drawn at.
         // It is an offset from the origin by `metrics.xmin`.
-        let unshifted_glyph_x = match self.calculate_origin_x(next_char) {
+        let unshifted_glyph_x = match self.calculate_origin_x(next_char, style_index) {
             Ok(x) => x + metrics.xmin as isize,
             Err(e) => return Some(Err(e))
         };
 
-        let baseline = match self.calculate_baseline(&metrics) {
+        let baseline = match self.calculate_baseline(&metrics, &style) {
             Ok(b) => b,
             Err(e) => return Some(Err(e))
         };
 
         let glyph_y = baseline - metrics.ymin as isize - metrics.height as isize;
 
-        let direction_negation = if matches!(self.settings.layout.align, LayoutAlign::Start) { 1.0 } else { -1.0 };
+        let reversed = Self::is_reversed(self.settings, self.align);
+        let direction_negation = if reversed { -1.0 } else { 1.0 };
 
-        let shifted_glyph_origin = match self.settings.layout.direction {
+        let mut shifted_glyph_origin = match self.settings.layout.direction {
             LayoutDirection::LeftToRight => match self.settings.layout.glyph_spacing {
                 SpacingMode::Scale(scale) => unshifted_glyph_x + (scale * metrics.advance_width.ceil() * direction_negation) as isize,
-                SpacingMode::Constant(spacing) => unshifted_glyph_x + (direction_negation*spacing) as isize,
+                SpacingMode::Constant(spacing) => unshifted_glyph_x + (direction_negation * spacing * style.scale) as isize,
             },
             LayoutDirection::TopToBottom => match self.settings.layout.glyph_spacing {
                 SpacingMode::Scale(scale) => glyph_y + (scale * (metrics.height as f32 + DEFAULT_VERTICAL_SPACING)) as isize,
-                SpacingMode::Constant(spacing) => baseline + spacing as isize,
+                SpacingMode::Constant(spacing) => baseline + (spacing * style.scale) as isize,
             }
         };
 
-        self.prev_data = Some((next_char, shifted_glyph_origin));
+        // Reversed rows start from the right of their last glyph, so the spacing after it is left out
+        let tracking = self.settings.layout.spacing_after(next_char);
+        if tracking != 0.0 && !(reversed && self.prev_data.is_none()) {
+            // Rounded from the start of the row, like the extra space of justified text
+            let step = (self.row_tracking + tracking).round() - self.row_tracking.round();
+            shifted_glyph_origin += (step * direction_negation) as isize;
+            self.row_tracking += tracking;
+        }
+
+        if let (' ', Some((_offset, extra_space))) = (next_char, self.row_spacing.get(self.row)) {
+            // Rounded from the start of the row, so that rounding errors don't add up along it
+            let spaces = self.row_spaces as f32;
+            shifted_glyph_origin += ((spaces + 1.0) * extra_space).round() as isize - (spaces * extra_space).round() as isize;
+            self.row_spaces += 1;
+        }
 
-        Some(Ok((next_char, if matches!(self.settings.layout.align, LayoutAlign::Start) { unshifted_glyph_x } else { shifted_glyph_origin }, glyph_y)))
+        self.prev_data = Some((next_char, style_index, shifted_glyph_origin));
+
+        Some(Ok((next_char, style, if reversed { shifted_glyph_origin } else { unshifted_glyph_x }, glyph_y)))
+    }
+}
+
+impl<T: PixelChannel> Iterator for LayoutIter<'_, T> {
+    type Item = Result<(char, isize, isize), LayoutError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_glyph().map(|glyph| glyph.map(|(glyph, _style, x, y)| (glyph, x, y)))
     }
 }
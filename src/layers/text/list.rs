@@ -0,0 +1,155 @@
+use crate::{
+    Filter,
+    Layer,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    BlendingMethod,
+    Error,
+    layers::text::{TextLayer, TextSettings, layout::{LayoutError, SpacingMode}}
+};
+
+/// The marker drawn before each item of a [`ListLayer`].
+#[derive(Clone)]
+pub enum ListMarker {
+    /// The same character before every item, such as `•` or `-`
+    Bullet(char),
+    /// The number of the item, counting from `start`, followed by `suffix`, such as `1.` or `1)`
+    Numbered { start: usize, suffix: String },
+    /// A function from the index of the item, starting at 0, to its marker
+    Custom(fn(usize) -> String)
+}
+
+impl ListMarker {
+    /// Get the marker text for the item at `index`, starting at 0.
+    pub fn marker_for(&self, index: usize) -> String {
+        match self {
+            ListMarker::Bullet(bullet) => bullet.to_string(),
+            ListMarker::Numbered { start, suffix } => format!("{}{suffix}", start + index),
+            ListMarker::Custom(function) => function(index)
+        }
+    }
+}
+
+/// The appearance of a [`ListLayer`].
+#[derive(Clone)]
+pub struct ListStyle {
+    pub marker: ListMarker,
+    /// Space between the widest marker and the items, in pixels
+    pub marker_gap: usize,
+    /// Extra space between items, in pixels
+    pub item_spacing: usize,
+    /// The maximum width of an item's text before it wraps, in pixels. Wrapped lines are indented to line up with the first line.
+    pub max_width: Option<f32>
+}
+
+impl Default for ListStyle {
+    fn default() -> Self {
+        Self { marker: ListMarker::Bullet('•'), marker_gap: 10, item_spacing: 0, max_width: None }
+    }
+}
+
+/// A bulleted or numbered list, where each line of the text settings is an item.
+/// 
+/// Markers are right aligned in a column, and items have a hanging indent, so that wrapped lines
+/// line up with the start of the item rather than the marker.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use image_template::{Canvas, AlphaPixel, layers::text::{TextSettings, layout::TextLayout, list::{ListLayer, ListStyle, ListMarker}}};
+/// 
+/// let font_bytes = std::fs::read("font.ttf").unwrap();
+/// let font = fontdue::Font::from_bytes(font_bytes, fontdue::FontSettings::default()).unwrap();
+/// 
+/// let tracks = ["Intro", "Daybreak", "Overture"];
+/// let settings = TextSettings {
+///     size: 24.0,
+///     fill: AlphaPixel::white(),
+///     layout: TextLayout::default(),
+///     text: tracks.join("\n"),
+///     font
+/// };
+/// let style = ListStyle { marker: ListMarker::Numbered { start: 1, suffix: String::from(".") }, ..Default::default() };
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(400, 300);
+/// canvas.add_layer(ListLayer::try_new(settings, 20, 20, style).unwrap());
+/// ```
+pub struct ListLayer<T: PixelChannel> {
+    pub filters: Vec<Box<dyn Filter<T>>>,
+    /// The marker and text of each item
+    items: Vec<(TextLayer<T>, TextLayer<T>)>,
+    rect: Rect
+}
+
+impl<T: PixelChannel> ListLayer<T> {
+    /// Create a list with its top left corner at (`x`, `y`). Each line of `settings.text` is one item.
+    pub fn try_new(settings: TextSettings<T>, x: usize, y: usize, style: ListStyle) -> Result<Self, Error> {
+        let line_height = match settings.layout.line_spacing {
+            SpacingMode::Constant(spacing) => spacing,
+            SpacingMode::Scale(scale) => match settings.font.horizontal_line_metrics(settings.size) {
+                Some(line_metrics) => line_metrics.new_line_size * scale,
+                None => return Err(LayoutError::MissingLineSpacing.into())
+            }
+        };
+
+        let item_texts: Vec<String> = settings.text.split('\n').map(String::from).collect();
+        let markers = item_texts.iter().enumerate()
+            .map(|(index, _)| TextLayer::try_new(TextSettings { text: style.marker.marker_for(index), ..settings.clone() }, x, y))
+            .collect::<Result<Vec<TextLayer<T>>, Error>>()?;
+        let marker_width = markers.iter().map(|marker| marker.get_rect().width).max().unwrap_or(0);
+        let item_x = x + marker_width + style.marker_gap;
+
+        let mut items = Vec::with_capacity(item_texts.len());
+        let mut item_y = y as f32;
+        for (text, mut marker) in item_texts.into_iter().zip(markers) {
+            let mut item_settings = TextSettings { text, ..settings.clone() };
+            if let Some(max_width) = style.max_width {
+                item_settings.wrap_text(max_width - (item_x - x) as f32);
+            }
+            let line_count = item_settings.text.split('\n').count();
+
+            marker.x = x + marker_width - marker.get_rect().width;
+            marker.y = item_y as usize;
+            let item = TextLayer::try_new(item_settings, item_x, item_y as usize)?;
+            items.push((marker, item));
+
+            item_y += line_height * line_count as f32 + style.item_spacing as f32;
+        }
+
+        let right_x = items.iter().map(|(_, item)| item.get_rect().right_x()).max().unwrap_or(item_x);
+        let bottom_y = items.iter()
+            .map(|(marker, item)| marker.get_rect().bottom_y().max(item.get_rect().bottom_y()))
+            .max()
+            .unwrap_or(y);
+        let rect = Rect { x, y, width: right_x - x, height: bottom_y - y };
+
+        Ok(Self { filters: vec![], items, rect })
+    }
+
+    /// Get the `Rect` of the text of each item, without its marker.
+    pub fn get_item_rects(&self) -> Vec<Rect> {
+        self.items.iter().map(|(_, item)| item.get_rect()).collect()
+    }
+
+    /// Get the `Rect` of the marker of each item.
+    pub fn get_marker_rects(&self) -> Vec<Rect> {
+        self.items.iter().map(|(marker, _)| marker.get_rect()).collect()
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for ListLayer<T> {
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        self.items.iter()
+            .flat_map(|(marker, item)| [marker.unfiltered_pixel_at(x, y), item.unfiltered_pixel_at(x, y)])
+            .flatten()
+            .fold(AlphaPixel::default(), |background, pixel| BlendingMethod::Over.blend(background, pixel))
+    }
+}
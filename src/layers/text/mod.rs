@@ -1,4 +1,15 @@
 pub mod layout;
+pub mod stroke;
+pub mod list;
+pub mod rich;
+pub mod shadow;
+pub mod decoration;
+pub mod curved;
+#[cfg(feature = "system-fonts")]
+pub mod system_font;
+#[cfg(feature = "fontdue-layout")]
+pub mod fontdue_layout;
+mod glyphs;
 
 use crate::{
     Filter,
@@ -8,12 +19,19 @@ use crate::{
     AlphaPixel,
     PixelChannel,
     Rect,
-    layers::text::layout::{TextLayout, LayoutIter}
+    Error,
+    layers::text::{
+        layout::{TextLayout, LayoutIter, LayoutAlign, LayoutDirection, SpacingMode, VerticalAlign, GlyphStyle, TextPiece, truncate_lines},
+        stroke::{TextStroke, stroke_raster, stroke_padding},
+        rich::StyledText,
+        shadow::{TextShadow, shadow_raster, shadow_margins},
+        decoration::TextDecoration,
+        glyphs::{PositionedGlyph, GlyphDecoration, raster_positioned_glyphs}
+    }
 };
 
 use fontdue::Font;
-use layout::LayoutError;
-use std::{collections::HashMap, iter::repeat};
+use std::borrow::Cow;
 
 #[derive(Clone)]
 pub struct TextSettings<T: PixelChannel> {
@@ -26,27 +44,55 @@ pub struct TextSettings<T: PixelChannel> {
     pub font: Font,
 }
 
+/// Optional styles of a [`TextLayer`], which are drawn around the text after it is laid out.
+/// 
+/// # Example
+/// ```rust,no_run
+/// # use image_template::{AlphaPixel, layers::text::{TextLayer, TextSettings, TextOptions, shadow::TextShadow, decoration::TextDecoration}};
+/// # let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+/// let settings = TextSettings::new("Underlined", font, 40.0, AlphaPixel::<u8>::black());
+/// let options = TextOptions { decoration: Some(TextDecoration::underline()), ..Default::default() };
+/// let text = TextLayer::try_with_options(settings, options, 10, 10).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct TextOptions<T: PixelChannel> {
+    /// A blurred shadow drawn under the text. The raster is made larger to fit it.
+    pub shadow: Option<TextShadow<T>>,
+    /// Underlines and strikethroughs drawn over the text
+    pub decoration: Option<TextDecoration<T>>
+}
+
+impl<T: PixelChannel> Default for TextOptions<T> {
+    fn default() -> Self {
+        Self { shadow: None, decoration: None }
+    }
+}
+
 type SignedCoord = (isize, isize);
-type GlyphPositionMapping = HashMap<char, Vec<SignedCoord>>;
 
 impl<T: PixelChannel> TextSettings<T> {
-    /// Return a HashMap mapping a glyph to a Vec of coordinates, the minimum coordinate, and the maximum coordinate
+    /// Create settings for `text` with the default layout.
+    pub fn new(text: impl Into<String>, font: Font, size: f32, fill: AlphaPixel<T>) -> Self {
+        Self { size, fill, layout: TextLayout::default(), text: text.into(), font }
+    }
+
+    /// Get the style of text which isn't in a span of styled text.
+    pub(crate) fn style(&self) -> GlyphStyle<'_, T> {
+        GlyphStyle { font: &self.font, size: self.size, fill: self.fill, scale: 1.0, baseline_shift: 0.0 }
+    }
+
+    /// Get the minimum and maximum coordinates of the glyphs of `pieces`.
     /// 
     /// Coordinates are `isize` as some glyphs may have negative coordinates.
     /// The minimum coordinates can be used to shift all coordinates to be positive.
-    fn glyph_positions(&self) -> Result<(GlyphPositionMapping, SignedCoord, SignedCoord), LayoutError> {
-        let mut positions: HashMap<char, Vec<(isize, isize)>> = HashMap::with_capacity(self.text.len());
+    fn glyph_extent(&self, pieces: Vec<TextPiece<'_, T>>) -> Result<(SignedCoord, SignedCoord), Error> {
         let mut minimum_coord = (0, 0);
         let mut maximum_coord = (0, 0);
 
-        for layout in LayoutIter::new(self) {
-            let (glyph, glyph_x, glyph_y) = layout?;
-
-            positions.entry(glyph)
-                .and_modify(|coordinates| coordinates.push((glyph_x, glyph_y)))
-                .or_insert_with(|| vec![(glyph_x, glyph_y)]);
-
-            let glyph_metrics = self.font.metrics(glyph, self.size);
+        let mut glyphs = LayoutIter::from_pieces(self, pieces, false);
+        while let Some(layout) = glyphs.next_glyph() {
+            let (glyph, style, glyph_x, glyph_y) = layout?;
+            let glyph_metrics = style.font.metrics(glyph, style.size);
 
             let glyph_greatest_coord = (glyph_x + glyph_metrics.width as isize, glyph_y + glyph_metrics.height as isize);
             maximum_coord.0 = maximum_coord.0.max(glyph_greatest_coord.0);
@@ -56,67 +102,604 @@ impl<T: PixelChannel> TextSettings<T> {
             minimum_coord.1 = minimum_coord.1.min(glyph_y);
         }
 
-        Ok((positions, minimum_coord, maximum_coord))
+        Ok((minimum_coord, maximum_coord))
     }
 
-    /// Create a rasterized image from the text settings
-    pub fn raster_from_settings(&self) -> Result<Image<T>, LayoutError> {
-        let (glyph_positions, minimum_coord, maximum_coord) = self.glyph_positions()?;
-        let final_size = ((maximum_coord.0 - minimum_coord.0) as usize, (maximum_coord.1 - minimum_coord.1) as usize);
-
-        let mut final_image = Image::from_pixels(repeat(AlphaPixel::default()).take(final_size.0*final_size.1).collect(), final_size.0).unwrap();
-
-        for (glyph, coordinates) in glyph_positions.iter() {
-            let (metrics, raster_pixels) = self.font.rasterize(*glyph, self.size);
-            let raster_pixels_rgba = raster_pixels
-                .iter()
-                .map(|p| AlphaPixel { a: T::from_u8(*p).unwrap(), ..self.fill })
-                .collect();
-            let raster_image = Image::from_pixels(raster_pixels_rgba, metrics.width).unwrap();
-            
-            for coordinate in coordinates {
-                final_image.draw_subimage(
-                    &raster_image,
-                    (coordinate.0 - minimum_coord.0) as usize, 
-                    (coordinate.1 - minimum_coord.1) as usize,
-                    BlendingMethod::Over
-                ).unwrap();
+    /// Get the distance from the top of the rasterized `pieces` to the baseline of their last line.
+    fn last_baseline(&self, pieces: Vec<TextPiece<'_, T>>) -> Result<isize, Error> {
+        let mut top = 0;
+        let mut baseline = 0;
+        let mut glyphs = LayoutIter::from_pieces(self, pieces, true);
+        while let Some(layout) = glyphs.next_glyph() {
+            let (glyph, style, _glyph_x, glyph_y) = layout?;
+            let metrics = style.font.metrics(glyph, style.size);
+            top = top.min(glyph_y);
+            baseline = baseline.max(glyph_y + metrics.ymin as isize + metrics.height as isize + style.baseline_shift as isize);
+        }
+        Ok(baseline - top)
+    }
+
+    /// Get the width of a single line of text in pixels, when it is laid out from left to right.
+    /// 
+    /// The text transform is applied before measuring. Small caps are measured as full size uppercase letters.
+    pub fn line_width(&self, line: &str) -> f32 {
+        let mut width = 0.0;
+        let mut prev_char = None;
+
+        for next_char in self.layout.transform.apply(line).chars() {
+            let metrics = self.font.metrics(next_char, self.size);
+
+            if let (Some(prev_char), true) = (prev_char, self.layout.use_kern) {
+                let kern = self.font.horizontal_kern(prev_char, next_char, self.size).unwrap_or(0.0);
+                width += match self.layout.glyph_spacing {
+                    SpacingMode::Scale(scale) => kern * scale,
+                    SpacingMode::Constant(_) => kern
+                };
             }
+
+            width += match self.layout.glyph_spacing {
+                SpacingMode::Scale(scale) => scale * metrics.advance_width.ceil(),
+                SpacingMode::Constant(spacing) => spacing
+            };
+            width += self.layout.spacing_after(next_char);
+            prev_char = Some(next_char);
+        }
+
+        width
+    }
+
+    /// Insert line breaks between words, so that no line is wider than `max_width` pixels.
+    /// 
+    /// Existing line breaks are kept. A single word that is wider than `max_width` is placed on its own line.
+    pub fn wrap_text(&mut self, max_width: f32) {
+        let mut wrapped_lines = vec![];
+
+        for line in self.text.split('\n') {
+            let mut current_line = String::new();
+            for word in line.split(' ') {
+                if current_line.is_empty() {
+                    current_line.push_str(word);
+                    continue
+                }
+
+                let candidate = format!("{current_line} {word}");
+                if self.line_width(&candidate) > max_width {
+                    wrapped_lines.push(current_line);
+                    current_line = word.to_string();
+                } else {
+                    current_line = candidate;
+                }
+            }
+            wrapped_lines.push(current_line);
         }
 
-        Ok(final_image)
+        self.text = wrapped_lines.join("\n");
+    }
+
+    /// Get the text as it is drawn, after it is cut short by the `max_width`, `max_lines` and `overflow` of the layout.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// # use image_template::{AlphaPixel, layers::text::{TextSettings, layout::{TextLayout, TextOverflow}}};
+    /// # let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// let settings: TextSettings<u8> = TextSettings {
+    ///     size: 30.0,
+    ///     fill: AlphaPixel::black(),
+    ///     layout: TextLayout { max_width: Some(200.0), max_lines: Some(1), overflow: TextOverflow::Ellipsis, ..Default::default() },
+    ///     text: String::from("A username which is far too long to fit\nA second line"),
+    ///     font
+    /// };
+    /// assert!(settings.truncated_text().ends_with('…'));
+    /// ```
+    pub fn truncated_text(&self) -> Cow<'_, str> {
+        if self.layout.max_width.is_none() && self.layout.max_lines.is_none() {
+            return Cow::Borrowed(&self.text)
+        }
+
+        let lines = self.text.split('\n').map(|line| line.chars().map(|c| (c, 0)).collect()).collect();
+        let truncated: Vec<String> = truncate_lines(self, &[self.style()], lines).into_iter()
+            .map(|line| line.into_iter().map(|(c, _style)| c).collect())
+            .collect();
+        Cow::Owned(truncated.join("\n"))
+    }
+
+    /// Create a rasterized image from the text settings
+    pub fn raster_from_settings(&self) -> Result<Image<T>, Error> {
+        self.raster_text(None)
+    }
+
+    /// Create a rasterized image from the text settings, with the shadow and decoration of `options`.
+    /// 
+    /// If there is a shadow, the text is moved right and down within the image by the left and top
+    /// margins of the shadow.
+    pub fn raster_with_options(&self, options: &TextOptions<T>) -> Result<Image<T>, Error> {
+        let text = self.raster_text(options.decoration.as_ref())?;
+        Ok(match &options.shadow {
+            Some(shadow) => shadow_raster(&text, shadow),
+            None => text
+        })
+    }
+
+    /// Rasterize the text with a decoration, but without a shadow.
+    fn raster_text(&self, decoration: Option<&TextDecoration<T>>) -> Result<Image<T>, Error> {
+        self.raster_pieces(vec![(&self.text, self.style())], decoration)
+    }
+
+    /// Rasterize pieces of text in their own styles, with a decoration but without a shadow.
+    pub(crate) fn raster_pieces(&self, pieces: Vec<TextPiece<'_, T>>, decoration: Option<&TextDecoration<T>>) -> Result<Image<T>, Error> {
+        let mut layout = LayoutIter::from_pieces(self, pieces, true);
+        let font_glyphs = std::iter::from_fn(|| layout.next_glyph())
+            .map(|glyph| glyph.map(|(glyph, style, x, y)| PositionedGlyph::new(glyph, style.font, style.size, style.fill, x, y)))
+            .collect::<Result<Vec<_>, _>>()?;
+        raster_positioned_glyphs(&font_glyphs, self.fill, self.glyph_decoration(decoration))
+    }
+
+    /// Get the decoration of the glyphs of the text, which is only drawn for left to right text.
+    pub(crate) fn glyph_decoration<'a>(&'a self, decoration: Option<&'a TextDecoration<T>>) -> Option<GlyphDecoration<'a, T>> {
+        match decoration {
+            Some(decoration) if self.layout.direction == LayoutDirection::LeftToRight => {
+                Some(GlyphDecoration { decoration, font: &self.font, size: self.size })
+            },
+            _ => None
+        }
+    }
+}
+
+/// How lines of text are divided between [`TextColumns`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnFlow {
+    /// Divide the lines evenly between all of the columns. When they don't divide evenly, the first columns have one
+    /// more line than the rest.
+    /// 
+    /// If there are fewer lines than columns, each line is in its own column.
+    Balanced,
+    /// Fill each column until it would be taller than this many pixels, before moving on to the next column.
+    /// 
+    /// Lines which don't fit in any column are added to the last column.
+    MaxHeight(usize)
+}
+
+/// Multi-column layout for a [`TextLayer`]. Lines flow from the bottom of one column to the top of the next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextColumns {
+    pub count: usize,
+    /// Horizontal space between columns, in pixels
+    pub gap: usize,
+    pub flow: ColumnFlow
+}
+
+impl TextColumns {
+    pub fn balanced(count: usize, gap: usize) -> Self {
+        Self { count, gap, flow: ColumnFlow::Balanced }
     }
 }
 
 /// A layer representing text. This may be a single character, a single line, or multiple lines.
+/// 
+/// `x` and `y` are the top left of the text. Strokes and shadows extend outside of this, up to the edges of the canvas.
 pub struct TextLayer<T: PixelChannel> {
     settings: TextSettings<T>,
+    /// Spans with their own styles, which are drawn instead of the text of the settings
+    styled: Option<StyledText<T>>,
+    options: TextOptions<T>,
+    strokes: Vec<TextStroke<T>>,
+    columns: Option<TextColumns>,
+    target: Option<(Rect, VerticalAlign)>,
+    /// The text without its strokes or shadow, which is kept so that it can be re-tinted when only the fill changes
+    text: Image<T>,
     rasterized: Image<T>,
+    /// Glyphs laid out by fontdue, which are drawn instead of laying out the text of the settings
+    #[cfg(feature = "fontdue-layout")]
+    fontdue_glyphs: Option<fontdue_layout::FontdueGlyphs>,
     pub x: usize,
     pub y: usize,
     pub filters: Vec<Box<dyn Filter<T>>>
 }
 
 impl<T: PixelChannel> TextLayer<T> {
-    pub fn try_new(settings: TextSettings<T>, x: usize, y: usize) -> Result<Self, LayoutError> {
-        let raster = settings.raster_from_settings()?;
-        Ok(Self { settings, rasterized: raster, x, y, filters: vec![] })
+    /// Lay out and rasterize text, with its top left at (`x`, `y`).
+    /// 
+    /// Text with nothing to draw, such as an empty string or only whitespace, is not an error. It makes a layer which
+    /// draws nothing, and has a zero size rect at (`x`, `y`) if the text has no size. Use [`TextLayer::is_empty`] to check for this.
+    /// 
+    /// Returns [`Error::Layout`] if the text can't be laid out with the font.
+    pub fn try_new(settings: TextSettings<T>, x: usize, y: usize) -> Result<Self, Error> {
+        Self::try_with_options(settings, TextOptions::default(), x, y)
+    }
+
+    /// Lay out and rasterize text with a shadow or decoration, with its top left at (`x`, `y`).
+    pub fn try_with_options(settings: TextSettings<T>, options: TextOptions<T>, x: usize, y: usize) -> Result<Self, Error> {
+        Self::try_with_styled(settings, None, options, x, y)
+    }
+
+    /// Lay out and rasterize [`StyledText`], with its top left at (`x`, `y`). The text of each span is drawn in its
+    /// own font, size and fill, and the settings are used for the rest of the text and the layout.
+    /// 
+    /// The text of the settings is replaced by the text of the spans. Each line is moved down to fit the largest font
+    /// on it, and the rest of the layout, strokes, columns and target apply to the styled text as they do to plain text.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use image_template::{AlphaPixel, layers::text::{TextLayer, TextSettings, rich::{StyledText, TextSpan}}};
+    /// 
+    /// let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// let bold = fontdue::Font::from_bytes(std::fs::read("bold.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// let settings = TextSettings::new("", font, 40.0, AlphaPixel::<u8>::black());
+    /// let text = StyledText::new()
+    ///     .plain("Tickets are ")
+    ///     .span(TextSpan::new("half price").with_font(bold).with_fill(AlphaPixel::red()))
+    ///     .plain(" today");
+    /// let layer = TextLayer::from_styled(settings, text, 20, 20).unwrap();
+    /// ```
+    pub fn from_styled(settings: TextSettings<T>, text: StyledText<T>, x: usize, y: usize) -> Result<Self, Error> {
+        Self::try_with_styled(settings, Some(text), TextOptions::default(), x, y)
+    }
+
+    fn try_with_styled(mut settings: TextSettings<T>, styled: Option<StyledText<T>>, options: TextOptions<T>, x: usize, y: usize) -> Result<Self, Error> {
+        if let Some(text) = &styled {
+            settings.text = text.text();
+        }
+        let mut layer = Self {
+            settings,
+            styled,
+            options,
+            strokes: vec![],
+            columns: None,
+            target: None,
+            text: Image::new(),
+            rasterized: Image::new(),
+            #[cfg(feature = "fontdue-layout")]
+            fontdue_glyphs: None,
+            x,
+            y,
+            filters: vec![]
+        };
+        layer.rasterize()?;
+        Ok(layer)
+    }
+
+    /// Whether the text has nothing to draw, such as when it is empty or only whitespace.
+    pub fn is_empty(&self) -> bool {
+        self.rasterized.get_pixels().iter().all(|pixel| pixel.a == T::zero())
     }
 
     pub fn get_settings(&self) -> &TextSettings<T> {
         &self.settings
     }
 
-    pub fn set_settings(&mut self, settings: TextSettings<T>) -> Result<(), LayoutError> {
+    /// Replace the settings. If the layer has styled text, it is kept, and its text replaces the text of `settings`.
+    pub fn set_settings(&mut self, settings: TextSettings<T>) -> Result<(), Error> {
         self.settings = settings;
-        self.rasterized = self.settings.raster_from_settings()?;
+        if let Some(text) = &self.styled {
+            self.settings.text = text.text();
+        }
+        self.rasterize()
+    }
+
+    /// Get the styled text of the layer, or `None` if it only has the text of its settings.
+    pub fn get_styled_text(&self) -> Option<&StyledText<T>> {
+        self.styled.as_ref()
+    }
+
+    /// Change the text to [`StyledText`], without replacing the rest of the settings.
+    pub fn set_styled_text(&mut self, text: StyledText<T>) -> Result<(), Error> {
+        #[cfg(feature = "fontdue-layout")]
+        self.fontdue_glyphs.take();
+        self.settings.text = text.text();
+        self.styled = Some(text);
+        self.rasterize()
+    }
+
+    pub fn get_options(&self) -> &TextOptions<T> {
+        &self.options
+    }
+
+    /// Change the shadow and decoration of the text.
+    pub fn set_options(&mut self, options: TextOptions<T>) -> Result<(), Error> {
+        self.options = options;
+        self.rasterize()
+    }
+
+    /// Change the text, without replacing the rest of the settings. Styled text is replaced by plain text.
+    pub fn set_text(&mut self, text: impl Into<String>) -> Result<(), Error> {
+        #[cfg(feature = "fontdue-layout")]
+        self.fontdue_glyphs.take();
+        self.styled = None;
+        self.settings.text = text.into();
+        self.rasterize()
+    }
+
+    /// Change the size of the text, in pixels.
+    pub fn set_size(&mut self, size: f32) -> Result<(), Error> {
+        #[cfg(feature = "fontdue-layout")]
+        self.fontdue_glyphs.take();
+        self.settings.size = size;
+        self.rasterize()
+    }
+
+    /// Change the colour of the text.
+    /// 
+    /// This is much faster than changing the fill with [`TextLayer::set_settings`], as the text is re-tinted instead
+    /// of being laid out and rasterized again, so it is suited to rendering many variants of the same text. Only the
+    /// strokes and shadow are redrawn. The text is rasterized again if it has a decoration which can't be re-tinted,
+    /// or styled spans with their own fill.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// # use image_template::{AlphaPixel, layers::text::{TextLayer, TextSettings, layout::TextLayout}};
+    /// # let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// # let settings = TextSettings { size: 80.0, fill: AlphaPixel::<u8>::red(), layout: TextLayout::default(), text: String::from("SALE"), font };
+    /// let mut label = TextLayer::try_new(settings, 20, 20).unwrap();
+    /// // Only the colour changes, so the text isn't laid out again
+    /// label.set_fill(AlphaPixel::blue()).unwrap();
+    /// ```
+    pub fn set_fill(&mut self, fill: AlphaPixel<T>) -> Result<(), Error> {
+        let old_fill = std::mem::replace(&mut self.settings.fill, fill);
+        // Decorations are drawn with the alpha of the fill, or their own colour, so they can't be re-tinted
+        let decoration_changes = self.options.decoration
+            .is_some_and(|decoration| decoration.color.is_some() || fill.a != old_fill.a);
+        // Spans with their own fill keep it
+        let span_fills = self.styled.as_ref().is_some_and(|text| text.spans.iter().any(|span| span.fill.is_some()));
+        if decoration_changes || span_fills {
+            return self.rasterize()
+        }
+
+        // The alpha of the rasterized text is the coverage of the glyphs, so only the colour changes
+        for pixel in self.text.get_pixels_mut() {
+            *pixel = AlphaPixel { a: pixel.a, ..fill };
+        }
+        self.composite()
+    }
+
+    pub fn get_strokes(&self) -> &[TextStroke<T>] {
+        &self.strokes
+    }
+
+    /// Set the outlines around the text, from the innermost to the outermost.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// # use image_template::{AlphaPixel, layers::text::{TextLayer, TextSettings, layout::TextLayout, stroke::TextStroke}};
+    /// # let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// # let settings = TextSettings { size: 80.0, fill: AlphaPixel::<u8>::red(), layout: TextLayout::default(), text: String::from("WOW"), font };
+    /// let mut text = TextLayer::try_new(settings, 50, 50).unwrap();
+    /// // A white inner outline, and a black outer outline
+    /// text.set_strokes(vec![
+    ///     TextStroke::solid(AlphaPixel::white(), 6.0),
+    ///     TextStroke::solid(AlphaPixel::black(), 4.0)
+    /// ]).unwrap();
+    /// ```
+    pub fn set_strokes(&mut self, strokes: Vec<TextStroke<T>>) -> Result<(), Error> {
+        self.strokes = strokes;
+        self.composite()
+    }
+
+    pub fn get_columns(&self) -> Option<TextColumns> {
+        self.columns
+    }
+
+    /// Split the lines of text into columns, or use a single column if `columns` is `None`.
+    /// 
+    /// Every column is as wide as the widest column. Each column is aligned within this width
+    /// by the layout's alignment.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// # use image_template::{AlphaPixel, layers::text::{TextLayer, TextSettings, TextColumns, layout::TextLayout}};
+    /// # let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// let tracks = ["Intro", "Daybreak", "Overture", "Coda"];
+    /// let text = tracks.join("\n");
+    /// # let settings = TextSettings { size: 20.0, fill: AlphaPixel::<u8>::white(), layout: TextLayout::default(), text, font };
+    /// let mut tracklist = TextLayer::try_new(settings, 50, 400).unwrap();
+    /// tracklist.set_columns(Some(TextColumns::balanced(2, 40))).unwrap();
+    /// ```
+    pub fn set_columns(&mut self, columns: Option<TextColumns>) -> Result<(), Error> {
+        self.columns = columns;
+        self.rasterize()
+    }
+
+    pub fn get_target(&self) -> Option<(Rect, VerticalAlign)> {
+        self.target
+    }
+
+    /// Position the text within a `Rect`, or stop positioning it if `target` is `None`.
+    /// 
+    /// The text is aligned vertically by `VerticalAlign`, and horizontally by the layout's alignment, where justified
+    /// text is start aligned. This sets `x` and `y`, and they are updated whenever the text is changed, so that it stays
+    /// aligned in the `Rect`. Text larger than the `Rect` overflows it, and strokes are drawn outside of it.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// # use image_template::{AlphaPixel, Rect, layers::text::{TextLayer, TextSettings, layout::{TextLayout, LayoutAlign, VerticalAlign}}};
+    /// # let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+    /// let layout = TextLayout { align: LayoutAlign::Center, ..Default::default() };
+    /// # let settings = TextSettings { size: 40.0, fill: AlphaPixel::<u8>::white(), layout, text: String::from("Play"), font };
+    /// let mut label = TextLayer::try_new(settings, 0, 0).unwrap();
+    /// // Centered on a 200x60 button
+    /// label.set_target(Some((Rect { x: 100, y: 500, width: 200, height: 60 }, VerticalAlign::Middle))).unwrap();
+    /// ```
+    pub fn set_target(&mut self, target: Option<(Rect, VerticalAlign)>) -> Result<(), Error> {
+        self.target = target;
+        self.position_in_target()
+    }
+
+    fn rasterize(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "fontdue-layout")]
+        if let Some(glyphs) = &self.fontdue_glyphs {
+            self.text = glyphs.raster(&self.settings, self.options.decoration.as_ref())?;
+            return self.composite()
+        }
+
+        // The shadow is cast by the strokes as well as the text, so it is drawn after them
+        self.text = match self.columns {
+            Some(columns) if columns.count > 1 => self.raster_columns(columns),
+            _ => self.settings.raster_pieces(self.pieces(), self.options.decoration.as_ref())
+        }?;
+        self.composite()
+    }
+
+    /// Get the text in each style, from the styled text, or the settings if there is none.
+    fn pieces(&self) -> Vec<TextPiece<'_, T>> {
+        match &self.styled {
+            Some(text) => text.pieces(&self.settings),
+            None => vec![(&self.settings.text, self.settings.style())]
+        }
+    }
+
+    /// Draw the strokes and shadow around the rasterized text.
+    fn composite(&mut self) -> Result<(), Error> {
+        let stroked = stroke_raster(&self.text, &self.strokes);
+        self.rasterized = match &self.options.shadow {
+            Some(shadow) => shadow_raster(&stroked, shadow),
+            None => stroked
+        };
+        self.position_in_target()
+    }
+
+    /// Get the number of pixels that the strokes and shadow extend past the left, top, right and bottom edges of the text.
+    fn margins(&self) -> [usize; 4] {
+        let padding = stroke_padding(&self.strokes);
+        let shadow = self.options.shadow.as_ref().map_or([0; 4], shadow_margins);
+        shadow.map(|margin| margin + padding)
+    }
+
+    /// Move the text to its place in the target `Rect`, if it has one.
+    fn position_in_target(&mut self) -> Result<(), Error> {
+        let Some((rect, vertical_align)) = self.target else {
+            return Ok(())
+        };
+
+        // The size of the text, without strokes or a shadow
+        let [left, top, right, bottom] = if self.is_empty() { [0; 4] } else { self.margins() };
+        let width = self.rasterized.get_width().saturating_sub(left + right) as isize;
+        let height = self.rasterized.get_height().saturating_sub(top + bottom) as isize;
+
+        let spare_width = rect.width as isize - width;
+        let x = rect.x as isize + match self.settings.layout.align {
+            LayoutAlign::Start | LayoutAlign::Justify => 0,
+            LayoutAlign::Center => spare_width / 2,
+            LayoutAlign::End => spare_width
+        };
+
+        let spare_height = rect.height as isize - height;
+        let y = rect.y as isize + match vertical_align {
+            VerticalAlign::Top => 0,
+            VerticalAlign::Middle => spare_height / 2,
+            VerticalAlign::Baseline if self.settings.layout.direction == LayoutDirection::LeftToRight => {
+                rect.height as isize - self.last_baseline()?
+            },
+            VerticalAlign::Bottom | VerticalAlign::Baseline => spare_height
+        };
+
+        // Text can't be drawn above or to the left of the canvas
+        self.x = x.max(0) as usize;
+        self.y = y.max(0) as usize;
         Ok(())
     }
+
+    /// Get the distance from the top of the text to the baseline of its last line, or of the first column's
+    /// last line when it is split into columns, as the first column is the tallest.
+    fn last_baseline(&self) -> Result<isize, Error> {
+        match self.columns {
+            Some(columns) if columns.count > 1 => {
+                let first_column = self.split_columns(columns)?.into_iter().next().unwrap_or_default();
+                self.settings.last_baseline(first_column)
+            },
+            _ => self.settings.last_baseline(self.pieces())
+        }
+    }
+
+    /// Rasterize each column of text, and draw them next to each other.
+    fn raster_columns(&self, columns: TextColumns) -> Result<Image<T>, Error> {
+        let rasters = self.split_columns(columns)?.into_iter()
+            .map(|column| self.settings.raster_pieces(column, self.options.decoration.as_ref()))
+            .collect::<Result<Vec<Image<T>>, Error>>()?;
+
+        let column_width = rasters.iter().map(|raster| raster.get_width()).max().unwrap_or(0);
+        let height = rasters.iter().map(|raster| raster.get_height()).max().unwrap_or(0);
+        let width = column_width*rasters.len() + columns.gap*(rasters.len().saturating_sub(1));
+
+        let mut image = Image::new_with_fill(AlphaPixel::default(), width, height);
+        for (index, raster) in rasters.iter().enumerate() {
+            let mut column_x = index * (column_width + columns.gap);
+            match self.settings.layout.align {
+                LayoutAlign::End => column_x += column_width - raster.get_width(),
+                LayoutAlign::Center => column_x += (column_width - raster.get_width()) / 2,
+                LayoutAlign::Start | LayoutAlign::Justify => {}
+            }
+            image.draw_subimage(raster, column_x, 0, BlendingMethod::Replace)?;
+        }
+
+        Ok(image)
+    }
+
+    /// Divide the lines of the text between columns, as the pieces of text in each column.
+    fn split_columns<'a>(&'a self, columns: TextColumns) -> Result<Vec<Vec<TextPiece<'a, T>>>, Error> {
+        let mut lines: Vec<Vec<TextPiece<'a, T>>> = vec![vec![]];
+        for (text, style) in self.pieces() {
+            for (index, line) in text.split('\n').enumerate() {
+                if index > 0 {
+                    lines.push(vec![]);
+                }
+                lines.last_mut().unwrap().push((line, style));
+            }
+        }
+        // The lines of a column are joined by line breaks in the style of the settings
+        let line_break = ("\n", self.settings.style());
+        let join = |group: &[Vec<TextPiece<'a, T>>]| group.join(&line_break);
+
+        let groups: Vec<Vec<TextPiece<T>>> = match columns.flow {
+            ColumnFlow::Balanced => {
+                // The first columns take one more line each when the lines don't divide evenly
+                let count = columns.count.clamp(1, lines.len());
+                let (lines_per_column, remainder) = (lines.len() / count, lines.len() % count);
+                let mut remaining = &lines[..];
+                (0..count).map(|column| {
+                    let (group, rest) = remaining.split_at(lines_per_column + usize::from(column < remainder));
+                    remaining = rest;
+                    join(group)
+                }).collect()
+            },
+            ColumnFlow::MaxHeight(max_height) => {
+                let mut groups: Vec<Vec<Vec<TextPiece<T>>>> = vec![vec![]];
+                for line in lines {
+                    let column_count = groups.len();
+                    let current = groups.last_mut().unwrap();
+                    current.push(line);
+
+                    let (minimum_coord, maximum_coord) = self.settings.glyph_extent(join(current))?;
+                    let column_height = (maximum_coord.1 - minimum_coord.1) as usize;
+
+                    // Move the line to a new column if it doesn't fit, unless it's alone in its column
+                    if column_height > max_height && current.len() > 1 && column_count < columns.count {
+                        let line = current.pop().unwrap();
+                        groups.push(vec![line]);
+                    }
+                }
+                groups.iter().map(|group| join(group)).collect()
+            }
+        };
+
+        Ok(groups)
+    }
 }
 
 impl<T: PixelChannel> Layer<T> for TextLayer<T> {
     fn get_rect(&self) -> Rect {
-        Rect { x: self.x, y: self.y, width: self.rasterized.get_width(), height: self.rasterized.get_height() }
+        if self.rasterized.get_width() == 0 || self.rasterized.get_height() == 0 {
+            return Rect { x: self.x, y: self.y, width: 0, height: 0 }
+        }
+
+        // The strokes and shadow are cut off at the top and left edges of the canvas
+        let [left, top, ..] = self.margins();
+        let x = self.x.saturating_sub(left);
+        let y = self.y.saturating_sub(top);
+        Rect {
+            x,
+            y,
+            width: self.rasterized.get_width() - (left - (self.x - x)),
+            height: self.rasterized.get_height() - (top - (self.y - y))
+        }
     }
 
     fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
@@ -124,6 +707,63 @@ impl<T: PixelChannel> Layer<T> for TextLayer<T> {
     }
 
     fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
-        self.rasterized.pixel_at(x-self.x, y-self.y).unwrap()
+        let [left, top, ..] = self.margins();
+        self.rasterized.pixel_at(x + left - self.x, y + top - self.y).unwrap()
+    }
+
+    fn scaled(&self, factor: f32) -> Option<Box<dyn Layer<T>>> {
+        let scale_spacing = |spacing: SpacingMode| match spacing {
+            SpacingMode::Constant(spacing) => SpacingMode::Constant(spacing*factor),
+            scale => scale
+        };
+        let scale_whole = |value: usize| (value as f32 * factor).round() as usize;
+
+        let mut settings = self.settings.clone();
+        settings.size *= factor;
+        settings.layout.line_spacing = scale_spacing(settings.layout.line_spacing);
+        settings.layout.glyph_spacing = scale_spacing(settings.layout.glyph_spacing);
+        settings.layout.letter_spacing *= factor;
+        settings.layout.word_spacing *= factor;
+        settings.layout.baseline_grid = settings.layout.baseline_grid.map(|grid| grid*factor);
+        settings.layout.max_width = settings.layout.max_width.map(|max_width| max_width*factor);
+        let mut options = self.options.clone();
+        if let Some(decoration) = &mut options.decoration {
+            decoration.thickness = decoration.thickness.map(|thickness| thickness * factor);
+        }
+        options.shadow = options.shadow.map(|shadow| TextShadow {
+            offset: ((shadow.offset.0 as f32 * factor).round() as isize, (shadow.offset.1 as f32 * factor).round() as isize),
+            radius: scale_whole(shadow.radius),
+            color: shadow.color
+        });
+
+        let (x, y) = (scale_whole(self.x), scale_whole(self.y));
+        #[cfg(feature = "fontdue-layout")]
+        let layer = match &self.fontdue_glyphs {
+            Some(glyphs) => TextLayer::from_fontdue_glyphs(glyphs.scaled(factor), settings, options, x, y),
+            None => TextLayer::try_with_options(settings, options, x, y)
+        };
+        #[cfg(not(feature = "fontdue-layout"))]
+        let layer = TextLayer::try_with_options(settings, options, x, y);
+        let mut layer = layer.ok()?;
+        if let Some(text) = &self.styled {
+            let mut text = text.clone();
+            for span in &mut text.spans {
+                span.size = span.size.map(|size| size * factor);
+            }
+            layer.set_styled_text(text).ok()?;
+        }
+        let strokes = self.strokes.iter().map(|stroke| TextStroke { width: stroke.width*factor, fill: stroke.fill.clone() }).collect();
+        layer.set_strokes(strokes).ok()?;
+        let columns = self.columns.map(|columns| TextColumns {
+            gap: scale_whole(columns.gap),
+            flow: match columns.flow {
+                ColumnFlow::MaxHeight(max_height) => ColumnFlow::MaxHeight(scale_whole(max_height)),
+                ColumnFlow::Balanced => ColumnFlow::Balanced
+            },
+            ..columns
+        });
+        layer.set_columns(columns).ok()?;
+        layer.set_target(self.target.map(|(rect, align)| (rect.scale(factor), align))).ok()?;
+        Some(Box::new(layer))
     }
 }
@@ -0,0 +1,255 @@
+use fontdue::Font;
+
+use crate::{
+    Filter,
+    Layer,
+    Image,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    Error,
+    layers::text::{
+        TextSettings,
+        TextOptions,
+        shadow::{shadow_margins, shadow_raster},
+        layout::{GlyphStyle, TextPiece}
+    }
+};
+
+/// A piece of text within a [`RichTextLayer`], with its own fill, font, scale and vertical offset.
+#[derive(Clone)]
+pub struct TextRun<T> {
+    pub text: String,
+    /// The fill of this run. If `None`, the fill of the layer's settings is used.
+    pub fill: Option<AlphaPixel<T>>,
+    /// The font of this run. If `None`, the font of the layer's settings is used.
+    pub font: Option<Font>,
+    /// The size of this run, relative to the size of the layer's settings
+    pub scale: f32,
+    /// How far the baseline of this run is raised, relative to the size of the layer's settings.
+    /// Negative values lower the run.
+    pub baseline_shift: f32
+}
+
+impl<T> TextRun<T> {
+    pub fn plain(text: &str) -> Self {
+        Self { text: String::from(text), fill: None, font: None, scale: 1.0, baseline_shift: 0.0 }
+    }
+
+    /// A smaller run, raised above the baseline, for ordinals ("1st"), footnotes and prices.
+    pub fn superscript(text: &str) -> Self {
+        Self { text: String::from(text), fill: None, font: None, scale: 0.6, baseline_shift: 0.35 }
+    }
+
+    /// A smaller run, lowered below the baseline, for chemical formulas and indices.
+    pub fn subscript(text: &str) -> Self {
+        Self { text: String::from(text), fill: None, font: None, scale: 0.6, baseline_shift: -0.15 }
+    }
+}
+
+/// A piece of [`StyledText`]. Any style that is `None` is taken from the `TextSettings` of the layer.
+#[derive(Clone)]
+pub struct TextSpan<T> {
+    pub text: String,
+    pub font: Option<Font>,
+    /// The size of the span in pixels
+    pub size: Option<f32>,
+    pub fill: Option<AlphaPixel<T>>
+}
+
+impl<T> TextSpan<T> {
+    pub fn new(text: &str) -> Self {
+        Self { text: String::from(text), font: None, size: None, fill: None }
+    }
+
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn with_fill(mut self, fill: AlphaPixel<T>) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+}
+
+/// Text made of [`TextSpan`]s with different fonts, sizes and fills, such as a paragraph with a bold word,
+/// which is drawn by a single [`TextLayer`](super::TextLayer) with [`TextLayer::from_styled`](super::TextLayer::from_styled),
+/// or laid out by [`LayoutIter::styled`](super::layout::LayoutIter::styled).
+/// 
+/// # Example
+/// ```rust,no_run
+/// use image_template::{AlphaPixel, layers::text::rich::{StyledText, TextSpan}};
+/// 
+/// let bold = fontdue::Font::from_bytes(std::fs::read("bold.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+/// let text = StyledText::<u8>::new()
+///     .plain("Tickets are ")
+///     .span(TextSpan::new("half price").with_font(bold).with_fill(AlphaPixel::red()))
+///     .plain(" today");
+/// assert_eq!(text.text(), "Tickets are half price today");
+/// ```
+#[derive(Clone)]
+pub struct StyledText<T> {
+    pub spans: Vec<TextSpan<T>>
+}
+
+impl<T> Default for StyledText<T> {
+    fn default() -> Self {
+        Self { spans: vec![] }
+    }
+}
+
+impl<T> StyledText<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a span in the style of the layer's settings.
+    pub fn plain(self, text: &str) -> Self {
+        self.span(TextSpan::new(text))
+    }
+
+    pub fn span(mut self, span: TextSpan<T>) -> Self {
+        self.spans.push(span);
+        self
+    }
+
+    /// Get the text of all the spans, without styles.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
+impl<T: PixelChannel> StyledText<T> {
+    /// Get the text of each span in its style, using `settings` for any style that the span doesn't set.
+    pub(crate) fn pieces<'a>(&'a self, settings: &'a TextSettings<T>) -> Vec<TextPiece<'a, T>> {
+        self.spans.iter().map(|span| (span.text.as_str(), GlyphStyle {
+            font: span.font.as_ref().unwrap_or(&settings.font),
+            size: span.size.unwrap_or(settings.size),
+            fill: span.fill.unwrap_or(settings.fill),
+            scale: span.size.map_or(1.0, |size| size / settings.size),
+            baseline_shift: 0.0
+        })).collect()
+    }
+}
+
+/// A layer of text made of [`TextRun`]s, which can each have a different fill, font, scale and baseline.
+/// 
+/// The default font, size and fill, and the layout, are taken from the `TextSettings`. Its `text` is not used.
+/// Runs can contain line breaks, and aren't wrapped, so [`TextSettings::wrap_text`] has no effect. The runs are laid
+/// out by [`LayoutIter`](super::layout::LayoutIter), as the text of a [`TextLayer`](super::TextLayer) is, so each line
+/// is moved down to fit the largest font on it, and the layout is followed. The baseline shift of each run only moves
+/// left to right text. The shadow and decoration of the options are drawn as they are for a `TextLayer`.
+/// 
+/// # Example
+/// ```rust,no_run
+/// use image_template::{AlphaPixel, layers::text::{TextSettings, rich::{RichTextLayer, TextRun}}};
+/// 
+/// let font_bytes = std::fs::read("font.ttf").unwrap();
+/// let font = fontdue::Font::from_bytes(font_bytes, fontdue::FontSettings::default()).unwrap();
+/// let settings = TextSettings::new("", font, 40.0, AlphaPixel::<u8>::black());
+/// 
+/// // "$9.99" with the cents raised
+/// let price = RichTextLayer::try_new(settings, vec![TextRun::plain("$9"), TextRun::superscript("99")], 10, 10).unwrap();
+/// ```
+pub struct RichTextLayer<T: PixelChannel> {
+    settings: TextSettings<T>,
+    options: TextOptions<T>,
+    runs: Vec<TextRun<T>>,
+    rasterized: Image<T>,
+    pub x: usize,
+    pub y: usize,
+    pub filters: Vec<Box<dyn Filter<T>>>
+}
+
+impl<T: PixelChannel> RichTextLayer<T> {
+    pub fn try_new(settings: TextSettings<T>, runs: Vec<TextRun<T>>, x: usize, y: usize) -> Result<Self, Error> {
+        Self::try_with_options(settings, TextOptions::default(), runs, x, y)
+    }
+
+    /// Create a layer with the decoration of `options` drawn under or through the runs.
+    pub fn try_with_options(settings: TextSettings<T>, options: TextOptions<T>, runs: Vec<TextRun<T>>, x: usize, y: usize) -> Result<Self, Error> {
+        let mut layer = Self { settings, options, runs, rasterized: Image::new(), x, y, filters: vec![] };
+        layer.rasterize()?;
+        Ok(layer)
+    }
+
+    pub fn get_settings(&self) -> &TextSettings<T> {
+        &self.settings
+    }
+
+    pub fn get_options(&self) -> &TextOptions<T> {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: TextOptions<T>) -> Result<(), Error> {
+        self.options = options;
+        self.rasterize()
+    }
+
+    pub fn get_runs(&self) -> &[TextRun<T>] {
+        &self.runs
+    }
+
+    pub fn set_runs(&mut self, runs: Vec<TextRun<T>>) -> Result<(), Error> {
+        self.runs = runs;
+        self.rasterize()
+    }
+
+    fn rasterize(&mut self) -> Result<(), Error> {
+        let settings = &self.settings;
+        let pieces = self.runs.iter().map(|run| (run.text.as_str(), GlyphStyle {
+            font: run.font.as_ref().unwrap_or(&settings.font),
+            size: settings.size * run.scale,
+            fill: run.fill.unwrap_or(settings.fill),
+            scale: run.scale,
+            baseline_shift: run.baseline_shift * settings.size
+        })).collect();
+
+        let text = settings.raster_pieces(pieces, self.options.decoration.as_ref())?;
+        self.rasterized = match &self.options.shadow {
+            Some(shadow) => shadow_raster(&text, shadow),
+            None => text
+        };
+        Ok(())
+    }
+
+    /// Get the number of pixels that the shadow extends past the left and top edges of the text.
+    fn margins(&self) -> [usize; 2] {
+        let [left, top, ..] = self.options.shadow.as_ref().map_or([0; 4], shadow_margins);
+        [left, top]
+    }
+}
+
+impl<T: PixelChannel> Layer<T> for RichTextLayer<T> {
+    fn get_rect(&self) -> Rect {
+        if self.rasterized.get_width() == 0 || self.rasterized.get_height() == 0 {
+            return Rect { x: self.x, y: self.y, width: 0, height: 0 }
+        }
+
+        // The shadow is cut off at the top and left edges of the canvas
+        let [left, top] = self.margins();
+        let x = self.x.saturating_sub(left);
+        let y = self.y.saturating_sub(top);
+        Rect {
+            x,
+            y,
+            width: self.rasterized.get_width() - (left - (self.x - x)),
+            height: self.rasterized.get_height() - (top - (self.y - y))
+        }
+    }
+
+    fn get_filters(&self) -> &[Box<dyn Filter<T>>] {
+        &self.filters
+    }
+
+    fn unfiltered_pixel_at_unchecked(&self, x: usize, y: usize) -> AlphaPixel<T> {
+        let [left, top] = self.margins();
+        self.rasterized.pixel_at(x + left - self.x, y + top - self.y).unwrap()
+    }
+}
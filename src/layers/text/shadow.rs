@@ -0,0 +1,99 @@
+use crate::{Image, AlphaPixel, PixelChannel, Rect, BlendingMethod, bitmap::integral::IntegralImage};
+
+/// A blurred copy of text, drawn underneath it.
+#[derive(Clone, Copy)]
+pub struct TextShadow<T> {
+    /// The distance that the shadow is moved right and down from the text, in pixels
+    pub offset: (isize, isize),
+    /// The blur radius of the shadow, in pixels. A radius of 0 is a hard shadow.
+    pub radius: usize,
+    pub color: AlphaPixel<T>
+}
+
+impl<T> TextShadow<T> {
+    pub fn new(color: AlphaPixel<T>, offset: (isize, isize), radius: usize) -> Self {
+        Self { offset, radius, color }
+    }
+}
+
+/// Get the number of pixels that a shadow extends past the left, top, right and bottom edges of the text.
+pub(crate) fn shadow_margins<T>(shadow: &TextShadow<T>) -> [usize; 4] {
+    let radius = shadow.radius as isize;
+    [
+        (radius - shadow.offset.0).max(0) as usize,
+        (radius - shadow.offset.1).max(0) as usize,
+        (radius + shadow.offset.0).max(0) as usize,
+        (radius + shadow.offset.1).max(0) as usize
+    ]
+}
+
+/// Draw a shadow under a rasterized text image.
+/// 
+/// The returned image is larger than `text` by [`shadow_margins`], unless `text` is empty,
+/// when there is nothing to cast a shadow and it is returned unchanged.
+pub(crate) fn shadow_raster<T: PixelChannel>(text: &Image<T>, shadow: &TextShadow<T>) -> Image<T> {
+    if text.get_width() == 0 || text.get_height() == 0 {
+        return text.clone()
+    }
+
+    let [left, top, right, bottom] = shadow_margins(shadow);
+    let width = text.get_width() + left + right;
+    let height = text.get_height() + top + bottom;
+    let integral = IntegralImage::new(text);
+    let radius = shadow.radius as isize;
+    let area = ((2*radius + 1) * (2*radius + 1)) as f64;
+
+    let mut image = Image::from_function(width, height, |x, y| {
+        // The center of the blurred square in the text image, which may be outside of it
+        let text_x = x as isize - left as isize - shadow.offset.0;
+        let text_y = y as isize - top as isize - shadow.offset.1;
+        let (square_left, square_top) = ((text_x - radius).max(0), (text_y - radius).max(0));
+        let (square_right, square_bottom) = (text_x + radius + 1, text_y + radius + 1);
+        if square_right <= square_left || square_bottom <= square_top {
+            return AlphaPixel::default()
+        }
+
+        // Pixels outside of the text are transparent, so they are counted in the area but add nothing to the sum
+        let square = Rect {
+            x: square_left as usize,
+            y: square_top as usize,
+            width: (square_right - square_left) as usize,
+            height: (square_bottom - square_top) as usize
+        };
+        let coverage = integral.sum(square)[3] / area;
+        shadow.color.scale_alpha(coverage as f32)
+    });
+    image.draw_subimage(text, left, top, BlendingMethod::Over).unwrap();
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn margins() {
+        assert_eq!(shadow_margins(&TextShadow::new(AlphaPixel::<u8>::black(), (3, -1), 2)), [0, 3, 5, 1]);
+        assert_eq!(shadow_margins(&TextShadow::new(AlphaPixel::<u8>::black(), (0, 0), 4)), [4; 4]);
+    }
+
+    #[test]
+    fn blurred_shadow() {
+        let text: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 3, 3);
+        let shadow = shadow_raster(&text, &TextShadow::new(AlphaPixel::black(), (2, 0), 1));
+        assert_eq!((shadow.get_width(), shadow.get_height()), (6, 5));
+
+        // The text is drawn over the shadow, which extends to the right of it
+        assert_eq!(shadow.pixel_at(2, 2).unwrap(), AlphaPixel::red());
+        assert_eq!(shadow.pixel_at(3, 2).unwrap(), AlphaPixel::black());
+        assert_eq!(shadow.pixel_at(4, 2).unwrap(), rgba!(0, 0, 0, 170));
+        assert_eq!(shadow.pixel_at(5, 2).unwrap(), rgba!(0, 0, 0, 85));
+        assert_eq!(shadow.pixel_at(5, 0).unwrap(), rgba!(0, 0, 0, 28));
+        assert_eq!(shadow.pixel_at(3, 0).unwrap(), rgba!(0, 0, 0, 85));
+        assert_eq!(shadow.pixel_at(0, 0).unwrap().a, 0);
+
+        let empty: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 0, 0);
+        assert_eq!(shadow_raster(&empty, &TextShadow::new(AlphaPixel::black(), (2, 2), 2)).get_width(), 0);
+    }
+}
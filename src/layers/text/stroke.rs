@@ -0,0 +1,161 @@
+use crate::{Image, AlphaPixel, PixelChannel, Rect, BlendingMethod, bitmap::ramp::ColorRamp, layers::shapes::linear_gradient_position};
+
+/// The colour of a [`TextStroke`].
+#[derive(Clone)]
+pub enum StrokeFill<T> {
+    Solid(AlphaPixel<T>),
+    /// A linear gradient across the whole stroked text, in the direction of `angle`,
+    /// in degrees clockwise from left to right. An angle of 90 goes from top to bottom.
+    Gradient { ramp: ColorRamp<T>, angle: f32 }
+}
+
+/// An outline around text.
+/// 
+/// A text layer can have multiple strokes, which are stacked outwards from the text. Each stroke's
+/// `width` is measured from the outside edge of the stroke before it, so a white stroke followed by a
+/// black stroke gives a white inner outline and a black outer outline.
+#[derive(Clone)]
+pub struct TextStroke<T> {
+    /// Width of the stroke in pixels
+    pub width: f32,
+    pub fill: StrokeFill<T>
+}
+
+impl<T: PixelChannel> TextStroke<T> {
+    pub fn solid(color: AlphaPixel<T>, width: f32) -> Self {
+        Self { width, fill: StrokeFill::Solid(color) }
+    }
+
+    /// Create a stroke with a gradient from `start` to `end`.
+    pub fn gradient(start: AlphaPixel<T>, end: AlphaPixel<T>, angle: f32, width: f32) -> Self {
+        Self::gradient_ramp(ColorRamp::two_color(start, end), angle, width)
+    }
+
+    pub fn gradient_ramp(ramp: ColorRamp<T>, angle: f32, width: f32) -> Self {
+        Self { width, fill: StrokeFill::Gradient { ramp, angle } }
+    }
+}
+
+/// Get the number of pixels that `strokes` extend past the edge of the text.
+pub(crate) fn stroke_padding<T>(strokes: &[TextStroke<T>]) -> usize {
+    strokes.iter().map(|stroke| stroke.width.max(0.0)).sum::<f32>().ceil() as usize
+}
+
+/// Draw `strokes` around a rasterized text image.
+/// 
+/// The returned image is larger than `text` by [`stroke_padding`] on every side, unless `text` is empty,
+/// when there is nothing to stroke and it is returned unchanged.
+pub(crate) fn stroke_raster<T: PixelChannel>(text: &Image<T>, strokes: &[TextStroke<T>]) -> Image<T> {
+    let padding = stroke_padding(strokes);
+    if padding == 0 || text.get_width() == 0 || text.get_height() == 0 {
+        return text.clone()
+    }
+
+    let width = text.get_width() + 2*padding;
+    let height = text.get_height() + 2*padding;
+    let distances = distances_to_text(text, padding);
+    let bounds = Rect { x: 0, y: 0, width, height };
+
+    // Draw the outer strokes first, so that inner strokes are drawn over them
+    let mut outer_edges: Vec<f32> = strokes.iter()
+        .scan(0.0, |edge, stroke| { *edge += stroke.width.max(0.0); Some(*edge) })
+        .collect();
+    outer_edges.reverse();
+
+    let mut image = Image::new_with_fill(AlphaPixel::default(), width, height);
+    for (stroke, outer_edge) in strokes.iter().rev().zip(outer_edges) {
+        for y in 0..height {
+            for x in 0..width {
+                // The edge of the text is half a pixel from the center of the nearest pixel inside the text
+                let distance = distances[y*width + x];
+                let coverage = (outer_edge + 1.0 - distance).clamp(0.0, 1.0);
+                if coverage == 0.0 {
+                    continue
+                }
+
+                let color = match &stroke.fill {
+                    StrokeFill::Solid(color) => *color,
+                    StrokeFill::Gradient { ramp, angle } => ramp.color_at(linear_gradient_position(bounds, *angle, x, y))
+                };
+                let pixel = image.pixel_at_mut(x, y).unwrap();
+                *pixel = BlendingMethod::Over.blend(*pixel, color.scale_alpha(coverage));
+            }
+        }
+    }
+
+    image.draw_subimage(text, padding, padding, BlendingMethod::Over).unwrap();
+    image
+}
+
+/// For each pixel of the padded image, get the distance to the center of the nearest pixel which is
+/// at least half covered by text. Pixels further than `padding` from the text have a distance of `f32::INFINITY`.
+fn distances_to_text<T: PixelChannel>(text: &Image<T>, padding: usize) -> Vec<f32> {
+    let width = text.get_width() + 2*padding;
+    let height = text.get_height() + 2*padding;
+    let half_alpha = T::MAX_PIXEL_VALUE.into() / 2.0;
+    let is_text = |x: isize, y: isize| {
+        x >= padding as isize && y >= padding as isize &&
+            text.pixel_at(x as usize - padding, y as usize - padding).is_some_and(|pixel| pixel.a.into() >= half_alpha)
+    };
+
+    // Offsets within the padding, nearest first, so that the search can stop at the first pixel of text
+    let radius = padding as isize + 1;
+    let mut offsets: Vec<(isize, isize, f32)> = (-radius..=radius)
+        .flat_map(|offset_y| (-radius..=radius).map(move |offset_x| (offset_x, offset_y, ((offset_x*offset_x + offset_y*offset_y) as f32).sqrt())))
+        .filter(|offset| offset.2 <= radius as f32)
+        .collect();
+    offsets.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+    let mut distances = Vec::with_capacity(width*height);
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let distance = offsets.iter()
+                .find(|(offset_x, offset_y, _)| is_text(x + offset_x, y + offset_y))
+                .map_or(f32::INFINITY, |offset| offset.2);
+            distances.push(distance);
+        }
+    }
+
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dot() -> Image<u8> {
+        Image::new_with_fill(AlphaPixel::red(), 2, 2)
+    }
+
+    #[test]
+    fn no_strokes() {
+        let raster = stroke_raster(&dot(), &[]);
+        assert_eq!(raster.get_width(), 2);
+        assert_eq!(raster.pixel_at(0, 0).unwrap(), AlphaPixel::red());
+    }
+
+    #[test]
+    fn stacked_strokes() {
+        let strokes = [TextStroke::solid(AlphaPixel::white(), 2.0), TextStroke::solid(AlphaPixel::black(), 3.0)];
+        let raster = stroke_raster(&dot(), &strokes);
+        assert_eq!(stroke_padding(&strokes), 5);
+        assert_eq!(raster.get_width(), 12);
+
+        // The text is drawn over the strokes
+        assert_eq!(raster.pixel_at(5, 5).unwrap(), AlphaPixel::red());
+        // The inner stroke is white, and the outer stroke is black
+        assert_eq!(raster.pixel_at(3, 5).unwrap(), AlphaPixel::white());
+        assert_eq!(raster.pixel_at(1, 5).unwrap(), AlphaPixel::black());
+        assert_eq!(raster.pixel_at(0, 0).unwrap().a, 0);
+    }
+
+    #[test]
+    fn gradient_stroke() {
+        let strokes = [TextStroke::gradient(AlphaPixel::white(), AlphaPixel::black(), 90.0, 4.0)];
+        let raster = stroke_raster(&dot(), &strokes);
+
+        let top = raster.pixel_at(4, 0).unwrap();
+        let bottom = raster.pixel_at(4, 9).unwrap();
+        assert!(top.r > 200 && bottom.r < 50);
+    }
+}
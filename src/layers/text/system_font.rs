@@ -0,0 +1,109 @@
+use std::sync::OnceLock;
+use fontdb::{Database, Family, Query, Weight, Style, Stretch};
+use fontdue::{Font, FontSettings};
+use thiserror::Error;
+use crate::{AlphaPixel, PixelChannel, Error, layers::text::{TextSettings, layout::TextLayout}};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SystemFontError {
+    #[error("No system font was found in the family \"{0}\"")]
+    NotFound(String),
+    #[error("The system font \"{family}\" couldn't be loaded: {reason}")]
+    Invalid { family: String, reason: &'static str }
+}
+
+/// The weight and slant of a font in a family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FontStyle {
+    #[default]
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic
+}
+
+/// The fonts installed on the system, which are found the first time a system font is loaded.
+fn system_fonts() -> &'static Database {
+    static DATABASE: OnceLock<Database> = OnceLock::new();
+    DATABASE.get_or_init(|| {
+        let mut database = Database::new();
+        database.load_system_fonts();
+        database
+    })
+}
+
+/// Find and load an installed font by its family name, such as "Arial" or "DejaVu Sans".
+/// 
+/// The generic families "serif", "sans-serif", "cursive", "fantasy" and "monospace" are also accepted, and are looked up
+/// as common fonts such as "Times New Roman" and "Arial". If the family has no font in `style`, the closest style is used,
+/// as in CSS.
+/// 
+/// The installed fonts are only searched the first time this is called, so fonts installed after that aren't found.
+pub fn load_system_font(family: &str, style: FontStyle) -> Result<Font, SystemFontError> {
+    let families = [match family {
+        "serif" => Family::Serif,
+        "sans-serif" => Family::SansSerif,
+        "cursive" => Family::Cursive,
+        "fantasy" => Family::Fantasy,
+        "monospace" => Family::Monospace,
+        name => Family::Name(name)
+    }];
+    let query = Query {
+        families: &families,
+        weight: if matches!(style, FontStyle::Bold | FontStyle::BoldItalic) { Weight::BOLD } else { Weight::NORMAL },
+        stretch: Stretch::Normal,
+        style: if matches!(style, FontStyle::Italic | FontStyle::BoldItalic) { Style::Italic } else { Style::Normal }
+    };
+
+    let database = system_fonts();
+    let id = database.query(&query).ok_or_else(|| SystemFontError::NotFound(family.to_string()))?;
+    database
+        .with_face_data(id, |data, index| Font::from_bytes(data, FontSettings { collection_index: index, ..Default::default() }))
+        .ok_or_else(|| SystemFontError::NotFound(family.to_string()))?
+        .map_err(|reason| SystemFontError::Invalid { family: family.to_string(), reason })
+}
+
+impl<T: PixelChannel> TextSettings<T> {
+    /// Create text settings with an installed font, found by [`load_system_font`], so that the font file doesn't need
+    /// to be bundled with the template.
+    /// 
+    /// The text is empty, black and 16 pixels tall, with the default layout, so the other settings will usually be set with
+    /// struct update syntax.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use image_template::{AlphaPixel, layers::text::{TextLayer, TextSettings, system_font::FontStyle}};
+    /// 
+    /// let settings = TextSettings {
+    ///     size: 40.0,
+    ///     fill: AlphaPixel::<u8>::white(),
+    ///     text: String::from("Headline"),
+    ///     ..TextSettings::with_system_font("Arial", FontStyle::Bold).unwrap()
+    /// };
+    /// let headline = TextLayer::try_new(settings, 20, 20).unwrap();
+    /// ```
+    pub fn with_system_font(family: &str, style: FontStyle) -> Result<Self, Error> {
+        Ok(Self {
+            size: 16.0,
+            fill: AlphaPixel::black(),
+            layout: TextLayout::default(),
+            text: String::new(),
+            font: load_system_font(family, style)?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_family() {
+        let missing = load_system_font("No Such Font Family 12345", FontStyle::Italic);
+        assert_eq!(missing.err(), Some(SystemFontError::NotFound(String::from("No Such Font Family 12345"))));
+        assert!(matches!(
+            TextSettings::<u8>::with_system_font("No Such Font Family 12345", FontStyle::Regular),
+            Err(Error::SystemFont(SystemFontError::NotFound(_)))
+        ));
+    }
+}
@@ -7,12 +7,21 @@
 //! for graphics not included in this library. Some examples of layers are [`ImageLayer`](crate::layers::image::ImageLayer)
 //! and [`TextLayer`](crate::layers::text::TextLayer).
 //! 
+//! Layers are normally blended over the layers beneath them one pixel at a time. A layer can instead
+//! [use the backdrop](Layer::uses_backdrop), to sample any part of the [`Backdrop`] that has already been composited,
+//! such as [`BackdropBlurLayer`](crate::layers::backdrop_blur::BackdropBlurLayer).
+//! 
 //! # Filters
 //! Filters can be added to layers to manipulate their output. They implement the [`Filter`] trait.
 //! A filter can modify the pixel, or the coordinate that the pixel is sampled from.
 //! Some examples of filters provided by this library are [`MatrixTransform`](crate::filters::transform::MatrixTransform)
 //! and [`BrightnessFilter`](crate::filters::brightness::BrightnessFilter).
 //! 
+//! # Randomness
+//! Procedural layers and filters, such as [`GlitchFilter`](crate::filters::retro::GlitchFilter), take a seed so that
+//! renders are reproducible. A [`Canvas`] stores a seed, and [`Canvas::rng`] creates an [`Rng`] that can be used to
+//! seed each procedural layer or filter.
+//! 
 //! # Image
 //! [`Image`] is a bitmap image which stores a `Vec` of pixels. This is the main way that images are represented in this library.
 //! Image implements `AsRef<[u8]>`, which can be used to get a slice of bytes representing the pixels.
@@ -37,12 +46,34 @@
 #[cfg(feature = "image-crate")]
 pub use image::ImageFormat;
 
+/// Check an internal invariant, such as a pixel's channels being in range, with the `strict` feature.
+/// 
+/// These checks are skipped by default, in debug and release builds, as they run for every pixel. The `strict` feature
+/// enables them, so that a bug which produces invalid pixels from untrusted input panics instead of producing a broken
+/// image.
+macro_rules! strict_assert {
+    ($($arg: tt)*) => {
+        if cfg!(feature = "strict") {
+            assert!($($arg)*);
+        }
+    };
+}
+
 mod canvas;
-pub use canvas::Canvas;
+pub use canvas::{Canvas, Backdrop, LayerSelector, Supersampling, BlendSpace};
 
 mod rect;
 pub use rect::Rect;
 
+mod error;
+pub use error::Error;
+
+mod debug;
+pub use debug::LayerOverlap;
+
+pub mod rng;
+pub use rng::Rng;
+
 pub mod bitmap;
 pub use bitmap::{
     pixel::{
@@ -56,4 +87,18 @@ pub mod layers;
 pub use layers::Layer;
 
 pub mod filters;
-pub use filters::Filter;
\ No newline at end of file
+pub use filters::{Filter, FilterContext};
+pub mod registry;
+pub mod cache;
+pub mod presets;
+pub mod manifest;
+mod json;
+pub use json::JsonError;
+pub mod figma;
+pub mod lottie;
+pub mod limits;
+#[cfg(feature = "ora")]
+pub mod ora;
+#[cfg(feature = "web")]
+pub mod web;
+pub mod templates;
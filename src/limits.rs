@@ -0,0 +1,132 @@
+//! Limits on the resources that a canvas can use, for rendering templates from untrusted input, such as on a server.
+//! 
+//! When a [`Canvas`](crate::Canvas) has [`Limits`], [`Canvas::try_add_layer`](crate::Canvas::try_add_layer) and
+//! [`Canvas::try_flatten`](crate::Canvas::try_flatten) return a [`LimitError`] instead of using unbounded memory.
+//! [`FigmaImporter::with_limits`](crate::figma::FigmaImporter::with_limits) applies limits to the imported canvas and
+//! its text, [`LottieAnimation::with_limits`](crate::lottie::LottieAnimation::with_limits) applies them to rendered
+//! frames, and images can be decoded with [`Image::load_from_memory_with_limits`](crate::Image::load_from_memory_with_limits).
+
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum LimitError {
+    #[error("A canvas of {width}x{height} pixels is larger than the limit of {max_pixels} pixels")]
+    CanvasTooLarge { width: usize, height: usize, max_pixels: usize },
+    #[error("A canvas can't have more than {0} layers")]
+    TooManyLayers(usize),
+    #[error("Text of {length} characters is longer than the limit of {max_length} characters")]
+    TextTooLong { length: usize, max_length: usize },
+    #[error("An image of {width}x{height} pixels is larger than the limit of {max_width}x{max_height} pixels")]
+    ImageTooLarge { width: usize, height: usize, max_width: usize, max_height: usize }
+}
+
+/// The largest canvases, images and text that can be rendered, and the most layers.
+/// 
+/// The default limits allow a canvas of up to 64 megapixels, which is 256 MB with 8 bit channels, with up to 1,000 layers.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Canvas, Rect, AlphaPixel, Error, layers::shapes::RectangleLayer, limits::{Limits, LimitError}};
+/// 
+/// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 100);
+/// canvas.limits = Some(Limits { max_layers: 1, ..Default::default() });
+/// 
+/// let square = || RectangleLayer::new(AlphaPixel::red(), Rect { x: 10, y: 10, width: 20, height: 20 });
+/// assert!(canvas.try_add_layer(square()).is_ok());
+/// assert!(matches!(canvas.try_add_layer(square()), Err(Error::Limit(LimitError::TooManyLayers(1)))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// The most pixels in a canvas, which is its width multiplied by its height
+    pub max_canvas_pixels: usize,
+    pub max_layers: usize,
+    /// The most characters in the text of a layer
+    pub max_text_length: usize,
+    /// The largest width and height of an image which can be decoded
+    pub max_decode_dimensions: (usize, usize)
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_canvas_pixels: 8192 * 8192,
+            max_layers: 1000,
+            max_text_length: 10_000,
+            max_decode_dimensions: (8192, 8192)
+        }
+    }
+}
+
+impl Limits {
+    /// Limits which allow anything.
+    pub fn unlimited() -> Self {
+        Self {
+            max_canvas_pixels: usize::MAX,
+            max_layers: usize::MAX,
+            max_text_length: usize::MAX,
+            max_decode_dimensions: (usize::MAX, usize::MAX)
+        }
+    }
+
+    pub fn check_canvas(&self, width: usize, height: usize) -> Result<(), LimitError> {
+        match width.checked_mul(height) {
+            Some(pixels) if pixels <= self.max_canvas_pixels => Ok(()),
+            _ => Err(LimitError::CanvasTooLarge { width, height, max_pixels: self.max_canvas_pixels })
+        }
+    }
+
+    /// Check that there can be `count` layers.
+    pub fn check_layer_count(&self, count: usize) -> Result<(), LimitError> {
+        if count > self.max_layers {
+            Err(LimitError::TooManyLayers(self.max_layers))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the length of some text, in characters.
+    pub fn check_text(&self, text: &str) -> Result<(), LimitError> {
+        let length = text.chars().count();
+        if length > self.max_text_length {
+            Err(LimitError::TextTooLong { length, max_length: self.max_text_length })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the dimensions of an image before it is decoded.
+    pub fn check_decode(&self, width: usize, height: usize) -> Result<(), LimitError> {
+        let (max_width, max_height) = self.max_decode_dimensions;
+        if width > max_width || height > max_height {
+            Err(LimitError::ImageTooLarge { width, height, max_width, max_height })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks() {
+        let limits = Limits { max_canvas_pixels: 100, max_layers: 2, max_text_length: 3, max_decode_dimensions: (10, 20) };
+        assert_eq!(limits.check_canvas(10, 10), Ok(()));
+        assert_eq!(limits.check_canvas(10, 11), Err(LimitError::CanvasTooLarge { width: 10, height: 11, max_pixels: 100 }));
+        assert!(limits.check_canvas(usize::MAX, 2).is_err());
+
+        assert_eq!(limits.check_layer_count(2), Ok(()));
+        assert_eq!(limits.check_layer_count(3), Err(LimitError::TooManyLayers(2)));
+
+        assert_eq!(limits.check_text("äöü"), Ok(()));
+        assert_eq!(limits.check_text("abcd"), Err(LimitError::TextTooLong { length: 4, max_length: 3 }));
+
+        assert_eq!(limits.check_decode(10, 20), Ok(()));
+        assert!(limits.check_decode(11, 1).is_err());
+
+        let unlimited = Limits::unlimited();
+        assert!(unlimited.check_canvas(usize::MAX, 1).is_ok());
+        assert!(unlimited.check_text("anything").is_ok());
+    }
+}
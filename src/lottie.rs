@@ -0,0 +1,693 @@
+//! Import a single frame of a Lottie animation, as exported from After Effects by Bodymovin, so that motion design
+//! assets can be used in static templates.
+//! 
+//! Shape layers and solid layers are imported, with their transforms and parents. These shapes are supported:
+//! - Rectangles, with rounded corners, ellipses and paths
+//! - Solid fills, with their fill rule, and solid strokes
+//! - Groups, with their transforms and opacity
+//! 
+//! Fills and strokes apply to the shapes listed before them in their group, including shapes in nested groups, as they
+//! do in After Effects. Animated properties are interpolated linearly between keyframes, without easing.
+//! Other layers, such as images, text and precompositions, and other shapes, such as gradients, trims and repeaters,
+//! are skipped, as are masks, mattes and effects.
+
+use thiserror::Error;
+use crate::{
+    Canvas,
+    AlphaPixel,
+    PixelChannel,
+    Error,
+    limits::Limits,
+    json::{JsonValue, JsonError},
+    layers::{
+        path::{Path, PathLayer},
+        shapes::FillRule,
+        metadata::AnnotatedLayer
+    }
+};
+
+/// The metadata key of the name of the Lottie layer that a layer was imported from.
+pub const LOTTIE_NAME_KEY: &str = "lottie_name";
+
+/// The distance of the control points of a cubic curve from its ends, relative to the radius,
+/// that best approximates a quarter of a circle
+const CIRCLE_CONTROL: f32 = 0.552_284_8;
+
+/// The deepest that layers can be parented, so that a loop of parents is an error rather than a stack overflow.
+const MAX_PARENT_DEPTH: usize = 64;
+
+/// The largest width or height of an animation. This is far larger than any real animation, so that a malformed
+/// size is an error rather than a canvas too large to render.
+const MAX_DIMENSION: f64 = 65_536.0;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LottieError {
+    #[error(transparent)]
+    Json(#[from] JsonError),
+    #[error("The animation is missing \"{0}\"")]
+    MissingField(&'static str),
+    #[error("Invalid value for \"{0}\"")]
+    InvalidValue(&'static str)
+}
+
+/// A parsed Lottie animation, which can be rendered at any frame.
+/// 
+/// # Example
+/// ```
+/// use image_template::{AlphaPixel, lottie::LottieAnimation};
+/// 
+/// // A red square which moves from left to right over 10 frames
+/// let json = r#"{
+///     "v": "5.7.0", "fr": 30, "ip": 0, "op": 10, "w": 100, "h": 50,
+///     "layers": [{
+///         "ty": 4, "nm": "Square", "ip": 0, "op": 10,
+///         "ks": { "p": { "a": 1, "k": [{ "t": 0, "s": [10, 25] }, { "t": 10, "s": [90, 25] }] } },
+///         "shapes": [
+///             { "ty": "rc", "p": { "a": 0, "k": [0, 0] }, "s": { "a": 0, "k": [20, 20] }, "r": { "a": 0, "k": 0 } },
+///             { "ty": "fl", "c": { "a": 0, "k": [1, 0, 0, 1] }, "o": { "a": 0, "k": 100 } }
+///         ]
+///     }]
+/// }"#;
+/// 
+/// let animation = LottieAnimation::parse(json).unwrap();
+/// assert_eq!((animation.width, animation.height), (100, 50));
+/// let middle = animation.render::<u8>(5.0).unwrap().flatten();
+/// assert_eq!(middle.pixel_at(50, 25).unwrap(), AlphaPixel::red());
+/// assert_eq!(middle.pixel_at(15, 25).unwrap(), AlphaPixel::default());
+/// ```
+pub struct LottieAnimation {
+    pub width: usize,
+    pub height: usize,
+    /// Frames per second
+    pub frame_rate: f32,
+    /// The first frame of the animation
+    pub in_point: f32,
+    /// The frame after the last frame of the animation
+    pub out_point: f32,
+    layers: Vec<JsonValue>,
+    limits: Option<Limits>
+}
+
+impl LottieAnimation {
+    /// Parse an animation. Returns an error if its width or height is negative, or larger than 65,536.
+    pub fn parse(json: &str) -> Result<Self, LottieError> {
+        let root = JsonValue::parse(json)?;
+        let number = |key: &'static str| root.get(key).and_then(JsonValue::as_f64).ok_or(LottieError::MissingField(key));
+        let dimension = |key: &'static str| match number(key)? {
+            // NaN isn't in the range
+            dimension if (0.0..=MAX_DIMENSION).contains(&dimension) => Ok(dimension as usize),
+            _ => Err(LottieError::InvalidValue(key))
+        };
+        let layers = root.get("layers").and_then(JsonValue::as_array).ok_or(LottieError::MissingField("layers"))?;
+
+        Ok(Self {
+            width: dimension("w")?,
+            height: dimension("h")?,
+            frame_rate: number("fr")? as f32,
+            in_point: number("ip")? as f32,
+            out_point: number("op")? as f32,
+            layers: layers.to_vec(),
+            limits: None
+        })
+    }
+
+    /// Return an error instead of rendering a frame which is larger, or has more layers, than `limits` allow.
+    /// The rendered canvas also has these limits, so they are checked when it is flattened.
+    /// 
+    /// This should be used when rendering animations from untrusted users.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Error, lottie::LottieAnimation, limits::{Limits, LimitError}};
+    /// 
+    /// let json = r#"{ "fr": 30, "ip": 0, "op": 10, "w": 50000, "h": 50000, "layers": [] }"#;
+    /// let animation = LottieAnimation::parse(json).unwrap().with_limits(Limits::default());
+    /// assert!(matches!(animation.render::<u8>(0.0), Err(Error::Limit(LimitError::CanvasTooLarge { .. }))));
+    /// ```
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Render a frame as a canvas the size of the animation, with a layer for each fill and stroke.
+    /// 
+    /// The frame doesn't have to be a whole number. Layers which aren't visible at the frame are skipped.
+    pub fn render<T: PixelChannel>(&self, frame: f32) -> Result<Canvas<T>, Error> {
+        let mut canvas = Canvas::from_dimensions(self.width, self.height);
+        if let Some(limits) = &self.limits {
+            limits.check_canvas(canvas.width, canvas.height)?;
+        }
+        canvas.limits = self.limits;
+
+        // The first layer is drawn on top
+        for layer in self.layers.iter().rev() {
+            let number = |key: &str| layer.get(key).and_then(JsonValue::as_f64).map(|number| number as f32);
+            let visible = number("ip").is_none_or(|in_point| frame >= in_point)
+                && number("op").is_none_or(|out_point| frame < out_point)
+                && layer.get("hd").and_then(JsonValue::as_bool) != Some(true);
+            if !visible {
+                continue
+            }
+
+            let (matrix, opacity) = self.layer_transform(layer, frame, 0)?;
+            let mut shapes = match layer.get("ty").and_then(JsonValue::as_f64) {
+                Some(1.0) => solid_shapes(layer)?,
+                Some(4.0) => {
+                    let items = layer.get("shapes").and_then(JsonValue::as_array).unwrap_or_default();
+                    group_shapes(items, frame)?.1
+                },
+                _ => continue
+            };
+
+            let name = layer.get("nm").and_then(JsonValue::as_str).unwrap_or_default();
+            // Shapes are listed from the top down
+            shapes.reverse();
+            for shape in shapes {
+                let path = subpaths_to_path(&shape.subpaths, matrix);
+                let path_layer = match shape.paint {
+                    Paint::Fill { color, rule } => {
+                        let mut path_layer = PathLayer::new(path).with_fill(scale_alpha(color, shape.opacity * opacity).as_different_channel());
+                        path_layer.fill_rule = rule;
+                        path_layer
+                    },
+                    Paint::Stroke { color, width } => PathLayer::new(path)
+                        .with_stroke(scale_alpha(color, shape.opacity * opacity).as_different_channel(), width * matrix.scale_factor())
+                };
+                canvas.try_add_layer(AnnotatedLayer::new(path_layer).with(LOTTIE_NAME_KEY, name))?;
+            }
+        }
+
+        Ok(canvas)
+    }
+
+    /// Get the transform of a layer, including the transforms of its parents, and its opacity.
+    fn layer_transform(&self, layer: &JsonValue, frame: f32, depth: usize) -> Result<(Affine, f32), LottieError> {
+        if depth > MAX_PARENT_DEPTH {
+            return Err(LottieError::InvalidValue("parent"))
+        }
+
+        let (matrix, opacity) = match layer.get("ks") {
+            Some(transform) => read_transform(transform, frame),
+            None => (Affine::IDENTITY, 1.0)
+        };
+        let Some(parent_index) = layer.get("parent").and_then(JsonValue::as_f64) else {
+            return Ok((matrix, opacity))
+        };
+
+        let parent = self.layers.iter()
+            .find(|parent| parent.get("ind").and_then(JsonValue::as_f64) == Some(parent_index))
+            .ok_or(LottieError::InvalidValue("parent"))?;
+        // The opacity of a parent doesn't affect its children
+        let (parent_matrix, _parent_opacity) = self.layer_transform(parent, frame, depth + 1)?;
+        Ok((parent_matrix.then(matrix), opacity))
+    }
+}
+
+fn scale_alpha(color: AlphaPixel<f32>, factor: f32) -> AlphaPixel<f32> {
+    AlphaPixel { a: (color.a * factor).clamp(0.0, 1.0), ..color }
+}
+
+/// A 2D affine transform, which maps (x, y) to (a*x + c*y + e, b*x + d*y + f).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Affine {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32
+}
+
+impl Affine {
+    const IDENTITY: Self = Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 };
+
+    fn translate(x: f32, y: f32) -> Self {
+        Self { e: x, f: y, ..Self::IDENTITY }
+    }
+
+    fn scale(x: f32, y: f32) -> Self {
+        Self { a: x, d: y, ..Self::IDENTITY }
+    }
+
+    /// A clockwise rotation on the canvas, where y points down
+    fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self { a: cos, b: sin, c: -sin, d: cos, e: 0.0, f: 0.0 }
+    }
+
+    /// Get the transform which applies `inner`, then this transform.
+    fn then(self, inner: Self) -> Self {
+        Self {
+            a: self.a * inner.a + self.c * inner.b,
+            b: self.b * inner.a + self.d * inner.b,
+            c: self.a * inner.c + self.c * inner.d,
+            d: self.b * inner.c + self.d * inner.d,
+            e: self.a * inner.e + self.c * inner.f + self.e,
+            f: self.b * inner.e + self.d * inner.f + self.f
+        }
+    }
+
+    fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f)
+    }
+
+    /// The factor that lengths are scaled by, on average
+    fn scale_factor(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+/// Get the value of an animated property at `frame`, as a list of numbers.
+/// 
+/// Keyframes are interpolated linearly. Before the first keyframe and after the last, the value is held.
+fn property(object: &JsonValue, key: &str, frame: f32) -> Option<Vec<f32>> {
+    let numbers = |value: &JsonValue| match value {
+        JsonValue::Number(number) => Some(vec![*number as f32]),
+        JsonValue::Array(values) => values.iter().map(|value| value.as_f64().map(|number| number as f32)).collect(),
+        _ => None
+    };
+    keyframed(object.get(key)?, frame, numbers, |start, end, progress| {
+        (start.len() == end.len()).then(|| start.iter().zip(&end).map(|(start, end)| start + (end - start) * progress).collect())
+    })
+}
+
+/// Get the value of a property at `frame`, using `read` to read values and `lerp` to interpolate between them.
+/// If `lerp` returns `None`, the value of the earlier keyframe is held.
+fn keyframed<V: Clone>(
+    property: &JsonValue,
+    frame: f32,
+    read: impl Fn(&JsonValue) -> Option<V>,
+    lerp: impl Fn(&V, V, f32) -> Option<V>
+) -> Option<V> {
+    let value = property.get("k")?;
+    let keyframes = match value.as_array() {
+        Some(keyframes) if keyframes.first().is_some_and(|keyframe| keyframe.get("t").is_some()) => keyframes,
+        _ => return read(value)
+    };
+
+    // The value at each keyframe, which is the end value of the previous keyframe in older files
+    let mut values: Vec<(f32, Option<V>, bool)> = vec![];
+    for (index, keyframe) in keyframes.iter().enumerate() {
+        let time = keyframe.get("t").and_then(JsonValue::as_f64)? as f32;
+        let start = keyframe.get("s").or_else(|| keyframes.get(index.wrapping_sub(1))?.get("e"));
+        // Values in keyframes are sometimes wrapped in a single element array
+        let start = start.and_then(|start| read(start).or_else(|| read(start.index(0)?)));
+        let hold = keyframe.get("h").and_then(JsonValue::as_f64) == Some(1.0);
+        values.push((time, start, hold));
+    }
+
+    let current = values.iter().rposition(|(time, value, _hold)| *time <= frame && value.is_some());
+    let Some(current) = current else {
+        return values.iter().find_map(|(_time, value, _hold)| value.clone())
+    };
+    let (start_time, start, hold) = values[current].clone();
+    let start = start?;
+    match values.get(current + 1) {
+        Some((end_time, Some(end), false)) | Some((end_time, Some(end), true)) if !hold && *end_time > start_time => {
+            let progress = (frame - start_time) / (end_time - start_time);
+            Some(lerp(&start, end.clone(), progress.clamp(0.0, 1.0)).unwrap_or(start))
+        },
+        _ => Some(start)
+    }
+}
+
+/// Read a transform, from the `ks` of a layer or a `tr` shape, as a matrix and an opacity from 0 to 1.
+fn read_transform(transform: &JsonValue, frame: f32) -> (Affine, f32) {
+    let pair = |key: &str, default: f32| {
+        let value = property(transform, key, frame).unwrap_or_default();
+        (value.first().copied().unwrap_or(default), value.get(1).copied().unwrap_or(default))
+    };
+
+    let position = match transform.get("p") {
+        // The position can be split into separately animated x and y
+        Some(position) if position.get("s").and_then(JsonValue::as_bool) == Some(true) => {
+            let axis = |key: &str| property(position, key, frame).and_then(|value| value.first().copied()).unwrap_or(0.0);
+            (axis("x"), axis("y"))
+        },
+        _ => pair("p", 0.0)
+    };
+    let anchor = pair("a", 0.0);
+    let scale = pair("s", 100.0);
+    let rotation = property(transform, "r", frame)
+        .or_else(|| property(transform, "rz", frame))
+        .and_then(|value| value.first().copied())
+        .unwrap_or(0.0);
+    let opacity = property(transform, "o", frame).and_then(|value| value.first().copied()).unwrap_or(100.0);
+
+    let matrix = Affine::translate(position.0, position.1)
+        .then(Affine::rotate(rotation))
+        .then(Affine::scale(scale.0 / 100.0, scale.1 / 100.0))
+        .then(Affine::translate(-anchor.0, -anchor.1));
+    (matrix, (opacity / 100.0).clamp(0.0, 1.0))
+}
+
+/// A subpath of cubic curves, with its start point and, for each curve, its control points and end point.
+#[derive(Debug, Clone, PartialEq)]
+struct Subpath {
+    start: (f32, f32),
+    curves: Vec<[(f32, f32); 3]>,
+    closed: bool
+}
+
+impl Subpath {
+    fn transformed(&self, matrix: Affine) -> Self {
+        Self {
+            start: matrix.apply(self.start),
+            curves: self.curves.iter().map(|curve| curve.map(|point| matrix.apply(point))).collect(),
+            closed: self.closed
+        }
+    }
+}
+
+fn subpaths_to_path(subpaths: &[Subpath], matrix: Affine) -> Path {
+    let mut path = Path::new();
+    for subpath in subpaths {
+        let subpath = subpath.transformed(matrix);
+        path = path.move_to(subpath.start.0, subpath.start.1);
+        for [control1, control2, end] in subpath.curves {
+            path = path.cubic_to(control1.0, control1.1, control2.0, control2.1, end.0, end.1);
+        }
+        if subpath.closed {
+            path = path.close();
+        }
+    }
+    path
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Paint {
+    Fill { color: AlphaPixel<f32>, rule: FillRule },
+    Stroke { color: AlphaPixel<f32>, width: f32 }
+}
+
+/// Subpaths painted by a fill or a stroke, in the coordinates of a layer, and the opacity of the groups they are in.
+#[derive(Debug, Clone, PartialEq)]
+struct PaintedShape {
+    subpaths: Vec<Subpath>,
+    paint: Paint,
+    opacity: f32
+}
+
+/// A straight line between two points, as a cubic curve.
+fn line(start: (f32, f32), end: (f32, f32)) -> [(f32, f32); 3] {
+    [start, end, end]
+}
+
+fn rectangle(center: (f32, f32), size: (f32, f32), radius: f32) -> Subpath {
+    let (half_width, half_height) = (size.0.abs() / 2.0, size.1.abs() / 2.0);
+    let radius = radius.max(0.0).min(half_width).min(half_height);
+    let (left, top) = (center.0 - half_width, center.1 - half_height);
+    let (right, bottom) = (center.0 + half_width, center.1 + half_height);
+    if radius == 0.0 {
+        return Subpath {
+            start: (right, top),
+            curves: vec![line((right, top), (right, bottom)), line((right, bottom), (left, bottom)), line((left, bottom), (left, top))],
+            closed: true
+        }
+    }
+
+    let control = radius * (1.0 - CIRCLE_CONTROL);
+    Subpath {
+        start: (right - radius, top),
+        curves: vec![
+            [(right - control, top), (right, top + control), (right, top + radius)],
+            line((right, top + radius), (right, bottom - radius)),
+            [(right, bottom - control), (right - control, bottom), (right - radius, bottom)],
+            line((right - radius, bottom), (left + radius, bottom)),
+            [(left + control, bottom), (left, bottom - control), (left, bottom - radius)],
+            line((left, bottom - radius), (left, top + radius)),
+            [(left, top + control), (left + control, top), (left + radius, top)]
+        ],
+        closed: true
+    }
+}
+
+fn ellipse(center: (f32, f32), size: (f32, f32)) -> Subpath {
+    let (radius_x, radius_y) = (size.0.abs() / 2.0, size.1.abs() / 2.0);
+    let (control_x, control_y) = (radius_x * CIRCLE_CONTROL, radius_y * CIRCLE_CONTROL);
+    let (x, y) = center;
+    Subpath {
+        start: (x, y - radius_y),
+        curves: vec![
+            [(x + control_x, y - radius_y), (x + radius_x, y - control_y), (x + radius_x, y)],
+            [(x + radius_x, y + control_y), (x + control_x, y + radius_y), (x, y + radius_y)],
+            [(x - control_x, y + radius_y), (x - radius_x, y + control_y), (x - radius_x, y)],
+            [(x - radius_x, y - control_y), (x - control_x, y - radius_y), (x, y - radius_y)]
+        ],
+        closed: true
+    }
+}
+
+/// Read the vertices `v`, and the in and out tangents `i` and `o` relative to each vertex, of a path.
+fn read_path(value: &JsonValue) -> Option<Subpath> {
+    let points = |key: &str| -> Option<Vec<(f32, f32)>> {
+        value.get(key)?.as_array()?.iter()
+            .map(|point| Some((point.index(0)?.as_f64()? as f32, point.index(1)?.as_f64()? as f32)))
+            .collect()
+    };
+    let (vertices, in_tangents, out_tangents) = (points("v")?, points("i")?, points("o")?);
+    if in_tangents.len() != vertices.len() || out_tangents.len() != vertices.len() {
+        return None
+    }
+    let closed = value.get("c").and_then(JsonValue::as_bool).unwrap_or(false);
+
+    let curve = |from: usize, to: usize| {
+        let control1 = (vertices[from].0 + out_tangents[from].0, vertices[from].1 + out_tangents[from].1);
+        let control2 = (vertices[to].0 + in_tangents[to].0, vertices[to].1 + in_tangents[to].1);
+        [control1, control2, vertices[to]]
+    };
+    let mut curves: Vec<[(f32, f32); 3]> = (1..vertices.len()).map(|index| curve(index - 1, index)).collect();
+    if closed && vertices.len() > 1 {
+        curves.push(curve(vertices.len() - 1, 0));
+    }
+    Some(Subpath { start: *vertices.first()?, curves, closed })
+}
+
+/// Get a path shape at `frame`. Paths with the same number of vertices are interpolated, and others are held.
+fn path_shape(shape: &JsonValue, frame: f32) -> Result<Subpath, LottieError> {
+    let property = shape.get("ks").ok_or(LottieError::MissingField("ks"))?;
+    keyframed(property, frame, read_path, |start, end, progress| {
+        (start.curves.len() == end.curves.len()).then(|| {
+            let lerp = |a: (f32, f32), b: (f32, f32)| (a.0 + (b.0 - a.0) * progress, a.1 + (b.1 - a.1) * progress);
+            Subpath {
+                start: lerp(start.start, end.start),
+                curves: start.curves.iter().zip(&end.curves)
+                    .map(|(a, b)| [lerp(a[0], b[0]), lerp(a[1], b[1]), lerp(a[2], b[2])])
+                    .collect(),
+                closed: start.closed
+            }
+        })
+    }).ok_or(LottieError::InvalidValue("ks"))
+}
+
+/// Read a colour from 0 to 1, and an opacity from 0 to 100, of a fill or stroke.
+fn paint_color(shape: &JsonValue, frame: f32) -> Result<AlphaPixel<f32>, LottieError> {
+    let color = property(shape, "c", frame).filter(|color| color.len() >= 3).ok_or(LottieError::InvalidValue("c"))?;
+    let opacity = property(shape, "o", frame).and_then(|opacity| opacity.first().copied()).unwrap_or(100.0);
+    let channel = |value: f32| value.clamp(0.0, 1.0);
+    Ok(AlphaPixel {
+        r: channel(color[0]),
+        g: channel(color[1]),
+        b: channel(color[2]),
+        a: channel(color.get(3).copied().unwrap_or(1.0) * opacity / 100.0)
+    })
+}
+
+/// Get the subpaths of the shapes in a group, and the shapes painted by its fills and strokes, from the top down.
+fn group_shapes(items: &[JsonValue], frame: f32) -> Result<(Vec<Subpath>, Vec<PaintedShape>), LottieError> {
+    let mut subpaths = vec![];
+    let mut painted = vec![];
+    let pair = |item: &JsonValue, key: &'static str| {
+        property(item, key, frame)
+            .filter(|value| value.len() >= 2)
+            .map(|value| (value[0], value[1]))
+            .ok_or(LottieError::InvalidValue(key))
+    };
+
+    for item in items {
+        if item.get("hd").and_then(JsonValue::as_bool) == Some(true) {
+            continue
+        }
+        match item.get("ty").and_then(JsonValue::as_str).unwrap_or_default() {
+            "rc" => {
+                let radius = property(item, "r", frame).and_then(|radius| radius.first().copied()).unwrap_or(0.0);
+                subpaths.push(rectangle(pair(item, "p")?, pair(item, "s")?, radius));
+            },
+            "el" => subpaths.push(ellipse(pair(item, "p")?, pair(item, "s")?)),
+            "sh" => subpaths.push(path_shape(item, frame)?),
+            "fl" => {
+                let rule = match item.get("r").and_then(JsonValue::as_f64) {
+                    Some(2.0) => FillRule::EvenOdd,
+                    _ => FillRule::NonZero
+                };
+                let paint = Paint::Fill { color: paint_color(item, frame)?, rule };
+                painted.push(PaintedShape { subpaths: subpaths.clone(), paint, opacity: 1.0 });
+            },
+            "st" => {
+                let width = property(item, "w", frame).and_then(|width| width.first().copied()).unwrap_or(1.0);
+                let paint = Paint::Stroke { color: paint_color(item, frame)?, width };
+                painted.push(PaintedShape { subpaths: subpaths.clone(), paint, opacity: 1.0 });
+            },
+            "gr" => {
+                let group_items = item.get("it").and_then(JsonValue::as_array).unwrap_or_default();
+                let (matrix, opacity) = group_items.iter()
+                    .find(|item| item.get("ty").and_then(JsonValue::as_str) == Some("tr"))
+                    .map_or((Affine::IDENTITY, 1.0), |transform| read_transform(transform, frame));
+                let (group_subpaths, group_painted) = group_shapes(group_items, frame)?;
+
+                subpaths.extend(group_subpaths.iter().map(|subpath| subpath.transformed(matrix)));
+                painted.extend(group_painted.into_iter().map(|shape| PaintedShape {
+                    subpaths: shape.subpaths.iter().map(|subpath| subpath.transformed(matrix)).collect(),
+                    paint: match shape.paint {
+                        Paint::Stroke { color, width } => Paint::Stroke { color, width: width * matrix.scale_factor() },
+                        fill => fill
+                    },
+                    opacity: shape.opacity * opacity
+                }));
+            },
+            _ => {}
+        }
+    }
+
+    Ok((subpaths, painted))
+}
+
+/// Get the rectangle of a solid layer, filled with its colour.
+fn solid_shapes(layer: &JsonValue) -> Result<Vec<PaintedShape>, LottieError> {
+    let size = |key: &'static str| layer.get(key).and_then(JsonValue::as_f64).ok_or(LottieError::MissingField(key));
+    let (width, height) = (size("sw")? as f32, size("sh")? as f32);
+
+    let hex = layer.get("sc").and_then(JsonValue::as_str).and_then(|color| color.strip_prefix('#'))
+        .filter(|hex| hex.len() == 6)
+        .ok_or(LottieError::InvalidValue("sc"))?;
+    let channel = |index: usize| {
+        u8::from_str_radix(hex.get(index..index + 2).ok_or(LottieError::InvalidValue("sc"))?, 16)
+            .map(|channel| channel as f32 / 255.0)
+            .map_err(|_| LottieError::InvalidValue("sc"))
+    };
+    let color = AlphaPixel { r: channel(0)?, g: channel(2)?, b: channel(4)?, a: 1.0 };
+
+    Ok(vec![PaintedShape {
+        subpaths: vec![rectangle((width / 2.0, height / 2.0), (width, height), 0.0)],
+        paint: Paint::Fill { color, rule: FillRule::NonZero },
+        opacity: 1.0
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rgba, limits::LimitError};
+
+    fn animation(layers: &str) -> LottieAnimation {
+        LottieAnimation::parse(&format!(r#"{{"fr": 30, "ip": 0, "op": 60, "w": 100, "h": 100, "layers": {layers}}}"#)).unwrap()
+    }
+
+    #[test]
+    fn keyframes() {
+        let position = JsonValue::parse(r#"{"p": {"a": 1, "k": [
+            {"t": 10, "s": [0, 0]},
+            {"t": 20, "s": [100, 50], "h": 1},
+            {"t": 30, "s": [0, 0]}
+        ]}}"#).unwrap();
+        assert_eq!(property(&position, "p", 0.0), Some(vec![0.0, 0.0]));
+        assert_eq!(property(&position, "p", 15.0), Some(vec![50.0, 25.0]));
+        // Held keyframes jump to the next value
+        assert_eq!(property(&position, "p", 25.0), Some(vec![100.0, 50.0]));
+        assert_eq!(property(&position, "p", 40.0), Some(vec![0.0, 0.0]));
+
+        // Older files give the end value of each keyframe
+        let old = JsonValue::parse(r#"{"o": {"k": [{"t": 0, "s": [0], "e": [100]}, {"t": 10}]}}"#).unwrap();
+        assert_eq!(property(&old, "o", 5.0), Some(vec![50.0]));
+        assert_eq!(property(&JsonValue::parse(r#"{"r": {"a": 0, "k": 45}}"#).unwrap(), "r", 5.0), Some(vec![45.0]));
+    }
+
+    #[test]
+    fn transforms() {
+        // Rotated by 90 degrees around its anchor at (10, 0), then moved to (50, 50)
+        let transform = JsonValue::parse(r#"{"a": {"k": [10, 0]}, "p": {"k": [50, 50]}, "r": {"k": 90}, "s": {"k": [200, 200]}}"#).unwrap();
+        let (matrix, opacity) = read_transform(&transform, 0.0);
+        let (x, y) = matrix.apply((20.0, 0.0));
+        assert!((x - 50.0).abs() < 1e-4 && (y - 70.0).abs() < 1e-4);
+        assert_eq!(opacity, 1.0);
+        assert!((matrix.scale_factor() - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn groups_and_parents() {
+        let animation = animation(r##"[
+            {
+                "ty": 4, "nm": "Child", "ind": 2, "parent": 1, "ks": {"o": {"k": 50}},
+                "shapes": [{"ty": "gr", "it": [
+                    {"ty": "el", "p": {"k": [0, 0]}, "s": {"k": [20, 20]}},
+                    {"ty": "fl", "c": {"k": [0, 0, 1, 1]}, "o": {"k": 100}},
+                    {"ty": "tr", "p": {"k": [10, 0]}}
+                ]}]
+            },
+            {"ty": 3, "nm": "Null", "ind": 1, "ks": {"p": {"k": [50, 50]}, "o": {"k": 0}}},
+            {
+                "ty": 1, "nm": "Background", "ind": 3, "sw": 100, "sh": 100, "sc": "#ff0000",
+                "ks": {"p": {"k": [50, 50]}, "a": {"k": [50, 50]}}
+            },
+            {"ty": 4, "nm": "Hidden", "ip": 30, "op": 60, "shapes": []}
+        ]"##);
+
+        let canvas: crate::Canvas<u8> = animation.render(0.0).unwrap();
+        assert_eq!(canvas.layers.len(), 2);
+        assert_eq!(canvas.layers[1].metadata().unwrap()[LOTTIE_NAME_KEY], "Child");
+
+        // The circle is moved by its group and its parent, and its parent's opacity is ignored
+        let image = canvas.flatten();
+        assert_eq!(image.pixel_at(60, 50).unwrap(), rgba!(127, 0, 127, 255));
+        assert_eq!(image.pixel_at(45, 50).unwrap(), AlphaPixel::red());
+    }
+
+    #[test]
+    fn fills_apply_to_shapes_above_them() {
+        let animation = animation(r#"[{
+            "ty": 4, "nm": "Shapes",
+            "shapes": [
+                {"ty": "rc", "p": {"k": [25, 50]}, "s": {"k": [20, 20]}, "r": {"k": 0}},
+                {"ty": "st", "c": {"k": [0, 0, 0]}, "o": {"k": 100}, "w": {"k": 4}},
+                {"ty": "rc", "p": {"k": [75, 50]}, "s": {"k": [20, 20]}, "r": {"k": 5}},
+                {"ty": "fl", "c": {"k": [0, 1, 0]}, "o": {"k": 100}}
+            ]
+        }]"#);
+
+        let image = animation.render::<u8>(0.0).unwrap().flatten();
+        // The stroke is drawn over the fill, and only around the first rectangle
+        assert_eq!(image.pixel_at(25, 50).unwrap(), AlphaPixel::green());
+        assert_eq!(image.pixel_at(15, 50).unwrap(), AlphaPixel::black());
+        assert_eq!(image.pixel_at(75, 50).unwrap(), AlphaPixel::green());
+        assert_eq!(image.pixel_at(65, 50).unwrap(), AlphaPixel::green());
+        assert_eq!(image.pixel_at(65, 40).unwrap().a, 0);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(LottieAnimation::parse(r#"{"w": 10}"#).err(), Some(LottieError::MissingField("layers")));
+        let parent_loop = animation(r#"[{"ty": 4, "ind": 1, "parent": 2}, {"ty": 4, "ind": 2, "parent": 1}]"#);
+        assert!(matches!(parent_loop.render::<u8>(0.0), Err(Error::Lottie(LottieError::InvalidValue("parent")))));
+        let bad_rectangle = animation(r#"[{"ty": 4, "shapes": [{"ty": "rc", "p": {"k": [0]}, "s": {"k": [1, 1]}}]}]"#);
+        assert!(matches!(bad_rectangle.render::<u8>(0.0), Err(Error::Lottie(LottieError::InvalidValue("p")))));
+
+        let sized = |width: &str, height: &str| LottieAnimation::parse(&format!(r#"{{"fr": 30, "ip": 0, "op": 60, "w": {width}, "h": {height}, "layers": []}}"#));
+        assert_eq!(sized("-5", "10").err(), Some(LottieError::InvalidValue("w")));
+        assert_eq!(sized("10", "1e30").err(), Some(LottieError::InvalidValue("h")));
+        assert_eq!(sized("10", "1e400").err(), Some(LottieError::InvalidValue("h")));
+        assert!(sized("0", "65536").is_ok());
+    }
+
+    #[test]
+    fn limits() {
+        let huge = LottieAnimation::parse(r#"{"fr": 30, "ip": 0, "op": 60, "w": 50000, "h": 50000, "layers": []}"#).unwrap();
+        assert!(matches!(huge.with_limits(Limits::default()).render::<u8>(0.0), Err(Error::Limit(LimitError::CanvasTooLarge { .. }))));
+
+        let square = r#"{"ty": 4, "shapes": [{"ty": "rc", "p": {"k": [50, 50]}, "s": {"k": [20, 20]}, "r": {"k": 0}}, {"ty": "fl", "c": {"k": [1, 0, 0]}, "o": {"k": 100}}]}"#;
+        let squares = animation(&format!("[{square}, {square}]")).with_limits(Limits { max_layers: 1, ..Default::default() });
+        assert!(matches!(squares.render::<u8>(0.0), Err(Error::Limit(LimitError::TooManyLayers(1)))));
+
+        let canvas = animation(&format!("[{square}]")).with_limits(Limits::default()).render::<u8>(0.0).unwrap();
+        assert_eq!(canvas.limits, Some(Limits::default()));
+    }
+}
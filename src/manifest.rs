@@ -0,0 +1,170 @@
+//! A record of what a canvas rendered where, for auditing images made by automated pipelines.
+
+use std::fmt::Write;
+use crate::{Canvas, Image, PixelChannel, Rect, Layer, layers::metadata::Metadata};
+
+/// The layers of a rendered canvas, created by [`Canvas::flatten_with_manifest`].
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    pub width: usize,
+    pub height: usize,
+    /// The layers of the canvas, from the bottom up
+    pub layers: Vec<ManifestLayer>
+}
+
+/// A layer of a [`Manifest`].
+#[derive(Debug, Clone, Default)]
+pub struct ManifestLayer {
+    /// The [name](Layer::name) of the layer
+    pub name: String,
+    /// The `Rect` of the layer, before it is moved by any filters
+    pub rect: Rect,
+    pub filter_count: usize,
+    /// The [metadata](Layer::metadata) of the layer, which is empty if it has none
+    pub metadata: Metadata,
+    /// The [children](Layer::children) of a container layer, in the order they are drawn
+    pub children: Vec<ManifestLayer>
+}
+
+impl ManifestLayer {
+    fn from_layer<T: PixelChannel>(layer: &dyn Layer<T>) -> Self {
+        Self {
+            name: layer.name(),
+            rect: layer.get_rect(),
+            filter_count: layer.get_filters().len(),
+            metadata: layer.metadata().cloned().unwrap_or_default(),
+            children: layer.children().into_iter().map(Self::from_layer).collect()
+        }
+    }
+
+    fn write_json(&self, json: &mut String) {
+        let rect = self.rect;
+        json.push_str("{\"name\":");
+        write_json_string(json, &self.name);
+        // Writing to a `String` can't fail
+        let _ = write!(
+            json,
+            ",\"rect\":{{\"x\":{},\"y\":{},\"width\":{},\"height\":{}}},\"filters\":{},\"metadata\":{{",
+            rect.x, rect.y, rect.width, rect.height, self.filter_count
+        );
+        for (index, (key, value)) in self.metadata.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            write_json_string(json, key);
+            json.push(':');
+            write_json_string(json, value);
+        }
+        json.push_str("},\"children\":");
+        write_json_array(json, &self.children);
+        json.push('}');
+    }
+}
+
+impl Manifest {
+    /// Describe the layers of `canvas`, including the children of container layers.
+    pub fn from_canvas<T: PixelChannel>(canvas: &Canvas<T>) -> Self {
+        Self {
+            width: canvas.width,
+            height: canvas.height,
+            layers: canvas.layers.iter().map(|layer| ManifestLayer::from_layer(layer.as_ref())).collect()
+        }
+    }
+
+    /// Write the manifest as compact JSON.
+    /// 
+    /// The manifest is an object with the `width` and `height` of the canvas and its `layers`. Each layer is an object
+    /// with its `name`, `rect` (an object with `x`, `y`, `width` and `height`), number of `filters`, `metadata` as an
+    /// object of strings, and `children` in the same format.
+    pub fn to_json(&self) -> String {
+        let mut json = format!("{{\"width\":{},\"height\":{},\"layers\":", self.width, self.height);
+        write_json_array(&mut json, &self.layers);
+        json.push('}');
+        json
+    }
+}
+
+fn write_json_array(json: &mut String, layers: &[ManifestLayer]) {
+    json.push('[');
+    for (index, layer) in layers.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        layer.write_json(json);
+    }
+    json.push(']');
+}
+
+/// Write `text` as a quoted JSON string, escaping quotes, backslashes and control characters.
+fn write_json_string(json: &mut String, text: &str) {
+    json.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            control if control.is_control() => {
+                let _ = write!(json, "\\u{:04x}", control as u32);
+            },
+            other => json.push(other)
+        }
+    }
+    json.push('"');
+}
+
+impl<T: PixelChannel> Canvas<T> {
+    /// Flatten the canvas, and describe each layer that was rendered, with the metadata attached to it
+    /// with [`AnnotatedLayer`](crate::layers::metadata::AnnotatedLayer).
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::{Canvas, Image, AlphaPixel, Rect};
+    /// use image_template::layers::{image::ImageLayer, shapes::RectangleLayer, metadata::AnnotatedLayer};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(100, 50);
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::white(), Rect { x: 0, y: 0, width: 100, height: 50 }));
+    /// let photo: Image<u8> = Image::new_with_fill(AlphaPixel::blue(), 20, 20);
+    /// canvas.add_layer(AnnotatedLayer::new(ImageLayer::new(photo, 5, 5)).with_source("photos/1234.jpg"));
+    /// 
+    /// let (_image, manifest) = canvas.flatten_with_manifest();
+    /// assert_eq!(manifest.layers[1].metadata["source"], "photos/1234.jpg");
+    /// assert_eq!(manifest.to_json(), concat!(
+    ///     r#"{"width":100,"height":50,"layers":["#,
+    ///     r#"{"name":"RectangleLayer","rect":{"x":0,"y":0,"width":100,"height":50},"filters":0,"metadata":{},"children":[]},"#,
+    ///     r#"{"name":"ImageLayer","rect":{"x":5,"y":5,"width":20,"height":20},"filters":0,"metadata":{"source":"photos/1234.jpg"},"children":[]}"#,
+    ///     "]}"
+    /// ));
+    /// ```
+    pub fn flatten_with_manifest(&self) -> (Image<T>, Manifest) {
+        (self.flatten(), Manifest::from_canvas(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AlphaPixel, layers::{shapes::RectangleLayer, metadata::AnnotatedLayer}};
+    use crate::layers::container::{CrossAlign, flow::{FlowLayer, FlowDirection}};
+
+    #[test]
+    fn nested_metadata() {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(50, 50);
+        let mut column = FlowLayer::new(0, 0, FlowDirection::Vertical, 2, CrossAlign::Start);
+        column.push(AnnotatedLayer::new(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 5, height: 5 })).with("id", "swatch"));
+        canvas.add_layer(AnnotatedLayer::new(column).with("id", "column"));
+
+        let manifest = Manifest::from_canvas(&canvas);
+        assert_eq!(manifest.layers[0].name, "FlowLayer");
+        assert_eq!(manifest.layers[0].metadata["id"], "column");
+        assert_eq!(manifest.layers[0].children[0].metadata["id"], "swatch");
+    }
+
+    #[test]
+    fn escaping() {
+        let mut json = String::new();
+        write_json_string(&mut json, "a \"quoted\" C:\\path\nwith\u{1}");
+        assert_eq!(json, r#""a \"quoted\" C:\\path\nwith\u0001""#);
+    }
+}
@@ -0,0 +1,257 @@
+//! Export a canvas as an OpenRaster (`.ora`) file with each layer preserved, so that generated compositions can be
+//! touched up by hand in image editors such as GIMP and Krita.
+
+use std::{io::Cursor, path::Path};
+use image::{ColorType, ImageFormat};
+use crate::{Canvas, Image, AlphaPixel, PixelChannel, Error, bitmap::resize::ResizeOptions};
+
+/// The largest width or height of the thumbnail which every OpenRaster file includes.
+const THUMBNAIL_SIZE: usize = 256;
+
+impl<T: PixelChannel> Canvas<T> {
+    /// Create an OpenRaster file of the canvas, with each layer as a separate layer in the file.
+    /// 
+    /// Each layer is [rendered alone](Canvas::render_layer_trimmed) and named by its [name](crate::Layer::name).
+    /// Container layers are exported as a single layer, and layers which [use the backdrop](crate::Layer::uses_backdrop)
+    /// are drawn over a transparent backdrop, as OpenRaster can't represent them. A background that isn't transparent
+    /// is exported as the bottom layer. The flattened image, with the palette applied, is also included for viewers
+    /// which don't read layers.
+    /// 
+    /// Layers are saved as 8 bit PNGs, whatever the channel type of the canvas.
+    /// 
+    /// # Example
+    /// ```rust,no_run
+    /// use image_template::{Canvas, AlphaPixel, Rect, layers::shapes::{RectangleLayer, CircleLayer}};
+    /// 
+    /// let mut canvas: Canvas<u8> = Canvas::from_dimensions(400, 300);
+    /// canvas.background = AlphaPixel::white();
+    /// canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 0, y: 0, width: 400, height: 60 }));
+    /// canvas.add_layer(CircleLayer::new(AlphaPixel::red(), 200.0, 180.0, 50.0));
+    /// canvas.save_ora("poster.ora").unwrap();
+    /// ```
+    pub fn to_ora(&self) -> Result<Vec<u8>, Error> {
+        let mut archive = StoredZip::default();
+        // The mimetype is the first file, uncompressed, so that the type of the file can be recognised from its start
+        archive.add("mimetype", b"image/openraster");
+
+        // The stack lists layers from the top down
+        let mut stack = String::new();
+        let mut layer_files = vec![];
+        for index in (0..self.layers.len()).rev() {
+            let (image, position) = self.render_layer_trimmed(index).expect("layer index is in bounds");
+            let path = format!("data/layer{index}.png");
+            stack.push_str(&format!(
+                "<layer name=\"{}\" src=\"{path}\" x=\"{}\" y=\"{}\" visibility=\"visible\"/>",
+                escape_xml(&self.layers[index].name()), position.x, position.y
+            ));
+            layer_files.push((path, encode_png(&image)?));
+        }
+        if self.background.a > T::zero() {
+            let background = Image::new_with_fill(self.background, self.width, self.height);
+            stack.push_str("<layer name=\"Background\" src=\"data/background.png\" x=\"0\" y=\"0\" visibility=\"visible\"/>");
+            layer_files.push((String::from("data/background.png"), encode_png(&background)?));
+        }
+
+        archive.add("stack.xml", format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><image version=\"0.0.3\" w=\"{}\" h=\"{}\"><stack>{stack}</stack></image>",
+            self.width, self.height
+        ).as_bytes());
+        for (path, png) in layer_files {
+            archive.add(&path, &png);
+        }
+
+        let merged = self.flatten();
+        let scale = (THUMBNAIL_SIZE as f32 / self.width.max(self.height).max(1) as f32).min(1.0);
+        let thumbnail_width = ((self.width as f32 * scale).round() as usize).max(1);
+        let thumbnail_height = ((self.height as f32 * scale).round() as usize).max(1);
+        let thumbnail = merged.resize(thumbnail_width, thumbnail_height, ResizeOptions::default());
+        archive.add("mergedimage.png", &encode_png(&merged)?);
+        archive.add("Thumbnails/thumbnail.png", &encode_png(&thumbnail)?);
+
+        Ok(archive.finish())
+    }
+
+    /// Save the canvas as an OpenRaster file. See [`Canvas::to_ora`].
+    pub fn save_ora<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        std::fs::write(path, self.to_ora()?).map_err(image::ImageError::IoError)?;
+        Ok(())
+    }
+}
+
+/// Encode an image as an 8 bit PNG. Empty images are saved as a single transparent pixel, as PNGs can't be empty.
+fn encode_png<T: PixelChannel>(image: &Image<T>) -> Result<Vec<u8>, Error> {
+    let (width, height) = (image.get_width(), image.get_height());
+    let image: Image<u8> = if width == 0 || height == 0 {
+        Image::new_with_fill(AlphaPixel::default(), 1, 1)
+    } else {
+        Image::from_pixels(image.get_pixels().iter().map(|pixel| pixel.as_different_channel()).collect(), width)?
+    };
+
+    let mut png = vec![];
+    image::write_buffer_with_format(
+        &mut Cursor::new(&mut png),
+        image.as_ref(),
+        image.get_width() as u32,
+        image.get_height() as u32,
+        ColorType::Rgba8,
+        ImageFormat::Png
+    )?;
+    Ok(png)
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            other => escaped.push(other)
+        }
+    }
+    escaped
+}
+
+/// A zip archive with uncompressed files, which is all that OpenRaster needs, as the PNGs in it are already compressed.
+#[derive(Default)]
+struct StoredZip {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    file_count: u16
+}
+
+impl StoredZip {
+    /// The version of the zip format needed to read the archive, which is 2.0 for uncompressed files
+    const VERSION: u16 = 20;
+    /// The date of every file, which is the earliest date that zip files can store, 1980-01-01, so that output is reproducible
+    const DATE: u16 = (1 << 5) | 1;
+
+    fn add(&mut self, name: &str, contents: &[u8]) {
+        let offset = self.data.len() as u32;
+        let crc = crc32fast::hash(contents);
+        let size = contents.len() as u32;
+
+        // The fields shared by the local file header and the central directory, from the version needed to the extra field length
+        let mut fields = vec![];
+        for value in [Self::VERSION, 0, 0, 0, Self::DATE] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [crc, size, size] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        fields.extend_from_slice(&0u16.to_le_bytes());
+
+        self.data.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        self.data.extend_from_slice(&fields);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(contents);
+
+        self.central_directory.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        // The version of the zip format that made the archive
+        self.central_directory.extend_from_slice(&Self::VERSION.to_le_bytes());
+        self.central_directory.extend_from_slice(&fields);
+        // The comment length, disk number, and internal and external file attributes
+        self.central_directory.extend_from_slice(&[0; 10]);
+        self.central_directory.extend_from_slice(&offset.to_le_bytes());
+        self.central_directory.extend_from_slice(name.as_bytes());
+
+        self.file_count += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.data.len() as u32;
+        let directory_size = self.central_directory.len() as u32;
+        self.data.append(&mut self.central_directory);
+
+        self.data.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        // The number of this disk, and the disk with the central directory
+        self.data.extend_from_slice(&[0; 4]);
+        self.data.extend_from_slice(&self.file_count.to_le_bytes());
+        self.data.extend_from_slice(&self.file_count.to_le_bytes());
+        self.data.extend_from_slice(&directory_size.to_le_bytes());
+        self.data.extend_from_slice(&directory_offset.to_le_bytes());
+        // The comment length
+        self.data.extend_from_slice(&[0; 2]);
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rect, layers::shapes::RectangleLayer};
+
+    /// Read the names and contents of the files in an archive, from their local file headers.
+    fn read_zip(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let read_u16 = |offset: usize| u16::from_le_bytes([archive[offset], archive[offset + 1]]) as usize;
+        let read_u32 = |offset: usize| u32::from_le_bytes(archive[offset..offset + 4].try_into().unwrap()) as usize;
+
+        let mut files = vec![];
+        let mut offset = 0;
+        while read_u32(offset) == 0x04034b50 {
+            let (size, name_length, extra_length) = (read_u32(offset + 18), read_u16(offset + 26), read_u16(offset + 28));
+            let name_start = offset + 30;
+            let data_start = name_start + name_length + extra_length;
+            let contents = archive[data_start..data_start + size].to_vec();
+            assert_eq!(crc32fast::hash(&contents) as usize, read_u32(offset + 14));
+
+            files.push((String::from_utf8(archive[name_start..name_start + name_length].to_vec()).unwrap(), contents));
+            offset = data_start + size;
+        }
+
+        // The central directory follows, with an entry for each file
+        assert_eq!(read_u32(offset), 0x02014b50);
+        let end = archive.len() - 22;
+        assert_eq!(read_u32(end), 0x06054b50);
+        assert_eq!(read_u16(end + 10), files.len());
+        assert_eq!(read_u32(end + 16), offset);
+        files
+    }
+
+    #[test]
+    fn layers_and_stack() {
+        let mut canvas: Canvas<u16> = Canvas::from_dimensions(600, 300);
+        canvas.background = AlphaPixel::white();
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 10, y: 20, width: 30, height: 40 }));
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 0, y: 0, width: 5, height: 5 }));
+
+        let files = read_zip(&canvas.to_ora().unwrap());
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, [
+            "mimetype", "stack.xml", "data/layer1.png", "data/layer0.png", "data/background.png", "mergedimage.png", "Thumbnails/thumbnail.png"
+        ]);
+        assert_eq!(files[0].1, b"image/openraster");
+
+        let stack = String::from_utf8(files[1].1.clone()).unwrap();
+        assert!(stack.contains("w=\"600\" h=\"300\""));
+        assert!(stack.contains("<layer name=\"RectangleLayer\" src=\"data/layer0.png\" x=\"10\" y=\"20\""));
+        // The top layer is listed first
+        assert!(stack.find("layer1.png").unwrap() < stack.find("layer0.png").unwrap());
+
+        let red: Image<u8> = Image::load_from_memory(&files[3].1, ImageFormat::Png).unwrap();
+        assert_eq!((red.get_width(), red.get_height()), (30, 40));
+        assert_eq!(red.pixel_at(0, 0).unwrap(), AlphaPixel::red());
+
+        let thumbnail: Image<u8> = Image::load_from_memory(&files[6].1, ImageFormat::Png).unwrap();
+        assert_eq!((thumbnail.get_width(), thumbnail.get_height()), (256, 128));
+    }
+
+    #[test]
+    fn empty_layer() {
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(10, 10);
+        canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), Rect { x: 0, y: 0, width: 0, height: 0 }));
+        let files = read_zip(&canvas.to_ora().unwrap());
+        // No background layer, and a single transparent pixel for the empty layer
+        assert_eq!(files.len(), 5);
+        let empty: Image<u8> = Image::load_from_memory(&files[2].1, ImageFormat::Png).unwrap();
+        assert_eq!(empty.pixel_at(0, 0).unwrap().a, 0);
+    }
+
+    #[test]
+    fn xml_escaping() {
+        assert_eq!(escape_xml("<\"Tom\" & 'Jerry'>"), "&lt;&quot;Tom&quot; &amp; &apos;Jerry&apos;&gt;");
+    }
+}
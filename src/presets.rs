@@ -0,0 +1,128 @@
+//! Canvas sizes for common social media images, with margins that keep content clear of cropping and overlays.
+//! 
+//! # Example
+//! ```
+//! use image_template::{Canvas, AlphaPixel, presets::OPEN_GRAPH, layers::shapes::RectangleLayer};
+//! 
+//! let mut canvas: Canvas<u8> = OPEN_GRAPH.canvas();
+//! assert_eq!((canvas.width, canvas.height), (1200, 630));
+//! 
+//! // Place the content inside the safe area
+//! let safe = OPEN_GRAPH.safe_rect();
+//! canvas.add_layer(RectangleLayer::new(AlphaPixel::red(), safe));
+//! assert_eq!((safe.x, safe.y, safe.width, safe.height), (60, 60, 1080, 510));
+//! ```
+
+use crate::{Canvas, PixelChannel, Rect};
+
+/// The distance from each edge of a preset that content should be kept inside, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Margins {
+    pub left: usize,
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize
+}
+
+impl Margins {
+    /// The same margin on every edge.
+    pub const fn uniform(margin: usize) -> Self {
+        Self { left: margin, top: margin, right: margin, bottom: margin }
+    }
+}
+
+/// The size of an image for a platform, and the margins that content should be kept inside.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub width: usize,
+    pub height: usize,
+    pub safe_margins: Margins
+}
+
+impl Preset {
+    /// Create an empty canvas with the size of the preset.
+    pub fn canvas<T: PixelChannel>(&self) -> Canvas<T> {
+        Canvas::from_dimensions(self.width, self.height)
+    }
+
+    /// Get a `Rect` covering the whole preset.
+    pub fn rect(&self) -> Rect {
+        Rect { x: 0, y: 0, width: self.width, height: self.height }
+    }
+
+    /// Get the `Rect` inside the safe margins, where content won't be cropped or covered.
+    /// 
+    /// If the margins are larger than the preset, the `Rect` is empty.
+    pub fn safe_rect(&self) -> Rect {
+        let margins = self.safe_margins;
+        let x = margins.left.min(self.width);
+        let y = margins.top.min(self.height);
+        Rect {
+            x,
+            y,
+            width: self.width.saturating_sub(margins.left + margins.right),
+            height: self.height.saturating_sub(margins.top + margins.bottom)
+        }
+    }
+
+    /// Find a preset in [`ALL`] by its name.
+    pub fn by_name(name: &str) -> Option<Preset> {
+        ALL.iter().find(|preset| preset.name == name).copied()
+    }
+}
+
+/// An Open Graph image, shown when a link is shared. Some sites crop the sides, so the margins are wide.
+pub const OPEN_GRAPH: Preset = Preset { name: "open_graph", width: 1200, height: 630, safe_margins: Margins::uniform(60) };
+
+/// A Twitter/X large summary card image.
+pub const TWITTER_CARD: Preset = Preset { name: "twitter_card", width: 1200, height: 628, safe_margins: Margins::uniform(60) };
+
+/// A square Instagram post.
+pub const INSTAGRAM_POST: Preset = Preset { name: "instagram_post", width: 1080, height: 1080, safe_margins: Margins::uniform(54) };
+
+/// A full screen Instagram story. The top and bottom are covered by the profile name and reply box.
+pub const INSTAGRAM_STORY: Preset = Preset {
+    name: "instagram_story",
+    width: 1080,
+    height: 1920,
+    safe_margins: Margins { left: 64, top: 250, right: 64, bottom: 250 }
+};
+
+/// A YouTube video thumbnail. The bottom right corner is covered by the length of the video.
+pub const YOUTUBE_THUMBNAIL: Preset = Preset {
+    name: "youtube_thumbnail",
+    width: 1280,
+    height: 720,
+    safe_margins: Margins { left: 64, top: 64, right: 64, bottom: 100 }
+};
+
+/// Every preset in this module.
+pub const ALL: [Preset; 5] = [OPEN_GRAPH, TWITTER_CARD, INSTAGRAM_POST, INSTAGRAM_STORY, YOUTUBE_THUMBNAIL];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_rects_are_inside() {
+        for preset in ALL {
+            let safe = preset.safe_rect();
+            assert!(safe.width > 0 && safe.height > 0);
+            assert!(preset.rect().intersection(&safe).is_some_and(|inside| inside.width == safe.width && inside.height == safe.height));
+        }
+    }
+
+    #[test]
+    fn large_margins() {
+        let tiny = Preset { name: "tiny", width: 10, height: 10, safe_margins: Margins::uniform(6) };
+        let safe = tiny.safe_rect();
+        assert_eq!((safe.width, safe.height), (0, 0));
+    }
+
+    #[test]
+    fn by_name() {
+        assert_eq!(Preset::by_name("instagram_story"), Some(INSTAGRAM_STORY));
+        assert_eq!(Preset::by_name("myspace"), None);
+    }
+}
@@ -41,6 +41,45 @@ impl Rect {
     pub fn right_x(&self) -> usize {
         self.x + self.width
     }
+
+    /// Get the area covered by both `Rect`s, or `None` if they don't overlap.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::Rect;
+    /// let a = Rect { x: 0, y: 0, width: 10, height: 10 };
+    /// let b = Rect { x: 5, y: 8, width: 10, height: 10 };
+    /// let overlap = a.intersection(&b).unwrap();
+    /// assert_eq!((overlap.x, overlap.y, overlap.width, overlap.height), (5, 8, 5, 2));
+    /// assert!(a.intersection(&Rect { x: 10, y: 0, width: 5, height: 5 }).is_none());
+    /// ```
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = self.right_x().min(other.right_x());
+        let bottom = self.bottom_y().min(other.bottom_y());
+
+        if left < right && top < bottom {
+            Some(Rect { x: left, y: top, width: right - left, height: bottom - top })
+        } else {
+            None
+        }
+    }
+
+    /// Multiply the position and size by `factor`. The edges are rounded to the nearest pixel, so that `Rect`s which
+    /// touch still touch after they are scaled.
+    /// 
+    /// # Example
+    /// ```
+    /// use image_template::Rect;
+    /// let rect = Rect { x: 3, y: 1, width: 5, height: 2 }.scale(1.5);
+    /// assert_eq!((rect.x, rect.y, rect.right_x(), rect.bottom_y()), (5, 2, 12, 5));
+    /// ```
+    pub fn scale(&self, factor: f32) -> Rect {
+        let scale = |value: usize| (value as f32 * factor).round().max(0.0) as usize;
+        let (x, y) = (scale(self.x), scale(self.y));
+        Rect { x, y, width: scale(self.right_x()).saturating_sub(x), height: scale(self.bottom_y()).saturating_sub(y) }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,725 @@
+//! Registries of layer and filter types by name, so that they can be created from parameters at runtime,
+//! for example from a config file.
+//! 
+//! Every built-in type that can be described by simple parameters is registered by [`LayerRegistry::with_builtins`]
+//! and [`FilterRegistry::with_builtins`]. Downstream crates can register their own types in the same way.
+//! 
+//! # Example
+//! ```
+//! use image_template::{Canvas, AlphaPixel, Layer};
+//! use image_template::registry::{LayerRegistry, FilterRegistry, Params, ParamValue};
+//! 
+//! let layers: LayerRegistry<u8> = LayerRegistry::with_builtins();
+//! let filters: FilterRegistry<u8> = FilterRegistry::with_builtins();
+//! 
+//! let params = Params::new()
+//!     .with("x", ParamValue::Number(10.0))
+//!     .with("y", ParamValue::Number(10.0))
+//!     .with("width", ParamValue::Number(50.0))
+//!     .with("height", ParamValue::Number(20.0))
+//!     .with("fill", ParamValue::Text("#ff0000".to_string()));
+//! let mut rectangle = layers.create("rectangle", &params).unwrap();
+//! assert_eq!(rectangle.filtered_pixel_at(20, 20).unwrap(), AlphaPixel::red());
+//! 
+//! let _brightness = filters.create("brightness", &Params::new().with("multiplier", ParamValue::Number(0.5))).unwrap();
+//! ```
+
+use std::collections::HashMap;
+use thiserror::Error;
+use crate::{
+    Layer,
+    Filter,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    layers::{
+        shapes::{RectangleLayer, CircleLayer, EllipseLayer, RoundedRectangleLayer, CornerRadii, ArcLayer, RegularPolygonLayer, StarLayer},
+        path::PathLayer,
+        arrow::{ArrowLayer, ArrowHead}
+    },
+    filters::{
+        brightness::BrightnessFilter,
+        transform::{TranslateFilter, MatrixTransform, FlipFilter},
+        retro::{ScanlineFilter, GlitchFilter, VhsNoiseFilter},
+        edge_mode::{EdgeModeFilter, EdgeMode}
+    }
+};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RegistryError {
+    #[error("No type is registered with the name \"{0}\"")]
+    UnknownType(String),
+    #[error("Missing parameter \"{0}\"")]
+    MissingParam(String),
+    #[error("Unknown parameter \"{0}\"")]
+    UnknownParam(String),
+    #[error("Parameter \"{name}\" should be {expected}")]
+    WrongType { name: String, expected: &'static str },
+    #[error("Parameter \"{name}\" is invalid: {reason}")]
+    InvalidParam { name: String, reason: String }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The value of a parameter used to create a layer or filter.
+pub enum ParamValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    /// A colour, with channels from 0.0 to 1.0
+    Color(AlphaPixel<f32>),
+    List(Vec<ParamValue>)
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// Named parameters used to create a layer or filter.
+pub struct Params {
+    values: HashMap<String, ParamValue>
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self { values: HashMap::new() }
+    }
+
+    /// Add a parameter, replacing any parameter with the same name.
+    pub fn with(mut self, name: &str, value: ParamValue) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    pub fn insert(&mut self, name: &str, value: ParamValue) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParamValue> {
+        self.values.get(name)
+    }
+
+    /// Iterate over the names and values of the parameters, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ParamValue)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    fn required(&self, name: &str) -> Result<&ParamValue, RegistryError> {
+        self.get(name).ok_or_else(|| RegistryError::MissingParam(name.to_string()))
+    }
+
+    pub fn number(&self, name: &str) -> Result<f64, RegistryError> {
+        match self.required(name)? {
+            ParamValue::Number(number) => Ok(*number),
+            _ => Err(RegistryError::WrongType { name: name.to_string(), expected: "a number" })
+        }
+    }
+
+    /// Get a number, or `default` if the parameter isn't set.
+    pub fn number_or(&self, name: &str, default: f64) -> Result<f64, RegistryError> {
+        if self.get(name).is_none() { Ok(default) } else { self.number(name) }
+    }
+
+    /// Get a number which must be a whole number that is 0 or greater.
+    pub fn usize(&self, name: &str) -> Result<usize, RegistryError> {
+        let number = self.number(name)?;
+        if number < 0.0 || number.fract() != 0.0 {
+            return Err(RegistryError::WrongType { name: name.to_string(), expected: "a whole number, 0 or greater" })
+        }
+        Ok(number as usize)
+    }
+
+    pub fn bool(&self, name: &str) -> Result<bool, RegistryError> {
+        match self.required(name)? {
+            ParamValue::Bool(value) => Ok(*value),
+            _ => Err(RegistryError::WrongType { name: name.to_string(), expected: "true or false" })
+        }
+    }
+
+    /// Get a bool, or `default` if the parameter isn't set.
+    pub fn bool_or(&self, name: &str, default: bool) -> Result<bool, RegistryError> {
+        if self.get(name).is_none() { Ok(default) } else { self.bool(name) }
+    }
+
+    pub fn text(&self, name: &str) -> Result<&str, RegistryError> {
+        match self.required(name)? {
+            ParamValue::Text(text) => Ok(text),
+            _ => Err(RegistryError::WrongType { name: name.to_string(), expected: "text" })
+        }
+    }
+
+    /// Get a colour, from either a [`ParamValue::Color`] or a hex string such as `"#ff8000"` or `"#ff800080"`.
+    /// 
+    /// The channels of a [`ParamValue::Color`] must be between 0.0 and 1.0.
+    pub fn color<T: PixelChannel>(&self, name: &str) -> Result<AlphaPixel<T>, RegistryError> {
+        let wrong_type = || RegistryError::WrongType { name: name.to_string(), expected: "a colour" };
+        match self.required(name)? {
+            ParamValue::Color(color) => {
+                check_color(name, color)?;
+                Ok(color.as_different_channel())
+            },
+            ParamValue::Text(text) => parse_hex_color(text).map(|color| color.as_different_channel()).ok_or_else(wrong_type),
+            _ => Err(wrong_type())
+        }
+    }
+
+    /// Get a `Rect` from the `x`, `y`, `width` and `height` parameters.
+    pub fn rect(&self) -> Result<Rect, RegistryError> {
+        Ok(Rect { x: self.usize("x")?, y: self.usize("y")?, width: self.usize("width")?, height: self.usize("height")? })
+    }
+}
+
+/// Check that every channel of a colour is between 0.0 and 1.0, which also excludes NaN.
+fn check_color(name: &str, color: &AlphaPixel<f32>) -> Result<(), RegistryError> {
+    match color.channels().iter().find(|channel| !(0.0..=1.0).contains(*channel)) {
+        Some(channel) => Err(RegistryError::InvalidParam { name: name.to_string(), reason: format!("the colour channel {channel} isn't between 0 and 1") }),
+        None => Ok(())
+    }
+}
+
+/// Parse a colour from a hex string of 6 or 8 digits, with an optional leading `#`.
+fn parse_hex_color(text: &str) -> Option<AlphaPixel<u8>> {
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    if !digits.is_ascii() || (digits.len() != 6 && digits.len() != 8) {
+        return None
+    }
+
+    let channel = |index: usize| digits.get(index*2..index*2 + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    Some(AlphaPixel { r: channel(0)?, g: channel(1)?, b: channel(2)?, a: if digits.len() == 8 { channel(3)? } else { 255 } })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The type of a parameter in a [`ParamSpec`].
+pub enum ParamKind {
+    Bool,
+    Number,
+    /// A number which must be a whole number
+    Integer,
+    Text,
+    /// A [`ParamValue::Color`], or a hex string
+    Color,
+    /// Text which must be one of the options
+    Choice(Vec<&'static str>)
+}
+
+impl ParamKind {
+    fn description(&self) -> &'static str {
+        match self {
+            ParamKind::Bool => "true or false",
+            ParamKind::Number => "a number",
+            ParamKind::Integer => "a whole number",
+            ParamKind::Text => "text",
+            ParamKind::Color => "a colour",
+            ParamKind::Choice(_) => "one of the options"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A description of a parameter of a registered type, so that controls can be generated for it
+/// and values can be checked before the type is created.
+/// 
+/// # Example
+/// ```
+/// use image_template::registry::{FilterRegistry, ParamKind};
+/// 
+/// let filters: FilterRegistry<u8> = FilterRegistry::with_builtins();
+/// for spec in filters.schema("scanlines").unwrap() {
+///     if spec.kind == ParamKind::Number {
+///         println!("Slider for {} from {:?} to {:?}", spec.name, spec.min, spec.max);
+///     }
+/// }
+/// ```
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub description: &'static str,
+    /// The smallest value of a number
+    pub min: Option<f64>,
+    /// The largest value of a number
+    pub max: Option<f64>,
+    /// The value used if the parameter isn't set. If there is no default, the parameter is required.
+    pub default: Option<ParamValue>
+}
+
+impl ParamSpec {
+    /// Create a required parameter with no range.
+    pub fn new(name: &'static str, kind: ParamKind, description: &'static str) -> Self {
+        Self { name, kind, description, min: None, max: None, default: None }
+    }
+
+    pub fn with_min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    pub fn with_default(mut self, default: ParamValue) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Check that `value` has the right type and is within range.
+    /// 
+    /// Numbers must be finite, and the channels of a [`ParamValue::Color`] must be between 0.0 and 1.0.
+    pub fn validate(&self, value: &ParamValue) -> Result<(), RegistryError> {
+        let wrong_type = || RegistryError::WrongType { name: self.name.to_string(), expected: self.kind.description() };
+        let invalid = |reason: String| RegistryError::InvalidParam { name: self.name.to_string(), reason };
+
+        match (&self.kind, value) {
+            (ParamKind::Bool, ParamValue::Bool(_)) | (ParamKind::Text, ParamValue::Text(_)) => Ok(()),
+            (ParamKind::Color, ParamValue::Color(color)) => check_color(self.name, color),
+            (ParamKind::Color, ParamValue::Text(text)) => parse_hex_color(text).map(|_| ()).ok_or_else(wrong_type),
+            (ParamKind::Choice(options), ParamValue::Text(text)) => {
+                if options.contains(&text.as_str()) {
+                    Ok(())
+                } else {
+                    Err(invalid(format!("\"{text}\" should be one of {}", options.join(", "))))
+                }
+            },
+            (ParamKind::Number | ParamKind::Integer, ParamValue::Number(number)) => {
+                if !number.is_finite() {
+                    return Err(invalid(format!("{number} isn't a finite number")))
+                }
+                if self.kind == ParamKind::Integer && number.fract() != 0.0 {
+                    return Err(wrong_type())
+                }
+                if let Some(min) = self.min.filter(|min| number < min) {
+                    return Err(invalid(format!("{number} is less than the minimum of {min}")))
+                }
+                if let Some(max) = self.max.filter(|max| number > max) {
+                    return Err(invalid(format!("{number} is more than the maximum of {max}")))
+                }
+                Ok(())
+            },
+            _ => Err(wrong_type())
+        }
+    }
+}
+
+/// Check `params` against `schema`, and fill in the defaults of parameters that aren't set.
+/// 
+/// Parameters which aren't in the schema are an error, to catch misspelled names in config files.
+pub fn apply_schema(schema: &[ParamSpec], params: &Params) -> Result<Params, RegistryError> {
+    if let Some((name, _)) = params.iter().find(|(name, _)| !schema.iter().any(|spec| spec.name == *name)) {
+        return Err(RegistryError::UnknownParam(name.to_string()))
+    }
+
+    let mut applied = params.clone();
+    for spec in schema {
+        match params.get(spec.name) {
+            Some(value) => spec.validate(value)?,
+            None => match &spec.default {
+                Some(default) => applied.insert(spec.name, default.clone()),
+                None => return Err(RegistryError::MissingParam(spec.name.to_string()))
+            }
+        }
+    }
+    Ok(applied)
+}
+
+/// A function which creates a layer from parameters.
+pub type LayerConstructor<T> = dyn Fn(&Params) -> Result<Box<dyn Layer<T>>, RegistryError>;
+
+/// A function which creates a filter from parameters.
+pub type FilterConstructor<T> = dyn Fn(&Params) -> Result<Box<dyn Filter<T>>, RegistryError>;
+
+/// Layer types registered by name, with a constructor from [`Params`].
+pub struct LayerRegistry<T: PixelChannel> {
+    constructors: HashMap<String, Box<LayerConstructor<T>>>
+}
+
+impl<T: PixelChannel + 'static> LayerRegistry<T> {
+    /// Create a registry with no layer types.
+    pub fn new() -> Self {
+        Self { constructors: HashMap::new() }
+    }
+
+    /// Create a registry with the built-in shape layers:
+    /// - `rectangle`: `x`, `y`, `width`, `height`, `fill`
+    /// - `rounded_rectangle`: `x`, `y`, `width`, `height`, `fill`, `radius`
+    /// - `circle`: `center_x`, `center_y`, `radius`, `fill`, and optionally `antialias`
+    /// - `ellipse`: `center_x`, `center_y`, `radius_x`, `radius_y`, `fill`, and optionally `antialias`
+    /// - `arc`: `center_x`, `center_y`, `outer_radius`, `start_angle`, `sweep_angle`, `fill`, and optionally `inner_radius` and `antialias`
+    /// - `regular_polygon`: `center_x`, `center_y`, `radius`, `sides`, `fill`, and optionally `rotation`
+    /// - `star`: `center_x`, `center_y`, `outer_radius`, `inner_radius`, `points`, `fill`, and optionally `rotation`
+    /// - `path`: `d`, which is SVG path data, and optionally `fill`, `stroke` and `stroke_width`
+    /// - `arrow`: `start_x`, `start_y`, `end_x`, `end_y`, `color`, and optionally `width`, `head` (`triangle`, `open` or `none`)
+    ///   and `double_headed`
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register("rectangle", |params| Ok(Box::new(RectangleLayer::new(params.color("fill")?, params.rect()?))));
+        registry.register("rounded_rectangle", |params| {
+            let radii = CornerRadii::uniform(params.number("radius")? as f32);
+            Ok(Box::new(RoundedRectangleLayer::new(params.color("fill")?, params.rect()?, radii)))
+        });
+        registry.register("circle", |params| {
+            let mut circle = CircleLayer::new(params.color("fill")?, params.number("center_x")? as f32, params.number("center_y")? as f32, params.number("radius")? as f32);
+            circle.antialias = params.bool_or("antialias", true)?;
+            Ok(Box::new(circle))
+        });
+        registry.register("ellipse", |params| {
+            let mut ellipse = EllipseLayer::new(
+                params.color("fill")?,
+                params.number("center_x")? as f32,
+                params.number("center_y")? as f32,
+                params.number("radius_x")? as f32,
+                params.number("radius_y")? as f32
+            );
+            ellipse.antialias = params.bool_or("antialias", true)?;
+            Ok(Box::new(ellipse))
+        });
+        registry.register("arc", |params| {
+            let mut arc = ArcLayer::new(
+                params.color("fill")?,
+                params.number("center_x")? as f32,
+                params.number("center_y")? as f32,
+                params.number_or("inner_radius", 0.0)? as f32,
+                params.number("outer_radius")? as f32,
+                params.number("start_angle")? as f32,
+                params.number("sweep_angle")? as f32
+            );
+            arc.antialias = params.bool_or("antialias", true)?;
+            Ok(Box::new(arc))
+        });
+        registry.register("regular_polygon", |params| {
+            Ok(Box::new(RegularPolygonLayer::new(
+                params.color("fill")?,
+                params.number("center_x")? as f32,
+                params.number("center_y")? as f32,
+                params.number("radius")? as f32,
+                params.usize("sides")?,
+                params.number_or("rotation", 0.0)? as f32
+            )))
+        });
+        registry.register("star", |params| {
+            Ok(Box::new(StarLayer::new(
+                params.color("fill")?,
+                params.number("center_x")? as f32,
+                params.number("center_y")? as f32,
+                params.number("outer_radius")? as f32,
+                params.number("inner_radius")? as f32,
+                params.usize("points")?,
+                params.number_or("rotation", 0.0)? as f32
+            )))
+        });
+        registry.register("path", |params| {
+            let mut path = PathLayer::from_svg(params.text("d")?)
+                .map_err(|error| RegistryError::InvalidParam { name: "d".to_string(), reason: error.to_string() })?;
+            if params.get("fill").is_some() {
+                path = path.with_fill(params.color("fill")?);
+            }
+            if params.get("stroke").is_some() {
+                path = path.with_stroke(params.color("stroke")?, params.number_or("stroke_width", 1.0)? as f32);
+            }
+            Ok(Box::new(path))
+        });
+        registry.register("arrow", |params| {
+            let mut arrow = ArrowLayer::new(
+                params.color("color")?,
+                (params.number("start_x")? as f32, params.number("start_y")? as f32),
+                (params.number("end_x")? as f32, params.number("end_y")? as f32),
+                params.number_or("width", 2.0)? as f32
+            );
+            if params.get("head").is_some() {
+                arrow.head = match params.text("head")? {
+                    "triangle" => ArrowHead::Triangle,
+                    "open" => ArrowHead::Open,
+                    "none" => ArrowHead::None,
+                    other => return Err(RegistryError::InvalidParam { name: "head".to_string(), reason: format!("unknown arrow head \"{other}\"") })
+                };
+            }
+            arrow.double_headed = params.bool_or("double_headed", false)?;
+            Ok(Box::new(arrow))
+        });
+        registry
+    }
+
+    /// Register a layer type under `name`, replacing any type already registered with that name.
+    pub fn register<F: Fn(&Params) -> Result<Box<dyn Layer<T>>, RegistryError> + 'static>(&mut self, name: &str, constructor: F) {
+        self.constructors.insert(name.to_string(), Box::new(constructor));
+    }
+
+    /// Create a layer of the type registered under `name`.
+    pub fn create(&self, name: &str, params: &Params) -> Result<Box<dyn Layer<T>>, RegistryError> {
+        let constructor = self.constructors.get(name).ok_or_else(|| RegistryError::UnknownType(name.to_string()))?;
+        constructor(params)
+    }
+
+    /// Get the names of the registered layer types, sorted alphabetically.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.constructors.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+}
+
+impl<T: PixelChannel + 'static> Default for LayerRegistry<T> {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+struct FilterEntry<T> {
+    constructor: Box<FilterConstructor<T>>,
+    schema: Option<Vec<ParamSpec>>
+}
+
+/// Filter types registered by name, with a constructor from [`Params`] and optionally a schema of their parameters.
+pub struct FilterRegistry<T: PixelChannel> {
+    entries: HashMap<String, FilterEntry<T>>
+}
+
+impl<T: PixelChannel + 'static> FilterRegistry<T> {
+    /// Create a registry with no filter types.
+    pub fn new() -> Self {
+        Self { entries: HashMap::new() }
+    }
+
+    /// Create a registry with the built-in filters. Use [`FilterRegistry::schema`] to get their parameters.
+    /// 
+    /// The filters are `brightness`, `translate`, `rotate`, `scale`, `flip`, `edge_mode`, `scanlines`, `glitch` and `vhs_noise`.
+    pub fn with_builtins() -> Self {
+        use ParamKind::*;
+        use ParamValue::Number as N;
+
+        let mut registry = Self::new();
+        registry.register_with_schema(
+            "brightness",
+            vec![ParamSpec::new("multiplier", Number, "Multiplier of each colour channel").with_min(0.0).with_default(N(1.0))],
+            |params| Ok(Box::new(BrightnessFilter { multiplier: params.number("multiplier")? as f32 }))
+        );
+        registry.register_with_schema(
+            "translate",
+            vec![
+                ParamSpec::new("x", Integer, "Pixels to move right").with_default(N(0.0)),
+                ParamSpec::new("y", Integer, "Pixels to move down").with_default(N(0.0))
+            ],
+            |params| Ok(Box::new(TranslateFilter { x: params.number("x")? as isize, y: params.number("y")? as isize }))
+        );
+        registry.register_with_schema(
+            "rotate",
+            vec![ParamSpec::new("angle", Number, "Degrees clockwise around the center of the layer").with_range(-360.0, 360.0).with_default(N(0.0))],
+            |params| Ok(Box::new(MatrixTransform::around_layer_center().rotate(params.number("angle")? as f32)))
+        );
+        registry.register_with_schema(
+            "scale",
+            vec![ParamSpec::new("factor", Number, "Scale around the center of the layer").with_min(0.0).with_default(N(1.0))],
+            |params| Ok(Box::new(MatrixTransform::around_layer_center().scale(params.number("factor")? as f32)))
+        );
+        registry.register_with_schema(
+            "flip",
+            vec![
+                ParamSpec::new("horizontal", Bool, "Flip left to right").with_default(ParamValue::Bool(false)),
+                ParamSpec::new("vertical", Bool, "Flip top to bottom").with_default(ParamValue::Bool(false))
+            ],
+            |params| Ok(Box::new(FlipFilter { horizontal: params.bool("horizontal")?, vertical: params.bool("vertical")? }))
+        );
+        registry.register_with_schema(
+            "edge_mode",
+            vec![ParamSpec::new("mode", Choice(vec!["transparent", "clamp", "wrap", "mirror"]), "How to sample outside of the layer")],
+            |params| {
+                let mode = match params.text("mode")? {
+                    "clamp" => EdgeMode::Clamp,
+                    "wrap" => EdgeMode::Wrap,
+                    "mirror" => EdgeMode::Mirror,
+                    _ => EdgeMode::Transparent
+                };
+                Ok(Box::new(EdgeModeFilter::new(mode)))
+            }
+        );
+        registry.register_with_schema(
+            "scanlines",
+            vec![
+                ParamSpec::new("spacing", Integer, "Rows from the start of one scanline to the next").with_min(1.0).with_default(N(4.0)),
+                ParamSpec::new("thickness", Integer, "Rows darkened in each scanline").with_min(0.0).with_default(N(1.0)),
+                ParamSpec::new("intensity", Number, "How much the scanlines are darkened").with_range(0.0, 1.0).with_default(N(0.5))
+            ],
+            |params| {
+                Ok(Box::new(ScanlineFilter { spacing: params.usize("spacing")?, thickness: params.usize("thickness")?, intensity: params.number("intensity")? as f32 }))
+            }
+        );
+        registry.register_with_schema(
+            "glitch",
+            vec![ParamSpec::new("seed", Integer, "Seed of the random offsets").with_min(0.0).with_default(N(0.0))],
+            |params| Ok(Box::new(GlitchFilter::new(params.usize("seed")? as u64)))
+        );
+        registry.register_with_schema(
+            "vhs_noise",
+            vec![ParamSpec::new("seed", Integer, "Seed of the random noise").with_min(0.0).with_default(N(0.0))],
+            |params| Ok(Box::new(VhsNoiseFilter::new(params.usize("seed")? as u64)))
+        );
+        registry
+    }
+
+    /// Register a filter type under `name`, replacing any type already registered with that name.
+    /// 
+    /// Parameters are passed to `constructor` unchecked. Use [`FilterRegistry::register_with_schema`] to describe them.
+    pub fn register<F: Fn(&Params) -> Result<Box<dyn Filter<T>>, RegistryError> + 'static>(&mut self, name: &str, constructor: F) {
+        self.entries.insert(name.to_string(), FilterEntry { constructor: Box::new(constructor), schema: None });
+    }
+
+    /// Register a filter type under `name` with a schema of its parameters, replacing any type already registered with that name.
+    /// 
+    /// Parameters are checked against the schema before being passed to `constructor`, with the defaults filled in.
+    pub fn register_with_schema<F: Fn(&Params) -> Result<Box<dyn Filter<T>>, RegistryError> + 'static>(&mut self, name: &str, schema: Vec<ParamSpec>, constructor: F) {
+        self.entries.insert(name.to_string(), FilterEntry { constructor: Box::new(constructor), schema: Some(schema) });
+    }
+
+    /// Get the schema of the parameters of the filter type registered under `name`, if it was registered with one.
+    pub fn schema(&self, name: &str) -> Option<&[ParamSpec]> {
+        self.entries.get(name)?.schema.as_deref()
+    }
+
+    /// Create a filter of the type registered under `name`.
+    pub fn create(&self, name: &str, params: &Params) -> Result<Box<dyn Filter<T>>, RegistryError> {
+        let entry = self.entries.get(name).ok_or_else(|| RegistryError::UnknownType(name.to_string()))?;
+        match &entry.schema {
+            Some(schema) => (entry.constructor)(&apply_schema(schema, params)?),
+            None => (entry.constructor)(params)
+        }
+    }
+
+    /// Get the names of the registered filter types, sorted alphabetically.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+}
+
+impl<T: PixelChannel + 'static> Default for FilterRegistry<T> {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rgba;
+
+    #[test]
+    fn params() {
+        let params = Params::new()
+            .with("size", ParamValue::Number(3.0))
+            .with("half", ParamValue::Number(0.5))
+            .with("color", ParamValue::Text("#10203040".to_string()))
+            .with("name", ParamValue::Bool(true));
+
+        assert_eq!(params.usize("size"), Ok(3));
+        assert!(matches!(params.usize("half"), Err(RegistryError::WrongType { .. })));
+        assert_eq!(params.color::<u8>("color"), Ok(rgba!(16, 32, 48, 64)));
+        assert_eq!(params.number_or("missing", 2.0), Ok(2.0));
+        assert_eq!(params.number("missing"), Err(RegistryError::MissingParam("missing".to_string())));
+        assert!(matches!(params.text("name"), Err(RegistryError::WrongType { .. })));
+    }
+
+    #[test]
+    fn out_of_range_colors() {
+        let layers: LayerRegistry<u8> = LayerRegistry::with_builtins();
+        for channel in [2.0, -0.5, f32::NAN] {
+            let params = Params::new()
+                .with("x", ParamValue::Number(0.0))
+                .with("y", ParamValue::Number(0.0))
+                .with("width", ParamValue::Number(4.0))
+                .with("height", ParamValue::Number(4.0))
+                .with("fill", ParamValue::Color(AlphaPixel { r: channel, g: 0.0, b: 0.0, a: 1.0 }));
+            assert!(matches!(params.color::<u8>("fill"), Err(RegistryError::InvalidParam { .. })));
+            assert!(matches!(layers.create("rectangle", &params), Err(RegistryError::InvalidParam { .. })));
+        }
+    }
+
+    #[test]
+    fn custom_types() {
+        let mut layers: LayerRegistry<u8> = LayerRegistry::new();
+        assert!(matches!(layers.create("square", &Params::new()), Err(RegistryError::UnknownType(_))));
+
+        layers.register("square", |params| {
+            let size = params.usize("size")?;
+            Ok(Box::new(RectangleLayer::new(AlphaPixel::blue(), Rect { x: 0, y: 0, width: size, height: size })))
+        });
+        let square = layers.create("square", &Params::new().with("size", ParamValue::Number(4.0))).unwrap();
+        assert_eq!(square.get_rect().width, 4);
+        assert_eq!(layers.names(), vec!["square"]);
+    }
+
+    #[test]
+    fn svg_path() {
+        let layers: LayerRegistry<u8> = LayerRegistry::with_builtins();
+        let params = Params::new()
+            .with("d", ParamValue::Text("M 0 0 H 10 V 10 H 0 Z".to_string()))
+            .with("fill", ParamValue::Text("#ff0000".to_string()));
+        let square = layers.create("path", &params).unwrap();
+        assert_eq!(square.filtered_pixel_at(5, 5).unwrap(), AlphaPixel::red());
+
+        let broken = Params::new().with("d", ParamValue::Text("L 10 10".to_string()));
+        assert!(matches!(layers.create("path", &broken), Err(RegistryError::InvalidParam { .. })));
+    }
+
+    #[test]
+    fn arrow() {
+        let layers: LayerRegistry<u8> = LayerRegistry::with_builtins();
+        let params = Params::new()
+            .with("start_x", ParamValue::Number(0.0))
+            .with("start_y", ParamValue::Number(5.0))
+            .with("end_x", ParamValue::Number(30.0))
+            .with("end_y", ParamValue::Number(5.0))
+            .with("color", ParamValue::Text("#ff0000".to_string()))
+            .with("head", ParamValue::Text("none".to_string()));
+        let arrow = layers.create("arrow", &params).unwrap();
+        assert_eq!(arrow.filtered_pixel_at(29, 4).unwrap(), AlphaPixel::red());
+
+        let params = params.with("head", ParamValue::Text("curly".to_string()));
+        assert!(matches!(layers.create("arrow", &params), Err(RegistryError::InvalidParam { .. })));
+    }
+
+    #[test]
+    fn builtin_filters() {
+        let filters: FilterRegistry<u8> = FilterRegistry::with_builtins();
+        let params = Params::new().with("mode", ParamValue::Text("sideways".to_string()));
+        assert!(matches!(filters.create("edge_mode", &params), Err(RegistryError::InvalidParam { .. })));
+
+        let mut layer = RectangleLayer::new(AlphaPixel::<u8>::red(), Rect { x: 0, y: 0, width: 4, height: 4 });
+        layer.filters.push(filters.create("translate", &Params::new().with("x", ParamValue::Number(2.0)).with("y", ParamValue::Number(0.0))).unwrap());
+        assert!(layer.filtered_pixel_at(5, 0).is_some());
+    }
+
+    #[test]
+    fn filter_schema() {
+        let filters: FilterRegistry<u8> = FilterRegistry::with_builtins();
+        let schema = filters.schema("scanlines").unwrap();
+        assert_eq!(schema.iter().map(|spec| spec.name).collect::<Vec<_>>(), vec!["spacing", "thickness", "intensity"]);
+
+        // Defaults are filled in
+        assert!(filters.create("scanlines", &Params::new()).is_ok());
+
+        let too_intense = Params::new().with("intensity", ParamValue::Number(2.0));
+        assert!(matches!(filters.create("scanlines", &too_intense), Err(RegistryError::InvalidParam { .. })));
+
+        let misspelled = Params::new().with("intensty", ParamValue::Number(0.5));
+        assert_eq!(filters.create("scanlines", &misspelled).err(), Some(RegistryError::UnknownParam("intensty".to_string())));
+
+        for number in [f64::NAN, f64::INFINITY] {
+            let not_finite = Params::new().with("multiplier", ParamValue::Number(number));
+            assert!(matches!(filters.create("brightness", &not_finite), Err(RegistryError::InvalidParam { .. })));
+        }
+
+        let fractional = Params::new().with("spacing", ParamValue::Number(2.5));
+        assert!(matches!(filters.create("scanlines", &fractional), Err(RegistryError::WrongType { .. })));
+
+        assert_eq!(filters.create("edge_mode", &Params::new()).err(), Some(RegistryError::MissingParam("mode".to_string())));
+    }
+
+    #[test]
+    fn validate_colors() {
+        let spec = ParamSpec::new("tint", ParamKind::Color, "The colour to tint with");
+        assert!(spec.validate(&ParamValue::Color(AlphaPixel { r: 1.0, g: 0.5, b: 0.0, a: 1.0 })).is_ok());
+        assert!(spec.validate(&ParamValue::Text("#ff8000".to_string())).is_ok());
+        for channel in [1.5, -0.1, f32::NAN] {
+            let color = ParamValue::Color(AlphaPixel { r: 0.0, g: channel, b: 0.0, a: 1.0 });
+            assert!(matches!(spec.validate(&color), Err(RegistryError::InvalidParam { .. })));
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Seeded pseudo-random numbers for procedural layers and filters.
+//! 
+//! Everything random in this crate is generated from a seed, using only integer arithmetic,
+//! so that renders are reproducible across runs and platforms.
+
+const GOLDEN_GAMMA: u64 = 0x9E3779B97F4A7C15;
+
+/// The [SplitMix64](https://prng.di.unimi.it/splitmix64.c) mixing function
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Convert the top 24 bits of a `u64` to a float in the range `[0, 1)`.
+fn unit_from_bits(bits: u64) -> f32 {
+    (bits >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Hash a seed and a value to a pseudo-random `u64`.
+/// 
+/// This is used by filters that need random values for a coordinate without storing any state.
+pub fn hash(seed: u64, value: u64) -> u64 {
+    mix(seed.wrapping_add(value.wrapping_mul(GOLDEN_GAMMA)))
+}
+
+/// Hash a seed and a value to a pseudo-random float in the range `[0, 1)`.
+/// 
+/// # Example
+/// ```
+/// use image_template::rng::hash_unit;
+/// 
+/// assert_eq!(hash_unit(10, 5), hash_unit(10, 5));
+/// assert!((0.0..1.0).contains(&hash_unit(10, 5)));
+/// ```
+pub fn hash_unit(seed: u64, value: u64) -> f32 {
+    unit_from_bits(hash(seed, value))
+}
+
+#[derive(Debug, Clone)]
+/// A seeded pseudo-random number generator, using SplitMix64.
+/// 
+/// # Example
+/// ```
+/// use image_template::Rng;
+/// 
+/// let mut rng = Rng::new(42);
+/// let mut same_rng = Rng::new(42);
+/// assert_eq!(rng.next_u64(), same_rng.next_u64());
+/// ```
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        mix(self.state)
+    }
+
+    /// Get a float in the range `[0, 1)`
+    pub fn next_f32(&mut self) -> f32 {
+        unit_from_bits(self.next_u64())
+    }
+
+    /// Get a float in the range `[min, max)`
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + self.next_f32() * (max - min)
+    }
+
+    /// Get an integer in the range `[min, max)`. Returns `min` if the range is empty.
+    pub fn range_usize(&mut self, min: usize, max: usize) -> usize {
+        if max <= min {
+            return min
+        }
+        min + (self.next_u64() % (max - min) as u64) as usize
+    }
+
+    /// Create a new generator with a seed taken from this one.
+    /// 
+    /// This is useful for giving each procedural layer or filter its own reproducible seed.
+    pub fn fork(&mut self) -> Rng {
+        Rng::new(self.next_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let mut first = Rng::new(1234);
+        let mut second = Rng::new(1234);
+        for _ in 0..100 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn known_sequence() {
+        // Reference values for SplitMix64, so that changes to the generator are caught
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::new(99);
+        for _ in 0..1000 {
+            assert!((0.0..1.0).contains(&rng.next_f32()));
+            assert!((-5.0..5.0).contains(&rng.range_f32(-5.0, 5.0)));
+            assert!((3..8).contains(&rng.range_usize(3, 8)));
+        }
+        assert_eq!(rng.range_usize(5, 5), 5);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    Canvas,
+    Image,
+    PixelChannel,
+    Rect,
+    Layer,
+    Error,
+    layers::{image::ImageLayer, text::{TextLayer, TextSettings}}
+};
+#[cfg(feature = "qr")]
+use crate::{AlphaPixel, layers::qr::QrCodeLayer};
+
+/// The smallest font size that a [`TextSlot`] shrinks text to.
+const MIN_FONT_SIZE: f32 = 6.0;
+
+/// An area of a template where text is drawn, centered in `rect`.
+/// 
+/// The `text` of `settings` is replaced by the text of each record. If the text is wider than `rect`,
+/// the font size is reduced until it fits, so that long names don't overflow.
+#[derive(Clone)]
+pub struct TextSlot<T: PixelChannel> {
+    pub settings: TextSettings<T>,
+    pub rect: Rect
+}
+
+impl<T: PixelChannel> TextSlot<T> {
+    pub fn new(settings: TextSettings<T>, rect: Rect) -> Self {
+        Self { settings, rect }
+    }
+
+    /// Create a text layer for `text`, shrunk to fit the width of the slot and centered in it.
+    pub fn layer(&self, text: &str) -> Result<TextLayer<T>, Error> {
+        let mut settings = self.settings.clone();
+        settings.text = text.to_string();
+        let mut layer = TextLayer::try_new(settings, 0, 0)?;
+
+        // Scale the font by how much too wide the text is, which is close to fitting as glyphs don't scale exactly
+        while layer.get_rect().width > self.rect.width && layer.get_settings().size > MIN_FONT_SIZE {
+            let mut settings = layer.get_settings().clone();
+            let scale = self.rect.width as f32 / layer.get_rect().width as f32;
+            settings.size = (settings.size * scale.min(0.95)).max(MIN_FONT_SIZE);
+            layer.set_settings(settings)?;
+        }
+
+        let text_rect = layer.get_rect();
+        layer.x = self.rect.x + self.rect.width.saturating_sub(text_rect.width) / 2;
+        layer.y = self.rect.y + self.rect.height.saturating_sub(text_rect.height) / 2;
+        Ok(layer)
+    }
+}
+
+/// How serial numbers are written, such as `CERT-000042`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerialFormat {
+    pub prefix: String,
+    /// The serial number of the first record in a batch
+    pub start: u64,
+    /// The minimum number of digits, which shorter numbers are padded to with zeros
+    pub digits: usize
+}
+
+impl SerialFormat {
+    pub fn new(prefix: &str, start: u64, digits: usize) -> Self {
+        Self { prefix: prefix.to_string(), start, digits }
+    }
+
+    /// Get the serial number of the record at `index` in a batch.
+    pub fn format(&self, index: usize) -> String {
+        format!("{}{:0width$}", self.prefix, self.start + index as u64, width = self.digits)
+    }
+}
+
+impl Default for SerialFormat {
+    fn default() -> Self {
+        Self::new("", 1, 6)
+    }
+}
+
+/// Where a QR code is drawn on a certificate, and what it encodes.
+#[cfg(feature = "qr")]
+#[derive(Clone)]
+pub struct QrSlot<T> {
+    pub rect: Rect,
+    /// The data of the code, where `{serial}` is replaced by the serial number, such as a verification URL
+    pub data: String,
+    pub dark: AlphaPixel<T>,
+    pub light: AlphaPixel<T>
+}
+
+#[cfg(feature = "qr")]
+impl<T: PixelChannel> QrSlot<T> {
+    /// A black and white QR code in `rect`, encoding `data`.
+    pub fn new(rect: Rect, data: &str) -> Self {
+        Self { rect, data: data.to_string(), dark: AlphaPixel::black(), light: AlphaPixel::white() }
+    }
+}
+
+/// The inputs which change between each certificate in a batch.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CertificateRecord {
+    pub name: String,
+    pub date: String
+}
+
+impl CertificateRecord {
+    pub fn new(name: &str, date: &str) -> Self {
+        Self { name: name.to_string(), date: date.to_string() }
+    }
+}
+
+/// A certificate or ticket, made from a background image with a name, and optionally a date, serial number and
+/// QR code, drawn over it for each record in a batch.
+/// 
+/// Serial numbers increase by one for each record, starting from [`SerialFormat::start`].
+/// 
+/// # Example
+/// ```rust,no_run
+/// # #[cfg(all(feature = "image-crate", feature = "qr"))] {
+/// use image_template::{Image, Rect, AlphaPixel, ImageFormat};
+/// use image_template::layers::text::{TextSettings, layout::TextLayout};
+/// use image_template::templates::certificate::{CertificateTemplate, CertificateRecord, TextSlot, SerialFormat, QrSlot};
+/// 
+/// let font = fontdue::Font::from_bytes(std::fs::read("font.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+/// let style = TextSettings { size: 60.0, fill: AlphaPixel::black(), layout: TextLayout::default(), text: String::new(), font };
+/// let background: Image<u8> = Image::load_from_file("certificate.png", ImageFormat::Png).unwrap();
+/// 
+/// let template = CertificateTemplate::new(background, TextSlot::new(style.clone(), Rect { x: 200, y: 400, width: 1000, height: 100 }))
+///     .with_date(TextSlot::new(TextSettings { size: 30.0, ..style.clone() }, Rect { x: 200, y: 550, width: 400, height: 50 }))
+///     .with_serial(TextSlot::new(TextSettings { size: 20.0, ..style }, Rect { x: 800, y: 550, width: 400, height: 50 }), SerialFormat::new("CERT-", 1, 5))
+///     .with_qr_code(QrSlot::new(Rect { x: 1250, y: 800, width: 150, height: 150 }, "https://example.com/verify/{serial}"));
+/// 
+/// let records = [CertificateRecord::new("Ada Lovelace", "10 December 1843"), CertificateRecord::new("Alan Turing", "23 June 1936")];
+/// for (index, certificate) in template.render_batch(&records).unwrap().iter().enumerate() {
+///     certificate.save(format!("{}.png", template.serial(index)), ImageFormat::Png).unwrap();
+/// }
+/// # }
+/// ```
+pub struct CertificateTemplate<T: PixelChannel> {
+    pub background: Image<T>,
+    pub name: TextSlot<T>,
+    pub date: Option<TextSlot<T>>,
+    pub serial: Option<TextSlot<T>>,
+    pub serial_format: SerialFormat,
+    #[cfg(feature = "qr")]
+    pub qr_code: Option<QrSlot<T>>
+}
+
+impl<T: PixelChannel + 'static> CertificateTemplate<T> {
+    /// Create a template the size of `background`, which only has a name.
+    pub fn new(background: Image<T>, name: TextSlot<T>) -> Self {
+        Self {
+            background,
+            name,
+            date: None,
+            serial: None,
+            serial_format: SerialFormat::default(),
+            #[cfg(feature = "qr")]
+            qr_code: None
+        }
+    }
+
+    pub fn with_date(mut self, slot: TextSlot<T>) -> Self {
+        self.date = Some(slot);
+        self
+    }
+
+    /// Draw the serial number of each record in `slot`, written with `format`.
+    pub fn with_serial(mut self, slot: TextSlot<T>, format: SerialFormat) -> Self {
+        self.serial = Some(slot);
+        self.serial_format = format;
+        self
+    }
+
+    #[cfg(feature = "qr")]
+    pub fn with_qr_code(mut self, slot: QrSlot<T>) -> Self {
+        self.qr_code = Some(slot);
+        self
+    }
+
+    /// Get the serial number of the record at `index` in a batch.
+    pub fn serial(&self, index: usize) -> String {
+        self.serial_format.format(index)
+    }
+
+    /// Create the canvas for the record at `index` in a batch, so that more layers can be added before it is flattened.
+    pub fn canvas(&self, record: &CertificateRecord, index: usize) -> Result<Canvas<T>, Error> {
+        let mut canvas = Canvas::from_dimensions(self.background.get_width(), self.background.get_height());
+        canvas.add_layer(ImageLayer::new(self.background.clone(), 0, 0));
+        canvas.add_layer(self.name.layer(&record.name)?);
+        if let Some(date) = &self.date {
+            canvas.add_layer(date.layer(&record.date)?);
+        }
+
+        let serial = self.serial(index);
+        if let Some(slot) = &self.serial {
+            canvas.add_layer(slot.layer(&serial)?);
+        }
+        #[cfg(feature = "qr")]
+        if let Some(slot) = &self.qr_code {
+            let code = QrCodeLayer::try_new(&slot.data.replace("{serial}", &serial), slot.rect)?;
+            canvas.add_layer(code.with_colors(slot.dark, slot.light));
+        }
+
+        Ok(canvas)
+    }
+
+    /// Render a certificate for each record, numbered in order.
+    pub fn render_batch(&self, records: &[CertificateRecord]) -> Result<Vec<Image<T>>, Error> {
+        records.iter()
+            .enumerate()
+            .map(|(index, record)| self.canvas(record, index)?.try_flatten())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serial_format() {
+        let format = SerialFormat::new("TICKET-", 98, 3);
+        assert_eq!(format.format(0), "TICKET-098");
+        assert_eq!(format.format(5), "TICKET-103");
+        assert_eq!(SerialFormat::default().format(0), "000001");
+    }
+}
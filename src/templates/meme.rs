@@ -0,0 +1,148 @@
+use fontdue::Font;
+use crate::{
+    Canvas,
+    Image,
+    AlphaPixel,
+    PixelChannel,
+    Layer,
+    Error,
+    layers::{
+        image::ImageLayer,
+        container::{CrossAlign, flow::{FlowLayer, FlowDirection}},
+        text::{TextLayer, TextSettings, layout::{TextLayout, TextTransform}, stroke::TextStroke}
+    }
+};
+
+/// The smallest font size that captions shrink to, in pixels.
+const MIN_FONT_SIZE: f32 = 8.0;
+
+/// The appearance of the captions of a [`meme`]. Sizes are fractions of the image, so that the same style
+/// suits images of any size.
+#[derive(Clone)]
+pub struct MemeStyle<T> {
+    /// The caption font, which is traditionally Impact
+    pub font: Font,
+    pub fill: AlphaPixel<T>,
+    pub outline: AlphaPixel<T>,
+    /// The width of the outline, as a fraction of the font size
+    pub outline_width: f32,
+    /// The largest font size, as a fraction of the height of the image
+    pub max_font_size: f32,
+    /// The tallest that each caption can be, as a fraction of the height of the image
+    pub max_caption_height: f32,
+    /// The space between the captions and the edges of the image, as a fraction of the width of the image
+    pub margin: f32
+}
+
+impl<T: PixelChannel> MemeStyle<T> {
+    /// The classic style, with white text and a black outline.
+    pub fn new(font: Font) -> Self {
+        Self {
+            font,
+            fill: AlphaPixel::white(),
+            outline: AlphaPixel::black(),
+            outline_width: 0.08,
+            max_font_size: 0.12,
+            max_caption_height: 0.3,
+            margin: 0.03
+        }
+    }
+}
+
+/// Create a canvas with `top` and `bottom` captions over `image`, in uppercase with an outline.
+/// 
+/// Each caption is wrapped to the width of the image, and its font size is reduced until it fits in
+/// [`MemeStyle::max_caption_height`]. Each line is centered. An empty caption isn't drawn.
+/// 
+/// # Example
+/// ```rust,no_run
+/// # #[cfg(feature = "image-crate")] {
+/// use image_template::{Image, ImageFormat, templates::meme::{meme, MemeStyle}};
+/// 
+/// let font = fontdue::Font::from_bytes(std::fs::read("impact.ttf").unwrap(), fontdue::FontSettings::default()).unwrap();
+/// let photo: Image<u8> = Image::load_from_file("cat.jpg", ImageFormat::Jpeg).unwrap();
+/// 
+/// let canvas = meme(&photo, "I can has", "cheezburger?", &MemeStyle::new(font)).unwrap();
+/// canvas.flatten().save("meme.png", ImageFormat::Png).unwrap();
+/// # }
+/// ```
+pub fn meme<T: PixelChannel + 'static>(image: &Image<T>, top: &str, bottom: &str, style: &MemeStyle<T>) -> Result<Canvas<T>, Error> {
+    let (width, height) = (image.get_width(), image.get_height());
+    let margin = (width as f32 * style.margin) as usize;
+    let max_width = width.saturating_sub(2 * margin);
+    let max_height = (height as f32 * style.max_caption_height) as usize;
+
+    let mut canvas = Canvas::from_dimensions(width, height);
+    canvas.add_layer(ImageLayer::new(image.clone(), 0, 0));
+
+    if let Some(lines) = fit_caption(top, style, height, max_width, max_height)? {
+        canvas.add_layer(centered_lines(lines, width, margin));
+    }
+    if let Some(lines) = fit_caption(bottom, style, height, max_width, max_height)? {
+        let caption_height = lines_height(&lines);
+        canvas.add_layer(centered_lines(lines, width, height.saturating_sub(margin + caption_height)));
+    }
+
+    Ok(canvas)
+}
+
+/// Wrap and shrink a caption until it fits in `max_width` and `max_height`, returning a text layer for each line,
+/// or `None` if the caption is empty.
+fn fit_caption<T: PixelChannel>(caption: &str, style: &MemeStyle<T>, image_height: usize, max_width: usize, max_height: usize) -> Result<Option<Vec<TextLayer<T>>>, Error> {
+    if caption.trim().is_empty() {
+        return Ok(None)
+    }
+
+    let mut size = (image_height as f32 * style.max_font_size).max(MIN_FONT_SIZE);
+    loop {
+        let outline_width = size * style.outline_width;
+        let mut settings = TextSettings {
+            size,
+            fill: style.fill,
+            layout: TextLayout { transform: TextTransform::Uppercase, ..TextLayout::default() },
+            text: caption.to_string(),
+            font: style.font.clone()
+        };
+        settings.wrap_text(max_width as f32 - 2.0 * outline_width);
+
+        // Lines are created away from the edge of the canvas, so that their outlines aren't cut off
+        let padding = outline_width.ceil() as usize + 1;
+        let lines = settings.text.lines()
+            .map(|line| {
+                let mut layer = TextLayer::try_new(TextSettings { text: line.to_string(), ..settings.clone() }, padding, padding)?;
+                layer.set_strokes(vec![TextStroke::solid(style.outline, outline_width)])?;
+                Ok(layer)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let widest = lines.iter().map(|line| line.get_rect().width).max().unwrap_or(0);
+        let fits = widest <= max_width && lines_height(&lines) <= max_height;
+        if fits || size <= MIN_FONT_SIZE {
+            return Ok(Some(lines))
+        }
+        size = (size * 0.9).max(MIN_FONT_SIZE);
+    }
+}
+
+/// The gap between lines of a caption, as a fraction of the font size.
+const LINE_GAP: f32 = 0.1;
+
+fn line_gap<T: PixelChannel>(lines: &[TextLayer<T>]) -> usize {
+    lines.first().map_or(0, |line| (line.get_settings().size * LINE_GAP) as usize)
+}
+
+/// Get the height of lines stacked with [`line_gap`] between them.
+fn lines_height<T: PixelChannel>(lines: &[TextLayer<T>]) -> usize {
+    let gaps = line_gap(lines) * lines.len().saturating_sub(1);
+    lines.iter().map(|line| line.get_rect().height).sum::<usize>() + gaps
+}
+
+/// Stack lines with their top at `y`, centered horizontally in a canvas `width` pixels wide.
+fn centered_lines<T: PixelChannel + 'static>(lines: Vec<TextLayer<T>>, width: usize, y: usize) -> FlowLayer<T> {
+    let widest = lines.iter().map(|line| line.get_rect().width).max().unwrap_or(0);
+    let mut flow = FlowLayer::new(width.saturating_sub(widest) / 2, y, FlowDirection::Vertical, line_gap(&lines), CrossAlign::Center);
+    for line in lines {
+        flow.push(line);
+    }
+    flow
+}
@@ -0,0 +1,217 @@
+use crate::{
+    Canvas,
+    Image,
+    AlphaPixel,
+    PixelChannel,
+    Rect,
+    bitmap::sampler::BilinearSampler,
+    filters::transform::PerspectiveTransform,
+    layers::{image::ImageLayer, shapes::{CircleLayer, RoundedRectangleLayer, CornerRadii}}
+};
+
+/// The number of samples along each axis of a pixel when a screenshot is drawn in perspective, to antialias its edges.
+const SCREEN_SUPERSAMPLING: usize = 4;
+
+/// A browser window drawn around a screenshot, with a title bar containing window buttons and an address bar.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, templates::mockup::BrowserFrame};
+/// 
+/// let screenshot: Image<u8> = Image::new_with_fill(AlphaPixel::white(), 400, 300);
+/// let window = BrowserFrame::light().mockup(&screenshot).flatten();
+/// assert_eq!((window.get_width(), window.get_height()), (400, 340));
+/// assert_eq!(window.pixel_at(200, 200).unwrap(), AlphaPixel::white());
+/// ```
+#[derive(Clone, Copy)]
+pub struct BrowserFrame<T> {
+    /// The height of the title bar above the screenshot, in pixels
+    pub title_bar_height: usize,
+    pub title_bar: AlphaPixel<T>,
+    pub address_bar: AlphaPixel<T>,
+    /// The colours of the close, minimise and maximise buttons, from left to right
+    pub buttons: [AlphaPixel<T>; 3],
+    /// The radius of the top corners of the window
+    pub corner_radius: f32
+}
+
+impl<T: PixelChannel> BrowserFrame<T> {
+    /// A light grey window with red, yellow and green buttons.
+    pub fn light() -> Self {
+        Self {
+            title_bar_height: 40,
+            title_bar: color(232, 232, 232),
+            address_bar: color(255, 255, 255),
+            buttons: [color(255, 95, 87), color(254, 188, 46), color(40, 200, 64)],
+            corner_radius: 10.0
+        }
+    }
+
+    /// A dark grey window with red, yellow and green buttons.
+    pub fn dark() -> Self {
+        Self {
+            title_bar: color(53, 53, 56),
+            address_bar: color(30, 30, 32),
+            ..Self::light()
+        }
+    }
+
+    /// Create a canvas with `screenshot` below the title bar, which is as wide as the screenshot.
+    /// 
+    /// The address bar is left out if the screenshot is too narrow for it.
+    pub fn mockup(&self, screenshot: &Image<T>) -> Canvas<T> {
+        let width = screenshot.get_width();
+        let bar_height = self.title_bar_height;
+        let mut canvas = Canvas::from_dimensions(width, screenshot.get_height() + bar_height);
+
+        let radius = self.corner_radius;
+        let radii = CornerRadii { top_left: radius, top_right: radius, bottom_right: 0.0, bottom_left: 0.0 };
+        canvas.add_layer(RoundedRectangleLayer::new(self.title_bar, Rect { x: 0, y: 0, width, height: bar_height }, radii));
+
+        // The buttons and address bar are sized relative to the title bar, so that they suit any height
+        let bar_height = bar_height as f32;
+        let button_spacing = bar_height * 0.5;
+        let first_button = bar_height * 0.6;
+        for (index, fill) in self.buttons.into_iter().enumerate() {
+            let center_x = first_button + index as f32 * button_spacing;
+            canvas.add_layer(CircleLayer::new(fill, center_x, bar_height / 2.0, bar_height * 0.15));
+        }
+
+        let address_left = first_button + 3.0 * button_spacing;
+        let address_right = width as f32 - bar_height * 0.4;
+        let address_height = bar_height * 0.6;
+        if address_right - address_left >= address_height {
+            let rect = Rect {
+                x: address_left as usize,
+                y: ((bar_height - address_height) / 2.0) as usize,
+                width: (address_right - address_left) as usize,
+                height: address_height as usize
+            };
+            canvas.add_layer(RoundedRectangleLayer::new(self.address_bar, rect, CornerRadii::uniform(address_height / 2.0)));
+        }
+
+        canvas.add_layer(ImageLayer::new(screenshot.clone(), 0, self.title_bar_height));
+        canvas
+    }
+}
+
+/// A device, such as a phone or laptop, from an image of its frame with the screen at known corners.
+/// 
+/// The screen of the frame image should be transparent, as screenshots are drawn beneath the frame.
+/// The corners don't need to form a rectangle, so a photo of a device at an angle can be used, and screenshots
+/// are drawn onto it in perspective.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, templates::mockup::DeviceFrame};
+/// 
+/// // A black frame around a transparent screen, which is tilted away on the right
+/// let frame: Image<u8> = Image::from_function(100, 80, |x, y| {
+///     if x >= 10 && x < 90 && y >= 10 && y < 70 { AlphaPixel::default() } else { AlphaPixel::black() }
+/// });
+/// let phone = DeviceFrame::new(frame, [(10.0, 10.0), (90.0, 20.0), (90.0, 60.0), (10.0, 70.0)]);
+/// 
+/// let screenshot: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 320, 240);
+/// let mockup = phone.mockup(&screenshot).unwrap().flatten();
+/// assert_eq!(mockup.pixel_at(50, 40).unwrap(), AlphaPixel::red());
+/// assert_eq!(mockup.pixel_at(5, 40).unwrap(), AlphaPixel::black());
+/// ```
+#[derive(Clone)]
+pub struct DeviceFrame<T: PixelChannel> {
+    pub frame: Image<T>,
+    /// The corners of the screen in the frame image, in the order top left, top right, bottom right, bottom left
+    pub screen: [(f32, f32); 4]
+}
+
+impl<T: PixelChannel + 'static> DeviceFrame<T> {
+    pub fn new(frame: Image<T>, screen: [(f32, f32); 4]) -> Self {
+        Self { frame, screen }
+    }
+
+    /// A frame where the screen is a flat `Rect`, facing the viewer.
+    pub fn from_rect(frame: Image<T>, screen: Rect) -> Self {
+        let (left, top) = (screen.x as f32, screen.y as f32);
+        let (right, bottom) = (left + screen.width as f32, top + screen.height as f32);
+        Self::new(frame, [(left, top), (right, top), (right, bottom), (left, bottom)])
+    }
+
+    /// Create a canvas the size of the frame, with `screenshot` stretched onto the screen beneath it.
+    /// 
+    /// Returns `None` if 3 corners of the screen are in a line, or the screenshot is empty, so that
+    /// there is no way to draw the screenshot onto the screen.
+    pub fn mockup(&self, screenshot: &Image<T>) -> Option<Canvas<T>> {
+        let screenshot_rect = Rect { x: 0, y: 0, width: screenshot.get_width(), height: screenshot.get_height() };
+        let transform = PerspectiveTransform::from_rect(screenshot_rect, self.screen)?
+            .with_sampler(BilinearSampler)
+            .with_supersampling(SCREEN_SUPERSAMPLING);
+
+        let mut canvas = Canvas::from_dimensions(self.frame.get_width(), self.frame.get_height());
+        let mut screen = ImageLayer::new(screenshot.clone(), 0, 0);
+        screen.filters.push(Box::new(transform));
+        canvas.add_layer(screen);
+        canvas.add_layer(ImageLayer::new(self.frame.clone(), 0, 0));
+        Some(canvas)
+    }
+
+    /// Create a canvas with the screen filled with `fill` beneath the frame, such as to preview where the screen is.
+    pub fn blank(&self, fill: AlphaPixel<T>) -> Option<Canvas<T>> {
+        self.mockup(&Image::new_with_fill(fill, 1, 1))
+    }
+}
+
+/// An opaque colour from 8 bit channels.
+fn color<T: PixelChannel>(r: u8, g: u8, b: u8) -> AlphaPixel<T> {
+    AlphaPixel { r, g, b, a: 255 }.as_different_channel()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn browser_layout() {
+        let screenshot: Image<u8> = Image::new_with_fill(AlphaPixel::blue(), 300, 100);
+        let frame = BrowserFrame::light();
+        let canvas = frame.mockup(&screenshot);
+        assert_eq!((canvas.width, canvas.height), (300, 140));
+        let window = canvas.flatten();
+
+        // The top corner is rounded, and the first button is red
+        assert_eq!(window.pixel_at(0, 0).unwrap().a, 0);
+        assert_eq!(window.pixel_at(24, 20).unwrap(), frame.buttons[0]);
+        assert_eq!(window.pixel_at(150, 20).unwrap(), frame.address_bar);
+        assert_eq!(window.pixel_at(150, 2).unwrap(), frame.title_bar);
+        assert_eq!(window.pixel_at(0, 139).unwrap(), AlphaPixel::blue());
+    }
+
+    #[test]
+    fn narrow_browser() {
+        // Too narrow for the address bar, so only the buttons are drawn
+        let screenshot: Image<u8> = Image::new_with_fill(AlphaPixel::blue(), 90, 10);
+        let canvas = BrowserFrame::dark().mockup(&screenshot);
+        assert_eq!(canvas.layers.len(), 5);
+    }
+
+    #[test]
+    fn flat_screen() {
+        let frame: Image<u8> = Image::from_function(20, 20, |x, y| {
+            if (5..15).contains(&x) && (5..15).contains(&y) { AlphaPixel::default() } else { AlphaPixel::black() }
+        });
+        let device = DeviceFrame::from_rect(frame, Rect { x: 5, y: 5, width: 10, height: 10 });
+        let screenshot = Image::from_function(10, 10, |x, _y| if x < 5 { AlphaPixel::red() } else { AlphaPixel::green() });
+
+        let canvas = device.mockup(&screenshot).unwrap();
+        assert_eq!(canvas.layers[0].filtered_pixel_at(7, 10).unwrap(), AlphaPixel::red());
+        let image = canvas.flatten();
+        assert_eq!(image.pixel_at(6, 6).unwrap(), AlphaPixel::red());
+        assert_eq!(image.pixel_at(13, 13).unwrap(), AlphaPixel::green());
+        assert_eq!(image.pixel_at(2, 10).unwrap(), AlphaPixel::black());
+        assert!(device.blank(AlphaPixel::white()).is_some());
+    }
+
+    #[test]
+    fn degenerate_screen() {
+        let device = DeviceFrame::new(Image::<u8>::new_with_fill(AlphaPixel::black(), 10, 10), [(0.0, 0.0), (5.0, 5.0), (10.0, 10.0), (0.0, 10.0)]);
+        assert!(device.mockup(&Image::new_with_fill(AlphaPixel::red(), 4, 4)).is_none());
+    }
+}
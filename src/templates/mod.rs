@@ -0,0 +1,6 @@
+//! Ready made templates for common kinds of images, which combine layers into a [`Canvas`](crate::Canvas)
+//! from a few inputs.
+
+pub mod certificate;
+pub mod meme;
+pub mod mockup;
@@ -0,0 +1,200 @@
+//! Helpers for serving rendered images from web servers, such as endpoints which render Open Graph images on request.
+//! 
+//! [`ImageResponse`] encodes an image with its `Content-Type`, `Cache-Control` and `ETag` headers. It can be converted
+//! to an [`http::Response`], which is used by axum and hyper, or its [`headers`](ImageResponse::headers) can be added
+//! to a response in any other framework, such as actix-web.
+
+use std::{io::Cursor, time::Duration};
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use http::{Response, StatusCode, header};
+use crate::{Image, PixelChannel, AlphaPixel, Error};
+
+/// How long browsers and CDNs can cache a response for by default, in seconds.
+const DEFAULT_MAX_AGE: u64 = 60 * 60;
+
+/// An encoded image, with the headers to send it in an HTTP response.
+/// 
+/// The `ETag` is a hash of the image's pixels and format, so a request from a client which already has the same image
+/// can be answered with `304 Not Modified`, without sending it again.
+/// 
+/// # Example
+/// ```
+/// use image_template::{Image, AlphaPixel, web::ImageResponse};
+/// 
+/// let card: Image<u8> = Image::new_with_fill(AlphaPixel::white(), 1200, 630);
+/// let response = ImageResponse::png(&card).unwrap().with_max_age(std::time::Duration::from_secs(600));
+/// 
+/// let if_none_match = None; // The `If-None-Match` header of the request
+/// let http_response = response.into_http_response(if_none_match);
+/// assert_eq!(http_response.headers()["content-type"], "image/png");
+/// assert_eq!(http_response.headers()["cache-control"], "public, max-age=600");
+/// 
+/// // With axum, the body is converted to axum's body type:
+/// // http_response.map(axum::body::Body::from)
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageResponse {
+    pub body: Vec<u8>,
+    pub content_type: &'static str,
+    /// A strong entity tag, including its quotes
+    pub etag: String,
+    pub cache_control: String
+}
+
+impl ImageResponse {
+    /// Encode an image in `format`, with 8 bits per channel.
+    /// 
+    /// The alpha channel is removed for formats which don't support it, such as JPEG. Empty images are encoded as a
+    /// single transparent pixel, as most formats can't be empty.
+    pub fn encode<T: PixelChannel>(image: &Image<T>, format: ImageFormat) -> Result<Self, Error> {
+        let (width, height) = (image.get_width(), image.get_height());
+        let buffer = if width == 0 || height == 0 {
+            RgbaImage::new(1, 1)
+        } else {
+            let pixels: Vec<u8> = image.get_pixels().iter().flat_map(|pixel| {
+                let pixel: AlphaPixel<u8> = pixel.as_different_channel();
+                [pixel.r, pixel.g, pixel.b, pixel.a]
+            }).collect();
+            // The buffer always has the right length, as it has a pixel for each pixel of the image
+            RgbaImage::from_raw(width as u32, height as u32, pixels).unwrap()
+        };
+        let encoded = match format {
+            ImageFormat::Jpeg | ImageFormat::Bmp | ImageFormat::Pnm => DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(buffer).to_rgb8()),
+            _ => DynamicImage::ImageRgba8(buffer)
+        };
+        let mut body = vec![];
+        encoded.write_to(&mut Cursor::new(&mut body), format)?;
+
+        let extension = format.extensions_str().first().copied().unwrap_or_default();
+        Ok(Self {
+            body,
+            content_type: format.to_mime_type(),
+            etag: format!("\"{:016x}-{extension}\"", image.content_hash()),
+            cache_control: format!("public, max-age={DEFAULT_MAX_AGE}")
+        })
+    }
+
+    pub fn png<T: PixelChannel>(image: &Image<T>) -> Result<Self, Error> {
+        Self::encode(image, ImageFormat::Png)
+    }
+
+    /// Allow browsers and CDNs to cache the image for `max_age`, rounded down to a whole number of seconds.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.cache_control = format!("public, max-age={}", max_age.as_secs());
+        self
+    }
+
+    /// Allow the image to be cached for a year without being checked again, for URLs whose image never changes,
+    /// such as URLs which include a version or hash.
+    pub fn immutable(mut self) -> Self {
+        self.cache_control = String::from("public, max-age=31536000, immutable");
+        self
+    }
+
+    /// Stop browsers and CDNs from using the image without checking that it hasn't changed.
+    pub fn no_cache(mut self) -> Self {
+        self.cache_control = String::from("no-cache");
+        self
+    }
+
+    /// The `Content-Type`, `Cache-Control` and `ETag` headers, as names and values.
+    pub fn headers(&self) -> [(&'static str, &str); 3] {
+        [
+            ("Content-Type", self.content_type),
+            ("Cache-Control", &self.cache_control),
+            ("ETag", &self.etag)
+        ]
+    }
+
+    /// Whether the value of a request's `If-None-Match` header includes this image's `ETag`, so the client already
+    /// has the image and can be sent `304 Not Modified`.
+    /// 
+    /// Weak tags are compared as if they were strong, as the image's `ETag` is only weakened by proxies which compress it.
+    pub fn matches(&self, if_none_match: &str) -> bool {
+        if_none_match.split(',')
+            .map(|tag| tag.trim())
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == self.etag)
+    }
+
+    /// Create an HTTP response for a request with the value of its `If-None-Match` header, if it has one.
+    /// 
+    /// If the request's tag matches, the response is `304 Not Modified` with no body. Otherwise, it is `200 OK` with the
+    /// encoded image. Both have the caching headers.
+    pub fn into_http_response(self, if_none_match: Option<&str>) -> Response<Vec<u8>> {
+        let not_modified = if_none_match.is_some_and(|tag| self.matches(tag));
+        let builder = Response::builder()
+            .header(header::CACHE_CONTROL, &self.cache_control)
+            .header(header::ETAG, &self.etag);
+
+        // The headers are valid, as they only contain printable ASCII, so building the response can't fail
+        if not_modified {
+            builder.status(StatusCode::NOT_MODIFIED).body(vec![]).unwrap()
+        } else {
+            builder.status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, self.content_type)
+                .header(header::CONTENT_LENGTH, self.body.len())
+                .body(self.body)
+                .unwrap()
+        }
+    }
+}
+
+impl From<ImageResponse> for Response<Vec<u8>> {
+    fn from(response: ImageResponse) -> Self {
+        response.into_http_response(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn etags() {
+        let image: Image<u8> = Image::new_with_fill(AlphaPixel::red(), 4, 4);
+        let png = ImageResponse::png(&image).unwrap();
+        let jpeg = ImageResponse::encode(&image, ImageFormat::Jpeg).unwrap();
+        assert_eq!(jpeg.content_type, "image/jpeg");
+        assert_ne!(png.etag, jpeg.etag);
+        assert_eq!(png.etag, ImageResponse::png(&image.clone()).unwrap().etag);
+
+        assert!(png.matches(&png.etag));
+        assert!(png.matches(&format!("\"other\", W/{}", png.etag)));
+        assert!(png.matches("*"));
+        assert!(!png.matches(&jpeg.etag));
+    }
+
+    #[test]
+    fn responses() {
+        let image: Image<u16> = Image::new_with_fill(AlphaPixel::blue(), 3, 2);
+        let response = ImageResponse::png(&image).unwrap().immutable();
+        let decoded = image::load_from_memory_with_format(&response.body, ImageFormat::Png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (3, 2));
+
+        let etag = response.etag.clone();
+        let not_modified = response.clone().into_http_response(Some(&etag));
+        assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+        assert!(not_modified.body().is_empty());
+        assert_eq!(not_modified.headers()[header::CACHE_CONTROL], "public, max-age=31536000, immutable");
+
+        let ok: Response<Vec<u8>> = response.into();
+        assert_eq!(ok.status(), StatusCode::OK);
+        assert_eq!(ok.headers()[header::ETAG], etag.as_str());
+        assert_eq!(ok.headers()[header::CONTENT_LENGTH], ok.body().len().to_string().as_str());
+
+        let empty = ImageResponse::encode(&Image::<u8>::new_with_fill(AlphaPixel::red(), 0, 0), ImageFormat::Png);
+        assert!(empty.is_ok());
+    }
+
+    #[test]
+    fn empty_images() {
+        for (width, height) in [(0, 0), (0, 5), (5, 0)] {
+            let image: Image<u8> = Image::new_with_fill(AlphaPixel::red(), width, height);
+            for format in [ImageFormat::Png, ImageFormat::Jpeg] {
+                let response = ImageResponse::encode(&image, format).unwrap();
+                let decoded = image::load_from_memory_with_format(&response.body, format).unwrap();
+                assert_eq!((decoded.width(), decoded.height()), (1, 1));
+            }
+        }
+    }
+}
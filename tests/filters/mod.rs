@@ -1,2 +1,3 @@
 #[cfg(feature = "image-crate")]
-pub mod transform;
\ No newline at end of file
+pub mod transform;
+pub mod order;
@@ -0,0 +1,13 @@
+use image_template::{filters::{brightness::BrightnessFilter, gradient_map::GradientMapFilter}, bitmap::ramp::ColorRamp, layers::shapes::RectangleLayer, AlphaPixel, Layer, Rect, rgba};
+
+#[test]
+fn colour_filters_apply_in_order() {
+    let ramp = ColorRamp::new(vec![(0.0, AlphaPixel::black()), (1.0, rgba!(100, 100, 100, 255))]);
+    let mut layer: RectangleLayer<u8> = RectangleLayer::new(rgba!(200, 200, 200, 255), Rect { x: 0, y: 0, width: 2, height: 2 });
+    layer.filters.push(Box::new(BrightnessFilter { multiplier: 2.0 }));
+    layer.filters.push(Box::new(GradientMapFilter::new(ramp)));
+
+    // Brightened to white, which maps to 100. Gradient mapping first would map to about 78, then double it.
+    let pixel = layer.filtered_pixel_at(1, 1).unwrap();
+    assert!(pixel.r.abs_diff(100) <= 1, "{pixel:?}");
+}
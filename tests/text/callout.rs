@@ -0,0 +1,38 @@
+use image_template::{layers::{callout::{CalloutLayer, CalloutStyle}, text::TextSettings}, Layer};
+
+use crate::text::text_settings;
+
+fn label() -> TextSettings<u8> {
+    TextSettings { size: 20.0, ..text_settings("Look here") }
+}
+
+#[test]
+fn callout_preferred_position() {
+    let style = CalloutStyle { offset: (60, 40), ..Default::default() };
+    let callout = CalloutLayer::try_new(label(), (50, 50), (400, 300), style).unwrap();
+    let label_rect = callout.get_label_rect();
+
+    assert!(label_rect.x > 50 && label_rect.y > 50);
+    assert!(callout.get_rect().contains(50, 50));
+    // The arrow head covers the target
+    assert_eq!(callout.filtered_pixel_at(50, 50).unwrap().a, 255);
+}
+
+#[test]
+fn callout_flips_at_edges() {
+    let style = CalloutStyle { offset: (60, 40), ..Default::default() };
+    let callout = CalloutLayer::try_new(label(), (380, 280), (400, 300), style).unwrap();
+    let label_rect = callout.get_label_rect();
+
+    assert!(label_rect.right_x() < 380 && label_rect.bottom_y() < 280);
+    assert!(label_rect.right_x() <= 400 && label_rect.bottom_y() <= 300);
+}
+
+#[test]
+fn callout_clamped() {
+    let style = CalloutStyle { offset: (1000, 0), ..Default::default() };
+    let callout = CalloutLayer::try_new(label(), (100, 100), (200, 200), style).unwrap();
+    let label_rect = callout.get_label_rect();
+
+    assert!(label_rect.right_x() <= 200);
+}
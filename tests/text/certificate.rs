@@ -0,0 +1,56 @@
+use image_template::{Image, AlphaPixel, Rect, Layer};
+use image_template::layers::text::TextSettings;
+use image_template::templates::certificate::{CertificateTemplate, CertificateRecord, TextSlot, SerialFormat, QrSlot};
+
+use crate::text::text_settings;
+
+fn style(size: f32) -> TextSettings<u8> {
+    TextSettings { size, ..text_settings("") }
+}
+
+fn template() -> CertificateTemplate<u8> {
+    let background = Image::new_with_fill(AlphaPixel::white(), 400, 300);
+    CertificateTemplate::new(background, TextSlot::new(style(40.0), Rect { x: 50, y: 50, width: 300, height: 60 }))
+        .with_date(TextSlot::new(style(16.0), Rect { x: 50, y: 150, width: 150, height: 30 }))
+        .with_serial(TextSlot::new(style(16.0), Rect { x: 200, y: 150, width: 150, height: 30 }), SerialFormat::new("NO. ", 7, 4))
+        .with_qr_code(QrSlot::new(Rect { x: 300, y: 200, width: 100, height: 100 }, "https://example.com/{serial}"))
+}
+
+#[test]
+fn name_is_centered() {
+    let template = template();
+    let name = template.name.layer("Ada").unwrap().get_rect();
+    let left_space = name.x - 50;
+    let right_space = 350 - name.right_x();
+    assert!(left_space.abs_diff(right_space) <= 1);
+}
+
+#[test]
+fn long_names_shrink() {
+    let template = template();
+    let long = template.name.layer("Maximilian Alexander Montgomery-Fitzgerald III").unwrap();
+    assert!(long.get_rect().width <= 300);
+    assert!(long.get_settings().size < 40.0);
+    assert_eq!(template.name.layer("Ada").unwrap().get_settings().size, 40.0);
+}
+
+#[test]
+fn batch() {
+    let template = template();
+    let records = [CertificateRecord::new("Ada Lovelace", "1843"), CertificateRecord::new("Alan Turing", "1936")];
+    let certificates = template.render_batch(&records).unwrap();
+    assert_eq!(certificates.len(), 2);
+    assert_eq!(template.serial(1), "NO. 0008");
+
+    // The serial numbers differ, so the text and QR codes differ
+    assert_ne!(certificates[0], certificates[1]);
+    let canvas = template.canvas(&records[0], 0).unwrap();
+    assert_eq!(canvas.layers.len(), 5);
+    // Text is drawn over the background
+    let name_rect = canvas.layers[1].get_rect();
+    let dark_pixels = (name_rect.y..name_rect.bottom_y())
+        .flat_map(|y| (name_rect.x..name_rect.right_x()).map(move |x| (x, y)))
+        .filter(|&(x, y)| certificates[0].pixel_at(x, y).unwrap().r < 100)
+        .count();
+    assert!(dark_pixels > 50);
+}
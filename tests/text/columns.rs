@@ -0,0 +1,88 @@
+use image_template::{layers::text::{ColumnFlow, TextColumns, TextLayer, TextSettings}, Layer};
+
+use crate::text::text_settings;
+
+fn tracklist(count: usize) -> TextSettings<u8> {
+    let text = (1..=count).map(|track| format!("Track {track}")).collect::<Vec<String>>().join("\n");
+    TextSettings { size: 20.0, ..text_settings(&text) }
+}
+
+/// Count the columns of drawn text, as runs of drawn pixels separated by at least `gap` empty columns of pixels
+fn column_count(layer: &TextLayer<u8>, gap: usize) -> usize {
+    let rect = layer.get_rect();
+    let drawn: Vec<bool> = (rect.x..rect.x + rect.width)
+        .map(|x| (rect.y..rect.y + rect.height).any(|y| layer.filtered_pixel_at(x, y).is_some_and(|pixel| pixel.a > 0)))
+        .collect();
+
+    let mut count = 0;
+    let mut empty_run = gap;
+    for is_drawn in drawn {
+        if is_drawn {
+            if empty_run >= gap {
+                count += 1;
+            }
+            empty_run = 0;
+        } else {
+            empty_run += 1;
+        }
+    }
+    count
+}
+
+fn single_line_height() -> usize {
+    let settings = TextSettings { text: String::from("Track 1"), ..tracklist(1) };
+    TextLayer::try_new(settings, 0, 0).unwrap().get_rect().height
+}
+
+#[test]
+fn balanced_columns() {
+    let mut text = TextLayer::try_new(tracklist(6), 10, 10).unwrap();
+    let single_column = text.get_rect();
+
+    text.set_columns(Some(TextColumns::balanced(3, 20))).unwrap();
+    let columns = text.get_rect();
+
+    // Three columns of two lines each
+    assert_eq!(column_count(&text, 20), 3);
+    assert!(columns.height < single_column.height / 2);
+    assert!(columns.width > single_column.width * 3 + 40 - 3);
+    assert_eq!(text.get_settings().text, tracklist(6).text);
+
+    text.set_columns(None).unwrap();
+    assert_eq!(text.get_rect().width, single_column.width);
+}
+
+#[test]
+fn balanced_columns_remainder() {
+    let line_height = single_line_height();
+
+    // Six lines in four columns are split 2, 2, 1, 1, rather than into three columns of two lines
+    let mut text = TextLayer::try_new(tracklist(6), 0, 0).unwrap();
+    text.set_columns(Some(TextColumns::balanced(4, 20))).unwrap();
+    assert_eq!(column_count(&text, 20), 4);
+    assert!(text.get_rect().height < line_height * 3);
+
+    // Each line gets its own column when there are more columns than lines
+    let mut text = TextLayer::try_new(tracklist(2), 0, 0).unwrap();
+    text.set_columns(Some(TextColumns::balanced(3, 20))).unwrap();
+    assert_eq!(column_count(&text, 20), 2);
+    assert!(text.get_rect().height < line_height * 2);
+}
+
+#[test]
+fn max_height_columns() {
+    let line_height = single_line_height();
+    let columns = TextColumns { count: 2, gap: 10, flow: ColumnFlow::MaxHeight(line_height * 3) };
+
+    let mut text = TextLayer::try_new(tracklist(4), 0, 0).unwrap();
+    text.set_columns(Some(columns)).unwrap();
+    let two_columns = text.get_rect();
+    assert_eq!(column_count(&text, 10), 2);
+
+    // Lines that don't fit in any column overflow the last column
+    let mut overflowing = TextLayer::try_new(tracklist(12), 0, 0).unwrap();
+    overflowing.set_columns(Some(columns)).unwrap();
+    assert_eq!(column_count(&overflowing, 10), 2);
+    assert!(overflowing.get_rect().height > two_columns.height * 2);
+    assert!(overflowing.get_rect().height < line_height * 12);
+}
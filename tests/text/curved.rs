@@ -0,0 +1,71 @@
+use image_template::{layers::{path::Path, text::{layout::{LayoutAlign, TextLayout}, curved::{CurvedTextLayer, TextCurve}, TextSettings}}, Layer};
+
+use crate::text::text_settings;
+
+fn settings(text: &str, align: LayoutAlign) -> TextSettings<u8> {
+    TextSettings { layout: TextLayout { align, ..Default::default() }, ..text_settings(text) }
+}
+
+/// Get the canvas coordinates of every visible pixel of a layer.
+fn visible_pixels(layer: &CurvedTextLayer<u8>) -> Vec<(usize, usize)> {
+    let rect = layer.get_rect();
+    (rect.y..rect.bottom_y())
+        .flat_map(|y| (rect.x..rect.right_x()).map(move |x| (x, y)))
+        .filter(|(x, y)| layer.filtered_pixel_at(*x, *y).is_some_and(|pixel| pixel.a > 127))
+        .collect()
+}
+
+#[test]
+fn straight_path() {
+    let path = Path::new().move_to(10.0, 50.0).line_to(300.0, 50.0);
+    let layer = CurvedTextLayer::try_new(settings("WOW", LayoutAlign::Start), TextCurve::Path(path)).unwrap();
+    let rect = layer.get_rect();
+    assert!((10..=12).contains(&rect.x));
+    assert!(rect.width > rect.height);
+
+    // The glyphs sit on the path
+    let pixels = visible_pixels(&layer);
+    assert!(!pixels.is_empty());
+    assert!(pixels.iter().all(|(_x, y)| *y < 51));
+    assert!(pixels.iter().any(|(_x, y)| *y == 49));
+}
+
+#[test]
+fn vertical_path() {
+    let path = Path::new().move_to(50.0, 10.0).line_to(50.0, 300.0);
+    let layer = CurvedTextLayer::try_new(settings("WOW", LayoutAlign::Start), TextCurve::Path(path)).unwrap();
+    let rect = layer.get_rect();
+    assert!(rect.height > rect.width);
+
+    // The glyphs are turned so that their tops face right, away from the path
+    assert!(visible_pixels(&layer).iter().all(|(x, _y)| *x >= 49));
+}
+
+#[test]
+fn badge_arcs() {
+    let distance = |(x, y): (usize, usize)| (x as f32 + 0.5 - 100.0).hypot(y as f32 + 0.5 - 100.0);
+
+    let top = TextCurve::Arc { center: (100.0, 100.0), radius: 60.0, start_angle: -180.0, end_angle: 0.0 };
+    let top = CurvedTextLayer::try_new(settings("BADGE", LayoutAlign::Center), top).unwrap();
+    let pixels = visible_pixels(&top);
+    assert!(pixels.iter().all(|pixel| pixel.1 < 100 && distance(*pixel) > 59.0));
+
+    // Centered text is centered over the top of the arc
+    let rect = top.get_rect();
+    assert!((rect.x as isize + rect.right_x() as isize - 200).abs() <= 4);
+
+    let bottom = TextCurve::Arc { center: (100.0, 100.0), radius: 60.0, start_angle: 180.0, end_angle: 0.0 };
+    let bottom = CurvedTextLayer::try_new(settings("BADGE", LayoutAlign::Center), bottom).unwrap();
+    let pixels = visible_pixels(&bottom);
+    assert!(!pixels.is_empty());
+    assert!(pixels.iter().all(|pixel| pixel.1 > 100 && distance(*pixel) < 61.0));
+}
+
+#[test]
+fn justify_fills_curve() {
+    let path = || TextCurve::Path(Path::new().move_to(0.0, 40.0).line_to(400.0, 40.0));
+    let start = CurvedTextLayer::try_new(settings("ABC", LayoutAlign::Start), path()).unwrap().get_rect();
+    let justified = CurvedTextLayer::try_new(settings("ABC", LayoutAlign::Justify), path()).unwrap().get_rect();
+    assert_eq!(justified.x, start.x);
+    assert!(justified.right_x() > 380);
+}
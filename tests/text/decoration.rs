@@ -0,0 +1,62 @@
+use image_template::{layers::text::{decoration::TextDecoration, TextOptions, TextSettings}, AlphaPixel, Image};
+
+use crate::text::text_settings;
+
+fn raster(text: &str, decoration: Option<TextDecoration<u8>>) -> Image<u8> {
+    let settings = TextSettings { size: 40.0, fill: AlphaPixel::red(), ..text_settings(text) };
+    settings.raster_with_options(&TextOptions { decoration, ..Default::default() }).unwrap()
+}
+
+/// The rows of an image in which almost every pixel is exactly `color`, as the glyphs don't start at its left edge.
+fn solid_rows(image: &Image<u8>, color: AlphaPixel<u8>) -> Vec<usize> {
+    (0..image.get_height())
+        .filter(|y| (0..image.get_width()).filter(|x| image.pixel_at(*x, *y).unwrap() == color).count() * 10 >= image.get_width() * 9)
+        .collect()
+}
+
+#[test]
+fn underline() {
+    let plain = raster("ace", None);
+    let underlined = raster("ace", Some(TextDecoration::underline()));
+
+    // The underline is below the glyphs, so the image grows, and it spans the whole line
+    assert_eq!(underlined.get_width(), plain.get_width());
+    assert!(underlined.get_height() > plain.get_height());
+    let rows = solid_rows(&underlined, AlphaPixel::red());
+    assert!(!rows.is_empty());
+    assert!(rows.iter().all(|row| *row >= plain.get_height()));
+}
+
+#[test]
+fn strikethrough() {
+    let plain = raster("ace", None);
+    let struck = raster("ace", Some(TextDecoration::strikethrough()));
+
+    // The strikethrough crosses the middle of the lowercase letters, without changing the size of the image
+    assert_eq!((struck.get_width(), struck.get_height()), (plain.get_width(), plain.get_height()));
+    let rows = solid_rows(&struck, AlphaPixel::red());
+    assert!(!rows.is_empty());
+    let inked: Vec<usize> = (0..plain.get_height()).filter(|y| (0..plain.get_width()).any(|x| plain.pixel_at(x, *y).unwrap().a > 0)).collect();
+    let (top, bottom) = (inked[0], inked[inked.len() - 1]);
+    assert!(rows.iter().all(|row| *row > top + (bottom - top) / 4 && *row < bottom - (bottom - top) / 4));
+    assert!(solid_rows(&plain, AlphaPixel::red()).is_empty());
+}
+
+#[test]
+fn color_and_thickness() {
+    let decoration = TextDecoration { color: Some(AlphaPixel::blue()), thickness: Some(6.0), ..TextDecoration::underline() };
+    let image = raster("ace", Some(decoration));
+    assert_eq!(solid_rows(&image, AlphaPixel::blue()).len(), 6);
+}
+
+#[test]
+fn each_line() {
+    let image = raster("ace\nace ace", Some(TextDecoration::strikethrough()));
+
+    // Only the longer line is struck across the whole image, and the first line is struck on its left
+    let full = solid_rows(&image, AlphaPixel::red());
+    assert!(!full.is_empty());
+    assert!(full.iter().all(|row| *row > image.get_height() / 2));
+    let first_line = (0..image.get_height() / 2).filter(|y| image.pixel_at(4, *y).unwrap() == AlphaPixel::red()).count();
+    assert!(first_line > 0);
+}
@@ -0,0 +1,36 @@
+use image_template::{layers::text::{layout::{TextTransform, LayoutAlign}, stroke::TextStroke, TextColumns, TextLayer}, AlphaPixel, Canvas, Layer};
+
+use crate::text::text_settings;
+
+#[test]
+fn empty_text() {
+    for text in ["", " ", "   ", "\n", " \n \n"] {
+        let mut layer = TextLayer::try_new(text_settings(text), 10, 10).unwrap();
+        assert!(layer.is_empty());
+        assert!(layer.filtered_pixel_at(10, 10).is_none_or(|pixel| pixel.a == 0));
+
+        layer.set_strokes(vec![TextStroke::solid(AlphaPixel::white(), 2.0)]).unwrap();
+        layer.set_columns(Some(TextColumns::balanced(2, 10))).unwrap();
+
+        let mut canvas: Canvas<u8> = Canvas::from_dimensions(40, 40);
+        canvas.add_layer(layer);
+        assert!(canvas.flatten().get_pixels().iter().all(|pixel| pixel.a == 0));
+    }
+}
+
+#[test]
+fn empty_text_layouts() {
+    for transform in [TextTransform::None, TextTransform::SmallCaps] {
+        for align in [LayoutAlign::Start, LayoutAlign::End, LayoutAlign::Center, LayoutAlign::Justify] {
+            let mut settings = text_settings(" ");
+            settings.layout.transform = transform;
+            settings.layout.align = align;
+            assert!(TextLayer::try_new(settings, 0, 0).unwrap().is_empty());
+        }
+    }
+}
+
+#[test]
+fn visible_text_is_not_empty() {
+    assert!(!TextLayer::try_new(text_settings(" a "), 0, 0).unwrap().is_empty());
+}
@@ -0,0 +1,60 @@
+use image_template::{figma::FigmaImporter, layers::text::{TextLayer, TextSettings}, AlphaPixel, Canvas, Layer};
+
+use crate::text::{get_font, text_settings};
+
+const FRAME: &str = r#"{
+    "type": "FRAME", "name": "Post", "id": "1:1",
+    "absoluteBoundingBox": { "x": 1000, "y": 500, "width": 400, "height": 200 },
+    "fills": [{ "type": "SOLID", "color": { "r": 1, "g": 1, "b": 1, "a": 1 } }],
+    "children": [
+        {
+            "type": "TEXT", "name": "Title", "id": "1:2", "characters": "Sale",
+            "absoluteBoundingBox": { "x": 1000, "y": 500, "width": 400, "height": 100 },
+            "style": { "fontFamily": "Calibri", "fontSize": 40, "textAlignHorizontal": "CENTER", "textAlignVertical": "CENTER", "textCase": "UPPER" },
+            "fills": [{ "type": "SOLID", "color": { "r": 1, "g": 0, "b": 0, "a": 1 } }]
+        },
+        {
+            "type": "TEXT", "name": "Body", "id": "1:3", "characters": "A very long line of text that does not fit in its box",
+            "absoluteBoundingBox": { "x": 1010, "y": 600, "width": 150, "height": 100 },
+            "style": { "fontFamily": "Calibri", "fontSize": 20 }
+        }
+    ]
+}"#;
+
+#[test]
+fn text_nodes() {
+    let canvas: Canvas<u8> = FigmaImporter::new().with_font("Calibri", get_font()).import(FRAME, None).unwrap();
+    assert_eq!(canvas.layers.len(), 3);
+
+    let title = canvas.layers[1].get_rect();
+    assert_eq!(title.x + title.width / 2, 200);
+    assert!(title.y > 0 && title.bottom_y() < 100);
+    // The title is red, and in capitals
+    let image = canvas.flatten();
+    let red = (title.y..title.bottom_y())
+        .flat_map(|y| (title.x..title.right_x()).map(move |x| (x, y)))
+        .filter(|&(x, y)| image.pixel_at(x, y).unwrap() == AlphaPixel::red())
+        .count();
+    assert!(red > 0);
+
+    let expected = TextLayer::try_new(TextSettings { size: 40.0, fill: AlphaPixel::<u8>::red(), ..text_settings("SALE") }, 0, 0).unwrap();
+    assert_eq!(title.width, expected.get_rect().width);
+
+    // The body is wrapped to fit its box
+    let body = canvas.layers[2].get_rect();
+    assert_eq!((body.x, body.y), (10, 100));
+    assert!(body.width <= 150);
+    assert!(body.height > 40);
+
+    // Truncated text has one line, which is cut short to fit
+    let truncated_frame = FRAME.replace(r#""style": { "fontFamily": "Calibri", "fontSize": 20 }"#, r#""style": { "fontFamily": "Calibri", "fontSize": 20 }, "textTruncation": "ENDING", "maxLines": 1"#);
+    let canvas: Canvas<u8> = FigmaImporter::new().with_font("Calibri", get_font()).import(&truncated_frame, None).unwrap();
+    let truncated = canvas.layers[2].get_rect();
+    assert!(truncated.width <= 150);
+    assert!(truncated.height < 30);
+
+    // Letter spacing widens the title by its spacing between each letter
+    let spaced_frame = FRAME.replace(r#""textCase": "UPPER""#, r#""textCase": "UPPER", "letterSpacing": 5"#);
+    let canvas: Canvas<u8> = FigmaImporter::new().with_font("Calibri", get_font()).import(&spaced_frame, None).unwrap();
+    assert_eq!(canvas.layers[1].get_rect().width, title.width + 3 * 5);
+}
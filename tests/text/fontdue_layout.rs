@@ -0,0 +1,55 @@
+use fontdue::layout::{Layout, LayoutSettings, CoordinateSystem, TextStyle, HorizontalAlign};
+use image_template::{layers::text::{layout::LayoutError, TextLayer, TextSettings}, AlphaPixel, Layer, Error};
+
+use crate::text::{get_font, text_settings};
+
+fn settings(text: &str) -> TextSettings<u8> {
+    TextSettings { size: 40.0, ..text_settings(text) }
+}
+
+fn layout(text: &str, max_width: Option<f32>) -> Layout {
+    let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+    layout.reset(&LayoutSettings { max_width, horizontal_align: HorizontalAlign::Center, ..Default::default() });
+    layout.append(&[get_font()], &TextStyle::new(text, 40.0, 0));
+    layout
+}
+
+#[test]
+fn wrapped_by_fontdue() {
+    let single = TextLayer::from_fontdue_layout(&layout("Hello world", None), &[get_font()], settings(""), 10, 10).unwrap();
+    let wrapped = TextLayer::from_fontdue_layout(&layout("Hello world", Some(120.0)), &[get_font()], settings(""), 10, 10).unwrap();
+    assert!(single.has_fontdue_layout());
+    assert!(!single.is_empty());
+
+    // Wrapping makes the text narrower and taller
+    let (single_rect, wrapped_rect) = (single.get_rect(), wrapped.get_rect());
+    assert!(wrapped_rect.width < single_rect.width);
+    assert!(wrapped_rect.height > single_rect.height * 3 / 2);
+
+    // The glyphs are spaced a little differently, but are about as wide as this crate's own layout of the same text
+    let own = TextLayer::try_new(settings("Hello world"), 10, 10).unwrap();
+    assert!(own.get_rect().width.abs_diff(single_rect.width) <= own.get_rect().width / 10);
+}
+
+#[test]
+fn setters_and_scaling() {
+    let mut layer = TextLayer::from_fontdue_layout(&layout("Hello", None), &[get_font()], settings("Hello"), 0, 0).unwrap();
+    let rect = layer.get_rect();
+    layer.set_fill(AlphaPixel::red()).unwrap();
+    assert_eq!((layer.get_rect().width, layer.get_rect().height), (rect.width, rect.height));
+    assert!(layer.has_fontdue_layout());
+
+    let scaled = layer.scaled(2.0).unwrap();
+    assert!(scaled.get_rect().width.abs_diff(rect.width * 2) <= 4);
+
+    // Changing the text lays it out with this crate's layout
+    layer.set_text("Hello there").unwrap();
+    assert!(!layer.has_fontdue_layout());
+    assert!(layer.get_rect().width > rect.width);
+}
+
+#[test]
+fn missing_font() {
+    let result = TextLayer::from_fontdue_layout(&layout("Hello", None), &[], settings(""), 0, 0);
+    assert!(matches!(result, Err(Error::Layout(LayoutError::MissingFont { index: 0, count: 0 }))));
+}
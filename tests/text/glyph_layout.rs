@@ -1,6 +1,6 @@
-use image_template::{layers::text::{layout::{LayoutIter, TextLayout}, TextSettings}, AlphaPixel};
+use image_template::{layers::text::{layout::{LayoutIter, TextLayout, LayoutAlign, LayoutDirection, SpacingMode}, TextSettings}, AlphaPixel};
 
-use crate::text::get_font;
+use crate::text::{get_font, text_settings};
 
 
 #[test]
@@ -55,3 +55,171 @@ fn layout_basic() {
     }
     assert_eq!(count, correct_layout.len());
 }
+
+#[test]
+fn layout_baseline_grid() {
+    let baselines = |size: f32| {
+        let settings = TextSettings {
+            size,
+            layout: TextLayout { baseline_grid: Some(24.0), ..Default::default() },
+            ..text_settings("Track one\nTrack two\nTrack three")
+        };
+
+        let mut baselines: Vec<isize> = LayoutIter::new(&settings)
+            .map(|position| {
+                let (glyph, _, glyph_y) = position.unwrap();
+                let metrics = settings.font.metrics(glyph, settings.size);
+                glyph_y + metrics.ymin as isize + metrics.height as isize
+            })
+            .collect();
+        baselines.dedup();
+        baselines
+    };
+
+    for size in [16.0, 18.0, 30.0] {
+        let size_baselines = baselines(size);
+        assert_eq!(size_baselines.len(), 3);
+        assert!(size_baselines.iter().all(|baseline| baseline % 24 == 0));
+    }
+
+    // Fonts with slightly different line heights still land on the same lines
+    assert_eq!(baselines(16.0), baselines(18.0));
+}
+
+/// Get the left and right edges of the visible glyphs in each row, in the order the rows are laid out.
+fn row_extents(text: &str, align: LayoutAlign) -> Vec<(isize, isize)> {
+    let settings = TextSettings { layout: TextLayout { align, ..Default::default() }, ..text_settings(text) };
+
+    let mut rows: Vec<(isize, (isize, isize))> = vec![];
+    for position in LayoutIter::new(&settings) {
+        let (glyph, glyph_x, glyph_y) = position.unwrap();
+        if glyph.is_whitespace() {
+            continue
+        }
+        let metrics = settings.font.metrics(glyph, settings.size);
+        let baseline = glyph_y + metrics.ymin as isize + metrics.height as isize;
+        let right = glyph_x + metrics.width as isize;
+        match rows.last_mut() {
+            Some((row_baseline, (left_edge, right_edge))) if *row_baseline == baseline => {
+                *left_edge = (*left_edge).min(glyph_x);
+                *right_edge = (*right_edge).max(right);
+            },
+            _ => rows.push((baseline, (glyph_x, right)))
+        }
+    }
+    rows.into_iter().map(|(_baseline, extent)| extent).collect()
+}
+
+#[test]
+fn layout_center() {
+    let rows = row_extents("Intro\nThe Longest Track Name\nCoda", LayoutAlign::Center);
+    let (block_left, block_right) = rows[1];
+    for (left, right) in rows {
+        let offset = (left - block_left) - (block_right - right);
+        assert!(offset.abs() <= 1, "row is off center by {offset}");
+    }
+}
+
+#[test]
+fn layout_justify() {
+    let rows = row_extents("one two three\nfour five six seven\nmore words\n\nend of it", LayoutAlign::Justify);
+    let block_right = rows.iter().map(|(_left, right)| *right).max().unwrap();
+    assert_eq!(rows.len(), 4);
+
+    // Lines are stretched to the widest line, except at the end of a paragraph
+    assert!((rows[0].1 - block_right).abs() <= 1);
+    assert_eq!(rows[1].1, block_right);
+    assert!(rows[2].1 < block_right - 20);
+    assert!(rows[3].1 < block_right - 20);
+    assert_eq!(rows[3], row_extents("end of it", LayoutAlign::Start)[0]);
+}
+
+/// Get the top and bottom edges of the visible glyphs in each column of top to bottom text.
+fn column_extents(text: &str, align: LayoutAlign) -> Vec<(isize, isize)> {
+    let settings = TextSettings {
+        layout: TextLayout { align, direction: LayoutDirection::TopToBottom, line_spacing: SpacingMode::Constant(40.0), ..Default::default() },
+        ..text_settings(text)
+    };
+
+    let mut columns: Vec<(isize, (isize, isize))> = vec![];
+    for position in LayoutIter::new(&settings) {
+        let (glyph, glyph_x, glyph_y) = position.unwrap();
+        if glyph.is_whitespace() {
+            continue
+        }
+        let metrics = settings.font.metrics(glyph, settings.size);
+        let origin = glyph_x - metrics.xmin as isize;
+        let bottom = glyph_y + metrics.height as isize;
+        match columns.last_mut() {
+            Some((column_origin, (top_edge, bottom_edge))) if *column_origin == origin => {
+                *top_edge = (*top_edge).min(glyph_y);
+                *bottom_edge = (*bottom_edge).max(bottom);
+            },
+            _ => columns.push((origin, (glyph_y, bottom)))
+        }
+    }
+    columns.into_iter().map(|(_origin, extent)| extent).collect()
+}
+
+#[test]
+fn layout_top_to_bottom_align() {
+    let text = "ABC\nABCDEF\nAB";
+    let start = column_extents(text, LayoutAlign::Start);
+    assert_eq!(start.len(), 3);
+    assert!(start.iter().all(|(top, _bottom)| *top == start[1].0));
+
+    // Columns are aligned within the tallest column, which doesn't move
+    let end = column_extents(text, LayoutAlign::End);
+    assert_eq!(end[1], start[1]);
+    assert!(end.iter().all(|(_top, bottom)| *bottom == end[1].1));
+
+    let center = column_extents(text, LayoutAlign::Center);
+    let (block_top, block_bottom) = center[1];
+    for (top, bottom) in center {
+        let offset = (top - block_top) - (block_bottom - bottom);
+        assert!(offset.abs() <= 1, "column is off center by {offset}");
+    }
+}
+
+fn glyph_xs(text: &str, layout: TextLayout) -> Vec<isize> {
+    let settings = TextSettings {
+        size: 30.0,
+        fill: AlphaPixel::<u8>::default(),
+        layout,
+        text: String::from(text),
+        font: get_font()
+    };
+    LayoutIter::new(&settings).map(|position| position.unwrap().1).collect()
+}
+
+#[test]
+fn layout_letter_and_word_spacing() {
+    // "AV" is kerned, and the kerning is kept when letters are spaced out
+    let text = "AVA to";
+    let plain = glyph_xs(text, TextLayout::default());
+    let tracked = glyph_xs(text, TextLayout { letter_spacing: 2.5, ..Default::default() });
+    let offsets: Vec<isize> = plain.iter().zip(&tracked).map(|(plain, tracked)| tracked - plain).collect();
+    assert_eq!(offsets, [0, 3, 5, 8, 10, 13]);
+
+    let spaced = glyph_xs(text, TextLayout { word_spacing: 10.0, ..Default::default() });
+    let offsets: Vec<isize> = plain.iter().zip(&spaced).map(|(plain, spaced)| spaced - plain).collect();
+    assert_eq!(offsets, [0, 0, 0, 0, 10, 10]);
+
+    // Line widths include the spacing, so alignment and wrapping account for it
+    let settings = |layout: TextLayout| TextSettings {
+        size: 30.0,
+        fill: AlphaPixel::<u8>::default(),
+        layout,
+        text: String::new(),
+        font: get_font()
+    };
+    let plain_width = settings(TextLayout::default()).line_width(text);
+    let spaced_width = settings(TextLayout { letter_spacing: 2.0, word_spacing: 4.0, ..Default::default() }).line_width(text);
+    assert_eq!(spaced_width - plain_width, 2.0 * 6.0 + 4.0);
+
+    // End aligned text is spaced out towards the left, from the last glyph, which doesn't move
+    let end = glyph_xs(text, TextLayout { align: LayoutAlign::End, ..Default::default() });
+    let end_tracked = glyph_xs(text, TextLayout { align: LayoutAlign::End, letter_spacing: 2.0, ..Default::default() });
+    assert_eq!(end.first(), end_tracked.first());
+    assert_eq!(end.last().unwrap() - end_tracked.last().unwrap(), 2 * 5);
+}
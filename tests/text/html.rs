@@ -0,0 +1,98 @@
+use image_template::{layers::html::{HtmlLayer, HtmlResources}, AlphaPixel, Error, Image, Layer, Rect};
+
+use crate::text::get_font;
+
+fn resources() -> HtmlResources<u8> {
+    HtmlResources::new(get_font()).with_image("logo", Image::new_with_fill(AlphaPixel::blue(), 40, 20))
+}
+
+/// Get the smallest `Rect` around the pixels of the layer that are `color`.
+fn bounds_of(layer: &HtmlLayer<u8>, color: AlphaPixel<u8>) -> Option<Rect> {
+    let rect = layer.get_rect();
+    let matching: Vec<(usize, usize)> = (rect.y..rect.bottom_y())
+        .flat_map(|y| (rect.x..rect.right_x()).map(move |x| (x, y)))
+        .filter(|&(x, y)| layer.filtered_pixel_at(x, y).unwrap() == color)
+        .collect();
+    let left = matching.iter().map(|(x, _)| *x).min()?;
+    let top = matching.iter().map(|(_, y)| *y).min()?;
+    let right = matching.iter().map(|(x, _)| *x).max()?;
+    let bottom = matching.iter().map(|(_, y)| *y).max()?;
+    Some(Rect { x: left, y: top, width: right - left + 1, height: bottom - top + 1 })
+}
+
+#[test]
+fn blocks_and_borders() {
+    let html = r#"
+        <div style="height: 30px; background: red"></div>
+        <div style="margin: 10px 20px; padding: 5px; border: 5px solid #00f; height: 20px; background: lime"></div>
+    "#;
+    let layer = HtmlLayer::try_new(html, &resources(), 0, 0, 200).unwrap();
+
+    // 30 + 10 + (5 + 5 + 20 + 5 + 5) + 10
+    assert_eq!((layer.get_rect().width, layer.get_rect().height), (200, 90));
+    assert_eq!(layer.filtered_pixel_at(100, 15).unwrap(), AlphaPixel::red());
+    assert_eq!(layer.filtered_pixel_at(10, 50).unwrap(), AlphaPixel::default());
+    assert_eq!(layer.filtered_pixel_at(22, 50).unwrap(), AlphaPixel::blue());
+    assert_eq!(layer.filtered_pixel_at(100, 60).unwrap(), AlphaPixel::green());
+    assert_eq!(bounds_of(&layer, AlphaPixel::blue()).unwrap().x, 20);
+    assert_eq!(bounds_of(&layer, AlphaPixel::blue()).unwrap().right_x(), 180);
+}
+
+#[test]
+fn flex() {
+    let html = r#"
+        <div style="display: flex; justify-content: space-between; align-items: center; height: 100px">
+            <img src="logo">
+            <div style="width: 50px; height: 50px; background: red"></div>
+        </div>
+        <div style="display: flex; gap: 10px; height: 10px">
+            <div style="flex: 1; background: lime"></div>
+            <div style="flex: 3; background: black"></div>
+        </div>
+    "#;
+    let layer = HtmlLayer::try_new(html, &resources(), 10, 10, 300).unwrap();
+
+    let logo = bounds_of(&layer, AlphaPixel::blue()).unwrap();
+    assert_eq!((logo.x, logo.y, logo.width, logo.height), (10, 50, 40, 20));
+    let square = bounds_of(&layer, AlphaPixel::red()).unwrap();
+    assert_eq!((square.right_x(), square.y), (310, 35));
+
+    // 290 pixels of free space, divided 1:3, so the second item starts 82.5 pixels in, after the gap.
+    // Pixels which are only partly covered are blended with the transparent background.
+    assert_eq!(bounds_of(&layer, AlphaPixel::green()).unwrap().width, 72);
+    assert_eq!(bounds_of(&layer, AlphaPixel::black()).unwrap().x, 10 + 83);
+}
+
+#[test]
+fn text() {
+    let red = r#"<div style="color: red; font-size: 30px">Sale <span style="color: #00f">today</span></div>"#;
+    let layer = HtmlLayer::try_new(red, &resources(), 0, 0, 400).unwrap();
+    let sale = bounds_of(&layer, AlphaPixel::red()).unwrap();
+    let today = bounds_of(&layer, AlphaPixel::blue()).unwrap();
+    assert!(sale.right_x() < today.x);
+    assert!(sale.height > 15);
+
+    // Text wraps to fit its block, and can be centered
+    let wrapped = r#"<p style="text-align: center">one two three four five six seven eight nine ten</p>"#;
+    let narrow = HtmlLayer::try_new(wrapped, &resources(), 0, 0, 100).unwrap();
+    let wide = HtmlLayer::try_new(wrapped, &resources(), 0, 0, 1000).unwrap();
+    assert!(narrow.get_rect().height > 3 * wide.get_rect().height);
+    let text = bounds_of(&wide, AlphaPixel::black()).unwrap();
+    assert!((text.x as isize - (1000 - text.right_x()) as isize).abs() <= 2);
+}
+
+#[test]
+fn errors() {
+    let error = |html: &str| HtmlLayer::try_new(html, &resources(), 0, 0, 100).err().unwrap();
+    assert!(matches!(error("<table></table>"), Error::Html(_)));
+    assert!(matches!(error("<img src='missing'>"), Error::Html(_)));
+    assert!(matches!(error("<div style='color: nope'></div>"), Error::Html(_)));
+    assert!(matches!(error("<div style='border: 99999999999px solid red'>x</div>"), Error::Html(_)));
+    assert!(matches!(error("<div style='font-size: -10px'>x</div>"), Error::Html(_)));
+    assert!(matches!(error("<img src='logo' width='1e12'>"), Error::Html(_)));
+
+    // Content too large to allocate is an error instead of an abort
+    let tall = "<div style='padding: 16000px'>".repeat(10) + &"</div>".repeat(10);
+    assert!(matches!(HtmlLayer::try_new(&tall, &resources(), 0, 0, usize::MAX / 2), Err(Error::NewImage(_))));
+    assert!(HtmlLayer::try_new("", &resources(), 0, 0, 100).unwrap().get_rect().height == 0);
+}
@@ -0,0 +1,47 @@
+use image_template::layers::text::{list::{ListLayer, ListMarker, ListStyle}, TextSettings};
+
+use crate::text::text_settings;
+
+fn items(text: &str) -> TextSettings<u8> {
+    TextSettings { size: 20.0, ..text_settings(text) }
+}
+
+#[test]
+fn list_markers() {
+    assert_eq!(ListMarker::Bullet('-').marker_for(3), "-");
+    assert_eq!(ListMarker::Numbered { start: 1, suffix: String::from(".") }.marker_for(9), "10.");
+    assert_eq!(ListMarker::Custom(|index| format!("({})", (b'a' + index as u8) as char)).marker_for(1), "(b)");
+}
+
+#[test]
+fn numbered_list() {
+    let text = (1..=10).map(|track| format!("Track {track}")).collect::<Vec<String>>().join("\n");
+    let style = ListStyle { marker: ListMarker::Numbered { start: 1, suffix: String::from(".") }, ..Default::default() };
+    let list = ListLayer::try_new(items(&text), 10, 10, style).unwrap();
+
+    let item_rects = list.get_item_rects();
+    let marker_rects = list.get_marker_rects();
+    assert_eq!(item_rects.len(), 10);
+
+    // Items line up, after the widest marker
+    assert!(item_rects.iter().all(|rect| rect.x == item_rects[0].x));
+    assert!(item_rects[0].x >= marker_rects[9].right_x() + 10);
+    // Markers are right aligned
+    assert!(marker_rects.iter().all(|rect| rect.right_x() == marker_rects[9].right_x()));
+    // Items are evenly spaced
+    let spacing = item_rects[1].y - item_rects[0].y;
+    assert!(spacing > 0 && item_rects[9].y - item_rects[8].y == spacing);
+}
+
+#[test]
+fn hanging_indent() {
+    let style = ListStyle { max_width: Some(150.0), item_spacing: 5, ..Default::default() };
+    let list = ListLayer::try_new(items("A long item which will wrap onto more lines\nShort"), 0, 0, style).unwrap();
+
+    let item_rects = list.get_item_rects();
+    assert!(item_rects[0].right_x() <= 150);
+    // The first item wraps, so the second item is pushed down by more than one line
+    let wrapped = list.get_marker_rects()[1].y - list.get_marker_rects()[0].y;
+    let single = ListLayer::try_new(items("Short\nShort"), 0, 0, ListStyle::default()).unwrap();
+    assert!(wrapped > 2 * (single.get_item_rects()[1].y - single.get_item_rects()[0].y));
+}
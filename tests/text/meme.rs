@@ -0,0 +1,45 @@
+use image_template::{Image, AlphaPixel};
+use image_template::templates::meme::{meme, MemeStyle};
+
+use crate::text::get_font;
+
+fn photo() -> Image<u8> {
+    Image::new_with_fill(AlphaPixel { r: 0, g: 0, b: 255, a: 255 }, 400, 300)
+}
+
+#[test]
+fn captions() {
+    let canvas = meme(&photo(), "top text", "bottom text", &MemeStyle::new(get_font())).unwrap();
+    assert_eq!(canvas.layers.len(), 3);
+
+    let top = canvas.layers[1].get_rect();
+    let bottom = canvas.layers[2].get_rect();
+    // The captions are centered, at the top and bottom of the image
+    assert!((top.x + top.right_x()).abs_diff(400) <= 2);
+    assert!(top.y < 30);
+    assert!(bottom.bottom_y() > 270 && bottom.bottom_y() <= 300);
+
+    // The text is white, outlined in black
+    let image = canvas.flatten();
+    let pixels: Vec<AlphaPixel<u8>> = (top.y..top.bottom_y())
+        .flat_map(|y| (top.x..top.right_x()).map(move |x| (x, y)))
+        .map(|(x, y)| image.pixel_at(x, y).unwrap())
+        .collect();
+    assert!(pixels.contains(&AlphaPixel::white()));
+    assert!(pixels.contains(&AlphaPixel::black()));
+}
+
+#[test]
+fn long_captions_wrap_and_shrink() {
+    let style = MemeStyle::new(get_font());
+    let short = meme(&photo(), "short", "", &style).unwrap();
+    let long = meme(&photo(), "when the caption is far too long to fit on a single line of the image", "", &style).unwrap();
+    // No bottom caption is drawn
+    assert_eq!(short.layers.len(), 2);
+
+    let short_rect = short.layers[1].get_rect();
+    let long_rect = long.layers[1].get_rect();
+    assert!(long_rect.width <= 400);
+    assert!(long_rect.height > short_rect.height);
+    assert!(long_rect.height <= 90);
+}
@@ -1,10 +1,41 @@
 #[cfg(feature = "image-crate")]
 pub mod raster_text;
 pub mod glyph_layout;
+pub mod wrap;
+pub mod callout;
+pub mod ribbon;
+pub mod stroke;
+pub mod shadow;
+pub mod decoration;
+pub mod setters;
+pub mod rotated;
+pub mod curved;
+pub mod scaled;
+pub mod truncate;
+pub mod columns;
+pub mod list;
+pub mod rich;
+pub mod transform;
+pub mod empty;
+pub mod target;
+pub mod figma;
+#[cfg(feature = "html")]
+pub mod html;
+pub mod meme;
+#[cfg(feature = "fontdue-layout")]
+pub mod fontdue_layout;
+#[cfg(feature = "qr")]
+pub mod certificate;
 
 use fontdue::Font;
+use image_template::{layers::text::TextSettings, AlphaPixel};
 
 static FONT_BYTES: &[u8] = include_bytes!("Calibri.ttf") as &[u8];
 fn get_font() -> Font {
     Font::from_bytes(FONT_BYTES, fontdue::FontSettings { collection_index: 0, scale: 30.0, load_substitutions: true  }).unwrap()
 }
+
+/// Black 30px text in the test font, with the default layout.
+fn text_settings(text: &str) -> TextSettings<u8> {
+    TextSettings::new(text, get_font(), 30.0, AlphaPixel::black())
+}
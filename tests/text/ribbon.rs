@@ -0,0 +1,54 @@
+use image_template::{layers::{ribbon::{Corner, RibbonLayer, RibbonStyle}, text::TextSettings}, AlphaPixel, Layer, Rect};
+
+use crate::text::text_settings;
+
+const CARD: Rect = Rect { x: 10, y: 20, width: 200, height: 100 };
+
+fn label() -> TextSettings<u8> {
+    TextSettings { size: 16.0, fill: AlphaPixel::white(), ..text_settings("NEW") }
+}
+
+fn rect_tuple(rect: Rect) -> (usize, usize, usize, usize) {
+    (rect.x, rect.y, rect.width, rect.height)
+}
+
+fn style() -> RibbonStyle<u8> {
+    RibbonStyle { size: 80, band_width: 24.0, band_offset: 40.0, ..Default::default() }
+}
+
+#[test]
+fn ribbon_corners() {
+    let top_right = RibbonLayer::try_new(label(), CARD, Corner::TopRight, style()).unwrap();
+    assert_eq!(rect_tuple(top_right.get_rect()), (130, 20, 80, 80));
+
+    let bottom_left = RibbonLayer::try_new(label(), CARD, Corner::BottomLeft, style()).unwrap();
+    assert_eq!(rect_tuple(bottom_left.get_rect()), (10, 40, 80, 80));
+
+    // The ribbon can't be bigger than the target
+    let large = RibbonStyle { size: 500, ..style() };
+    let clipped = RibbonLayer::try_new(label(), CARD, Corner::TopLeft, large).unwrap();
+    assert_eq!(rect_tuple(clipped.get_rect()), (10, 20, 100, 100));
+}
+
+#[test]
+fn ribbon_band() {
+    let ribbon = RibbonLayer::try_new(label(), CARD, Corner::TopRight, style()).unwrap();
+
+    // The very corner, and the inner corner of the square, are outside of the band
+    assert_eq!(ribbon.filtered_pixel_at(209, 20).unwrap().a, 0);
+    assert_eq!(ribbon.filtered_pixel_at(130, 99).unwrap().a, 0);
+
+    // The band crosses the diagonal, 40 pixels from the corner
+    let offset = (40.0 / 2f32.sqrt()) as usize;
+    assert_eq!(ribbon.filtered_pixel_at(210 - offset, 20 + offset).unwrap().a, 255);
+    // Away from the text, the band is the fill colour
+    assert_eq!(ribbon.filtered_pixel_at(209, 20 + 2*offset - 1).unwrap(), AlphaPixel::red());
+
+    // The text is drawn over the band
+    let rect = ribbon.get_rect();
+    let text_pixels = (rect.y..rect.bottom_y())
+        .flat_map(|y| (rect.x..rect.right_x()).map(move |x| (x, y)))
+        .filter(|(x, y)| ribbon.filtered_pixel_at(*x, *y).unwrap().g > 200)
+        .count();
+    assert!(text_pixels > 20);
+}
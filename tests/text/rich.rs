@@ -0,0 +1,203 @@
+use image_template::{
+    layers::text::{
+        layout::{LayoutAlign, LayoutDirection, LayoutIter, SpacingMode, TextLayout, TextOverflow, TextTransform},
+        rich::{RichTextLayer, StyledText, TextRun, TextSpan},
+        shadow::TextShadow,
+        TextColumns,
+        TextLayer,
+        TextOptions,
+        TextSettings
+    },
+    AlphaPixel,
+    Layer
+};
+
+use crate::text::{get_font, text_settings};
+
+fn settings() -> TextSettings<u8> {
+    TextSettings { size: 40.0, ..text_settings("") }
+}
+
+/// Get the lowest row containing a visible pixel of the layer.
+fn lowest_row(layer: &dyn Layer<u8>) -> usize {
+    let rect = layer.get_rect();
+    (rect.y..rect.bottom_y())
+        .filter(|y| (rect.x..rect.right_x()).any(|x| layer.filtered_pixel_at(x, *y).unwrap().a > 0))
+        .max()
+        .unwrap()
+}
+
+#[test]
+fn superscript_ordinal() {
+    let plain = RichTextLayer::try_new(settings(), vec![TextRun::plain("1st")], 0, 0).unwrap();
+    let ordinal = RichTextLayer::try_new(settings(), vec![TextRun::plain("1"), TextRun::superscript("st")], 0, 0).unwrap();
+
+    // The superscript is smaller, and doesn't reach the baseline
+    assert!(ordinal.get_rect().width < plain.get_rect().width);
+    let superscript_x = ordinal.get_rect().right_x() - 2;
+    let superscript_bottom = (0..ordinal.get_rect().height)
+        .filter(|y| ordinal.filtered_pixel_at(superscript_x, *y).unwrap().a > 0)
+        .max()
+        .unwrap();
+    assert!(superscript_bottom < lowest_row(&ordinal) - 5);
+}
+
+#[test]
+fn subscript_and_fill() {
+    let plain = RichTextLayer::try_new(settings(), vec![TextRun::plain("H2O")], 0, 0).unwrap();
+    let mut subscript = TextRun::subscript("2");
+    subscript.fill = Some(AlphaPixel::red());
+    let formula = RichTextLayer::try_new(settings(), vec![TextRun::plain("H"), subscript, TextRun::plain("O")], 0, 0).unwrap();
+
+    assert!(lowest_row(&formula) > lowest_row(&plain));
+
+    let rect = formula.get_rect();
+    let has_red = (rect.y..rect.bottom_y())
+        .flat_map(|y| (rect.x..rect.right_x()).map(move |x| (x, y)))
+        .any(|(x, y)| formula.filtered_pixel_at(x, y).unwrap() == AlphaPixel::red());
+    assert!(has_red);
+}
+
+#[test]
+fn styled_spans() {
+    let plain = RichTextLayer::try_new(settings(), vec![TextRun::plain("Buy now\nToday")], 0, 0).unwrap();
+
+    // A span with its own font, which has the same glyphs, is laid out like the rest of the text
+    let same_font = StyledText::new().plain("Buy ").span(TextSpan::new("now").with_font(get_font())).plain("\nToday");
+    let same_font = TextLayer::from_styled(settings(), same_font, 0, 0).unwrap();
+    assert_eq!(same_font.get_rect().width, plain.get_rect().width);
+    assert_eq!(same_font.get_rect().height, plain.get_rect().height);
+
+    // A larger span makes its line taller, and moves the lines below it down
+    let large = StyledText::new()
+        .plain("Buy ")
+        .span(TextSpan::new("now").with_size(80.0).with_fill(AlphaPixel::red()))
+        .plain("\nToday");
+    assert_eq!(large.text(), "Buy now\nToday");
+    let large = TextLayer::from_styled(settings(), large, 0, 0).unwrap();
+    assert!(large.get_rect().width > plain.get_rect().width);
+    assert!(lowest_row(&large) >= lowest_row(&plain) + 40);
+
+    let rect = large.get_rect();
+    let red = (rect.y..rect.bottom_y())
+        .flat_map(|y| (rect.x..rect.right_x()).map(move |x| (x, y)))
+        .filter(|&(x, y)| large.filtered_pixel_at(x, y).unwrap() == AlphaPixel::red())
+        .count();
+    assert!(red > 100);
+}
+
+/// Get the left and right edges of the visible pixels in rows `top..bottom` of a layer.
+fn row_edges(layer: &dyn Layer<u8>, top: usize, bottom: usize) -> (usize, usize) {
+    let rect = layer.get_rect();
+    let inked: Vec<usize> = (top..bottom)
+        .flat_map(|y| (rect.x..rect.right_x()).map(move |x| (x, y)))
+        .filter(|&(x, y)| layer.filtered_pixel_at(x, y).unwrap().a > 0)
+        .map(|(x, _y)| x)
+        .collect();
+    (*inked.iter().min().unwrap(), *inked.iter().max().unwrap())
+}
+
+#[test]
+fn layout_settings() {
+    // The second, shorter line is aligned with the first
+    let aligned = |align| {
+        let settings = TextSettings { layout: TextLayout { align, ..Default::default() }, ..settings() };
+        let layer = RichTextLayer::try_new(settings, vec![TextRun::plain("Buy it now\nSoon")], 0, 0).unwrap();
+        let middle = layer.get_rect().height / 2;
+        (row_edges(&layer, 0, middle), row_edges(&layer, middle, layer.get_rect().height))
+    };
+    let ((first_left, first_right), (second_left, second_right)) = aligned(LayoutAlign::Start);
+    assert!(first_left.abs_diff(second_left) <= 2 && second_right + 10 < first_right);
+    let ((_, first_right), (second_left, second_right)) = aligned(LayoutAlign::End);
+    assert!(first_right.abs_diff(second_right) <= 2 && second_left > 10);
+    let ((first_left, first_right), (second_left, second_right)) = aligned(LayoutAlign::Center);
+    assert!((first_left + first_right).abs_diff(second_left + second_right) <= 3 && second_left > 5);
+
+    // Title case continues across runs
+    let title = TextSettings { layout: TextLayout { transform: TextTransform::TitleCase, ..Default::default() }, ..settings() };
+    let split = RichTextLayer::try_new(title.clone(), vec![TextRun::plain("buy n"), TextRun::plain("OW")], 0, 0).unwrap();
+    let whole = RichTextLayer::try_new(settings(), vec![TextRun::plain("Buy Now")], 0, 0).unwrap();
+    assert_eq!(split.get_rect().width, whole.get_rect().width);
+
+    // The shadow extends the layer, without moving the text
+    let shadow = TextOptions { shadow: Some(TextShadow::new(AlphaPixel::black(), (4, 4), 0)), ..Default::default() };
+    let shadowed = RichTextLayer::try_with_options(settings(), shadow, vec![TextRun::plain("Buy Now")], 10, 10).unwrap();
+    assert_eq!((shadowed.get_rect().x, shadowed.get_rect().y), (10, 10));
+    assert_eq!(shadowed.get_rect().width, whole.get_rect().width + 4);
+
+    // Runs are laid out like the text of a `TextLayer`, so they can be top to bottom and truncated
+    let vertical_layout = TextLayout { direction: LayoutDirection::TopToBottom, line_spacing: SpacingMode::Constant(40.0), ..Default::default() };
+    let vertical = TextSettings { layout: vertical_layout, ..settings() };
+    let vertical = RichTextLayer::try_new(vertical, vec![TextRun::plain("Buy")], 0, 0).unwrap();
+    assert!(vertical.get_rect().height > vertical.get_rect().width);
+    let truncated = TextSettings { layout: TextLayout { max_lines: Some(1), ..Default::default() }, ..settings() };
+    let truncated = RichTextLayer::try_new(truncated, vec![TextRun::plain("Buy now\nToday")], 0, 0).unwrap();
+    let one_line = RichTextLayer::try_new(settings(), vec![TextRun::plain("Buy now")], 0, 0).unwrap();
+    assert_eq!((truncated.get_rect().width, truncated.get_rect().height), (one_line.get_rect().width, one_line.get_rect().height));
+}
+
+#[test]
+fn baseline_grid() {
+    let last_baseline = |size: f32| {
+        let layout = TextLayout { baseline_grid: Some(24.0), ..Default::default() };
+        let settings = TextSettings { size, layout, ..settings() };
+        let layer = RichTextLayer::try_new(settings, vec![TextRun::plain("H\nH"), TextRun::superscript("2")], 0, 0).unwrap();
+        lowest_row(&layer)
+    };
+
+    // The baseline of each line is snapped onto the grid, whatever the size of the text
+    for size in [16.0, 18.0, 30.0] {
+        assert_eq!(last_baseline(size) % 24, 0);
+    }
+    assert_eq!(last_baseline(16.0), last_baseline(18.0));
+}
+
+#[test]
+fn kerning_across_spans() {
+    // Spans in a clone of the font are kerned with the text around them
+    let settings = TextSettings { size: 80.0, ..settings() };
+    let plain = LayoutIter::new(&TextSettings { text: String::from("AVA"), ..settings.clone() })
+        .map(|glyph| glyph.unwrap())
+        .collect::<Vec<_>>();
+    let styled = StyledText::new().plain("A").span(TextSpan::new("V").with_font(get_font())).plain("A");
+    let styled = LayoutIter::styled(&settings, &styled).map(|glyph| glyph.unwrap()).collect::<Vec<_>>();
+    assert_eq!(styled, plain);
+
+    // Spans of a different size aren't kerned with their neighbours
+    let larger = StyledText::new().plain("A").span(TextSpan::new("V").with_size(80.5)).plain("A");
+    let larger = LayoutIter::styled(&settings, &larger).map(|glyph| glyph.unwrap()).collect::<Vec<_>>();
+    assert!(larger[1].1 > plain[1].1);
+}
+
+#[test]
+fn styled_text_layer() {
+    let styled = || StyledText::new()
+        .plain("Buy ")
+        .span(TextSpan::new("now").with_size(60.0).with_fill(AlphaPixel::red()))
+        .plain("\nToday\nOnly");
+
+    // Styled text follows the layout, and the columns of the layer
+    let truncated = TextSettings { layout: TextLayout { max_lines: Some(1), overflow: TextOverflow::Ellipsis, ..Default::default() }, ..settings() };
+    let truncated = TextLayer::from_styled(truncated, styled(), 0, 0).unwrap();
+    let full = TextLayer::from_styled(settings(), styled(), 0, 0).unwrap();
+    assert!(truncated.get_rect().height < full.get_rect().height / 2);
+    assert!(truncated.get_rect().width > full.get_rect().width);
+
+    let mut columns = TextLayer::from_styled(settings(), styled(), 0, 0).unwrap();
+    columns.set_columns(Some(TextColumns::balanced(3, 10))).unwrap();
+    assert!(columns.get_rect().height < full.get_rect().height / 2);
+    assert!(columns.get_rect().width > full.get_rect().width);
+
+    // Changing the fill keeps the fill of the span
+    columns.set_fill(AlphaPixel::blue()).unwrap();
+    let rect = columns.get_rect();
+    let pixels: Vec<AlphaPixel<u8>> = (rect.y..rect.bottom_y())
+        .flat_map(|y| (rect.x..rect.right_x()).map(move |x| (x, y)))
+        .map(|(x, y)| columns.filtered_pixel_at(x, y).unwrap())
+        .collect();
+    assert!(pixels.contains(&AlphaPixel::red()) && pixels.contains(&AlphaPixel::blue()));
+
+    // Plain text replaces the styled text
+    columns.set_text("Buy now").unwrap();
+    assert!(columns.get_styled_text().is_none());
+}
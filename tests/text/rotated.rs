@@ -0,0 +1,29 @@
+use image_template::{layers::text::{TextLayer, TextSettings}, AlphaPixel, Layer};
+
+use crate::text::text_settings;
+
+#[test]
+fn rotated_caption() {
+    let settings = TextSettings { size: 40.0, fill: AlphaPixel::<u8>::red(), ..text_settings("Caption") };
+    let caption = TextLayer::try_new(settings, 100, 100).unwrap();
+    let rect = caption.get_rect();
+
+    // A quarter turn stands the caption on its end, around the same center
+    let standing = caption.rotated(90.0);
+    let rotated_rect = standing.get_rect();
+    assert!(rotated_rect.width.abs_diff(rect.height) <= 1);
+    assert!(rotated_rect.height.abs_diff(rect.width) <= 1);
+    let center = |rect: image_template::Rect| (rect.x + rect.width / 2, rect.y + rect.height / 2);
+    let (center, rotated_center) = (center(rect), center(rotated_rect));
+    assert!(center.0.abs_diff(rotated_center.0) <= 1 && center.1.abs_diff(rotated_center.1) <= 1);
+
+    let drawn = (rotated_rect.y..rotated_rect.bottom_y())
+        .flat_map(|y| (rotated_rect.x..rotated_rect.right_x()).map(move |x| (x, y)))
+        .filter(|&(x, y)| standing.filtered_pixel_at(x, y).is_some_and(|pixel| pixel.a > 0))
+        .count();
+    assert!(drawn > 0);
+
+    // A slight tilt grows the rect to fit the corners
+    let tilted = standing.layer.rotated(15.0).get_rect();
+    assert!(tilted.width > rect.width && tilted.height > rect.height);
+}
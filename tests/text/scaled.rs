@@ -0,0 +1,37 @@
+use image_template::{layers::text::{layout::VerticalAlign, stroke::TextStroke, TextLayer, TextSettings}, AlphaPixel, Canvas, Image, Rect};
+
+use crate::text::text_settings;
+
+/// Get the bounding box of the pixels which aren't the background.
+fn ink(image: &Image<u8>) -> Rect {
+    let pixel = |x: usize, y: usize| image.pixel_at(x, y).unwrap();
+    let inked: Vec<(usize, usize)> = (0..image.get_height())
+        .flat_map(|y| (0..image.get_width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| pixel(x, y) != AlphaPixel::white())
+        .collect();
+    let left = inked.iter().map(|(x, _)| *x).min().unwrap();
+    let top = inked.iter().map(|(_, y)| *y).min().unwrap();
+    let right = inked.iter().map(|(x, _)| *x).max().unwrap();
+    let bottom = inked.iter().map(|(_, y)| *y).max().unwrap();
+    Rect { x: left, y: top, width: right - left + 1, height: bottom - top + 1 }
+}
+
+#[test]
+fn text_at_2x() {
+    let settings = TextSettings { size: 24.0, ..text_settings("Hello") };
+    let mut text = TextLayer::try_new(settings, 0, 0).unwrap();
+    text.set_strokes(vec![TextStroke::solid(AlphaPixel::red(), 2.0)]).unwrap();
+    text.set_target(Some((Rect { x: 20, y: 10, width: 100, height: 40 }, VerticalAlign::Middle))).unwrap();
+
+    let mut canvas: Canvas<u8> = Canvas::from_dimensions(140, 60);
+    canvas.background = AlphaPixel::white();
+    canvas.add_layer(text);
+
+    let normal = ink(&canvas.flatten());
+    let retina = ink(&canvas.flatten_scaled(2.0));
+
+    // The text is redrawn at twice the size, in the same place
+    for (scaled, original) in [(retina.x, normal.x), (retina.y, normal.y), (retina.width, normal.width), (retina.height, normal.height)] {
+        assert!(scaled.abs_diff(original * 2) <= 2, "{scaled} isn't twice {original}");
+    }
+}
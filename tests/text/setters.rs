@@ -0,0 +1,44 @@
+use image_template::{layers::text::{shadow::TextShadow, stroke::TextStroke, TextLayer, TextOptions, TextSettings}, AlphaPixel, Layer, rgba};
+
+use crate::text::text_settings;
+
+fn layer(text: &str, size: f32, fill: AlphaPixel<u8>) -> TextLayer<u8> {
+    let options = TextOptions { shadow: Some(TextShadow::new(AlphaPixel::black(), (3, 3), 1)), ..Default::default() };
+    let mut layer = TextLayer::try_with_options(TextSettings { size, fill, ..text_settings(text) }, options, 10, 10).unwrap();
+    layer.set_strokes(vec![TextStroke::solid(AlphaPixel::white(), 2.0)]).unwrap();
+    layer
+}
+
+fn pixels(layer: &TextLayer<u8>) -> Vec<AlphaPixel<u8>> {
+    let rect = layer.get_rect();
+    (rect.y..rect.bottom_y())
+        .flat_map(|y| (rect.x..rect.right_x()).map(move |x| (x, y)))
+        .map(|(x, y)| layer.filtered_pixel_at(x, y).unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn set_fill_retints() {
+    let mut text = layer("Sale", 40.0, AlphaPixel::red());
+    text.set_fill(AlphaPixel::blue()).unwrap();
+    assert_eq!(text.get_settings().fill, AlphaPixel::blue());
+
+    let fresh = layer("Sale", 40.0, AlphaPixel::blue());
+    let rect = (text.get_rect(), fresh.get_rect());
+    assert_eq!((rect.0.x, rect.0.y, rect.0.width, rect.0.height), (rect.1.x, rect.1.y, rect.1.width, rect.1.height));
+    assert_eq!(pixels(&text), pixels(&fresh));
+
+    // The alpha of the glyphs is their coverage, as with a fresh layer
+    text.set_fill(rgba!(0, 255, 0, 128)).unwrap();
+    assert_eq!(pixels(&text), pixels(&layer("Sale", 40.0, rgba!(0, 255, 0, 128))));
+}
+
+#[test]
+fn set_text_and_size() {
+    let mut text = layer("Sale", 40.0, AlphaPixel::red());
+    text.set_text("Sold out").unwrap();
+    assert_eq!(pixels(&text), pixels(&layer("Sold out", 40.0, AlphaPixel::red())));
+
+    text.set_size(20.0).unwrap();
+    assert_eq!(pixels(&text), pixels(&layer("Sold out", 20.0, AlphaPixel::red())));
+}
@@ -0,0 +1,41 @@
+use image_template::{layers::text::{shadow::TextShadow, stroke::TextStroke, TextLayer, TextOptions, TextSettings}, AlphaPixel, Layer};
+
+use crate::text::text_settings;
+
+fn layer(shadow: Option<TextShadow<u8>>, x: usize, y: usize) -> TextLayer<u8> {
+    let settings = TextSettings { size: 40.0, fill: AlphaPixel::red(), ..text_settings("WOW") };
+    TextLayer::try_with_options(settings, TextOptions { shadow, ..Default::default() }, x, y).unwrap()
+}
+
+#[test]
+fn shadow_rect() {
+    let plain = layer(None, 20, 20).get_rect();
+    let text = layer(Some(TextShadow::new(AlphaPixel::black(), (4, 6), 2)), 20, 20);
+
+    // The shadow only extends the rect on the sides it is cast towards, and the text doesn't move
+    let shadowed = text.get_rect();
+    assert_eq!((shadowed.x, shadowed.y), (20, 20));
+    assert_eq!((shadowed.width, shadowed.height), (plain.width + 6, plain.height + 8));
+
+    let pixels: Vec<AlphaPixel<u8>> = (shadowed.y..shadowed.bottom_y())
+        .flat_map(|y| (shadowed.x..shadowed.right_x()).map(move |x| (x, y)))
+        .filter_map(|(x, y)| text.filtered_pixel_at(x, y))
+        .collect();
+    assert!(pixels.iter().any(|pixel| pixel.r == 0 && pixel.a > 0));
+    assert!(pixels.contains(&AlphaPixel::red()));
+}
+
+#[test]
+fn shadow_under_strokes() {
+    let mut text = layer(Some(TextShadow::new(AlphaPixel::black(), (-3, 0), 0)), 10, 10);
+    text.set_strokes(vec![TextStroke::solid(AlphaPixel::white(), 2.0)]).unwrap();
+
+    // The shadow is cast by the strokes, so it extends to the left of them without covering them
+    let rect = text.get_rect();
+    let plain = layer(None, 10, 10).get_rect();
+    assert_eq!((rect.x, rect.y), (5, 8));
+    assert_eq!((rect.width, rect.height), (plain.width + 7, plain.height + 4));
+    let left_column: Vec<AlphaPixel<u8>> = (rect.y..rect.bottom_y()).filter_map(|y| text.filtered_pixel_at(rect.x + 2, y)).collect();
+    assert!(left_column.contains(&AlphaPixel::black()));
+    assert!(!left_column.contains(&AlphaPixel::white()));
+}
@@ -0,0 +1,39 @@
+use image_template::{layers::text::{stroke::TextStroke, TextLayer, TextSettings}, AlphaPixel, Layer};
+
+use crate::text::text_settings;
+
+fn settings() -> TextSettings<u8> {
+    TextSettings { size: 40.0, fill: AlphaPixel::red(), ..text_settings("WOW") }
+}
+
+#[test]
+fn stroke_rect() {
+    let mut text = TextLayer::try_new(settings(), 20, 20).unwrap();
+    let unstroked = text.get_rect();
+
+    text.set_strokes(vec![TextStroke::solid(AlphaPixel::white(), 3.0), TextStroke::solid(AlphaPixel::black(), 2.0)]).unwrap();
+    let stroked = text.get_rect();
+    assert_eq!((stroked.x, stroked.y), (15, 15));
+    assert_eq!((stroked.width, stroked.height), (unstroked.width + 10, unstroked.height + 10));
+
+    // Both outlines are visible around the text
+    let pixels: Vec<AlphaPixel<u8>> = (stroked.y..stroked.bottom_y())
+        .flat_map(|y| (stroked.x..stroked.right_x()).map(move |x| (x, y)))
+        .filter_map(|(x, y)| text.filtered_pixel_at(x, y))
+        .collect();
+    assert!(pixels.contains(&AlphaPixel::white()));
+    assert!(pixels.contains(&AlphaPixel::black()));
+    assert!(pixels.contains(&AlphaPixel::red()));
+}
+
+#[test]
+fn stroke_clipped_at_canvas_edge() {
+    let mut text = TextLayer::try_new(settings(), 2, 0).unwrap();
+    let unstroked = text.get_rect();
+    text.set_strokes(vec![TextStroke::gradient(AlphaPixel::white(), AlphaPixel::black(), 90.0, 5.0)]).unwrap();
+
+    let stroked = text.get_rect();
+    assert_eq!((stroked.x, stroked.y), (0, 0));
+    assert_eq!((stroked.width, stroked.height), (unstroked.width + 7, unstroked.height + 5));
+    assert!(text.filtered_pixel_at(stroked.right_x() - 1, stroked.bottom_y() - 1).is_some());
+}
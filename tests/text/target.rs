@@ -0,0 +1,63 @@
+use image_template::{layers::text::{layout::{TextLayout, LayoutAlign, VerticalAlign}, stroke::TextStroke, TextLayer, TextSettings}, AlphaPixel, Layer, Rect};
+
+use crate::text::text_settings;
+
+fn label(text: &str, size: f32, align: LayoutAlign) -> TextSettings<u8> {
+    TextSettings { size, layout: TextLayout { align, ..Default::default() }, ..text_settings(text) }
+}
+
+/// Get the lowest row of a layer with a pixel of a glyph without a descender.
+fn bottom_of_ink(layer: &TextLayer<u8>) -> usize {
+    let rect = layer.get_rect();
+    (rect.y..rect.bottom_y()).rev()
+        .find(|&y| (rect.x..rect.right_x()).any(|x| layer.filtered_pixel_at(x, y).unwrap().a > 128))
+        .unwrap()
+}
+
+#[test]
+fn align_in_box() {
+    let target = Rect { x: 100, y: 50, width: 300, height: 200 };
+    let mut text = TextLayer::try_new(label("Play", 30.0, LayoutAlign::Center), 0, 0).unwrap();
+    let size = text.get_rect();
+
+    text.set_target(Some((target, VerticalAlign::Top))).unwrap();
+    let rect = text.get_rect();
+    assert_eq!((rect.x, rect.y), (100 + (300 - size.width) / 2, 50));
+
+    text.set_target(Some((target, VerticalAlign::Middle))).unwrap();
+    assert_eq!(text.get_rect().y, 50 + (200 - size.height) / 2);
+
+    text.set_target(Some((target, VerticalAlign::Bottom))).unwrap();
+    assert_eq!(text.get_rect().bottom_y(), target.bottom_y());
+
+    // Changing the text keeps it in place, and strokes are drawn around it
+    let mut settings = text.get_settings().clone();
+    settings.text = String::from("Play again");
+    text.set_settings(settings).unwrap();
+    text.set_strokes(vec![TextStroke::solid(AlphaPixel::white(), 4.0)]).unwrap();
+    let rect = text.get_rect();
+    assert_eq!(rect.bottom_y(), target.bottom_y() + 4);
+    assert!(rect.x < 100 + (300 - size.width) / 2);
+
+    text.set_target(None).unwrap();
+    assert!(text.get_target().is_none());
+    assert_eq!(text.get_rect().bottom_y(), target.bottom_y() + 4);
+}
+
+#[test]
+fn baseline() {
+    let target = Rect { x: 0, y: 0, width: 400, height: 100 };
+    let mut small = TextLayer::try_new(label("Ham", 20.0, LayoutAlign::Start), 0, 0).unwrap();
+    let mut large = TextLayer::try_new(label("Ham", 50.0, LayoutAlign::Start), 0, 0).unwrap();
+    small.set_target(Some((target, VerticalAlign::Baseline))).unwrap();
+    large.set_target(Some((target, VerticalAlign::Baseline))).unwrap();
+
+    // Both sit on the bottom of the box
+    assert!(bottom_of_ink(&small).abs_diff(99) <= 1);
+    assert!(bottom_of_ink(&large).abs_diff(99) <= 1);
+
+    // Descenders hang below it
+    let mut descender = TextLayer::try_new(label("Hog", 50.0, LayoutAlign::Start), 0, 0).unwrap();
+    descender.set_target(Some((target, VerticalAlign::Baseline))).unwrap();
+    assert!(descender.get_rect().bottom_y() > 105);
+}
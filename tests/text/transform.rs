@@ -0,0 +1,42 @@
+use image_template::{layers::text::{layout::{LayoutAlign, LayoutIter, TextLayout, TextTransform}, TextSettings}};
+
+use crate::text::text_settings;
+
+fn settings(transform: TextTransform) -> TextSettings<u8> {
+    TextSettings { layout: TextLayout { transform, ..Default::default() }, ..text_settings("hello World") }
+}
+
+#[test]
+fn transform_layout() {
+    let glyphs = |transform| LayoutIter::new(&settings(transform))
+        .map(|position| position.unwrap().0)
+        .collect::<String>();
+
+    assert_eq!(glyphs(TextTransform::None), "hello World");
+    assert_eq!(glyphs(TextTransform::Uppercase), "HELLO WORLD");
+    assert_eq!(glyphs(TextTransform::Lowercase), "hello world");
+    assert_eq!(glyphs(TextTransform::TitleCase), "Hello World");
+    assert_eq!(glyphs(TextTransform::SmallCaps), "HELLO WORLD");
+}
+
+#[test]
+fn small_caps_raster() {
+    let uppercase = settings(TextTransform::Uppercase).raster_from_settings().unwrap();
+    let small_caps = settings(TextTransform::SmallCaps).raster_from_settings().unwrap();
+
+    assert!(small_caps.get_width() < uppercase.get_width());
+    assert!(small_caps.get_width() as f32 > uppercase.get_width() as f32 * 0.7);
+    // Both versions are drawn on the same baseline
+    assert_eq!(small_caps.get_height(), uppercase.get_height());
+
+    // Small caps are synthesized for every alignment
+    for align in [LayoutAlign::Center, LayoutAlign::End] {
+        let aligned = |transform| TextSettings { layout: TextLayout { align, ..settings(transform).layout }, ..settings(transform) };
+        let uppercase = aligned(TextTransform::Uppercase).raster_from_settings().unwrap();
+        let small_caps = aligned(TextTransform::SmallCaps).raster_from_settings().unwrap();
+        assert!(small_caps.get_width() < uppercase.get_width());
+    }
+
+    let measured = settings(TextTransform::Uppercase).line_width("hello World");
+    assert!(measured > settings(TextTransform::None).line_width("hello World"));
+}
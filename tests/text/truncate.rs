@@ -0,0 +1,66 @@
+use image_template::{layers::text::{layout::{LayoutDirection, TextLayout, TextOverflow}, TextLayer, TextSettings}, Layer};
+
+use crate::text::text_settings;
+
+fn settings(text: &str, max_width: Option<f32>, max_lines: Option<usize>, overflow: TextOverflow) -> TextSettings<u8> {
+    TextSettings { layout: TextLayout { max_width, max_lines, overflow, ..Default::default() }, ..text_settings(text) }
+}
+
+#[test]
+fn ellipsis() {
+    let name = settings("Someone with an extraordinarily long display name", Some(200.0), None, TextOverflow::Ellipsis);
+    let truncated = name.truncated_text();
+    assert!(truncated.ends_with('…'));
+    assert!(!truncated.ends_with(" …"));
+    assert!(name.line_width(&truncated) > 150.0);
+
+    // The layer is no wider than the maximum
+    let layer = TextLayer::try_new(name.clone(), 0, 0).unwrap();
+    assert!(layer.get_rect().width <= 200);
+
+    // Text which fits is unchanged
+    let short = settings("Sam", Some(200.0), None, TextOverflow::Ellipsis);
+    assert_eq!(short.truncated_text(), "Sam");
+
+    // Nothing fits in a tiny width, not even the ellipsis
+    let tiny = settings("Sam", Some(2.0), None, TextOverflow::Ellipsis);
+    assert_eq!(tiny.truncated_text(), "");
+}
+
+#[test]
+fn clip() {
+    let clipped = settings("Someone with an extraordinarily long display name", Some(200.0), None, TextOverflow::Clip);
+    let truncated = clipped.truncated_text();
+    assert!(clipped.text.starts_with(truncated.as_ref()));
+    let width = |text: &str| TextLayer::try_new(settings(text, None, None, TextOverflow::Clip), 0, 0).unwrap().get_rect().width;
+    assert!(width(&truncated) <= 200);
+    let next = clipped.text[truncated.len()..].chars().next().unwrap();
+    assert!(width(&format!("{truncated}{next}")) > 200);
+}
+
+#[test]
+fn max_lines() {
+    let text = "First line\nSecond line\nThird line";
+    assert_eq!(settings(text, None, Some(2), TextOverflow::Clip).truncated_text(), "First line\nSecond line");
+    assert_eq!(settings(text, None, Some(2), TextOverflow::Ellipsis).truncated_text(), "First line\nSecond line…");
+    assert_eq!(settings(text, None, Some(3), TextOverflow::Ellipsis).truncated_text(), text);
+
+    let two_lines = TextLayer::try_new(settings(text, None, Some(2), TextOverflow::Ellipsis), 0, 0).unwrap();
+    let three_lines = TextLayer::try_new(settings(text, None, None, TextOverflow::Ellipsis), 0, 0).unwrap();
+    assert!(two_lines.get_rect().height < three_lines.get_rect().height);
+
+    // Each line is cut short by width, and the last line gets an ellipsis even if it fits
+    let both = settings("A line which is much too long\nShort\nRemoved", Some(150.0), Some(2), TextOverflow::Ellipsis);
+    let truncated = both.truncated_text();
+    let lines: Vec<&str> = truncated.split('\n').collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with('…') && both.line_width(lines[0]) < 150.0);
+    assert_eq!(lines[1], "Short…");
+}
+
+#[test]
+fn top_to_bottom() {
+    let mut vertical = settings("Much too long for the width", Some(50.0), None, TextOverflow::Ellipsis);
+    vertical.layout.direction = LayoutDirection::TopToBottom;
+    assert_eq!(vertical.truncated_text(), "Much too long for the width");
+}
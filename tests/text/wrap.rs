@@ -0,0 +1,42 @@
+use image_template::{layers::{speech_bubble::{SpeechBubbleLayer, SpeechBubbleStyle}, text::TextLayer}, AlphaPixel, Layer};
+
+use crate::text::text_settings;
+
+#[test]
+fn wrap_text() {
+    let mut text_settings = text_settings("The quick brown fox jumps over a lazy dog.");
+    let full_width = text_settings.line_width(&text_settings.text);
+
+    text_settings.wrap_text(full_width / 2.0);
+    let lines: Vec<&str> = text_settings.text.split('\n').collect();
+
+    assert!(lines.len() >= 2);
+    assert_eq!(lines.join(" "), "The quick brown fox jumps over a lazy dog.");
+    for line in lines {
+        assert!(text_settings.line_width(line) <= full_width / 2.0);
+    }
+}
+
+#[test]
+fn wrap_long_word() {
+    let mut text_settings = text_settings("a incomprehensibilities b");
+    text_settings.wrap_text(20.0);
+    assert_eq!(text_settings.text, "a\nincomprehensibilities\nb");
+}
+
+#[test]
+fn speech_bubble_fits_text() {
+    let style = SpeechBubbleStyle { padding: 10, max_text_width: Some(150.0), ..Default::default() };
+    let bubble = SpeechBubbleLayer::try_new(text_settings("Hello there, this is a speech bubble"), 50, 40, style).unwrap();
+    let text = TextLayer::try_new(bubble.get_text_settings().clone(), 60, 50).unwrap();
+
+    let body = bubble.get_body_rect();
+    assert_eq!(body.width, text.get_rect().width + 20);
+    assert_eq!(body.height, text.get_rect().height + 20);
+    assert!(bubble.get_rect().bottom_y() > body.bottom_y());
+
+    // Inside the bubble, away from the text
+    assert_eq!(bubble.filtered_pixel_at(body.x + 5, body.y + body.height / 2).unwrap(), AlphaPixel::white());
+    // Outside the rounded corner
+    assert_eq!(bubble.filtered_pixel_at(body.x, body.y).unwrap().a, 0);
+}